@@ -1,14 +1,23 @@
 //! Base agent implementation
 
-use crate::agents::{Agent, AgentConfig, AgentMessage, MessageResponse, ToolCallInfo};
-use luts_llm::{AiService, InternalChatMessage, LLMService};
+use crate::agents::{
+    Agent, AgentConfig, AgentMessage, ConversationPersistencePolicy, MessageResponse, SharedMemory,
+    SharedMemorySlot, ToolCallInfo,
+};
+use crate::agents::persistence::TurnRecorder;
+use crate::agents::streaming::stream_agent_turns;
+use luts_llm::{
+    AiService, GenerationParams, InternalChatMessage, LLMService, ResponseStreamManager,
+    StreamableResponse,
+};
 use luts_memory::{MemoryManager, SurrealMemoryStore, SurrealConfig};
-use luts_llm::tools::AiTool;
+use luts_llm::tools::{AiTool, ToolErrorKind, ToolResult};
 use crate::tools::modify_core_block::ModifyCoreBlockTool;
 use anyhow::{Error, anyhow};
 use async_trait::async_trait;
+use luts_core::context::core_blocks::CoreBlockManager;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tokio::sync::RwLock;
 use tracing::{debug, error, info};
 
@@ -18,10 +27,13 @@ pub struct BaseAgent {
     config: AgentConfig,
     
     /// LLM service for this agent
-    llm_service: LLMService,
+    llm_service: Arc<dyn AiService>,
     
     /// Memory manager for this agent's personal memory
-    memory_manager: MemoryManager,
+    memory_manager: Arc<MemoryManager>,
+    
+    /// Stores finished turns in memory, as the config's policy asks
+    turn_recorder: Arc<TurnRecorder>,
     
     /// Available tools for this agent
     tools: HashMap<String, Box<dyn AiTool>>,
@@ -31,6 +43,21 @@ pub struct BaseAgent {
     
     /// Conversation history for this agent
     conversation_history: Vec<InternalChatMessage>,
+    
+    /// Streams replies for `process_message_streaming`
+    stream_manager: Arc<ResponseStreamManager>,
+    
+    /// Messages from streamed replies, not yet folded into the history
+    streamed_history: Arc<Mutex<Vec<InternalChatMessage>>>,
+    
+    /// Most user turns kept in the history, or no limit
+    max_history_turns: Option<usize>,
+    
+    /// Registry shared memory handle, read by the memory tools
+    shared_memory: SharedMemorySlot,
+    
+    /// System prompt and pinned context, edited by `modify_core_block`
+    core_blocks: Arc<RwLock<CoreBlockManager>>,
 }
 
 /// Trait for sending messages (implemented by registry)
@@ -46,6 +73,10 @@ impl BaseAgent {
         config: AgentConfig,
         tools: HashMap<String, Box<dyn AiTool>>,
     ) -> Result<Self, Error> {
+        let shared_memory = SharedMemorySlot::default();
+        let core_blocks = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(config.core_blocks())
+        })?;
         // Clone tools for LLM service - we need to implement a proper clone method
         // For now, let's pass the tools directly to LLM service without cloning
         let tool_vec: Vec<Box<dyn AiTool>> = tools.iter()
@@ -53,9 +84,9 @@ impl BaseAgent {
                 // Create a new instance of each tool type based on its name
                 // This is a temporary workaround until we implement proper tool cloning
                 match name.as_str() {
-                    "calculator" | "calc" => Box::new(luts_tools::calc::MathTool) as Box<dyn AiTool>,
-                    "search" => Box::new(luts_tools::search::DDGSearchTool) as Box<dyn AiTool>,
-                    "website" => Box::new(luts_tools::website::WebsiteTool) as Box<dyn AiTool>,
+                    "calculator" | "calc" => Box::new(luts_tools::calc::MathTool::default()) as Box<dyn AiTool>,
+                    "search" => Box::new(luts_tools::search::DDGSearchTool::default()) as Box<dyn AiTool>,
+                    "website" => Box::new(luts_tools::website::WebsiteTool::default()) as Box<dyn AiTool>,
                     "retrieve_context" => {
                         let agent_data_dir = format!("{}/agents/{}", config.data_dir, config.agent_id);
                         std::fs::create_dir_all(&agent_data_dir).unwrap_or_default();
@@ -63,6 +94,7 @@ impl BaseAgent {
                             path: std::path::PathBuf::from(agent_data_dir).join("memory.db"),
                             namespace: "luts".to_string(),
                             database: "memory".to_string(),
+                            encryption_key: None,
                         };
                         let memory_store = tokio::task::block_in_place(|| {
                             tokio::runtime::Handle::current().block_on(async {
@@ -70,7 +102,7 @@ impl BaseAgent {
                             })
                         });
                         let memory_manager = std::sync::Arc::new(luts_memory::MemoryManager::new(memory_store));
-                        Box::new(crate::tools::retrieve_context::RetrieveContextTool { memory_manager }) as Box<dyn AiTool>
+                        Box::new(crate::tools::retrieve_context::RetrieveContextTool { memory_manager, shared_memory: shared_memory.clone() }) as Box<dyn AiTool>
                     },
                     "block" => {
                         let agent_data_dir = format!("{}/agents/{}", config.data_dir, config.agent_id);
@@ -80,6 +112,7 @@ impl BaseAgent {
                                 path: std::path::PathBuf::from(&agent_data_dir).join("memory.db"),
                                 namespace: "luts".to_string(),
                                 database: "memory".to_string(),
+                                encryption_key: None,
                             };
                             tokio::task::block_in_place(|| {
                                 tokio::runtime::Handle::current().block_on(async {
@@ -88,7 +121,7 @@ impl BaseAgent {
                             })
                         };
                         let memory_manager = std::sync::Arc::new(luts_memory::MemoryManager::new(memory_store));
-                        Box::new(crate::tools::block::BlockTool { memory_manager }) as Box<dyn AiTool>
+                        Box::new(crate::tools::block::BlockTool { memory_manager, shared_memory: shared_memory.clone() }) as Box<dyn AiTool>
                     },
                     "update_block" => {
                         let agent_data_dir = format!("{}/agents/{}", config.data_dir, config.agent_id);
@@ -98,6 +131,7 @@ impl BaseAgent {
                                 path: std::path::PathBuf::from(&agent_data_dir).join("memory.db"),
                                 namespace: "luts".to_string(),
                                 database: "memory".to_string(),
+                                encryption_key: None,
                             };
                             tokio::task::block_in_place(|| {
                                 tokio::runtime::Handle::current().block_on(async {
@@ -116,6 +150,7 @@ impl BaseAgent {
                                 path: std::path::PathBuf::from(&agent_data_dir).join("memory.db"),
                                 namespace: "luts".to_string(),
                                 database: "memory".to_string(),
+                                encryption_key: None,
                             };
                             tokio::task::block_in_place(|| {
                                 tokio::runtime::Handle::current().block_on(async {
@@ -127,7 +162,7 @@ impl BaseAgent {
                         Box::new(crate::tools::delete_block::DeleteBlockTool { memory_manager }) as Box<dyn AiTool>
                     },
                     "modify_core_block" => {
-                        Box::new(ModifyCoreBlockTool::new(config.agent_id.clone(), None)) as Box<dyn AiTool>
+                        Box::new(ModifyCoreBlockTool::from_manager(core_blocks.clone())) as Box<dyn AiTool>
                     },
                     _ => {
                         tracing::warn!("Unknown tool type: {}, using dummy tool", name);
@@ -151,24 +186,76 @@ impl BaseAgent {
             path: std::path::PathBuf::from(agent_data_dir).join("memory.db"),
             namespace: "luts".to_string(),
             database: "memory".to_string(),
+            encryption_key: None,
         };
         let memory_store = tokio::task::block_in_place(|| {
             tokio::runtime::Handle::current().block_on(async {
                 SurrealMemoryStore::new(surreal_config).await
             })
         })?;
-        let memory_manager = MemoryManager::new(memory_store);
+        let memory_manager = Arc::new(MemoryManager::new(memory_store));
+        let turn_recorder = Arc::new(TurnRecorder::new(
+            config.persistence,
+            config.agent_id.clone(),
+            memory_manager.clone(),
+        ));
         
         Ok(BaseAgent {
             config,
-            llm_service,
+            llm_service: Arc::new(llm_service),
             memory_manager,
+            turn_recorder,
             tools,
             message_sender: None,
             conversation_history: Vec::new(),
+            stream_manager: Arc::new(ResponseStreamManager::new()),
+            streamed_history: Arc::new(Mutex::new(Vec::new())),
+            max_history_turns: None,
+            shared_memory,
+            core_blocks,
         })
     }
     
+    /// Keep at most `turns` user turns in the history, dropping the oldest
+    ///
+    /// A turn is a user message and everything up to the next one. System
+    /// messages - the prompt and any notes - are never dropped.
+    pub fn with_max_history_turns(mut self, turns: usize) -> Self {
+        self.max_history_turns = Some(turns);
+        self.trim_history();
+        self
+    }
+    
+    /// Generate replies with `service` instead of the configured provider
+    pub fn with_ai_service(mut self, service: Arc<dyn AiService>) -> Self {
+        self.llm_service = service;
+        self
+    }
+    
+    /// Messages exchanged so far, oldest first
+    ///
+    /// A streamed reply shows up here once the agent handles its next message.
+    pub fn history(&self) -> &[InternalChatMessage] {
+        &self.conversation_history
+    }
+    
+    /// Forget the conversation so far
+    pub fn clear_history(&mut self) {
+        debug!("Agent {} clearing history", self.config.agent_id);
+        self.conversation_history.clear();
+        if let Ok(mut streamed) = self.streamed_history.lock() {
+            streamed.clear();
+        }
+    }
+    
+    /// Replace the conversation with `history`, e.g. one recovered from an autosave
+    pub fn load_history(&mut self, history: Vec<InternalChatMessage>) {
+        debug!("Agent {} loading {} history messages", self.config.agent_id, history.len());
+        self.clear_history();
+        self.conversation_history = history;
+        self.trim_history();
+    }
+    
     /// Set the message sender (called by registry)
     pub fn set_message_sender(&mut self, sender: Arc<RwLock<dyn MessageSender>>) {
         self.message_sender = Some(sender);
@@ -178,6 +265,47 @@ impl BaseAgent {
     pub fn memory_manager(&self) -> &MemoryManager {
         &self.memory_manager
     }
+
+    /// Insert a system note that applies to all subsequent turns
+    pub fn insert_system_note(&mut self, note: impl AsRef<str>) {
+        debug!("Agent {} inserting system note", self.config.agent_id);
+        self.conversation_history.push(InternalChatMessage::system_note(note));
+    }
+    
+    /// Fold messages from finished streamed replies into the history
+    fn absorb_streamed_history(&mut self) {
+        if let Ok(mut streamed) = self.streamed_history.lock() {
+            self.conversation_history.append(&mut streamed);
+        }
+    }
+    
+    /// Drop the oldest turns beyond `max_history_turns`, keeping system messages
+    fn trim_history(&mut self) {
+        let Some(max_turns) = self.max_history_turns else {
+            return;
+        };
+        let turn_starts: Vec<usize> = self
+            .conversation_history
+            .iter()
+            .enumerate()
+            .filter(|(_, message)| matches!(message, InternalChatMessage::User { .. }))
+            .map(|(idx, _)| idx)
+            .collect();
+        // The turn in progress is always kept
+        let max_turns = max_turns.max(1);
+        if turn_starts.len() <= max_turns {
+            return;
+        }
+        
+        let keep_from = turn_starts[turn_starts.len() - max_turns];
+        let mut idx = 0;
+        self.conversation_history.retain(|message| {
+            let keep = idx >= keep_from || matches!(message, InternalChatMessage::System { .. });
+            idx += 1;
+            keep
+        });
+        debug!("Agent {} trimmed history to {} messages", self.config.agent_id, self.conversation_history.len());
+    }
 }
 
 #[async_trait]
@@ -194,13 +322,21 @@ impl Agent for BaseAgent {
         &self.config.role
     }
     
+    fn provider(&self) -> Option<&str> {
+        Some(&self.config.provider)
+    }
+    
     async fn process_message(&mut self, message: AgentMessage) -> Result<MessageResponse, Error> {
         debug!("Agent {} processing message from {}", self.agent_id(), message.from_agent_id);
+        self.absorb_streamed_history();
+        let turn = self.turn_recorder.begin(&message);
+        let tool_policy = self.config.tool_policy_for(&message);
         
         // Add the user message to conversation history
         self.conversation_history.push(InternalChatMessage::User {
             content: message.content.clone(),
         });
+        self.trim_history();
 
         // Start with the full conversation history
         let mut conversation_messages = self.conversation_history.clone();
@@ -225,7 +361,8 @@ impl Agent for BaseAgent {
                    self.agent_id(), iteration_count, conversation_messages.len());
 
             // Generate response using LLM service
-            match self.llm_service.generate_response(&conversation_messages).await {
+            let params = GenerationParams::default();
+            match self.llm_service.generate_response(&conversation_messages, &params).await {
                 Ok(response_content) => {
                     debug!("Agent {} received response content type: {:?}", 
                            self.agent_id(), std::mem::discriminant(&response_content));
@@ -253,31 +390,37 @@ impl Agent for BaseAgent {
                                 debug!("Executing tool: {} with args: {:?}", tool_name, tool_args);
                                 
                                 // Find and execute the tool
-                                let (tool_result, tool_success) = if let Some(tool) = self.tools.get(tool_name) {
-                                    match tool.execute(tool_args.clone()).await {
-                                        Ok(result) => {
+                                let (tool_result, outcome) = if let Some(tool) = self.tools.get(tool_name) {
+                                    // Refused tools aren't run; run() times out slow ones
+                                    let outcome =
+                                        tool_policy.run(tool.as_ref(), tool_args.clone()).await;
+                                    let text = match (&outcome.result, &outcome.error) {
+                                        (result, None) => {
                                             info!("Tool {} completed successfully: {:?}", tool_name, result);
-                                            (result.to_string(), true)
+                                            result.clone().unwrap_or_default().to_string()
                                         }
-                                        Err(e) => {
+                                        (_, Some(e)) => {
                                             info!("Tool {} failed: {}", tool_name, e);
-                                            (format!("Error executing tool {}: {}", tool_name, e), false)
+                                            format!("Error executing tool {}: {}", tool_name, e)
                                         }
-                                    }
+                                    };
+                                    (text, outcome)
                                 } else {
-                                    (format!("Tool '{}' not found. Available tools: {:?}", tool_name, self.tools.keys().collect::<Vec<_>>()), false)
+                                    let message = format!("Tool '{}' not found. Available tools: {:?}", tool_name, self.tools.keys().collect::<Vec<_>>());
+                                    let outcome = ToolResult::failure_with_kind(
+                                        tool_name.clone(),
+                                        ToolErrorKind::NotFound,
+                                        message.clone(),
+                                    );
+                                    (message, outcome)
                                 };
+                                let tool_success = outcome.is_success();
                                 
                                 debug!("Tool {} result: {}", tool_name, tool_result);
                                 
                                 // Record tool call info for API response
-                                let tool_call_info = ToolCallInfo {
-                                    tool_name: tool_name.clone(),
-                                    tool_args: tool_args.clone(),
-                                    tool_result: tool_result.clone(),
-                                    success: tool_success,
-                                    call_id: Some(call_id.clone()),
-                                };
+                                let tool_call_info =
+                                    ToolCallInfo::from_result(tool_args.clone(), Some(call_id.clone()), &outcome);
                                 all_tool_calls.push(tool_call_info);
                                 debug!("Agent {} recorded tool call: {} (success: {})", self.agent_id(), tool_name, tool_success);
                                 
@@ -307,6 +450,9 @@ impl Agent for BaseAgent {
                                 tool_responses: None,
                             };
                             self.conversation_history.push(assistant_message);
+                            if let Some(turn) = turn {
+                                turn.complete(self.llm_service.clone(), &response_text).await;
+                            }
                             
                             debug!("Agent {} returning response with {} tool calls", self.agent_id(), all_tool_calls.len());
                             
@@ -336,6 +482,9 @@ impl Agent for BaseAgent {
                                     tool_responses: None,
                                 };
                                 self.conversation_history.push(assistant_message);
+                                if let Some(turn) = turn {
+                                    turn.complete(self.llm_service.clone(), &combined_text).await;
+                                }
                                 
                                 debug!("Agent {} returning response with {} tool calls (from parts)", self.agent_id(), all_tool_calls.len());
                                 
@@ -372,6 +521,62 @@ impl Agent for BaseAgent {
         }
     }
     
+    async fn process_message_streaming(&mut self, message: AgentMessage) -> Result<StreamableResponse, Error> {
+        debug!("Agent {} streaming reply to {}", self.agent_id(), message.from_agent_id);
+        self.absorb_streamed_history();
+        let turn = self.turn_recorder.begin(&message);
+        let tool_policy = self.config.tool_policy_for(&message);
+        self.conversation_history.push(InternalChatMessage::User {
+            content: message.content,
+        });
+        self.trim_history();
+        
+        // The reply, its tool calls and their results land in
+        // `streamed_history` and join the history on the next message
+        Ok(stream_agent_turns(
+            self.stream_manager.clone(),
+            self.llm_service.clone(),
+            message.message_id,
+            self.conversation_history.clone(),
+            self.streamed_history.clone(),
+            turn,
+            tool_policy,
+        ))
+    }
+    
+    fn set_shared_memory(&mut self, shared_memory: Option<SharedMemory>) {
+        self.shared_memory.set(shared_memory);
+    }
+    
+    fn core_blocks(&self) -> Option<Arc<RwLock<CoreBlockManager>>> {
+        Some(self.core_blocks.clone())
+    }
+    
+    fn set_persistence_policy(&mut self, policy: ConversationPersistencePolicy) -> Result<(), Error> {
+        debug!("Agent {} persistence policy: {:?}", self.agent_id(), policy);
+        self.config.persistence = policy;
+        self.turn_recorder = Arc::new(self.turn_recorder.with_policy(policy));
+        Ok(())
+    }
+    
+    fn get_history(&self) -> Vec<InternalChatMessage> {
+        let mut history = self.conversation_history.clone();
+        if let Ok(streamed) = self.streamed_history.lock() {
+            history.extend(streamed.iter().cloned());
+        }
+        history
+    }
+    
+    fn set_history(&mut self, history: Vec<InternalChatMessage>) -> Result<(), Error> {
+        self.load_history(history);
+        Ok(())
+    }
+    
+    fn add_context(&mut self, context: &str) -> Result<(), Error> {
+        self.insert_system_note(context);
+        Ok(())
+    }
+    
     async fn send_message(&self, message: AgentMessage) -> Result<(), Error> {
         if let Some(sender) = &self.message_sender {
             sender.read().await.send_message(message).await
@@ -411,4 +616,198 @@ impl AiTool for DummyTool {
     async fn execute(&self, _params: serde_json::Value) -> Result<serde_json::Value, Error> {
         Ok(serde_json::json!({"result": "dummy"}))
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use luts_llm::{MockAiService, MockResponse};
+    use luts_memory::BlockType;
+
+    /// Service that answers "Noted." to each of `turns` requests
+    fn noted(turns: usize) -> MockAiService {
+        let service = MockAiService::new();
+        for _ in 0..turns {
+            service.push(MockResponse::Text("Noted.".to_string()));
+        }
+        service
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_history_grows_and_trims_oldest_turns() {
+        let data_dir = tempfile::TempDir::new().unwrap();
+        let config = AgentConfig {
+            agent_id: "historian".to_string(),
+            name: "Historian".to_string(),
+            role: "test".to_string(),
+            system_prompt: None,
+            provider: "gemini-2.5-flash".to_string(),
+            tool_names: Vec::new(),
+            data_dir: data_dir.path().to_str().unwrap().to_string(),
+            generation: Default::default(),
+            persistence: Default::default(),
+            tool_policy: Default::default(),
+        };
+        let mut agent = BaseAgent::new(config, HashMap::new())
+            .unwrap()
+            .with_ai_service(Arc::new(noted(3)))
+            .with_max_history_turns(2);
+        agent.insert_system_note("Be brief");
+        
+        let send = |content: &str| {
+            AgentMessage::new_chat("user".to_string(), "historian".to_string(), content.to_string())
+        };
+        
+        agent.process_message(send("first")).await.unwrap();
+        assert_eq!(agent.history().len(), 3);
+        agent.process_message(send("second")).await.unwrap();
+        assert_eq!(agent.history().len(), 5);
+        
+        // The first turn goes, the note stays
+        agent.process_message(send("third")).await.unwrap();
+        let history = agent.history();
+        assert_eq!(history.len(), 5);
+        assert!(history[0].is_system_note());
+        assert_eq!(history[1].content(), "second");
+        assert_eq!(history[4].content(), "Noted.");
+        
+        let saved = history.to_vec();
+        agent.clear_history();
+        assert!(agent.history().is_empty());
+        agent.load_history(saved);
+        assert_eq!(agent.history().len(), 5);
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_core_blocks_start_from_the_system_prompt() {
+        use luts_core::context::core_blocks::CoreBlockType;
+
+        let data_dir = tempfile::TempDir::new().unwrap();
+        let config = AgentConfig {
+            agent_id: "framed".to_string(),
+            name: "Framed".to_string(),
+            role: "test".to_string(),
+            system_prompt: Some("Answer in haiku".to_string()),
+            provider: "gemini-2.5-flash".to_string(),
+            tool_names: Vec::new(),
+            data_dir: data_dir.path().to_str().unwrap().to_string(),
+            generation: Default::default(),
+            persistence: Default::default(),
+            tool_policy: Default::default(),
+        };
+        let agent = BaseAgent::new(config, HashMap::new()).unwrap();
+        
+        let core_blocks = agent.core_blocks().unwrap();
+        let mut core_blocks = core_blocks.write().await;
+        let system_prompt = core_blocks.get_block(CoreBlockType::SystemPrompt).unwrap();
+        assert_eq!(system_prompt.get_text_content(), Some("Answer in haiku"));
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_messages_only_policy_stores_each_turn() {
+        let data_dir = tempfile::TempDir::new().unwrap();
+        let config = AgentConfig {
+            agent_id: "scribe".to_string(),
+            name: "Scribe".to_string(),
+            role: "test".to_string(),
+            system_prompt: None,
+            provider: "gemini-2.5-flash".to_string(),
+            tool_names: Vec::new(),
+            data_dir: data_dir.path().to_str().unwrap().to_string(),
+            generation: Default::default(),
+            persistence: Default::default(),
+            tool_policy: Default::default(),
+        };
+        let mut agent = BaseAgent::new(config, HashMap::new())
+            .unwrap()
+            .with_ai_service(Arc::new(noted(1)));
+        agent
+            .set_persistence_policy(ConversationPersistencePolicy::MessagesOnly)
+            .unwrap();
+        assert!(agent.memory_manager().list("user").await.unwrap().is_empty());
+        
+        let message = AgentMessage::new_chat(
+            "user".to_string(),
+            "scribe".to_string(),
+            "Remember that I like tea".to_string(),
+        );
+        agent.process_message(message).await.unwrap();
+        
+        let mut blocks = agent.memory_manager().list("user").await.unwrap();
+        assert_eq!(blocks.len(), 2);
+        blocks.sort_by(|a, b| a.content().as_text().cmp(&b.content().as_text()));
+        let contents: Vec<_> = blocks
+            .iter()
+            .map(|block| block.content().as_text().unwrap())
+            .collect();
+        assert_eq!(contents, ["Noted.", "Remember that I like tea"]);
+        for block in &blocks {
+            assert_eq!(block.block_type(), BlockType::Message);
+            assert_eq!(block.session_id(), Some("scribe"));
+        }
+    }
+    
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_read_only_message_policy_refuses_delete_block() {
+        use crate::tools::DeleteBlockTool;
+        use luts_llm::ToolPolicy;
+        use luts_memory::{InMemoryMemoryStore, MemoryBlock, MemoryContent};
+        
+        let memory_manager = Arc::new(MemoryManager::new(InMemoryMemoryStore::new()));
+        let block_id = memory_manager
+            .store(MemoryBlock::new(
+                BlockType::Fact,
+                "user",
+                MemoryContent::Text("The kettle is blue".to_string()),
+            ))
+            .await
+            .unwrap();
+        let delete_call = MockResponse::ToolCall {
+            name: "delete_block".to_string(),
+            args: serde_json::json!({ "block_id": block_id.as_str() }),
+        };
+        let service = MockAiService::new()
+            .with_response(delete_call.clone())
+            .with_response(MockResponse::Text("I can't delete it here.".to_string()))
+            .with_response(delete_call)
+            .with_response(MockResponse::Text("Deleted.".to_string()));
+        
+        let data_dir = tempfile::TempDir::new().unwrap();
+        let config = AgentConfig {
+            agent_id: "janitor".to_string(),
+            name: "Janitor".to_string(),
+            role: "test".to_string(),
+            system_prompt: None,
+            provider: "gemini-2.5-flash".to_string(),
+            tool_names: Vec::new(),
+            data_dir: data_dir.path().to_str().unwrap().to_string(),
+            generation: Default::default(),
+            persistence: Default::default(),
+            tool_policy: Default::default(),
+        };
+        let mut tools: HashMap<String, Box<dyn AiTool>> = HashMap::new();
+        tools.insert(
+            "delete_block".to_string(),
+            Box::new(DeleteBlockTool { memory_manager: memory_manager.clone() }),
+        );
+        let mut agent = BaseAgent::new(config, tools)
+            .unwrap()
+            .with_ai_service(Arc::new(service));
+        let send = || {
+            AgentMessage::new_chat("user".to_string(), "janitor".to_string(), "Forget the kettle".to_string())
+        };
+        
+        // A read-only sender keeps the agent's destructive tools from running
+        let response = agent
+            .process_message(send().with_tool_policy(ToolPolicy::read_only()))
+            .await
+            .unwrap();
+        assert_eq!(response.content, "I can't delete it here.");
+        let refusal = response.tool_calls[0].tool_error.as_ref().unwrap();
+        assert_eq!(refusal.kind, ToolErrorKind::Refused);
+        assert!(memory_manager.get(&block_id).await.unwrap().is_some());
+        
+        let response = agent.process_message(send()).await.unwrap();
+        assert!(response.tool_calls[0].success);
+        assert!(memory_manager.get(&block_id).await.unwrap().is_none());
+    }
+}
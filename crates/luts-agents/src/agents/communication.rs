@@ -1,7 +1,10 @@
 //! Communication primitives for agent messaging
 
+use luts_llm::{ToolError, ToolPolicy, ToolResult};
+use luts_llm::streaming::{ChunkMetadata, ChunkType, ResponseChunk};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 /// Information about a tool call that was executed
@@ -13,8 +16,12 @@ pub struct ToolCallInfo {
     /// Arguments passed to the tool
     pub tool_args: Value,
     
-    /// Result returned by the tool
-    pub tool_result: String,
+    /// Result returned by the tool; `null` if it failed or hasn't answered
+    pub tool_result: Value,
+    
+    /// Why the tool call failed, if it did
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_error: Option<ToolError>,
     
     /// Whether the tool call was successful
     pub success: bool,
@@ -23,6 +30,42 @@ pub struct ToolCallInfo {
     pub call_id: Option<String>,
 }
 
+impl ToolCallInfo {
+    /// Record a call that was answered with `result`
+    pub fn from_result(tool_args: Value, call_id: Option<String>, result: &ToolResult) -> Self {
+        let mut info = Self {
+            tool_name: result.tool.clone(),
+            tool_args,
+            tool_result: Value::Null,
+            tool_error: None,
+            success: false,
+            call_id,
+        };
+        info.set_result(result);
+        info
+    }
+
+    /// Fill in the answer to this call
+    fn set_result(&mut self, result: &ToolResult) {
+        self.tool_result = result.result.clone().unwrap_or(Value::Null);
+        self.tool_error = result.tool_error();
+        self.success = result.is_success();
+    }
+
+    /// The result as text, the way it was shown before results were typed
+    ///
+    /// String results are used as-is, other values as compact JSON, and
+    /// failures as their error message.
+    pub fn to_display_string(&self) -> String {
+        match (&self.tool_error, &self.tool_result) {
+            (Some(error), _) => error.message.clone(),
+            (None, Value::String(text)) => text.clone(),
+            (None, Value::Null) => String::new(),
+            (None, value) => value.to_string(),
+        }
+    }
+}
+
 /// A message sent between agents
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AgentMessage {
@@ -49,6 +92,11 @@ pub struct AgentMessage {
     
     /// Timestamp when message was created
     pub timestamp: i64,
+    
+    /// Tool safety levels the sender allows while answering, on top of the
+    /// agent's own policy; `None` leaves the agent's policy as is
+    #[serde(default)]
+    pub tool_policy: Option<ToolPolicy>,
 }
 
 /// Response to an agent message
@@ -108,6 +156,7 @@ impl AgentMessage {
             message_type: MessageType::Chat,
             correlation_id: None,
             timestamp: chrono::Utc::now().timestamp(),
+            tool_policy: None,
         }
     }
     
@@ -128,8 +177,15 @@ impl AgentMessage {
             message_type: MessageType::TaskRequest,
             correlation_id: Some(correlation_id),
             timestamp: chrono::Utc::now().timestamp(),
+            tool_policy: None,
         }
     }
+    
+    /// Only let tools run while answering if `policy` allows them too
+    pub fn with_tool_policy(mut self, policy: ToolPolicy) -> Self {
+        self.tool_policy = Some(policy);
+        self
+    }
 }
 
 impl MessageResponse {
@@ -183,4 +239,105 @@ impl MessageResponse {
             timestamp: chrono::Utc::now().timestamp(),
         }
     }
+
+    /// Collect a streamed response back into a single response
+    ///
+    /// Text and completion chunks make up the content. Each `ToolCall` chunk
+    /// is paired with the next `ToolResponse` for the same tool, and a
+    /// `Complete` chunk may carry already-collected calls under
+    /// `custom["tool_calls"]`. An `Error` chunk turns the whole response
+    /// into an error.
+    pub fn from_chunks(in_response_to: String, chunks: &[ResponseChunk]) -> Self {
+        let mut content = String::new();
+        let mut tool_calls: Vec<ToolCallInfo> = Vec::new();
+        // Calls still waiting for their response
+        let mut pending = 0;
+
+        for chunk in chunks {
+            let custom = &chunk.metadata.custom;
+            match chunk.chunk_type {
+                ChunkType::Text => content.push_str(&chunk.content),
+                ChunkType::Complete => {
+                    content.push_str(&chunk.content);
+                    if let Some(collected) = custom
+                        .get("tool_calls")
+                        .and_then(|calls| serde_json::from_value::<Vec<ToolCallInfo>>(calls.clone()).ok())
+                    {
+                        tool_calls.extend(collected);
+                    }
+                }
+                ChunkType::ToolCall => {
+                    tool_calls.push(ToolCallInfo {
+                        tool_name: custom
+                            .get("tool_name")
+                            .and_then(Value::as_str)
+                            .unwrap_or_default()
+                            .to_string(),
+                        tool_args: custom.get("tool_args").cloned().unwrap_or(Value::Null),
+                        tool_result: Value::Null,
+                        tool_error: None,
+                        success: false,
+                        call_id: custom.get("call_id").and_then(Value::as_str).map(str::to_string),
+                    });
+                    pending += 1;
+                }
+                ChunkType::ToolResponse => {
+                    let Some(result) = ToolResult::from_json(&chunk.content) else {
+                        continue;
+                    };
+                    let waiting = tool_calls.len() - pending;
+                    if let Some(offset) = tool_calls[waiting..]
+                        .iter()
+                        .position(|call| call.tool_name == result.tool)
+                    {
+                        tool_calls[waiting + offset].set_result(&result);
+                        // Earlier unanswered calls can no longer be answered
+                        pending -= offset + 1;
+                    }
+                }
+                ChunkType::Error => {
+                    return Self::error(in_response_to, chunk.content.clone());
+                }
+                ChunkType::Reasoning | ChunkType::Status => {}
+            }
+        }
+
+        Self::success_with_tools(in_response_to, content, None, tool_calls)
+    }
+
+    /// This response as the single, final chunk of a stream
+    ///
+    /// Tool calls travel in `custom["tool_calls"]`, so
+    /// [`MessageResponse::from_chunks`] gets them back.
+    pub fn to_chunk(&self, session_id: &str) -> ResponseChunk {
+        let mut custom = HashMap::new();
+        if !self.tool_calls.is_empty() {
+            custom.insert(
+                "tool_calls".to_string(),
+                serde_json::to_value(&self.tool_calls).unwrap_or_default(),
+            );
+        }
+
+        let (chunk_type, content) = if self.success {
+            (ChunkType::Complete, self.content.clone())
+        } else {
+            (ChunkType::Error, self.error.clone().unwrap_or_default())
+        };
+
+        ResponseChunk {
+            id: format!("{}_0", session_id),
+            sequence: 0,
+            content,
+            is_final: true,
+            timestamp: chrono::Utc::now(),
+            chunk_type,
+            metadata: ChunkMetadata {
+                token_count: None,
+                processing_time_ms: None,
+                model: None,
+                confidence: None,
+                custom,
+            },
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,129 @@
+//! Memory sharing between agents in a registry
+//!
+//! By default every agent keeps its memory in its own store under its own
+//! data directory. An [`AgentRegistry`](crate::agents::AgentRegistry) can be
+//! given a shared [`MemoryManager`] and a shared user ID; agents whose scope is
+//! [`MemoryScope::Shared`] or [`MemoryScope::SharedReadOnly`] then get a
+//! [`SharedMemory`] handle onto that common block space.
+//!
+//! The registry hands the handle to the agent when its scope is set, and the
+//! agent's memory tools read it from a [`SharedMemorySlot`] they share with
+//! the agent.
+//!
+//! A [`MemoryScope::Private`] agent never gets a handle. Its blocks stay in
+//! its own store, which no other agent's tools query, so nothing it remembers
+//! is visible to the rest of the registry - and it can't see the shared
+//! space either.
+
+use anyhow::{Result, anyhow};
+use luts_memory::{BlockId, MemoryBlock, MemoryManager, MemoryQuery};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, RwLock};
+
+/// How an agent takes part in the registry's shared memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum MemoryScope {
+    /// Only the agent's own memory
+    #[default]
+    Private,
+    /// Reads and writes the shared block space as well as its own
+    Shared,
+    /// Reads the shared block space but never writes to it
+    SharedReadOnly,
+}
+
+/// Handle onto a registry's shared block space, as seen by one agent
+#[derive(Clone)]
+pub struct SharedMemory {
+    memory_manager: Arc<MemoryManager>,
+    user_id: String,
+    scope: MemoryScope,
+}
+
+impl SharedMemory {
+    pub(crate) fn new(memory_manager: Arc<MemoryManager>, user_id: String, scope: MemoryScope) -> Self {
+        Self {
+            memory_manager,
+            user_id,
+            scope,
+        }
+    }
+
+    /// User ID the shared blocks are stored under
+    pub fn user_id(&self) -> &str {
+        &self.user_id
+    }
+
+    /// Scope this handle was issued for
+    pub fn scope(&self) -> MemoryScope {
+        self.scope
+    }
+
+    /// Whether this agent may write to the shared space
+    pub fn can_write(&self) -> bool {
+        self.scope == MemoryScope::Shared
+    }
+
+    /// Search the shared space; the query's user ID is replaced by the shared one
+    pub async fn search(&self, query: &MemoryQuery) -> Result<Vec<MemoryBlock>> {
+        let query = MemoryQuery {
+            user_id: Some(self.user_id.clone()),
+            ..query.clone()
+        };
+        Ok(self.memory_manager.search(&query).await?)
+    }
+
+    /// Store a block in the shared space under the shared user ID
+    pub async fn store(&self, mut block: MemoryBlock) -> Result<BlockId> {
+        if !self.can_write() {
+            return Err(anyhow!("Shared memory is read-only for this agent"));
+        }
+        block.metadata.user_id = self.user_id.clone();
+        Ok(self.memory_manager.store(block).await?)
+    }
+}
+
+/// Where an agent and its memory tools find the agent's current shared memory handle
+///
+/// Clones share the same slot, so the tools an agent was built with see the
+/// handle the registry gives it later, and lose it when the agent is made
+/// private again.
+#[derive(Clone, Default)]
+pub struct SharedMemorySlot(Arc<RwLock<Option<SharedMemory>>>);
+
+impl SharedMemorySlot {
+    /// Replace the handle; `None` makes the agent private
+    pub fn set(&self, shared_memory: Option<SharedMemory>) {
+        *self.0.write().unwrap_or_else(|e| e.into_inner()) = shared_memory;
+    }
+
+    /// The current handle, if the agent has a shared scope
+    pub fn get(&self) -> Option<SharedMemory> {
+        self.0.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+impl From<SharedMemory> for SharedMemorySlot {
+    fn from(shared_memory: SharedMemory) -> Self {
+        Self(Arc::new(RwLock::new(Some(shared_memory))))
+    }
+}
+
+/// Merge an agent's own results with shared ones, dropping duplicates
+///
+/// Own blocks come first; `limit` applies to the merged list.
+pub(crate) fn merge_results(
+    mut own: Vec<MemoryBlock>,
+    shared: Vec<MemoryBlock>,
+    limit: Option<usize>,
+) -> Vec<MemoryBlock> {
+    for block in shared {
+        if !own.iter().any(|existing| existing.id() == block.id()) {
+            own.push(block);
+        }
+    }
+    if let Some(limit) = limit {
+        own.truncate(limit);
+    }
+    own
+}
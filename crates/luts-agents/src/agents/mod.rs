@@ -5,17 +5,29 @@
 
 pub mod base_agent;
 pub mod communication;
+pub mod memory_scope;
+pub mod persistence;
 pub mod personality;
+pub mod providers;
 pub mod registry;
+mod streaming;
 
 pub use base_agent::{BaseAgent, MessageSender};
 pub use communication::{AgentMessage, MessageResponse, MessageType, ToolCallInfo};
-pub use personality::{PersonalityAgent, PersonalityAgentBuilder};
-pub use registry::AgentRegistry;
+pub use memory_scope::{MemoryScope, SharedMemory, SharedMemorySlot};
+pub use persistence::ConversationPersistencePolicy;
+pub use personality::{PersonalityAgent, PersonalityAgentBuilder, PersonalityMatch};
+pub use providers::AgentProviders;
+pub use registry::{AgentFactory, AgentRegistry};
 
 use anyhow::Error;
 use async_trait::async_trait;
+use luts_core::context::core_blocks::{CoreBlockManager, CoreBlockType};
+use luts_llm::{GenerationParams, InternalChatMessage, ToolPolicy};
+use luts_llm::streaming::StreamableResponse;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::RwLock;
 
 /// Core trait for agents in the LUTS system
 #[async_trait]
@@ -29,12 +41,77 @@ pub trait Agent: Send + Sync {
     /// Role or type of this agent (e.g., "research", "memory", "coordinator")
     fn role(&self) -> &str;
     
+    /// LLM provider this agent generates replies with, if it uses one
+    fn provider(&self) -> Option<&str> {
+        None
+    }
+    
     /// Process an incoming message and generate a response
     async fn process_message(&mut self, message: AgentMessage) -> Result<MessageResponse, Error>;
     
+    /// Process an incoming message, streaming the response as it's generated
+    ///
+    /// The default runs [`Agent::process_message`] and yields its result as a
+    /// single final chunk. Agents that can stream should override this.
+    /// [`MessageResponse::from_chunks`] turns the stream back into a response.
+    async fn process_message_streaming(
+        &mut self,
+        message: AgentMessage,
+    ) -> Result<StreamableResponse, Error> {
+        let session_id = message.message_id.clone();
+        let response = self.process_message(message).await?;
+        let chunk = response.to_chunk(&session_id);
+        Ok(StreamableResponse::from_chunks(session_id, vec![chunk]))
+    }
+    
     /// Send a message to another agent (handled by registry)
     async fn send_message(&self, message: AgentMessage) -> Result<(), Error>;
     
+    /// Add context that applies to every later turn, such as files the user loaded
+    ///
+    /// Agents that keep a history send it to the model as a system note. The
+    /// default rejects it, since the agent would have nowhere to keep it.
+    fn add_context(&mut self, _context: &str) -> Result<(), Error> {
+        Err(anyhow::anyhow!("Agent {} does not accept added context", self.agent_id()))
+    }
+
+    /// Give the agent's memory tools a handle onto the registry's shared memory
+    ///
+    /// The registry calls this when the agent's scope is set; `None` makes it
+    /// private again. The default ignores it, for agents without memory tools.
+    fn set_shared_memory(&mut self, _shared_memory: Option<SharedMemory>) {}
+
+    /// Choose what the agent keeps in its memory once a turn completes
+    ///
+    /// The default only accepts [`ConversationPersistencePolicy::None`], since
+    /// the agent has no memory to store turns in.
+    fn set_persistence_policy(&mut self, policy: ConversationPersistencePolicy) -> Result<(), Error> {
+        if policy.stores_messages() {
+            return Err(anyhow::anyhow!("Agent {} cannot store its conversation", self.agent_id()));
+        }
+        Ok(())
+    }
+
+    /// Messages exchanged so far, oldest first; empty for agents that keep no history
+    fn get_history(&self) -> Vec<InternalChatMessage> {
+        Vec::new()
+    }
+
+    /// Replace the conversation history, e.g. with one restored from an autosave
+    ///
+    /// The default rejects it, since the agent would have nowhere to keep it.
+    fn set_history(&mut self, _history: Vec<InternalChatMessage>) -> Result<(), Error> {
+        Err(anyhow::anyhow!("Agent {} does not keep a conversation history", self.agent_id()))
+    }
+
+    /// Core blocks framing the agent's context: its system prompt and pinned blocks
+    ///
+    /// Exporters read them to carry the framing along with a conversation.
+    /// `None` for agents without core blocks.
+    fn core_blocks(&self) -> Option<Arc<RwLock<CoreBlockManager>>> {
+        None
+    }
+
     /// Get the list of available tools for this agent
     fn get_available_tools(&self) -> Vec<String>;
     
@@ -65,4 +142,39 @@ pub struct AgentConfig {
     
     /// Data directory for this agent's memory
     pub data_dir: String,
-}
\ No newline at end of file
+
+    /// Sampling parameters (temperature, etc.) for this agent's requests
+    #[serde(default)]
+    pub generation: GenerationParams,
+
+    /// What is kept in memory once a conversation turn completes
+    #[serde(default)]
+    pub persistence: ConversationPersistencePolicy,
+
+    /// Safety levels of the tools this agent may run
+    #[serde(default)]
+    pub tool_policy: ToolPolicy,
+}
+
+impl AgentConfig {
+    /// Tool policy for answering `message`: this agent's, narrowed by the sender's
+    pub fn tool_policy_for(&self, message: &AgentMessage) -> ToolPolicy {
+        match &message.tool_policy {
+            Some(policy) => self.tool_policy.intersect(policy),
+            None => self.tool_policy.clone(),
+        }
+    }
+
+    /// Core blocks for an agent built from this config, starting from its system prompt
+    ///
+    /// Shared with the agent's `modify_core_block` tool, so its edits show up here.
+    pub(crate) async fn core_blocks(&self) -> Result<Arc<RwLock<CoreBlockManager>>, Error> {
+        let mut core_blocks = CoreBlockManager::new(self.agent_id.clone(), None);
+        if let Some(system_prompt) = &self.system_prompt {
+            core_blocks
+                .update_block(CoreBlockType::SystemPrompt, system_prompt.clone())
+                .await?;
+        }
+        Ok(Arc::new(RwLock::new(core_blocks)))
+    }
+}
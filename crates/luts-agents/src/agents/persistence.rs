@@ -0,0 +1,232 @@
+//! Persisting conversation turns to an agent's memory
+//!
+//! An agent's history lives only as long as the agent does. With a
+//! [`ConversationPersistencePolicy`] other than `None`, each finished turn -
+//! the user's message and the agent's reply - is also stored as a pair of
+//! [`BlockType::Message`] blocks in the agent's memory, where they can be
+//! searched and, when the store has an embedding service, found by semantic
+//! search later. With `MessagesAndSummaries` a rolling [`BlockType::Summary`]
+//! block for the session is rewritten every [`SUMMARY_INTERVAL_TURNS`] turns.
+
+use crate::agents::AgentMessage;
+use anyhow::{Result, anyhow};
+use luts_llm::AiService;
+use luts_llm::conversation::{AiSummaryGenerator, SummaryGenerator};
+use luts_memory::{BlockId, BlockType, MemoryBlockBuilder, MemoryContent, MemoryManager};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::{debug, warn};
+
+/// Turns between updates of a session's rolling summary
+pub const SUMMARY_INTERVAL_TURNS: usize = 5;
+
+/// Tokens to aim for in a rolling summary
+const SUMMARY_TARGET_TOKENS: usize = 300;
+
+/// What an agent keeps in its memory once a turn completes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum ConversationPersistencePolicy {
+    /// Turns are kept in the history only
+    #[default]
+    None,
+    /// Each user message and reply is stored as a message block
+    MessagesOnly,
+    /// Message blocks, plus a rolling summary block for each session
+    MessagesAndSummaries,
+}
+
+impl ConversationPersistencePolicy {
+    /// Whether turns are stored as message blocks
+    pub fn stores_messages(&self) -> bool {
+        *self != ConversationPersistencePolicy::None
+    }
+
+    /// Whether sessions get a rolling summary block
+    pub fn stores_summaries(&self) -> bool {
+        *self == ConversationPersistencePolicy::MessagesAndSummaries
+    }
+}
+
+impl std::str::FromStr for ConversationPersistencePolicy {
+    type Err = anyhow::Error;
+
+    /// Parse `none`, `messages` or `messages-and-summaries`, as given on the command line
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().replace('_', "-").as_str() {
+            "none" => Ok(ConversationPersistencePolicy::None),
+            "messages" | "messages-only" => Ok(ConversationPersistencePolicy::MessagesOnly),
+            "messages-and-summaries" | "summaries" => {
+                Ok(ConversationPersistencePolicy::MessagesAndSummaries)
+            }
+            _ => Err(anyhow!(
+                "Unknown persistence policy: {}. Available: none, messages, messages-and-summaries",
+                s
+            )),
+        }
+    }
+}
+
+/// Turns of one session since its summary was last written
+#[derive(Default)]
+struct SessionLog {
+    turns: usize,
+    summary: Option<String>,
+    unsummarized: Vec<String>,
+}
+
+/// Stores an agent's finished turns according to its policy
+pub(crate) struct TurnRecorder {
+    policy: ConversationPersistencePolicy,
+    agent_id: String,
+    memory_manager: Arc<MemoryManager>,
+    sessions: Mutex<HashMap<String, SessionLog>>,
+}
+
+impl TurnRecorder {
+    pub(crate) fn new(
+        policy: ConversationPersistencePolicy,
+        agent_id: impl Into<String>,
+        memory_manager: Arc<MemoryManager>,
+    ) -> Self {
+        Self {
+            policy,
+            agent_id: agent_id.into(),
+            memory_manager,
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// A recorder for the same agent and memory that follows `policy` instead
+    pub(crate) fn with_policy(&self, policy: ConversationPersistencePolicy) -> Self {
+        Self::new(policy, self.agent_id.clone(), self.memory_manager.clone())
+    }
+
+    /// Start the turn `message` opens, or `None` if turns aren't persisted
+    ///
+    /// The turn belongs to the sender, and to the session named by the
+    /// message's correlation ID - or to the agent's own session without one.
+    pub(crate) fn begin(self: &Arc<Self>, message: &AgentMessage) -> Option<PendingTurn> {
+        if !self.policy.stores_messages() {
+            return None;
+        }
+        Some(PendingTurn {
+            recorder: self.clone(),
+            user_id: message.from_agent_id.clone(),
+            session_id: message
+                .correlation_id
+                .clone()
+                .unwrap_or_else(|| self.agent_id.clone()),
+            message: message.content.clone(),
+        })
+    }
+
+    async fn record(
+        &self,
+        ai_service: Arc<dyn AiService>,
+        turn: &PendingTurn,
+        reply: &str,
+    ) -> Result<()> {
+        for (role, content) in [("user", turn.message.as_str()), ("assistant", reply)] {
+            let block = MemoryBlockBuilder::new()
+                .with_type(BlockType::Message)
+                .with_user_id(&turn.user_id)
+                .with_session_id(&turn.session_id)
+                .with_property("role", role)
+                .with_property("agent_id", self.agent_id.as_str())
+                .with_content(MemoryContent::Text(content.to_string()))
+                .build()?;
+            self.memory_manager.store(block).await?;
+        }
+        debug!(
+            "Agent {} stored turn for session {}",
+            self.agent_id, turn.session_id
+        );
+
+        if self.policy.stores_summaries() {
+            self.update_summary(ai_service, turn, reply).await?;
+        }
+        Ok(())
+    }
+
+    /// Rewrite the session's summary once enough turns have piled up
+    async fn update_summary(
+        &self,
+        ai_service: Arc<dyn AiService>,
+        turn: &PendingTurn,
+        reply: &str,
+    ) -> Result<()> {
+        let (text, turns, summarized) = {
+            let mut sessions = self
+                .sessions
+                .lock()
+                .map_err(|_| anyhow!("Session log lock poisoned"))?;
+            let log = sessions.entry(turn.session_id.clone()).or_default();
+            log.turns += 1;
+            log.unsummarized.push(format!("user: {}", turn.message));
+            log.unsummarized.push(format!("assistant: {}", reply));
+            if log.turns % SUMMARY_INTERVAL_TURNS != 0 {
+                return Ok(());
+            }
+
+            let mut text = log
+                .summary
+                .as_ref()
+                .map(|summary| format!("Summary so far: {}\n\n", summary))
+                .unwrap_or_default();
+            text.push_str(&log.unsummarized.join("\n"));
+            (text, log.turns, log.unsummarized.len())
+        };
+
+        let summary = AiSummaryGenerator::new(ai_service)
+            .summarize(&text, SUMMARY_TARGET_TOKENS, None)
+            .await?;
+
+        // One block per session, replaced each time
+        let block = MemoryBlockBuilder::new()
+            .with_id(BlockId::deterministic(
+                "conversation_summary",
+                &format!("{}:{}:{}", self.agent_id, turn.user_id, turn.session_id),
+            ))
+            .with_type(BlockType::Summary)
+            .with_user_id(&turn.user_id)
+            .with_session_id(&turn.session_id)
+            .with_property("agent_id", self.agent_id.as_str())
+            .with_property("turns", turns)
+            .with_content(MemoryContent::Text(summary.clone()))
+            .build()?;
+        self.memory_manager.store(block).await?;
+
+        if let Ok(mut sessions) = self.sessions.lock() {
+            let log = sessions.entry(turn.session_id.clone()).or_default();
+            log.summary = Some(summary);
+            // Turns that finished while the summary was being written stay
+            let summarized = summarized.min(log.unsummarized.len());
+            log.unsummarized.drain(..summarized);
+        }
+        Ok(())
+    }
+}
+
+/// A user message waiting for the reply that completes its turn
+pub(crate) struct PendingTurn {
+    recorder: Arc<TurnRecorder>,
+    user_id: String,
+    session_id: String,
+    message: String,
+}
+
+impl PendingTurn {
+    /// Store the turn now that `reply` has completed it
+    ///
+    /// Failures are logged rather than returned: the reply has already been
+    /// given, and losing its copy in memory shouldn't turn it into an error.
+    pub(crate) async fn complete(self, ai_service: Arc<dyn AiService>, reply: &str) {
+        if let Err(e) = self.recorder.record(ai_service, &self, reply).await {
+            warn!(
+                "Agent {} failed to store turn for session {}: {}",
+                self.recorder.agent_id, self.session_id, e
+            );
+        }
+    }
+}
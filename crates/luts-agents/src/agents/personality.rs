@@ -1,20 +1,31 @@
 //! Personality-based agents for LUTS CLI
 
-use crate::agents::{Agent, AgentConfig, AgentMessage, MessageResponse};
+use crate::agents::persistence::TurnRecorder;
+use crate::agents::streaming::stream_agent_turns;
+use crate::agents::{
+    Agent, AgentConfig, AgentMessage, AgentProviders, ConversationPersistencePolicy,
+    MessageResponse, SharedMemory, SharedMemorySlot,
+};
 use crate::tools::{
     block::BlockTool, delete_block::DeleteBlockTool, modify_core_block::ModifyCoreBlockTool,
     retrieve_context::RetrieveContextTool, update_block::UpdateBlockTool,
 };
 use anyhow::{Error, anyhow};
 use async_trait::async_trait;
+use luts_core::context::core_blocks::CoreBlockManager;
 use luts_llm::tools::AiTool;
-use luts_llm::{AiService, InternalChatMessage, LLMService};
+use luts_llm::{
+    AiService, GenerationParams, InternalChatMessage, LLMService, ResponseStreamManager,
+    StreamableResponse,
+};
 use luts_memory::{MemoryManager, SurrealConfig, SurrealMemoryStore};
 use luts_tools::{
-    calc::MathTool, search::DDGSearchTool, semantic_search::SemanticSearchTool,
-    website::WebsiteTool,
+    calc::MathTool, memory_stats::MemoryStatsTool, search::DDGSearchTool,
+    semantic_search::SemanticSearchTool, website::WebsiteTool,
 };
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock;
 use tracing::{debug, info};
 
 /// Create personality-based agents with different reasoning styles and tools
@@ -22,7 +33,11 @@ pub struct PersonalityAgentBuilder;
 
 impl PersonalityAgentBuilder {
     /// Create a "Researcher" agent - thorough, analytical, uses web tools
-    pub fn create_researcher(data_dir: &str, provider: &str) -> Result<Box<dyn Agent>, Error> {
+    pub fn create_researcher(
+        data_dir: &str,
+        provider: &str,
+        overrides: &GenerationParams,
+    ) -> Result<Box<dyn Agent>, Error> {
         let config = AgentConfig {
             agent_id: "researcher".to_string(),
             name: "Dr. Research".to_string(),
@@ -41,8 +56,11 @@ impl PersonalityAgentBuilder {
                 \n\nIMPORTANT: When you use any tools: Always give a clear final answer or response after using tools".to_string()
             ),
             provider: provider.to_string(),
-            tool_names: vec!["search".to_string(), "website".to_string(), "block".to_string(), "retrieve_context".to_string(), "update_block".to_string(), "modify_core_block".to_string(), "semantic_search".to_string()],
+            tool_names: vec!["search".to_string(), "website".to_string(), "block".to_string(), "retrieve_context".to_string(), "update_block".to_string(), "modify_core_block".to_string(), "semantic_search".to_string(), "memory_stats".to_string()],
             data_dir: data_dir.to_string(),
+            generation: Self::default_generation_params("researcher").merged_with(overrides),
+            persistence: Default::default(),
+            tool_policy: Default::default(),
         };
 
         let memory_manager = {
@@ -53,6 +71,7 @@ impl PersonalityAgentBuilder {
                 path: std::path::PathBuf::from(agent_data_dir).join("memory.db"),
                 namespace: "luts".to_string(),
                 database: "memory".to_string(),
+                encryption_key: None,
             };
             let memory_store = tokio::task::block_in_place(|| {
                 tokio::runtime::Handle::current()
@@ -61,25 +80,28 @@ impl PersonalityAgentBuilder {
             std::sync::Arc::new(MemoryManager::new(memory_store))
         };
 
+        let shared_memory = SharedMemorySlot::default();
         let mut tools = HashMap::new();
         tools.insert(
             "search".to_string(),
-            Box::new(DDGSearchTool) as Box<dyn AiTool>,
+            Box::new(DDGSearchTool::default()) as Box<dyn AiTool>,
         );
         tools.insert(
             "website".to_string(),
-            Box::new(WebsiteTool) as Box<dyn AiTool>,
+            Box::new(WebsiteTool::default()) as Box<dyn AiTool>,
         );
         tools.insert(
             "block".to_string(),
             Box::new(BlockTool {
                 memory_manager: memory_manager.clone(),
+                shared_memory: shared_memory.clone(),
             }) as Box<dyn AiTool>,
         );
         tools.insert(
             "retrieve_context".to_string(),
             Box::new(RetrieveContextTool {
                 memory_manager: memory_manager.clone(),
+                shared_memory: shared_memory.clone(),
             }) as Box<dyn AiTool>,
         );
         tools.insert(
@@ -92,12 +114,24 @@ impl PersonalityAgentBuilder {
             "semantic_search".to_string(),
             Box::new(SemanticSearchTool::new(memory_manager.clone()).unwrap()) as Box<dyn AiTool>,
         );
+        tools.insert(
+            "memory_stats".to_string(),
+            Box::new(MemoryStatsTool::new(memory_manager.clone())) as Box<dyn AiTool>,
+        );
 
-        Ok(Box::new(PersonalityAgent::new(config, tools)?))
+        Ok(Box::new(PersonalityAgent::new_with_shared_memory(
+            config,
+            tools,
+            shared_memory,
+        )?))
     }
 
     /// Create a "Calculator" agent - logical, precise, math-focused
-    pub fn create_calculator(data_dir: &str, provider: &str) -> Result<Box<dyn Agent>, Error> {
+    pub fn create_calculator(
+        data_dir: &str,
+        provider: &str,
+        overrides: &GenerationParams,
+    ) -> Result<Box<dyn Agent>, Error> {
         let config = AgentConfig {
             agent_id: "calculator".to_string(),
             name: "Logic".to_string(),
@@ -115,16 +149,23 @@ impl PersonalityAgentBuilder {
             provider: provider.to_string(),
             tool_names: vec!["calc".to_string()],
             data_dir: data_dir.to_string(),
+            generation: Self::default_generation_params("calculator").merged_with(overrides),
+            persistence: Default::default(),
+            tool_policy: Default::default(),
         };
 
         let mut tools = HashMap::new();
-        tools.insert("calc".to_string(), Box::new(MathTool) as Box<dyn AiTool>);
+        tools.insert("calc".to_string(), Box::new(MathTool::default()) as Box<dyn AiTool>);
 
         Ok(Box::new(PersonalityAgent::new(config, tools)?))
     }
 
     /// Create a "Creative" agent - imaginative, artistic, big-picture thinking
-    pub fn create_creative(data_dir: &str, provider: &str) -> Result<Box<dyn Agent>, Error> {
+    pub fn create_creative(
+        data_dir: &str,
+        provider: &str,
+        overrides: &GenerationParams,
+    ) -> Result<Box<dyn Agent>, Error> {
         let config = AgentConfig {
             agent_id: "creative".to_string(),
             name: "Spark".to_string(),
@@ -141,6 +182,9 @@ impl PersonalityAgentBuilder {
             provider: provider.to_string(),
             tool_names: vec![],
             data_dir: data_dir.to_string(),
+            generation: Self::default_generation_params("creative").merged_with(overrides),
+            persistence: Default::default(),
+            tool_policy: Default::default(),
         };
 
         let tools = HashMap::new(); // Creative agent relies on pure reasoning
@@ -148,8 +192,46 @@ impl PersonalityAgentBuilder {
         Ok(Box::new(PersonalityAgent::new(config, tools)?))
     }
 
+    /// Create a "Critic" agent - skeptical reviewer of other agents' work
+    pub fn create_critic(
+        data_dir: &str,
+        provider: &str,
+        overrides: &GenerationParams,
+    ) -> Result<Box<dyn Agent>, Error> {
+        let config = AgentConfig {
+            agent_id: "critic".to_string(),
+            name: "Sage".to_string(),
+            role: "critic".to_string(),
+            system_prompt: Some(
+                "You are Sage, a careful and constructive critic. You review answers, plans and code \
+                produced by other agents or the user. You excel at:\
+                \n- Finding factual errors, flawed reasoning and logical gaps\
+                \n- Spotting missing edge cases, unstated assumptions and failure modes\
+                \n- Checking that a response actually answers the question that was asked\
+                \n- Ranking issues by severity so the most important ones come first\
+                \n- Suggesting concrete fixes rather than only pointing out problems\
+                \n\nBe direct and specific: quote the part you are critiquing and explain why it is wrong or incomplete.\
+                \nIf the work holds up, say so plainly instead of inventing objections.".to_string()
+            ),
+            provider: provider.to_string(),
+            tool_names: vec![],
+            data_dir: data_dir.to_string(),
+            generation: Self::default_generation_params("critic").merged_with(overrides),
+            persistence: Default::default(),
+            tool_policy: Default::default(),
+        };
+
+        let tools = HashMap::new(); // Critic reviews by pure reasoning
+
+        Ok(Box::new(PersonalityAgent::new(config, tools)?))
+    }
+
     /// Create a "Coordinator" agent - organized, strategic, good at delegation
-    pub fn create_coordinator(data_dir: &str, provider: &str) -> Result<Box<dyn Agent>, Error> {
+    pub fn create_coordinator(
+        data_dir: &str,
+        provider: &str,
+        overrides: &GenerationParams,
+    ) -> Result<Box<dyn Agent>, Error> {
         let config = AgentConfig {
             agent_id: "coordinator".to_string(),
             name: "Maestro".to_string(),
@@ -170,6 +252,9 @@ impl PersonalityAgentBuilder {
             provider: provider.to_string(),
             tool_names: vec!["calc".to_string(), "search".to_string(), "website".to_string(), "block".to_string(), "retrieve_context".to_string(), "update_block".to_string(), "modify_core_block".to_string(), "semantic_search".to_string()],
             data_dir: data_dir.to_string(),
+            generation: Self::default_generation_params("coordinator").merged_with(overrides),
+            persistence: Default::default(),
+            tool_policy: Default::default(),
         };
 
         let memory_manager = {
@@ -180,6 +265,7 @@ impl PersonalityAgentBuilder {
                 path: std::path::PathBuf::from(agent_data_dir).join("memory.db"),
                 namespace: "luts".to_string(),
                 database: "memory".to_string(),
+                encryption_key: None,
             };
             let memory_store = tokio::task::block_in_place(|| {
                 tokio::runtime::Handle::current()
@@ -188,26 +274,29 @@ impl PersonalityAgentBuilder {
             std::sync::Arc::new(MemoryManager::new(memory_store))
         };
 
+        let shared_memory = SharedMemorySlot::default();
         let mut tools = HashMap::new();
-        tools.insert("calc".to_string(), Box::new(MathTool) as Box<dyn AiTool>);
+        tools.insert("calc".to_string(), Box::new(MathTool::default()) as Box<dyn AiTool>);
         tools.insert(
             "search".to_string(),
-            Box::new(DDGSearchTool) as Box<dyn AiTool>,
+            Box::new(DDGSearchTool::default()) as Box<dyn AiTool>,
         );
         tools.insert(
             "website".to_string(),
-            Box::new(WebsiteTool) as Box<dyn AiTool>,
+            Box::new(WebsiteTool::default()) as Box<dyn AiTool>,
         );
         tools.insert(
             "block".to_string(),
             Box::new(BlockTool {
                 memory_manager: memory_manager.clone(),
+                shared_memory: shared_memory.clone(),
             }) as Box<dyn AiTool>,
         );
         tools.insert(
             "retrieve_context".to_string(),
             Box::new(RetrieveContextTool {
                 memory_manager: memory_manager.clone(),
+                shared_memory: shared_memory.clone(),
             }) as Box<dyn AiTool>,
         );
         tools.insert(
@@ -221,11 +310,19 @@ impl PersonalityAgentBuilder {
             Box::new(SemanticSearchTool::new(memory_manager.clone()).unwrap()) as Box<dyn AiTool>,
         );
 
-        Ok(Box::new(PersonalityAgent::new(config, tools)?))
+        Ok(Box::new(PersonalityAgent::new_with_shared_memory(
+            config,
+            tools,
+            shared_memory,
+        )?))
     }
 
     /// Create a "Pragmatic" agent - practical, efficient, solution-focused
-    pub fn create_pragmatic(data_dir: &str, provider: &str) -> Result<Box<dyn Agent>, Error> {
+    pub fn create_pragmatic(
+        data_dir: &str,
+        provider: &str,
+        overrides: &GenerationParams,
+    ) -> Result<Box<dyn Agent>, Error> {
         let config = AgentConfig {
             agent_id: "pragmatic".to_string(),
             name: "Practical".to_string(),
@@ -244,13 +341,16 @@ impl PersonalityAgentBuilder {
             provider: provider.to_string(),
             tool_names: vec!["calc".to_string(), "search".to_string()],
             data_dir: data_dir.to_string(),
+            generation: Self::default_generation_params("pragmatic").merged_with(overrides),
+            persistence: Default::default(),
+            tool_policy: Default::default(),
         };
 
         let mut tools = HashMap::new();
-        tools.insert("calc".to_string(), Box::new(MathTool) as Box<dyn AiTool>);
+        tools.insert("calc".to_string(), Box::new(MathTool::default()) as Box<dyn AiTool>);
         tools.insert(
             "search".to_string(),
-            Box::new(DDGSearchTool) as Box<dyn AiTool>,
+            Box::new(DDGSearchTool::default()) as Box<dyn AiTool>,
         );
 
         Ok(Box::new(PersonalityAgent::new(config, tools)?))
@@ -272,37 +372,222 @@ impl PersonalityAgentBuilder {
                 "Strategic organizer and delegator",
             ),
             ("pragmatic", "Practical", "Efficient and solution-focused"),
+            (
+                "critic",
+                "Sage",
+                "Reviewer that finds flaws, gaps and missed edge cases",
+            ),
         ]
     }
 
+    /// Resolve a typed personality id or name, tolerating prefixes and typos
+    ///
+    /// Exact ids and names win, then prefixes, then substrings, then the
+    /// closest names by edit distance.
+    pub fn resolve_personality(input: &str) -> PersonalityMatch {
+        let input = input.trim().to_lowercase();
+        if input.is_empty() {
+            return PersonalityMatch::NotFound;
+        }
+
+        let personalities = Self::list_personalities();
+        let matching = |matches: &dyn Fn(&str) -> bool| -> Vec<&'static str> {
+            personalities
+                .iter()
+                .filter(|(id, name, _)| matches(id) || matches(&name.to_lowercase()))
+                .map(|(id, _, _)| *id)
+                .collect()
+        };
+
+        let exact = matching(&|candidate| candidate == input);
+        if !exact.is_empty() {
+            return PersonalityMatch::from_candidates(exact);
+        }
+
+        let prefixed = matching(&|candidate| candidate.starts_with(&input));
+        if !prefixed.is_empty() {
+            return PersonalityMatch::from_candidates(prefixed);
+        }
+
+        // Very short inputs appear inside too many names to be useful
+        if input.chars().count() >= 3 {
+            let containing = matching(&|candidate| candidate.contains(&input));
+            if !containing.is_empty() {
+                return PersonalityMatch::from_candidates(containing);
+            }
+        }
+
+        // Allow roughly one typo per four characters
+        let max_distance = (input.chars().count() / 4).clamp(1, 3);
+        let distances: Vec<(&'static str, usize)> = personalities
+            .iter()
+            .map(|(id, name, _)| {
+                let distance = levenshtein(&input, id).min(levenshtein(&input, &name.to_lowercase()));
+                (*id, distance)
+            })
+            .filter(|(_, distance)| *distance <= max_distance)
+            .collect();
+
+        match distances.iter().map(|(_, distance)| *distance).min() {
+            Some(best) => PersonalityMatch::from_candidates(
+                distances
+                    .into_iter()
+                    .filter(|(_, distance)| *distance == best)
+                    .map(|(id, _)| id)
+                    .collect(),
+            ),
+            None => PersonalityMatch::NotFound,
+        }
+    }
+
+    /// Default sampling parameters for a personality
+    ///
+    /// Creative work runs hotter, precise work cooler. Unknown ids get the
+    /// provider's defaults.
+    pub fn default_generation_params(personality: &str) -> GenerationParams {
+        let temperature = match personality {
+            "researcher" => 0.4,
+            "calculator" => 0.1,
+            "creative" => 0.9,
+            "coordinator" => 0.5,
+            "pragmatic" => 0.3,
+            "critic" => 0.2,
+            _ => return GenerationParams::default(),
+        };
+        GenerationParams::default().with_temperature(temperature)
+    }
+
     /// Create an agent by personality type
+    ///
+    /// Close matches such as typos are accepted when they point to a single
+    /// personality; ambiguous input is rejected with the candidates.
     pub fn create_by_type(
         personality: &str,
         data_dir: &str,
         provider: &str,
     ) -> Result<Box<dyn Agent>, Error> {
-        match personality.to_lowercase().as_str() {
-            "researcher" => Self::create_researcher(data_dir, provider),
-            "calculator" => Self::create_calculator(data_dir, provider),
-            "creative" => Self::create_creative(data_dir, provider),
-            "coordinator" => Self::create_coordinator(data_dir, provider),
-            "pragmatic" => Self::create_pragmatic(data_dir, provider),
-            _ => Err(anyhow!(
-                "Unknown personality type: {}. Available: researcher, calculator, creative, coordinator, pragmatic",
-                personality
-            )),
+        Self::create_by_type_with_overrides(
+            personality,
+            data_dir,
+            provider,
+            &GenerationParams::default(),
+        )
+    }
+
+    /// Create an agent by personality type with the provider chosen for it
+    ///
+    /// The provider is looked up by the resolved personality id, so a typo
+    /// in `personality` still gets that personality's provider.
+    pub fn create_by_type_with_provider(
+        personality: &str,
+        data_dir: &str,
+        providers: &AgentProviders,
+    ) -> Result<Box<dyn Agent>, Error> {
+        let resolved = match Self::resolve_personality(personality) {
+            PersonalityMatch::Found(id) => id,
+            // Let create_by_type report the failed match
+            _ => personality,
+        };
+        Self::create_by_type(personality, data_dir, providers.provider_for(resolved))
+    }
+
+    /// Create an agent by personality type, replacing any preset sampling
+    /// parameters that are set in `overrides`
+    pub fn create_by_type_with_overrides(
+        personality: &str,
+        data_dir: &str,
+        provider: &str,
+        overrides: &GenerationParams,
+    ) -> Result<Box<dyn Agent>, Error> {
+        let resolved = match Self::resolve_personality(personality) {
+            PersonalityMatch::Found(id) => id,
+            PersonalityMatch::Ambiguous(candidates) => {
+                return Err(anyhow!(
+                    "Ambiguous personality type: {}. Did you mean: {}?",
+                    personality,
+                    candidates.join(", ")
+                ));
+            }
+            PersonalityMatch::NotFound => {
+                return Err(anyhow!(
+                    "Unknown personality type: {}. Available: researcher, calculator, creative, coordinator, pragmatic, critic",
+                    personality
+                ));
+            }
+        };
+
+        if !resolved.eq_ignore_ascii_case(personality.trim()) {
+            info!("Resolved personality '{}' to '{}'", personality, resolved);
+        }
+
+        match resolved {
+            "researcher" => Self::create_researcher(data_dir, provider, overrides),
+            "calculator" => Self::create_calculator(data_dir, provider, overrides),
+            "creative" => Self::create_creative(data_dir, provider, overrides),
+            "coordinator" => Self::create_coordinator(data_dir, provider, overrides),
+            "pragmatic" => Self::create_pragmatic(data_dir, provider, overrides),
+            "critic" => Self::create_critic(data_dir, provider, overrides),
+            _ => unreachable!("resolve_personality only returns listed personalities"),
+        }
+    }
+}
+
+/// Outcome of [`PersonalityAgentBuilder::resolve_personality`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PersonalityMatch {
+    /// A single personality id matched
+    Found(&'static str),
+    /// Several personality ids matched equally well
+    Ambiguous(Vec<&'static str>),
+    /// Nothing was close enough
+    NotFound,
+}
+
+impl PersonalityMatch {
+    fn from_candidates(mut candidates: Vec<&'static str>) -> Self {
+        candidates.dedup();
+        match candidates.len() {
+            0 => PersonalityMatch::NotFound,
+            1 => PersonalityMatch::Found(candidates[0]),
+            _ => PersonalityMatch::Ambiguous(candidates),
+        }
+    }
+}
+
+/// Edit distance between two strings, counted in characters
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
         }
+        previous = current;
     }
+
+    previous[b.len()]
 }
 
 /// A personality-based agent implementation
 pub struct PersonalityAgent {
     config: AgentConfig,
-    llm_service: LLMService,
-    _memory_manager: MemoryManager,
+    llm_service: Arc<LLMService>,
+    /// Stores finished turns in the agent's memory, as the config's policy asks
+    turn_recorder: Arc<TurnRecorder>,
     tools: HashMap<String, Box<dyn AiTool>>,
     /// Conversation history for this agent
     conversation_history: Vec<InternalChatMessage>,
+    /// Streams replies for `process_message_streaming`
+    stream_manager: Arc<ResponseStreamManager>,
+    /// Messages from streamed replies, not yet folded into the history
+    streamed_history: Arc<Mutex<Vec<InternalChatMessage>>>,
+    /// Registry shared memory handle, read by the memory tools
+    shared_memory: SharedMemorySlot,
+    /// System prompt and pinned context, edited by `modify_core_block`
+    core_blocks: Arc<RwLock<CoreBlockManager>>,
 }
 
 impl PersonalityAgent {
@@ -310,6 +595,21 @@ impl PersonalityAgent {
         config: AgentConfig,
         tools: HashMap<String, Box<dyn AiTool>>,
     ) -> Result<Self, Error> {
+        Self::new_with_shared_memory(config, tools, SharedMemorySlot::default())
+    }
+
+    /// Create an agent whose memory tools read `shared_memory`
+    ///
+    /// Pass the same slot to the memory tools in `tools`; the registry fills
+    /// it through [`Agent::set_shared_memory`].
+    pub fn new_with_shared_memory(
+        config: AgentConfig,
+        tools: HashMap<String, Box<dyn AiTool>>,
+        shared_memory: SharedMemorySlot,
+    ) -> Result<Self, Error> {
+        let core_blocks = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(config.core_blocks())
+        })?;
         // Create LLM service with agent's tools
         let tool_vec: Vec<Box<dyn AiTool>> = tools
             .values()
@@ -317,9 +617,9 @@ impl PersonalityAgent {
                 // Create a simple clone of the tool for the LLM service
                 // In a real implementation, you'd want better tool sharing
                 match tool.name() {
-                    "calc" => Box::new(MathTool) as Box<dyn AiTool>,
-                    "search" => Box::new(DDGSearchTool) as Box<dyn AiTool>,
-                    "website" => Box::new(WebsiteTool) as Box<dyn AiTool>,
+                    "calc" => Box::new(MathTool::default()) as Box<dyn AiTool>,
+                    "search" => Box::new(DDGSearchTool::default()) as Box<dyn AiTool>,
+                    "website" => Box::new(WebsiteTool::default()) as Box<dyn AiTool>,
                     "block" => {
                         // Create memory manager for this tool instance
                         let agent_data_dir =
@@ -330,6 +630,7 @@ impl PersonalityAgent {
                                 path: std::path::PathBuf::from(&agent_data_dir).join("memory.db"),
                                 namespace: "luts".to_string(),
                                 database: "memory".to_string(),
+                                encryption_key: None,
                             };
                             tokio::task::block_in_place(|| {
                                 tokio::runtime::Handle::current().block_on(async {
@@ -337,8 +638,27 @@ impl PersonalityAgent {
                                 })
                             })
                         };
-                        let memory_manager = std::sync::Arc::new(MemoryManager::new(memory_store));
-                        Box::new(BlockTool { memory_manager }) as Box<dyn AiTool>
+                        // New blocks wait in a review queue until the user approves them
+                        let review_queue = {
+                            let surreal_config = SurrealConfig::File {
+                                path: std::path::PathBuf::from(&agent_data_dir).join("proposals.db"),
+                                namespace: "luts".to_string(),
+                                database: "proposals".to_string(),
+                                encryption_key: None,
+                            };
+                            tokio::task::block_in_place(|| {
+                                tokio::runtime::Handle::current().block_on(async {
+                                    SurrealMemoryStore::new(surreal_config).await.unwrap()
+                                })
+                            })
+                        };
+                        let memory_manager = std::sync::Arc::new(
+                            MemoryManager::new(memory_store).with_review_queue(review_queue),
+                        );
+                        Box::new(BlockTool {
+                            memory_manager,
+                            shared_memory: shared_memory.clone(),
+                        }) as Box<dyn AiTool>
                     }
                     "retrieve_context" => {
                         let agent_data_dir =
@@ -349,6 +669,7 @@ impl PersonalityAgent {
                                 path: std::path::PathBuf::from(&agent_data_dir).join("memory.db"),
                                 namespace: "luts".to_string(),
                                 database: "memory".to_string(),
+                                encryption_key: None,
                             };
                             tokio::task::block_in_place(|| {
                                 tokio::runtime::Handle::current().block_on(async {
@@ -357,7 +678,10 @@ impl PersonalityAgent {
                             })
                         };
                         let memory_manager = std::sync::Arc::new(MemoryManager::new(memory_store));
-                        Box::new(RetrieveContextTool { memory_manager }) as Box<dyn AiTool>
+                        Box::new(RetrieveContextTool {
+                            memory_manager,
+                            shared_memory: shared_memory.clone(),
+                        }) as Box<dyn AiTool>
                     }
                     "update_block" => {
                         let agent_data_dir =
@@ -368,6 +692,7 @@ impl PersonalityAgent {
                                 path: std::path::PathBuf::from(&agent_data_dir).join("memory.db"),
                                 namespace: "luts".to_string(),
                                 database: "memory".to_string(),
+                                encryption_key: None,
                             };
                             tokio::task::block_in_place(|| {
                                 tokio::runtime::Handle::current().block_on(async {
@@ -387,6 +712,7 @@ impl PersonalityAgent {
                                 path: std::path::PathBuf::from(&agent_data_dir).join("memory.db"),
                                 namespace: "luts".to_string(),
                                 database: "memory".to_string(),
+                                encryption_key: None,
                             };
                             tokio::task::block_in_place(|| {
                                 tokio::runtime::Handle::current().block_on(async {
@@ -398,7 +724,7 @@ impl PersonalityAgent {
                         Box::new(DeleteBlockTool { memory_manager }) as Box<dyn AiTool>
                     }
                     "modify_core_block" => {
-                        Box::new(ModifyCoreBlockTool::new(config.agent_id.clone(), None))
+                        Box::new(ModifyCoreBlockTool::from_manager(core_blocks.clone()))
                             as Box<dyn AiTool>
                     }
                     "semantic_search" => {
@@ -411,6 +737,7 @@ impl PersonalityAgent {
                                 path: std::path::PathBuf::from(&agent_data_dir).join("memory.db"),
                                 namespace: "luts".to_string(),
                                 database: "memory".to_string(),
+                                encryption_key: None,
                             };
                             tokio::task::block_in_place(|| {
                                 tokio::runtime::Handle::current().block_on(async {
@@ -422,6 +749,26 @@ impl PersonalityAgent {
                         Box::new(SemanticSearchTool::new(memory_manager).unwrap())
                             as Box<dyn AiTool>
                     }
+                    "memory_stats" => {
+                        let agent_data_dir =
+                            format!("{}/agents/{}", config.data_dir, config.agent_id);
+                        std::fs::create_dir_all(&agent_data_dir).unwrap();
+                        let memory_store = {
+                            let surreal_config = SurrealConfig::File {
+                                path: std::path::PathBuf::from(&agent_data_dir).join("memory.db"),
+                                namespace: "luts".to_string(),
+                                database: "memory".to_string(),
+                                encryption_key: None,
+                            };
+                            tokio::task::block_in_place(|| {
+                                tokio::runtime::Handle::current().block_on(async {
+                                    SurrealMemoryStore::new(surreal_config).await.unwrap()
+                                })
+                            })
+                        };
+                        let memory_manager = std::sync::Arc::new(MemoryManager::new(memory_store));
+                        Box::new(MemoryStatsTool::new(memory_manager)) as Box<dyn AiTool>
+                    }
                     _ => Box::new(DummyTool {
                         name: tool.name().to_string(),
                     }) as Box<dyn AiTool>,
@@ -430,7 +777,8 @@ impl PersonalityAgent {
             .collect();
 
         let llm_service =
-            LLMService::new(config.system_prompt.as_deref(), tool_vec, &config.provider)?;
+            LLMService::new(config.system_prompt.as_deref(), tool_vec, &config.provider)?
+                .with_generation_params(config.generation.clone());
 
         // Create memory manager with agent-specific data directory
         let agent_data_dir = format!("{}/agents/{}", config.data_dir, config.agent_id);
@@ -439,21 +787,38 @@ impl PersonalityAgent {
             path: std::path::PathBuf::from(agent_data_dir).join("memory.db"),
             namespace: "luts".to_string(),
             database: "memory".to_string(),
+            encryption_key: None,
         };
         let memory_store = tokio::task::block_in_place(|| {
             tokio::runtime::Handle::current()
                 .block_on(async { SurrealMemoryStore::new(surreal_config).await })
         })?;
-        let memory_manager = MemoryManager::new(memory_store);
+        let memory_manager = Arc::new(MemoryManager::new(memory_store));
+        let turn_recorder = Arc::new(TurnRecorder::new(
+            config.persistence,
+            config.agent_id.clone(),
+            memory_manager,
+        ));
 
         Ok(PersonalityAgent {
             config,
-            llm_service,
-            _memory_manager: memory_manager,
+            llm_service: Arc::new(llm_service),
+            turn_recorder,
             tools,
             conversation_history: Vec::new(),
+            stream_manager: Arc::new(ResponseStreamManager::new()),
+            streamed_history: Arc::new(Mutex::new(Vec::new())),
+            shared_memory,
+            core_blocks,
         })
     }
+
+    /// Fold messages from finished streamed replies into the history
+    fn absorb_streamed_history(&mut self) {
+        if let Ok(mut streamed) = self.streamed_history.lock() {
+            self.conversation_history.append(&mut streamed);
+        }
+    }
 }
 
 #[async_trait]
@@ -470,6 +835,10 @@ impl Agent for PersonalityAgent {
         &self.config.role
     }
 
+    fn provider(&self) -> Option<&str> {
+        Some(&self.config.provider)
+    }
+
     async fn process_message(&mut self, message: AgentMessage) -> Result<MessageResponse, Error> {
         debug!(
             "Agent {} ({}) processing message from {}",
@@ -484,6 +853,10 @@ impl Agent for PersonalityAgent {
             self.tools.keys().collect::<Vec<_>>()
         );
 
+        self.absorb_streamed_history();
+        let turn = self.turn_recorder.begin(&message);
+        let tool_policy = self.config.tool_policy_for(&message);
+
         // Add the user message to conversation history
         self.conversation_history.push(InternalChatMessage::User {
             content: message.content.clone(),
@@ -515,7 +888,7 @@ impl Agent for PersonalityAgent {
             // Generate response using LLM service
             match self
                 .llm_service
-                .generate_response(&conversation_messages)
+                .generate_response(&conversation_messages, &GenerationParams::default())
                 .await
             {
                 Ok(response_content) => {
@@ -569,15 +942,18 @@ impl Agent for PersonalityAgent {
                                 // Find and execute the tool
                                 let tool_result = if let Some(tool) = self.tools.get(tool_name) {
                                     debug!("Found tool '{}', executing...", tool_name);
-                                    match tool.execute(tool_args.clone()).await {
-                                        Ok(result) => {
+                                    // Refused tools aren't run; run() times out slow ones
+                                    let outcome =
+                                        tool_policy.run(tool.as_ref(), tool_args.clone()).await;
+                                    match (outcome.result, outcome.error) {
+                                        (result, None) => {
                                             info!(
                                                 "Tool {} completed successfully: {:?}",
                                                 tool_name, result
                                             );
-                                            result.to_string()
+                                            result.unwrap_or_default().to_string()
                                         }
-                                        Err(e) => {
+                                        (_, Some(e)) => {
                                             info!("Tool {} failed: {}", tool_name, e);
                                             format!("Error executing tool {}: {}", tool_name, e)
                                         }
@@ -652,6 +1028,9 @@ impl Agent for PersonalityAgent {
                                 tool_responses: None,
                             };
                             self.conversation_history.push(assistant_message);
+                            if let Some(turn) = turn {
+                                turn.complete(self.llm_service.clone(), &response_text).await;
+                            }
 
                             return Ok(MessageResponse::success(
                                 message.message_id,
@@ -683,6 +1062,10 @@ impl Agent for PersonalityAgent {
                                     tool_responses: None,
                                 };
                                 self.conversation_history.push(assistant_message);
+                                if let Some(turn) = turn {
+                                    turn.complete(self.llm_service.clone(), &combined_text)
+                                        .await;
+                                }
 
                                 return Ok(MessageResponse::success(
                                     message.message_id,
@@ -716,6 +1099,75 @@ impl Agent for PersonalityAgent {
         }
     }
 
+    async fn process_message_streaming(
+        &mut self,
+        message: AgentMessage,
+    ) -> Result<StreamableResponse, Error> {
+        debug!(
+            "Agent {} ({}) streaming reply to {}",
+            self.name(),
+            self.agent_id(),
+            message.from_agent_id
+        );
+        self.absorb_streamed_history();
+        let turn = self.turn_recorder.begin(&message);
+        let tool_policy = self.config.tool_policy_for(&message);
+        self.conversation_history.push(InternalChatMessage::User {
+            content: message.content,
+        });
+
+        // The reply, its tool calls and their results land in
+        // `streamed_history` and join the history on the next message
+        Ok(stream_agent_turns(
+            self.stream_manager.clone(),
+            self.llm_service.clone(),
+            message.message_id,
+            self.conversation_history.clone(),
+            self.streamed_history.clone(),
+            turn,
+            tool_policy,
+        ))
+    }
+
+    fn set_shared_memory(&mut self, shared_memory: Option<SharedMemory>) {
+        self.shared_memory.set(shared_memory);
+    }
+
+    fn core_blocks(&self) -> Option<Arc<RwLock<CoreBlockManager>>> {
+        Some(self.core_blocks.clone())
+    }
+
+    fn set_persistence_policy(&mut self, policy: ConversationPersistencePolicy) -> Result<(), Error> {
+        debug!("Agent {} persistence policy: {:?}", self.agent_id(), policy);
+        self.config.persistence = policy;
+        self.turn_recorder = Arc::new(self.turn_recorder.with_policy(policy));
+        Ok(())
+    }
+
+    fn get_history(&self) -> Vec<InternalChatMessage> {
+        let mut history = self.conversation_history.clone();
+        if let Ok(streamed) = self.streamed_history.lock() {
+            history.extend(streamed.iter().cloned());
+        }
+        history
+    }
+
+    fn set_history(&mut self, history: Vec<InternalChatMessage>) -> Result<(), Error> {
+        debug!("Agent {} loading {} history messages", self.agent_id(), history.len());
+        if let Ok(mut streamed) = self.streamed_history.lock() {
+            streamed.clear();
+        }
+        self.conversation_history = history;
+        Ok(())
+    }
+
+    fn add_context(&mut self, context: &str) -> Result<(), Error> {
+        debug!("Agent {} adding context", self.agent_id());
+        self.conversation_history
+            .push(InternalChatMessage::system_note(context));
+        Ok(())
+    }
+
     async fn send_message(&self, _message: AgentMessage) -> Result<(), Error> {
         // In CLI mode, agents don't need to send messages to each other
         // This would be implemented if running in a full multiagent environment
@@ -754,3 +1206,134 @@ impl AiTool for DummyTool {
         Ok(serde_json::json!({"result": "dummy"}))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_personality_handles_typos_and_prefixes() {
+        assert_eq!(
+            PersonalityAgentBuilder::resolve_personality("researcher"),
+            PersonalityMatch::Found("researcher")
+        );
+        assert_eq!(
+            PersonalityAgentBuilder::resolve_personality("Spark"),
+            PersonalityMatch::Found("creative")
+        );
+
+        // One-character typo
+        assert_eq!(
+            PersonalityAgentBuilder::resolve_personality("reseacher"),
+            PersonalityMatch::Found("researcher")
+        );
+
+        // Ambiguous prefix lists every candidate
+        assert_eq!(
+            PersonalityAgentBuilder::resolve_personality("c"),
+            PersonalityMatch::Ambiguous(vec!["calculator", "creative", "coordinator", "critic"])
+        );
+
+        assert_eq!(
+            PersonalityAgentBuilder::resolve_personality("xyzzy"),
+            PersonalityMatch::NotFound
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_creative_preset_is_hotter_than_pragmatic_unless_overridden() {
+        let data_dir = tempfile::TempDir::new().unwrap();
+        // Temperature the built agent's LLMService sends with a plain request
+        let temperature = |personality: &str, overrides: &GenerationParams| {
+            let agent = PersonalityAgentBuilder::create_by_type_with_overrides(
+                personality,
+                data_dir.path().join(personality).to_str().unwrap(),
+                "gemini-2.5-flash",
+                overrides,
+            )
+            .unwrap();
+            let agent = agent.as_any().downcast_ref::<PersonalityAgent>().unwrap();
+            agent
+                .llm_service
+                .generation_params()
+                .merged_with(&GenerationParams::default())
+                .chat_options()
+                .temperature
+                .unwrap()
+        };
+
+        let none = GenerationParams::default();
+        assert!(temperature("creative", &none) > temperature("pragmatic", &none));
+
+        // A user override replaces the preset
+        let hot = GenerationParams::default().with_temperature(1.2);
+        assert_eq!(temperature("pragmatic", &hot), 1.2);
+        assert!(temperature("pragmatic", &hot) > temperature("creative", &none));
+
+        // Overrides that don't touch temperature keep the preset
+        let capped = GenerationParams::default().with_max_tokens(256);
+        assert_eq!(temperature("creative", &capped), temperature("creative", &none));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_critic_personality_is_listed_and_creatable() {
+        let personalities = PersonalityAgentBuilder::list_personalities();
+        assert_eq!(personalities.len(), 6);
+        assert!(personalities.iter().any(|(id, _, _)| *id == "critic"));
+
+        let data_dir = tempfile::TempDir::new().unwrap();
+        let agent = PersonalityAgentBuilder::create_by_type(
+            "critic",
+            data_dir.path().to_str().unwrap(),
+            "gemini-2.5-flash",
+        )
+        .unwrap();
+        assert_eq!(agent.role(), "critic");
+        assert_eq!(agent.name(), "Sage");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_agents_use_the_provider_chosen_for_their_personality() {
+        let data_dir = tempfile::TempDir::new().unwrap();
+        let data_dir = data_dir.path().to_str().unwrap();
+        let providers = AgentProviders::new("gemini-2.5-pro")
+            .with_provider("calculator", "gemini-2.5-flash");
+
+        // "calc" resolves to the calculator, and so does its provider
+        let calculator =
+            PersonalityAgentBuilder::create_by_type_with_provider("calc", data_dir, &providers)
+                .unwrap();
+        let critic =
+            PersonalityAgentBuilder::create_by_type_with_provider("critic", data_dir, &providers)
+                .unwrap();
+        assert_eq!(calculator.role(), "calculator");
+        assert_eq!(calculator.provider(), Some("gemini-2.5-flash"));
+        assert_eq!(critic.provider(), Some("gemini-2.5-pro"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_context_note_is_sent_with_the_persona_prompt() {
+        let data_dir = tempfile::TempDir::new().unwrap();
+        let mut agent = PersonalityAgentBuilder::create_by_type(
+            "calculator",
+            data_dir.path().to_str().unwrap(),
+            "gemini-2.5-flash",
+        )
+        .unwrap();
+        agent.add_context("# TaskContext\n\nnotes.md").unwrap();
+
+        let agent = agent.as_any().downcast_ref::<PersonalityAgent>().unwrap();
+        let history = &agent.conversation_history;
+        assert!(history[0].is_system_note());
+        assert!(history[0].content().contains("notes.md"));
+        let prompt = agent.llm_service.system_prompt_for(history).unwrap();
+        assert!(prompt.contains("You are Logic"));
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("same", "same"), 0);
+    }
+}
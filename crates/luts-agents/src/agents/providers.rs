@@ -0,0 +1,94 @@
+//! Choosing the LLM provider for each personality
+//!
+//! Agents don't have to share a model: a cheap, fast one is enough for the
+//! calculator while the critic gets a strong one. [`AgentProviders`] maps
+//! personality ids to providers, with a default for the rest, and can be
+//! read from a JSON file such as `{"calculator": "gemini-2.5-flash"}`.
+
+use crate::agents::PersonalityAgentBuilder;
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// LLM provider for each personality, falling back to a default
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentProviders {
+    default: String,
+    by_personality: HashMap<String, String>,
+}
+
+impl AgentProviders {
+    /// Use `default` for every personality
+    pub fn new(default: impl Into<String>) -> Self {
+        Self {
+            default: default.into(),
+            by_personality: HashMap::new(),
+        }
+    }
+
+    /// Use `provider` for the personality with id `personality`
+    pub fn with_provider(
+        mut self,
+        personality: impl Into<String>,
+        provider: impl Into<String>,
+    ) -> Self {
+        self.by_personality
+            .insert(personality.into(), provider.into());
+        self
+    }
+
+    /// Add the personality-to-provider pairs of a JSON file
+    ///
+    /// Keys must be personality ids, so a typo is reported instead of
+    /// quietly leaving that agent on the default provider.
+    pub fn with_file(mut self, path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read provider file {:?}: {}", path, e))?;
+        let providers: HashMap<String, String> = serde_json::from_str(&content)
+            .map_err(|e| anyhow!("Invalid provider file {:?}: {}", path, e))?;
+
+        let personalities = PersonalityAgentBuilder::list_personalities();
+        for (personality, provider) in providers {
+            if !personalities.iter().any(|(id, _, _)| *id == personality) {
+                return Err(anyhow!(
+                    "Unknown personality {:?} in provider file {:?}",
+                    personality,
+                    path
+                ));
+            }
+            self.by_personality.insert(personality, provider);
+        }
+        Ok(self)
+    }
+
+    /// Provider for the personality with id `personality`
+    pub fn provider_for(&self, personality: &str) -> &str {
+        self.by_personality
+            .get(personality)
+            .unwrap_or(&self.default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_overrides_default_and_rejects_unknown_personalities() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("providers.json");
+        std::fs::write(&path, r#"{"calculator": "gemini-2.5-flash"}"#).unwrap();
+
+        let providers = AgentProviders::new("gemini-2.5-pro")
+            .with_file(&path)
+            .unwrap();
+        assert_eq!(providers.provider_for("calculator"), "gemini-2.5-flash");
+        assert_eq!(providers.provider_for("critic"), "gemini-2.5-pro");
+
+        std::fs::write(&path, r#"{"calculater": "gemini-2.5-flash"}"#).unwrap();
+        let err = AgentProviders::new("gemini-2.5-pro")
+            .with_file(&path)
+            .unwrap_err();
+        assert!(err.to_string().contains("calculater"));
+    }
+}
@@ -2,15 +2,60 @@
 
 use crate::agents::{Agent, AgentMessage, MessageResponse};
 use crate::agents::base_agent::{BaseAgent, MessageSender};
+use crate::agents::memory_scope::{MemoryScope, SharedMemory, SharedMemorySlot};
 use anyhow::{Error, anyhow};
 use async_trait::async_trait;
+use luts_memory::MemoryManager;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, Semaphore};
 use tracing::{debug, error};
 
+/// Builds a fresh agent instance for per-request registrations
+pub type AgentFactory = Arc<dyn Fn() -> Result<Box<dyn Agent>, Error> + Send + Sync>;
+
 /// Type alias for agent storage
-type AgentMap = Arc<RwLock<HashMap<String, Arc<RwLock<Box<dyn Agent>>>>>>;
+type AgentMap = Arc<RwLock<HashMap<String, AgentEntry>>>;
+
+/// How a registered agent handles incoming messages
+#[derive(Clone)]
+enum AgentEntry {
+    /// A single shared instance; messages are processed one at a time
+    Shared(Arc<RwLock<Box<dyn Agent>>>),
+    /// A fresh instance per message, built from a template
+    PerRequest(Arc<AgentTemplate>),
+}
+
+/// Template for agents that get a new instance for every message
+struct AgentTemplate {
+    agent_id: String,
+    name: String,
+    role: String,
+    factory: AgentFactory,
+    /// Limits how many instances of this agent run at once
+    permits: Arc<Semaphore>,
+    /// Shared memory handed to every new instance
+    shared_memory: SharedMemorySlot,
+}
+
+impl AgentEntry {
+    async fn process_message(&self, message: AgentMessage) -> Result<MessageResponse, Error> {
+        match self {
+            AgentEntry::Shared(agent) => agent.write().await.process_message(message).await,
+            AgentEntry::PerRequest(template) => {
+                let _permit = template
+                    .permits
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .map_err(|e| anyhow!("Agent {} is shutting down: {}", template.agent_id, e))?;
+                let mut agent = (template.factory)()?;
+                agent.set_shared_memory(template.shared_memory.get());
+                agent.process_message(message).await
+            }
+        }
+    }
+}
 
 /// Registry for managing multiple agents and routing messages between them
 pub struct AgentRegistry {
@@ -19,6 +64,12 @@ pub struct AgentRegistry {
     
     /// Message routing and delivery
     message_router: MessageRouter,
+
+    /// Common block space and the user ID its blocks are stored under
+    shared_memory: Option<(Arc<MemoryManager>, String)>,
+
+    /// Memory scope per agent ID; agents not listed are private
+    memory_scopes: RwLock<HashMap<String, MemoryScope>>,
 }
 
 /// Internal message router
@@ -37,7 +88,74 @@ impl AgentRegistry {
         AgentRegistry {
             agents,
             message_router,
+            shared_memory: None,
+            memory_scopes: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Give the registry a block space that agents can share
+    ///
+    /// Shared blocks are stored in `memory_manager` under `shared_user_id`.
+    /// Agents stay private until given a scope with [`Self::set_memory_scope`].
+    pub fn with_shared_memory(
+        mut self,
+        memory_manager: Arc<MemoryManager>,
+        shared_user_id: impl Into<String>,
+    ) -> Self {
+        self.shared_memory = Some((memory_manager, shared_user_id.into()));
+        self
+    }
+
+    /// Set how a registered agent takes part in the shared memory
+    ///
+    /// The agent gets the matching [`SharedMemory`] handle through
+    /// [`Agent::set_shared_memory`], so its memory tools honor the new scope.
+    pub async fn set_memory_scope(&self, agent_id: &str, scope: MemoryScope) -> Result<(), Error> {
+        let entry = self
+            .agents
+            .read()
+            .await
+            .get(agent_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("Agent {} not found", agent_id))?;
+        if scope != MemoryScope::Private && self.shared_memory.is_none() {
+            return Err(anyhow!(
+                "Cannot give agent {} a {:?} scope: the registry has no shared memory",
+                agent_id,
+                scope
+            ));
+        }
+        debug!("Memory scope for agent {}: {:?}", agent_id, scope);
+        self.memory_scopes.write().await.insert(agent_id.to_string(), scope);
+
+        let shared_memory = self.shared_memory_for(agent_id).await;
+        match entry {
+            AgentEntry::Shared(agent) => agent.write().await.set_shared_memory(shared_memory),
+            AgentEntry::PerRequest(template) => template.shared_memory.set(shared_memory),
+        }
+        Ok(())
+    }
+
+    /// Memory scope of an agent
+    pub async fn memory_scope(&self, agent_id: &str) -> MemoryScope {
+        self.memory_scopes
+            .read()
+            .await
+            .get(agent_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Handle onto the shared memory for an agent, or `None` if it is private
+    ///
+    /// Hand this to the agent's memory tools so they honor its scope.
+    pub async fn shared_memory_for(&self, agent_id: &str) -> Option<SharedMemory> {
+        let scope = self.memory_scope(agent_id).await;
+        if scope == MemoryScope::Private {
+            return None;
         }
+        let (memory_manager, user_id) = self.shared_memory.clone()?;
+        Some(SharedMemory::new(memory_manager, user_id, scope))
     }
     
     /// Register a new agent
@@ -56,7 +174,45 @@ impl AgentRegistry {
             return Err(anyhow!("Agent with ID {} already exists", agent_id));
         }
         
-        agents.insert(agent_id.clone(), Arc::new(RwLock::new(agent)));
+        agents.insert(agent_id.clone(), AgentEntry::Shared(Arc::new(RwLock::new(agent))));
+        debug!("Successfully registered agent: {}", agent_id);
+        Ok(())
+    }
+
+    /// Register an agent that gets a fresh instance from `factory` for every message
+    ///
+    /// Concurrent messages to the same agent ID run in parallel, up to
+    /// `max_concurrent` at a time, instead of queueing on one shared instance.
+    /// Instances don't keep conversation history between messages; state that
+    /// should persist belongs in the memory store the factory hands them.
+    pub async fn register_agent_factory(
+        &self,
+        factory: AgentFactory,
+        max_concurrent: usize,
+    ) -> Result<(), Error> {
+        if max_concurrent == 0 {
+            return Err(anyhow!("max_concurrent must be at least 1"));
+        }
+
+        // Build one instance up front to validate the factory and read the agent's identity
+        let prototype = factory()?;
+        let agent_id = prototype.agent_id().to_string();
+        debug!("Registering per-request agent: {} (max {} concurrent)", agent_id, max_concurrent);
+
+        let mut agents = self.agents.write().await;
+        if agents.contains_key(&agent_id) {
+            return Err(anyhow!("Agent with ID {} already exists", agent_id));
+        }
+
+        let template = AgentTemplate {
+            agent_id: agent_id.clone(),
+            name: prototype.name().to_string(),
+            role: prototype.role().to_string(),
+            factory,
+            permits: Arc::new(Semaphore::new(max_concurrent)),
+            shared_memory: SharedMemorySlot::default(),
+        };
+        agents.insert(agent_id.clone(), AgentEntry::PerRequest(Arc::new(template)));
         debug!("Successfully registered agent: {}", agent_id);
         Ok(())
     }
@@ -68,6 +224,7 @@ impl AgentRegistry {
         let mut agents = self.agents.write().await;
         agents.remove(agent_id)
             .ok_or_else(|| anyhow!("Agent {} not found", agent_id))?;
+        self.memory_scopes.write().await.remove(agent_id);
         
         debug!("Successfully unregistered agent: {}", agent_id);
         Ok(())
@@ -92,11 +249,14 @@ impl AgentRegistry {
     /// Get agent information
     pub async fn get_agent_info(&self, agent_id: &str) -> Option<(String, String, String)> {
         let agents = self.agents.read().await;
-        if let Some(agent) = agents.get(agent_id) {
-            let agent_guard = agent.read().await;
-            Some((agent_guard.agent_id().to_string(), agent_guard.name().to_string(), agent_guard.role().to_string()))
-        } else {
-            None
+        match agents.get(agent_id)? {
+            AgentEntry::Shared(agent) => {
+                let agent_guard = agent.read().await;
+                Some((agent_guard.agent_id().to_string(), agent_guard.name().to_string(), agent_guard.role().to_string()))
+            }
+            AgentEntry::PerRequest(template) => {
+                Some((template.agent_id.clone(), template.name.clone(), template.role.clone()))
+            }
         }
     }
     
@@ -125,7 +285,7 @@ impl MessageSender for MessageRouter {
         drop(agents); // Release the read lock early
         
         // Process the message asynchronously (fire and forget)
-        let result = target_agent.write().await.process_message(message).await;
+        let result = target_agent.process_message(message).await;
         match result {
             Ok(response) => {
                 debug!("Message processed successfully, response: {:?}", response);
@@ -148,8 +308,7 @@ impl MessageSender for MessageRouter {
         drop(agents); // Release the read lock early
         
         // Process the message and return the response
-        let result = target_agent.write().await.process_message(message).await;
-        result
+        target_agent.process_message(message).await
     }
 }
 
@@ -157,6 +316,10 @@ impl MessageSender for MessageRouter {
 mod tests {
     use super::*;
     use crate::agents::AgentMessage;
+    use crate::tools::{BlockTool, RetrieveContextTool};
+    use luts_llm::tools::AiTool;
+    use luts_memory::{BlockType, InMemoryMemoryStore, MemoryBlock, MemoryContent};
+    use serde_json::json;
     
     
     // Mock agent for testing
@@ -233,4 +396,225 @@ mod tests {
         assert!(response.success);
         assert!(response.content.contains("Echo from Echo Agent: Hello, agent!"));
     }
-}
\ No newline at end of file
+
+    /// Agent that waits at a shared barrier, so it only finishes if another
+    /// request is being processed at the same time
+    struct RendezvousAgent {
+        barrier: Arc<tokio::sync::Barrier>,
+    }
+
+    #[async_trait]
+    impl Agent for RendezvousAgent {
+        fn agent_id(&self) -> &str { "rendezvous" }
+        fn name(&self) -> &str { "Rendezvous" }
+        fn role(&self) -> &str { "test" }
+
+        async fn process_message(&mut self, message: AgentMessage) -> Result<MessageResponse, Error> {
+            self.barrier.wait().await;
+            Ok(MessageResponse::success(
+                message.message_id,
+                format!("Handled: {}", message.content),
+                None,
+            ))
+        }
+
+        async fn send_message(&self, _message: AgentMessage) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn get_available_tools(&self) -> Vec<String> {
+            vec![]
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_to_same_agent() {
+        let registry = Arc::new(AgentRegistry::new());
+        let barrier = Arc::new(tokio::sync::Barrier::new(2));
+
+        let factory: AgentFactory = Arc::new(move || {
+            Ok(Box::new(RendezvousAgent { barrier: barrier.clone() }) as Box<dyn Agent>)
+        });
+        registry.register_agent_factory(factory, 2).await.unwrap();
+
+        let info = registry.get_agent_info("rendezvous").await.unwrap();
+        assert_eq!(info.1, "Rendezvous");
+
+        let send = |content: &str| {
+            let registry = registry.clone();
+            let message = AgentMessage::new_chat(
+                "user".to_string(),
+                "rendezvous".to_string(),
+                content.to_string(),
+            );
+            async move { registry.send_message_and_wait(message).await }
+        };
+
+        // A serialized registry would deadlock here, with the first request
+        // waiting at the barrier for a second one that never starts
+        let (first, second) = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            async { tokio::join!(send("first"), send("second")) },
+        )
+        .await
+        .expect("requests to the same agent were serialized");
+
+        let first = first.unwrap();
+        let second = second.unwrap();
+        assert!(first.success && second.success);
+        assert_eq!(first.content, "Handled: first");
+        assert_eq!(second.content, "Handled: second");
+    }
+
+    /// Agent that answers with how many facts its retrieve tool finds: its
+    /// own, empty, store plus whatever the registry's shared memory allows
+    struct FactCountingAgent {
+        id: String,
+        retrieve: RetrieveContextTool,
+    }
+
+    impl FactCountingAgent {
+        fn new(id: &str) -> Self {
+            FactCountingAgent {
+                id: id.to_string(),
+                retrieve: RetrieveContextTool {
+                    memory_manager: Arc::new(MemoryManager::new(InMemoryMemoryStore::new())),
+                    shared_memory: SharedMemorySlot::default(),
+                },
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Agent for FactCountingAgent {
+        fn agent_id(&self) -> &str { &self.id }
+        fn name(&self) -> &str { "Fact Counter" }
+        fn role(&self) -> &str { "test" }
+
+        async fn process_message(&mut self, message: AgentMessage) -> Result<MessageResponse, Error> {
+            let result = self
+                .retrieve
+                .execute(json!({ "user_id": self.id, "block_types": ["Fact"] }))
+                .await?;
+            let count = result["blocks"].as_array().map_or(0, |blocks| blocks.len());
+            Ok(MessageResponse::success(message.message_id, count.to_string(), None))
+        }
+
+        async fn send_message(&self, _message: AgentMessage) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn get_available_tools(&self) -> Vec<String> {
+            vec![self.retrieve.name().to_string()]
+        }
+
+        fn set_shared_memory(&mut self, shared_memory: Option<SharedMemory>) {
+            self.retrieve.shared_memory.set(shared_memory);
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    /// Facts the coordinator agent finds when asked through the registry
+    async fn coordinator_fact_count(registry: &AgentRegistry) -> usize {
+        let message = AgentMessage::new_chat(
+            "user".to_string(),
+            "coordinator".to_string(),
+            "How many facts do you know?".to_string(),
+        );
+        let response = registry.send_message_and_wait(message).await.unwrap();
+        response.content.parse().unwrap()
+    }
+
+    /// Have the researcher's block tool write a fact to the shared space
+    async fn store_researcher_fact(registry: &AgentRegistry) {
+        let block = BlockTool {
+            memory_manager: Arc::new(MemoryManager::new(InMemoryMemoryStore::new())),
+            shared_memory: SharedMemorySlot::default(),
+        };
+        let params = json!({
+            "user_id": "researcher",
+            "block_type": "Fact",
+            "content": "Lisbon has seven hills",
+            "shared": true
+        });
+        assert!(block.execute(params.clone()).await.is_err());
+
+        block.shared_memory.set(registry.shared_memory_for("researcher").await);
+        let result = block.execute(params).await.unwrap();
+        assert_eq!(result["shared"], true);
+    }
+
+    #[tokio::test]
+    async fn test_shared_scope_lets_coordinator_see_researcher_facts() {
+        let shared_store = Arc::new(MemoryManager::new(InMemoryMemoryStore::new()));
+        let registry = AgentRegistry::new().with_shared_memory(shared_store, "team");
+        registry
+            .register_agent(Box::new(MockAgent {
+                id: "researcher".to_string(),
+                name: "Dr. Research".to_string(),
+                role: "researcher".to_string(),
+            }))
+            .await
+            .unwrap();
+        registry
+            .register_agent(Box::new(FactCountingAgent::new("coordinator")))
+            .await
+            .unwrap();
+        registry.set_memory_scope("researcher", MemoryScope::Shared).await.unwrap();
+        store_researcher_fact(&registry).await;
+
+        assert_eq!(registry.memory_scope("coordinator").await, MemoryScope::Private);
+        assert_eq!(coordinator_fact_count(&registry).await, 0);
+
+        registry.set_memory_scope("coordinator", MemoryScope::Shared).await.unwrap();
+        assert_eq!(coordinator_fact_count(&registry).await, 1);
+
+        // Read-only agents see the shared space but can't add to it
+        registry
+            .set_memory_scope("coordinator", MemoryScope::SharedReadOnly)
+            .await
+            .unwrap();
+        assert_eq!(coordinator_fact_count(&registry).await, 1);
+        let coordinator_memory = registry.shared_memory_for("coordinator").await.unwrap();
+        assert!(coordinator_memory
+            .store(MemoryBlock::new(
+                BlockType::Fact,
+                "coordinator",
+                MemoryContent::Text("Porto is north of Lisbon".to_string()),
+            ))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_per_request_agents_get_the_shared_memory_scope() {
+        let shared_store = Arc::new(MemoryManager::new(InMemoryMemoryStore::new()));
+        let registry = AgentRegistry::new().with_shared_memory(shared_store, "team");
+        registry
+            .register_agent(Box::new(MockAgent {
+                id: "researcher".to_string(),
+                name: "Dr. Research".to_string(),
+                role: "researcher".to_string(),
+            }))
+            .await
+            .unwrap();
+        let factory: AgentFactory =
+            Arc::new(|| Ok(Box::new(FactCountingAgent::new("coordinator")) as Box<dyn Agent>));
+        registry.register_agent_factory(factory, 1).await.unwrap();
+        registry.set_memory_scope("researcher", MemoryScope::Shared).await.unwrap();
+        store_researcher_fact(&registry).await;
+
+        assert_eq!(coordinator_fact_count(&registry).await, 0);
+        registry.set_memory_scope("coordinator", MemoryScope::SharedReadOnly).await.unwrap();
+        assert_eq!(coordinator_fact_count(&registry).await, 1);
+        registry.set_memory_scope("coordinator", MemoryScope::Private).await.unwrap();
+        assert_eq!(coordinator_fact_count(&registry).await, 0);
+    }
+}
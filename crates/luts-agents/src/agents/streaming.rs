@@ -0,0 +1,385 @@
+//! Streaming agent replies with tool execution
+//!
+//! [`ResponseStreamManager::stream_genai_response`] runs the whole loop of
+//! model turns and tool calls, so callers see tool calls and their results in
+//! the same stream as the answer. [`stream_agent_turns`] forwards that stream
+//! and rebuilds the messages it added to the conversation, for the agent to
+//! keep in its history.
+
+use crate::agents::persistence::PendingTurn;
+use futures::StreamExt;
+use luts_llm::streaming::{
+    ChunkMetadata, ChunkType, ResponseChunk, ResponseStreamManager, StreamableResponse,
+};
+use luts_llm::{AiService, GenerationParams, InternalChatMessage, ToolPolicy, ToolResult};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tracing::debug;
+
+/// Stream an agent's reply to `conversation`, executing tools between turns
+///
+/// Every message the reply adds to the conversation - tool requests, tool
+/// results and the final answer - is appended to `history` as it happens,
+/// for the agent to fold into its own history. A `turn` to persist is
+/// completed with the final answer. Tools run only if `tool_policy` allows
+/// them.
+pub(crate) fn stream_agent_turns(
+    stream_manager: Arc<ResponseStreamManager>,
+    ai_service: Arc<dyn AiService>,
+    session_id: String,
+    conversation: Vec<InternalChatMessage>,
+    history: Arc<Mutex<Vec<InternalChatMessage>>>,
+    mut turn: Option<PendingTurn>,
+    tool_policy: ToolPolicy,
+) -> StreamableResponse {
+    let (sender, receiver) = mpsc::channel(1000);
+    let response = StreamableResponse::from_receiver(session_id.clone(), receiver);
+
+    tokio::spawn(async move {
+        let mut reply = match stream_manager
+            .stream_genai_response_with_policy(
+                session_id.clone(),
+                ai_service.clone(),
+                conversation,
+                GenerationParams::default(),
+                tool_policy,
+            )
+            .await
+        {
+            Ok(reply) => reply,
+            Err(e) => {
+                let content = format!("Error: {}", e);
+                let chunk = final_chunk(&session_id, 0, ChunkType::Error, content);
+                let _ = sender.send(chunk).await;
+                return;
+            }
+        };
+
+        let mut sequence = 0u64;
+        let mut text = String::new();
+        let mut tool_messages = Vec::new();
+        let mut last_call_id = None;
+
+        while let Some(chunk) = reply.next().await {
+            match chunk.chunk_type {
+                // A new model turn: the previous one asked for tools
+                ChunkType::Status if !tool_messages.is_empty() => {
+                    let request = InternalChatMessage::Assistant {
+                        content: if text.is_empty() {
+                            "Tool calls requested".to_string()
+                        } else {
+                            std::mem::take(&mut text)
+                        },
+                        tool_responses: None,
+                    };
+                    record(
+                        &history,
+                        std::iter::once(request).chain(tool_messages.drain(..)),
+                    );
+                }
+                ChunkType::Text => text.push_str(&chunk.content),
+                ChunkType::ToolCall => {
+                    last_call_id = chunk
+                        .metadata
+                        .custom
+                        .get("call_id")
+                        .and_then(|id| id.as_str())
+                        .map(str::to_string);
+                }
+                ChunkType::ToolResponse if !chunk.is_final => {
+                    if let Some(result) = ToolResult::from_json(&chunk.content) {
+                        tool_messages.push(InternalChatMessage::Tool {
+                            tool_name: result.tool.clone(),
+                            content: result.display_text(),
+                            call_id: last_call_id.take(),
+                        });
+                    }
+                }
+                ChunkType::Complete => {
+                    let answer = std::mem::take(&mut text);
+                    complete_turn(turn.take(), &ai_service, &answer);
+                    record(&history, [InternalChatMessage::Assistant {
+                        content: answer,
+                        tool_responses: None,
+                    }]);
+                }
+                _ => {}
+            }
+
+            let is_final = chunk.is_final;
+            sequence = chunk.sequence + 1;
+            if sender.send(chunk).await.is_err() {
+                debug!("Receiver dropped, cancelling agent stream {}", session_id);
+                stream_manager.cancel_stream(&session_id).await;
+                return;
+            }
+            if is_final {
+                // Completion, errors and aborted tools end the reply
+                return;
+            }
+        }
+
+        // The provider's stream ended without saying so
+        complete_turn(turn.take(), &ai_service, &text);
+        record(&history, [InternalChatMessage::Assistant {
+            content: text,
+            tool_responses: None,
+        }]);
+        let _ = sender
+            .send(final_chunk(&session_id, sequence, ChunkType::Complete, String::new()))
+            .await;
+    });
+
+    response
+}
+
+/// Append `messages` to the history shared with the agent
+fn record(
+    history: &Mutex<Vec<InternalChatMessage>>,
+    messages: impl IntoIterator<Item = InternalChatMessage>,
+) {
+    if let Ok(mut history) = history.lock() {
+        history.extend(messages);
+    }
+}
+
+/// Persist the finished turn in the background, so the stream isn't held up
+fn complete_turn(turn: Option<PendingTurn>, ai_service: &Arc<dyn AiService>, answer: &str) {
+    if let Some(turn) = turn {
+        let ai_service = ai_service.clone();
+        let answer = answer.to_string();
+        tokio::spawn(async move { turn.complete(ai_service, &answer).await });
+    }
+}
+
+/// Last chunk of the reply streamed as `session_id`
+fn final_chunk(session_id: &str, sequence: u64, chunk_type: ChunkType, content: String) -> ResponseChunk {
+    ResponseChunk {
+        id: format!("{}_{}", session_id, sequence),
+        sequence,
+        content,
+        is_final: true,
+        timestamp: chrono::Utc::now(),
+        chunk_type,
+        metadata: ChunkMetadata {
+            token_count: None,
+            processing_time_ms: None,
+            model: None,
+            confidence: None,
+            custom: HashMap::new(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agents::{Agent, AgentMessage, MessageResponse, ToolCallInfo};
+    use anyhow::Error;
+    use async_trait::async_trait;
+    use luts_llm::{MockAiService, MockResponse, ToolErrorKind};
+
+    /// Agent that only implements `process_message`
+    struct OneShotAgent;
+
+    #[async_trait]
+    impl Agent for OneShotAgent {
+        fn agent_id(&self) -> &str {
+            "one_shot"
+        }
+
+        fn name(&self) -> &str {
+            "One Shot"
+        }
+
+        fn role(&self) -> &str {
+            "test"
+        }
+
+        async fn process_message(&mut self, message: AgentMessage) -> Result<MessageResponse, Error> {
+            let tool_call = ToolCallInfo {
+                tool_name: "calc".to_string(),
+                tool_args: serde_json::json!({ "expression": "2+2" }),
+                tool_result: serde_json::json!("4"),
+                tool_error: None,
+                success: true,
+                call_id: Some("call_1".to_string()),
+            };
+            Ok(MessageResponse::success_with_tools(
+                message.message_id,
+                "It's 4.".to_string(),
+                None,
+                vec![tool_call],
+            ))
+        }
+
+        async fn send_message(&self, _message: AgentMessage) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn get_available_tools(&self) -> Vec<String> {
+            Vec::new()
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    #[tokio::test]
+    async fn test_default_streaming_wraps_the_whole_response() {
+        let mut agent = OneShotAgent;
+        let message =
+            AgentMessage::new_chat("user".to_string(), "one_shot".to_string(), "2+2?".to_string());
+        let message_id = message.message_id.clone();
+
+        let stream = agent.process_message_streaming(message).await.unwrap();
+        let chunks: Vec<ResponseChunk> = stream.collect().await;
+        assert_eq!(chunks.len(), 1);
+        assert!(chunks[0].is_final);
+
+        let response = MessageResponse::from_chunks(message_id, &chunks);
+        assert!(response.success);
+        assert_eq!(response.content, "It's 4.");
+        assert_eq!(response.tool_calls.len(), 1);
+        assert_eq!(response.tool_calls[0].tool_result, "4");
+    }
+
+    #[test]
+    fn test_from_chunks_pairs_tool_calls_with_results() {
+        let mut call = final_chunk("s", 0, ChunkType::ToolCall, String::new());
+        call.metadata.custom.insert("tool_name".to_string(), serde_json::json!("calc"));
+        call.metadata
+            .custom
+            .insert("tool_args".to_string(), serde_json::json!({ "expression": "6*7" }));
+        call.metadata.custom.insert("call_id".to_string(), serde_json::json!("call_1"));
+        let result = final_chunk(
+            "s",
+            1,
+            ChunkType::ToolResponse,
+            ToolResult::success("calc", serde_json::json!(42)).to_json(),
+        );
+        let text = final_chunk("s", 2, ChunkType::Text, "The answer is 42".to_string());
+
+        let response = MessageResponse::from_chunks("m".to_string(), &[call, result, text]);
+        assert_eq!(response.content, "The answer is 42");
+        assert_eq!(response.tool_calls.len(), 1);
+        assert_eq!(response.tool_calls[0].tool_result, serde_json::json!(42));
+        assert_eq!(response.tool_calls[0].to_display_string(), "42");
+        assert!(response.tool_calls[0].success);
+        assert_eq!(response.tool_calls[0].call_id.as_deref(), Some("call_1"));
+    }
+
+    #[tokio::test]
+    async fn test_text_turn_ends_stream_and_records_answer() {
+        let history = Arc::new(Mutex::new(Vec::new()));
+        let stream = stream_agent_turns(
+            Arc::new(ResponseStreamManager::new()),
+            Arc::new(
+                MockAiService::new().with_response(MockResponse::Text("Hello there".to_string())),
+            ),
+            "session".to_string(),
+            vec![InternalChatMessage::User {
+                content: "Hi".to_string(),
+            }],
+            history.clone(),
+            None,
+            ToolPolicy::default(),
+        );
+        let chunks: Vec<ResponseChunk> = stream.collect().await;
+
+        let last = chunks.last().unwrap();
+        assert_eq!(last.chunk_type, ChunkType::Complete);
+        assert!(chunks.iter().enumerate().all(|(i, chunk)| chunk.sequence == i as u64));
+        assert_eq!(MessageResponse::from_chunks("m".to_string(), &chunks).content, "Hello there");
+
+        let history = history.lock().unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].content(), "Hello there");
+    }
+
+    #[tokio::test]
+    async fn test_tool_turns_are_recorded_before_the_answer() {
+        let service = MockAiService::new()
+            .with_tool(luts_tools::calc::MathTool::default())
+            .with_response(MockResponse::ToolCall {
+                name: "calculator".to_string(),
+                args: serde_json::json!({ "expression": "6 * 7" }),
+            })
+            .with_response(MockResponse::Text("It's 42.".to_string()));
+        let history = Arc::new(Mutex::new(Vec::new()));
+        let stream = stream_agent_turns(
+            Arc::new(ResponseStreamManager::new()),
+            Arc::new(service),
+            "session".to_string(),
+            vec![InternalChatMessage::User {
+                content: "6 * 7?".to_string(),
+            }],
+            history.clone(),
+            None,
+            ToolPolicy::default(),
+        );
+        let chunks: Vec<ResponseChunk> = stream.collect().await;
+
+        assert_eq!(chunks.last().unwrap().chunk_type, ChunkType::Complete);
+        let response = MessageResponse::from_chunks("m".to_string(), &chunks);
+        assert_eq!(response.content, "It's 42.");
+        assert_eq!(response.tool_calls.len(), 1);
+
+        let history = history.lock().unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0].content(), "Tool calls requested");
+        assert!(matches!(
+            &history[1],
+            InternalChatMessage::Tool { tool_name, call_id, .. }
+                if tool_name == "calculator" && call_id.as_deref() == Some("call_1")
+        ));
+        assert_eq!(history[2].content(), "It's 42.");
+    }
+
+    #[tokio::test]
+    async fn test_failed_tool_calls_carry_typed_errors() {
+        let service = MockAiService::new()
+            .with_tool(luts_tools::calc::MathTool::default())
+            .with_response(MockResponse::ToolCall {
+                name: "calculator".to_string(),
+                args: serde_json::json!({ "expr": "6 * 7" }),
+            })
+            .with_response(MockResponse::ToolCall {
+                name: "weather".to_string(),
+                args: serde_json::json!({ "city": "Lisbon" }),
+            })
+            .with_response(MockResponse::Text("Sorry, I couldn't.".to_string()));
+        let stream = stream_agent_turns(
+            Arc::new(ResponseStreamManager::new()),
+            Arc::new(service),
+            "session".to_string(),
+            vec![InternalChatMessage::User {
+                content: "6 * 7, and the weather?".to_string(),
+            }],
+            Arc::new(Mutex::new(Vec::new())),
+            None,
+            ToolPolicy::default(),
+        );
+        let chunks: Vec<ResponseChunk> = stream.collect().await;
+        let response = MessageResponse::from_chunks("m".to_string(), &chunks);
+        assert_eq!(response.tool_calls.len(), 2);
+
+        let invalid = &response.tool_calls[0];
+        assert!(!invalid.success);
+        assert_eq!(invalid.tool_result, serde_json::Value::Null);
+        let error = invalid.tool_error.as_ref().unwrap();
+        assert_eq!(error.kind, ToolErrorKind::Validation);
+        assert!(error.message.contains("`expression`"));
+        assert_eq!(invalid.to_display_string(), error.message);
+
+        let missing = response.tool_calls[1].tool_error.as_ref().unwrap();
+        assert_eq!(missing.kind, ToolErrorKind::NotFound);
+        assert_eq!(missing.message, "Tool 'weather' not found");
+
+        // The typed error survives the trip through a final chunk
+        let replayed = MessageResponse::from_chunks("m".to_string(), &[response.to_chunk("s")]);
+        assert_eq!(replayed.tool_calls[1].tool_error.as_ref(), Some(missing));
+    }
+}
@@ -8,8 +8,9 @@ pub mod tools;
 
 // Re-export key types for convenience
 pub use agents::{
-    Agent, AgentConfig, AgentMessage, BaseAgent, MessageResponse, MessageSender, MessageType,
-    PersonalityAgent, PersonalityAgentBuilder, AgentRegistry, ToolCallInfo,
+    Agent, AgentConfig, AgentMessage, AgentProviders, BaseAgent, MemoryScope, MessageResponse, MessageSender, SharedMemory, SharedMemorySlot,
+    MessageType, PersonalityAgent, PersonalityAgentBuilder, PersonalityMatch, AgentFactory, AgentRegistry, ToolCallInfo,
+    ConversationPersistencePolicy,
 };
 pub use tools::{
     BlockTool, DeleteBlockTool, InteractiveToolTester, ModifyCoreBlockTool, 
@@ -3,9 +3,11 @@
 //! This tool provides agents with powerful semantic search capabilities over their memory,
 //! allowing them to find relevant context and information based on meaning rather than keywords.
 
+use crate::agents::{SharedMemory, SharedMemorySlot};
+use crate::agents::memory_scope::merge_results;
 use anyhow::{Result, anyhow};
 use async_trait::async_trait;
-use luts_llm::tools::AiTool;
+use luts_llm::tools::{AiTool, ToolSafety};
 use luts_memory::{BlockType, MemoryContent, MemoryManager, MemoryQuery};
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
@@ -16,6 +18,8 @@ use tracing::{debug, info, warn};
 pub struct AgentMemorySearchTool {
     pub memory_manager: Arc<MemoryManager>,
     pub user_id: String,
+    /// Registry shared space to search as well, while the agent's scope allows it
+    pub shared_memory: SharedMemorySlot,
 }
 
 impl AgentMemorySearchTool {
@@ -24,8 +28,21 @@ impl AgentMemorySearchTool {
         Self {
             memory_manager,
             user_id,
+            shared_memory: SharedMemorySlot::default(),
         }
     }
+
+    /// Also search the registry's shared space through `shared_memory`
+    pub fn with_shared_memory(mut self, shared_memory: SharedMemory) -> Self {
+        self.shared_memory = shared_memory.into();
+        self
+    }
+
+    /// Search whatever shared space the agent owning `slot` is given
+    pub fn with_shared_memory_slot(mut self, slot: SharedMemorySlot) -> Self {
+        self.shared_memory = slot;
+        self
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -117,6 +134,10 @@ impl AiTool for AgentMemorySearchTool {
          This tool understands meaning and context, not just keywords, making it excellent for finding related concepts and insights."
     }
 
+    fn safety(&self) -> ToolSafety {
+        ToolSafety::ReadOnly
+    }
+
     fn schema(&self) -> Value {
         json!({
             "type": "object",
@@ -204,11 +225,18 @@ impl AiTool for AgentMemorySearchTool {
             ..Default::default()
         };
 
-        let results = self
+        let mut results = self
             .memory_manager
             .search(&query)
             .await
             .map_err(|e| anyhow!("Memory search failed: {}", e))?;
+        if let Some(shared_memory) = self.shared_memory.get() {
+            let shared = shared_memory
+                .search(&query)
+                .await
+                .map_err(|e| anyhow!("Shared memory search failed: {}", e))?;
+            results = merge_results(results, shared, Some(max_results));
+        }
 
         let duration = start_time.elapsed();
 
@@ -336,6 +364,7 @@ mod tests {
         let config = SurrealConfig::Memory {
             namespace: "test".to_string(),
             database: "memory".to_string(),
+            encryption_key: None,
         };
 
         let store = SurrealMemoryStore::new(config).await.unwrap();
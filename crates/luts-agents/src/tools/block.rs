@@ -1,5 +1,6 @@
+use crate::agents::SharedMemorySlot;
 use luts_memory::{BlockType, MemoryBlockBuilder, MemoryContent, MemoryManager};
-use luts_llm::tools::AiTool;
+use luts_llm::tools::{AiTool, ToolSafety};
 use anyhow::{Error, Result, anyhow};
 use async_trait::async_trait;
 use serde_json::{Value, json};
@@ -7,8 +8,12 @@ use std::sync::Arc;
 use tracing::info;
 
 /// Tool for creating and storing a new memory block (fact, message, summary, etc.)
+///
+/// With `shared` set, the block goes to the registry's shared space instead,
+/// if the agent's scope lets it write there.
 pub struct BlockTool {
     pub memory_manager: Arc<MemoryManager>,
+    pub shared_memory: SharedMemorySlot,
 }
 
 #[async_trait]
@@ -21,6 +26,10 @@ impl AiTool for BlockTool {
         "Creates and stores a new memory block (e.g., fact, message, summary) for the user/session."
     }
 
+    fn safety(&self) -> ToolSafety {
+        ToolSafety::Mutating
+    }
+
     fn schema(&self) -> Value {
         json!({
             "type": "object",
@@ -37,6 +46,10 @@ impl AiTool for BlockTool {
                     "type": "array",
                     "items": { "type": "string" },
                     "description": "Optional tags to categorize the block"
+                },
+                "shared": {
+                    "type": "boolean",
+                    "description": "Store the block in the memory shared with other agents, if this agent may write to it"
                 }
             },
             "required": ["user_id", "block_type", "content"]
@@ -66,6 +79,10 @@ impl AiTool for BlockTool {
                     .collect::<Vec<_>>()
             })
             .unwrap_or_default();
+        let shared = params
+            .get("shared")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
 
         let block_type = match block_type {
             "Fact" => BlockType::Fact,
@@ -95,6 +112,33 @@ impl AiTool for BlockTool {
         info!("Done initing block");
 
         let block = builder.build()?;
+
+        if shared {
+            let shared_memory = self
+                .shared_memory
+                .get()
+                .ok_or_else(|| anyhow!("This agent has no shared memory to store blocks in"))?;
+            let block_id = shared_memory.store(block).await?;
+            return Ok(json!({
+                "success": true,
+                "shared": true,
+                "block_id": block_id.as_str(),
+                "message": format!("Created shared {} block with ID {}", block_type, block_id)
+            }));
+        }
+
+        // With a review queue, the user approves new blocks before they're committed
+        if self.memory_manager.has_review_queue() {
+            info!("Proposing block for review: {:?}", block);
+            let block_id = self.memory_manager.propose(block).await?;
+            return Ok(json!({
+                "success": true,
+                "pending_review": true,
+                "block_id": block_id.as_str(),
+                "message": format!("Proposed {} block {}; it will be saved once the user approves it", block_type, block_id)
+            }));
+        }
+
         info!("Done building block, Storing block: {:?}", block);
         let block_id = self.memory_manager.store(block).await?;
 
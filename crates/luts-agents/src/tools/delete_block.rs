@@ -1,4 +1,4 @@
-use luts_llm::tools::AiTool;
+use luts_llm::tools::{AiTool, ToolSafety};
 use luts_memory::{MemoryManager, BlockId};
 use anyhow::{Error, Result, anyhow};
 use async_trait::async_trait;
@@ -20,6 +20,10 @@ impl AiTool for DeleteBlockTool {
         "Deletes a memory block by its ID. Use with caution - this permanently removes the block from storage."
     }
 
+    fn safety(&self) -> ToolSafety {
+        ToolSafety::Destructive
+    }
+
     fn schema(&self) -> Value {
         json!({
             "type": "object",
@@ -2,10 +2,14 @@
 //!
 //! This module provides a direct interface to test agent tools without LLM intervention.
 //! Perfect for debugging SurrealDB operations and tool functionality.
+//!
+//! Tool calls can also be recorded with their expected output and replayed as a
+//! regression suite; see [`tool_suite`](super::tool_suite).
 
 use crate::tools::{
     block::BlockTool, delete_block::DeleteBlockTool, modify_core_block::ModifyCoreBlockTool,
     retrieve_context::RetrieveContextTool, update_block::UpdateBlockTool,
+    tool_suite::{TestOutcome, ToolSuite, ToolTestCase},
 };
 use anyhow::Result;
 use luts_llm::tools::AiTool;
@@ -14,8 +18,9 @@ use luts_tools::{
     calc::MathTool, search::DDGSearchTool, semantic_search::SemanticSearchTool,
     website::WebsiteTool,
 };
-use serde_json::json;
+use serde_json::{Value, json};
 use std::io::{self, Write};
+use std::path::Path;
 use std::sync::Arc;
 
 /// Interactive tool tester for direct tool invocation
@@ -32,6 +37,8 @@ pub struct InteractiveToolTester {
     calc_tool: MathTool,
     search_tool: DDGSearchTool,
     website_tool: WebsiteTool,
+    // Recorded regression cases
+    suite: ToolSuite,
 }
 
 impl InteractiveToolTester {
@@ -39,12 +46,14 @@ impl InteractiveToolTester {
     pub async fn new(memory_manager: Arc<MemoryManager>) -> Result<Self> {
         let retrieve_tool = RetrieveContextTool {
             memory_manager: memory_manager.clone(),
+            shared_memory: Default::default(),
         };
 
         let modify_tool = ModifyCoreBlockTool::new("test_user", None);
 
         let block_tool = BlockTool {
             memory_manager: memory_manager.clone(),
+            shared_memory: Default::default(),
         };
 
         let delete_tool = DeleteBlockTool {
@@ -66,12 +75,69 @@ impl InteractiveToolTester {
             delete_tool,
             update_tool,
             semantic_search_tool,
-            calc_tool: MathTool,
-            search_tool: DDGSearchTool,
-            website_tool: WebsiteTool,
+            calc_tool: MathTool::default(),
+            search_tool: DDGSearchTool::default(),
+            website_tool: WebsiteTool::default(),
+            suite: ToolSuite::default(),
         })
     }
 
+    /// The tool registered under `name`, if any
+    pub fn tool(&self, name: &str) -> Option<&dyn AiTool> {
+        let tools: [Option<&dyn AiTool>; 9] = [
+            Some(&self.retrieve_tool),
+            Some(&self.modify_tool),
+            Some(&self.block_tool),
+            Some(&self.delete_tool),
+            Some(&self.update_tool),
+            self.semantic_search_tool.as_ref().map(|tool| tool as &dyn AiTool),
+            Some(&self.calc_tool),
+            Some(&self.search_tool),
+            Some(&self.website_tool),
+        ];
+        tools.into_iter().flatten().find(|tool| tool.name() == name)
+    }
+
+    /// Record a tool call and the output it should produce
+    pub fn record_case(&mut self, tool_name: impl Into<String>, params: Value, expected: Value) {
+        self.suite.cases.push(ToolTestCase {
+            tool_name: tool_name.into(),
+            params,
+            expected,
+        });
+    }
+
+    /// Cases recorded or loaded so far
+    pub fn cases(&self) -> &[ToolTestCase] {
+        &self.suite.cases
+    }
+
+    /// Add the cases of a saved suite, returning how many were loaded
+    pub fn load_suite(&mut self, path: impl AsRef<Path>) -> Result<usize> {
+        let suite = ToolSuite::load(path)?;
+        let loaded = suite.cases.len();
+        self.suite.cases.extend(suite.cases);
+        Ok(loaded)
+    }
+
+    /// Save the recorded cases as JSON
+    pub fn save_suite(&self, path: impl AsRef<Path>) -> Result<()> {
+        self.suite.save(path)
+    }
+
+    /// Replay every recorded case in order and compare the outputs
+    pub async fn run_suite(&self) -> Vec<TestOutcome> {
+        let mut outcomes = Vec::with_capacity(self.suite.cases.len());
+        for case in &self.suite.cases {
+            let result = match self.tool(&case.tool_name) {
+                Some(tool) => tool.execute(case.params.clone()).await,
+                None => Err(anyhow::anyhow!("No tool named {}", case.tool_name)),
+            };
+            outcomes.push(TestOutcome::from_result(case.clone(), result));
+        }
+        outcomes
+    }
+
     /// Start the interactive testing session
     pub async fn run_interactive_session(&self) -> Result<()> {
         println!("🧪 Interactive Agent Tool Tester");
@@ -685,6 +751,7 @@ mod tests {
             path: db_path,
             namespace: "test".to_string(),
             database: "memory".to_string(),
+            encryption_key: None,
         };
 
         let store = SurrealMemoryStore::new(config).await.unwrap();
@@ -696,6 +763,35 @@ mod tests {
         (tester, temp_dir)
     }
 
+    #[tokio::test]
+    async fn test_suite_flags_changed_tool_output() {
+        let (mut tester, temp_dir) = create_test_tester().await;
+        tester.record_case("calculator", json!({"expression": "2 + 3"}), json!(5.0));
+        // Recorded when the calculator still got this wrong
+        tester.record_case("calculator", json!({"expression": "2 + 3 * 4"}), json!(20.0));
+        tester.record_case("no_such_tool", json!({}), json!(null));
+
+        // The suite survives a round trip through disk
+        let path = temp_dir.path().join("calc_suite.json");
+        tester.save_suite(&path).unwrap();
+        let (mut replay, _replay_dir) = create_test_tester().await;
+        assert_eq!(replay.load_suite(&path).unwrap(), 3);
+        assert_eq!(replay.cases(), tester.cases());
+
+        let outcomes = replay.run_suite().await;
+        assert!(outcomes[0].passed);
+        assert!(outcomes[0].diff.is_empty());
+
+        assert!(!outcomes[1].passed);
+        assert_eq!(outcomes[1].diff.len(), 1);
+        assert_eq!(outcomes[1].diff[0].path, "$");
+        assert_eq!(outcomes[1].diff[0].expected, Some(json!(20.0)));
+        assert_eq!(outcomes[1].diff[0].actual, Some(json!(14.0)));
+
+        assert!(!outcomes[2].passed);
+        assert!(outcomes[2].error.as_deref().unwrap().contains("No tool named no_such_tool"));
+    }
+
     #[tokio::test]
     async fn test_tool_creation() {
         let (tester, _temp_dir) = create_test_tester().await;
@@ -10,6 +10,7 @@ pub mod modify_core_block;
 pub mod retrieve_context;
 pub mod update_block;
 pub mod interactive_tester;
+pub mod tool_suite;
 
 // Re-export key tools for convenience
 pub use agent_memory_search::AgentMemorySearchTool;
@@ -18,4 +19,5 @@ pub use delete_block::DeleteBlockTool;
 pub use modify_core_block::ModifyCoreBlockTool;
 pub use retrieve_context::RetrieveContextTool;
 pub use update_block::UpdateBlockTool;
-pub use interactive_tester::InteractiveToolTester;
\ No newline at end of file
+pub use interactive_tester::InteractiveToolTester;
+pub use tool_suite::{JsonDifference, TestOutcome, ToolSuite, ToolTestCase};
\ No newline at end of file
@@ -6,7 +6,7 @@
 use anyhow::{Error, Result, anyhow};
 use async_trait::async_trait;
 use luts_core::context::core_blocks::{CoreBlockConfig, CoreBlockManager, CoreBlockType};
-use luts_llm::tools::AiTool;
+use luts_llm::tools::{AiTool, ToolSafety};
 use serde_json::{Value, json};
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -41,6 +41,10 @@ impl AiTool for ModifyCoreBlockTool {
         "Modifies core context blocks that are always present in the AI's context window. Use this to update your system prompt, user persona, current task context, key facts, preferences, goals, or working memory. Changes persist across conversations."
     }
 
+    fn safety(&self) -> ToolSafety {
+        ToolSafety::Mutating
+    }
+
     fn schema(&self) -> Value {
         json!({
             "type": "object",
@@ -106,7 +110,7 @@ impl AiTool for ModifyCoreBlockTool {
         let mut manager = self.core_block_manager.write().await;
 
         // Ensure core blocks are initialized
-        manager.initialize()?;
+        manager.initialize().await?;
 
         // Handle different operations
         let final_content = match operation {
@@ -144,7 +148,9 @@ impl AiTool for ModifyCoreBlockTool {
         };
 
         // Update the core block
-        manager.update_block(core_block_type, final_content.clone())?;
+        let budget_warning = manager
+            .update_block(core_block_type, final_content.clone())
+            .await?;
 
         // Get stats for response
         let stats = manager.get_stats();
@@ -165,7 +171,8 @@ impl AiTool for ModifyCoreBlockTool {
             "content_length": final_content.len(),
             "active_blocks": stats.active_blocks,
             "token_usage": stats.token_usage,
-            "budget_utilization": format!("{:.1}%", stats.budget_utilization)
+            "budget_utilization": format!("{:.1}%", stats.budget_utilization),
+            "budget_warning": budget_warning.map(|warning| warning.to_string())
         }))
     }
 }
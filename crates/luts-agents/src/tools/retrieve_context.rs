@@ -1,13 +1,19 @@
+use crate::agents::SharedMemorySlot;
+use crate::agents::memory_scope::merge_results;
 use luts_memory::{BlockType, MemoryManager, MemoryQuery};
-use luts_llm::tools::AiTool;
+use luts_llm::tools::{AiTool, ToolSafety};
 use anyhow::{Error, Result, anyhow};
 use async_trait::async_trait;
 use serde_json::{Value, json};
 use std::sync::Arc;
 
 /// Tool for retrieving relevant memory blocks from the MemoryManager.
+///
+/// While the agent holds a shared memory handle, blocks from the registry's
+/// shared space are returned after the agent's own.
 pub struct RetrieveContextTool {
     pub memory_manager: Arc<MemoryManager>,
+    pub shared_memory: SharedMemorySlot,
 }
 
 #[async_trait]
@@ -20,6 +26,10 @@ impl AiTool for RetrieveContextTool {
         "Fetches relevant memory blocks for the conversation, given user/session/content parameters."
     }
 
+    fn safety(&self) -> ToolSafety {
+        ToolSafety::ReadOnly
+    }
+
     fn schema(&self) -> Value {
         json!({
             "type": "object",
@@ -83,7 +93,11 @@ impl AiTool for RetrieveContextTool {
             ..Default::default()
         };
 
-        let blocks = self.memory_manager.search(&query).await?;
+        let mut blocks = self.memory_manager.search(&query).await?;
+        if let Some(shared_memory) = self.shared_memory.get() {
+            let shared = shared_memory.search(&query).await?;
+            blocks = merge_results(blocks, shared, limit);
+        }
         let blocks_json: Vec<Value> = blocks
             .iter()
             .map(|b| {
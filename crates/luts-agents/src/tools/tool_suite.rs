@@ -0,0 +1,154 @@
+//! Recorded tool invocations replayed as regression tests
+//!
+//! A [`ToolSuite`] is a list of tool calls with the output each one is
+//! expected to produce. [`InteractiveToolTester`](super::InteractiveToolTester)
+//! records cases and replays them against its tools; any difference between
+//! the expected and actual JSON is reported as a [`JsonDifference`]. Suites
+//! are plain JSON, so they can be checked into the repository.
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+
+/// One tool call and the output it should produce
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolTestCase {
+    pub tool_name: String,
+    pub params: Value,
+    pub expected: Value,
+}
+
+/// A list of recorded cases, as saved to disk
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToolSuite {
+    pub cases: Vec<ToolTestCase>,
+}
+
+impl ToolSuite {
+    /// Load a suite saved with [`ToolSuite::save`]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Cannot read tool suite {}: {}", path.display(), e))?;
+        serde_json::from_str(&json)
+            .map_err(|e| anyhow!("Invalid tool suite {}: {}", path.display(), e))
+    }
+
+    /// Save the suite as pretty-printed JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .map_err(|e| anyhow!("Cannot write tool suite {}: {}", path.display(), e))
+    }
+}
+
+/// A value that differs between the expected and actual output
+///
+/// `path` is a JSON path such as `$.results[0].title`. A missing side means
+/// the key or array element is absent there.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonDifference {
+    pub path: String,
+    pub expected: Option<Value>,
+    pub actual: Option<Value>,
+}
+
+/// Result of replaying one [`ToolTestCase`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestOutcome {
+    pub case: ToolTestCase,
+    pub passed: bool,
+    /// What the tool returned, if it succeeded
+    pub actual: Option<Value>,
+    /// Why the tool failed, if it did
+    pub error: Option<String>,
+    /// Differences from the expected output; empty when the case passed
+    pub diff: Vec<JsonDifference>,
+}
+
+impl TestOutcome {
+    pub(crate) fn from_result(case: ToolTestCase, result: Result<Value>) -> Self {
+        match result {
+            Ok(actual) => {
+                let diff = json_diff(&case.expected, &actual);
+                Self {
+                    passed: diff.is_empty(),
+                    case,
+                    actual: Some(actual),
+                    error: None,
+                    diff,
+                }
+            }
+            Err(e) => Self {
+                case,
+                passed: false,
+                actual: None,
+                error: Some(e.to_string()),
+                diff: Vec::new(),
+            },
+        }
+    }
+}
+
+/// Every place where `actual` differs from `expected`
+pub fn json_diff(expected: &Value, actual: &Value) -> Vec<JsonDifference> {
+    let mut differences = Vec::new();
+    diff_at("$".to_string(), Some(expected), Some(actual), &mut differences);
+    differences
+}
+
+fn diff_at(
+    path: String,
+    expected: Option<&Value>,
+    actual: Option<&Value>,
+    differences: &mut Vec<JsonDifference>,
+) {
+    match (expected, actual) {
+        (Some(Value::Object(expected)), Some(Value::Object(actual))) => {
+            let mut keys: Vec<&String> = expected.keys().chain(actual.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                diff_at(
+                    format!("{}.{}", path, key),
+                    expected.get(key),
+                    actual.get(key),
+                    differences,
+                );
+            }
+        }
+        (Some(Value::Array(expected)), Some(Value::Array(actual))) => {
+            for i in 0..expected.len().max(actual.len()) {
+                diff_at(format!("{}[{}]", path, i), expected.get(i), actual.get(i), differences);
+            }
+        }
+        (expected, actual) if expected != actual => differences.push(JsonDifference {
+            path,
+            expected: expected.cloned(),
+            actual: actual.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_json_diff_reports_paths() {
+        let expected = json!({"title": "Rust", "tags": ["a", "b"], "score": 1});
+        let actual = json!({"title": "Rust", "tags": ["a", "c", "d"], "extra": true, "score": 1});
+
+        let diff = json_diff(&expected, &actual);
+        let paths: Vec<&str> = diff.iter().map(|d| d.path.as_str()).collect();
+        assert_eq!(paths, vec!["$.extra", "$.tags[1]", "$.tags[2]"]);
+        assert_eq!(diff[0].expected, None);
+        assert_eq!(diff[1].expected, Some(json!("b")));
+        assert_eq!(diff[1].actual, Some(json!("c")));
+
+        assert!(json_diff(&expected, &expected).is_empty());
+    }
+}
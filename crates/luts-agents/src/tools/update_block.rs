@@ -1,4 +1,4 @@
-use luts_llm::tools::AiTool;
+use luts_llm::tools::{AiTool, ToolSafety};
 use luts_memory::{MemoryManager, MemoryContent, BlockId};
 use anyhow::{Error, Result, anyhow};
 use async_trait::async_trait;
@@ -20,6 +20,10 @@ impl AiTool for UpdateBlockTool {
         "Updates the content of an existing memory block by its ID. Useful for correcting or expanding stored information."
     }
 
+    fn safety(&self) -> ToolSafety {
+        ToolSafety::Mutating
+    }
+
     fn schema(&self) -> Value {
         json!({
             "type": "object",
@@ -0,0 +1,185 @@
+//! Administrative endpoints
+//!
+//! Every route here needs `Authorization: Bearer <admin_key>`, with the key
+//! taken from the config file. Without a configured key they're refused.
+
+use crate::api::idempotency::api_key;
+use crate::api::rate_limit::{RateLimiter, redact_key};
+use crate::config::{ConfigError, LiveConfig};
+use axum::{
+    Json, Router,
+    extract::{Request, State},
+    http::{HeaderMap, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use std::sync::Arc;
+use tracing::warn;
+
+/// Shared state for the admin endpoints
+pub struct AdminState {
+    pub config: Arc<LiveConfig>,
+    pub rate_limiter: Arc<RateLimiter>,
+}
+
+/// Re-read the config file and hand the new limits to the rate limiter
+///
+/// Shared by `POST /admin/reload` and the SIGHUP handler.
+pub fn reload(config: &LiveConfig, rate_limiter: &RateLimiter) -> Result<Vec<String>, ConfigError> {
+    let changes = config.reload()?;
+    rate_limiter.set_config(config.current().rate_limits);
+    Ok(changes)
+}
+
+/// Re-read the config file and apply hot-swappable changes
+///
+/// Responds with the applied changes. Nothing is applied on failure: 400 for
+/// an invalid file or a server started without `--config`, 409 when the file
+/// changes settings that need a restart, and 500 when it can't be read.
+pub async fn reload_config(
+    State(state): State<Arc<AdminState>>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    match reload(&state.config, &state.rate_limiter) {
+        Ok(changes) => Ok(Json(serde_json::json!({
+            "reloaded": true,
+            "changes": changes,
+        }))),
+        Err(e) => {
+            warn!("Config reload rejected: {}", e);
+            let status = match e {
+                ConfigError::NoConfigFile | ConfigError::Invalid(_) => StatusCode::BAD_REQUEST,
+                ConfigError::RestartRequired(_) => StatusCode::CONFLICT,
+                ConfigError::Io(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            Err((status, e.to_string()))
+        }
+    }
+}
+
+/// Current rate limit usage by API key
+///
+/// Keys are redacted to their last four characters.
+pub async fn rate_limit_usage(State(state): State<Arc<AdminState>>) -> Json<serde_json::Value> {
+    let usage: serde_json::Map<String, serde_json::Value> = state
+        .rate_limiter
+        .usage()
+        .into_iter()
+        .map(|(key, usage)| (redact_key(&key), serde_json::json!(usage)))
+        .collect();
+    let config = state.rate_limiter.config();
+    Json(serde_json::json!({
+        "defaults": {
+            "requests_per_minute": config.requests_per_minute,
+            "max_concurrent_streams": config.max_concurrent_streams,
+        },
+        "keys": usage,
+    }))
+}
+
+/// Turn away requests that don't carry the configured admin key
+async fn require_admin_key(
+    State(state): State<Arc<AdminState>>,
+    headers: HeaderMap,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(admin_key) = state.config.admin_key() else {
+        return (
+            StatusCode::FORBIDDEN,
+            "Admin endpoints are disabled; set admin_key in the config file",
+        )
+            .into_response();
+    };
+    if !keys_match(&api_key(&headers), &admin_key) {
+        return (StatusCode::UNAUTHORIZED, "Invalid admin key").into_response();
+    }
+    next.run(request).await
+}
+
+/// Compare keys without stopping at the first differing byte
+fn keys_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+pub fn admin_routes(state: AdminState) -> Router {
+    let state = Arc::new(state);
+    Router::new()
+        .route("/admin/reload", post(reload_config))
+        .route("/admin/rate-limits", get(rate_limit_usage))
+        .layer(middleware::from_fn_with_state(state.clone(), require_admin_key))
+        .with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::rate_limit::RateLimitConfig;
+    use axum::body::Body;
+    use std::path::Path;
+    use tower::ServiceExt;
+
+    fn write_config(path: &Path, config: serde_json::Value) {
+        std::fs::write(path, config.to_string()).unwrap();
+    }
+
+    async fn post_reload(state: &AdminState, key: Option<&str>) -> StatusCode {
+        let mut request = Request::post("/admin/reload");
+        if let Some(key) = key {
+            request = request.header("authorization", format!("Bearer {}", key));
+        }
+        admin_routes(AdminState {
+            config: state.config.clone(),
+            rate_limiter: state.rate_limiter.clone(),
+        })
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_reload_needs_the_admin_key_and_swaps_rate_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("luts-api.json");
+        write_config(&path, serde_json::json!({ "port": 3000 }));
+        let state = AdminState {
+            config: Arc::new(LiveConfig::load(Some(path.clone())).unwrap()),
+            rate_limiter: Arc::new(RateLimiter::new(RateLimitConfig::default())),
+        };
+
+        // No key configured yet
+        assert_eq!(post_reload(&state, Some("sk-admin")).await, StatusCode::FORBIDDEN);
+
+        write_config(
+            &path,
+            serde_json::json!({
+                "port": 3000,
+                "admin_key": "sk-admin",
+                "rate_limits": { "requests_per_minute": 5 }
+            }),
+        );
+        state.config.reload().unwrap();
+        assert_eq!(post_reload(&state, None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(post_reload(&state, Some("sk-wrong")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(post_reload(&state, Some("sk-admin")).await, StatusCode::OK);
+        assert_eq!(state.rate_limiter.config().requests_per_minute, 5);
+
+        std::fs::write(&path, "{ not json").unwrap();
+        assert_eq!(post_reload(&state, Some("sk-admin")).await, StatusCode::BAD_REQUEST);
+
+        write_config(&path, serde_json::json!({ "port": 4000, "admin_key": "sk-admin" }));
+        assert_eq!(post_reload(&state, Some("sk-admin")).await, StatusCode::CONFLICT);
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            post_reload(&state, Some("sk-admin")).await,
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+}
@@ -331,6 +331,28 @@ fn get_default_agents() -> Vec<AgentConfig> {
             created_at: None,
             updated_at: None,
         },
+        AgentConfig {
+            id: "critic".to_string(),
+            name: "Sage".to_string(),
+            role: "Reviewer that finds flaws, gaps and missed edge cases".to_string(),
+            description: "A constructive critic that reviews other agents' answers and plans, pointing out errors, logical gaps and missed edge cases with concrete fixes.".to_string(),
+            capabilities: vec![
+                "Error and logical gap detection".to_string(),
+                "Edge case and failure mode analysis".to_string(),
+                "Assumption checking".to_string(),
+                "Severity-ranked feedback".to_string(),
+                "Concrete fix suggestions".to_string(),
+                "Pure reasoning and review".to_string(),
+            ],
+            tools: vec![],
+            icon: Some("SearchCheck".to_string()),
+            color: "from-emerald-500 to-teal-500".to_string(),
+            custom: false,
+            system_prompt: None,
+            provider: None,
+            created_at: None,
+            updated_at: None,
+        },
     ]
 }
 
@@ -532,7 +554,7 @@ async fn seed_default_agents(db: &Surreal<Db>) -> Result<(), Box<dyn std::error:
     let existing_agents = load_custom_agents(db).await?;
     
     // Get default agent IDs
-    let default_agent_ids: Vec<&str> = vec!["researcher", "calculator", "creative", "coordinator", "pragmatic"];
+    let default_agent_ids: Vec<&str> = vec!["researcher", "calculator", "creative", "coordinator", "pragmatic", "critic"];
     
     // Check if all default agents exist
     let has_all_defaults = default_agent_ids.iter().all(|&id| {
@@ -644,12 +666,13 @@ mod tests {
     #[test]
     fn test_default_agents() {
         let agents = get_default_agents();
-        assert_eq!(agents.len(), 5);
+        assert_eq!(agents.len(), 6);
         assert!(agents.iter().all(|a| !a.custom));
         assert!(agents.iter().any(|a| a.id == "researcher"));
         assert!(agents.iter().any(|a| a.id == "calculator"));
         assert!(agents.iter().any(|a| a.id == "creative"));
         assert!(agents.iter().any(|a| a.id == "coordinator"));
         assert!(agents.iter().any(|a| a.id == "pragmatic"));
+        assert!(agents.iter().any(|a| a.id == "critic"));
     }
 }
\ No newline at end of file
@@ -1,28 +1,153 @@
+//! Memory block endpoints
+//!
+//! CRUD over the memory store, plus a filtered, paginated listing so
+//! dashboards can browse blocks without the TUI. Blocks are sent and
+//! returned as serialized [`MemoryBlock`]s; failures are `{"error": ...}`
+//! with a matching status code.
+
+use crate::api::error;
 use axum::{
     Router,
-    extract::{Json, Path, State},
+    extract::{Json, Path, Query, State},
+    http::StatusCode,
     routing::{get, post},
 };
+use chrono::{DateTime, Utc};
 use luts_framework::BlockUtils;
-use luts_framework::memory::{BlockId, MemoryBlock, MemoryQuery};
+use luts_framework::memory::{BlockId, BlockType, MemoryBlock, MemoryQuery, MemoryQueryPage};
+use serde::Deserialize;
 use serde_json::json;
 use std::sync::Arc;
 
+/// Largest page `GET /blocks` will return
+pub const MAX_PAGE_SIZE: usize = 500;
+
 #[derive(Clone)]
 pub struct ApiState {
     pub block_utils: Arc<BlockUtils>,
 }
 
+/// Error response: a status code and an `{"error": ...}` body
+pub type ApiError = (StatusCode, Json<serde_json::Value>);
+
+fn api_error(status: StatusCode, message: impl Into<String>) -> ApiError {
+    (status, Json(json!({ "error": message.into() })))
+}
+
+/// Map a storage failure to the status code for its kind
+fn storage_error(e: anyhow::Error) -> ApiError {
+    api_error(error::status_of(&e), e.to_string())
+}
+
+fn not_found(id: &BlockId) -> ApiError {
+    api_error(StatusCode::NOT_FOUND, format!("Block {} not found", id))
+}
+
+/// Query parameters of `GET /blocks`
+#[derive(Debug, Default, Deserialize)]
+pub struct ListBlocksParams {
+    pub user_id: Option<String>,
+    pub session_id: Option<String>,
+    /// Comma-separated block types, e.g. `fact,personal_info`
+    pub block_types: Option<String>,
+    pub content_contains: Option<String>,
+    /// RFC 3339 timestamp; only blocks created at or after it
+    pub created_after: Option<DateTime<Utc>>,
+    /// RFC 3339 timestamp; only blocks created at or before it
+    pub created_before: Option<DateTime<Utc>>,
+    /// Page size, 1 to [`MAX_PAGE_SIZE`]
+    pub limit: Option<usize>,
+    /// `next_cursor` from the previous page
+    pub cursor: Option<String>,
+}
+
+impl ListBlocksParams {
+    /// Validate the parameters and turn them into a memory query
+    fn into_query(self) -> Result<MemoryQuery, String> {
+        if self.limit.is_some_and(|limit| limit == 0 || limit > MAX_PAGE_SIZE) {
+            return Err(format!("limit must be between 1 and {}", MAX_PAGE_SIZE));
+        }
+        if self
+            .created_after
+            .zip(self.created_before)
+            .is_some_and(|(after, before)| after > before)
+        {
+            return Err("created_after must not be later than created_before".to_string());
+        }
+
+        let block_types = match &self.block_types {
+            Some(types) => types
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(parse_block_type)
+                .collect::<Result<Vec<_>, _>>()?,
+            None => Vec::new(),
+        };
+
+        Ok(MemoryQuery {
+            user_id: self.user_id,
+            session_id: self.session_id,
+            block_types,
+            content_contains: self.content_contains,
+            created_after: self.created_after,
+            created_before: self.created_before,
+            limit: self.limit,
+            cursor: self.cursor,
+            ..Default::default()
+        })
+    }
+}
+
+/// Parse a block type as displayed (`personal_info`, `custom_3`) or named (`PersonalInfo`)
+fn parse_block_type(name: &str) -> Result<BlockType, String> {
+    let normalized = name.to_lowercase().replace('_', "");
+    match normalized.as_str() {
+        "message" => Ok(BlockType::Message),
+        "summary" => Ok(BlockType::Summary),
+        "fact" => Ok(BlockType::Fact),
+        "preference" => Ok(BlockType::Preference),
+        "personalinfo" => Ok(BlockType::PersonalInfo),
+        "goal" => Ok(BlockType::Goal),
+        "task" => Ok(BlockType::Task),
+        _ => normalized
+            .strip_prefix("custom")
+            .and_then(|id| id.parse::<u8>().ok())
+            .map(BlockType::Custom)
+            .ok_or_else(|| format!("Unknown block type: {}", name)),
+    }
+}
+
+/// Handler to list blocks matching the query parameters, one page at a time.
+/// GET /blocks
+pub async fn list_blocks(
+    State(state): State<ApiState>,
+    Query(params): Query<ListBlocksParams>,
+) -> Result<Json<MemoryQueryPage>, ApiError> {
+    let query = params
+        .into_query()
+        .map_err(|e| api_error(StatusCode::BAD_REQUEST, e))?;
+    let page = state
+        .block_utils
+        .search_blocks_page(&query)
+        .await
+        .map_err(storage_error)?;
+    Ok(Json(page))
+}
+
 /// Handler to create a new memory block.
 /// POST /blocks
 pub async fn create_block(
     State(state): State<ApiState>,
-    Json(block): Json<MemoryBlock>,
-) -> Json<serde_json::Value> {
-    match state.block_utils.create_block(block).await {
-        Ok(id) => Json(json!({ "block_id": id })),
-        Err(e) => Json(json!({ "error": e.to_string() })),
-    }
+    Json(mut block): Json<MemoryBlock>,
+) -> Result<(StatusCode, Json<MemoryBlock>), ApiError> {
+    let id = state
+        .block_utils
+        .create_block(block.clone())
+        .await
+        .map_err(storage_error)?;
+    block.metadata.id = id;
+    Ok((StatusCode::CREATED, Json(block)))
 }
 
 /// Handler to get a memory block by ID.
@@ -30,12 +155,11 @@ pub async fn create_block(
 pub async fn get_block(
     State(state): State<ApiState>,
     Path(id): Path<String>,
-) -> Json<serde_json::Value> {
+) -> Result<Json<MemoryBlock>, ApiError> {
     let bid = BlockId::from(id);
-    match state.block_utils.get_block(&bid).await {
-        Ok(Some(block)) => Json(json!({ "block": block })),
-        Ok(None) => Json(json!({ "error": "Block not found" })),
-        Err(e) => Json(json!({ "error": e.to_string() })),
+    match state.block_utils.get_block(&bid).await.map_err(storage_error)? {
+        Some(block) => Ok(Json(block)),
+        None => Err(not_found(&bid)),
     }
 }
 
@@ -44,26 +168,41 @@ pub async fn get_block(
 pub async fn delete_block(
     State(state): State<ApiState>,
     Path(id): Path<String>,
-) -> Json<serde_json::Value> {
+) -> Result<StatusCode, ApiError> {
     let bid = BlockId::from(id);
-    match state.block_utils.delete_block(&bid).await {
-        Ok(_) => Json(json!({ "status": "deleted" })),
-        Err(e) => Json(json!({ "error": e.to_string() })),
+    if state.block_utils.delete_block(&bid).await.map_err(storage_error)? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(not_found(&bid))
     }
 }
 
-/// Handler to update a memory block by ID (delete + create).
+/// Handler to replace a memory block by ID.
 /// PUT /blocks/:id
+///
+/// The block keeps its ID and creation time; `updated_at` is set to now.
 pub async fn update_block(
     State(state): State<ApiState>,
     Path(id): Path<String>,
-    Json(new_block): Json<MemoryBlock>,
-) -> Json<serde_json::Value> {
+    Json(mut new_block): Json<MemoryBlock>,
+) -> Result<Json<MemoryBlock>, ApiError> {
     let bid = BlockId::from(id);
-    match state.block_utils.update_block(&bid, new_block).await {
-        Ok(new_id) => Json(json!({ "block_id": new_id })),
-        Err(e) => Json(json!({ "error": e.to_string() })),
-    }
+    let existing = state
+        .block_utils
+        .get_block(&bid)
+        .await
+        .map_err(storage_error)?
+        .ok_or_else(|| not_found(&bid))?;
+
+    new_block.metadata.id = bid.clone();
+    new_block.metadata.created_at = existing.created_at();
+    new_block.metadata.updated_at = Utc::now().timestamp_millis() as u64;
+    state
+        .block_utils
+        .update_block(&bid, new_block.clone())
+        .await
+        .map_err(storage_error)?;
+    Ok(Json(new_block))
 }
 
 /// Handler to search for memory blocks.
@@ -71,11 +210,13 @@ pub async fn update_block(
 pub async fn search_blocks(
     State(state): State<ApiState>,
     Json(query): Json<MemoryQuery>,
-) -> Json<serde_json::Value> {
-    match state.block_utils.search_blocks(&query).await {
-        Ok(blocks) => Json(json!({ "blocks": blocks })),
-        Err(e) => Json(json!({ "error": e.to_string() })),
-    }
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let blocks = state
+        .block_utils
+        .search_blocks(&query)
+        .await
+        .map_err(storage_error)?;
+    Ok(Json(json!({ "blocks": blocks })))
 }
 
 /// Handler to list all blocks for a user.
@@ -83,17 +224,19 @@ pub async fn search_blocks(
 pub async fn list_blocks_for_user(
     State(state): State<ApiState>,
     Path(user_id): Path<String>,
-) -> Json<serde_json::Value> {
-    match state.block_utils.list_blocks(&user_id).await {
-        Ok(blocks) => Json(json!({ "blocks": blocks })),
-        Err(e) => Json(json!({ "error": e.to_string() })),
-    }
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let blocks = state
+        .block_utils
+        .list_blocks(&user_id)
+        .await
+        .map_err(storage_error)?;
+    Ok(Json(json!({ "blocks": blocks })))
 }
 
 /// Register block management routes under /blocks
 pub fn block_routes(state: ApiState) -> Router {
     Router::new()
-        .route("/blocks", post(create_block))
+        .route("/blocks", get(list_blocks).post(create_block))
         .route("/blocks/search", post(search_blocks))
         .route(
             "/blocks/:id",
@@ -102,3 +245,95 @@ pub fn block_routes(state: ApiState) -> Router {
         .route("/blocks/user/:user_id", get(list_blocks_for_user))
         .with_state(state)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use http_body_util::BodyExt;
+    use luts_framework::memory::{InMemoryMemoryStore, MemoryContent, MemoryManager};
+    use serde_json::Value;
+    use tower::ServiceExt;
+
+    async fn seeded_router() -> Router {
+        let memory_manager = Arc::new(MemoryManager::new(InMemoryMemoryStore::new()));
+        for (user_id, block_type, text) in [
+            ("alice", BlockType::Fact, "Alice drinks green tea"),
+            ("alice", BlockType::Fact, "Alice lives in Lisbon"),
+            ("alice", BlockType::Preference, "Alice prefers tea to coffee"),
+            ("bob", BlockType::Fact, "Bob drinks black tea"),
+        ] {
+            memory_manager
+                .store(MemoryBlock::new(
+                    block_type,
+                    user_id,
+                    MemoryContent::Text(text.to_string()),
+                ))
+                .await
+                .unwrap();
+        }
+        block_routes(ApiState {
+            block_utils: Arc::new(BlockUtils::new(memory_manager)),
+        })
+    }
+
+    async fn get_json(router: Router, uri: &str) -> (StatusCode, Value) {
+        let response = router
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_list_applies_filters() {
+        let router = seeded_router().await;
+
+        let (status, page) = get_json(
+            router.clone(),
+            "/blocks?user_id=alice&block_types=fact&content_contains=tea&limit=10",
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let blocks = page["blocks"].as_array().unwrap();
+        assert_eq!(blocks.len(), 1);
+        let block: MemoryBlock = serde_json::from_value(blocks[0].clone()).unwrap();
+        assert_eq!(block.content().as_text(), Some("Alice drinks green tea"));
+        assert_eq!(page["next_cursor"], Value::Null);
+
+        let (status, page) =
+            get_json(router.clone(), "/blocks?user_id=alice&block_types=fact,preference&limit=2").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(page["blocks"].as_array().unwrap().len(), 2);
+        assert!(page["next_cursor"].is_string());
+
+        let (status, body) = get_json(router.clone(), "/blocks?limit=0").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body["error"].as_str().unwrap().contains("limit"));
+
+        let (status, _) = get_json(router, "/blocks?block_types=rumour").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_block_is_not_found() {
+        let router = seeded_router().await;
+
+        let (status, body) = get_json(router.clone(), "/blocks/no-such-block").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["error"], "Block no-such-block not found");
+
+        let response = router
+            .oneshot(
+                Request::delete("/blocks/no-such-block")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
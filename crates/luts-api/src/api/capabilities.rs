@@ -0,0 +1,143 @@
+//! Capability discovery endpoint
+//!
+//! Reports the models, tools and optional features this server supports so
+//! clients can adapt their UI instead of hard-coding assumptions.
+
+use axum::{Json, Router, extract::State, routing::get};
+use luts_framework::llm::conversation::ExportFormat;
+use luts_framework::llm::{LLMService, ProviderCapabilities};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// A tool the server's models can call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCapability {
+    pub name: String,
+    pub description: String,
+}
+
+/// Embedding support used for semantic search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingCapability {
+    pub dimensions: usize,
+}
+
+/// Optional features that depend on server configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureCapabilities {
+    pub streaming: bool,
+    pub semantic_search: bool,
+    pub embeddings: Option<EmbeddingCapability>,
+    pub export_formats: Vec<ExportFormat>,
+}
+
+/// Response body for `GET /v1/capabilities`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapabilitiesDocument {
+    pub version: String,
+    pub provider: ProviderCapabilities,
+    pub tools: Vec<ToolCapability>,
+    pub agents: Vec<String>,
+    pub features: FeatureCapabilities,
+}
+
+impl CapabilitiesDocument {
+    /// Assemble the document from the server's LLM service and configuration
+    pub fn new(
+        llm_service: &LLMService,
+        agents: Vec<String>,
+        embedding_dimensions: Option<usize>,
+    ) -> Self {
+        let provider = llm_service.capabilities();
+        let tools = llm_service
+            .tools
+            .iter()
+            .map(|tool| ToolCapability {
+                name: tool.name().to_string(),
+                description: tool.description().to_string(),
+            })
+            .collect();
+
+        CapabilitiesDocument {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            features: FeatureCapabilities {
+                // The endpoint streams unless the model is known not to
+                streaming: provider.streaming != Some(false),
+                semantic_search: embedding_dimensions.is_some(),
+                embeddings: embedding_dimensions.map(|dimensions| EmbeddingCapability { dimensions }),
+                export_formats: ExportFormat::ALL.to_vec(),
+            },
+            provider,
+            tools,
+            agents,
+        }
+    }
+}
+
+/// Shared state for the capabilities endpoint
+pub struct CapabilitiesState {
+    pub document: CapabilitiesDocument,
+}
+
+/// Handler for the capabilities endpoint
+/// GET /v1/capabilities
+pub async fn get_capabilities(
+    State(state): State<Arc<CapabilitiesState>>,
+) -> Json<CapabilitiesDocument> {
+    Json(state.document.clone())
+}
+
+/// Create router for the capabilities endpoint
+pub fn capabilities_routes(state: CapabilitiesState) -> Router {
+    Router::new()
+        .route("/v1/capabilities", get(get_capabilities))
+        .with_state(Arc::new(state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use luts_framework::llm::AiTool;
+    use luts_framework::tools::calc::MathTool;
+    use luts_framework::tools::search::DDGSearchTool;
+
+    #[tokio::test]
+    async fn test_capabilities_lists_tools_and_export_formats() {
+        let llm_service = LLMService::new(
+            None,
+            vec![Box::new(MathTool::default()), Box::new(DDGSearchTool::default())],
+            "test_provider",
+        )
+        .unwrap();
+        let document = CapabilitiesDocument::new(&llm_service, vec!["researcher".to_string()], None);
+        let state = Arc::new(CapabilitiesState { document });
+
+        let Json(document) = get_capabilities(State(state)).await;
+
+        let tool_names: Vec<&str> = document.tools.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(tool_names, vec![MathTool::default().name(), DDGSearchTool::default().name()]);
+        assert_eq!(document.provider.provider, "test_provider");
+        // Not in the model registry, so nothing is claimed about it
+        assert_eq!(document.provider.tool_calling, None);
+        assert_eq!(document.provider.max_context_tokens, None);
+        assert_eq!(document.agents, vec!["researcher".to_string()]);
+        assert!(!document.features.semantic_search);
+
+        let json = serde_json::to_value(&document).unwrap();
+        let formats = json["features"]["export_formats"].as_array().unwrap();
+        assert_eq!(formats.len(), ExportFormat::ALL.len());
+        assert!(formats.contains(&serde_json::json!("Markdown")));
+        assert!(formats.contains(&serde_json::json!("Jsonl")));
+    }
+
+    #[test]
+    fn test_provider_capabilities_come_from_the_model_registry() {
+        let llm_service = LLMService::new(None, Vec::new(), "DeepSeek-R1-0528").unwrap();
+        let document = CapabilitiesDocument::new(&llm_service, Vec::new(), None);
+
+        assert_eq!(document.provider.streaming, Some(true));
+        assert_eq!(document.provider.tool_calling, Some(true));
+        assert_eq!(document.provider.max_context_tokens, Some(131_072));
+        assert!(document.features.streaming);
+    }
+}
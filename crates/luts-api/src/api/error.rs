@@ -0,0 +1,45 @@
+//! HTTP status codes for LUTS errors
+
+use axum::http::StatusCode;
+use luts_framework::LutsError;
+
+/// Status code a handler should answer with when it fails with `error`
+pub fn status_code(error: &LutsError) -> StatusCode {
+    match error {
+        LutsError::NotFound(_) | LutsError::BlockNotFound(_) | LutsError::ProviderNotFound(_) => {
+            StatusCode::NOT_FOUND
+        }
+        LutsError::InvalidQuery(_) | LutsError::InvalidParams(_) | LutsError::Validation(_) => {
+            StatusCode::BAD_REQUEST
+        }
+        LutsError::BudgetExceeded { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+        LutsError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+        LutsError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+        LutsError::Backend(_) => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Status code for an `anyhow` error, from the `LutsError` it wraps if any
+pub fn status_of(error: &anyhow::Error) -> StatusCode {
+    error
+        .downcast_ref::<LutsError>()
+        .map(status_code)
+        .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_kinds_map_to_statuses() {
+        let not_found = anyhow::Error::from(LutsError::BlockNotFound("b1".to_string()));
+        assert_eq!(status_of(&not_found), StatusCode::NOT_FOUND);
+
+        let limited = anyhow::Error::from(LutsError::RateLimited { retry_after: None });
+        assert_eq!(status_of(&limited), StatusCode::TOO_MANY_REQUESTS);
+
+        assert_eq!(status_of(&anyhow::anyhow!("untyped")), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,213 @@
+//! Health, readiness and model listing endpoints
+//!
+//! `/healthz` only says the process is serving requests. `/readyz` checks the
+//! things a request actually needs - a reachable memory store and at least
+//! one registered agent - so a load balancer can hold traffic back until
+//! both are there. `/v1/models` lists the configured models in OpenAI's list
+//! format for client SDKs that probe it.
+
+use axum::{Json, Router, extract::State, http::StatusCode, routing::get};
+use luts_framework::agents::AgentRegistry;
+use luts_framework::memory::MemoryManager;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+/// User whose stats are fetched to check the store; it never owns any blocks
+const READINESS_USER: &str = "__luts_readiness__";
+
+/// Shared state for the health endpoints
+pub struct HealthState {
+    pub memory_manager: Arc<MemoryManager>,
+    pub agent_registry: Arc<AgentRegistry>,
+    /// Models requests can name, the server's provider first
+    pub models: Vec<String>,
+    /// Unix time the server started, reported as each model's creation time
+    pub started_at: i64,
+}
+
+/// One entry of `GET /v1/models`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelInfo {
+    pub id: String,
+    pub object: String,
+    pub created: i64,
+    pub owned_by: String,
+}
+
+/// Response body for `GET /v1/models`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelList {
+    pub object: String,
+    pub data: Vec<ModelInfo>,
+}
+
+/// Handler for the liveness endpoint
+/// GET /healthz
+pub async fn healthz() -> Json<Value> {
+    Json(json!({
+        "status": "ok",
+        "version": env!("CARGO_PKG_VERSION")
+    }))
+}
+
+/// Handler for the readiness endpoint
+/// GET /readyz
+pub async fn readyz(State(state): State<Arc<HealthState>>) -> (StatusCode, Json<Value>) {
+    let store = match state.memory_manager.get_stats(READINESS_USER).await {
+        Ok(_) => json!({ "ok": true }),
+        Err(e) => json!({ "ok": false, "error": e.to_string() }),
+    };
+    let agents = state.agent_registry.list_agents().await.len();
+
+    let ready = store["ok"] == true && agents > 0;
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    let body = json!({
+        "status": if ready { "ready" } else { "not_ready" },
+        "checks": {
+            "store": store,
+            "agents": { "ok": agents > 0, "registered": agents },
+        }
+    });
+    (status, Json(body))
+}
+
+/// Handler for the models endpoint
+/// GET /v1/models
+pub async fn list_models(State(state): State<Arc<HealthState>>) -> Json<ModelList> {
+    Json(ModelList {
+        object: "list".to_string(),
+        data: state
+            .models
+            .iter()
+            .map(|id| ModelInfo {
+                id: id.clone(),
+                object: "model".to_string(),
+                created: state.started_at,
+                owned_by: "luts".to_string(),
+            })
+            .collect(),
+    })
+}
+
+/// Create router for the health endpoints
+pub fn health_routes(state: HealthState) -> Router {
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .route("/v1/models", get(list_models))
+        .with_state(Arc::new(state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use axum::body::Body;
+    use axum::http::Request;
+    use http_body_util::BodyExt;
+    use luts_framework::agents::{Agent, AgentMessage, MessageResponse};
+    use luts_framework::memory::InMemoryMemoryStore;
+    use tower::ServiceExt;
+
+    /// Agent that is only ever registered, never asked anything
+    struct IdleAgent;
+
+    #[async_trait]
+    impl Agent for IdleAgent {
+        fn agent_id(&self) -> &str {
+            "idle"
+        }
+
+        fn name(&self) -> &str {
+            "Idle"
+        }
+
+        fn role(&self) -> &str {
+            "test"
+        }
+
+        async fn process_message(&mut self, message: AgentMessage) -> anyhow::Result<MessageResponse> {
+            Ok(MessageResponse::success(message.message_id, String::new(), None))
+        }
+
+        async fn send_message(&self, _message: AgentMessage) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn get_available_tools(&self) -> Vec<String> {
+            Vec::new()
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    fn health_state(agent_registry: Arc<AgentRegistry>) -> HealthState {
+        HealthState {
+            memory_manager: Arc::new(MemoryManager::new(InMemoryMemoryStore::new())),
+            agent_registry,
+            models: vec!["gemini-2.5-flash".to_string()],
+            started_at: 1_700_000_000,
+        }
+    }
+
+    async fn get_json(router: Router, uri: &str) -> (StatusCode, Value) {
+        let response = router
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_healthz_is_always_ok() {
+        let router = health_routes(health_state(Arc::new(AgentRegistry::new())));
+
+        let (status, body) = get_json(router, "/healthz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ok");
+    }
+
+    #[tokio::test]
+    async fn test_readyz_needs_an_agent() {
+        let agent_registry = Arc::new(AgentRegistry::new());
+
+        let (status, body) =
+            get_json(health_routes(health_state(agent_registry.clone())), "/readyz").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["checks"]["store"]["ok"], true);
+        assert_eq!(body["checks"]["agents"]["registered"], 0);
+
+        agent_registry.register_agent(Box::new(IdleAgent)).await.unwrap();
+        let (status, body) =
+            get_json(health_routes(health_state(agent_registry)), "/readyz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["status"], "ready");
+    }
+
+    #[tokio::test]
+    async fn test_models_use_openai_list_format() {
+        let router = health_routes(health_state(Arc::new(AgentRegistry::new())));
+
+        let (status, body) = get_json(router, "/v1/models").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["object"], "list");
+        assert_eq!(
+            body["data"],
+            json!([{
+                "id": "gemini-2.5-flash",
+                "object": "model",
+                "created": 1_700_000_000,
+                "owned_by": "luts"
+            }])
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Idempotency-Key support for retry-safe requests
+//!
+//! A client that retries a request with the same `Idempotency-Key` header gets
+//! the original response back instead of triggering a second generation.
+
+use axum::http::HeaderMap;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::OnceCell;
+use tracing::debug;
+
+/// Header clients use to mark retries of the same request
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Caches results by (API key, idempotency key) for a fixed window
+pub struct IdempotencyCache<T> {
+    window: Duration,
+    entries: Mutex<HashMap<(String, String), CacheEntry<T>>>,
+}
+
+struct CacheEntry<T> {
+    created_at: Instant,
+    result: Arc<OnceCell<T>>,
+}
+
+impl<T: Clone> IdempotencyCache<T> {
+    /// Create a cache that remembers results for `window`
+    pub fn new(window: Duration) -> Self {
+        IdempotencyCache {
+            window,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Return the cached result for this key, or run `generate` to produce it
+    ///
+    /// Concurrent calls with the same key wait for the first one instead of
+    /// generating again. Errors aren't cached, so a failed request can be retried.
+    pub async fn get_or_run<F, Fut, E>(
+        &self,
+        api_key: &str,
+        idempotency_key: &str,
+        generate: F,
+    ) -> Result<T, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let result = {
+            let mut entries = self.entries.lock().unwrap();
+            let now = Instant::now();
+            entries.retain(|_, entry| now.duration_since(entry.created_at) < self.window);
+
+            entries
+                .entry((api_key.to_string(), idempotency_key.to_string()))
+                .or_insert_with(|| CacheEntry {
+                    created_at: now,
+                    result: Arc::new(OnceCell::new()),
+                })
+                .result
+                .clone()
+        };
+
+        if result.initialized() {
+            debug!("Replaying cached response for idempotency key {}", idempotency_key);
+        }
+
+        result.get_or_try_init(generate).await.cloned()
+    }
+}
+
+/// Extract the idempotency key from request headers, if present
+pub fn idempotency_key(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(str::to_string)
+}
+
+/// Extract the caller's API key from the `Authorization: Bearer` header
+///
+/// Requests without one share an anonymous namespace.
+pub fn api_key(headers: &HeaderMap) -> String {
+    headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|key| key.trim().to_string())
+        .unwrap_or_else(|| "anonymous".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_same_key_generates_once() {
+        let cache = IdempotencyCache::new(Duration::from_secs(60));
+        let generations = AtomicUsize::new(0);
+        let (cache, generations) = (&cache, &generations);
+
+        let request = move || async move {
+            cache
+                .get_or_run("sk-test", "retry-1", move || async move {
+                    generations.fetch_add(1, Ordering::SeqCst);
+                    // Keep the first request in flight while the duplicate arrives
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    Ok::<_, String>(format!("completion #{}", generations.load(Ordering::SeqCst)))
+                })
+                .await
+        };
+
+        let (first, second) = tokio::join!(request(), request());
+        assert_eq!(generations.load(Ordering::SeqCst), 1);
+        assert_eq!(first.unwrap(), second.unwrap());
+
+        // A later retry is served from the cache too
+        let third = request().await.unwrap();
+        assert_eq!(third, "completion #1");
+        assert_eq!(generations.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_keys_are_scoped_and_errors_not_cached() {
+        let cache = IdempotencyCache::new(Duration::from_secs(60));
+
+        let a = cache.get_or_run("key-a", "same", || async { Ok::<_, String>(1) }).await;
+        let b = cache.get_or_run("key-b", "same", || async { Ok::<_, String>(2) }).await;
+        assert_eq!((a.unwrap(), b.unwrap()), (1, 2));
+
+        let failed = cache.get_or_run("key-a", "flaky", || async { Err::<i32, _>("boom".to_string()) }).await;
+        assert!(failed.is_err());
+        let retried = cache.get_or_run("key-a", "flaky", || async { Ok::<_, String>(3) }).await;
+        assert_eq!(retried.unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_entries_expire_after_window() {
+        let cache = IdempotencyCache::new(Duration::from_millis(10));
+
+        let first = cache.get_or_run("k", "expiring", || async { Ok::<_, String>(1) }).await;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let second = cache.get_or_run("k", "expiring", || async { Ok::<_, String>(2) }).await;
+        assert_eq!((first.unwrap(), second.unwrap()), (1, 2));
+    }
+
+    #[test]
+    fn test_header_parsing() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(idempotency_key(&headers), None);
+        assert_eq!(api_key(&headers), "anonymous");
+
+        headers.insert(IDEMPOTENCY_KEY_HEADER, " abc-123 ".parse().unwrap());
+        headers.insert(axum::http::header::AUTHORIZATION, "Bearer sk-live".parse().unwrap());
+        assert_eq!(idempotency_key(&headers), Some("abc-123".to_string()));
+        assert_eq!(api_key(&headers), "sk-live");
+    }
+}
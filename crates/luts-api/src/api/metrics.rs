@@ -0,0 +1,75 @@
+//! Prometheus metrics endpoint
+//!
+//! Built with the `metrics` feature, the server installs a Prometheus
+//! recorder at startup and `GET /metrics` renders everything recorded since
+//! in the text exposition format: LLM requests, errors and latency, tokens
+//! and tool calls. See `luts_framework::llm::telemetry` for the names.
+
+use axum::{Router, extract::State, http::header, response::IntoResponse, routing::get};
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+
+/// Install a Prometheus recorder as the process-wide metrics recorder
+///
+/// Metrics recorded before this are lost, so call it before building the
+/// services.
+pub fn install_recorder() -> anyhow::Result<PrometheusHandle> {
+    Ok(PrometheusBuilder::new().install_recorder()?)
+}
+
+/// Handler for the metrics endpoint
+/// GET /metrics
+pub async fn render_metrics(State(handle): State<PrometheusHandle>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        handle.render(),
+    )
+}
+
+/// Create router for the metrics endpoint
+pub fn metrics_routes(handle: PrometheusHandle) -> Router {
+    Router::new()
+        .route("/metrics", get(render_metrics))
+        .with_state(handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use http_body_util::BodyExt;
+    use luts_framework::llm::telemetry;
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_metrics_endpoint_renders_recorded_metrics() {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+        metrics::with_local_recorder(&recorder, || {
+            telemetry::record_tool_call("calculator", true);
+            telemetry::record_llm_request("test-model", "chat", Duration::from_millis(250), false);
+        });
+
+        let response = metrics_routes(handle)
+            .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+
+        let line = |name: &str| {
+            body.lines()
+                .find(|line| line.starts_with(&format!("{}{{", name)))
+                .unwrap_or_else(|| panic!("no {} in {}", name, body))
+                .to_string()
+        };
+        let tool_calls = line(telemetry::TOOL_CALLS);
+        assert!(tool_calls.contains("tool=\"calculator\""), "{}", tool_calls);
+        assert!(tool_calls.ends_with(" 1"), "{}", tool_calls);
+        let errors = line(telemetry::LLM_ERRORS);
+        assert!(errors.contains("provider=\"test-model\""), "{}", errors);
+        assert!(errors.ends_with(" 1"), "{}", errors);
+    }
+}
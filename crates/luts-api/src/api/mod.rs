@@ -1,3 +1,12 @@
+pub mod admin;
 pub mod agents;
 pub mod blocks;
+pub mod capabilities;
+pub mod error;
+pub mod health;
+pub mod idempotency;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod openai;
+pub mod rate_limit;
+pub mod stream_events;
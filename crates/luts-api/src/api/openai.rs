@@ -1,33 +1,48 @@
 use axum::{
     Json, Router,
     extract::State,
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::{IntoResponse, Sse},
     routing::{get, post},
 };
 use axum::response::sse::{Event, KeepAlive};
+use crate::api::error::status_of as error_status;
+use crate::api::idempotency::{self, IdempotencyCache};
+use crate::api::rate_limit::{RateLimiter, StreamPermit};
+use crate::config::LiveConfig;
 use chrono;
 use futures::Stream;
 use futures_util::StreamExt;
-use genai::chat;
 use luts_framework::agents::{AgentRegistry, AgentMessage, MessageType};
-use luts_framework::llm::{AiService, InternalChatMessage as ChatMessage, LLMService, ToolResponse};
+use luts_framework::llm::{
+    AiService, ChunkType, GenerationParams, InternalChatMessage as ChatMessage, ResponseChunk,
+    ResponseStreamManager, ToolResponse,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::convert::Infallible;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::Mutex;
+use tokio::sync::mpsc::UnboundedSender;
 use tracing::{debug, error, info};
 use uuid::Uuid;
 
 pub struct OpenAIState {
-    pub llm_service: LLMService,
+    pub llm_service: Arc<dyn AiService>,
+    /// Runs `stream: true` completions, so they can be cancelled on disconnect
+    pub stream_manager: Arc<ResponseStreamManager>,
     pub agent_registry: Arc<AgentRegistry>,
     pub _conversation_store: Arc<Mutex<HashMap<String, Vec<ChatMessage>>>>,
+    /// Completed non-streaming responses, replayed for retried `Idempotency-Key`s
+    pub idempotency: IdempotencyCache<ChatCompletionResponse>,
+    /// Reloadable settings such as pricing and the system prompt
+    pub config: Arc<LiveConfig>,
+    /// Per-key request rate and concurrent stream limits
+    pub rate_limiter: Arc<RateLimiter>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct OpenAIChatMessage {
     pub role: String,
     pub content: String,
@@ -36,13 +51,13 @@ pub struct OpenAIChatMessage {
     pub tool_call_id: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct OpenAIToolCall {
     pub id: String,
     pub function: OpenAIFunctionCall,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct OpenAIFunctionCall {
     pub name: String,
     pub arguments: String,
@@ -54,9 +69,42 @@ pub struct ChatCompletionRequest {
     pub messages: Vec<OpenAIChatMessage>,
     pub stream: Option<bool>,
     pub agent: Option<String>,
+    pub temperature: Option<f64>,
+    pub top_p: Option<f64>,
+    pub max_tokens: Option<u32>,
+    pub stop: Option<StopSequences>,
+    /// Not supported; requests that set it are rejected
+    pub seed: Option<u64>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+impl ChatCompletionRequest {
+    /// Sampling settings of this request, applied over the service's own
+    ///
+    /// Agents answer with their configured settings instead.
+    pub fn generation_params(&self) -> GenerationParams {
+        GenerationParams {
+            temperature: self.temperature,
+            top_p: self.top_p,
+            max_tokens: self.max_tokens,
+            stop: match &self.stop {
+                Some(StopSequences::One(stop)) => vec![stop.clone()],
+                Some(StopSequences::Many(stop)) => stop.clone(),
+                None => Vec::new(),
+            },
+            ..Default::default()
+        }
+    }
+}
+
+/// The `stop` field, which may be a single sequence or a list of them
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum StopSequences {
+    One(String),
+    Many(Vec<String>),
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ChatCompletionResponse {
     pub id: String,
     pub object: String,
@@ -66,18 +114,21 @@ pub struct ChatCompletionResponse {
     pub usage: Usage,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ChatCompletionChoice {
     pub index: u32,
     pub message: OpenAIChatMessage,
     pub finish_reason: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Usage {
     pub prompt_tokens: u32,
     pub completion_tokens: u32,
     pub total_tokens: u32,
+    /// Estimated cost in USD, when pricing is configured for the model
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_cost_usd: Option<f64>,
 }
 
 #[derive(Debug, Serialize)]
@@ -100,7 +151,18 @@ pub struct ChatCompletionChunkChoice {
 pub struct ChatCompletionDelta {
     pub role: Option<String>,
     pub content: Option<String>,
-    pub tool_calls: Option<Vec<OpenAIToolCall>>,
+    pub tool_calls: Option<Vec<OpenAIToolCallDelta>>,
+}
+
+/// A tool call in a streamed delta
+#[derive(Debug, Serialize)]
+pub struct OpenAIToolCallDelta {
+    /// Position of the call among the response's tool calls
+    pub index: u32,
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub function: OpenAIFunctionCall,
 }
 
 /// Convert OpenAI chat messages to LUTS format
@@ -151,13 +213,44 @@ pub fn openai_to_luts_messages(messages: &[OpenAIChatMessage]) -> Vec<ChatMessag
 /// Handler for the chat completions endpoint
 pub async fn chat_completions(
     State(state): State<Arc<OpenAIState>>,
+    headers: HeaderMap,
     Json(request): Json<ChatCompletionRequest>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
     info!("Chat completion request for model: {}", request.model);
     debug!("Request: {:?}", request);
 
+    // Providers aren't sent a seed yet, so accepting one would promise
+    // reproducible sampling that doesn't happen
+    if request.seed.is_some() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "The seed parameter is not supported".to_string(),
+        ));
+    }
+
+    // A stream keeps its slot until the task feeding it ends
+    let api_key = idempotency::api_key(&headers);
+    let stream_permit = if request.stream.unwrap_or(false) {
+        match state.rate_limiter.acquire_stream(&api_key) {
+            Ok(permit) => Some(permit),
+            Err(limited) => return Ok(limited.into_response()),
+        }
+    } else {
+        None
+    };
+    if let Err(limited) = state.rate_limiter.check_request(&api_key) {
+        return Ok(limited.into_response());
+    }
+
     // Convert OpenAI messages to LUTS format
-    let messages = openai_to_luts_messages(&request.messages);
+    let mut messages = openai_to_luts_messages(&request.messages);
+
+    // The configured system prompt applies when the client doesn't send one
+    if let Some(prompt) = state.config.system_prompt()
+        && !matches!(messages.first(), Some(ChatMessage::System { .. }))
+    {
+        messages.insert(0, ChatMessage::System { content: prompt });
+    }
 
     let completion_id = Uuid::new_v4().to_string();
     let now = std::time::SystemTime::now()
@@ -166,16 +259,17 @@ pub async fn chat_completions(
         .as_secs();
 
     // Check if streaming is requested
-    if request.stream.unwrap_or(false) {
+    if let Some(permit) = stream_permit {
         // Handle streaming response
-        let stream = create_streaming_response(
-            state,
-            messages,
+        let streaming = StreamingRequest {
             completion_id,
-            now,
-            request.model,
-            request.agent,
-        ).await.map_err(|e| {
+            created: now,
+            params: request.generation_params(),
+            model: request.model,
+            agent_name: request.agent,
+            permit,
+        };
+        let stream = create_streaming_response(state, messages, streaming).await.map_err(|e| {
             error!("Error creating stream: {}", e);
             (StatusCode::INTERNAL_SERVER_ERROR, format!("Error creating stream: {}", e))
         })?;
@@ -186,15 +280,31 @@ pub async fn chat_completions(
                 .text("keep-alive-text"))
             .into_response())
     } else {
-        // Handle non-streaming response  
-        let response = create_non_streaming_response(
-            state,
-            messages,
-            completion_id,
-            now,
-            request,
-        ).await?;
-        Ok(response.into_response())
+        // Handle non-streaming response, replaying the cached result for a retried key
+        let response = match idempotency::idempotency_key(&headers) {
+            Some(key) => {
+                state
+                    .idempotency
+                    .get_or_run(&api_key, &key, || async {
+                        create_non_streaming_response(
+                            state.clone(),
+                            messages,
+                            completion_id,
+                            now,
+                            request,
+                        )
+                        .await
+                        .map(|Json(response)| response)
+                    })
+                    .await?
+            }
+            None => {
+                create_non_streaming_response(state, messages, completion_id, now, request)
+                    .await?
+                    .0
+            }
+        };
+        Ok(Json(response).into_response())
     }
 }
 
@@ -229,17 +339,18 @@ async fn create_non_streaming_response(
             message_type: MessageType::Chat,
             correlation_id: None,
             timestamp: chrono::Utc::now().timestamp(),
+            tool_policy: Some(state.config.tool_policy()),
         };
         
         let response = state.agent_registry.send_message_and_wait(agent_message).await
             .map_err(|e| {
                 error!("Error processing message with agent: {}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, format!("Error processing message: {}", e))
+                (error_status(&e), format!("Error processing message: {}", e))
             })?;
         
         debug!("Non-streaming agent response received with {} tool calls", response.tool_calls.len());
         for (i, tool_call) in response.tool_calls.iter().enumerate() {
-            debug!("Tool call {}: {} -> {}", i, tool_call.tool_name, tool_call.tool_result);
+            debug!("Tool call {}: {} -> {}", i, tool_call.tool_name, tool_call.to_display_string());
         }
         
         // Convert tool calls to OpenAI format
@@ -260,12 +371,12 @@ async fn create_non_streaming_response(
         // Fallback to LLM service
         let res = state
             .llm_service
-            .generate_response(&messages)
+            .generate_response(&messages, &request.generation_params())
             .await
             .map_err(|e| {
                 error!("Error generating response: {}", e);
                 (
-                    StatusCode::INTERNAL_SERVER_ERROR,
+                    error_status(&e),
                     format!("Error generating response: {}", e),
                 )
             })?;
@@ -288,6 +399,7 @@ async fn create_non_streaming_response(
         .map(|m| m.content.len() as u32 / 4)
         .sum();
     let completion_tokens = response_text.len() as u32 / 4;
+    let estimated_cost_usd = state.config.cost(&request.model, prompt_tokens, completion_tokens);
 
     let api_response = ChatCompletionResponse {
         id: completion_id,
@@ -309,21 +421,39 @@ async fn create_non_streaming_response(
             prompt_tokens,
             completion_tokens,
             total_tokens: prompt_tokens + completion_tokens,
+            estimated_cost_usd,
         },
     };
 
     Ok(Json(api_response))
 }
 
-/// Create a streaming response
-async fn create_streaming_response(
-    state: Arc<OpenAIState>,
-    messages: Vec<ChatMessage>,
+/// The per-request values a streamed completion is answered with
+struct StreamingRequest {
     completion_id: String,
     created: u64,
     model: String,
     agent_name: Option<String>,
+    params: GenerationParams,
+    /// Concurrent stream slot, held until the stream ends
+    permit: StreamPermit,
+}
+
+/// Create a streaming response
+async fn create_streaming_response(
+    state: Arc<OpenAIState>,
+    messages: Vec<ChatMessage>,
+    request: StreamingRequest,
 ) -> Result<impl Stream<Item = Result<Event, Infallible>>, anyhow::Error> {
+    let StreamingRequest {
+        completion_id,
+        created,
+        model,
+        agent_name,
+        params,
+        permit,
+    } = request;
+
     // Use a channel to collect the stream items
     let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
     
@@ -333,14 +463,15 @@ async fn create_streaming_response(
     
     // Spawn a task to consume the stream and send to channel
     tokio::spawn(async move {
-        use futures_util::StreamExt;
-        
+        // Released when the stream completes or the client disconnects
+        let _permit = permit;
+
         // Use agent if specified, otherwise fallback to LLM service
-        let stream_result = if let Some(agent_name) = &agent_name {
+        if let Some(agent_name) = &agent_name {
             // Check if agent exists in registry
             if !state.agent_registry.has_agent(agent_name).await {
                 error!("Agent {} not found in registry", agent_name);
-                let _ = sender.send(Event::default().data(format!("{{\"error\":\"Agent '{}' not found\"}}", agent_name)));
+                let _ = sender.send(error_event(&format!("Agent '{}' not found", agent_name)));
                 return;
             }
             
@@ -359,15 +490,23 @@ async fn create_streaming_response(
                 message_type: MessageType::Chat,
                 correlation_id: None,
                 timestamp: chrono::Utc::now().timestamp(),
+                tool_policy: Some(state.config.tool_policy()),
             };
             
             // For now, agents don't support streaming, so we'll get the full response
             // and simulate streaming by sending it as chunks
-            match state.agent_registry.send_message_and_wait(agent_message).await {
+            let result = tokio::select! {
+                result = state.agent_registry.send_message_and_wait(agent_message) => result,
+                _ = sender.closed() => {
+                    info!("Client disconnected before agent {} answered", agent_name);
+                    return;
+                }
+            };
+            match result {
                 Ok(response) => {
                     debug!("Agent response received with {} tool calls", response.tool_calls.len());
                     for (i, tool_call) in response.tool_calls.iter().enumerate() {
-                        debug!("Tool call {}: {} -> {}", i, tool_call.tool_name, tool_call.tool_result);
+                        debug!("Tool call {}: {} -> {}", i, tool_call.tool_name, tool_call.to_display_string());
                     }
                     
                     // Send start event
@@ -407,10 +546,9 @@ async fn create_streaming_response(
                                         tool_call.tool_name,
                                         serde_json::to_string(&tool_call.tool_args)
                                             .unwrap_or_else(|_| "{}".to_string()),
-                                        if tool_call.success {
-                                            tool_call.tool_result.clone()
-                                        } else {
-                                            format!("❌ Error: {}", tool_call.tool_result)
+                                        match &tool_call.tool_error {
+                                            Some(error) => format!("❌ {}", error),
+                                            None => tool_call.to_display_string(),
                                         }
                                     )),
                                     tool_calls: None,
@@ -465,164 +603,24 @@ async fn create_streaming_response(
                     if let Ok(json_data) = serde_json::to_string(&end_chunk) {
                         let _ = sender.send(Event::default().data(json_data));
                     }
-                    
-                    return;
+                    let _ = sender.send(Event::default().data("[DONE]"));
                 }
                 Err(e) => {
                     error!("Error processing message with agent: {}", e);
-                    let _ = sender.send(Event::default().data(format!("{{\"error\":\"{}\"}}", e)));
-                    return;
+                    let _ = sender.send(error_event(&e.to_string()));
                 }
             }
         } else {
-            // Fallback to LLM service streaming
-            state
-                .llm_service
-                .generate_response_stream(&messages)
-                .await
-        };
-        
-        // If we're using LLM service, handle the streaming
-        if agent_name.is_none() {
-            let mut stream = match stream_result {
-                Ok(stream) => stream,
-                Err(e) => {
-                    error!("Error creating stream: {}", e);
-                    let _ = sender.send(Event::default().data(format!("{{\"error\":\"{}\"}}", e)));
-                    return;
-                }
-            };
-            
-            while let Some(chunk_result) = stream.next().await {
-                let event = match chunk_result {
-                    Ok(chunk) => {
-                        // Convert genai ChatStreamEvent to OpenAI format
-                        let chunk_data = match chunk {
-                            chat::ChatStreamEvent::Start => {
-                                ChatCompletionChunk {
-                                    id: completion_id_clone.clone(),
-                                    object: "chat.completion.chunk".to_string(),
-                                    created,
-                                    model: model_clone.clone(),
-                                    choices: vec![ChatCompletionChunkChoice {
-                                        index: 0,
-                                        delta: ChatCompletionDelta {
-                                            role: Some("assistant".to_string()),
-                                            content: None,
-                                            tool_calls: None,
-                                        },
-                                        finish_reason: None,
-                                    }],
-                                }
-                            },
-                            chat::ChatStreamEvent::Chunk(stream_chunk) => {
-                                // stream_chunk.content is a String, not an Option<MessageContent>
-                                let content_text = if stream_chunk.content.is_empty() {
-                                    None
-                                } else {
-                                    Some(stream_chunk.content.clone())
-                                };
-                                
-                                ChatCompletionChunk {
-                                    id: completion_id_clone.clone(),
-                                    object: "chat.completion.chunk".to_string(),
-                                    created,
-                                    model: model_clone.clone(),
-                                    choices: vec![ChatCompletionChunkChoice {
-                                        index: 0,
-                                        delta: ChatCompletionDelta {
-                                            role: None,
-                                            content: content_text,
-                                            tool_calls: None,
-                                        },
-                                        finish_reason: None,
-                                    }],
-                                }
-                            },
-                            chat::ChatStreamEvent::End(_) => {
-                                ChatCompletionChunk {
-                                    id: completion_id_clone.clone(),
-                                    object: "chat.completion.chunk".to_string(),
-                                    created,
-                                    model: model_clone.clone(),
-                                    choices: vec![ChatCompletionChunkChoice {
-                                        index: 0,
-                                        delta: ChatCompletionDelta {
-                                            role: None,
-                                            content: None,
-                                            tool_calls: None,
-                                        },
-                                        finish_reason: Some("stop".to_string()),
-                                    }],
-                                }
-                            },
-                            chat::ChatStreamEvent::ReasoningChunk(_) => {
-                                // Handle reasoning chunks - for now just skip them
-                                ChatCompletionChunk {
-                                    id: completion_id_clone.clone(),
-                                    object: "chat.completion.chunk".to_string(),
-                                    created,
-                                    model: model_clone.clone(),
-                                    choices: vec![ChatCompletionChunkChoice {
-                                        index: 0,
-                                        delta: ChatCompletionDelta {
-                                            role: None,
-                                            content: None,
-                                            tool_calls: None,
-                                        },
-                                        finish_reason: None,
-                                    }],
-                                }
-                            },
-                            chat::ChatStreamEvent::ToolCallChunk(tool_chunk) => {
-                                // Handle tool call chunk - show the tool being called
-                                let tool_content = format!(
-                                    "🔧 Calling {} with args: {}",
-                                    tool_chunk.tool_call.fn_name,
-                                    serde_json::to_string(&tool_chunk.tool_call.fn_arguments)
-                                        .unwrap_or_else(|_| "{}".to_string())
-                                );
-                                
-                                ChatCompletionChunk {
-                                    id: completion_id_clone.clone(),
-                                    object: "chat.completion.chunk".to_string(),
-                                    created,
-                                    model: model_clone.clone(),
-                                    choices: vec![ChatCompletionChunkChoice {
-                                        index: 0,
-                                        delta: ChatCompletionDelta {
-                                            role: None,
-                                            content: Some(tool_content),
-                                            tool_calls: None,
-                                        },
-                                        finish_reason: None,
-                                    }],
-                                }
-                            },
-                        };
-
-                        // Serialize to JSON and create SSE event
-                        match serde_json::to_string(&chunk_data) {
-                            Ok(json_data) => {
-                                Event::default().data(json_data)
-                            }
-                            Err(e) => {
-                                error!("Failed to serialize chunk: {}", e);
-                                Event::default().data("{\"error\":\"serialization_error\"}")
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        error!("Error in stream: {}", e);
-                        Event::default().data(format!("{{\"error\":\"{}\"}}", e))
-                    }
-                };
-                
-                // Send to channel
-                if sender.send(event).is_err() {
-                    break; // Receiver dropped
-                }
-            }
+            stream_llm_response(
+                &state,
+                messages,
+                params,
+                &completion_id_clone,
+                created,
+                &model_clone,
+                &sender,
+            )
+            .await;
         }
     });
 
@@ -632,19 +630,154 @@ async fn create_streaming_response(
     Ok(Box::pin(event_stream.map(Ok)))
 }
 
-/// Handler for the models endpoint
-pub async fn list_models() -> impl IntoResponse {
-    Json(serde_json::json!({
-        "object": "list",
-        "data": [
-            {
-                "id": "DeepSeek-R1-0528",
-                "object": "model",
-                "created": 1716508800,
-                "owned_by": "luts"
+/// Stream the LLM service's answer to `sender` as OpenAI `chat.completion.chunk`s
+///
+/// The answer runs through the stream manager, so tools the config's tool
+/// policy allows are executed as they are called. The stream ends with
+/// `data: [DONE]`; if the client disconnects first, the underlying stream is
+/// cancelled.
+async fn stream_llm_response(
+    state: &OpenAIState,
+    messages: Vec<ChatMessage>,
+    params: GenerationParams,
+    completion_id: &str,
+    created: u64,
+    model: &str,
+    sender: &UnboundedSender<Event>,
+) {
+    let session_id = format!("chatcmpl-{}", completion_id);
+    let mut chunks = match state
+        .stream_manager
+        .stream_genai_response_with_policy(
+            session_id.clone(),
+            state.llm_service.clone(),
+            messages,
+            params,
+            state.config.tool_policy(),
+        )
+        .await
+    {
+        Ok(chunks) => chunks,
+        Err(e) => {
+            error!("Error creating stream: {}", e);
+            let _ = sender.send(error_event(&e.to_string()));
+            return;
+        }
+    };
+
+    let completion_chunk = |delta: ChatCompletionDelta, finish_reason: Option<String>| {
+        let chunk = ChatCompletionChunk {
+            id: completion_id.to_string(),
+            object: "chat.completion.chunk".to_string(),
+            created,
+            model: model.to_string(),
+            choices: vec![ChatCompletionChunkChoice {
+                index: 0,
+                delta,
+                finish_reason,
+            }],
+        };
+        match serde_json::to_string(&chunk) {
+            Ok(json_data) => Event::default().data(json_data),
+            Err(e) => {
+                error!("Failed to serialize chunk: {}", e);
+                error_event("serialization_error")
             }
-        ]
-    }))
+        }
+    };
+
+    let _ = sender.send(completion_chunk(
+        ChatCompletionDelta {
+            role: Some("assistant".to_string()),
+            content: None,
+            tool_calls: None,
+        },
+        None,
+    ));
+
+    let mut tool_calls_sent = 0;
+    let mut finished = false;
+    loop {
+        let chunk = tokio::select! {
+            chunk = chunks.next() => match chunk {
+                Some(chunk) => chunk,
+                None => break,
+            },
+            _ = sender.closed() => {
+                info!("Client disconnected, cancelling stream {}", session_id);
+                state.stream_manager.cancel_stream(&session_id).await;
+                return;
+            }
+        };
+
+        if chunk.chunk_type == ChunkType::Error {
+            let _ = sender.send(error_event(&chunk.content));
+            break;
+        }
+        let Some((delta, finish_reason)) = openai_delta(&chunk, tool_calls_sent) else {
+            continue;
+        };
+        if delta.tool_calls.is_some() {
+            tool_calls_sent += 1;
+        }
+        finished |= finish_reason.is_some();
+        let _ = sender.send(completion_chunk(delta, finish_reason));
+    }
+
+    if !finished {
+        let _ = sender.send(completion_chunk(
+            ChatCompletionDelta {
+                role: None,
+                content: None,
+                tool_calls: None,
+            },
+            Some("stop".to_string()),
+        ));
+    }
+    let _ = sender.send(Event::default().data("[DONE]"));
+}
+
+/// OpenAI delta for a stream manager chunk, with its finish reason
+///
+/// `tool_index` is the number of tool calls already sent. Chunks with no
+/// OpenAI counterpart (status, reasoning, tool results) map to `None`.
+fn openai_delta(
+    chunk: &ResponseChunk,
+    tool_index: u32,
+) -> Option<(ChatCompletionDelta, Option<String>)> {
+    let mut delta = ChatCompletionDelta {
+        role: None,
+        content: None,
+        tool_calls: None,
+    };
+    match chunk.chunk_type {
+        ChunkType::Text if !chunk.content.is_empty() => {
+            delta.content = Some(chunk.content.clone());
+            Some((delta, None))
+        }
+        ChunkType::ToolCall => {
+            let custom = &chunk.metadata.custom;
+            let name = custom.get("tool_name")?.as_str()?.to_string();
+            let arguments = custom
+                .get("tool_args")
+                .map(|args| args.to_string())
+                .unwrap_or_else(|| "{}".to_string());
+            delta.tool_calls = Some(vec![OpenAIToolCallDelta {
+                index: tool_index,
+                id: format!("call_{}", chunk.sequence),
+                kind: "function".to_string(),
+                function: OpenAIFunctionCall { name, arguments },
+            }]);
+            Some((delta, None))
+        }
+        ChunkType::Complete => Some((delta, Some("stop".to_string()))),
+        _ => None,
+    }
+}
+
+/// SSE event reporting an error to the client
+fn error_event(message: &str) -> Event {
+    Event::default().data(serde_json::json!({ "error": { "message": message } }).to_string())
 }
 
 /// Handler for the health check endpoint
@@ -658,7 +791,236 @@ pub async fn health_check() -> impl IntoResponse {
 pub fn openai_routes(state: std::sync::Arc<OpenAIState>) -> Router {
     Router::new()
         .route("/v1/chat/completions", post(chat_completions))
-        .route("/v1/models", get(list_models))
         .route("/health", get(health_check))
         .with_state(state)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::rate_limit::RateLimitConfig;
+    use axum::body::Body;
+    use axum::http::Request;
+    use genai::chat::{ChatStreamEvent, StreamChunk};
+    use http_body_util::BodyExt;
+    use luts_framework::llm::streaming::manager::ChunkMetadata;
+    use luts_framework::llm::{MockAiService, MockResponse};
+    use std::pin::Pin;
+    use tower::ServiceExt;
+
+    /// Service whose stream says hello and then never ends
+    ///
+    /// MockAiService always finishes its streams, and this one has to keep
+    /// its rate limit slot taken until the client disconnects.
+    struct EndlessService;
+
+    #[async_trait::async_trait]
+    impl AiService for EndlessService {
+        async fn generate_response(
+            &self,
+            _messages: &[ChatMessage],
+            _params: &GenerationParams,
+        ) -> anyhow::Result<genai::chat::MessageContent> {
+            Err(anyhow::anyhow!("not used"))
+        }
+
+        async fn generate_response_stream<'a>(
+            &'a self,
+            _messages: &'a [ChatMessage],
+            _params: &GenerationParams,
+        ) -> anyhow::Result<
+            Pin<Box<dyn Stream<Item = anyhow::Result<ChatStreamEvent>> + Send + 'a>>,
+        > {
+            Ok(Box::pin(
+                futures_util::stream::iter(vec![
+                    Ok(ChatStreamEvent::Start),
+                    Ok(ChatStreamEvent::Chunk(StreamChunk {
+                        content: "Hello".to_string(),
+                    })),
+                ])
+                .chain(futures_util::stream::pending()),
+            ))
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    fn openai_state(service: impl AiService + 'static, rate_limits: RateLimitConfig) -> Arc<OpenAIState> {
+        Arc::new(OpenAIState {
+            llm_service: Arc::new(service),
+            stream_manager: Arc::new(ResponseStreamManager::new()),
+            agent_registry: Arc::new(AgentRegistry::new()),
+            _conversation_store: Arc::new(Mutex::new(HashMap::new())),
+            idempotency: IdempotencyCache::new(Duration::from_secs(60)),
+            config: Arc::new(LiveConfig::load(None).unwrap()),
+            rate_limiter: Arc::new(RateLimiter::new(rate_limits)),
+        })
+    }
+
+    fn streaming_request(api_key: &str) -> Request<Body> {
+        Request::post("/v1/chat/completions")
+            .header("content-type", "application/json")
+            .header("authorization", format!("Bearer {}", api_key))
+            .body(Body::from(
+                serde_json::json!({
+                    "model": "test-model",
+                    "messages": [{ "role": "user", "content": "Hi" }],
+                    "stream": true
+                })
+                .to_string(),
+            ))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_streaming_completion_sends_sse_deltas_and_done() {
+        let service = MockAiService::new()
+            .with_chunk_size(5)
+            .with_response(MockResponse::Text("Hello world".to_string()));
+        let state = openai_state(service, RateLimitConfig::default());
+
+        let response = openai_routes(state).oneshot(streaming_request("sk-test")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+
+        let frames: Vec<&str> = body
+            .split("\n\n")
+            .map(str::trim)
+            .filter(|frame| !frame.is_empty())
+            .collect();
+        assert!(frames.iter().all(|frame| frame.starts_with("data: ")), "{}", body);
+        assert_eq!(frames.last(), Some(&"data: [DONE]"));
+
+        let chunks: Vec<serde_json::Value> = frames[..frames.len() - 1]
+            .iter()
+            .map(|frame| serde_json::from_str(&frame["data: ".len()..]).unwrap())
+            .collect();
+        assert!(chunks.iter().all(|chunk| chunk["object"] == "chat.completion.chunk"));
+        assert_eq!(chunks[0]["choices"][0]["delta"]["role"], "assistant");
+        let content: String = chunks
+            .iter()
+            .filter_map(|chunk| chunk["choices"][0]["delta"]["content"].as_str())
+            .collect();
+        assert_eq!(content, "Hello world");
+        assert_eq!(chunks.last().unwrap()["choices"][0]["finish_reason"], "stop");
+    }
+
+    #[tokio::test]
+    async fn test_seed_is_rejected() {
+        let state = openai_state(MockAiService::new(), RateLimitConfig::default());
+        let request = Request::post("/v1/chat/completions")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                serde_json::json!({
+                    "model": "test-model",
+                    "messages": [{ "role": "user", "content": "Hi" }],
+                    "seed": 7
+                })
+                .to_string(),
+            ))
+            .unwrap();
+
+        let response = openai_routes(state).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], b"The seed parameter is not supported");
+    }
+
+    #[tokio::test]
+    async fn test_stream_over_concurrency_cap_is_rejected() {
+        let state = openai_state(
+            EndlessService,
+            RateLimitConfig {
+                max_concurrent_streams: 2,
+                ..RateLimitConfig::default()
+            },
+        );
+        let router = openai_routes(state.clone());
+
+        let first = router.clone().oneshot(streaming_request("sk-a")).await.unwrap();
+        let second = router.clone().oneshot(streaming_request("sk-a")).await.unwrap();
+        assert_eq!((first.status(), second.status()), (StatusCode::OK, StatusCode::OK));
+
+        let third = router.clone().oneshot(streaming_request("sk-a")).await.unwrap();
+        assert_eq!(third.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(third.headers().contains_key("retry-after"));
+
+        // Other keys have their own slots
+        let other = router.clone().oneshot(streaming_request("sk-b")).await.unwrap();
+        assert_eq!(other.status(), StatusCode::OK);
+
+        // Disconnecting one stream frees its slot
+        drop(first);
+        for _ in 0..100 {
+            if state.rate_limiter.usage()["sk-a"].active_streams < 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let retried = router.oneshot(streaming_request("sk-a")).await.unwrap();
+        assert_eq!(retried.status(), StatusCode::OK);
+        drop(second);
+    }
+
+    #[test]
+    fn test_tool_call_chunk_maps_to_tool_calls_delta() {
+        let chunk = ResponseChunk {
+            id: "session_3".to_string(),
+            sequence: 3,
+            content: "🔧 Calling calculator".to_string(),
+            is_final: false,
+            timestamp: chrono::Utc::now(),
+            chunk_type: ChunkType::ToolCall,
+            metadata: ChunkMetadata {
+                token_count: None,
+                processing_time_ms: None,
+                model: None,
+                confidence: None,
+                custom: HashMap::from([
+                    ("tool_name".to_string(), serde_json::json!("calculator")),
+                    ("tool_args".to_string(), serde_json::json!({ "expression": "2+2" })),
+                ]),
+            },
+        };
+
+        let (delta, finish_reason) = openai_delta(&chunk, 1).unwrap();
+        assert_eq!(finish_reason, None);
+        assert_eq!(
+            serde_json::to_value(&delta).unwrap()["tool_calls"],
+            serde_json::json!([{
+                "index": 1,
+                "id": "call_3",
+                "type": "function",
+                "function": { "name": "calculator", "arguments": "{\"expression\":\"2+2\"}" }
+            }])
+        );
+    }
+
+    #[test]
+    fn test_request_sampling_fields_become_generation_params() {
+        let request: ChatCompletionRequest = serde_json::from_value(serde_json::json!({
+            "model": "test-model",
+            "messages": [{ "role": "user", "content": "Hi" }],
+            "temperature": 0.2,
+            "max_tokens": 64,
+            "stop": "\n\n"
+        }))
+        .unwrap();
+        let params = request.generation_params();
+        assert_eq!(params.temperature, Some(0.2));
+        assert_eq!(params.top_p, None);
+        assert_eq!(params.max_tokens, Some(64));
+        assert_eq!(params.stop, ["\n\n"]);
+
+        let request: ChatCompletionRequest = serde_json::from_value(serde_json::json!({
+            "model": "test-model",
+            "messages": [],
+            "stop": ["END", "</answer>"]
+        }))
+        .unwrap();
+        assert_eq!(request.generation_params().stop, ["END", "</answer>"]);
+    }
+}
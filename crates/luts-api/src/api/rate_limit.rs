@@ -0,0 +1,313 @@
+//! Per-key rate limiting and streaming concurrency caps
+//!
+//! Each API key gets a token bucket that refills at its requests-per-minute
+//! limit, plus a cap on how many streaming completions it may have open at
+//! once. A stream holds its slot through a [`StreamPermit`] for as long as
+//! the task feeding it runs, so the slot comes back when the stream
+//! finishes or the client disconnects. Requests over either limit get a 429
+//! with a `Retry-After` header. Limits can be swapped on config reload; keys
+//! keep their buckets and open streams across the swap.
+
+use axum::http::{HeaderValue, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+/// Suggested wait when a key is at its stream cap; there's no telling when
+/// one of its streams will end
+const STREAM_RETRY_AFTER: Duration = Duration::from_secs(5);
+
+/// Limits for a single API key; unset fields fall back to the defaults
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyLimits {
+    pub requests_per_minute: Option<u32>,
+    pub max_concurrent_streams: Option<usize>,
+}
+
+/// The `rate_limits` section of the config file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    /// Requests each key may make per minute (0 = unlimited)
+    pub requests_per_minute: u32,
+    /// Streaming completions each key may have open at once (0 = unlimited)
+    pub max_concurrent_streams: usize,
+    /// Overrides by API key
+    pub keys: HashMap<String, KeyLimits>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: 60,
+            max_concurrent_streams: 4,
+            keys: HashMap::new(),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    fn requests_per_minute(&self, key: &str) -> u32 {
+        self.keys
+            .get(key)
+            .and_then(|limits| limits.requests_per_minute)
+            .unwrap_or(self.requests_per_minute)
+    }
+
+    fn max_concurrent_streams(&self, key: &str) -> usize {
+        self.keys
+            .get(key)
+            .and_then(|limits| limits.max_concurrent_streams)
+            .unwrap_or(self.max_concurrent_streams)
+    }
+}
+
+/// A request turned away by the limiter
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimited {
+    pub reason: String,
+    pub retry_after: Duration,
+}
+
+impl IntoResponse for RateLimited {
+    fn into_response(self) -> Response {
+        // Retry-After is whole seconds; round up so an early retry isn't refused again
+        let seconds = self.retry_after.as_secs_f64().ceil().max(1.0) as u64;
+        let mut response = (StatusCode::TOO_MANY_REQUESTS, self.reason).into_response();
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(seconds));
+        response
+    }
+}
+
+/// Current usage of one key, as reported by the admin endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyUsage {
+    pub requests_per_minute: u32,
+    /// Requests the key can make right now before being limited
+    pub requests_available: u32,
+    pub max_concurrent_streams: usize,
+    pub active_streams: usize,
+}
+
+struct KeyState {
+    tokens: f64,
+    last_refill: Instant,
+    active_streams: usize,
+}
+
+/// Token-bucket limiter keyed by API key
+pub struct RateLimiter {
+    config: RwLock<RateLimitConfig>,
+    keys: Mutex<HashMap<String, KeyState>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            keys: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Snapshot of the limits in force
+    pub fn config(&self) -> RateLimitConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    /// Replace the limits; the next request from each key sees the new ones
+    pub fn set_config(&self, config: RateLimitConfig) {
+        *self.config.write().unwrap() = config;
+    }
+
+    /// Take one request from `key`'s bucket
+    pub fn check_request(&self, key: &str) -> Result<(), RateLimited> {
+        let rpm = self.config.read().unwrap().requests_per_minute(key);
+        if rpm == 0 {
+            return Ok(());
+        }
+
+        let mut keys = self.keys.lock().unwrap();
+        let state = Self::refilled(&mut keys, key, rpm);
+        if state.tokens >= 1.0 {
+            state.tokens -= 1.0;
+            return Ok(());
+        }
+
+        let per_second = rpm as f64 / 60.0;
+        Err(RateLimited {
+            reason: format!("Rate limit exceeded: at most {} requests per minute", rpm),
+            retry_after: Duration::from_secs_f64((1.0 - state.tokens) / per_second),
+        })
+    }
+
+    /// Reserve one of `key`'s streaming slots until the permit is dropped
+    pub fn acquire_stream(self: &Arc<Self>, key: &str) -> Result<StreamPermit, RateLimited> {
+        let config = self.config.read().unwrap();
+        let max = config.max_concurrent_streams(key);
+        let mut keys = self.keys.lock().unwrap();
+        let state = Self::refilled(&mut keys, key, config.requests_per_minute(key));
+        if max > 0 && state.active_streams >= max {
+            return Err(RateLimited {
+                reason: format!("Too many concurrent streams: at most {} per API key", max),
+                retry_after: STREAM_RETRY_AFTER,
+            });
+        }
+
+        state.active_streams += 1;
+        Ok(StreamPermit {
+            limiter: self.clone(),
+            key: key.to_string(),
+        })
+    }
+
+    /// Usage of every key seen since startup
+    pub fn usage(&self) -> HashMap<String, KeyUsage> {
+        let config = self.config.read().unwrap();
+        let mut keys = self.keys.lock().unwrap();
+        let names: Vec<String> = keys.keys().cloned().collect();
+        names
+            .into_iter()
+            .map(|key| {
+                let rpm = config.requests_per_minute(&key);
+                let state = Self::refilled(&mut keys, &key, rpm);
+                let usage = KeyUsage {
+                    requests_per_minute: rpm,
+                    requests_available: if rpm == 0 { u32::MAX } else { state.tokens as u32 },
+                    max_concurrent_streams: config.max_concurrent_streams(&key),
+                    active_streams: state.active_streams,
+                };
+                (key, usage)
+            })
+            .collect()
+    }
+
+    /// `key`'s state with its bucket topped up for the time since the last refill
+    fn refilled<'a>(
+        keys: &'a mut HashMap<String, KeyState>,
+        key: &str,
+        rpm: u32,
+    ) -> &'a mut KeyState {
+        let now = Instant::now();
+        let state = keys.entry(key.to_string()).or_insert_with(|| KeyState {
+            tokens: rpm as f64,
+            last_refill: now,
+            active_streams: 0,
+        });
+        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * rpm as f64 / 60.0).min(rpm as f64);
+        state.last_refill = now;
+        state
+    }
+
+    fn release_stream(&self, key: &str) {
+        if let Some(state) = self.keys.lock().unwrap().get_mut(key) {
+            state.active_streams = state.active_streams.saturating_sub(1);
+        }
+    }
+}
+
+/// A streaming slot, given back to its key when dropped
+pub struct StreamPermit {
+    limiter: Arc<RateLimiter>,
+    key: String,
+}
+
+impl Drop for StreamPermit {
+    fn drop(&mut self) {
+        self.limiter.release_stream(&self.key);
+    }
+}
+
+/// API key as shown by the admin endpoint: only its last four characters
+pub fn redact_key(key: &str) -> String {
+    if key == "anonymous" {
+        return key.to_string();
+    }
+    let chars: Vec<char> = key.chars().collect();
+    let tail: String = chars[chars.len().saturating_sub(4)..].iter().collect();
+    format!("…{}", tail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(requests_per_minute: u32, max_concurrent_streams: usize) -> Arc<RateLimiter> {
+        Arc::new(RateLimiter::new(RateLimitConfig {
+            requests_per_minute,
+            max_concurrent_streams,
+            keys: HashMap::from([(
+                "sk-vip".to_string(),
+                KeyLimits {
+                    requests_per_minute: Some(0),
+                    max_concurrent_streams: None,
+                },
+            )]),
+        }))
+    }
+
+    #[test]
+    fn test_bucket_empties_per_key() {
+        let limiter = limiter(2, 4);
+
+        assert!(limiter.check_request("sk-a").is_ok());
+        assert!(limiter.check_request("sk-a").is_ok());
+        let limited = limiter.check_request("sk-a").unwrap_err();
+        // Two per minute refill one token every 30 seconds
+        assert!(limited.retry_after > Duration::from_secs(29), "{:?}", limited);
+
+        assert!(limiter.check_request("sk-b").is_ok());
+        for _ in 0..10 {
+            assert!(limiter.check_request("sk-vip").is_ok());
+        }
+    }
+
+    #[test]
+    fn test_dropped_permit_frees_its_slot() {
+        let limiter = limiter(60, 1);
+
+        let permit = limiter.acquire_stream("sk-a").unwrap();
+        assert!(limiter.acquire_stream("sk-a").is_err());
+        assert_eq!(limiter.usage()["sk-a"].active_streams, 1);
+
+        drop(permit);
+        assert_eq!(limiter.usage()["sk-a"].active_streams, 0);
+        assert!(limiter.acquire_stream("sk-a").is_ok());
+    }
+
+    #[test]
+    fn test_swapped_limits_apply_to_known_keys() {
+        let limiter = limiter(1, 1);
+        assert!(limiter.check_request("sk-a").is_ok());
+        assert!(limiter.check_request("sk-a").is_err());
+        let _permit = limiter.acquire_stream("sk-a").unwrap();
+
+        limiter.set_config(RateLimitConfig {
+            requests_per_minute: 0,
+            max_concurrent_streams: 2,
+            keys: HashMap::new(),
+        });
+        assert!(limiter.check_request("sk-a").is_ok());
+        // The stream opened before the swap still counts against the new cap
+        let _second = limiter.acquire_stream("sk-a").unwrap();
+        assert!(limiter.acquire_stream("sk-a").is_err());
+    }
+
+    #[test]
+    fn test_limited_response_has_retry_after() {
+        let response = RateLimited {
+            reason: "slow down".to_string(),
+            retry_after: Duration::from_millis(1500),
+        }
+        .into_response();
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "2");
+        assert_eq!(redact_key("sk-live-abcdef"), "…cdef");
+    }
+}
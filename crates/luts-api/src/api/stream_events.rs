@@ -0,0 +1,176 @@
+//! Live stream events over a websocket
+//!
+//! `GET /v1/stream/:session_id` upgrades to a websocket that receives the
+//! stream manager's events for that session as JSON text frames: chunks,
+//! typing status and the start and end of the stream. A client that falls
+//! too far behind is told how many events it missed instead of being
+//! disconnected.
+
+use axum::{
+    Router,
+    extract::{
+        Path, State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    response::Response,
+    routing::get,
+};
+use luts_framework::llm::{ResponseStreamManager, StreamEvent};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::debug;
+
+/// Shared state for the stream event endpoint
+pub struct StreamEventsState {
+    pub stream_manager: Arc<ResponseStreamManager>,
+}
+
+/// Handler for the stream event websocket
+/// GET /v1/stream/:session_id
+pub async fn stream_events(
+    State(state): State<Arc<StreamEventsState>>,
+    Path(session_id): Path<String>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    // Subscribe before the upgrade so no event after the request is missed
+    let events = state.stream_manager.subscribe_to_events();
+    upgrade.on_upgrade(move |socket| forward_events(socket, session_id, events))
+}
+
+/// Send the session's events to the socket until either side goes away
+async fn forward_events(
+    mut socket: WebSocket,
+    session_id: String,
+    mut events: tokio::sync::broadcast::Receiver<StreamEvent>,
+) {
+    loop {
+        let frame = tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) if event_session(&event) == session_id => {
+                    match serde_json::to_string(&event) {
+                        Ok(json) => json,
+                        Err(e) => {
+                            debug!("Failed to serialize stream event: {}", e);
+                            continue;
+                        }
+                    }
+                }
+                Ok(_) => continue,
+                Err(RecvError::Lagged(dropped)) => serde_json::json!({
+                    "type": "dropped",
+                    "count": dropped,
+                    "message": format!("dropped {} events", dropped),
+                })
+                .to_string(),
+                Err(RecvError::Closed) => break,
+            },
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Pings are answered for us; nothing else is expected
+                Some(Ok(_)) => continue,
+            },
+        };
+
+        if socket.send(Message::Text(frame)).await.is_err() {
+            break;
+        }
+    }
+    debug!("Stream event socket for session {} closed", session_id);
+}
+
+/// Session an event belongs to
+fn event_session(event: &StreamEvent) -> &str {
+    match event {
+        StreamEvent::ChunkReceived { session_id, .. }
+        | StreamEvent::TypingStatusChanged { session_id, .. }
+        | StreamEvent::StreamStarted { session_id }
+        | StreamEvent::StreamCompleted { session_id, .. }
+        | StreamEvent::StreamError { session_id, .. }
+        | StreamEvent::StreamCancelled { session_id } => session_id,
+    }
+}
+
+/// Create router for the stream event endpoint
+pub fn stream_event_routes(state: StreamEventsState) -> Router {
+    Router::new()
+        .route("/v1/stream/:session_id", get(stream_events))
+        .with_state(Arc::new(state))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use luts_framework::llm::{InternalChatMessage, MockAiService, MockResponse};
+    use serde_json::Value;
+    use tokio_tungstenite::tungstenite;
+
+    /// Next JSON frame from the socket
+    async fn next_event<S>(socket: &mut S) -> Value
+    where
+        S: futures::Stream<Item = Result<tungstenite::Message, tungstenite::Error>> + Unpin,
+    {
+        loop {
+            match socket.next().await.unwrap().unwrap() {
+                tungstenite::Message::Text(text) => return serde_json::from_str(&text).unwrap(),
+                _ => continue,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_socket_receives_chunk_events_for_its_session() {
+        let stream_manager = Arc::new(ResponseStreamManager::new());
+        let app = stream_event_routes(StreamEventsState {
+            stream_manager: stream_manager.clone(),
+        });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let (mut socket, _) =
+            tokio_tungstenite::connect_async(format!("ws://{}/v1/stream/session_1", addr))
+                .await
+                .unwrap();
+
+        let service = Arc::new(
+            MockAiService::new().with_response(MockResponse::Text("Hello there".to_string())),
+        );
+        let other = Arc::new(
+            MockAiService::new().with_response(MockResponse::Text("Not for you".to_string())),
+        );
+        let messages = vec![InternalChatMessage::User {
+            content: "Hi".to_string(),
+        }];
+        let unrelated = stream_manager
+            .stream_genai_response("session_2".to_string(), other, messages.clone())
+            .await
+            .unwrap();
+        let _: Vec<_> = unrelated.collect().await;
+        let stream = stream_manager
+            .stream_genai_response("session_1".to_string(), service, messages)
+            .await
+            .unwrap();
+        let _: Vec<_> = stream.collect().await;
+
+        // Chunks arrive in order, so the Complete chunk comes after the text
+        let mut types = Vec::new();
+        let mut text = String::new();
+        loop {
+            let event = next_event(&mut socket).await;
+            assert_eq!(event["session_id"], "session_1");
+            types.push(event["type"].as_str().unwrap().to_string());
+            if event["type"] != "chunk_received" {
+                continue;
+            }
+            match event["chunk"]["chunk_type"].as_str().unwrap() {
+                "Text" => text.push_str(event["chunk"]["content"].as_str().unwrap()),
+                "Complete" => break,
+                _ => {}
+            }
+        }
+        assert!(types.iter().any(|kind| kind == "stream_started"));
+        assert!(types.iter().any(|kind| kind == "typing_status_changed"));
+        assert_eq!(text, "Hello there");
+    }
+}
@@ -0,0 +1,323 @@
+//! Server configuration file with live reload
+//!
+//! Settings that are safe to swap while serving (pricing, system prompt, agent
+//! sampling parameters, tool policy, rate limits, admin key) are applied in place by
+//! [`LiveConfig::reload`]. Settings that need a restart, like the bind
+//! address, make the reload fail instead of being half-applied.
+
+use crate::api::rate_limit::RateLimitConfig;
+use luts_framework::llm::ToolPolicy;
+use luts_framework::memory::EncryptionKey;
+use luts_framework::prelude::{GenerationParams, TokenPricing};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tracing::info;
+
+/// Why a config file couldn't be loaded or reloaded
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    /// The server was started without `--config`
+    NoConfigFile,
+    /// The file couldn't be read
+    Io(String),
+    /// The file isn't a valid config
+    Invalid(String),
+    /// The file changes settings that need a restart; nothing was applied
+    RestartRequired(Vec<&'static str>),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::NoConfigFile => {
+                write!(f, "Server was started without --config; nothing to reload")
+            }
+            ConfigError::Io(e) | ConfigError::Invalid(e) => write!(f, "{}", e),
+            ConfigError::RestartRequired(settings) => write!(
+                f,
+                "Config changes {} require a restart; nothing was reloaded",
+                settings.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Contents of the `--config` JSON file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// Host to bind to (restart required)
+    pub host: Option<String>,
+    /// Port to listen on (restart required)
+    pub port: Option<u16>,
+    /// LLM provider to use (restart required)
+    pub provider: Option<String>,
+    /// LLM provider by personality, over `provider` (restart required)
+    pub providers: HashMap<String, String>,
+    /// Data directory (restart required)
+    pub data_dir: Option<PathBuf>,
+    /// Base64 AES-256 key that encrypts stored memory content (restart required)
+    pub encryption_key: Option<EncryptionKey>,
+    /// System prompt for requests that don't bring their own
+    pub system_prompt: Option<String>,
+    /// Token pricing by model name, used for cost estimates
+    pub pricing: HashMap<String, TokenPricing>,
+    /// Sampling parameter overrides by personality, applied over its preset
+    pub agents: HashMap<String, GenerationParams>,
+    /// Per-key request and streaming limits
+    pub rate_limits: RateLimitConfig,
+    /// Bearer token the `/admin` endpoints require; they're refused without one
+    pub admin_key: Option<String>,
+    /// Safety levels of the tools completions may run, e.g.
+    /// `{"allowed": ["read_only"]}` for a read-only server; all by default
+    pub tool_policy: ToolPolicy,
+}
+
+impl ServerConfig {
+    /// Read a config file
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| ConfigError::Io(format!("Failed to read config file {:?}: {}", path, e)))?;
+        serde_json::from_str(&content)
+            .map_err(|e| ConfigError::Invalid(format!("Invalid config file {:?}: {}", path, e)))
+    }
+
+    /// Settings that differ from `other` and can't change without a restart
+    fn restart_only_changes(&self, other: &ServerConfig) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        if self.host != other.host || self.port != other.port {
+            changed.push("bind address (host/port)");
+        }
+        if self.provider != other.provider || self.providers != other.providers {
+            changed.push("provider");
+        }
+        if self.data_dir != other.data_dir {
+            changed.push("data_dir");
+        }
+        if self.encryption_key != other.encryption_key {
+            changed.push("encryption_key");
+        }
+        changed
+    }
+
+    /// Human-readable list of hot-swappable settings that differ from `other`
+    fn hot_changes(&self, other: &ServerConfig) -> Vec<String> {
+        let mut changes = Vec::new();
+        if self.system_prompt != other.system_prompt {
+            changes.push("system prompt".to_string());
+        }
+        if self.tool_policy != other.tool_policy {
+            changes.push("tool policy".to_string());
+        }
+        if self.rate_limits != other.rate_limits {
+            changes.push("rate limits".to_string());
+        }
+        if self.admin_key != other.admin_key {
+            changes.push("admin key".to_string());
+        }
+
+        let mut models: Vec<&String> = self.pricing.keys().chain(other.pricing.keys()).collect();
+        models.sort();
+        models.dedup();
+        for model in models {
+            match (self.pricing.get(model), other.pricing.get(model)) {
+                (Some(_), None) => changes.push(format!("pricing for {} removed", model)),
+                (None, Some(_)) => changes.push(format!("pricing for {} added", model)),
+                (Some(old), Some(new))
+                    if old.input_price_per_1k != new.input_price_per_1k
+                        || old.output_price_per_1k != new.output_price_per_1k =>
+                {
+                    changes.push(format!("pricing for {} updated", model))
+                }
+                _ => {}
+            }
+        }
+
+        let mut agents: Vec<&String> = self.agents.keys().chain(other.agents.keys()).collect();
+        agents.sort();
+        agents.dedup();
+        for agent in agents {
+            if self.agents.get(agent) != other.agents.get(agent) {
+                changes.push(format!("generation params for {} updated", agent));
+            }
+        }
+        changes
+    }
+}
+
+/// Config shared by the running server, reloadable from its file
+pub struct LiveConfig {
+    path: Option<PathBuf>,
+    current: RwLock<ServerConfig>,
+}
+
+impl LiveConfig {
+    /// Load the config at `path`, or use defaults when no file was given
+    pub fn load(path: Option<PathBuf>) -> Result<Self, ConfigError> {
+        let config = match &path {
+            Some(path) => ServerConfig::load(path)?,
+            None => ServerConfig::default(),
+        };
+        Ok(Self {
+            path,
+            current: RwLock::new(config),
+        })
+    }
+
+    /// Snapshot of the current settings
+    pub fn current(&self) -> ServerConfig {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Estimated cost in USD of a request to `model`, if pricing is configured for it
+    pub fn cost(&self, model: &str, prompt_tokens: u32, completion_tokens: u32) -> Option<f64> {
+        self.current
+            .read()
+            .unwrap()
+            .pricing
+            .get(model)
+            .map(|pricing| pricing.calculate_cost(prompt_tokens, completion_tokens))
+    }
+
+    /// Configured system prompt, if any
+    pub fn system_prompt(&self) -> Option<String> {
+        self.current.read().unwrap().system_prompt.clone()
+    }
+
+    /// Bearer token required by the admin endpoints, if one is configured
+    pub fn admin_key(&self) -> Option<String> {
+        self.current.read().unwrap().admin_key.clone()
+    }
+
+    /// Safety levels of the tools completions may run
+    pub fn tool_policy(&self) -> ToolPolicy {
+        self.current.read().unwrap().tool_policy.clone()
+    }
+
+    /// Configured sampling parameter overrides for `personality`
+    pub fn generation_overrides(&self, personality: &str) -> GenerationParams {
+        self.current
+            .read()
+            .unwrap()
+            .agents
+            .get(personality)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Re-read the config file and apply it
+    ///
+    /// Returns the list of applied changes. Nothing is applied if the file
+    /// changes a setting that needs a restart.
+    pub fn reload(&self) -> Result<Vec<String>, ConfigError> {
+        let path = self.path.as_ref().ok_or(ConfigError::NoConfigFile)?;
+        let new = ServerConfig::load(path)?;
+
+        let mut current = self.current.write().unwrap();
+        let unsafe_changes = current.restart_only_changes(&new);
+        if !unsafe_changes.is_empty() {
+            return Err(ConfigError::RestartRequired(unsafe_changes));
+        }
+
+        let changes = current.hot_changes(&new);
+        *current = new;
+        if changes.is_empty() {
+            info!("Config reloaded from {:?}: no changes", path);
+        } else {
+            info!("Config reloaded from {:?}: {}", path, changes.join("; "));
+        }
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_config(path: &Path, config: serde_json::Value) {
+        std::fs::write(path, config.to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_reload_updates_pricing_without_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("luts-api.json");
+        write_config(
+            &path,
+            serde_json::json!({
+                "port": 3000,
+                "pricing": { "test-model": { "input_price_per_1k": 1.0, "output_price_per_1k": 2.0 } }
+            }),
+        );
+
+        let config = LiveConfig::load(Some(path.clone())).unwrap();
+        assert_eq!(config.cost("test-model", 1000, 1000), Some(3.0));
+
+        write_config(
+            &path,
+            serde_json::json!({
+                "port": 3000,
+                "system_prompt": "Be brief.",
+                "pricing": { "test-model": { "input_price_per_1k": 0.5, "output_price_per_1k": 0.5 } }
+            }),
+        );
+        let changes = config.reload().unwrap();
+        assert_eq!(changes, vec!["system prompt", "pricing for test-model updated"]);
+        assert_eq!(config.cost("test-model", 1000, 1000), Some(1.0));
+        assert_eq!(config.system_prompt().as_deref(), Some("Be brief."));
+        assert_eq!(config.generation_overrides("creative"), GenerationParams::default());
+
+        write_config(
+            &path,
+            serde_json::json!({
+                "port": 3000,
+                "system_prompt": "Be brief.",
+                "pricing": { "test-model": { "input_price_per_1k": 0.5, "output_price_per_1k": 0.5 } },
+                "agents": { "creative": { "temperature": 1.1 } }
+            }),
+        );
+        assert_eq!(config.reload().unwrap(), vec!["generation params for creative updated"]);
+        assert_eq!(config.generation_overrides("creative").temperature, Some(1.1));
+    }
+
+    #[test]
+    fn test_reload_rejects_bind_address_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("luts-api.json");
+        write_config(&path, serde_json::json!({ "port": 3000 }));
+        let config = LiveConfig::load(Some(path.clone())).unwrap();
+
+        write_config(
+            &path,
+            serde_json::json!({
+                "port": 4000,
+                "pricing": { "test-model": { "input_price_per_1k": 1.0, "output_price_per_1k": 1.0 } }
+            }),
+        );
+        let err = config.reload().unwrap_err();
+        assert_eq!(err, ConfigError::RestartRequired(vec!["bind address (host/port)"]));
+        // The safe part of the rejected file isn't applied either
+        assert_eq!(config.cost("test-model", 1000, 0), None);
+
+        assert_eq!(LiveConfig::load(None).unwrap().reload(), Err(ConfigError::NoConfigFile));
+    }
+
+    #[test]
+    fn test_reload_tells_unreadable_from_invalid() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("luts-api.json");
+        write_config(&path, serde_json::json!({ "port": 3000 }));
+        let config = LiveConfig::load(Some(path.clone())).unwrap();
+
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(matches!(config.reload(), Err(ConfigError::Invalid(_))));
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(config.reload(), Err(ConfigError::Io(_))));
+    }
+}
@@ -10,17 +10,19 @@ use std::sync::Arc;
 use anyhow::Result;
 use axum::Router;
 use clap::Parser;
-use luts_framework::agents::{PersonalityAgentBuilder, AgentRegistry};
+use luts_framework::agents::{AgentFactory, AgentProviders, PersonalityAgentBuilder, AgentRegistry};
 use luts_framework::BlockUtils;
-use luts_framework::llm::LLMService;
+use luts_framework::llm::{LLMService, ResponseStreamManager};
 use luts_framework::tools::calc::MathTool;
 use luts_framework::tools::search::DDGSearchTool;
 use luts_framework::tools::website::WebsiteTool;
 use tokio::sync::Mutex;
-use tracing::info;
+use tracing::{info, warn};
 use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
 
 mod api;
+mod config;
+mod shutdown;
 
 /// Command-line arguments for the LUTS API server
 #[derive(Parser, Debug)]
@@ -45,6 +47,22 @@ struct Args {
     /// LLM provider to use
     #[clap(long, default_value = "DeepSeek-R1-0528")]
     provider: String,
+
+    /// Maximum concurrent requests handled by each agent
+    #[clap(long, default_value = "8")]
+    max_concurrent_per_agent: usize,
+
+    /// How long responses are remembered for retried `Idempotency-Key`s, in seconds
+    #[clap(long, default_value = "3600")]
+    idempotency_window_secs: u64,
+
+    /// JSON config file; reload it with SIGHUP or `POST /admin/reload`
+    #[clap(long)]
+    config: Option<PathBuf>,
+
+    /// How long in-flight requests get to finish on SIGINT/SIGTERM, in seconds
+    #[clap(long, default_value = "10")]
+    shutdown_grace_secs: u64,
 }
 
 #[tokio::main]
@@ -53,7 +71,7 @@ async fn main() -> Result<()> {
     dotenvy::dotenv().ok();
     
     // Parse command-line arguments
-    let args = Args::parse();
+    let mut args = Args::parse();
 
     // Setup tracing
     tracing_subscriber::registry()
@@ -61,7 +79,27 @@ async fn main() -> Result<()> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
+    // Installed before anything records, so nothing is missed
+    #[cfg(feature = "metrics")]
+    let metrics_handle = api::metrics::install_recorder()?;
+
     info!("Starting LUTS API server...");
+
+    // Settings in the config file take precedence over their command-line flags
+    let live_config = Arc::new(config::LiveConfig::load(args.config.clone())?);
+    let file_config = live_config.current();
+    if let Some(host) = file_config.host {
+        args.host = host;
+    }
+    if let Some(port) = file_config.port {
+        args.port = port;
+    }
+    if let Some(provider) = file_config.provider {
+        args.provider = provider;
+    }
+    if let Some(data_dir) = file_config.data_dir {
+        args.data_dir = data_dir;
+    }
     info!("Data directory: {:?}", args.data_dir);
     info!("Provider: {}", args.provider);
 
@@ -79,31 +117,47 @@ async fn main() -> Result<()> {
 
     info!("Using system prompt: {}", prompt_string);
 
-    // Create agent registry and register all personality agents
+    // Create agent registry and register all personality agents. Each request gets its
+    // own agent instance so concurrent requests to the same agent don't queue up.
     let agent_registry = Arc::new(AgentRegistry::new());
-    
-    // Create all personality agents
-    let agents = vec![
-        ("researcher", PersonalityAgentBuilder::create_researcher(&args.data_dir.to_string_lossy(), &args.provider)?),
-        ("calculator", PersonalityAgentBuilder::create_calculator(&args.data_dir.to_string_lossy(), &args.provider)?),
-        ("creative", PersonalityAgentBuilder::create_creative(&args.data_dir.to_string_lossy(), &args.provider)?),
-        ("coordinator", PersonalityAgentBuilder::create_coordinator(&args.data_dir.to_string_lossy(), &args.provider)?),
-        ("pragmatic", PersonalityAgentBuilder::create_pragmatic(&args.data_dir.to_string_lossy(), &args.provider)?),
-    ];
-
-    // Register all agents
-    for (name, agent) in agents {
-        agent_registry.register_agent(agent).await?;
-        info!("Registered agent: {}", name);
+    let data_dir = args.data_dir.to_string_lossy().to_string();
+    let providers = file_config
+        .providers
+        .iter()
+        .fold(AgentProviders::new(&args.provider), |providers, (personality, provider)| {
+            providers.with_provider(personality, provider)
+        });
+
+    for (personality, _, _) in PersonalityAgentBuilder::list_personalities() {
+        let data_dir = data_dir.clone();
+        let provider = providers.provider_for(personality).to_string();
+        let live_config = live_config.clone();
+        // Overrides are read per instance so a config reload applies to new requests
+        let factory: AgentFactory = Arc::new(move || {
+            PersonalityAgentBuilder::create_by_type_with_overrides(
+                personality,
+                &data_dir,
+                &provider,
+                &live_config.generation_overrides(personality),
+            )
+        });
+        agent_registry
+            .register_agent_factory(factory, args.max_concurrent_per_agent)
+            .await?;
+        info!(
+            "Registered agent: {} ({})",
+            personality,
+            providers.provider_for(personality)
+        );
     }
 
     // Initialize LLM service (for fallback)
     let llm_service = LLMService::new(
         Some(&prompt_string),
         vec![
-            Box::new(MathTool),
-            Box::new(DDGSearchTool),
-            Box::new(WebsiteTool),
+            Box::new(MathTool::default()),
+            Box::new(DDGSearchTool::default()),
+            Box::new(WebsiteTool::default()),
         ],
         &args.provider,
     )?;
@@ -116,16 +170,45 @@ async fn main() -> Result<()> {
         path: args.data_dir.join("memory.db"),
         namespace: "luts".to_string(),
         database: "memory".to_string(),
+        encryption_key: file_config.encryption_key,
     };
     let surreal_store = luts_framework::memory::SurrealMemoryStore::new(surreal_config).await.unwrap();
     let memory_manager = Arc::new(luts_framework::memory::MemoryManager::new(surreal_store.clone()));
     let block_utils = Arc::new(BlockUtils::new(memory_manager.clone()));
 
+    // Describe what this server supports before the LLM service moves into shared state
+    let capabilities_state = api::capabilities::CapabilitiesState {
+        document: api::capabilities::CapabilitiesDocument::new(
+            &llm_service,
+            agent_registry.list_agents().await,
+            surreal_store.embedding_dimensions(),
+        ),
+    };
+
+    // Per-key limits; a config reload swaps in new ones
+    let rate_limiter = Arc::new(api::rate_limit::RateLimiter::new(file_config.rate_limits));
+    let limits = rate_limiter.config();
+    info!(
+        "Rate limits: {} requests/minute, {} concurrent streams per key ({} overrides)",
+        limits.requests_per_minute,
+        limits.max_concurrent_streams,
+        limits.keys.len()
+    );
+
+    // Streams are shared with the event websocket so it can watch them
+    let stream_manager = Arc::new(ResponseStreamManager::new());
+
     // Build shared state for OpenAI endpoints
     let openai_state = api::openai::OpenAIState {
-        llm_service,
+        llm_service: Arc::new(llm_service),
+        stream_manager: stream_manager.clone(),
         agent_registry: agent_registry.clone(),
         _conversation_store: Arc::new(conversation_store),
+        idempotency: api::idempotency::IdempotencyCache::new(std::time::Duration::from_secs(
+            args.idempotency_window_secs,
+        )),
+        config: live_config.clone(),
+        rate_limiter: rate_limiter.clone(),
     };
 
     // Build shared state for block endpoints
@@ -133,6 +216,14 @@ async fn main() -> Result<()> {
         block_utils: block_utils.clone(),
     };
 
+    // Build shared state for health and model listing endpoints
+    let health_state = api::health::HealthState {
+        memory_manager: memory_manager.clone(),
+        agent_registry: agent_registry.clone(),
+        models: vec![args.provider.clone()],
+        started_at: chrono::Utc::now().timestamp(),
+    };
+
     // Build shared state for agent endpoints
     let agent_api_state = api::agents::AgentApiState {
         db: Arc::new(surreal_store.db()),
@@ -142,14 +233,62 @@ async fn main() -> Result<()> {
     let app = Router::new()
         .merge(api::openai::openai_routes(Arc::new(openai_state)))
         .merge(api::blocks::block_routes(block_api_state))
-        .merge(api::agents::agent_routes(agent_api_state));
+        .merge(api::agents::agent_routes(agent_api_state))
+        .merge(api::capabilities::capabilities_routes(capabilities_state))
+        .merge(api::health::health_routes(health_state))
+        .merge(api::stream_events::stream_event_routes(
+            api::stream_events::StreamEventsState { stream_manager },
+        ))
+        .merge(api::admin::admin_routes(api::admin::AdminState {
+            config: live_config.clone(),
+            rate_limiter: rate_limiter.clone(),
+        }));
+    #[cfg(feature = "metrics")]
+    let app = app.merge(api::metrics::metrics_routes(metrics_handle));
+
+    // Reload the config file on SIGHUP
+    #[cfg(unix)]
+    {
+        let live_config = live_config.clone();
+        let rate_limiter = rate_limiter.clone();
+        let mut hangups =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())?;
+        tokio::spawn(async move {
+            while hangups.recv().await.is_some() {
+                info!("Received SIGHUP, reloading config");
+                if let Err(e) = api::admin::reload(&live_config, &rate_limiter) {
+                    warn!("Config reload rejected: {}", e);
+                }
+            }
+        });
+    }
 
     // Start the server
     let addr = format!("{}:{}", args.host, args.port);
     info!("Binding to address: {}", addr);
     let listener = tokio::net::TcpListener::bind(&addr).await.unwrap();
     info!("Server listening on {}", addr);
-    axum::serve(listener, app).await.unwrap();
+    let report = shutdown::serve_until_drained(
+        listener,
+        app,
+        shutdown::InFlight::new(),
+        shutdown::shutdown_signal(),
+        std::time::Duration::from_secs(args.shutdown_grace_secs),
+    )
+    .await?;
+    if report.aborted > 0 {
+        warn!(
+            "Shutdown: drained {} of {} in-flight request(s), aborted {}",
+            report.drained, report.in_flight, report.aborted
+        );
+    } else {
+        info!("Shutdown: drained {} in-flight request(s)", report.drained);
+    }
+
+    // The server's copies went with it; dropping the last handles lets the
+    // embedded database close its files before the runtime stops
+    drop((block_utils, memory_manager, surreal_store));
+    info!("Memory store released");
 
     Ok(())
 }
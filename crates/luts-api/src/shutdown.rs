@@ -0,0 +1,289 @@
+//! Graceful shutdown with in-flight request draining
+//!
+//! On SIGINT or SIGTERM the server stops accepting connections and gives the
+//! requests already running a grace period to finish. That includes event
+//! streams, which count as in flight until their body has been sent, so a
+//! stream can flush its last chunk and `[DONE]`. Whatever is still running
+//! when the grace period ends is aborted.
+
+use axum::{
+    Router,
+    body::Body,
+    extract::{Request, State},
+    http::header,
+    middleware::{self, Next},
+    response::Response,
+};
+use futures_util::StreamExt;
+use std::future::{Future, IntoFuture};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tracing::{info, warn};
+
+/// Counts requests that haven't finished sending their response
+#[derive(Default)]
+pub struct InFlight {
+    count: AtomicUsize,
+}
+
+impl InFlight {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Requests currently in flight
+    pub fn count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+
+    /// Count a request until the returned guard is dropped
+    pub fn track(self: &Arc<Self>) -> InFlightGuard {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        InFlightGuard(self.clone())
+    }
+}
+
+/// One in-flight request, uncounted when dropped
+pub struct InFlightGuard(Arc<InFlight>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.count.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Middleware that counts each request as in flight
+///
+/// Event stream responses stay counted until their body ends or the client
+/// goes away; other responses are done once the handler returns.
+pub async fn track_in_flight(
+    State(in_flight): State<Arc<InFlight>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let guard = in_flight.track();
+    let response = next.run(request).await;
+
+    let is_event_stream = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"text/event-stream"));
+    if !is_event_stream {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = body.into_data_stream().map(move |chunk| {
+        let _in_flight = &guard;
+        chunk
+    });
+    Response::from_parts(parts, Body::from_stream(body))
+}
+
+/// Resolves on SIGINT (Ctrl-C) or, on Unix, SIGTERM
+pub async fn shutdown_signal() {
+    let interrupt = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Cannot listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(e) => {
+                warn!("Cannot listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = interrupt => info!("Received SIGINT, shutting down"),
+        _ = terminate => info!("Received SIGTERM, shutting down"),
+    }
+}
+
+/// What happened to the requests that were running at shutdown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DrainReport {
+    /// Requests in flight when the shutdown signal arrived
+    pub in_flight: usize,
+    /// Requests that finished within the grace period
+    pub drained: usize,
+    /// Requests still running when the grace period ended
+    pub aborted: usize,
+}
+
+/// Serve `app` until `signal` resolves, then drain in-flight requests
+///
+/// After the signal no new connections are accepted. Running requests get
+/// up to `grace` to finish before this returns and they are abandoned.
+pub async fn serve_until_drained(
+    listener: TcpListener,
+    app: Router,
+    in_flight: Arc<InFlight>,
+    signal: impl Future<Output = ()>,
+    grace: Duration,
+) -> std::io::Result<DrainReport> {
+    let app = app.layer(middleware::from_fn_with_state(in_flight.clone(), track_in_flight));
+    let (stop, stopped) = oneshot::channel::<()>();
+    let server = axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            let _ = stopped.await;
+        })
+        .into_future();
+    tokio::pin!(server);
+
+    tokio::select! {
+        result = &mut server => {
+            result?;
+            return Ok(DrainReport::default());
+        }
+        _ = signal => {}
+    }
+
+    let pending = in_flight.count();
+    info!(
+        "Draining {} in-flight request(s), waiting up to {:?}",
+        pending, grace
+    );
+    let _ = stop.send(());
+
+    match tokio::time::timeout(grace, &mut server).await {
+        Ok(result) => {
+            result?;
+            Ok(DrainReport {
+                in_flight: pending,
+                drained: pending,
+                aborted: 0,
+            })
+        }
+        Err(_) => {
+            let aborted = in_flight.count().min(pending);
+            Ok(DrainReport {
+                in_flight: pending,
+                drained: pending - aborted,
+                aborted,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::response::sse::{Event, Sse};
+    use axum::routing::get;
+    use futures::Stream;
+    use std::convert::Infallible;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    /// Stream of `one`, `two`, `done`, 50ms apart
+    async fn slow_stream() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+        Sse::new(futures::stream::iter(["one", "two", "done"]).then(|data| async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok(Event::default().data(data))
+        }))
+    }
+
+    /// Stream that sends `one` and then never ends
+    async fn endless_stream() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+        Sse::new(
+            futures::stream::iter([Ok(Event::default().data("one"))])
+                .chain(futures::stream::pending()),
+        )
+    }
+
+    /// Start the server and a stream request; returns once the first event arrived
+    async fn start_stream(
+        app: Router,
+        grace: Duration,
+    ) -> (
+        TcpStream,
+        Vec<u8>,
+        oneshot::Sender<()>,
+        tokio::task::JoinHandle<std::io::Result<DrainReport>>,
+        Arc<InFlight>,
+    ) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let in_flight = InFlight::new();
+        let (trigger, triggered) = oneshot::channel::<()>();
+        let server = tokio::spawn(serve_until_drained(
+            listener,
+            app,
+            in_flight.clone(),
+            async move {
+                let _ = triggered.await;
+            },
+            grace,
+        ));
+
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        client
+            .write_all(b"GET /stream HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut received = Vec::new();
+        let mut buf = [0u8; 1024];
+        while !String::from_utf8_lossy(&received).contains("data: one") {
+            let n = client.read(&mut buf).await.unwrap();
+            assert!(n > 0, "connection closed early");
+            received.extend_from_slice(&buf[..n]);
+        }
+        (client, received, trigger, server, in_flight)
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_stream_completes_after_shutdown() {
+        let app = Router::new().route("/stream", get(slow_stream));
+        let (mut client, mut received, trigger, server, in_flight) =
+            start_stream(app, Duration::from_secs(5)).await;
+        assert_eq!(in_flight.count(), 1);
+
+        trigger.send(()).unwrap();
+        client.read_to_end(&mut received).await.unwrap();
+        let received = String::from_utf8_lossy(&received);
+        assert!(received.contains("data: two"), "{}", received);
+        assert!(received.contains("data: done"), "{}", received);
+
+        let report = server.await.unwrap().unwrap();
+        assert_eq!(
+            report,
+            DrainReport {
+                in_flight: 1,
+                drained: 1,
+                aborted: 0
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_past_grace_period_is_aborted() {
+        let app = Router::new().route("/stream", get(endless_stream));
+        let (_client, _, trigger, server, _) =
+            start_stream(app, Duration::from_millis(100)).await;
+
+        trigger.send(()).unwrap();
+        let report = server.await.unwrap().unwrap();
+        assert_eq!(
+            report,
+            DrainReport {
+                in_flight: 1,
+                drained: 0,
+                aborted: 1
+            }
+        );
+    }
+}
@@ -0,0 +1,144 @@
+//! Loading files and stdin into the agent's context
+//!
+//! The sources are combined into a TaskContext core block, formatted the way
+//! core blocks are formatted for the context window, and given to the agent
+//! before the first message. Context that would leave no room for the
+//! conversation in the model's window is rejected up front.
+
+use anyhow::{Context, Result, bail};
+use luts_framework::agents::Agent;
+use luts_framework::common::ModelRegistry;
+use luts_framework::context::{ContextWindowConfig, CoreBlockManager, CoreBlockType};
+use luts_framework::prelude::TokenCounter;
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+/// Text to load into the agent's context, and where it came from
+pub struct ContextSource {
+    pub name: String,
+    pub content: String,
+}
+
+/// Read each file, then stdin if asked to
+pub fn read_sources(files: &[PathBuf], stdin: bool) -> Result<Vec<ContextSource>> {
+    let mut sources = files
+        .iter()
+        .map(|path| {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read context file {}", path.display()))?;
+            Ok(ContextSource {
+                name: path.display().to_string(),
+                content,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if stdin {
+        let mut content = String::new();
+        io::stdin()
+            .read_to_string(&mut content)
+            .context("Failed to read context from stdin")?;
+        sources.push(ContextSource {
+            name: "stdin".to_string(),
+            content,
+        });
+    }
+    Ok(sources)
+}
+
+/// Give the sources to `agent`, sized for the window of the provider it uses
+///
+/// Agents that don't report a provider are sized for `default_provider`.
+pub async fn add_task_context(
+    agent: &mut dyn Agent,
+    sources: &[ContextSource],
+    default_provider: &str,
+) -> Result<()> {
+    if sources.is_empty() {
+        return Ok(());
+    }
+    let provider = agent.provider().unwrap_or(default_provider).to_string();
+    let context = task_context(sources, &provider).await?;
+    agent.add_context(&context)
+}
+
+/// Format the sources as a TaskContext block sized for `provider`'s window
+async fn task_context(sources: &[ContextSource], provider: &str) -> Result<String> {
+    let window = ContextWindowConfig::for_model(provider, &ModelRegistry::bundled());
+    task_context_within(sources, &TokenCounter::for_model(provider), &window).await
+}
+
+/// Format the sources as a TaskContext block, failing if it won't fit `window`
+///
+/// The window's conversation budget is kept free for the message and reply.
+async fn task_context_within(
+    sources: &[ContextSource],
+    counter: &TokenCounter,
+    window: &ContextWindowConfig,
+) -> Result<String> {
+    let content = sources
+        .iter()
+        .map(|source| {
+            format!(
+                "## {}\n\n```\n{}\n```",
+                source.name,
+                source.content.trim_end()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    // In memory only: loaded files shouldn't replace the agent's stored task context
+    let mut core_blocks = CoreBlockManager::new("cli", None);
+    core_blocks
+        .update_block(CoreBlockType::TaskContext, content)
+        .await?;
+    let context = core_blocks.format_for_context();
+
+    let tokens = counter.count(&context);
+    let budget = window
+        .max_total_tokens
+        .saturating_sub(window.conversation_tokens) as usize;
+    if tokens > budget {
+        bail!(
+            "Context is about {} tokens, but only {} of the model's {}-token window \
+             can be spared for it; pass fewer or smaller files",
+            tokens,
+            budget,
+            window.max_total_tokens
+        );
+    }
+    Ok(context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_contents_reach_the_context_within_budget() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("main.rs");
+        std::fs::write(&path, "fn main() {\n    println!(\"hello\");\n}\n").unwrap();
+        let sources = read_sources(std::slice::from_ref(&path), false).unwrap();
+
+        let counter = TokenCounter::default();
+        let window = ContextWindowConfig::default();
+        let context = task_context_within(&sources, &counter, &window)
+            .await
+            .unwrap();
+        assert!(context.starts_with("# TaskContext"));
+        assert!(context.contains(&format!("## {}", path.display())));
+        assert!(context.contains("    println!(\"hello\");\n}\n```"));
+
+        let small_window = ContextWindowConfig::default().with_context_size(200);
+        let large = ContextSource {
+            name: "large.txt".to_string(),
+            content: "word ".repeat(1000),
+        };
+        let error = task_context_within(&[large], &counter, &small_window)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("200-token window"));
+    }
+}
@@ -1,14 +1,22 @@
 use anyhow::Result;
 use clap::Parser;
 use colored::*;
-use luts_framework::agents::{Agent, AgentMessage, PersonalityAgentBuilder};
+use luts_framework::agents::{
+    Agent, AgentMessage, AgentProviders, ConversationPersistencePolicy, PersonalityAgentBuilder,
+    PersonalityMatch,
+};
 use regex::Regex;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
 use std::path::PathBuf;
-use termimad::MadSkin;
+use std::process::ExitCode;
+use stream_output::{StreamedReply, TerminalSink, stream_reply};
 use tracing::{error, info};
 use tracing_subscriber::FmtSubscriber;
 
+mod context_files;
+mod one_shot;
+mod stream_output;
+
 /// Command-line arguments for the LUTS CLI
 #[derive(Parser)]
 #[command(
@@ -32,6 +40,11 @@ pub struct Args {
     #[clap(long, default_value = "gemini-2.5-pro", short_alias = 'r')]
     provider: String,
 
+    /// JSON file choosing providers by personality, e.g.
+    /// {"calculator": "gemini-2.5-flash"}; others use --provider
+    #[clap(long, value_name = "PATH")]
+    agent_providers: Option<PathBuf>,
+
     /// Agent personality to use
     #[clap(long, short_alias = 'a')]
     agent: Option<String>,
@@ -39,6 +52,30 @@ pub struct Args {
     /// List available agent personalities
     #[clap(long)]
     list_agents: bool,
+
+    /// Send this message to the agent, print the response and exit; without
+    /// it, a message piped to stdin is sent the same way
+    #[clap(short, long, requires = "agent")]
+    message: Option<String>,
+
+    /// Print the full response as JSON (with a single message)
+    #[clap(long)]
+    json: bool,
+
+    /// Load a file into the agent's context before the conversation; can be
+    /// given more than once
+    #[clap(long = "context-file", value_name = "PATH")]
+    context_files: Vec<PathBuf>,
+
+    /// Load stdin into the agent's context; the message then has to be given
+    /// with --message
+    #[clap(long, requires = "message")]
+    context_stdin: bool,
+
+    /// Store finished turns in the agent's memory: none, messages, or
+    /// messages-and-summaries
+    #[clap(long, value_name = "POLICY", default_value = "none")]
+    persistence: ConversationPersistencePolicy,
 }
 
 /// Replace Markdown links with OSC 8 hyperlinks for supported terminals.
@@ -91,15 +128,27 @@ fn select_agent_interactively() -> Result<String> {
             }
         }
 
-        // Try matching by name
-        let input_lower = input.to_lowercase();
-        for (id, name, _) in &personalities {
-            if id.to_lowercase() == input_lower || name.to_lowercase() == input_lower {
+        // Try matching by name, accepting close matches
+        match PersonalityAgentBuilder::resolve_personality(input) {
+            PersonalityMatch::Found(id) => {
+                let exact = personalities.iter().any(|(pid, name, _)| {
+                    *pid == id && (pid.eq_ignore_ascii_case(input) || name.eq_ignore_ascii_case(input))
+                });
+                if !exact {
+                    println!("{}", format!("✨ Using closest match: {}", id).bright_green());
+                }
                 return Ok(id.to_string());
             }
+            PersonalityMatch::Ambiguous(candidates) => {
+                println!(
+                    "{}",
+                    format!("🤔 Did you mean one of: {}?", candidates.join(", ")).yellow()
+                );
+            }
+            PersonalityMatch::NotFound => {
+                println!("{}", "❌ Invalid choice. Please try again.".red());
+            }
         }
-
-        println!("{}", "❌ Invalid choice. Please try again.".red());
     }
 }
 
@@ -151,8 +200,6 @@ async fn conversation_loop(mut agent: Box<dyn Agent>) -> Result<()> {
     println!("{}", "Type '/switch' to change agents.".bright_yellow());
     println!();
 
-    let skin = MadSkin::default();
-
     loop {
         // Get user input
         print!("{}", "You: ".bright_cyan().bold());
@@ -185,30 +232,27 @@ async fn conversation_loop(mut agent: Box<dyn Agent>) -> Result<()> {
             input.to_string(),
         );
 
-        // Process message with agent
-        print!("{}", format!("{}: ", agent.name()).bright_green().bold());
+        // Stream the reply, printing its text as it arrives
+        let prefix = format!("{}: ", agent.name());
+        print!("{}", prefix.bright_green().bold());
         io::stdout().flush()?;
 
-        match agent.process_message(message).await {
-            Ok(response) => {
-                if response.success {
-                    // Format and display the response with markdown rendering
-                    let formatted_content = add_osc8_hyperlinks(&response.content);
-                    let rendered = skin.term_text(&formatted_content);
-                    println!("{}", rendered);
-                } else {
-                    println!(
-                        "{}",
-                        format!(
-                            "❌ Error: {}",
-                            response
-                                .error
-                                .unwrap_or_else(|| "Unknown error".to_string())
-                        )
-                        .red()
-                    );
+        let render_markdown = io::stdout().is_terminal();
+        let mut sink = TerminalSink::new(io::stdout(), prefix.chars().count(), render_markdown);
+        match agent.process_message_streaming(message).await {
+            Ok(stream) => match stream_reply(stream, &mut sink).await {
+                Ok(StreamedReply {
+                    error: Some(error), ..
+                }) => {
+                    println!();
+                    println!("{}", format!("❌ Error: {}", error).red());
                 }
-            }
+                Ok(_) => {}
+                Err(e) => {
+                    println!();
+                    println!("{}", format!("❌ Output error: {}", e).red());
+                }
+            },
             Err(e) => {
                 println!("{}", format!("❌ Agent error: {}", e).red());
             }
@@ -221,16 +265,20 @@ async fn conversation_loop(mut agent: Box<dyn Agent>) -> Result<()> {
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> Result<ExitCode> {
     let args = Args::parse();
+    let one_shot = args.message.is_some() || !io::stdin().is_terminal();
 
-    // Setup tracing
+    // Setup tracing; logs go to stderr so a one-shot response can be piped
     let subscriber = FmtSubscriber::builder()
         .with_max_level(if args.debug {
             tracing::Level::DEBUG
+        } else if one_shot {
+            tracing::Level::WARN
         } else {
             tracing::Level::INFO
         })
+        .with_writer(io::stderr)
         .finish();
     tracing::subscriber::set_global_default(subscriber)?;
 
@@ -250,7 +298,7 @@ async fn main() -> Result<()> {
                 description
             );
         }
-        return Ok(());
+        return Ok(ExitCode::SUCCESS);
     }
 
     // Ensure data directory exists
@@ -261,6 +309,37 @@ async fn main() -> Result<()> {
     info!("Data directory: {}", data_dir);
     info!("Provider: {}", args.provider);
 
+    let mut providers = AgentProviders::new(&args.provider);
+    if let Some(path) = &args.agent_providers {
+        providers = providers.with_file(path)?;
+    }
+
+    let sources = context_files::read_sources(&args.context_files, args.context_stdin)?;
+
+    if one_shot {
+        if !io::stdout().is_terminal() {
+            colored::control::set_override(false);
+        }
+        let Some(agent_type) = &args.agent else {
+            anyhow::bail!("--agent is required when sending a single message");
+        };
+        let message = match args.message {
+            Some(message) => message,
+            None => one_shot::read_message_from_stdin()?,
+        };
+        let mut agent = PersonalityAgentBuilder::create_by_type_with_provider(
+            agent_type, &data_dir, &providers,
+        )?;
+        agent.set_persistence_policy(args.persistence)?;
+        context_files::add_task_context(agent.as_mut(), &sources, &args.provider).await?;
+        let success = one_shot::run(agent, message, args.json).await?;
+        return Ok(if success {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        });
+    }
+
     // Main application loop
     loop {
         // Determine which agent to use
@@ -276,15 +355,20 @@ async fn main() -> Result<()> {
             format!("🚀 Loading {} agent...", agent_type).bright_yellow()
         );
 
-        let agent =
-            match PersonalityAgentBuilder::create_by_type(&agent_type, &data_dir, &args.provider) {
-                Ok(agent) => agent,
-                Err(e) => {
-                    error!("Failed to create agent: {}", e);
-                    println!("{}", format!("❌ Failed to create agent: {}", e).red());
-                    continue;
-                }
-            };
+        let mut agent = match PersonalityAgentBuilder::create_by_type_with_provider(
+            &agent_type,
+            &data_dir,
+            &providers,
+        ) {
+            Ok(agent) => agent,
+            Err(e) => {
+                error!("Failed to create agent: {}", e);
+                println!("{}", format!("❌ Failed to create agent: {}", e).red());
+                continue;
+            }
+        };
+        agent.set_persistence_policy(args.persistence)?;
+        context_files::add_task_context(agent.as_mut(), &sources, &args.provider).await?;
 
         // Start conversation with the agent
         match conversation_loop(agent).await {
@@ -300,5 +384,5 @@ async fn main() -> Result<()> {
         }
     }
 
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
@@ -0,0 +1,103 @@
+//! Answering a single message without the interactive prompt
+//!
+//! For scripts: the agent's reply goes to stdout, rendered as markdown only
+//! when stdout is a terminal, and errors go to stderr. With `--json` the
+//! whole [`MessageResponse`] is printed instead, so tool calls and the
+//! success flag can be read by other tools.
+
+use crate::add_osc8_hyperlinks;
+use anyhow::{Context, Result, bail};
+use colored::*;
+use luts_framework::agents::{Agent, AgentMessage, MessageResponse};
+use std::io::{self, IsTerminal, Read};
+use termimad::MadSkin;
+
+/// Read the message to send from stdin
+pub fn read_message_from_stdin() -> Result<String> {
+    let mut message = String::new();
+    io::stdin()
+        .read_to_string(&mut message)
+        .context("Failed to read the message from stdin")?;
+    let message = message.trim();
+    if message.is_empty() {
+        bail!("No message given on stdin");
+    }
+    Ok(message.to_string())
+}
+
+/// Send one message to the agent and print its response
+///
+/// Returns whether the agent answered successfully.
+pub async fn run(mut agent: Box<dyn Agent>, message: String, json: bool) -> Result<bool> {
+    let message = AgentMessage::new_chat("user".to_string(), agent.agent_id().to_string(), message);
+    let message_id = message.message_id.clone();
+    let response = agent
+        .process_message(message)
+        .await
+        .unwrap_or_else(|e| MessageResponse::error(message_id, format!("Agent error: {}", e)));
+
+    if json {
+        println!("{}", response_json(&response)?);
+    } else if response.success {
+        print_content(&response.content);
+    } else {
+        eprintln!(
+            "{}",
+            format!(
+                "❌ Error: {}",
+                response.error.as_deref().unwrap_or("Unknown error")
+            )
+            .red()
+        );
+    }
+    Ok(response.success)
+}
+
+/// The response as pretty-printed JSON
+fn response_json(response: &MessageResponse) -> Result<String> {
+    serde_json::to_string_pretty(response).context("Failed to serialize the response")
+}
+
+fn print_content(content: &str) {
+    if io::stdout().is_terminal() {
+        let skin = MadSkin::default();
+        println!("{}", skin.term_text(&add_osc8_hyperlinks(content)));
+    } else {
+        println!("{}", content);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use luts_framework::agents::ToolCallInfo;
+    use serde_json::{Value, json};
+
+    #[test]
+    fn test_json_output_has_the_full_response() {
+        let response = MessageResponse::success_with_tools(
+            "msg-1".to_string(),
+            "It's 4.".to_string(),
+            None,
+            vec![ToolCallInfo {
+                tool_name: "calculator".to_string(),
+                tool_args: json!({ "expression": "2 + 2" }),
+                tool_result: json!("4"),
+                tool_error: None,
+                success: true,
+                call_id: Some("call_1".to_string()),
+            }],
+        );
+
+        let output: Value = serde_json::from_str(&response_json(&response).unwrap()).unwrap();
+        assert_eq!(output["in_response_to"], "msg-1");
+        assert_eq!(output["content"], "It's 4.");
+        assert_eq!(output["success"], true);
+        assert_eq!(output["error"], Value::Null);
+        let tool_call = &output["tool_calls"][0];
+        assert_eq!(tool_call["tool_name"], "calculator");
+        assert_eq!(tool_call["tool_args"]["expression"], "2 + 2");
+        assert_eq!(tool_call["tool_result"], "4");
+        assert_eq!(tool_call["success"], true);
+    }
+}
@@ -0,0 +1,291 @@
+//! Printing a reply while it streams in
+//!
+//! The interactive prompt shows the reply's text as it arrives, so slow
+//! models still give feedback, and a `[tool: name]` line for each tool the
+//! agent calls. Markdown can't be rendered reliably from half a reply, so on
+//! a terminal the live plain text of the answer is replaced by its rendered
+//! form, links included, once the reply is complete.
+
+use crate::add_osc8_hyperlinks;
+use anyhow::Result;
+use colored::*;
+use futures::StreamExt;
+use luts_framework::llm::streaming::{ChunkType, ResponseChunk, StreamableResponse};
+use std::io::{self, Write};
+use termimad::MadSkin;
+use termimad::crossterm::{
+    cursor::{MoveToColumn, MoveUp},
+    queue,
+    terminal::{self, Clear, ClearType},
+};
+use unicode_width::UnicodeWidthChar;
+
+/// Where a streamed reply is shown as it arrives
+pub trait StreamSink {
+    /// More text of the reply
+    fn text(&mut self, text: &str) -> io::Result<()>;
+
+    /// The agent called a tool
+    fn tool_call(&mut self, tool_name: &str) -> io::Result<()>;
+
+    /// The reply is complete; `answer` is its text after the last tool call
+    fn finish(&mut self, answer: &str) -> io::Result<()>;
+}
+
+/// What a streamed reply came to
+#[derive(Debug, Default)]
+pub struct StreamedReply {
+    /// Text after the last tool call, i.e. the agent's answer
+    pub answer: String,
+    /// Why the reply failed, if it did
+    pub error: Option<String>,
+}
+
+/// Pass `stream` to `sink` chunk by chunk until the reply ends
+///
+/// The sink is only told the reply is finished when it succeeded.
+pub async fn stream_reply(
+    mut stream: StreamableResponse,
+    sink: &mut impl StreamSink,
+) -> Result<StreamedReply> {
+    let mut reply = StreamedReply::default();
+    while let Some(chunk) = stream.next().await {
+        match chunk.chunk_type {
+            ChunkType::Text => {
+                reply.answer.push_str(&chunk.content);
+                sink.text(&chunk.content)?;
+            }
+            ChunkType::ToolCall => {
+                // Text before a tool call is the model thinking aloud
+                reply.answer.clear();
+                sink.tool_call(&tool_name(&chunk))?;
+            }
+            ChunkType::Error => {
+                reply.error = Some(chunk.content);
+                return Ok(reply);
+            }
+            ChunkType::Complete => {
+                // Agents that don't stream put the whole reply here
+                if reply.answer.is_empty() && !chunk.content.is_empty() {
+                    reply.answer = chunk.content;
+                    sink.text(&reply.answer)?;
+                }
+                break;
+            }
+            _ => {}
+        }
+    }
+    sink.finish(&reply.answer)?;
+    Ok(reply)
+}
+
+/// Name of the tool a `ToolCall` chunk is about
+fn tool_name(chunk: &ResponseChunk) -> String {
+    chunk
+        .metadata
+        .custom
+        .get("tool_name")
+        .and_then(|name| name.as_str())
+        .unwrap_or(&chunk.content)
+        .to_string()
+}
+
+/// Prints a streamed reply to the terminal
+///
+/// With `render_markdown`, the answer's plain text is erased once complete
+/// and printed again as rendered markdown. The rows to erase are counted as
+/// the text is written, wrapping at the terminal's width; an answer that has
+/// scrolled off the screen is left as plain text.
+pub struct TerminalSink<W: Write> {
+    out: W,
+    skin: MadSkin,
+    render_markdown: bool,
+    width: usize,
+    height: usize,
+    /// Column the answer started at, after the agent's name
+    start_column: usize,
+    column: usize,
+    /// Rows the answer has moved down since it started
+    rows: usize,
+}
+
+impl<W: Write> TerminalSink<W> {
+    /// Print to `out`, where the cursor is at `start_column`
+    pub fn new(out: W, start_column: usize, render_markdown: bool) -> Self {
+        let (width, height) = terminal::size().unwrap_or((80, 24));
+        Self {
+            out,
+            skin: MadSkin::default(),
+            render_markdown,
+            width: usize::from(width).max(1),
+            height: usize::from(height),
+            start_column,
+            column: start_column,
+            rows: 0,
+        }
+    }
+}
+
+impl<W: Write> StreamSink for TerminalSink<W> {
+    fn text(&mut self, text: &str) -> io::Result<()> {
+        for c in text.chars() {
+            if c == '\n' {
+                self.rows += 1;
+                self.column = 0;
+            } else {
+                let char_width = c.width().unwrap_or(0);
+                // A wide character that doesn't fit wraps to the next row whole
+                if self.column + char_width > self.width {
+                    self.rows += 1;
+                    self.column = 0;
+                }
+                self.column += char_width;
+                if self.column == self.width {
+                    self.rows += 1;
+                    self.column = 0;
+                }
+            }
+        }
+        write!(self.out, "{}", text)?;
+        self.out.flush()
+    }
+
+    fn tool_call(&mut self, tool_name: &str) -> io::Result<()> {
+        if self.column != 0 {
+            writeln!(self.out)?;
+        }
+        writeln!(
+            self.out,
+            "{}",
+            format!("[tool: {}]", tool_name).bright_black()
+        )?;
+        // The answer starts over below the tool line
+        self.start_column = 0;
+        self.column = 0;
+        self.rows = 0;
+        self.out.flush()
+    }
+
+    fn finish(&mut self, answer: &str) -> io::Result<()> {
+        if !self.render_markdown || self.rows >= self.height {
+            if self.column != 0 {
+                writeln!(self.out)?;
+            }
+            return self.out.flush();
+        }
+
+        if self.rows > 0 {
+            queue!(self.out, MoveUp(self.rows as u16))?;
+        }
+        queue!(
+            self.out,
+            MoveToColumn(self.start_column as u16),
+            Clear(ClearType::FromCursorDown)
+        )?;
+        let formatted_content = add_osc8_hyperlinks(answer);
+        writeln!(self.out, "{}", self.skin.term_text(&formatted_content))?;
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use luts_framework::llm::{
+        InternalChatMessage, MockAiService, MockResponse, ResponseStreamManager, StreamConfig,
+    };
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Text(String),
+        ToolCall(String),
+        Finish(String),
+    }
+
+    #[derive(Default)]
+    struct RecordingSink {
+        events: Vec<Event>,
+    }
+
+    impl StreamSink for RecordingSink {
+        fn text(&mut self, text: &str) -> io::Result<()> {
+            self.events.push(Event::Text(text.to_string()));
+            Ok(())
+        }
+
+        fn tool_call(&mut self, tool_name: &str) -> io::Result<()> {
+            self.events.push(Event::ToolCall(tool_name.to_string()));
+            Ok(())
+        }
+
+        fn finish(&mut self, answer: &str) -> io::Result<()> {
+            self.events.push(Event::Finish(answer.to_string()));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sink_receives_chunks_in_order() {
+        let answer = "It's noon, see [the clock](https://example.com/clock).";
+        let service = MockAiService::new()
+            .with_chunk_size(5)
+            .with_response(MockResponse::ToolCall {
+                name: "clock".to_string(),
+                args: json!({}),
+            })
+            .with_response(MockResponse::Text(answer.to_string()));
+        // No coalescing, so each mock chunk reaches the sink on its own
+        let manager = ResponseStreamManager::new();
+        manager
+            .update_config(StreamConfig {
+                coalesce_ms: 0,
+                coalesce_chars: 0,
+                ..StreamConfig::default()
+            })
+            .await
+            .unwrap();
+        let stream = manager
+            .stream_genai_response(
+                "session".to_string(),
+                Arc::new(service),
+                vec![InternalChatMessage::User {
+                    content: "What time is it?".to_string(),
+                }],
+            )
+            .await
+            .unwrap();
+
+        let mut sink = RecordingSink::default();
+        let reply = stream_reply(stream, &mut sink).await.unwrap();
+
+        assert_eq!(reply.answer, answer);
+        assert_eq!(reply.error, None);
+        assert_eq!(sink.events[0], Event::ToolCall("clock".to_string()));
+        assert_eq!(sink.events.last(), Some(&Event::Finish(answer.to_string())));
+        let texts: Vec<&str> = sink.events[1..sink.events.len() - 1]
+            .iter()
+            .map(|event| match event {
+                Event::Text(text) => text.as_str(),
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect();
+        assert!(texts.len() > 1, "the answer should arrive in pieces");
+        assert_eq!(texts.concat(), answer);
+    }
+
+    #[test]
+    fn test_terminal_sink_counts_display_width() {
+        let mut sink = TerminalSink::new(Vec::new(), 0, false);
+        sink.width = 5;
+
+        // 語 is two columns wide and doesn't fit after "ab日", so it wraps
+        sink.text("ab日語").unwrap();
+        assert_eq!((sink.rows, sink.column), (1, 2));
+
+        // Combining marks take no column
+        sink.text("e\u{301}e\u{301}").unwrap();
+        assert_eq!((sink.rows, sink.column), (1, 4));
+    }
+}
@@ -1,14 +1,19 @@
 //! LUTS Common Error Types
 //!
 //! Centralized error handling for all LUTS components
+//!
+//! Callers match on the variant to tell failure kinds apart; the API server,
+//! for one, maps them to HTTP status codes. Code that hasn't been given a
+//! specific variant yet reports [`LutsError::Other`].
 
 use std::fmt;
+use std::time::Duration;
 
 /// Main error type for LUTS operations
 #[derive(Debug)]
 pub enum LutsError {
-    /// Generic error with message
-    Generic(String),
+    /// Catch-all for failures without a more specific variant yet
+    Other(String),
     /// IO-related errors
     Io(std::io::Error),
     /// Serialization/deserialization errors
@@ -23,12 +28,39 @@ pub enum LutsError {
     Tool(String),
     /// Memory/context management errors
     Memory(String),
+    /// A backend is unavailable and requests are failing fast
+    Backend(String),
+    /// A search or filter query that cannot be run as written
+    InvalidQuery(String),
+    /// A referenced item no longer exists
+    NotFound(String),
+    /// Tool parameters that don't match the tool's schema
+    InvalidParams(String),
+    /// No context provider is registered under this name
+    ProviderNotFound(String),
+    /// No memory block has this ID
+    BlockNotFound(String),
+    /// A request needs more tokens than its budget has left
+    BudgetExceeded { requested: usize, available: usize },
+    /// Embeddings of different lengths can't be compared
+    EmbeddingDimensionMismatch {
+        /// What had the wrong length, e.g. "stored embeddings"
+        subject: String,
+        expected: usize,
+        actual: usize,
+    },
+    /// An operation didn't finish in time
+    Timeout(String),
+    /// A backend asked us to slow down, and for how long if it said
+    RateLimited { retry_after: Option<Duration> },
+    /// Input that was rejected before doing any work
+    Validation(String),
 }
 
 impl fmt::Display for LutsError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            LutsError::Generic(msg) => write!(f, "LUTS error: {}", msg),
+            LutsError::Other(msg) => write!(f, "{}", msg),
             LutsError::Io(err) => write!(f, "IO error: {}", err),
             LutsError::Serde(err) => write!(f, "Serialization error: {}", err),
             LutsError::Storage(msg) => write!(f, "Storage error: {}", msg),
@@ -36,11 +68,48 @@ impl fmt::Display for LutsError {
             LutsError::Agent(msg) => write!(f, "Agent error: {}", msg),
             LutsError::Tool(msg) => write!(f, "Tool error: {}", msg),
             LutsError::Memory(msg) => write!(f, "Memory error: {}", msg),
+            LutsError::Backend(msg) => write!(f, "Backend unavailable: {}", msg),
+            LutsError::InvalidQuery(msg) => write!(f, "Invalid query: {}", msg),
+            LutsError::NotFound(msg) => write!(f, "Not found: {}", msg),
+            LutsError::InvalidParams(msg) => write!(f, "Invalid parameters: {}", msg),
+            LutsError::ProviderNotFound(name) => write!(f, "Provider '{}' not found", name),
+            LutsError::BlockNotFound(id) => write!(f, "Memory block {} not found", id),
+            LutsError::BudgetExceeded {
+                requested,
+                available,
+            } => write!(
+                f,
+                "Token budget exceeded: {} tokens requested, {} available",
+                requested, available
+            ),
+            LutsError::EmbeddingDimensionMismatch {
+                subject,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Embedding dimension mismatch in {}: {} dimensions, expected {}",
+                subject, actual, expected
+            ),
+            LutsError::Timeout(what) => write!(f, "Timed out: {}", what),
+            LutsError::RateLimited {
+                retry_after: Some(delay),
+            } => write!(f, "Rate limited; retry after {}s", delay.as_secs_f64().ceil()),
+            LutsError::RateLimited { retry_after: None } => write!(f, "Rate limited"),
+            LutsError::Validation(msg) => write!(f, "Validation failed: {}", msg),
         }
     }
 }
 
-impl std::error::Error for LutsError {}
+impl std::error::Error for LutsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LutsError::Io(err) => Some(err),
+            LutsError::Serde(err) => Some(err),
+            _ => None,
+        }
+    }
+}
 
 /// Convenience result type for LUTS operations
 pub type Result<T> = std::result::Result<T, LutsError>;
@@ -59,7 +128,33 @@ impl From<serde_json::Error> for LutsError {
 }
 
 impl From<anyhow::Error> for LutsError {
+    /// Keeps the variant of a `LutsError` that was wrapped in `anyhow`
     fn from(err: anyhow::Error) -> Self {
-        LutsError::Generic(err.to_string())
+        match err.downcast::<LutsError>() {
+            Ok(err) => err,
+            Err(err) => LutsError::Other(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anyhow_round_trip_keeps_variant() {
+        let wrapped = anyhow::Error::from(LutsError::BlockNotFound("b1".to_string()));
+        assert!(matches!(LutsError::from(wrapped), LutsError::BlockNotFound(id) if id == "b1"));
+
+        let plain = LutsError::from(anyhow::anyhow!("something broke"));
+        assert!(matches!(&plain, LutsError::Other(msg) if msg == "something broke"));
+    }
+
+    #[test]
+    fn test_rate_limited_display_rounds_up() {
+        let err = LutsError::RateLimited {
+            retry_after: Some(Duration::from_millis(1200)),
+        };
+        assert_eq!(err.to_string(), "Rate limited; retry after 2s");
     }
 }
\ No newline at end of file
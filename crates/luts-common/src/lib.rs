@@ -6,7 +6,9 @@
 pub mod config;
 pub mod constants;
 pub mod error;
+pub mod model_registry;
 pub mod pricing;
+pub mod retry;
 pub mod types;
 pub mod utils;
 
@@ -14,6 +16,7 @@ pub mod utils;
 pub use error::{LutsError, Result};
 pub use config::{BaseConfig, ProviderConfig, StorageConfig};
 pub use constants::*;
+pub use model_registry::{ModelInfo, ModelRegistry};
 pub use pricing::{TokenPricing, PricingConfig};
 pub use types::{ExportFormat, ProviderType, ModelType, UsageFilter};
 pub use utils::*;
\ No newline at end of file
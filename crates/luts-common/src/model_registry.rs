@@ -0,0 +1,159 @@
+//! Model capabilities and pricing keyed by provider string
+//!
+//! The registry answers "how big is this model's context window, can it call
+//! tools or stream, and what does it cost" for provider strings such as
+//! `gemini-2.5-pro` or `DeepSeek-R1-0528`. A default set ships with the crate
+//! in `models.toml`; entries can be added or replaced at runtime.
+
+use crate::error::{LutsError, Result};
+use crate::pricing::TokenPricing;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// The bundled model list
+const BUNDLED_MODELS: &str = include_str!("../models.toml");
+
+/// What a model supports and what it costs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelInfo {
+    /// Context window size in tokens, prompt and completion together
+    pub max_context_tokens: usize,
+    /// Whether the model can call tools
+    pub supports_tools: bool,
+    /// Whether responses can be streamed
+    pub supports_streaming: bool,
+    /// Price per 1K input tokens in USD
+    pub input_price: f64,
+    /// Price per 1K output tokens in USD
+    pub output_price: f64,
+}
+
+impl ModelInfo {
+    /// The model's prices as used for cost estimates
+    pub fn pricing(&self) -> TokenPricing {
+        TokenPricing {
+            input_price_per_1k: self.input_price,
+            output_price_per_1k: self.output_price,
+        }
+    }
+}
+
+/// Layout of `models.toml` and override files
+#[derive(Debug, Default, Deserialize)]
+struct ModelFile {
+    #[serde(default)]
+    models: HashMap<String, ModelInfo>,
+}
+
+/// Model capabilities and pricing by provider string
+///
+/// Lookups ignore case and an optional `provider/` prefix, so
+/// `google/Gemini-2.5-Pro` finds the `gemini-2.5-pro` entry.
+#[derive(Debug, Clone, Default)]
+pub struct ModelRegistry {
+    models: HashMap<String, ModelInfo>,
+}
+
+impl ModelRegistry {
+    /// An empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The models bundled with LUTS
+    pub fn bundled() -> Self {
+        let mut registry = Self::new();
+        registry
+            .load_overrides(BUNDLED_MODELS)
+            .expect("bundled models.toml is valid");
+        registry
+    }
+
+    /// Add or replace the models listed in a TOML document
+    pub fn load_overrides(&mut self, toml: &str) -> Result<()> {
+        let file: ModelFile = toml::from_str(toml)
+            .map_err(|e| LutsError::Config(format!("Invalid model list: {}", e)))?;
+        for (name, info) in file.models {
+            self.register(&name, info);
+        }
+        Ok(())
+    }
+
+    /// Add or replace the models listed in a TOML file
+    pub fn load_overrides_file(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let toml = std::fs::read_to_string(path)?;
+        self.load_overrides(&toml)
+    }
+
+    /// Add or replace a single model
+    pub fn register(&mut self, name: &str, info: ModelInfo) {
+        self.models.insert(name.to_lowercase(), info);
+    }
+
+    /// Capabilities and pricing of `provider`, if the model is known
+    pub fn lookup(&self, provider: &str) -> Option<ModelInfo> {
+        let name = provider.to_lowercase();
+        self.models
+            .get(&name)
+            .or_else(|| {
+                name.rsplit_once('/')
+                    .and_then(|(_, model)| self.models.get(model))
+            })
+            .cloned()
+    }
+
+    /// Names of all known models, lowercased
+    pub fn model_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.models.keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_known_and_unknown_models() {
+        let registry = ModelRegistry::bundled();
+
+        assert_eq!(registry.lookup("no-such-model"), None);
+
+        let pro = registry.lookup("gemini-2.5-pro").unwrap();
+        assert_eq!(pro.max_context_tokens, 1_048_576);
+        assert!(pro.supports_tools);
+        assert!(pro.supports_streaming);
+        assert_eq!(pro.input_price, 0.00125);
+        assert_eq!(pro.output_price, 0.01);
+
+        // Case and a provider prefix don't matter
+        let r1 = registry.lookup("deepseek/DeepSeek-R1-0528").unwrap();
+        assert_eq!(r1.max_context_tokens, 131_072);
+    }
+
+    #[test]
+    fn test_overrides_replace_bundled_entries() {
+        let mut registry = ModelRegistry::bundled();
+        registry
+            .load_overrides(
+                r#"
+                [models."gpt-4"]
+                max_context_tokens = 32768
+                supports_tools = true
+                supports_streaming = false
+                input_price = 0.06
+                output_price = 0.12
+                "#,
+            )
+            .unwrap();
+
+        let gpt4 = registry.lookup("gpt-4").unwrap();
+        assert_eq!(gpt4.max_context_tokens, 32768);
+        assert!(!gpt4.supports_streaming);
+        assert_eq!(gpt4.pricing().calculate_cost(1000, 1000), 0.18);
+
+        assert!(registry.load_overrides("[models.broken]\nmax_context_tokens = \"lots\"").is_err());
+    }
+}
@@ -87,6 +87,18 @@ impl PricingConfig {
         self.pricing.get(&key)
     }
     
+    /// Get pricing for a model when the provider isn't known
+    ///
+    /// Matches a key equal to `model` first, then any `provider/model` key.
+    pub fn pricing_for_model(&self, model: &str) -> Option<&TokenPricing> {
+        self.pricing.get(model).or_else(|| {
+            self.pricing
+                .iter()
+                .find(|(key, _)| key.rsplit_once('/').is_some_and(|(_, name)| name == model))
+                .map(|(_, pricing)| pricing)
+        })
+    }
+
     /// Calculate cost for a provider/model combination
     pub fn calculate_cost(&self, provider: &str, model: &str, input_tokens: u32, output_tokens: u32) -> Option<f64> {
         self.get_pricing(provider, model)
@@ -98,4 +110,22 @@ impl PricingConfig {
         let key = format!("{}/{}", provider, model);
         self.pricing.insert(key, pricing);
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pricing_for_model_without_provider() {
+        let mut config = PricingConfig::default();
+        config.set_pricing("acme", "rocket-1", TokenPricing {
+            input_price_per_1k: 1.0,
+            output_price_per_1k: 2.0,
+        });
+
+        let pricing = config.pricing_for_model("rocket-1").unwrap();
+        assert_eq!(pricing.calculate_cost(1000, 500), 2.0);
+        assert!(config.pricing_for_model("rocket").is_none());
+    }
+}
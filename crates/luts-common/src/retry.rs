@@ -0,0 +1,238 @@
+//! Retrying transient provider and storage errors with jittered exponential backoff
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+use tracing::warn;
+
+/// Categories of provider and storage errors worth retrying
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RetryableError {
+    /// 429 Too Many Requests
+    RateLimit,
+    /// The request or connection timed out
+    Timeout,
+    /// 5xx responses from the provider
+    ServerError,
+    /// The connection dropped, or the backend was briefly busy
+    Connection,
+}
+
+impl RetryableError {
+    /// Classify an error from its message
+    ///
+    /// genai reports HTTP failures with the status in the message, so this
+    /// matches on status codes and the usual reason phrases. Anything else,
+    /// including 4xx validation and query parse errors, is not retryable.
+    pub fn classify(error: &str) -> Option<Self> {
+        let error = error.to_lowercase();
+        let has_status = |codes: &[&str]| {
+            error
+                .split(|c: char| !c.is_ascii_digit())
+                .any(|number| codes.contains(&number))
+        };
+
+        if has_status(&["429"]) || error.contains("rate limit") || error.contains("too many requests") {
+            Some(RetryableError::RateLimit)
+        } else if error.contains("timed out") || error.contains("timeout") {
+            Some(RetryableError::Timeout)
+        } else if has_status(&["500", "502", "503", "504"])
+            || error.contains("internal server error")
+            || error.contains("bad gateway")
+            || error.contains("service unavailable")
+            || error.contains("overloaded")
+        {
+            Some(RetryableError::ServerError)
+        } else if ["connection", "unavailable", "can be retried", "conflict", "busy", "temporarily"]
+            .iter()
+            .any(|marker| error.contains(marker))
+        {
+            Some(RetryableError::Connection)
+        } else {
+            None
+        }
+    }
+}
+
+/// How failed provider and storage calls are retried
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetryConfig {
+    /// Retries after the first attempt (0 disables retrying)
+    pub max_retries: u32,
+    /// Delay before the first retry; doubles on each further retry
+    pub base_delay_ms: u64,
+    /// Upper bound for a single delay
+    pub max_delay_ms: u64,
+    /// Error categories that trigger a retry
+    pub retry_on: Vec<RetryableError>,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay_ms: 500,
+            max_delay_ms: 10_000,
+            retry_on: vec![
+                RetryableError::RateLimit,
+                RetryableError::Timeout,
+                RetryableError::ServerError,
+                RetryableError::Connection,
+            ],
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Never retry
+    pub fn disabled() -> Self {
+        Self {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    /// The category of `error` if this config retries it
+    pub fn retryable(&self, error: &impl Display) -> Option<RetryableError> {
+        RetryableError::classify(&error.to_string()).filter(|kind| self.retry_on.contains(kind))
+    }
+
+    /// Delay before retry number `retry` (0-based)
+    ///
+    /// The exponential delay is capped at `max_delay_ms`, then jittered to
+    /// somewhere between half and all of it so clients don't retry in lockstep.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let exponential = self
+            .base_delay_ms
+            .saturating_mul(1u64.checked_shl(retry).unwrap_or(u64::MAX));
+        let capped = exponential.min(self.max_delay_ms);
+        let jittered = capped / 2 + rand::random::<u64>() % (capped / 2 + 1);
+        Duration::from_millis(jittered)
+    }
+
+    /// Run `attempt` until it succeeds, fails permanently, or retries run out
+    ///
+    /// Returns the last error if every attempt fails.
+    pub async fn run<T, E, F, Fut>(&self, operation: &str, mut attempt: F) -> Result<T, E>
+    where
+        E: Display,
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut retries = 0;
+        loop {
+            match attempt().await {
+                Ok(value) => return Ok(value),
+                Err(error) => {
+                    let Some(kind) = self.retryable(&error) else {
+                        return Err(error);
+                    };
+                    if retries >= self.max_retries {
+                        warn!("{} failed after {} retries: {}", operation, retries, error);
+                        return Err(error);
+                    }
+
+                    let delay = self.delay(retries);
+                    retries += 1;
+                    warn!(
+                        "{} hit a {:?} error, retry {}/{} in {}ms: {}",
+                        operation,
+                        kind,
+                        retries,
+                        self.max_retries,
+                        delay.as_millis(),
+                        error
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn test_classify_errors() {
+        assert_eq!(
+            RetryableError::classify("HTTP 429 Too Many Requests"),
+            Some(RetryableError::RateLimit)
+        );
+        assert_eq!(
+            RetryableError::classify("request timed out"),
+            Some(RetryableError::Timeout)
+        );
+        assert_eq!(
+            RetryableError::classify("status 503 Service Unavailable"),
+            Some(RetryableError::ServerError)
+        );
+        assert_eq!(
+            RetryableError::classify("connection reset by peer"),
+            Some(RetryableError::Connection)
+        );
+        assert_eq!(RetryableError::classify("400 Bad Request: invalid model"), None);
+        assert_eq!(RetryableError::classify("Parse error: unexpected token"), None);
+        assert_eq!(RetryableError::classify("400: max_tokens 5000 too large"), None);
+    }
+
+    #[test]
+    fn test_delay_is_capped_and_jittered() {
+        let config = RetryConfig {
+            base_delay_ms: 100,
+            max_delay_ms: 1_000,
+            ..Default::default()
+        };
+        for _ in 0..20 {
+            let first = config.delay(0).as_millis();
+            assert!((50..=100).contains(&first), "first delay {}", first);
+            let late = config.delay(40).as_millis();
+            assert!((500..=1_000).contains(&late), "late delay {}", late);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_retries_transient_errors_only() {
+        let config = RetryConfig {
+            base_delay_ms: 1,
+            max_delay_ms: 2,
+            ..Default::default()
+        };
+
+        let calls = &AtomicU32::new(0);
+        let result = config
+            .run("flaky", move || async move {
+                if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                    Err("503 Service Unavailable".to_string())
+                } else {
+                    Ok("done")
+                }
+            })
+            .await;
+        assert_eq!(result, Ok("done"));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let calls = &AtomicU32::new(0);
+        let result: Result<(), String> = config
+            .run("invalid", move || async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err("400 Bad Request".to_string())
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let calls = &AtomicU32::new(0);
+        let result: Result<(), String> = config
+            .run("overloaded", move || async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err("429 rate limited".to_string())
+            })
+            .await;
+        assert_eq!(result, Err("429 rate limited".to_string()));
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+}
@@ -146,6 +146,33 @@ impl PersonalityAgentBuilder {
         Ok(Box::new(PersonalityAgent::new(config, tools)?))
     }
 
+    /// Create a "Critic" agent - skeptical reviewer of other agents' work
+    pub fn create_critic(data_dir: &str, provider: &str) -> Result<Box<dyn Agent>, Error> {
+        let config = AgentConfig {
+            agent_id: "critic".to_string(),
+            name: "Sage".to_string(),
+            role: "critic".to_string(),
+            system_prompt: Some(
+                "You are Sage, a careful and constructive critic. You review answers, plans and code \
+                produced by other agents or the user. You excel at:\
+                \n- Finding factual errors, flawed reasoning and logical gaps\
+                \n- Spotting missing edge cases, unstated assumptions and failure modes\
+                \n- Checking that a response actually answers the question that was asked\
+                \n- Ranking issues by severity so the most important ones come first\
+                \n- Suggesting concrete fixes rather than only pointing out problems\
+                \n\nBe direct and specific: quote the part you are critiquing and explain why it is wrong or incomplete.\
+                \nIf the work holds up, say so plainly instead of inventing objections.".to_string()
+            ),
+            provider: provider.to_string(),
+            tool_names: vec![],
+            data_dir: data_dir.to_string(),
+        };
+
+        let tools = HashMap::new(); // Critic reviews by pure reasoning
+
+        Ok(Box::new(PersonalityAgent::new(config, tools)?))
+    }
+
     /// Create a "Coordinator" agent - organized, strategic, good at delegation
     pub fn create_coordinator(data_dir: &str, provider: &str) -> Result<Box<dyn Agent>, Error> {
         let config = AgentConfig {
@@ -271,6 +298,11 @@ impl PersonalityAgentBuilder {
                 "Strategic organizer and delegator",
             ),
             ("pragmatic", "Practical", "Efficient and solution-focused"),
+            (
+                "critic",
+                "Sage",
+                "Reviewer that finds flaws, gaps and missed edge cases",
+            ),
         ]
     }
 
@@ -286,8 +318,9 @@ impl PersonalityAgentBuilder {
             "creative" => Self::create_creative(data_dir, provider),
             "coordinator" => Self::create_coordinator(data_dir, provider),
             "pragmatic" => Self::create_pragmatic(data_dir, provider),
+            "critic" => Self::create_critic(data_dir, provider),
             _ => Err(anyhow!(
-                "Unknown personality type: {}. Available: researcher, calculator, creative, coordinator, pragmatic",
+                "Unknown personality type: {}. Available: researcher, calculator, creative, coordinator, pragmatic, critic",
                 personality
             )),
         }
@@ -2,11 +2,16 @@
 //!
 //! This module provides essential context blocks that should always be present
 //! in the AI context window, similar to Letta's core memory architecture.
+//! When a [`MemoryManager`] is attached, blocks are stored there as
+//! `BlockType::Custom` blocks tagged `core:<type>` and survive restarts.
 
-use crate::memory::{MemoryBlock, MemoryContent, BlockType, BlockId};
+use crate::memory::{MemoryBlock, MemoryContent, BlockType, BlockId, MemoryManager, MemoryQuery};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use tracing::{debug, warn};
 
 /// Types of core context blocks that persist across conversations
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -95,6 +100,18 @@ impl CoreBlockType {
         }
     }
     
+    /// Tag identifying a stored memory block as this core block
+    pub fn storage_tag(&self) -> String {
+        format!("core:{:?}", self)
+    }
+
+    /// Core block type a stored memory block belongs to, if any
+    pub fn from_memory_block(block: &MemoryBlock) -> Option<CoreBlockType> {
+        Self::all_types()
+            .into_iter()
+            .find(|core_type| block.tags().contains(&core_type.storage_tag()))
+    }
+
     /// Check if this core block type should be automatically created
     pub fn auto_create(&self) -> bool {
         match self {
@@ -142,12 +159,18 @@ impl CoreBlock {
         let content_text = content.unwrap_or_else(|| core_type.default_template().to_string());
         let memory_content = MemoryContent::Text(content_text);
         
-        let memory_block = MemoryBlock::new(
+        let mut memory_block = MemoryBlock::new(
             BlockType::Custom(core_type as u8),
             user_id,
             memory_content,
         );
-        
+        memory_block.add_tag(core_type.storage_tag());
+
+        Self::from_memory_block(core_type, memory_block)
+    }
+
+    /// Wrap a memory block, e.g. one loaded from storage, as a core block
+    pub fn from_memory_block(core_type: CoreBlockType, memory_block: MemoryBlock) -> Self {
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
@@ -240,6 +263,10 @@ pub struct CoreBlockConfig {
     
     /// Maximum number of core blocks to keep active
     pub max_active_blocks: usize,
+
+    /// Optional token budget for individual block types, within the total
+    #[serde(default)]
+    pub per_block_budgets: HashMap<CoreBlockType, u32>,
 }
 
 impl Default for CoreBlockConfig {
@@ -250,6 +277,7 @@ impl Default for CoreBlockConfig {
             auto_update_enabled: true,
             min_active_blocks: 3, // SystemPrompt, UserPersona, WorkingMemory
             max_active_blocks: 8, // All core block types
+            per_block_budgets: HashMap::new(),
         }
     }
 }
@@ -264,6 +292,9 @@ pub struct CoreBlockManager {
     
     /// User ID this manager belongs to
     user_id: String,
+
+    /// Store that blocks are loaded from and written through to
+    memory_manager: Option<Arc<MemoryManager>>,
 }
 
 impl CoreBlockManager {
@@ -273,11 +304,23 @@ impl CoreBlockManager {
             core_blocks: HashMap::new(),
             config: config.unwrap_or_default(),
             user_id: user_id.into(),
+            memory_manager: None,
         }
     }
+
+    /// Persist blocks in `memory_manager` instead of keeping them in memory only
+    pub fn with_memory_manager(mut self, memory_manager: Arc<MemoryManager>) -> Self {
+        self.memory_manager = Some(memory_manager);
+        self
+    }
     
-    /// Initialize core blocks with default templates
-    pub fn initialize(&mut self) -> Result<()> {
+    /// Initialize core blocks
+    ///
+    /// Blocks stored for the user are loaded first; default templates are
+    /// only used for types that have no stored block.
+    pub async fn initialize(&mut self) -> Result<()> {
+        self.load_stored_blocks().await?;
+
         if self.config.auto_create_missing {
             for core_type in CoreBlockType::all_types() {
                 if core_type.auto_create() && !self.core_blocks.contains_key(&core_type) {
@@ -288,6 +331,57 @@ impl CoreBlockManager {
         }
         Ok(())
     }
+
+    /// Replace in-memory blocks with the user's stored ones
+    async fn load_stored_blocks(&mut self) -> Result<()> {
+        let Some(memory_manager) = &self.memory_manager else {
+            return Ok(());
+        };
+
+        let query = MemoryQuery {
+            user_id: Some(self.user_id.clone()),
+            block_types: CoreBlockType::all_types()
+                .into_iter()
+                .map(|core_type| BlockType::Custom(core_type as u8))
+                .collect(),
+            ..Default::default()
+        };
+        let mut stored = memory_manager.search(&query).await?;
+        // If a block was somehow stored twice, the latest edit wins
+        stored.sort_by_key(|block| block.updated_at());
+        let stored_count = stored.len();
+
+        for block in stored {
+            if let Some(core_type) = CoreBlockType::from_memory_block(&block) {
+                let is_active = self
+                    .core_blocks
+                    .get(&core_type)
+                    .is_none_or(|existing| existing.is_active);
+                let mut core_block = CoreBlock::from_memory_block(core_type, block);
+                core_block.is_active = is_active;
+                self.core_blocks.insert(core_type, core_block);
+            }
+        }
+        debug!("Loaded {} stored core blocks for {}", stored_count, self.user_id);
+        Ok(())
+    }
+
+    /// Write a block through to the memory manager, if one is attached
+    async fn persist_block(&self, core_type: CoreBlockType) -> Result<()> {
+        let (Some(memory_manager), Some(block)) =
+            (&self.memory_manager, self.core_blocks.get(&core_type))
+        else {
+            return Ok(());
+        };
+
+        let memory_block = block.memory_block.clone();
+        if memory_manager.get(memory_block.id()).await?.is_some() {
+            memory_manager.update(memory_block.id(), memory_block.clone()).await?;
+        } else {
+            memory_manager.store(memory_block).await?;
+        }
+        Ok(())
+    }
     
     /// Get a core block by type
     pub fn get_block(&mut self, core_type: CoreBlockType) -> Option<&mut CoreBlock> {
@@ -300,14 +394,52 @@ impl CoreBlockManager {
     }
     
     /// Update or create a core block
-    pub fn update_block(&mut self, core_type: CoreBlockType, content: String) -> Result<()> {
+    ///
+    /// The content is always saved, and written through to the memory manager
+    /// if one is attached; a warning is returned when it exceeds the block's
+    /// entry in `per_block_budgets`.
+    pub async fn update_block(
+        &mut self,
+        core_type: CoreBlockType,
+        content: String,
+    ) -> Result<Option<BlockBudgetWarning>> {
         if let Some(block) = self.core_blocks.get_mut(&core_type) {
             block.update_content(content)?;
         } else {
             let core_block = CoreBlock::new(core_type, &self.user_id, Some(content));
             self.core_blocks.insert(core_type, core_block);
         }
-        Ok(())
+        self.persist_block(core_type).await?;
+
+        let warning = self.budget_for(core_type).and_then(|budget| {
+            budget.is_over_budget().then(|| BlockBudgetWarning {
+                core_type,
+                used_tokens: budget.used_tokens,
+                allotted_tokens: budget.allotted_tokens.unwrap_or_default(),
+            })
+        });
+        if let Some(warning) = &warning {
+            warn!("{}", warning);
+        }
+        Ok(warning)
+    }
+
+    /// Per-block token usage against `per_block_budgets`, by priority
+    pub fn budget_report(&self) -> Vec<BlockBudget> {
+        let mut core_types: Vec<_> = self.core_blocks.keys().copied().collect();
+        core_types.sort_by_key(|core_type| core_type.priority());
+        core_types
+            .into_iter()
+            .filter_map(|core_type| self.budget_for(core_type))
+            .collect()
+    }
+
+    fn budget_for(&self, core_type: CoreBlockType) -> Option<BlockBudget> {
+        self.core_blocks.get(&core_type).map(|block| BlockBudget {
+            core_type,
+            used_tokens: estimate_block_tokens(block),
+            allotted_tokens: self.config.per_block_budgets.get(&core_type).copied(),
+        })
     }
     
     /// Get all active core blocks sorted by priority
@@ -322,8 +454,12 @@ impl CoreBlockManager {
     }
     
     /// Get core blocks formatted for AI context
-    pub fn format_for_context(&mut self) -> String {
-        let active_blocks = self.get_active_blocks();
+    pub fn format_for_context(&self) -> String {
+        let mut active_blocks: Vec<_> = self.core_blocks
+            .values()
+            .filter(|block| block.is_active)
+            .collect();
+        active_blocks.sort_by_key(|block| block.core_type.priority());
         let mut context = String::new();
         
         for block in active_blocks {
@@ -343,11 +479,7 @@ impl CoreBlockManager {
         self.core_blocks
             .values()
             .filter(|block| block.is_active)
-            .map(|block| {
-                block.get_text_content()
-                    .map(|content| content.len() as u32 / 4) // Rough token estimation
-                    .unwrap_or(0)
-            })
+            .map(estimate_block_tokens)
             .sum()
     }
     
@@ -423,6 +555,56 @@ impl CoreBlockManager {
     }
 }
 
+/// Rough token estimation for a block's text content
+fn estimate_block_tokens(block: &CoreBlock) -> u32 {
+    block.get_text_content()
+        .map(|content| content.len() as u32 / 4)
+        .unwrap_or(0)
+}
+
+/// Token usage of one core block against its budget
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockBudget {
+    /// Block type
+    pub core_type: CoreBlockType,
+
+    /// Tokens the block's content currently uses
+    pub used_tokens: u32,
+
+    /// Tokens allotted in `per_block_budgets`, if any
+    pub allotted_tokens: Option<u32>,
+}
+
+impl BlockBudget {
+    /// Whether the block uses more than its allotted tokens
+    pub fn is_over_budget(&self) -> bool {
+        self.allotted_tokens.is_some_and(|allotted| self.used_tokens > allotted)
+    }
+}
+
+/// Returned by [`CoreBlockManager::update_block`] when a block outgrows its budget
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockBudgetWarning {
+    /// Block that is over budget
+    pub core_type: CoreBlockType,
+
+    /// Tokens the block's content uses
+    pub used_tokens: u32,
+
+    /// Tokens allotted to the block
+    pub allotted_tokens: u32,
+}
+
+impl fmt::Display for BlockBudgetWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Core block {:?} uses {} tokens, over its budget of {}",
+            self.core_type, self.used_tokens, self.allotted_tokens
+        )
+    }
+}
+
 /// Statistics about core block usage
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CoreBlockStats {
@@ -445,6 +627,7 @@ pub struct CoreBlockStats {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::memory::{SurrealConfig, SurrealMemoryStore};
 
     #[test]
     fn test_core_block_creation() {
@@ -454,10 +637,10 @@ mod tests {
         assert!(block.get_text_content().is_some());
     }
 
-    #[test]
-    fn test_core_block_manager() {
+    #[tokio::test]
+    async fn test_core_block_manager() {
         let mut manager = CoreBlockManager::new("user1", None);
-        manager.initialize().unwrap();
+        manager.initialize().await.unwrap();
         
         // Should have auto-created core blocks
         assert!(!manager.core_blocks.is_empty());
@@ -466,7 +649,7 @@ mod tests {
         manager.update_block(
             CoreBlockType::UserPersona, 
             "User is a software developer interested in AI".to_string()
-        ).unwrap();
+        ).await.unwrap();
         
         let context = manager.format_for_context();
         assert!(context.contains("software developer"));
@@ -477,4 +660,85 @@ mod tests {
         assert!(CoreBlockType::SystemPrompt.priority() < CoreBlockType::WorkingMemory.priority());
         assert!(CoreBlockType::UserPersona.priority() < CoreBlockType::ActiveGoals.priority());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_per_block_budget_warning() {
+        let mut config = CoreBlockConfig::default();
+        config.per_block_budgets.insert(CoreBlockType::SystemPrompt, 10);
+        let mut manager = CoreBlockManager::new("user1", Some(config));
+        manager.initialize().await.unwrap();
+
+        // 20 characters is about 5 tokens
+        let warning = manager
+            .update_block(CoreBlockType::SystemPrompt, "Be brief and helpful".to_string())
+            .await
+            .unwrap();
+        assert!(warning.is_none());
+
+        // 80 characters is about 20 tokens; still saved, but flagged
+        let warning = manager
+            .update_block(CoreBlockType::SystemPrompt, "x".repeat(80))
+            .await
+            .unwrap()
+            .expect("over-budget update should warn");
+        assert_eq!(
+            warning,
+            BlockBudgetWarning {
+                core_type: CoreBlockType::SystemPrompt,
+                used_tokens: 20,
+                allotted_tokens: 10,
+            }
+        );
+        assert!(manager.format_for_context().contains(&"x".repeat(80)));
+
+        // Blocks without a budget are reported but never over it
+        let report = manager.budget_report();
+        assert_eq!(report[0].core_type, CoreBlockType::SystemPrompt);
+        assert!(report[0].is_over_budget());
+        assert!(report[1..].iter().all(|budget| {
+            budget.allotted_tokens.is_none() && !budget.is_over_budget()
+        }));
+        assert!(manager
+            .update_block(CoreBlockType::UserPersona, "y".repeat(400))
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_edits_survive_a_fresh_manager() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let store = SurrealMemoryStore::new(SurrealConfig::File {
+            path: temp_dir.path().join("core.db"),
+            namespace: "test".to_string(),
+            database: "memory".to_string(),
+        })
+        .await
+        .unwrap();
+        store.initialize_schema_with_dimensions(384).await.unwrap();
+        let memory_manager = Arc::new(MemoryManager::new(store));
+
+        let mut manager = CoreBlockManager::new("user1", None)
+            .with_memory_manager(memory_manager.clone());
+        manager.initialize().await.unwrap();
+        manager
+            .update_block(CoreBlockType::UserPersona, "Prefers answers in Rust".to_string())
+            .await
+            .unwrap();
+        manager
+            .update_block(CoreBlockType::UserPersona, "Prefers answers in Go".to_string())
+            .await
+            .unwrap();
+
+        let mut fresh = CoreBlockManager::new("user1", None).with_memory_manager(memory_manager);
+        fresh.initialize().await.unwrap();
+        let persona = fresh.get_block(CoreBlockType::UserPersona).unwrap();
+        assert_eq!(persona.get_text_content(), Some("Prefers answers in Go"));
+        // Unedited blocks still come from their templates
+        let system_prompt = fresh.get_block(CoreBlockType::SystemPrompt).unwrap();
+        assert_eq!(
+            system_prompt.get_text_content(),
+            Some(CoreBlockType::SystemPrompt.default_template())
+        );
+    }
+}
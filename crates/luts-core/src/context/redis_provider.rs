@@ -1,72 +1,182 @@
 use crate::context::ContextProvider;
 use anyhow::{Error, Result};
 use async_trait::async_trait;
+use redis::aio::MultiplexedConnection;
+use redis::{AsyncCommands, RedisError, RedisResult};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+/// Connection settings for [`RedisContextProvider`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedisContextConfig {
+    /// Redis connection URL, e.g. `redis://127.0.0.1:6379`
+    pub url: String,
+    /// Namespace prefix for every key, joined to the ID with `:`
+    pub prefix: String,
+    /// Expiry applied to stored values; `None` keeps them until deleted
+    pub default_ttl: Option<Duration>,
+}
+
+impl Default for RedisContextConfig {
+    fn default() -> Self {
+        Self {
+            url: "redis://127.0.0.1:6379".to_string(),
+            prefix: "context".to_string(),
+            default_ttl: None,
+        }
+    }
+}
+
+/// Errors raised by [`RedisContextProvider`]
+#[derive(Debug)]
+pub enum RedisContextError {
+    /// The server couldn't be reached or dropped the connection; the next
+    /// call reconnects
+    Connection(String),
+    /// The server rejected a command
+    Command(String),
+    /// A stored value isn't valid JSON
+    Serialization(String),
+}
+
+impl fmt::Display for RedisContextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RedisContextError::Connection(msg) => write!(f, "Redis connection error: {}", msg),
+            RedisContextError::Command(msg) => write!(f, "Redis command error: {}", msg),
+            RedisContextError::Serialization(msg) => {
+                write!(f, "Redis value is not valid JSON: {}", msg)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RedisContextError {}
 
 /// RedisContextProvider implements the ContextProvider trait using Redis as the storage backend.
-/// This is a placeholder implementation that will be properly implemented in the future.
+///
+/// Values are stored as JSON strings. The connection is opened on first use
+/// and reopened on the call after it fails.
 pub struct RedisContextProvider {
-    namespace: String,
+    client: redis::Client,
+    config: RedisContextConfig,
+    connection: Mutex<Option<MultiplexedConnection>>,
 }
 
-#[allow(dead_code)]
 impl RedisContextProvider {
     /// Create a new RedisContextProvider
     ///
-    /// Note: This is a placeholder that will be implemented in the future.
-    pub fn new(_connection_string: &str) -> Result<Self> {
+    /// Only the URL is checked here; the server is first contacted on use.
+    pub fn new(config: RedisContextConfig) -> Result<Self> {
+        let client = redis::Client::open(config.url.as_str()).map_err(|e| {
+            RedisContextError::Connection(format!("invalid URL {}: {}", config.url, e))
+        })?;
         Ok(Self {
-            namespace: "context".to_string(),
+            client,
+            config,
+            connection: Mutex::new(None),
         })
     }
 
-    /// Create a new RedisContextProvider with a custom namespace
-    ///
-    /// Note: This is a placeholder that will be implemented in the future.
-    pub fn with_namespace(_connection_string: &str, namespace: &str) -> Result<Self> {
-        Ok(Self {
-            namespace: namespace.to_string(),
+    /// Store a value that expires after `ttl` instead of the default TTL
+    pub async fn store_with_ttl(
+        &self,
+        id: &str,
+        data: &Value,
+        ttl: Option<Duration>,
+    ) -> Result<()> {
+        let key = self.get_full_key(id);
+        let payload = serde_json::to_string(data)
+            .map_err(|e| RedisContextError::Serialization(e.to_string()))?;
+        self.run(|mut conn| async move {
+            match ttl {
+                // Redis expiries are whole seconds; round sub-second TTLs up
+                Some(ttl) => conn.set_ex(&key, payload, ttl.as_secs().max(1)).await,
+                None => conn.set(&key, payload).await,
+            }
         })
+        .await
     }
 
     /// Get the full key with namespace prefix
     fn get_full_key(&self, id: &str) -> String {
-        format!("{}:{}", self.namespace, id)
+        format!("{}:{}", self.config.prefix, id)
+    }
+
+    /// Run a command on the shared connection, connecting first if needed
+    async fn run<T, F, Fut>(&self, command: F) -> Result<T>
+    where
+        F: FnOnce(MultiplexedConnection) -> Fut,
+        Fut: Future<Output = RedisResult<T>>,
+    {
+        let connection = self.connection().await?;
+        match command(connection).await {
+            Ok(value) => Ok(value),
+            Err(e) if is_connection_error(&e) => {
+                // Drop the broken connection so the next call reconnects
+                *self.connection.lock().await = None;
+                warn!("Lost Redis connection to {}: {}", self.config.url, e);
+                Err(RedisContextError::Connection(e.to_string()).into())
+            }
+            Err(e) => Err(RedisContextError::Command(e.to_string()).into()),
+        }
+    }
+
+    async fn connection(&self) -> Result<MultiplexedConnection> {
+        let mut cached = self.connection.lock().await;
+        if let Some(ref connection) = *cached {
+            return Ok(connection.clone());
+        }
+
+        debug!("Connecting to Redis at {}", self.config.url);
+        let connection = self
+            .client
+            .get_multiplexed_async_connection()
+            .await
+            .map_err(|e| RedisContextError::Connection(e.to_string()))?;
+        *cached = Some(connection.clone());
+        Ok(connection)
     }
 }
 
+fn is_connection_error(error: &RedisError) -> bool {
+    error.is_io_error()
+        || error.is_connection_dropped()
+        || error.is_connection_refusal()
+        || error.is_timeout()
+}
+
 #[async_trait]
 impl ContextProvider for RedisContextProvider {
-    async fn store(&self, id: &str, _data: &Value) -> Result<(), Error> {
-        let key = self.get_full_key(id);
-        Err(anyhow::anyhow!(
-            "Redis provider not yet implemented for key: {}",
-            key
-        ))
+    async fn store(&self, id: &str, data: &Value) -> Result<(), Error> {
+        self.store_with_ttl(id, data, self.config.default_ttl).await
     }
 
     async fn retrieve(&self, id: &str) -> Result<Option<Value>, Error> {
         let key = self.get_full_key(id);
-        Err(anyhow::anyhow!(
-            "Redis provider not yet implemented for key: {}",
-            key
-        ))
+        let payload: Option<String> =
+            self.run(|mut conn| async move { conn.get(&key).await }).await?;
+        payload
+            .map(|payload| {
+                serde_json::from_str(&payload)
+                    .map_err(|e| RedisContextError::Serialization(e.to_string()).into())
+            })
+            .transpose()
     }
 
     async fn delete(&self, id: &str) -> Result<(), Error> {
         let key = self.get_full_key(id);
-        Err(anyhow::anyhow!(
-            "Redis provider not yet implemented for key: {}",
-            key
-        ))
+        self.run(|mut conn| async move { conn.del(&key).await }).await
     }
 
     async fn exists(&self, id: &str) -> Result<bool, Error> {
         let key = self.get_full_key(id);
-        Err(anyhow::anyhow!(
-            "Redis provider not yet implemented for key: {}",
-            key
-        ))
+        self.run(|mut conn| async move { conn.exists(&key).await }).await
     }
 
     fn name(&self) -> &str {
@@ -79,9 +189,43 @@ mod tests {
     use super::*;
 
     #[tokio::test]
-    #[ignore = "Redis provider not yet implemented"]
-    async fn test_redis_provider() -> Result<()> {
-        // This test will be implemented when the Redis provider is fully implemented
-        Ok(())
+    async fn test_unreachable_server_is_a_connection_error() {
+        // Nothing listens on port 1
+        let provider = RedisContextProvider::new(RedisContextConfig {
+            url: "redis://127.0.0.1:1".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+
+        for _ in 0..2 {
+            let err = provider.exists("missing").await.unwrap_err();
+            assert!(matches!(
+                err.downcast_ref::<RedisContextError>(),
+                Some(RedisContextError::Connection(_))
+            ));
+        }
+    }
+
+    /// Needs a Redis server; run with `cargo test -p luts-core --features redis-tests`
+    #[cfg(feature = "redis-tests")]
+    #[tokio::test]
+    async fn test_round_trip_against_local_redis() {
+        let provider = RedisContextProvider::new(RedisContextConfig {
+            url: std::env::var("REDIS_URL")
+                .unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string()),
+            prefix: format!("luts_test_{}", uuid::Uuid::new_v4()),
+            default_ttl: Some(Duration::from_secs(60)),
+        })
+        .unwrap();
+        let value = serde_json::json!({ "topic": "rust", "turns": [1, 2, 3] });
+
+        assert!(!provider.exists("session").await.unwrap());
+        provider.store("session", &value).await.unwrap();
+        assert!(provider.exists("session").await.unwrap());
+        assert_eq!(provider.retrieve("session").await.unwrap(), Some(value));
+
+        provider.delete("session").await.unwrap();
+        assert!(!provider.exists("session").await.unwrap());
+        assert_eq!(provider.retrieve("session").await.unwrap(), None);
     }
 }
@@ -3,12 +3,20 @@
 //! This module provides intelligent context window management, automatically
 //! selecting and organizing memory blocks for optimal AI performance.
 
-use crate::context::core_blocks::{CoreBlockManager, CoreBlockType, CoreBlockConfig, CoreBlockStats};
-use crate::memory::{MemoryManager, MemoryBlock, MemoryQuery, QuerySort};
+use crate::context::core_blocks::{
+    BlockBudgetWarning, CoreBlockConfig, CoreBlockManager, CoreBlockStats, CoreBlockType,
+};
+use crate::conversation::summarization::ConversationSummarizer;
+use crate::llm::InternalChatMessage;
+use crate::memory::{
+    BlockId, EmbeddingService, MemoryManager, MemoryBlock, MemoryQuery, QuerySort, VectorSimilarity,
+};
+use crate::utils::tokenizer::TokenCounter;
 use crate::utils::tokens::TokenManager;
 use anyhow::Result;
+use luts_common::ModelRegistry;
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
@@ -39,6 +47,19 @@ pub struct ContextWindowConfig {
 
     /// Update frequency for context management (in seconds)
     pub update_interval: u64,
+
+    /// What to do when the assembled window exceeds `max_total_tokens`
+    #[serde(default)]
+    pub overflow_policy: OverflowPolicy,
+
+    /// Relevance versus diversity for [`SelectionStrategy::Diversified`]:
+    /// 1.0 ranks by relevance alone, 0.0 by dissimilarity alone
+    #[serde(default = "default_diversity_lambda")]
+    pub diversity_lambda: f32,
+}
+
+fn default_diversity_lambda() -> f32 {
+    0.7
 }
 
 impl Default for ContextWindowConfig {
@@ -52,8 +73,89 @@ impl Default for ContextWindowConfig {
             min_relevance_score: 0.3,
             auto_manage: true,
             update_interval: 30, // Update every 30 seconds
+            overflow_policy: OverflowPolicy::default(),
+            diversity_lambda: default_diversity_lambda(),
+        }
+    }
+}
+
+impl ContextWindowConfig {
+    /// Default config sized to `provider`'s context window
+    ///
+    /// Models the registry doesn't know keep the default size.
+    pub fn for_model(provider: &str, models: &ModelRegistry) -> Self {
+        match models.lookup(provider) {
+            Some(info) => Self::default().with_context_size(info.max_context_tokens),
+            None => Self::default(),
         }
     }
+
+    /// Resize the window to `max_tokens`
+    ///
+    /// The core block, conversation and dynamic memory budgets keep their
+    /// share of the window.
+    pub fn with_context_size(mut self, max_tokens: usize) -> Self {
+        let max_tokens = u32::try_from(max_tokens).unwrap_or(u32::MAX);
+        let old_max = self.max_total_tokens.max(1) as u64;
+        let scale = |budget: u32| (budget as u64 * max_tokens as u64 / old_max) as u32;
+        self.core_block_tokens = scale(self.core_block_tokens);
+        self.conversation_tokens = scale(self.conversation_tokens);
+        self.dynamic_memory_tokens = scale(self.dynamic_memory_tokens);
+        self.max_total_tokens = max_tokens;
+        self
+    }
+}
+
+/// How to bring a context window back under `max_total_tokens`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum OverflowPolicy {
+    /// Drop the oldest conversation messages, then the last-selected memories
+    #[default]
+    Truncate,
+
+    /// Collapse the oldest conversation turns into a summary, then truncate
+    /// whatever still doesn't fit
+    SummarizeOldest,
+
+    /// Drop the least relevant memories first, then truncate
+    DropLowestRelevance,
+}
+
+/// Prefix of the conversation entry that replaces summarized turns
+const SUMMARY_PREFIX: &str = "Summary of earlier conversation: ";
+
+/// Summarizes the oldest conversation turns for [`OverflowPolicy::SummarizeOldest`]
+#[async_trait]
+pub trait HistorySummarizer: Send + Sync {
+    /// Summarize `messages`, oldest first
+    async fn summarize_history(
+        &self,
+        messages: &[String],
+        user_id: &str,
+        session_id: &str,
+    ) -> Result<String>;
+}
+
+#[async_trait]
+impl HistorySummarizer for ConversationSummarizer {
+    async fn summarize_history(
+        &self,
+        messages: &[String],
+        user_id: &str,
+        session_id: &str,
+    ) -> Result<String> {
+        // The window keeps history as plain text, so roles are already inlined
+        let messages: Vec<InternalChatMessage> = messages
+            .iter()
+            .map(|content| InternalChatMessage::User {
+                content: content.clone(),
+            })
+            .collect();
+        let summary = self
+            .summarize_conversation(&messages, user_id, session_id)
+            .await?;
+        Ok(summary.summary_text)
+    }
 }
 
 /// Strategy for selecting dynamic memory blocks
@@ -71,7 +173,8 @@ pub enum SelectionStrategy {
     /// Select by access frequency
     ByFrequency,
 
-    /// Diversified selection (try to include different types)
+    /// Relevant but mutually dissimilar blocks (Maximal Marginal Relevance);
+    /// needs an embedding service, otherwise the same as `ByRelevance`
     Diversified,
 }
 
@@ -93,11 +196,11 @@ pub struct ContextMemoryBlock {
     /// Estimated token count
     pub estimated_tokens: u32,
 
-    /// Last access time
+    /// Last time the block was included in a context window (0 if never)
     pub last_accessed: u64,
 
-    /// Access frequency counter
-    pub access_count: u32,
+    /// Times the block has been included in a context window
+    pub access_count: u64,
 }
 
 /// Context window state and contents
@@ -120,6 +223,9 @@ pub struct ContextWindow {
 
     /// Last update timestamp
     pub last_updated: u64,
+
+    /// Tokens removed to bring the window under its budget
+    pub tokens_reclaimed: u32,
 }
 
 /// Token usage breakdown for context window
@@ -153,12 +259,12 @@ pub struct ContextWindowManager {
     /// Configuration
     config: ContextWindowConfig,
 
+    /// Counts tokens with the model's tokenizer
+    counter: TokenCounter,
+
     /// Current context window state
     current_context: Arc<RwLock<Option<ContextWindow>>>,
 
-    /// Memory block access tracking
-    access_tracking: Arc<RwLock<HashMap<String, (u32, u64)>>>, // (access_count, last_accessed)
-
     /// Selection strategy
     strategy: SelectionStrategy,
 
@@ -166,15 +272,26 @@ pub struct ContextWindowManager {
     user_id: String,
 
     /// Session ID
-    #[allow(dead_code)]
     session_id: String,
+
+    /// Summarizer used by [`OverflowPolicy::SummarizeOldest`]
+    summarizer: Option<Arc<dyn HistorySummarizer>>,
+
+    /// Embeds candidate blocks for [`SelectionStrategy::Diversified`]
+    embedding_service: Option<Arc<dyn EmbeddingService>>,
 }
 
 impl ContextWindowManager {
     /// Create a new context window manager
-    pub fn new(
+    ///
+    /// Without a `config` the window is sized to `provider`'s context from
+    /// the bundled model registry, or the default size for unknown models.
+    /// Core blocks are stored in `memory_manager`, so edits from an earlier
+    /// run are loaded here.
+    pub async fn new(
         user_id: impl Into<String>,
         session_id: impl Into<String>,
+        provider: &str,
         memory_manager: Arc<MemoryManager>,
         token_manager: Arc<RwLock<TokenManager>>,
         config: Option<ContextWindowConfig>,
@@ -182,10 +299,12 @@ impl ContextWindowManager {
     ) -> Self {
         let user_id = user_id.into();
         let session_id = session_id.into();
-        let config = config.unwrap_or_default();
+        let config = config
+            .unwrap_or_else(|| ContextWindowConfig::for_model(provider, &ModelRegistry::bundled()));
 
-        let mut core_manager = CoreBlockManager::new(&user_id, core_config);
-        core_manager.initialize().unwrap_or_else(|e| {
+        let mut core_manager = CoreBlockManager::new(&user_id, core_config)
+            .with_memory_manager(memory_manager.clone());
+        core_manager.initialize().await.unwrap_or_else(|e| {
             warn!("Failed to initialize core blocks: {}", e);
         });
 
@@ -194,18 +313,85 @@ impl ContextWindowManager {
             memory_manager,
             token_manager,
             config,
+            counter: TokenCounter::for_model(provider),
             current_context: Arc::new(RwLock::new(None)),
-            access_tracking: Arc::new(RwLock::new(HashMap::new())),
             strategy: SelectionStrategy::default(),
             user_id,
             session_id,
+            summarizer: None,
+            embedding_service: None,
         }
     }
 
+    /// The window's size and budgets
+    pub fn config(&self) -> &ContextWindowConfig {
+        &self.config
+    }
+
+    /// Set the summarizer used when the overflow policy is `SummarizeOldest`
+    pub fn set_summarizer(&mut self, summarizer: Arc<dyn HistorySummarizer>) {
+        self.summarizer = Some(summarizer);
+    }
+
+    /// Set the embedding service used to compare blocks for `Diversified` selection
+    pub fn set_embedding_service(&mut self, embedding_service: Arc<dyn EmbeddingService>) {
+        self.embedding_service = Some(embedding_service);
+    }
+
     /// Update the context window with current conversation and memory
     pub async fn update_context(&mut self, conversation_history: Vec<String>) -> Result<()> {
         info!("Updating context window for user: {}", self.user_id);
 
+        let context_window = self.assemble_context(conversation_history, true).await?;
+        self.record_accesses(&context_window).await;
+
+        // Update current context
+        let mut current = self.current_context.write().await;
+        *current = Some(context_window);
+
+        debug!("Context window updated. Total tokens: {}", current.as_ref().unwrap().total_tokens);
+
+        Ok(())
+    }
+
+    /// Preview the prompt a turn with `user_message` would get, without changing state
+    ///
+    /// Runs the same selection and assembly as [`Self::update_context`], with
+    /// `user_message` appended to the current conversation, and returns the
+    /// formatted prompt with its token breakdown. The current context window is
+    /// left untouched and no model is called, so an over-budget conversation is
+    /// truncated rather than summarized.
+    pub async fn simulate(&self, user_message: &str) -> Result<(String, ContextWindowStats)> {
+        let mut conversation_history = self
+            .current_context
+            .read()
+            .await
+            .as_ref()
+            .map(|context| context.conversation_history.clone())
+            .unwrap_or_default();
+        conversation_history.push(user_message.to_string());
+
+        let context_window = self.assemble_context(conversation_history, false).await?;
+        debug!(
+            "Simulated context window for user {}: {} tokens",
+            self.user_id, context_window.total_tokens
+        );
+
+        Ok((
+            Self::format_context(&context_window),
+            self.stats_for(Some(&context_window)),
+        ))
+    }
+
+    /// Build a context window for `conversation_history`
+    ///
+    /// The window is then fitted to `max_total_tokens`; `allow_summarize`
+    /// controls whether that may call the summarizer.
+    async fn assemble_context(
+        &self,
+        conversation_history: Vec<String>,
+        allow_summarize: bool,
+    ) -> Result<ContextWindow> {
         // Get core blocks content
         let core_content = self.core_manager.format_for_context();
         let core_tokens = self.estimate_tokens(&core_content);
@@ -225,8 +411,7 @@ impl ContextWindowManager {
         let dynamic_blocks = self.select_dynamic_blocks(available_tokens).await?;
         let dynamic_tokens = dynamic_blocks.iter().map(|b| b.estimated_tokens).sum::<u32>();
 
-        // Create context window
-        let context_window = ContextWindow {
+        let mut context_window = ContextWindow {
             core_blocks_content: core_content,
             conversation_history,
             dynamic_blocks,
@@ -241,19 +426,123 @@ impl ContextWindowManager {
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
                 .as_millis() as u64,
+            tokens_reclaimed: 0,
         };
+        self.fit_to_budget(&mut context_window, allow_summarize).await;
 
-        // Update current context
-        let mut current = self.current_context.write().await;
-        *current = Some(context_window);
+        Ok(context_window)
+    }
 
-        debug!("Context window updated. Total tokens: {}", current.as_ref().unwrap().total_tokens);
+    /// Apply the overflow policy until the window fits `max_total_tokens`
+    ///
+    /// Core blocks and the latest message are never dropped, so a window can
+    /// stay over budget when those alone exceed it.
+    async fn fit_to_budget(&self, context: &mut ContextWindow, allow_summarize: bool) {
+        let budget = self.config.max_total_tokens;
+        if context.total_tokens <= budget {
+            return;
+        }
+        let before = context.total_tokens;
 
+        match self.config.overflow_policy {
+            OverflowPolicy::Truncate => {}
+            OverflowPolicy::SummarizeOldest if allow_summarize => {
+                if let Err(e) = self.summarize_oldest(context).await {
+                    warn!("Failed to summarize old conversation, truncating instead: {}", e);
+                }
+            }
+            OverflowPolicy::SummarizeOldest => {}
+            OverflowPolicy::DropLowestRelevance => {
+                while context.total_tokens > budget && !context.dynamic_blocks.is_empty() {
+                    let lowest = context
+                        .dynamic_blocks
+                        .iter()
+                        .enumerate()
+                        .min_by(|(_, a), (_, b)| a.relevance_score.total_cmp(&b.relevance_score))
+                        .map(|(index, _)| index)
+                        .unwrap();
+                    context.dynamic_blocks.remove(lowest);
+                    self.recount(context);
+                }
+            }
+        }
+
+        // Whatever the policy left over budget is truncated
+        while context.total_tokens > budget && context.conversation_history.len() > 1 {
+            context.conversation_history.remove(0);
+            self.recount(context);
+        }
+        while context.total_tokens > budget && context.dynamic_blocks.pop().is_some() {
+            self.recount(context);
+        }
+
+        context.tokens_reclaimed = before.saturating_sub(context.total_tokens);
+        info!(
+            "Context window over budget by {} tokens; reclaimed {} with {:?}",
+            before - budget,
+            context.tokens_reclaimed,
+            self.config.overflow_policy
+        );
+    }
+
+    /// Replace the oldest conversation turns with a summary
+    ///
+    /// The newest turns are kept verbatim as long as they fit in half the
+    /// room left after core blocks and memories.
+    async fn summarize_oldest(&self, context: &mut ContextWindow) -> Result<()> {
+        let summarizer = self
+            .summarizer
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("no summarizer configured"))?;
+        let history = &context.conversation_history;
+        if history.len() < 2 {
+            return Ok(());
+        }
+
+        let room = self.config.max_total_tokens.saturating_sub(
+            context.token_breakdown.core_blocks + context.token_breakdown.dynamic_memory,
+        );
+        let mut split = history.len() - 1; // The latest message always stays
+        let mut kept_tokens = self.estimate_tokens(&history[split]);
+        while split > 0 && kept_tokens + self.estimate_tokens(&history[split - 1]) <= room / 2 {
+            split -= 1;
+            kept_tokens += self.estimate_tokens(&history[split]);
+        }
+        if split == 0 {
+            return Ok(());
+        }
+
+        let summary = summarizer
+            .summarize_history(&history[..split], &self.user_id, &self.session_id)
+            .await?;
+        debug!("Summarized {} old conversation messages", split);
+
+        let mut compressed = vec![format!("{}{}", SUMMARY_PREFIX, summary.trim())];
+        compressed.extend_from_slice(&history[split..]);
+        context.conversation_history = compressed;
+        self.recount(context);
         Ok(())
     }
 
+    /// Recompute token totals after the window's contents changed
+    fn recount(&self, context: &mut ContextWindow) {
+        let conversation = context
+            .conversation_history
+            .iter()
+            .map(|msg| self.estimate_tokens(msg))
+            .sum::<u32>();
+        let dynamic_memory = context.dynamic_blocks.iter().map(|b| b.estimated_tokens).sum::<u32>();
+
+        context.token_breakdown.conversation = conversation;
+        context.token_breakdown.dynamic_memory = dynamic_memory;
+        context.token_breakdown.total = context.token_breakdown.core_blocks + conversation + dynamic_memory;
+        context.total_tokens = context.token_breakdown.total;
+    }
+
     /// Select dynamic memory blocks based on strategy and available tokens
-    async fn select_dynamic_blocks(&mut self, available_tokens: u32) -> Result<Vec<ContextMemoryBlock>> {
+    ///
+    /// Blocks without a stored relevance score are left out.
+    async fn select_dynamic_blocks(&self, available_tokens: u32) -> Result<Vec<ContextMemoryBlock>> {
         let query = MemoryQuery {
             user_id: Some(self.user_id.clone()),
             session_id: None,
@@ -267,6 +556,10 @@ impl ContextWindowManager {
         };
 
         let candidate_blocks = self.memory_manager.search(&query).await?;
+        // Stored core blocks are already part of the window
+        let candidate_blocks = candidate_blocks
+            .into_iter()
+            .filter(|block| CoreBlockType::from_memory_block(block).is_none());
         let mut context_blocks = Vec::new();
         let mut used_tokens = 0u32;
 
@@ -274,17 +567,17 @@ impl ContextWindowManager {
         let mut candidates: Vec<ContextMemoryBlock> = candidate_blocks
             .into_iter()
             .filter_map(|block| {
-                let content_len = block.content.as_text()?.len();
-                let estimated_tokens = (content_len as f32 / 4.0).ceil() as u32;
+                let text = block.content.as_text()?;
+                let estimated_tokens = (text.len() as f32 / 4.0).ceil() as u32;
                 let relevance = block.metadata.relevance?.score();
 
                 if relevance >= self.config.min_relevance_score {
                     Some(ContextMemoryBlock {
+                        last_accessed: block.last_accessed_at().unwrap_or(0),
+                        access_count: block.access_count(),
                         block,
                         relevance_score: relevance,
                         estimated_tokens,
-                        last_accessed: 0, // Will be updated from tracking
-                        access_count: 0,  // Will be updated from tracking
                     })
                 } else {
                     None
@@ -292,17 +585,12 @@ impl ContextWindowManager {
             })
             .collect();
 
-        // Update access tracking info
-        let tracking = self.access_tracking.read().await;
-        for context_block in &mut candidates {
-            if let Some((count, last_accessed)) = tracking.get(context_block.block.id().as_str()) {
-                context_block.access_count = *count;
-                context_block.last_accessed = *last_accessed;
-            }
-        }
-
         // Sort by strategy
-        self.sort_candidates_by_strategy(&mut candidates);
+        if self.strategy == SelectionStrategy::Diversified {
+            candidates = self.diversify(candidates).await;
+        } else {
+            self.sort_candidates_by_strategy(&mut candidates);
+        }
 
         // Select blocks within token budget
         for candidate in candidates {
@@ -326,10 +614,13 @@ impl ContextWindowManager {
                 candidates.sort_by(|a, b| b.relevance_score.partial_cmp(&a.relevance_score).unwrap());
             },
             SelectionStrategy::ByRecency => {
-                candidates.sort_by(|a, b| b.block.metadata.updated_at.cmp(&a.block.metadata.updated_at));
+                // Blocks never used in a window fall back to when they were last edited
+                candidates.sort_by_key(|c| {
+                    std::cmp::Reverse((c.last_accessed, c.block.metadata.updated_at))
+                });
             },
             SelectionStrategy::ByFrequency => {
-                candidates.sort_by(|a, b| b.access_count.cmp(&a.access_count));
+                candidates.sort_by_key(|c| std::cmp::Reverse((c.access_count, c.last_accessed)));
             },
             SelectionStrategy::Balanced => {
                 // Combine relevance and recency with weights
@@ -342,55 +633,113 @@ impl ContextWindowManager {
                 });
             },
             SelectionStrategy::Diversified => {
-                // Sort by relevance first, then try to diversify by block type
+                // Only reached without embeddings; see `diversify`
                 candidates.sort_by(|a, b| b.relevance_score.partial_cmp(&a.relevance_score).unwrap());
-
-                // TODO: Implement type-based diversification
-                // This would require tracking block types and ensuring variety
             },
         }
     }
 
+    /// Order candidates by Maximal Marginal Relevance
+    ///
+    /// Each pick maximizes `λ·relevance - (1-λ)·similarity` to the blocks
+    /// already picked, so near-duplicates sink below blocks that add something
+    /// new. Falls back to relevance order when the blocks can't be embedded.
+    async fn diversify(&self, mut candidates: Vec<ContextMemoryBlock>) -> Vec<ContextMemoryBlock> {
+        let Some(mut embeddings) = self.embed_candidates(&candidates).await else {
+            self.sort_candidates_by_strategy(&mut candidates);
+            return candidates;
+        };
+
+        let lambda = self.config.diversity_lambda.clamp(0.0, 1.0);
+        let mut picked: Vec<(ContextMemoryBlock, Vec<f32>)> = Vec::with_capacity(candidates.len());
+        while !candidates.is_empty() {
+            let score = |index: usize| {
+                let redundancy = picked
+                    .iter()
+                    .map(|(_, embedding)| {
+                        VectorSimilarity::cosine_similarity(&embeddings[index], embedding)
+                            .unwrap_or(0.0)
+                    })
+                    .fold(0.0, f32::max);
+                lambda * candidates[index].relevance_score - (1.0 - lambda) * redundancy
+            };
+            let best = (0..candidates.len())
+                .max_by(|&a, &b| score(a).total_cmp(&score(b)))
+                .unwrap();
+            picked.push((candidates.remove(best), embeddings.remove(best)));
+        }
+        picked.into_iter().map(|(candidate, _)| candidate).collect()
+    }
+
+    /// Embeddings of the candidates' text, or `None` if there is no embedding service or it failed
+    async fn embed_candidates(&self, candidates: &[ContextMemoryBlock]) -> Option<Vec<Vec<f32>>> {
+        let embedding_service = self.embedding_service.as_ref()?;
+        let texts: Vec<String> = candidates
+            .iter()
+            .map(|candidate| candidate.block.content.as_text().unwrap_or_default().to_string())
+            .collect();
+
+        match embedding_service.embed_texts(&texts).await {
+            Ok(embeddings) if embeddings.len() == texts.len() => Some(embeddings),
+            Ok(_) => {
+                warn!("Embedding service returned too few embeddings, selecting by relevance");
+                None
+            }
+            Err(e) => {
+                warn!("Failed to embed memory blocks, selecting by relevance: {}", e);
+                None
+            }
+        }
+    }
+
     /// Get the current context formatted for AI input
     pub async fn get_formatted_context(&self) -> Result<String> {
         let context_guard = self.current_context.read().await;
 
-        if let Some(context) = context_guard.as_ref() {
-            let mut formatted = String::new();
-
-            // Add core blocks
-            formatted.push_str("# Core Context\n\n");
-            formatted.push_str(&context.core_blocks_content);
-            formatted.push_str("\n");
-
-            // Add relevant memories
-            if !context.dynamic_blocks.is_empty() {
-                formatted.push_str("# Relevant Memories\n\n");
-                for (i, memory_block) in context.dynamic_blocks.iter().enumerate() {
-                    if let Some(content) = memory_block.block.content.as_text() {
-                        formatted.push_str(&format!("## Memory {} (Relevance: {:.2})\n\n{}\n\n",
-                            i + 1, memory_block.relevance_score, content));
-                    }
-                }
-            }
+        match context_guard.as_ref() {
+            Some(context) => Ok(Self::format_context(context)),
+            None => Ok("# Context\n\nNo context available yet.".to_string()),
+        }
+    }
 
-            // Add recent conversation (this would typically be managed separately)
-            if !context.conversation_history.is_empty() {
-                formatted.push_str("# Recent Conversation\n\n");
-                for (_i, message) in context.conversation_history.iter().rev().take(5).enumerate() {
-                    formatted.push_str(&format!("{}\n\n", message));
+    /// Format a context window for AI input
+    fn format_context(context: &ContextWindow) -> String {
+        let mut formatted = String::new();
+
+        // Add core blocks
+        formatted.push_str("# Core Context\n\n");
+        formatted.push_str(&context.core_blocks_content);
+        formatted.push('\n');
+
+        // Add relevant memories
+        if !context.dynamic_blocks.is_empty() {
+            formatted.push_str("# Relevant Memories\n\n");
+            for (i, memory_block) in context.dynamic_blocks.iter().enumerate() {
+                if let Some(content) = memory_block.block.content.as_text() {
+                    formatted.push_str(&format!("## Memory {} (Relevance: {:.2})\n\n{}\n\n",
+                        i + 1, memory_block.relevance_score, content));
                 }
             }
+        }
 
-            Ok(formatted)
-        } else {
-            Ok("# Context\n\nNo context available yet.".to_string())
+        // Add recent conversation (this would typically be managed separately)
+        if !context.conversation_history.is_empty() {
+            formatted.push_str("# Recent Conversation\n\n");
+            for message in context.conversation_history.iter().rev().take(5) {
+                formatted.push_str(&format!("{}\n\n", message));
+            }
         }
+
+        formatted
     }
 
-    /// Update a core block
-    pub fn update_core_block(&mut self, core_type: CoreBlockType, content: String) -> Result<()> {
-        self.core_manager.update_block(core_type, content)
+    /// Update a core block, returning a warning if it exceeds its block budget
+    pub async fn update_core_block(
+        &mut self,
+        core_type: CoreBlockType,
+        content: String,
+    ) -> Result<Option<BlockBudgetWarning>> {
+        self.core_manager.update_block(core_type, content).await
     }
 
     /// Get core block content
@@ -399,17 +748,24 @@ impl ContextWindowManager {
             .and_then(|block| block.get_text_content().map(|s| s.to_string()))
     }
 
-    /// Add a memory block and mark it as accessed
+    /// Mark a memory block as accessed
+    ///
+    /// The access is buffered in the memory manager and written with the
+    /// next batch.
     pub async fn access_memory_block(&self, block_id: &str) {
-        let mut tracking = self.access_tracking.write().await;
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as u64;
+        if let Err(e) = self.memory_manager.touch(&BlockId::from(block_id)).await {
+            warn!("Failed to record access to block {}: {}", block_id, e);
+        }
+    }
 
-        let entry = tracking.entry(block_id.to_string()).or_insert((0, now));
-        entry.0 += 1; // Increment access count
-        entry.1 = now; // Update last accessed time
+    /// Count an access to every memory block in `context` and write them in one batch
+    async fn record_accesses(&self, context: &ContextWindow) {
+        for context_block in &context.dynamic_blocks {
+            self.access_memory_block(context_block.block.id().as_str()).await;
+        }
+        if let Err(e) = self.memory_manager.flush_accesses().await {
+            warn!("Failed to write memory block accesses: {}", e);
+        }
     }
 
     /// Set the selection strategy
@@ -421,9 +777,14 @@ impl ContextWindowManager {
     /// Get context window statistics
     pub async fn get_stats(&self) -> ContextWindowStats {
         let context_guard = self.current_context.read().await;
+        self.stats_for(context_guard.as_ref())
+    }
+
+    /// Statistics for `context`, or for an empty window
+    fn stats_for(&self, context: Option<&ContextWindow>) -> ContextWindowStats {
         let core_stats = self.core_manager.get_stats();
 
-        if let Some(context) = context_guard.as_ref() {
+        if let Some(context) = context {
             ContextWindowStats {
                 core_block_stats: core_stats,
                 total_tokens: context.total_tokens,
@@ -432,6 +793,7 @@ impl ContextWindowManager {
                 max_tokens: self.config.max_total_tokens,
                 utilization: (context.total_tokens as f32 / self.config.max_total_tokens as f32) * 100.0,
                 last_updated: context.last_updated,
+                tokens_reclaimed: context.tokens_reclaimed,
             }
         } else {
             ContextWindowStats {
@@ -447,14 +809,14 @@ impl ContextWindowManager {
                 max_tokens: self.config.max_total_tokens,
                 utilization: 0.0,
                 last_updated: 0,
+                tokens_reclaimed: 0,
             }
         }
     }
 
-    /// Estimate tokens for text content
+    /// Tokens in `text` for this window's model
     fn estimate_tokens(&self, text: &str) -> u32 {
-        // Simple token estimation: ~4 characters per token
-        (text.len() as f32 / 4.0).ceil() as u32
+        self.counter.count(text) as u32
     }
 
     /// Perform maintenance on the context window
@@ -462,18 +824,8 @@ impl ContextWindowManager {
         // Auto-manage core blocks
         self.core_manager.auto_manage_blocks()?;
 
-        // Clean up old access tracking entries (keep only last 1000)
-        let mut tracking = self.access_tracking.write().await;
-        if tracking.len() > 1000 {
-            let mut entries: Vec<_> = tracking.iter().map(|(k, v)| (k.clone(), *v)).collect();
-            entries.sort_by_key(|(_, (_, last_accessed))| *last_accessed);
-
-            // Keep only the 800 most recently accessed
-            tracking.clear();
-            for (key, value) in entries.into_iter().rev().take(800) {
-                tracking.insert(key, value);
-            }
-        }
+        // Write out any block accesses still buffered
+        self.memory_manager.flush_accesses().await?;
 
         info!("Context window maintenance completed");
         Ok(())
@@ -503,14 +855,38 @@ pub struct ContextWindowStats {
 
     /// Last update timestamp
     pub last_updated: u64,
+
+    /// Tokens removed by the overflow policy in the last update
+    pub tokens_reclaimed: u32,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::memory::{SurrealMemoryStore, SurrealConfig};
+    use crate::memory::{BlockType, MemoryBlockBuilder, MemoryContent, SurrealMemoryStore, SurrealConfig};
     use tempfile::TempDir;
 
+    #[test]
+    fn test_config_is_sized_to_model() {
+        let models = ModelRegistry::bundled();
+
+        let small = ContextWindowConfig::for_model("gpt-4", &models);
+        assert_eq!(small.max_total_tokens, 8192);
+        // Budgets keep their 3:3:2 share of the window
+        assert_eq!(small.core_block_tokens, 3072);
+        assert_eq!(small.conversation_tokens, 3072);
+        assert_eq!(small.dynamic_memory_tokens, 2048);
+
+        let large = ContextWindowConfig::for_model("gemini-2.5-pro", &models);
+        assert_eq!(large.max_total_tokens, 1_048_576);
+        assert_eq!(large.core_block_tokens, 393_216);
+        assert_eq!(large.dynamic_memory_tokens, 262_144);
+
+        let unknown = ContextWindowConfig::for_model("no-such-model", &models);
+        assert_eq!(unknown.max_total_tokens, 8000);
+        assert_eq!(unknown.core_block_tokens, 3000);
+    }
+
     #[tokio::test]
     async fn test_context_window_manager() {
         let temp_dir = TempDir::new().unwrap();
@@ -528,17 +904,19 @@ mod tests {
         let mut manager = ContextWindowManager::new(
             "test_user",
             "test_session",
+            "test_provider",
             memory_manager,
             token_manager,
             None,
             None,
-        );
+        )
+        .await;
 
         // Test core block update
         manager.update_core_block(
             CoreBlockType::UserPersona,
             "Test user who likes programming".to_string(),
-        ).unwrap();
+        ).await.unwrap();
 
         // Test context update
         let conversation = vec!["Hello".to_string(), "How are you?".to_string()];
@@ -549,4 +927,325 @@ mod tests {
         assert!(formatted.contains("Core Context"));
         assert!(formatted.contains("programming"));
     }
+
+    #[tokio::test]
+    async fn test_simulate_previews_prompt_without_updating_context() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = SurrealConfig::File {
+            path: temp_dir.path().join("test.db"),
+            namespace: "test".to_string(),
+            database: "memory".to_string(),
+        };
+        let store = SurrealMemoryStore::new(config).await.unwrap();
+        store.initialize_schema_with_dimensions(384).await.unwrap();
+        let memory_manager = Arc::new(MemoryManager::new(store));
+
+        for (content, relevance) in [
+            ("Rust's borrow checker enforces ownership rules at compile time", 0.9),
+            ("The capital of France is Paris", 0.1),
+        ] {
+            let block = MemoryBlockBuilder::new()
+                .with_user_id("test_user")
+                .with_type(BlockType::Fact)
+                .with_content(MemoryContent::Text(content.to_string()))
+                .with_relevance(relevance)
+                .build()
+                .unwrap();
+            memory_manager.store(block).await.unwrap();
+        }
+
+        let token_manager = Arc::new(RwLock::new(TokenManager::new(std::path::PathBuf::from("./data"))));
+        let mut manager = ContextWindowManager::new(
+            "test_user",
+            "test_session",
+            "test_provider",
+            memory_manager,
+            token_manager,
+            None,
+            None,
+        )
+        .await;
+        manager.update_core_block(
+            CoreBlockType::UserPersona,
+            "Test user who likes programming".to_string(),
+        ).await.unwrap();
+
+        let (prompt, stats) = manager
+            .simulate("How does the Rust borrow checker work?")
+            .await
+            .unwrap();
+        assert!(prompt.contains("likes programming"));
+        assert!(prompt.contains("borrow checker enforces ownership"));
+        assert!(!prompt.contains("capital of France"));
+        assert!(prompt.contains("How does the Rust borrow checker work?"));
+        assert_eq!(stats.dynamic_blocks_count, 1);
+        assert!(stats.token_breakdown.dynamic_memory > 0);
+        assert_eq!(
+            stats.total_tokens,
+            stats.token_breakdown.core_blocks + stats.token_breakdown.conversation + stats.token_breakdown.dynamic_memory
+        );
+
+        // The live context window is untouched
+        assert_eq!(manager.get_stats().await.total_tokens, 0);
+        assert!(manager.get_formatted_context().await.unwrap().contains("No context available yet"));
+    }
+
+    /// The first memory block in the manager's current window
+    async fn selected_block(manager: &ContextWindowManager) -> BlockId {
+        let context = manager.current_context.read().await;
+        context.as_ref().unwrap().dynamic_blocks[0].block.id().clone()
+    }
+
+    #[tokio::test]
+    async fn test_repeated_access_moves_block_up_by_frequency() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = SurrealConfig::File {
+            path: temp_dir.path().join("test.db"),
+            namespace: "test".to_string(),
+            database: "memory".to_string(),
+        };
+        let store = SurrealMemoryStore::new(config).await.unwrap();
+        store.initialize_schema_with_dimensions(384).await.unwrap();
+        let memory_manager = Arc::new(MemoryManager::new(store));
+
+        let mut ids = Vec::new();
+        for content in [
+            "Alice drinks green tea every morning",
+            "Alice drinks black tea every evening",
+        ] {
+            let block = MemoryBlockBuilder::new()
+                .with_user_id("test_user")
+                .with_type(BlockType::Fact)
+                .with_content(MemoryContent::Text(content.to_string()))
+                .with_relevance(0.8)
+                .build()
+                .unwrap();
+            ids.push(memory_manager.store(block).await.unwrap());
+        }
+        let (green, black) = (&ids[0], &ids[1]);
+
+        let token_manager = Arc::new(RwLock::new(TokenManager::new(std::path::PathBuf::from("./data"))));
+        let mut manager = ContextWindowManager::new(
+            "test_user",
+            "test_session",
+            "test_provider",
+            memory_manager.clone(),
+            token_manager,
+            Some(ContextWindowConfig {
+                max_dynamic_blocks: 1,
+                ..Default::default()
+            }),
+            None,
+        )
+        .await;
+        manager.set_selection_strategy(SelectionStrategy::ByFrequency);
+        let question = vec!["What tea does Alice drink?".to_string()];
+
+        for _ in 0..3 {
+            manager.access_memory_block(black.as_str()).await;
+        }
+        memory_manager.flush_accesses().await.unwrap();
+        manager.update_context(question.clone()).await.unwrap();
+        assert_eq!(&selected_block(&manager).await, black);
+
+        // Being selected counts as an access too
+        let stored = memory_manager.get(black).await.unwrap().unwrap();
+        assert_eq!(stored.access_count(), 4);
+        assert!(stored.last_accessed_at().is_some());
+
+        for _ in 0..5 {
+            manager.access_memory_block(green.as_str()).await;
+        }
+        memory_manager.flush_accesses().await.unwrap();
+        manager.update_context(question).await.unwrap();
+        assert_eq!(&selected_block(&manager).await, green);
+    }
+
+    /// Embeds text by topic: tea texts and Rust texts point different ways
+    struct TopicEmbedder;
+
+    #[async_trait]
+    impl EmbeddingService for TopicEmbedder {
+        async fn embed_text(&self, text: &str) -> Result<Vec<f32>> {
+            Ok(vec![
+                text.contains("tea") as u8 as f32,
+                text.contains("Rust") as u8 as f32,
+                0.1,
+            ])
+        }
+
+        async fn embed_texts(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            let mut embeddings = Vec::new();
+            for text in texts {
+                embeddings.push(self.embed_text(text).await?);
+            }
+            Ok(embeddings)
+        }
+
+        fn dimensions(&self) -> usize {
+            3
+        }
+
+        fn max_text_length(&self) -> usize {
+            1000
+        }
+    }
+
+    #[tokio::test]
+    async fn test_diversified_selection_spans_clusters() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = SurrealConfig::File {
+            path: temp_dir.path().join("test.db"),
+            namespace: "test".to_string(),
+            database: "memory".to_string(),
+        };
+        let store = SurrealMemoryStore::new(config).await.unwrap();
+        store.initialize_schema_with_dimensions(384).await.unwrap();
+        let memory_manager = Arc::new(MemoryManager::new(store));
+
+        // Three near-identical tea facts outrank the one Rust fact
+        for (content, relevance) in [
+            ("Alice drinks green tea", 0.8),
+            ("Alice likes green tea", 0.8),
+            ("Alice brews green tea", 0.8),
+            ("Rust is a systems language", 0.6),
+        ] {
+            let block = MemoryBlockBuilder::new()
+                .with_user_id("test_user")
+                .with_type(BlockType::Fact)
+                .with_content(MemoryContent::Text(content.to_string()))
+                .with_relevance(relevance)
+                .build()
+                .unwrap();
+            memory_manager.store(block).await.unwrap();
+        }
+
+        let token_manager = Arc::new(RwLock::new(TokenManager::new(std::path::PathBuf::from("./data"))));
+        let mut manager = ContextWindowManager::new(
+            "test_user",
+            "test_session",
+            "test_provider",
+            memory_manager,
+            token_manager,
+            Some(ContextWindowConfig {
+                max_dynamic_blocks: 2,
+                diversity_lambda: 0.5,
+                ..Default::default()
+            }),
+            None,
+        )
+        .await;
+        let question = "Alice tea Rust";
+        let selected = |manager: &ContextWindowManager| {
+            let context = manager.current_context.try_read().unwrap();
+            let mut texts: Vec<String> = context
+                .as_ref()
+                .unwrap()
+                .dynamic_blocks
+                .iter()
+                .map(|b| b.block.content.as_text().unwrap().to_string())
+                .collect();
+            texts.sort();
+            texts
+        };
+
+        // Without embeddings Diversified is plain relevance order
+        manager.set_selection_strategy(SelectionStrategy::Diversified);
+        manager.update_context(vec![question.to_string()]).await.unwrap();
+        assert!(selected(&manager).iter().all(|text| text.contains("tea")));
+
+        manager.set_embedding_service(Arc::new(TopicEmbedder));
+        manager.update_context(vec![question.to_string()]).await.unwrap();
+        let texts = selected(&manager);
+        assert_eq!(texts.len(), 2);
+        assert!(texts[0].contains("tea"), "{:?}", texts);
+        assert_eq!(texts[1], "Rust is a systems language");
+    }
+
+    struct FakeSummarizer {
+        calls: std::sync::Mutex<Vec<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl HistorySummarizer for FakeSummarizer {
+        async fn summarize_history(
+            &self,
+            messages: &[String],
+            _user_id: &str,
+            _session_id: &str,
+        ) -> Result<String> {
+            self.calls.lock().unwrap().push(messages.to_vec());
+            Ok("they talked about trains".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_summarize_oldest_fits_window_to_budget() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = SurrealConfig::File {
+            path: temp_dir.path().join("test.db"),
+            namespace: "test".to_string(),
+            database: "memory".to_string(),
+        };
+        let store = SurrealMemoryStore::new(config).await.unwrap();
+        store.initialize_schema_with_dimensions(384).await.unwrap();
+        let memory_manager = Arc::new(MemoryManager::new(store));
+        let token_manager = Arc::new(RwLock::new(TokenManager::new(std::path::PathBuf::from("./data"))));
+
+        // Measure the core blocks so the budget leaves 60 tokens of conversation
+        let mut probe = ContextWindowManager::new(
+            "test_user",
+            "test_session",
+            "test_provider",
+            memory_manager.clone(),
+            token_manager.clone(),
+            None,
+            None,
+        )
+        .await;
+        probe.update_context(Vec::new()).await.unwrap();
+        let core_tokens = probe.get_stats().await.token_breakdown.core_blocks;
+
+        let mut manager = ContextWindowManager::new(
+            "test_user",
+            "test_session",
+            "test_provider",
+            memory_manager,
+            token_manager,
+            Some(ContextWindowConfig {
+                max_total_tokens: core_tokens + 60,
+                overflow_policy: OverflowPolicy::SummarizeOldest,
+                ..Default::default()
+            }),
+            None,
+        )
+        .await;
+        let summarizer = Arc::new(FakeSummarizer {
+            calls: std::sync::Mutex::new(Vec::new()),
+        });
+        manager.set_summarizer(summarizer.clone());
+
+        // Ten messages of seven words, about 90 tokens
+        let conversation: Vec<String> = (0..10)
+            .map(|i| format!("Message {} talks about trains at length", i))
+            .collect();
+        manager.update_context(conversation.clone()).await.unwrap();
+
+        let stats = manager.get_stats().await;
+        assert!(stats.total_tokens <= stats.max_tokens);
+        assert!(stats.tokens_reclaimed > 0);
+
+        let context = manager.current_context.read().await.clone().unwrap();
+        assert_eq!(
+            context.conversation_history[0],
+            "Summary of earlier conversation: they talked about trains"
+        );
+        assert_eq!(context.conversation_history.last(), conversation.last());
+
+        // The summarizer got the oldest turns, in order
+        let calls = summarizer.calls.lock().unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0][0], conversation[0]);
+        assert_eq!(calls[0].len() + context.conversation_history.len() - 1, conversation.len());
+    }
 }
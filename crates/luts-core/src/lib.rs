@@ -20,11 +20,12 @@ pub use agents::{
     PersonalityAgentBuilder,
 };
 pub use context::{
-    ContextManager, ContextProvider, ContextSaveConfig, ContextSavingManager, ContextSnapshot,
+    ContextManager, ContextManagerBuilder, ContextProvider, ContextSaveConfig, ContextSavingManager, ContextSnapshot,
     ContextStorageStats, RestoredContext, SnapshotQuery,
     CoreBlock, CoreBlockManager, CoreBlockType, CoreBlockConfig, CoreBlockStats,
+    BlockBudget, BlockBudgetWarning,
     ContextWindowManager, ContextWindowConfig, ContextWindow, ContextWindowStats,
-    SelectionStrategy, TokenBreakdown, ContextMemoryBlock,
+    SelectionStrategy, TokenBreakdown, ContextMemoryBlock, HistorySummarizer, OverflowPolicy,
 };
 pub use conversation::{
     AutoSaveConfig, AutoSaveData, AutoSaveManager, AutoSaveState, AutoSaveStats, AutoSaveType,
@@ -38,15 +39,15 @@ pub use conversation::{
     SummarizationStrategy, UndoRedoOperation,
 };
 pub use memory::{
-    BlockId, BlockType, MemoryBlock, MemoryBlockBuilder, MemoryContent,
+    BlockAccess, BlockId, BlockType, MemoryBlock, MemoryBlockBuilder, MemoryContent,
     MemoryManager, MemoryQuery, MemoryStore, QuerySort, TimeRange,
 };
 pub use streaming::{
-    ChunkType, ResponseChunk, ResponseStreamManager, StreamConfig, StreamEvent, StreamableResponse,
-    StreamingResponseBuilder, TypingIndicator, TypingStatus,
+    ChunkType, ProgressEstimate, ResponseChunk, ResponseStreamManager, StreamConfig, StreamEvent,
+    StreamableResponse, StreamingResponseBuilder, TypingIndicator, TypingStatus,
 };
-pub use tools::AiTool;
-pub use utils::{BlockUtils, BudgetStatus, TokenAnalytics, TokenBudget, TokenManager, TokenUsage};
+pub use tools::{AiTool, ToolResult};
+pub use utils::{BlockUtils, BudgetReport, BudgetStatus, TokenAnalytics, TokenBudget, TokenManager, TokenUsage};
 
 /// The LLM service for interacting with AI models
 pub mod llm;
@@ -99,6 +99,16 @@ pub enum InternalChatMessage {
 }
 
 impl InternalChatMessage {
+    /// Text content of the message, whatever its role
+    pub fn content(&self) -> &str {
+        match self {
+            InternalChatMessage::System { content }
+            | InternalChatMessage::User { content }
+            | InternalChatMessage::Assistant { content, .. }
+            | InternalChatMessage::Tool { content, .. } => content,
+        }
+    }
+
     pub fn to_genai(&self) -> GenaiChatMessage {
         match self {
             InternalChatMessage::System { content } => GenaiChatMessage::system(content),
@@ -271,6 +281,11 @@ impl LLMService {
         }
     }
 
+    /// Provider/model requests are sent to
+    pub fn provider(&self) -> &str {
+        &self.provider
+    }
+
     /// Set the system prompt
     pub fn set_system_prompt(&mut self, prompt: String) {
         self.system_prompt = Some(prompt);
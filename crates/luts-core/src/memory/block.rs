@@ -40,6 +40,14 @@ pub struct MemoryBlockMetadata {
 
     /// Relevance score for the block (optional)
     pub relevance: Option<Relevance>,
+
+    /// When the block was last included in a context window, as Unix timestamp (milliseconds)
+    #[serde(default)]
+    pub last_accessed_at: Option<u64>,
+
+    /// How many times the block has been included in a context window
+    #[serde(default)]
+    pub access_count: u64,
 }
 
 /// A memory block that contains content and metadata
@@ -72,6 +80,8 @@ impl MemoryBlock {
                 tags: Vec::new(),
                 properties: HashMap::new(),
                 relevance: None,
+                last_accessed_at: None,
+                access_count: 0,
             },
             content,
         }
@@ -127,6 +137,26 @@ impl MemoryBlock {
         self.metadata.relevance
     }
 
+    /// Get the last time the block was included in a context window
+    pub fn last_accessed_at(&self) -> Option<u64> {
+        self.metadata.last_accessed_at
+    }
+
+    /// Get how many times the block has been included in a context window
+    pub fn access_count(&self) -> u64 {
+        self.metadata.access_count
+    }
+
+    /// Count `count` more accesses, the latest at `accessed_at`
+    pub fn record_access(&mut self, count: u64, accessed_at: u64) {
+        self.metadata.access_count += count;
+        self.metadata.last_accessed_at = Some(
+            self.metadata
+                .last_accessed_at
+                .map_or(accessed_at, |last| last.max(accessed_at)),
+        );
+    }
+
     /// Get the content
     pub fn content(&self) -> &MemoryContent {
         &self.content
@@ -333,6 +363,8 @@ impl MemoryBlockBuilder {
                 tags: self.tags,
                 properties: self.properties,
                 relevance: self.relevance,
+                last_accessed_at: None,
+                access_count: 0,
             },
             content,
         })
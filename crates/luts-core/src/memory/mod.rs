@@ -43,6 +43,20 @@ pub trait MemoryStore: Send + Sync {
     /// Search for memory blocks based on criteria
     async fn query(&self, query: MemoryQuery) -> Result<Vec<MemoryBlock>, Error>;
 
+    /// Add batched accesses to the blocks' access counts and times
+    ///
+    /// The default implementation rewrites each block; backends should
+    /// override it with a single write. Blocks that no longer exist are skipped.
+    async fn record_accesses(&self, accesses: &[BlockAccess]) -> Result<(), Error> {
+        for access in accesses {
+            if let Some(mut block) = self.retrieve(&access.id).await? {
+                block.record_access(access.count, access.last_accessed_at);
+                self.update(&access.id, block).await?;
+            }
+        }
+        Ok(())
+    }
+
     /// Clear all data for a specific user
     async fn clear_user_data(&self, user_id: &str) -> Result<u64, Error>;
 
@@ -104,6 +118,19 @@ pub enum QuerySort {
     Relevance,
 }
 
+/// Accesses to one block that haven't been written to the store yet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockAccess {
+    pub id: BlockId,
+    /// Number of accesses
+    pub count: u64,
+    /// Time of the latest access, as Unix timestamp (milliseconds)
+    pub last_accessed_at: u64,
+}
+
+/// Pending accesses that trigger a write without waiting for a flush
+const ACCESS_FLUSH_THRESHOLD: usize = 64;
+
 /// Memory statistics for the MemoryStore trait
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryStats {
@@ -133,6 +160,8 @@ impl Default for MemoryQuery {
 /// A memory manager that interfaces with a storage backend
 pub struct MemoryManager {
     store: Arc<dyn MemoryStore>,
+    /// Accesses recorded by `touch` and not yet written, by block
+    pending_accesses: std::sync::Mutex<HashMap<BlockId, BlockAccess>>,
 }
 
 impl MemoryManager {
@@ -140,7 +169,43 @@ impl MemoryManager {
     pub fn new(store: impl MemoryStore + 'static) -> Self {
         MemoryManager {
             store: Arc::new(store),
+            pending_accesses: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record that a block was used, e.g. included in a context window
+    ///
+    /// Accesses are buffered and written in batches by [`Self::flush_accesses`],
+    /// which runs by itself once enough blocks have pending accesses.
+    pub async fn touch(&self, id: &BlockId) -> Result<(), Error> {
+        let now = Utc::now().timestamp_millis() as u64;
+        let pending = {
+            let mut pending = self.pending_accesses.lock().unwrap();
+            let access = pending.entry(id.clone()).or_insert_with(|| BlockAccess {
+                id: id.clone(),
+                count: 0,
+                last_accessed_at: now,
+            });
+            access.count += 1;
+            access.last_accessed_at = now;
+            pending.len()
+        };
+
+        if pending >= ACCESS_FLUSH_THRESHOLD {
+            self.flush_accesses().await?;
+        }
+        Ok(())
+    }
+
+    /// Write buffered accesses to the store
+    pub async fn flush_accesses(&self) -> Result<(), Error> {
+        let accesses: Vec<BlockAccess> = std::mem::take(&mut *self.pending_accesses.lock().unwrap())
+            .into_values()
+            .collect();
+        if accesses.is_empty() {
+            return Ok(());
         }
+        self.store.record_accesses(&accesses).await
     }
 
     /// Store a memory block
@@ -5,7 +5,7 @@
 //! compared to the basic FjallMemoryStore.
 
 use crate::memory::{
-    BlockId, BlockType, EmbeddingService, MemoryBlock, MemoryBlockMetadata, MemoryContent,
+    BlockAccess, BlockId, BlockType, EmbeddingService, MemoryBlock, MemoryBlockMetadata, MemoryContent,
     MemoryQuery, MemoryStore, Relevance, VectorQuery,
 };
 use anyhow::{Result, anyhow};
@@ -109,9 +109,11 @@ impl From<MemoryBlock> for EnhancedMemoryBlock {
             metadata: metadata.clone(),
             tags: metadata.tags.clone(),
             embedding: None,
-            relevance_score: None,
-            access_count: 0,
-            last_accessed: chrono::DateTime::from_timestamp_millis(metadata.updated_at as i64)
+            relevance_score: metadata.relevance.map(|r| r.score()),
+            access_count: metadata.access_count,
+            last_accessed: chrono::DateTime::from_timestamp_millis(
+                metadata.last_accessed_at.unwrap_or(metadata.updated_at) as i64,
+            )
                 .unwrap_or_else(|| chrono::Utc::now())
                 .to_rfc3339(),
             created_at: chrono::DateTime::from_timestamp_millis(metadata.created_at as i64)
@@ -142,6 +144,12 @@ impl From<EnhancedMemoryBlock> for MemoryBlock {
                 tags: enhanced.tags,
                 properties: enhanced.metadata.properties,
                 relevance: enhanced.relevance_score.map(|s| Relevance::from(s)),
+                // Until its first access the column holds the modification time
+                last_accessed_at: chrono::DateTime::parse_from_rfc3339(&enhanced.last_accessed)
+                    .ok()
+                    .filter(|_| enhanced.access_count > 0)
+                    .map(|dt| dt.timestamp_millis() as u64),
+                access_count: enhanced.access_count,
             },
             content: enhanced.content,
         }
@@ -831,7 +839,7 @@ impl MemoryStore for SurrealMemoryStore {
         let mut response = self
             .db
             .query("SELECT * FROM type::thing('memory_blocks', $block_id)")
-            .bind(("block_id", block_id_string.clone()))
+            .bind(("block_id", block_id_string))
             .await
             .map_err(|e| anyhow!("Failed to retrieve memory block: {}", e))?;
 
@@ -845,14 +853,6 @@ impl MemoryStore for SurrealMemoryStore {
             // Manually set the ID since raw block has Thing ID
             enhanced_block.id = id.clone();
 
-            // Update access count when retrieving
-            self.db
-                .query("UPDATE type::thing('memory_blocks', $block_id) SET access_count += 1, last_accessed = time::now()")
-                .bind(("block_id", block_id_string))
-                .await
-                .map_err(|e| anyhow!("Failed to update access count: {}", e))?
-                .check()?;
-
             Ok(Some(enhanced_block.into()))
         } else {
             Ok(None)
@@ -950,6 +950,42 @@ impl MemoryStore for SurrealMemoryStore {
         }
     }
 
+    async fn record_accesses(&self, accesses: &[BlockAccess]) -> Result<()> {
+        if accesses.is_empty() {
+            return Ok(());
+        }
+        self.initialize_schema().await?;
+
+        // One round trip for the whole batch
+        let rows: Vec<serde_json::Value> = accesses
+            .iter()
+            .map(|access| {
+                serde_json::json!({
+                    "id": access.id.as_str(),
+                    "count": access.count,
+                    "at": chrono::DateTime::from_timestamp_millis(access.last_accessed_at as i64)
+                        .unwrap_or_else(chrono::Utc::now)
+                        .to_rfc3339(),
+                })
+            })
+            .collect();
+        self.db
+            .query(
+                "FOR $access IN $accesses {
+                    UPDATE type::thing('memory_blocks', $access.id) SET
+                        access_count += $access.count,
+                        last_accessed = $access.at;
+                }",
+            )
+            .bind(("accesses", rows))
+            .await
+            .map_err(|e| anyhow!("Failed to record block accesses: {}", e))?
+            .check()?;
+
+        debug!("Recorded accesses to {} memory blocks", accesses.len());
+        Ok(())
+    }
+
     async fn clear_user_data(&self, user_id: &str) -> Result<u64> {
         self.initialize_schema().await?;
 
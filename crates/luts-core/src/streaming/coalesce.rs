@@ -0,0 +1,79 @@
+//! Coalescing of streamed text chunks
+//!
+//! Fast models stream a token or even a character at a time, and every chunk
+//! costs consumers a re-render. [`TextCoalescer`] holds text back and
+//! releases it as one combined chunk once it has waited long enough or grown
+//! large enough.
+
+use std::time::Duration;
+use tokio::time::Instant;
+
+#[derive(Debug)]
+struct HeldText {
+    content: String,
+    chars: usize,
+    token_count: u32,
+    since: Instant,
+}
+
+/// Buffers text chunks until a time or size threshold is reached
+#[derive(Debug)]
+pub struct TextCoalescer {
+    max_delay: Duration,
+    max_chars: usize,
+    held: Option<HeldText>,
+}
+
+impl TextCoalescer {
+    /// Hold text for at most `max_ms` milliseconds or `max_chars` characters
+    ///
+    /// Zero turns a threshold off; with both off every chunk passes straight
+    /// through. With only a size threshold, text waits for the next chunk
+    /// boundary however long that takes.
+    pub fn new(max_ms: u64, max_chars: usize) -> Self {
+        Self {
+            max_delay: Duration::from_millis(max_ms),
+            max_chars,
+            held: None,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.max_delay.is_zero() || self.max_chars > 0
+    }
+
+    /// Add a text chunk; returns the combined text once a threshold is reached
+    pub fn push(&mut self, content: String, token_count: u32) -> Option<(String, u32)> {
+        if !self.is_enabled() {
+            return Some((content, token_count));
+        }
+
+        let now = Instant::now();
+        let held = self.held.get_or_insert_with(|| HeldText {
+            content: String::new(),
+            chars: 0,
+            token_count: 0,
+            since: now,
+        });
+        held.chars += content.chars().count();
+        held.content.push_str(&content);
+        held.token_count += token_count;
+
+        let full = self.max_chars > 0 && held.chars >= self.max_chars;
+        let due = !self.max_delay.is_zero() && now.duration_since(held.since) >= self.max_delay;
+        if full || due { self.take() } else { None }
+    }
+
+    /// When the held text is due, if there is any and a time threshold is set
+    pub fn deadline(&self) -> Option<Instant> {
+        if self.max_delay.is_zero() {
+            return None;
+        }
+        self.held.as_ref().map(|held| held.since + self.max_delay)
+    }
+
+    /// Release the held text and its token count
+    pub fn take(&mut self) -> Option<(String, u32)> {
+        self.held.take().map(|held| (held.content, held.token_count))
+    }
+}
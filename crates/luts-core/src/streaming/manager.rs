@@ -4,6 +4,10 @@
 //! progress tracking, and smooth UI updates for both TUI and API interfaces.
 
 use crate::llm::{AiService, InternalChatMessage};
+use super::coalesce::TextCoalescer;
+use super::progress::{ProgressEstimate, ProgressEstimator, ResponsePhase};
+use super::reasoning::ThinkTagSplitter;
+use crate::tools::ToolResult;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use futures_util::{Stream, StreamExt};
@@ -13,7 +17,7 @@ use std::collections::HashMap;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
-use tokio::sync::{RwLock, broadcast, mpsc};
+use tokio::sync::{RwLock, broadcast, mpsc, watch};
 use tokio_stream::wrappers::ReceiverStream;
 use tracing::{debug, info, warn};
 
@@ -89,6 +93,14 @@ pub struct TypingIndicator {
     pub progress_percent: Option<u8>,
 }
 
+impl TypingIndicator {
+    /// Whole seconds until the projected completion, if one is known
+    pub fn seconds_remaining(&self) -> Option<i64> {
+        self.estimated_completion
+            .map(|completion| (completion - Utc::now()).num_seconds().max(0))
+    }
+}
+
 /// Typing status states
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TypingStatus {
@@ -119,6 +131,15 @@ pub struct StreamConfig {
     pub max_chunk_delay_ms: u64,
     /// Enable progress estimation
     pub enable_progress_estimation: bool,
+    /// Update the typing indicator's progress every this many content chunks
+    #[serde(default = "default_progress_update_interval")]
+    pub progress_update_interval: usize,
+    /// Hold streamed text back for up to this many ms and send it as one chunk (0 = off)
+    #[serde(default = "default_coalesce_ms")]
+    pub coalesce_ms: u64,
+    /// Send held-back text once it reaches this many characters (0 = no size limit)
+    #[serde(default = "default_coalesce_chars")]
+    pub coalesce_chars: usize,
     /// Buffer size for streaming
     pub buffer_size: usize,
     /// Timeout for streaming responses
@@ -136,6 +157,9 @@ impl Default for StreamConfig {
             min_chunk_delay_ms: 10,
             max_chunk_delay_ms: 100,
             enable_progress_estimation: true,
+            progress_update_interval: default_progress_update_interval(),
+            coalesce_ms: default_coalesce_ms(),
+            coalesce_chars: default_coalesce_chars(),
             buffer_size: 1000,
             stream_timeout_seconds: 300, // 5 minute timeout
             enable_chunk_compression: false,
@@ -143,6 +167,18 @@ impl Default for StreamConfig {
     }
 }
 
+fn default_progress_update_interval() -> usize {
+    5
+}
+
+fn default_coalesce_ms() -> u64 {
+    30
+}
+
+fn default_coalesce_chars() -> usize {
+    200
+}
+
 /// Streaming response stats
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StreamingStats {
@@ -165,13 +201,109 @@ pub struct ResponseStreamManager {
     /// Configuration
     config: RwLock<StreamConfig>,
     /// Active streams
-    active_streams: RwLock<HashMap<String, StreamSession>>,
+    active_streams: Arc<RwLock<HashMap<String, StreamSession>>>,
     /// Typing indicators
-    typing_indicators: RwLock<HashMap<String, TypingIndicator>>,
+    typing_indicators: Arc<RwLock<HashMap<String, TypingIndicator>>>,
     /// Event broadcaster for UI updates
     event_sender: broadcast::Sender<StreamEvent>,
     /// Statistics
-    stats: RwLock<StreamingStats>,
+    stats: Arc<RwLock<StreamingStats>>,
+}
+
+/// What a streaming task sent before it finished
+#[derive(Debug, Clone, Copy, Default)]
+struct StreamTotals {
+    chunks: u64,
+    characters: u64,
+}
+
+/// Handle a spawned streaming task uses to report back to its manager
+struct StreamTracker {
+    session_id: String,
+    started_at: DateTime<Utc>,
+    active_streams: Arc<RwLock<HashMap<String, StreamSession>>>,
+    typing_indicators: Arc<RwLock<HashMap<String, TypingIndicator>>>,
+    event_sender: broadcast::Sender<StreamEvent>,
+    stats: Arc<RwLock<StreamingStats>>,
+    /// Flipped to `true` when the session is cancelled
+    cancelled: watch::Receiver<bool>,
+}
+
+impl StreamTracker {
+    /// Whether the session has been cancelled
+    fn is_cancelled(&self) -> bool {
+        *self.cancelled.borrow()
+    }
+
+    /// Resolves once the session is cancelled
+    ///
+    /// Never resolves if the session is dropped without being cancelled.
+    async fn wait_for_cancel(&self) {
+        let mut cancelled = self.cancelled.clone();
+        if cancelled.wait_for(|cancelled| *cancelled).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+
+    /// Update the session's typing indicator, if it has one
+    async fn update_typing_status(&self, status: TypingStatus, estimate: Option<ProgressEstimate>) {
+        set_typing_status(
+            &self.typing_indicators,
+            &self.event_sender,
+            &self.session_id,
+            status,
+            estimate,
+        )
+        .await;
+    }
+
+    /// Record the task's outcome, then drop its session
+    ///
+    /// Removing the session drops the manager's copy of the chunk sender, so
+    /// the `StreamableResponse` ends once the task's own sender is gone.
+    async fn finish(self, outcome: Result<StreamTotals>) {
+        let totals = match outcome {
+            Ok(totals) => totals,
+            Err(e) => {
+                warn!("Streaming error for session {}: {}", self.session_id, e);
+                let _ = self.event_sender.send(StreamEvent::StreamError {
+                    session_id: self.session_id.clone(),
+                    error: e.to_string(),
+                });
+                StreamTotals::default()
+            }
+        };
+
+        {
+            let duration_ms = (Utc::now() - self.started_at).num_milliseconds().max(0) as u64;
+            let mut stats = self.stats.write().await;
+            stats.total_chunks += totals.chunks;
+            stats.total_characters += totals.characters;
+            stats.total_stream_time_ms += duration_ms;
+            if stats.total_chunks > 0 {
+                stats.avg_chunk_size = stats.total_characters as f64 / stats.total_chunks as f64;
+            }
+            if stats.total_stream_time_ms > 0 {
+                stats.chars_per_second =
+                    stats.total_characters as f64 / stats.total_stream_time_ms as f64 * 1000.0;
+            }
+        }
+
+        if let Some(indicator) = self.typing_indicators.write().await.remove(&self.session_id) {
+            let _ = self.event_sender.send(StreamEvent::TypingStatusChanged {
+                session_id: self.session_id.clone(),
+                indicator: TypingIndicator {
+                    status: TypingStatus::Stopped,
+                    last_activity: Utc::now(),
+                    estimated_completion: None,
+                    ..indicator
+                },
+            });
+        }
+
+        self.active_streams.write().await.remove(&self.session_id);
+        debug!("Cleaned up stream session: {}", self.session_id);
+    }
 }
 
 /// Individual streaming session
@@ -187,6 +319,8 @@ struct StreamSession {
     chunks_sent: u64,
     /// Total characters sent
     characters_sent: u64,
+    /// Set to true by `cancel_stream`
+    cancel: watch::Sender<bool>,
 }
 
 /// Stream events for UI updates
@@ -213,6 +347,8 @@ pub enum StreamEvent {
     },
     /// Stream error
     StreamError { session_id: String, error: String },
+    /// Stream cancelled before it completed
+    StreamCancelled { session_id: String },
 }
 
 /// Streamable response wrapper
@@ -237,17 +373,17 @@ impl ResponseStreamManager {
 
         Self {
             config: RwLock::new(StreamConfig::default()),
-            active_streams: RwLock::new(HashMap::new()),
-            typing_indicators: RwLock::new(HashMap::new()),
+            active_streams: Arc::new(RwLock::new(HashMap::new())),
+            typing_indicators: Arc::new(RwLock::new(HashMap::new())),
             event_sender,
-            stats: RwLock::new(StreamingStats {
+            stats: Arc::new(RwLock::new(StreamingStats {
                 total_chunks: 0,
                 total_characters: 0,
                 avg_chunk_size: 0.0,
                 total_stream_time_ms: 0,
                 chars_per_second: 0.0,
                 active_streams: 0,
-            }),
+            })),
         }
     }
 
@@ -281,12 +417,14 @@ impl ResponseStreamManager {
         }
 
         // Create stream session
+        let (cancel, cancelled) = watch::channel(false);
         let stream_session = StreamSession {
             session_id: session_id.clone(),
             chunk_sender: chunk_sender.clone(),
             started_at: Utc::now(),
             chunks_sent: 0,
             characters_sent: 0,
+            cancel,
         };
 
         self.active_streams
@@ -300,23 +438,19 @@ impl ResponseStreamManager {
         });
 
         // Spawn background task for streaming
-        let session_id_clone = session_id.clone();
         let config_clone = config.clone();
-        let event_sender = self.event_sender.clone();
+        let tracker = self.tracker(&session_id, cancelled);
 
         tokio::spawn(async move {
-            if let Err(e) = Self::stream_response_task(
-                session_id_clone,
+            let outcome = Self::stream_response_task(
                 ai_service,
                 messages,
                 chunk_sender,
                 config_clone,
-                event_sender,
+                &tracker,
             )
-            .await
-            {
-                warn!("Streaming error: {}", e);
-            }
+            .await;
+            tracker.finish(outcome).await;
         });
 
         Ok(StreamableResponse {
@@ -349,25 +483,21 @@ impl ResponseStreamManager {
         });
     }
 
-    /// Update typing status
+    /// Update typing status and progress
     pub async fn update_typing_status(
         &self,
         session_id: &str,
         status: TypingStatus,
-        progress: Option<u8>,
+        estimate: Option<ProgressEstimate>,
     ) {
-        let mut indicators = self.typing_indicators.write().await;
-        if let Some(indicator) = indicators.get_mut(session_id) {
-            indicator.status = status;
-            indicator.last_activity = Utc::now();
-            indicator.progress_percent = progress;
-
-            // Broadcast updated typing event
-            let _ = self.event_sender.send(StreamEvent::TypingStatusChanged {
-                session_id: session_id.to_string(),
-                indicator: indicator.clone(),
-            });
-        }
+        set_typing_status(
+            &self.typing_indicators,
+            &self.event_sender,
+            session_id,
+            status,
+            estimate,
+        )
+        .await;
     }
 
     /// Stop typing indicator
@@ -408,6 +538,26 @@ impl ResponseStreamManager {
         stats
     }
 
+    /// Cancel an in-flight stream
+    ///
+    /// The streaming task stops at its next await point. A tool call that is
+    /// still running is abandoned and reported in an aborted tool response
+    /// chunk; the response stream then ends without a completion chunk.
+    /// Returns false if no stream is active for `session_id`.
+    pub async fn cancel_stream(&self, session_id: &str) -> bool {
+        let Some(session) = self.active_streams.write().await.remove(session_id) else {
+            return false;
+        };
+        let _ = session.cancel.send(true);
+        self.stop_typing_indicator(session_id).await;
+
+        let _ = self.event_sender.send(StreamEvent::StreamCancelled {
+            session_id: session_id.to_string(),
+        });
+        info!("Cancelled stream for session: {}", session_id);
+        true
+    }
+
     /// Stream response from an AI service with live genai streaming and tool calling
     pub async fn stream_genai_response(
         &self,
@@ -421,12 +571,14 @@ impl ResponseStreamManager {
         let event_sender = self.event_sender.clone();
 
         // Start streaming session
+        let (cancel, cancelled) = watch::channel(false);
         let session_info = StreamSession {
             session_id: session_id.clone(),
             chunk_sender: chunk_sender.clone(),
             started_at: Utc::now(),
             chunks_sent: 0,
             characters_sent: 0,
+            cancel,
         };
 
         self.active_streams
@@ -434,20 +586,23 @@ impl ResponseStreamManager {
             .await
             .insert(session_id.clone(), session_info);
 
+        if config.enable_typing_indicators {
+            self.start_typing_indicator(session_id.clone(), "Assistant".to_string())
+                .await;
+        }
+
         // Send stream started event
         let _ = event_sender.send(StreamEvent::StreamStarted {
             session_id: session_id.clone(),
         });
 
         // Spawn genai streaming task
-        tokio::spawn(Self::genai_stream_task(
-            session_id.clone(),
-            ai_service,
-            messages,
-            chunk_sender,
-            config.clone(),
-            event_sender.clone(),
-        ));
+        let tracker = self.tracker(&session_id, cancelled);
+        tokio::spawn(async move {
+            let outcome =
+                Self::genai_stream_task(ai_service, messages, chunk_sender, config, &tracker).await;
+            tracker.finish(outcome).await;
+        });
 
         Ok(StreamableResponse {
             receiver: ReceiverStream::new(chunk_receiver),
@@ -457,19 +612,35 @@ impl ResponseStreamManager {
 
     // Private helper methods
 
+    /// Handle for the task streaming `session_id`
+    fn tracker(&self, session_id: &str, cancelled: watch::Receiver<bool>) -> StreamTracker {
+        StreamTracker {
+            session_id: session_id.to_string(),
+            started_at: Utc::now(),
+            active_streams: self.active_streams.clone(),
+            typing_indicators: self.typing_indicators.clone(),
+            event_sender: self.event_sender.clone(),
+            stats: self.stats.clone(),
+            cancelled,
+        }
+    }
+
     async fn stream_response_task(
-        session_id: String,
         ai_service: Arc<dyn AiService>,
         messages: Vec<InternalChatMessage>,
         chunk_sender: mpsc::Sender<ResponseChunk>,
         config: StreamConfig,
-        event_sender: broadcast::Sender<StreamEvent>,
-    ) -> Result<()> {
+        tracker: &StreamTracker,
+    ) -> Result<StreamTotals> {
+        let session_id = tracker.session_id.clone();
         let start_time = Utc::now();
         let mut sequence = 0u64;
 
         // Generate response (this would ideally be streaming from the AI service)
-        let response = ai_service.generate_response(&messages).await?;
+        let response = tokio::select! {
+            response = ai_service.generate_response(&messages) => response?,
+            _ = tracker.wait_for_cancel() => return Ok(StreamTotals::default()),
+        };
 
         let content = match response {
             genai::chat::MessageContent::Text(text) => text,
@@ -481,6 +652,14 @@ impl ResponseStreamManager {
         let chars: Vec<char> = content.chars().collect();
 
         for chunk_start in (0..chars.len()).step_by(config.chunk_size) {
+            if tracker.is_cancelled() {
+                info!("Stream cancelled for session: {}", session_id);
+                return Ok(StreamTotals {
+                    chunks: sequence,
+                    characters: total_chars,
+                });
+            }
+
             let chunk_end = (chunk_start + config.chunk_size).min(chars.len());
             let chunk_content: String = chars[chunk_start..chunk_end].iter().collect();
             let is_final = chunk_end >= chars.len();
@@ -513,7 +692,7 @@ impl ResponseStreamManager {
             }
 
             // Broadcast chunk event
-            let _ = event_sender.send(StreamEvent::ChunkReceived {
+            let _ = tracker.event_sender.send(StreamEvent::ChunkReceived {
                 session_id: session_id.clone(),
                 chunk: chunk.clone(),
             });
@@ -528,23 +707,28 @@ impl ResponseStreamManager {
             );
             tokio::time::sleep(tokio::time::Duration::from_millis(delay)).await;
 
-            // Update progress if enabled (simplified - would need manager reference for full implementation)
-            if config.enable_progress_estimation {
-                let progress = ((chunk_end as f64 / chars.len() as f64) * 100.0) as u8;
-                // Note: In full implementation, would update typing status via manager
-                debug!("Progress: {}%", progress);
+            // The full response is known here, so progress is exact
+            if config.enable_progress_estimation && !is_final {
+                let remaining_chunks = (chars.len() - chunk_end).div_ceil(config.chunk_size);
+                let estimate = ProgressEstimate {
+                    percent: ((chunk_end as f64 / chars.len() as f64) * 100.0) as u8,
+                    estimated_completion: Some(
+                        Utc::now()
+                            + chrono::Duration::milliseconds((remaining_chunks as u64 * delay) as i64),
+                    ),
+                };
+                debug!("Progress: {}%", estimate.percent);
+                tracker
+                    .update_typing_status(TypingStatus::Typing, Some(estimate))
+                    .await;
             }
         }
 
         let duration = Utc::now().signed_duration_since(start_time);
         let duration_ms = duration.num_milliseconds() as u64;
 
-        // Note: In full implementation, would stop typing indicator and update stats via manager
-        // manager.stop_typing_indicator(&session_id).await;
-        // manager.active_streams.write().await.remove(&session_id);
-
         // Broadcast completion event
-        let _ = event_sender.send(StreamEvent::StreamCompleted {
+        let _ = tracker.event_sender.send(StreamEvent::StreamCompleted {
             session_id: session_id.clone(),
             total_chunks: sequence,
             total_characters: total_chars,
@@ -555,18 +739,21 @@ impl ResponseStreamManager {
             "Completed streaming response: {} chars in {}ms",
             total_chars, duration_ms
         );
-        Ok(())
+        Ok(StreamTotals {
+            chunks: sequence,
+            characters: total_chars,
+        })
     }
 
     // Genai streaming task with tool calling support
     async fn genai_stream_task(
-        session_id: String,
         ai_service: Arc<dyn AiService>,
         messages: Vec<InternalChatMessage>,
         chunk_sender: mpsc::Sender<ResponseChunk>,
-        _config: StreamConfig,
-        event_sender: broadcast::Sender<StreamEvent>,
-    ) -> Result<()> {
+        config: StreamConfig,
+        tracker: &StreamTracker,
+    ) -> Result<StreamTotals> {
+        let session_id = tracker.session_id.clone();
         let start_time = Utc::now();
         let mut sequence = 0u64;
         let mut total_chars = 0u64;
@@ -574,17 +761,69 @@ impl ResponseStreamManager {
         debug!("Starting genai streaming for session: {}", session_id);
 
         // Get streaming response from AI service
-        let mut stream = ai_service.generate_response_stream(&messages).await?;
+        let mut stream = tokio::select! {
+            stream = ai_service.generate_response_stream(&messages) => stream?,
+            _ = tracker.wait_for_cancel() => {
+                info!("Stream cancelled for session: {}", session_id);
+                return Ok(StreamTotals::default());
+            }
+        };
 
         let mut accumulated_text = String::new();
         let mut tool_calls: Vec<genai::chat::ToolCall> = Vec::new();
+        let mut progress = ProgressEstimator::new(
+            messages.iter().map(|m| estimate_tokens(m.content())).sum(),
+        );
+        let mut content_chunks = 0usize;
+        // Pulls inline <think> reasoning out of text chunks
+        let mut think_tags = ThinkTagSplitter::new();
+        let mut coalescer = TextCoalescer::new(config.coalesce_ms, config.coalesce_chars);
+
+        // Process stream events until the stream ends or is cancelled
+        'events: loop {
+            let event_result = tokio::select! {
+                event = stream.next() => match event {
+                    Some(event) => event,
+                    None => break,
+                },
+                _ = tracker.wait_for_cancel() => {
+                    info!("Stream cancelled for session: {}", session_id);
+                    break;
+                }
+                _ = sleep_until_due(coalescer.deadline()) => {
+                    if !flush_coalesced(
+                        &mut coalescer,
+                        &chunk_sender,
+                        &session_id,
+                        &mut sequence,
+                        start_time,
+                    )
+                    .await
+                    {
+                        break;
+                    }
+                    continue;
+                }
+            };
 
-        // Process stream events
-        while let Some(event_result) = stream.next().await {
             match event_result {
                 Ok(event) => {
                     debug!("Received stream event: {:?}", event);
 
+                    // Held-back text goes out before anything that isn't more text
+                    if !matches!(event, ChatStreamEvent::Chunk(_))
+                        && !flush_coalesced(
+                            &mut coalescer,
+                            &chunk_sender,
+                            &session_id,
+                            &mut sequence,
+                            start_time,
+                        )
+                        .await
+                    {
+                        break;
+                    }
+
                     match event {
                         ChatStreamEvent::Start => {
                             info!("Stream started for session: {}", session_id);
@@ -616,6 +855,18 @@ impl ResponseStreamManager {
                         ChatStreamEvent::End(_m) => {
                             info!("Stream ended for session: {}", session_id);
 
+                            // Release text held back as a possible tag start
+                            if let Some((phase, content)) = think_tags.finish() {
+                                total_chars += content.len() as u64;
+                                let token_count = estimate_tokens(&content);
+                                let chunk = content_chunk(
+                                    &session_id, sequence, start_time, phase, content, token_count,
+                                );
+                                if chunk_sender.send(chunk).await.is_ok() {
+                                    sequence += 1;
+                                }
+                            }
+
                             // Send final completion chunk
                             let duration_ms = (Utc::now() - start_time).num_milliseconds() as u64;
 
@@ -658,7 +909,7 @@ impl ResponseStreamManager {
                             }
 
                             // Send stream completed event
-                            let _ = event_sender.send(StreamEvent::StreamCompleted {
+                            let _ = tracker.event_sender.send(StreamEvent::StreamCompleted {
                                 session_id: session_id.clone(),
                                 total_chunks: sequence,
                                 total_characters: total_chars,
@@ -718,137 +969,59 @@ impl ResponseStreamManager {
                             }
                             sequence += 1;
 
+                            tracker
+                                .update_typing_status(
+                                    TypingStatus::CallingTools,
+                                    config
+                                        .enable_progress_estimation
+                                        .then(|| progress.estimate(Utc::now())),
+                                )
+                                .await;
+
                             // Execute the tool call if we have access to the LLM service
                             if let Some(llm_service) = ai_service.as_any().downcast_ref::<crate::llm::LLMService>() {
-                                if let Some(tool) = llm_service.find_tool(&t.tool_call.fn_name) {
-                                    debug!("Executing tool: {}", t.tool_call.fn_name);
-                                    
-                                    // Execute the tool
-                                    match tool.execute(t.tool_call.fn_arguments.clone()).await {
-                                        Ok(result) => {
-                                            debug!("Tool {} executed successfully: {:?}", t.tool_call.fn_name, result);
-                                            
-                                            // Send tool result chunk
-                                            let result_content = format!("✅ Tool result: {}", serde_json::to_string(&result).unwrap_or_else(|_| result.to_string()));
-                                            
-                                            let result_chunk = ResponseChunk {
-                                                id: format!("{}_{}", session_id, sequence),
-                                                sequence,
-                                                content: result_content,
-                                                is_final: false,
-                                                timestamp: Utc::now(),
-                                                chunk_type: ChunkType::ToolResponse,
-                                                metadata: ChunkMetadata {
-                                                    token_count: None,
-                                                    processing_time_ms: Some(
-                                                        (Utc::now() - start_time).num_milliseconds() as u64,
-                                                    ),
-                                                    model: None,
-                                                    confidence: None,
-                                                    custom: {
-                                                        let mut custom = HashMap::new();
-                                                        custom.insert(
-                                                            "tool_name".to_string(),
-                                                            serde_json::Value::String(t.tool_call.fn_name.clone()),
-                                                        );
-                                                        custom.insert(
-                                                            "tool_result".to_string(),
-                                                            result.clone(),
-                                                        );
-                                                        custom
-                                                    },
-                                                },
-                                            };
-
-                                            if chunk_sender.send(result_chunk).await.is_err() {
-                                                warn!("Failed to send tool result chunk for session: {}", session_id);
-                                                break;
-                                            }
-                                            sequence += 1;
-                                        }
-                                        Err(e) => {
-                                            warn!("Tool {} execution failed: {}", t.tool_call.fn_name, e);
-                                            
-                                            // Send error chunk  
-                                            let error_content = format!("❌ Tool error: {}", e);
-                                            
-                                            let error_chunk = ResponseChunk {
-                                                id: format!("{}_{}", session_id, sequence),
-                                                sequence,
-                                                content: error_content,
-                                                is_final: false,
-                                                timestamp: Utc::now(),
-                                                chunk_type: ChunkType::ToolResponse,
-                                                metadata: ChunkMetadata {
-                                                    token_count: None,
-                                                    processing_time_ms: Some(
-                                                        (Utc::now() - start_time).num_milliseconds() as u64,
-                                                    ),
-                                                    model: None,
-                                                    confidence: None,
-                                                    custom: {
-                                                        let mut custom = HashMap::new();
-                                                        custom.insert(
-                                                            "tool_name".to_string(),
-                                                            serde_json::Value::String(t.tool_call.fn_name.clone()),
-                                                        );
-                                                        custom.insert(
-                                                            "error".to_string(),
-                                                            serde_json::Value::String(e.to_string()),
-                                                        );
-                                                        custom
-                                                    },
-                                                },
-                                            };
-
-                                            if chunk_sender.send(error_chunk).await.is_err() {
-                                                warn!("Failed to send tool error chunk for session: {}", session_id);
+                                let tool_name = &t.tool_call.fn_name;
+                                let tool_result = match llm_service.find_tool(tool_name) {
+                                    Some(tool) => {
+                                        debug!("Executing tool: {}", tool_name);
+
+                                        // Run the tool, abandoning it if the stream is cancelled
+                                        tokio::select! {
+                                            result = tool.run(t.tool_call.fn_arguments.clone()) => result,
+                                            _ = tracker.wait_for_cancel() => {
+                                                info!("Tool {} aborted: stream cancelled for session {}", tool_name, session_id);
+
+                                                let aborted = ToolResult::failure(tool_name, "Aborted: response was cancelled");
+                                                let mut chunk = tool_response_chunk(&session_id, sequence, start_time, &aborted);
+                                                chunk.is_final = true;
+                                                chunk.metadata.custom.insert(
+                                                    "aborted".to_string(),
+                                                    serde_json::Value::Bool(true),
+                                                );
+                                                let _ = chunk_sender.send(chunk).await;
                                                 break;
                                             }
-                                            sequence += 1;
                                         }
                                     }
+                                    None => {
+                                        warn!("Tool not found: {}", tool_name);
+                                        ToolResult::failure(tool_name, format!("Tool '{}' not found", tool_name))
+                                    }
+                                };
+
+                                if tool_result.is_success() {
+                                    debug!("Tool {} executed successfully: {}", tool_name, tool_result.display_text());
                                 } else {
-                                    warn!("Tool not found: {}", t.tool_call.fn_name);
-                                    
-                                    // Send tool not found error
-                                    let error_content = format!("❌ Tool error: Tool '{}' not found", t.tool_call.fn_name);
-                                    
-                                    let error_chunk = ResponseChunk {
-                                        id: format!("{}_{}", session_id, sequence),
-                                        sequence,
-                                        content: error_content,
-                                        is_final: false,
-                                        timestamp: Utc::now(),
-                                        chunk_type: ChunkType::ToolResponse,
-                                        metadata: ChunkMetadata {
-                                            token_count: None,
-                                            processing_time_ms: Some(
-                                                (Utc::now() - start_time).num_milliseconds() as u64,
-                                            ),
-                                            model: None,
-                                            confidence: None,
-                                            custom: {
-                                                let mut custom = HashMap::new();
-                                                custom.insert(
-                                                    "tool_name".to_string(),
-                                                    serde_json::Value::String(t.tool_call.fn_name.clone()),
-                                                );
-                                                custom.insert(
-                                                    "error".to_string(),
-                                                    serde_json::Value::String(format!("Tool '{}' not found", t.tool_call.fn_name)),
-                                                );
-                                                custom
-                                            },
-                                        },
-                                    };
+                                    warn!("Tool {} execution failed: {}", tool_name, tool_result.display_text());
+                                }
 
-                                    if chunk_sender.send(error_chunk).await.is_err() {
-                                        warn!("Failed to send tool not found error chunk for session: {}", session_id);
-                                        break;
-                                    }
-                                    sequence += 1;
+                                // Send the result envelope
+                                let chunk = tool_response_chunk(&session_id, sequence, start_time, &tool_result);
+                                if chunk_sender.send(chunk).await.is_err() {
+                                    warn!("Failed to send tool result chunk for session: {}", session_id);
+                                    break;
                                 }
+                                sequence += 1;
                             } else {
                                 warn!("Cannot execute tools: AI service is not an LLMService instance");
                             }
@@ -891,49 +1064,97 @@ impl ResponseStreamManager {
                                     break;
                                 }
                                 sequence += 1;
+
+                                progress.record(ResponsePhase::Reasoning, content.chars().count(), Utc::now());
+                                content_chunks += 1;
+                                if config.enable_progress_estimation
+                                    && content_chunks.is_multiple_of(config.progress_update_interval.max(1))
+                                {
+                                    tracker
+                                        .update_typing_status(
+                                            TypingStatus::Thinking,
+                                            Some(progress.estimate(Utc::now())),
+                                        )
+                                        .await;
+                                }
                             }
                         }
 
                         ChatStreamEvent::Chunk(c) => {
-                            // Handle regular text chunk
+                            // Handle regular text chunk, which may carry inline reasoning
                             debug!("Received text chunk: {:?}", c);
-                            let content = c.content;
-                            if !content.is_empty() {
+                            for (phase, content) in think_tags.push(&c.content) {
                                 accumulated_text.push_str(&content);
                                 total_chars += content.len() as u64;
-
-                                let chunk = ResponseChunk {
-                                    id: format!("{}_{}", session_id, sequence),
-                                    sequence,
-                                    content: content.clone(),
-                                    is_final: false,
-                                    timestamp: Utc::now(),
-                                    chunk_type: ChunkType::Text,
-                                    metadata: ChunkMetadata {
-                                        token_count: Some(
-                                            (content.split_whitespace().count() as f32 * 1.3)
-                                                as u32,
-                                        ),
-                                        processing_time_ms: Some(
-                                            (Utc::now() - start_time).num_milliseconds() as u64,
-                                        ),
-                                        model: None,
-                                        confidence: None,
-                                        custom: HashMap::new(),
-                                    },
+                                let token_count = estimate_tokens(&content);
+                                let chars = content.chars().count();
+
+                                let ready = match phase {
+                                    ResponsePhase::Text => coalescer.push(content, token_count),
+                                    ResponsePhase::Reasoning => {
+                                        if !flush_coalesced(
+                                            &mut coalescer,
+                                            &chunk_sender,
+                                            &session_id,
+                                            &mut sequence,
+                                            start_time,
+                                        )
+                                        .await
+                                        {
+                                            break 'events;
+                                        }
+                                        Some((content, token_count))
+                                    }
                                 };
+                                if let Some((content, token_count)) = ready {
+                                    let chunk = content_chunk(
+                                        &session_id,
+                                        sequence,
+                                        start_time,
+                                        phase,
+                                        content,
+                                        token_count,
+                                    );
+                                    if chunk_sender.send(chunk).await.is_err() {
+                                        warn!(
+                                            "Failed to send text chunk for session: {}",
+                                            session_id
+                                        );
+                                        break 'events;
+                                    }
+                                    sequence += 1;
+                                }
 
-                                if chunk_sender.send(chunk).await.is_err() {
-                                    warn!("Failed to send text chunk for session: {}", session_id);
-                                    break;
+                                progress.record(phase, chars, Utc::now());
+                                content_chunks += 1;
+                                if config.enable_progress_estimation
+                                    && content_chunks.is_multiple_of(config.progress_update_interval.max(1))
+                                {
+                                    let status = match phase {
+                                        ResponsePhase::Reasoning => TypingStatus::Thinking,
+                                        ResponsePhase::Text => TypingStatus::Typing,
+                                    };
+                                    tracker
+                                        .update_typing_status(
+                                            status,
+                                            Some(progress.estimate(Utc::now())),
+                                        )
+                                        .await;
                                 }
-                                sequence += 1;
                             }
                         }
                     }
                 }
                 Err(e) => {
                     warn!("Stream error for session {}: {}", session_id, e);
+                    flush_coalesced(
+                        &mut coalescer,
+                        &chunk_sender,
+                        &session_id,
+                        &mut sequence,
+                        start_time,
+                    )
+                    .await;
 
                     // Send error chunk
                     let chunk = ResponseChunk {
@@ -955,7 +1176,7 @@ impl ResponseStreamManager {
                     };
 
                     let _ = chunk_sender.send(chunk).await;
-                    let _ = event_sender.send(StreamEvent::StreamError {
+                    let _ = tracker.event_sender.send(StreamEvent::StreamError {
                         session_id: session_id.clone(),
                         error: e.to_string(),
                     });
@@ -970,8 +1191,152 @@ impl ResponseStreamManager {
             // }
         }
 
+        // Text still held back when the stream ended without an End event
+        flush_coalesced(
+            &mut coalescer,
+            &chunk_sender,
+            &session_id,
+            &mut sequence,
+            start_time,
+        )
+        .await;
+
         info!("Genai streaming task completed for session: {}", session_id);
-        Ok(())
+        Ok(StreamTotals {
+            chunks: sequence,
+            characters: total_chars,
+        })
+    }
+}
+
+/// Rough token estimate for streamed text
+fn estimate_tokens(text: &str) -> u32 {
+    (text.split_whitespace().count() as f32 * 1.3) as u32
+}
+
+/// Reasoning or answer text chunk, typed by the phase it belongs to
+fn content_chunk(
+    session_id: &str,
+    sequence: u64,
+    start_time: DateTime<Utc>,
+    phase: ResponsePhase,
+    content: String,
+    token_count: u32,
+) -> ResponseChunk {
+    ResponseChunk {
+        id: format!("{}_{}", session_id, sequence),
+        sequence,
+        content,
+        is_final: false,
+        timestamp: Utc::now(),
+        chunk_type: match phase {
+            ResponsePhase::Reasoning => ChunkType::Reasoning,
+            ResponsePhase::Text => ChunkType::Text,
+        },
+        metadata: ChunkMetadata {
+            token_count: Some(token_count),
+            processing_time_ms: Some((Utc::now() - start_time).num_milliseconds() as u64),
+            model: None,
+            confidence: None,
+            custom: HashMap::new(),
+        },
+    }
+}
+
+/// Send the text `coalescer` is holding back, if any
+///
+/// Returns false once the receiver has gone away.
+async fn flush_coalesced(
+    coalescer: &mut TextCoalescer,
+    chunk_sender: &mpsc::Sender<ResponseChunk>,
+    session_id: &str,
+    sequence: &mut u64,
+    start_time: DateTime<Utc>,
+) -> bool {
+    let Some((content, token_count)) = coalescer.take() else {
+        return true;
+    };
+    let chunk = content_chunk(
+        session_id,
+        *sequence,
+        start_time,
+        ResponsePhase::Text,
+        content,
+        token_count,
+    );
+    if chunk_sender.send(chunk).await.is_err() {
+        return false;
+    }
+    *sequence += 1;
+    true
+}
+
+/// Tool response chunk carrying `result` as its [`ToolResult`] envelope
+///
+/// The chunk content is the envelope JSON; `custom["tool_result"]` holds the
+/// same envelope as a value.
+fn tool_response_chunk(
+    session_id: &str,
+    sequence: u64,
+    start_time: DateTime<Utc>,
+    result: &ToolResult,
+) -> ResponseChunk {
+    let mut custom = HashMap::new();
+    custom.insert(
+        "tool_name".to_string(),
+        serde_json::Value::String(result.tool.clone()),
+    );
+    custom.insert(
+        "tool_result".to_string(),
+        serde_json::to_value(result).unwrap_or_default(),
+    );
+
+    ResponseChunk {
+        id: format!("{}_{}", session_id, sequence),
+        sequence,
+        content: result.to_json(),
+        is_final: false,
+        timestamp: Utc::now(),
+        chunk_type: ChunkType::ToolResponse,
+        metadata: ChunkMetadata {
+            token_count: None,
+            processing_time_ms: Some((Utc::now() - start_time).num_milliseconds() as u64),
+            model: None,
+            confidence: None,
+            custom,
+        },
+    }
+}
+
+/// Update the typing indicator for `session_id` and broadcast the change
+///
+/// Does nothing if the session has no typing indicator.
+async fn set_typing_status(
+    typing_indicators: &RwLock<HashMap<String, TypingIndicator>>,
+    event_sender: &broadcast::Sender<StreamEvent>,
+    session_id: &str,
+    status: TypingStatus,
+    estimate: Option<ProgressEstimate>,
+) {
+    let mut indicators = typing_indicators.write().await;
+    if let Some(indicator) = indicators.get_mut(session_id) {
+        indicator.status = status;
+        indicator.last_activity = Utc::now();
+        indicator.progress_percent = estimate.map(|estimate| estimate.percent);
+        indicator.estimated_completion = estimate.and_then(|estimate| estimate.estimated_completion);
+
+        let _ = event_sender.send(StreamEvent::TypingStatusChanged {
+            session_id: session_id.to_string(),
+            indicator: indicator.clone(),
+        });
+    }
+}
+
+/// Resolves at `deadline`, or never if there is none
+async fn sleep_until_due(deadline: Option<tokio::time::Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending::<()>().await,
     }
 }
 
@@ -3,10 +3,16 @@
 //! This module contains the streaming response manager for handling
 //! real-time AI responses with tool calling support.
 
+pub mod coalesce;
 pub mod manager;
+pub mod progress;
+pub mod reasoning;
 
 // Re-export key types for convenience
 pub use manager::{
     ChunkType, ResponseChunk, ResponseStreamManager, StreamConfig, StreamEvent, StreamableResponse,
     StreamingResponseBuilder, TypingIndicator, TypingStatus,
-};
\ No newline at end of file
+};
+pub use coalesce::TextCoalescer;
+pub use progress::{ProgressEstimate, ProgressEstimator, ResponsePhase};
+pub use reasoning::ThinkTagSplitter;
\ No newline at end of file
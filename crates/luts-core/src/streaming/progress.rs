@@ -0,0 +1,195 @@
+//! Progress and ETA estimation for streamed responses
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Characters per token, for turning token counts into expected text length
+const CHARS_PER_TOKEN: f64 = 4.0;
+/// Smallest answer length expected, in tokens
+const MIN_EXPECTED_TOKENS: u32 = 150;
+/// Largest answer length expected, in tokens
+const MAX_EXPECTED_TOKENS: u32 = 1500;
+/// Share of the bar given to reasoning when the model reasons before answering
+const REASONING_SHARE: f64 = 0.3;
+/// Number of recent chunks the streaming rate is computed over
+const RATE_WINDOW: usize = 20;
+/// Highest percentage reported before the stream actually ends
+const MAX_PERCENT: u8 = 99;
+
+/// Which part of a response a chunk belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponsePhase {
+    /// Reasoning emitted before the answer
+    Reasoning,
+    /// The answer itself
+    Text,
+}
+
+/// How far along a streaming response is
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ProgressEstimate {
+    /// Estimated progress (0-99 while streaming)
+    pub percent: u8,
+    /// Projected completion time, once a streaming rate is known
+    pub estimated_completion: Option<DateTime<Utc>>,
+}
+
+/// Estimates the progress of a streamed response from the chunks received so far
+///
+/// The expected answer length is guessed from the prompt size and stretched
+/// whenever the answer outgrows it, so the estimate slows down rather than
+/// overshooting. When the model reasons before answering, reasoning fills the
+/// first part of the bar and the answer the rest, and the reported percentage
+/// never moves backward.
+#[derive(Debug, Clone)]
+pub struct ProgressEstimator {
+    expected_chars: f64,
+    phase: Option<ResponsePhase>,
+    reasoning_chars: f64,
+    text_chars: f64,
+    /// Percentage the answer phase starts from
+    text_base: f64,
+    /// Recent chunks as (received at, characters)
+    recent: VecDeque<(DateTime<Utc>, usize)>,
+    percent: u8,
+}
+
+impl ProgressEstimator {
+    /// Estimator for a response to a prompt of `prompt_tokens` tokens
+    pub fn new(prompt_tokens: u32) -> Self {
+        let expected_tokens = (prompt_tokens / 2).clamp(MIN_EXPECTED_TOKENS, MAX_EXPECTED_TOKENS);
+        Self {
+            expected_chars: expected_tokens as f64 * CHARS_PER_TOKEN,
+            phase: None,
+            reasoning_chars: 0.0,
+            text_chars: 0.0,
+            text_base: 0.0,
+            recent: VecDeque::with_capacity(RATE_WINDOW),
+            percent: 0,
+        }
+    }
+
+    /// Record a chunk of `chars` characters received at `at`
+    pub fn record(&mut self, phase: ResponsePhase, chars: usize, at: DateTime<Utc>) {
+        if self.phase == Some(ResponsePhase::Reasoning) && phase == ResponsePhase::Text {
+            // The answer picks up where reasoning left the bar
+            self.text_base = self.percent as f64;
+        }
+        self.phase = Some(phase);
+
+        match phase {
+            ResponsePhase::Reasoning => self.reasoning_chars += chars as f64,
+            ResponsePhase::Text => self.text_chars += chars as f64,
+        }
+
+        if self.recent.len() == RATE_WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back((at, chars));
+
+        let percent = match phase {
+            ResponsePhase::Reasoning => {
+                REASONING_SHARE * self.fraction(self.reasoning_chars) * 100.0
+            }
+            ResponsePhase::Text => {
+                self.text_base + (100.0 - self.text_base) * self.fraction(self.text_chars)
+            }
+        };
+        self.percent = (percent as u8).clamp(self.percent, MAX_PERCENT);
+    }
+
+    /// Current estimate, with the completion time projected from `now`
+    pub fn estimate(&self, now: DateTime<Utc>) -> ProgressEstimate {
+        let remaining_chars = match self.phase {
+            Some(ResponsePhase::Reasoning) => {
+                (self.expected_chars - self.reasoning_chars).max(0.0) + self.expected_chars
+            }
+            Some(ResponsePhase::Text) => self.stretched(self.text_chars) - self.text_chars,
+            None => self.expected_chars,
+        };
+        let estimated_completion = self.chars_per_second().map(|rate| {
+            now + Duration::milliseconds((remaining_chars / rate * 1000.0) as i64)
+        });
+
+        ProgressEstimate {
+            percent: self.percent,
+            estimated_completion,
+        }
+    }
+
+    /// Rolling streaming rate over the recent chunks
+    ///
+    /// The first chunk in the window only marks the start of the interval.
+    fn chars_per_second(&self) -> Option<f64> {
+        let (first, _) = self.recent.front()?;
+        let (last, _) = self.recent.back()?;
+        let elapsed = (*last - *first).num_milliseconds() as f64 / 1000.0;
+        if elapsed <= 0.0 {
+            return None;
+        }
+        let chars: usize = self.recent.iter().skip(1).map(|(_, chars)| chars).sum();
+        (chars > 0).then(|| chars as f64 / elapsed)
+    }
+
+    /// Expected length of a phase that has received `received` characters
+    fn stretched(&self, received: f64) -> f64 {
+        self.expected_chars.max(received * 1.25)
+    }
+
+    /// Fraction of a phase done after `received` characters
+    fn fraction(&self, received: f64) -> f64 {
+        received / self.stretched(received)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_advances_through_reasoning_then_text() {
+        let start = Utc::now();
+        let mut estimator = ProgressEstimator::new(400);
+        assert_eq!(estimator.estimate(start).estimated_completion, None);
+
+        let mut last = 0;
+        let mut at = start;
+        for i in 0..40 {
+            let phase = if i < 10 {
+                ResponsePhase::Reasoning
+            } else {
+                ResponsePhase::Text
+            };
+            at += Duration::milliseconds(100);
+            estimator.record(phase, 20, at);
+
+            let estimate = estimator.estimate(at);
+            assert!(estimate.percent >= last, "progress went backward at chunk {}", i);
+            assert!(estimate.percent <= MAX_PERCENT);
+            last = estimate.percent;
+
+            if i == 9 {
+                assert!(estimate.percent <= (REASONING_SHARE * 100.0) as u8);
+            }
+        }
+
+        // 20 chars every 100ms, 600 of the expected 800 answer chars received
+        let estimate = estimator.estimate(at);
+        assert!(estimate.percent > 50);
+        let eta = estimate.estimated_completion.unwrap() - at;
+        assert!((990..=1010).contains(&eta.num_milliseconds()), "eta was {}", eta);
+    }
+
+    #[test]
+    fn test_long_answers_never_reach_complete() {
+        let start = Utc::now();
+        let mut estimator = ProgressEstimator::new(0);
+        for i in 0..500 {
+            estimator.record(ResponsePhase::Text, 50, start + Duration::milliseconds(i * 10));
+        }
+        let estimate = estimator.estimate(start + Duration::seconds(5));
+        assert!(estimate.percent < 100);
+        assert!(estimate.estimated_completion.is_some());
+    }
+}
@@ -0,0 +1,137 @@
+//! Separating inline reasoning from answer text
+//!
+//! Providers that report reasoning natively send it as reasoning events, but
+//! many models (DeepSeek R1, QwQ, most local models) wrap it in
+//! `<think>...</think>` inside the text instead. [`ThinkTagSplitter`] pulls
+//! those spans out of a text stream so they can be tagged as reasoning, even
+//! when a tag is split across chunks.
+
+use super::progress::ResponsePhase;
+
+const OPEN_TAG: &str = "<think>";
+const CLOSE_TAG: &str = "</think>";
+
+/// Splits streamed text into reasoning and answer segments at `<think>` tags
+#[derive(Debug, Default)]
+pub struct ThinkTagSplitter {
+    in_reasoning: bool,
+    /// Text held back because it may be the start of a tag
+    pending: String,
+}
+
+impl ThinkTagSplitter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Split the next text chunk into segments, in stream order
+    ///
+    /// Tags are dropped. A trailing fragment that could begin a tag is held
+    /// back until the next chunk (or [`finish`](Self::finish)) decides it.
+    pub fn push(&mut self, text: &str) -> Vec<(ResponsePhase, String)> {
+        let buffer = std::mem::take(&mut self.pending) + text;
+        let mut segments = Vec::new();
+        let mut rest = buffer.as_str();
+        loop {
+            let tag = if self.in_reasoning {
+                CLOSE_TAG
+            } else {
+                OPEN_TAG
+            };
+            match rest.find(tag) {
+                Some(pos) => {
+                    self.emit(&mut segments, &rest[..pos]);
+                    rest = &rest[pos + tag.len()..];
+                    self.in_reasoning = !self.in_reasoning;
+                }
+                None => {
+                    let split = rest.len() - partial_tag_len(rest, tag);
+                    self.emit(&mut segments, &rest[..split]);
+                    self.pending = rest[split..].to_string();
+                    return segments;
+                }
+            }
+        }
+    }
+
+    /// Release any text still held back once the stream has ended
+    pub fn finish(&mut self) -> Option<(ResponsePhase, String)> {
+        let pending = std::mem::take(&mut self.pending);
+        (!pending.is_empty()).then(|| (self.phase(), pending))
+    }
+
+    fn phase(&self) -> ResponsePhase {
+        if self.in_reasoning {
+            ResponsePhase::Reasoning
+        } else {
+            ResponsePhase::Text
+        }
+    }
+
+    fn emit(&self, segments: &mut Vec<(ResponsePhase, String)>, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        let phase = self.phase();
+        match segments.last_mut() {
+            Some((last_phase, last)) if *last_phase == phase => last.push_str(text),
+            _ => segments.push((phase, text.to_string())),
+        }
+    }
+}
+
+/// Length of the longest end of `text` that is the start of `tag`
+fn partial_tag_len(text: &str, tag: &str) -> usize {
+    (1..tag.len())
+        .rev()
+        .find(|&len| text.ends_with(&tag[..len]))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tags_split_across_chunks_are_recognized() {
+        let mut splitter = ThinkTagSplitter::new();
+        let mut segments = Vec::new();
+        for chunk in [
+            "<thi",
+            "nk>The user wants",
+            " a greeting.</th",
+            "ink>\n\nHello",
+            " <b>there</b>",
+        ] {
+            segments.extend(splitter.push(chunk));
+        }
+        segments.extend(splitter.finish());
+
+        let reasoning: String = segments
+            .iter()
+            .filter(|(phase, _)| *phase == ResponsePhase::Reasoning)
+            .map(|(_, text)| text.as_str())
+            .collect();
+        let text: String = segments
+            .iter()
+            .filter(|(phase, _)| *phase == ResponsePhase::Text)
+            .map(|(_, text)| text.as_str())
+            .collect();
+        assert_eq!(reasoning, "The user wants a greeting.");
+        assert_eq!(text, "\n\nHello <b>there</b>");
+    }
+
+    #[test]
+    fn test_held_back_fragment_is_released_at_the_end() {
+        let mut splitter = ThinkTagSplitter::new();
+        assert_eq!(
+            splitter.push("x <"),
+            vec![(ResponsePhase::Text, "x ".to_string())]
+        );
+        assert_eq!(
+            splitter.finish(),
+            Some((ResponsePhase::Text, "<".to_string()))
+        );
+        assert_eq!(splitter.finish(), None);
+    }
+}
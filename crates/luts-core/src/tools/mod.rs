@@ -17,7 +17,293 @@ pub mod website;
 
 use anyhow::Error;
 use async_trait::async_trait;
+use luts_common::LutsError;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::time::Duration;
+
+/// Why a tool call failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolErrorKind {
+    /// The tool outlasted its [`AiTool::timeout`]
+    Timeout,
+    /// The parameters didn't match the tool's schema
+    Validation,
+    /// No tool has the requested name
+    NotFound,
+    /// The tool ran and returned an error
+    Execution,
+    /// The session's tool policy doesn't allow the tool to run
+    Refused,
+}
+
+impl std::fmt::Display for ToolErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ToolErrorKind::Timeout => "timeout",
+            ToolErrorKind::Validation => "validation",
+            ToolErrorKind::NotFound => "not_found",
+            ToolErrorKind::Execution => "execution",
+            ToolErrorKind::Refused => "refused",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A failed tool call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolError {
+    /// What went wrong
+    pub kind: ToolErrorKind,
+    /// Error message, as the model is shown it
+    pub message: String,
+}
+
+impl std::fmt::Display for ToolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} error: {}", self.kind, self.message)
+    }
+}
+
+/// Envelope that tool output is reported in
+///
+/// Serializes as `{"tool": "<name>", "result": <value>}` on success and
+/// `{"tool": "<name>", "error": "<message>", "error_kind": "<kind>"}` on
+/// failure. `result` is exactly what the tool returned, so text tools carry
+/// a JSON string and structured tools an object; use
+/// [`ToolResult::display_text`] to show either without extra quoting.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolResult {
+    /// Name of the tool that ran
+    pub tool: String,
+    /// Output of a successful run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    /// Error message of a failed run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Kind of failure; envelopes written before kinds existed lack it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_kind: Option<ToolErrorKind>,
+}
+
+impl ToolResult {
+    /// Successful run of `tool` that produced `result`
+    pub fn success(tool: impl Into<String>, result: Value) -> Self {
+        Self {
+            tool: tool.into(),
+            result: Some(result),
+            error: None,
+            error_kind: None,
+        }
+    }
+
+    /// Failed run of `tool` that returned an error
+    pub fn failure(tool: impl Into<String>, error: impl Into<String>) -> Self {
+        Self::failure_with_kind(tool, ToolErrorKind::Execution, error)
+    }
+
+    /// Failed call of `tool` for the reason given by `kind`
+    pub fn failure_with_kind(
+        tool: impl Into<String>,
+        kind: ToolErrorKind,
+        error: impl Into<String>,
+    ) -> Self {
+        Self {
+            tool: tool.into(),
+            result: None,
+            error: Some(error.into()),
+            error_kind: Some(kind),
+        }
+    }
+
+    /// Wrap the outcome of [`AiTool::execute`]
+    ///
+    /// A [`LutsError::InvalidParams`] or [`LutsError::Timeout`] from the tool
+    /// is reported as that kind of failure rather than as
+    /// [`ToolErrorKind::Execution`].
+    pub fn from_outcome(tool: impl Into<String>, outcome: Result<Value, Error>) -> Self {
+        match outcome {
+            Ok(result) => Self::success(tool, result),
+            Err(e) => {
+                let kind = match e.downcast_ref::<LutsError>() {
+                    Some(LutsError::InvalidParams(_)) => ToolErrorKind::Validation,
+                    Some(LutsError::Timeout(_)) => ToolErrorKind::Timeout,
+                    _ => ToolErrorKind::Execution,
+                };
+                Self::failure_with_kind(tool, kind, e.to_string())
+            }
+        }
+    }
+
+    /// Whether the tool ran without error
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// The failure, if the tool didn't succeed
+    pub fn tool_error(&self) -> Option<ToolError> {
+        self.error.as_ref().map(|message| ToolError {
+            kind: self.error_kind.unwrap_or(ToolErrorKind::Execution),
+            message: message.clone(),
+        })
+    }
+
+    /// Text to show for this result
+    ///
+    /// String results are used as-is, other values as compact JSON, and
+    /// failures as their error message.
+    pub fn display_text(&self) -> String {
+        match (&self.error, &self.result) {
+            (Some(error), _) => error.clone(),
+            (None, Some(Value::String(text))) => text.clone(),
+            (None, Some(value)) => value.to_string(),
+            (None, None) => String::new(),
+        }
+    }
+
+    /// The envelope as a JSON string
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| {
+            serde_json::json!({ "tool": self.tool, "error": "Unserializable tool result" })
+                .to_string()
+        })
+    }
+
+    /// Parse an envelope produced by [`ToolResult::to_json`]
+    ///
+    /// Returns `None` for anything that isn't an envelope.
+    pub fn from_json(json: &str) -> Option<Self> {
+        serde_json::from_str::<Self>(json)
+            .ok()
+            .filter(|envelope| envelope.result.is_some() || envelope.error.is_some())
+    }
+}
+
+/// Ways `value` breaks `schema`, worded so a model can correct its call
+///
+/// Covers the parts of JSON Schema tool schemas use: `type`, `required`,
+/// `properties`, `items`, `enum`, `minimum` and `maximum`; anything else is
+/// ignored. A `null` optional field counts as absent, since models often
+/// send one for parameters they mean to leave out.
+pub fn schema_violations(schema: &Value, value: &Value) -> Vec<String> {
+    let mut violations = Vec::new();
+    check_against_schema(schema, value, "", &mut violations);
+    violations
+}
+
+fn check_against_schema(schema: &Value, value: &Value, path: &str, violations: &mut Vec<String>) {
+    let subject = if path.is_empty() {
+        "parameters".to_string()
+    } else {
+        format!("`{}`", path)
+    };
+
+    let expected: Vec<&str> = match schema.get("type") {
+        Some(Value::String(name)) => vec![name.as_str()],
+        Some(Value::Array(names)) => names.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    if !expected.is_empty() && !expected.iter().any(|name| has_json_type(value, name)) {
+        violations.push(format!(
+            "{} should be {}, got {}",
+            subject,
+            expected.join(" or "),
+            json_type_name(value)
+        ));
+        return;
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array)
+        && !allowed.contains(value)
+    {
+        let allowed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+        violations.push(format!("{} must be one of {}", subject, allowed.join(", ")));
+    }
+
+    if let Some(number) = value.as_f64() {
+        if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64)
+            && number < minimum
+        {
+            violations.push(format!("{} must be at least {}", subject, minimum));
+        }
+        if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64)
+            && number > maximum
+        {
+            violations.push(format!("{} must be at most {}", subject, maximum));
+        }
+    }
+
+    let field_path = |name: &str| {
+        if path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", path, name)
+        }
+    };
+    match value {
+        Value::Object(fields) => {
+            let required: Vec<&str> = schema
+                .get("required")
+                .and_then(Value::as_array)
+                .map(|names| names.iter().filter_map(Value::as_str).collect())
+                .unwrap_or_default();
+            for name in &required {
+                if fields.get(*name).is_none_or(Value::is_null) {
+                    violations.push(format!("missing required field `{}`", field_path(name)));
+                }
+            }
+
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (name, field) in fields {
+                if field.is_null() && !required.contains(&name.as_str()) {
+                    continue;
+                }
+                if let Some(field_schema) = properties.and_then(|properties| properties.get(name)) {
+                    check_against_schema(field_schema, field, &field_path(name), violations);
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (idx, item) in items.iter().enumerate() {
+                    let item_path = format!("{}[{}]", path, idx);
+                    check_against_schema(item_schema, item, &item_path, violations);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Whether `value` is of the JSON Schema type `name`; unknown types match anything
+fn has_json_type(value: &Value, name: &str) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+fn json_type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
 
 /// A tool that can be used by an AI assistant
 #[async_trait]
@@ -34,6 +320,29 @@ pub trait AiTool: Send + Sync {
     /// Execute the tool with the given parameters
     async fn execute(&self, params: Value) -> Result<Value, Error>;
 
+    /// How long [`AiTool::run`] waits for the tool before giving up
+    ///
+    /// `None`, the default, waits indefinitely.
+    fn timeout(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Execute the tool and report the outcome in a [`ToolResult`] envelope
+    ///
+    /// Callers that show or forward tool output should use this rather than
+    /// [`AiTool::execute`], so every tool's output has the same shape. A run
+    /// that outlasts [`AiTool::timeout`] is abandoned and reported as a
+    /// [`ToolErrorKind::Timeout`] failure with the error `"timeout"`.
+    async fn run(&self, params: Value) -> ToolResult {
+        let Some(limit) = self.timeout() else {
+            return ToolResult::from_outcome(self.name(), self.execute(params).await);
+        };
+        match tokio::time::timeout(limit, self.execute(params)).await {
+            Ok(outcome) => ToolResult::from_outcome(self.name(), outcome),
+            Err(_) => ToolResult::failure_with_kind(self.name(), ToolErrorKind::Timeout, "timeout"),
+        }
+    }
+
     /// Validate the parameters against the schema
     fn validate_params(&self, _params: &Value) -> Result<(), Error> {
         // Default implementation that just passes validation
@@ -106,7 +106,7 @@ impl AiTool for ModifyCoreBlockTool {
         let mut manager = self.core_block_manager.write().await;
 
         // Ensure core blocks are initialized
-        manager.initialize()?;
+        manager.initialize().await?;
 
         // Handle different operations
         let final_content = match operation {
@@ -144,7 +144,9 @@ impl AiTool for ModifyCoreBlockTool {
         };
 
         // Update the core block
-        manager.update_block(core_block_type, final_content.clone())?;
+        let budget_warning = manager
+            .update_block(core_block_type, final_content.clone())
+            .await?;
 
         // Get stats for response
         let stats = manager.get_stats();
@@ -165,7 +167,8 @@ impl AiTool for ModifyCoreBlockTool {
             "content_length": final_content.len(),
             "active_blocks": stats.active_blocks,
             "token_usage": stats.token_usage,
-            "budget_utilization": format!("{:.1}%", stats.budget_utilization)
+            "budget_utilization": format!("{:.1}%", stats.budget_utilization),
+            "budget_warning": budget_warning.map(|warning| warning.to_string())
         }))
     }
 }
@@ -9,6 +9,7 @@ use reqwest;
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::time::Duration;
 use tracing::{debug, trace};
 
 /// Parameters for the DuckDuckGo search tool.
@@ -69,6 +70,11 @@ inurl:cats	URL contains "cats""#
         })
     }
 
+    fn timeout(&self) -> Option<Duration> {
+        // A slow search shouldn't stall the whole turn
+        Some(Duration::from_secs(15))
+    }
+
     async fn execute(&self, args: Value) -> Result<Value, Error> {
         let params: SearchParams = serde_json::from_value(args.clone())
             .map_err(|_| anyhow!("Missing or invalid 'query' parameter"))?;
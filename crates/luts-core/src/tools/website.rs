@@ -1,5 +1,6 @@
 use anyhow::{Error, anyhow};
 use serde_json::Value;
+use std::time::Duration;
 use tracing::debug;
 
 use crate::tools::AiTool;
@@ -40,6 +41,11 @@ Note: The website must start with http:// or https://. If not, https:// will be
         })
     }
 
+    fn timeout(&self) -> Option<Duration> {
+        // A hanging site shouldn't stall the whole turn
+        Some(Duration::from_secs(15))
+    }
+
     fn validate_params(&self, params: &Value) -> Result<(), Error> {
         if !params.is_object() {
             return Err(anyhow!("Parameters must be an object"));
@@ -4,8 +4,12 @@
 //! aspects of the LUTS system.
 
 pub mod blocks;
+pub mod tokenizer;
 pub mod tokens;
+pub mod usage_report;
 
 // Re-export key types for convenience
 pub use blocks::BlockUtils;
-pub use tokens::{BudgetStatus, TokenAnalytics, TokenBudget, TokenManager, TokenUsage};
\ No newline at end of file
+pub use tokenizer::TokenCounter;
+pub use tokens::{BudgetReport, BudgetStatus, TokenAnalytics, TokenBudget, TokenManager, TokenUsage};
+pub use usage_report::{UsageReport, UsageTotals};
\ No newline at end of file
@@ -0,0 +1,98 @@
+//! Token counting with the model's own tokenizer
+//!
+//! Models with a known BPE encoding (the OpenAI families tiktoken covers)
+//! are counted exactly. Anything else falls back to a words-times-1.3
+//! estimate, which is rough for code and non-English text. Encodings are
+//! loaded once and shared by every counter.
+
+use std::fmt;
+use std::sync::OnceLock;
+use tiktoken_rs::CoreBPE;
+use tiktoken_rs::tokenizer::{Tokenizer, get_tokenizer};
+use tracing::warn;
+
+/// Rough token count for text whose tokenizer is unknown
+pub fn estimate_tokens(text: &str) -> usize {
+    (text.split_whitespace().count() as f32 * 1.3) as usize
+}
+
+/// The shared encoding for `tokenizer`, loaded on first use
+fn encoding(tokenizer: Tokenizer) -> Option<&'static CoreBPE> {
+    static O200K: OnceLock<Option<CoreBPE>> = OnceLock::new();
+    static CL100K: OnceLock<Option<CoreBPE>> = OnceLock::new();
+    static P50K: OnceLock<Option<CoreBPE>> = OnceLock::new();
+    static R50K: OnceLock<Option<CoreBPE>> = OnceLock::new();
+
+    let (cell, load): (_, fn() -> anyhow::Result<CoreBPE>) = match tokenizer {
+        Tokenizer::O200kBase => (&O200K, tiktoken_rs::o200k_base),
+        Tokenizer::Cl100kBase => (&CL100K, tiktoken_rs::cl100k_base),
+        Tokenizer::P50kBase => (&P50K, tiktoken_rs::p50k_base),
+        Tokenizer::R50kBase | Tokenizer::Gpt2 => (&R50K, tiktoken_rs::r50k_base),
+        _ => return None,
+    };
+    cell.get_or_init(|| {
+        load()
+            .map_err(|e| warn!("Failed to load {:?} tokenizer: {}", tokenizer, e))
+            .ok()
+    })
+    .as_ref()
+}
+
+/// Counts tokens the way a model's tokenizer does
+///
+/// The default counter has no tokenizer and always estimates.
+#[derive(Clone, Copy, Default)]
+pub struct TokenCounter {
+    bpe: Option<&'static CoreBPE>,
+}
+
+impl TokenCounter {
+    /// Counter for `model`, estimating when its tokenizer is unknown
+    ///
+    /// A `provider/` prefix is ignored, so `openai/gpt-4o` counts like `gpt-4o`.
+    pub fn for_model(model: &str) -> Self {
+        let name = model.rsplit_once('/').map_or(model, |(_, name)| name);
+        Self {
+            bpe: get_tokenizer(name).and_then(encoding),
+        }
+    }
+
+    /// Tokens in `text`
+    pub fn count(&self, text: &str) -> usize {
+        match self.bpe {
+            Some(bpe) => bpe.encode_ordinary(text).len(),
+            None => estimate_tokens(text),
+        }
+    }
+
+    /// Whether counts come from a real tokenizer rather than the estimate
+    pub fn is_exact(&self) -> bool {
+        self.bpe.is_some()
+    }
+}
+
+impl fmt::Debug for TokenCounter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TokenCounter")
+            .field("exact", &self.is_exact())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_match_tiktoken() {
+        let gpt4 = TokenCounter::for_model("gpt-4");
+        assert!(gpt4.is_exact());
+        // cl100k_base: [83, 1609, 5963, 374, 2294, 0]
+        assert_eq!(gpt4.count("tiktoken is great!"), 6);
+        assert_eq!(TokenCounter::for_model("openai/gpt-4").count("hello world"), 2);
+
+        let unknown = TokenCounter::for_model("DeepSeek-R1-0528");
+        assert!(!unknown.is_exact());
+        assert_eq!(unknown.count("tiktoken is great!"), estimate_tokens("tiktoken is great!"));
+    }
+}
@@ -3,8 +3,11 @@
 //! This module provides comprehensive token tracking, budgeting, and analytics
 //! for AI conversations and tool usage, integrating with genai's Usage struct.
 
+use crate::utils::tokenizer::TokenCounter;
+use crate::utils::usage_report::UsageReport;
 use anyhow::Result;
 use chrono::{DateTime, Datelike, Utc};
+use luts_common::ModelRegistry;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tokio::sync::RwLock;
@@ -137,12 +140,18 @@ pub struct TokenManager {
     usage_history: RwLock<Vec<TokenUsage>>,
     /// Budget configuration
     budget: RwLock<TokenBudget>,
+    /// Budgets that replace the global one for a user's own usage
+    user_budgets: RwLock<HashMap<String, TokenBudget>>,
+    /// Total tokens each limited session may use
+    session_limits: RwLock<HashMap<String, u32>>,
     /// Pricing configuration per provider/model
     pricing: RwLock<HashMap<String, TokenPricing>>,
     /// Current analytics cache
     analytics_cache: RwLock<Option<TokenAnalytics>>,
     /// Data storage path
     storage_path: std::path::PathBuf,
+    /// Counts tokens for budgeting before the provider reports usage
+    counter: TokenCounter,
 }
 
 impl TokenManager {
@@ -175,12 +184,29 @@ impl TokenManager {
         Self {
             usage_history: RwLock::new(Vec::new()),
             budget: RwLock::new(TokenBudget::default()),
+            user_budgets: RwLock::new(HashMap::new()),
+            session_limits: RwLock::new(HashMap::new()),
             pricing: RwLock::new(pricing),
             analytics_cache: RwLock::new(None),
             storage_path,
+            counter: TokenCounter::default(),
         }
     }
 
+    /// Count tokens with `model`'s tokenizer instead of the word estimate
+    pub fn with_model(mut self, model: &str) -> Self {
+        self.counter = TokenCounter::for_model(model);
+        self
+    }
+
+    /// Tokens in `text` for the configured model
+    ///
+    /// Without a model, or for one whose tokenizer is unknown, this is an
+    /// estimate.
+    pub fn count_tokens(&self, text: &str) -> usize {
+        self.counter.count(text)
+    }
+
     /// Record token usage for an operation
     pub async fn record_usage(&self, mut usage: TokenUsage) -> Result<()> {
         // Calculate estimated cost
@@ -194,10 +220,9 @@ impl TokenManager {
         }
         drop(pricing);
 
-        // Record usage
-        let mut history = self.usage_history.write().await;
-        history.push(usage.clone());
-        
+        // Record usage, releasing the history before the budget check and save read it
+        self.usage_history.write().await.push(usage.clone());
+
         // Clear analytics cache to force recalculation
         *self.analytics_cache.write().await = None;
 
@@ -232,6 +257,95 @@ impl TokenManager {
         Ok(())
     }
 
+    /// Give `user_id` its own budget, counted against only their usage
+    pub async fn set_user_budget(&self, user_id: &str, budget: TokenBudget) -> Result<()> {
+        self.user_budgets
+            .write()
+            .await
+            .insert(user_id.to_string(), budget);
+        self.save_to_storage().await?;
+        info!("Updated token budget for user {}", user_id);
+        Ok(())
+    }
+
+    /// Limit the total tokens `session_id` may use
+    pub async fn set_session_limit(&self, session_id: &str, limit: u32) -> Result<()> {
+        self.session_limits
+            .write()
+            .await
+            .insert(session_id.to_string(), limit);
+        self.save_to_storage().await?;
+        info!("Limited session {} to {} tokens", session_id, limit);
+        Ok(())
+    }
+
+    /// Check whether a request of `estimated_tokens` fits the token budgets
+    ///
+    /// The user's own budget applies if they have one, otherwise the global
+    /// budget over everyone's usage; a session limit applies on top. Only
+    /// token limits are checked, since a request's cost isn't known before
+    /// it is sent.
+    pub async fn check_budget(
+        &self,
+        user_id: &str,
+        session_id: &str,
+        estimated_tokens: u32,
+    ) -> BudgetStatus {
+        let user_budget = self.user_budgets.read().await.get(user_id).cloned();
+        let per_user = user_budget.is_some();
+        let budget = match user_budget {
+            Some(budget) => budget,
+            None => self.budget.read().await.clone(),
+        };
+
+        let (today_start, month_start) = period_starts(Utc::now());
+        let (mut daily, mut monthly, mut session) = (0u32, 0u32, 0u32);
+        for usage in self.usage_history.read().await.iter() {
+            if usage.session_id == session_id {
+                session = session.saturating_add(usage.total_tokens);
+            }
+            if per_user && usage.user_id != user_id {
+                continue;
+            }
+            if usage.timestamp >= month_start {
+                monthly = monthly.saturating_add(usage.total_tokens);
+            }
+            if usage.timestamp >= today_start {
+                daily = daily.saturating_add(usage.total_tokens);
+            }
+        }
+
+        let session_limit = self.session_limits.read().await.get(session_id).copied();
+        let limits = [
+            ("daily", daily, budget.daily_limit),
+            ("monthly", monthly, budget.monthly_limit),
+            ("session", session, session_limit),
+        ];
+
+        let mut status = BudgetStatus::WithinBudget;
+        for (scope, used, limit) in limits {
+            let Some(limit) = limit else { continue };
+            let after = used.saturating_add(estimated_tokens);
+            if after > limit {
+                return BudgetStatus::Exceeded {
+                    scope: scope.to_string(),
+                    requested: estimated_tokens,
+                    available: limit.saturating_sub(used),
+                };
+            }
+            if status == BudgetStatus::WithinBudget
+                && after as f64 >= limit as f64 * budget.warning_threshold
+            {
+                status = BudgetStatus::Warning {
+                    scope: scope.to_string(),
+                    used: after,
+                    limit,
+                };
+            }
+        }
+        status
+    }
+
     /// Get comprehensive token analytics
     pub async fn get_analytics(&self) -> Result<TokenAnalytics> {
         // Check if we have cached analytics
@@ -249,7 +363,7 @@ impl TokenManager {
     }
 
     /// Check if current usage is within budget limits
-    pub async fn check_budget_status(&self) -> Result<BudgetStatus> {
+    pub async fn check_budget_status(&self) -> Result<BudgetReport> {
         let analytics = self.get_analytics().await?;
         let budget = self.budget.read().await;
 
@@ -300,7 +414,7 @@ impl TokenManager {
             }
         }
 
-        Ok(BudgetStatus {
+        Ok(BudgetReport {
             within_limits: exceeded.is_empty(),
             warnings,
             exceeded: exceeded.clone(),
@@ -324,14 +438,16 @@ impl TokenManager {
 
     /// Clear usage history (with optional date range)
     pub async fn clear_history(&self, before_date: Option<DateTime<Utc>>) -> Result<()> {
-        let mut history = self.usage_history.write().await;
-        
-        if let Some(before) = before_date {
-            history.retain(|usage| usage.timestamp >= before);
-            info!("Cleared token usage history before {}", before);
-        } else {
-            history.clear();
-            info!("Cleared all token usage history");
+        {
+            let mut history = self.usage_history.write().await;
+
+            if let Some(before) = before_date {
+                history.retain(|usage| usage.timestamp >= before);
+                info!("Cleared token usage history before {}", before);
+            } else {
+                history.clear();
+                info!("Cleared all token usage history");
+            }
         }
 
         // Clear analytics cache
@@ -341,6 +457,19 @@ impl TokenManager {
         Ok(())
     }
 
+    /// Usage matching `filter`, totalled by model, day and user
+    ///
+    /// Costs not estimated when the usage was recorded come from the bundled
+    /// model registry's prices.
+    pub async fn usage_report(&self, filter: &luts_common::UsageFilter) -> UsageReport {
+        let history = self.usage_history.read().await;
+        UsageReport::build(
+            history.iter(),
+            &UsageFilter::from(filter.clone()),
+            &ModelRegistry::bundled(),
+        )
+    }
+
     /// Export usage data to various formats
     pub async fn export_usage(&self, format: ExportFormat, path: &std::path::Path) -> Result<()> {
         let history = self.usage_history.read().await;
@@ -382,8 +511,7 @@ impl TokenManager {
         let now = Utc::now();
         
         // Filter for today and this month
-        let today_start = now.date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc();
-        let month_start = now.date_naive().with_day(1).unwrap().and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let (today_start, month_start) = period_starts(now);
         
         let daily_usage: Vec<_> = history.iter()
             .filter(|usage| usage.timestamp >= today_start)
@@ -487,6 +615,8 @@ impl TokenManager {
             usage_history: history.clone(),
             budget: budget.clone(),
             pricing: pricing.clone(),
+            user_budgets: self.user_budgets.read().await.clone(),
+            session_limits: self.session_limits.read().await.clone(),
         };
 
         let json = serde_json::to_string_pretty(&storage_data)?;
@@ -507,6 +637,8 @@ impl TokenManager {
             *manager.usage_history.write().await = storage_data.usage_history;
             *manager.budget.write().await = storage_data.budget;
             *manager.pricing.write().await = storage_data.pricing;
+            *manager.user_budgets.write().await = storage_data.user_budgets;
+            *manager.session_limits.write().await = storage_data.session_limits;
             
             info!("Loaded token manager from storage with {} usage records", usage_history_len);
         }
@@ -515,15 +647,44 @@ impl TokenManager {
     }
 }
 
+/// Start of today and of this month, in UTC
+fn period_starts(now: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+    let today = now.date_naive();
+    let today_start = today.and_hms_opt(0, 0, 0).unwrap().and_utc();
+    let month_start = today.with_day(1).unwrap().and_hms_opt(0, 0, 0).unwrap().and_utc();
+    (today_start, month_start)
+}
+
 /// Budget status information
 #[derive(Debug, Clone)]
-pub struct BudgetStatus {
+pub struct BudgetReport {
     pub within_limits: bool,
     pub warnings: Vec<String>,
     pub exceeded: Vec<String>,
     pub should_auto_summarize: bool,
 }
 
+/// Whether a request fits the token budgets, from [`TokenManager::check_budget`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BudgetStatus {
+    /// The request fits every budget
+    WithinBudget,
+    /// The request fits but takes `scope` past its warning threshold
+    Warning {
+        scope: String,
+        /// Tokens used in `scope` once the request is counted
+        used: u32,
+        limit: u32,
+    },
+    /// The request would take `scope` over its limit
+    Exceeded {
+        scope: String,
+        requested: u32,
+        /// Tokens `scope` has left
+        available: u32,
+    },
+}
+
 /// Filter for usage history queries
 #[derive(Debug, Clone)]
 pub struct UsageFilter {
@@ -537,6 +698,21 @@ pub struct UsageFilter {
     pub max_tokens: Option<u32>,
 }
 
+impl From<luts_common::UsageFilter> for UsageFilter {
+    fn from(filter: luts_common::UsageFilter) -> Self {
+        Self {
+            provider: filter.provider,
+            model: filter.model,
+            operation_type: filter.operation_type,
+            session_id: filter.session_id,
+            user_id: filter.user_id,
+            date_range: filter.date_range,
+            min_tokens: filter.min_tokens,
+            max_tokens: filter.max_tokens,
+        }
+    }
+}
+
 impl UsageFilter {
     pub fn matches(&self, usage: &TokenUsage) -> bool {
         if let Some(ref provider) = self.provider {
@@ -604,4 +780,140 @@ struct TokenStorageData {
     usage_history: Vec<TokenUsage>,
     budget: TokenBudget,
     pricing: HashMap<String, TokenPricing>,
-}
\ No newline at end of file
+    #[serde(default)]
+    user_budgets: HashMap<String, TokenBudget>,
+    #[serde(default)]
+    session_limits: HashMap<String, u32>,
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(user_id: &str, session_id: &str, total_tokens: u32) -> TokenUsage {
+        TokenUsage {
+            input_tokens: total_tokens,
+            output_tokens: 0,
+            total_tokens,
+            estimated_cost: None,
+            timestamp: Utc::now(),
+            provider: "test".to_string(),
+            model: "test".to_string(),
+            operation_type: "chat".to_string(),
+            session_id: session_id.to_string(),
+            user_id: user_id.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_budget_warns_then_stops() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = TokenManager::new(dir.path().join("tokens.json"));
+        manager
+            .update_budget(TokenBudget {
+                daily_limit: Some(1000),
+                monthly_limit: None,
+                warning_threshold: 0.8,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        manager.record_usage(usage("alice", "s1", 700)).await.unwrap();
+
+        assert_eq!(manager.check_budget("alice", "s1", 50).await, BudgetStatus::WithinBudget);
+        assert_eq!(
+            manager.check_budget("alice", "s1", 150).await,
+            BudgetStatus::Warning {
+                scope: "daily".to_string(),
+                used: 850,
+                limit: 1000
+            }
+        );
+        assert_eq!(
+            manager.check_budget("alice", "s1", 400).await,
+            BudgetStatus::Exceeded {
+                scope: "daily".to_string(),
+                requested: 400,
+                available: 300
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_usage_report_totals_by_model() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = TokenManager::new(dir.path().join("tokens.json"));
+        for (user_id, model, input, output) in [
+            ("alice", "gpt-4", 1000, 500),
+            ("bob", "gpt-4", 2000, 0),
+            ("alice", "gemini-2.5-flash", 1000, 1000),
+        ] {
+            let mut record = usage(user_id, "s1", input + output);
+            record.model = model.to_string();
+            record.input_tokens = input;
+            record.output_tokens = output;
+            manager.record_usage(record).await.unwrap();
+        }
+
+        let report = manager
+            .usage_report(&luts_common::UsageFilter::default())
+            .await;
+        let gpt4 = &report.by_model["gpt-4"];
+        assert_eq!(gpt4.requests, 2);
+        assert_eq!(gpt4.total_tokens, 3500);
+        // 3000 input at $0.03/1K and 500 output at $0.06/1K
+        assert!((gpt4.estimated_cost - 0.12).abs() < 1e-9);
+        let flash = &report.by_model["gemini-2.5-flash"];
+        assert_eq!(flash.total_tokens, 2000);
+        assert!((flash.estimated_cost - 0.0028).abs() < 1e-9);
+
+        assert_eq!(report.totals.total_tokens, 5500);
+        assert!((report.totals.estimated_cost - 0.1228).abs() < 1e-9);
+        assert_eq!(report.by_user["alice"].requests, 2);
+        assert_eq!(report.by_day.len(), 1);
+
+        let csv = report.to_csv();
+        assert!(csv.contains("model,gpt-4,2,3000,500,3500,0.120000\n"), "{}", csv);
+        assert!(csv.ends_with("total,,3,4000,1500,5500,0.122800\n"), "{}", csv);
+    }
+
+    #[tokio::test]
+    async fn test_user_and_session_budgets_survive_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tokens.json");
+        let manager = TokenManager::new(path.clone());
+        manager
+            .set_user_budget(
+                "bob",
+                TokenBudget {
+                    daily_limit: Some(100),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        manager.set_session_limit("s2", 500).await.unwrap();
+        drop(manager);
+
+        let manager = TokenManager::load_from_storage(path).await.unwrap();
+        manager.record_usage(usage("alice", "s2", 450)).await.unwrap();
+
+        assert_eq!(
+            manager.check_budget("alice", "s2", 100).await,
+            BudgetStatus::Exceeded {
+                scope: "session".to_string(),
+                requested: 100,
+                available: 50
+            }
+        );
+        // Bob's budget only counts his own usage
+        assert_eq!(
+            manager.check_budget("bob", "s3", 150).await,
+            BudgetStatus::Exceeded {
+                scope: "daily".to_string(),
+                requested: 150,
+                available: 100
+            }
+        );
+        assert_eq!(manager.check_budget("bob", "s3", 50).await, BudgetStatus::WithinBudget);
+    }
+}
@@ -0,0 +1,147 @@
+//! Usage reports for billing
+//!
+//! A [`UsageReport`] totals recorded token usage by model, by day and by
+//! user, with an estimated cost for each group, and writes itself out as
+//! JSON or as CSV for a spreadsheet.
+
+use crate::utils::tokens::{TokenUsage, UsageFilter};
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
+use luts_common::ModelRegistry;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Token and cost totals for one group of usage records
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageTotals {
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub total_tokens: u64,
+    /// Estimated cost in USD
+    pub estimated_cost: f64,
+}
+
+impl UsageTotals {
+    fn add(&mut self, usage: &TokenUsage, cost: f64) {
+        self.requests += 1;
+        self.input_tokens += usage.input_tokens as u64;
+        self.output_tokens += usage.output_tokens as u64;
+        self.total_tokens += usage.total_tokens as u64;
+        self.estimated_cost += cost;
+    }
+}
+
+/// Usage totals grouped by model, day (UTC) and user
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageReport {
+    pub generated_at: DateTime<Utc>,
+    /// Time window the report covers, if the filter set one
+    pub date_range: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    pub totals: UsageTotals,
+    pub by_model: BTreeMap<String, UsageTotals>,
+    pub by_day: BTreeMap<NaiveDate, UsageTotals>,
+    pub by_user: BTreeMap<String, UsageTotals>,
+}
+
+impl UsageReport {
+    /// Total the records `filter` matches
+    ///
+    /// A record's cost is the one estimated when it was recorded; records
+    /// without one are priced from `models`, and count as free if the model
+    /// is unknown.
+    pub fn build<'a>(
+        records: impl IntoIterator<Item = &'a TokenUsage>,
+        filter: &UsageFilter,
+        models: &ModelRegistry,
+    ) -> Self {
+        let mut report = Self {
+            generated_at: Utc::now(),
+            date_range: filter.date_range,
+            totals: UsageTotals::default(),
+            by_model: BTreeMap::new(),
+            by_day: BTreeMap::new(),
+            by_user: BTreeMap::new(),
+        };
+
+        for usage in records.into_iter().filter(|usage| filter.matches(usage)) {
+            let cost = usage
+                .estimated_cost
+                .or_else(|| {
+                    models.lookup(&usage.model).map(|info| {
+                        info.pricing()
+                            .calculate_cost(usage.input_tokens, usage.output_tokens)
+                    })
+                })
+                .unwrap_or(0.0);
+
+            report.totals.add(usage, cost);
+            report
+                .by_model
+                .entry(usage.model.clone())
+                .or_default()
+                .add(usage, cost);
+            report
+                .by_day
+                .entry(usage.timestamp.date_naive())
+                .or_default()
+                .add(usage, cost);
+            report
+                .by_user
+                .entry(usage.user_id.clone())
+                .or_default()
+                .add(usage, cost);
+        }
+        report
+    }
+
+    /// The report as pretty-printed JSON
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// The report as CSV, one row per group plus a final total row
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "group,key,requests,input_tokens,output_tokens,total_tokens,estimated_cost\n",
+        );
+        let groups = self
+            .by_model
+            .iter()
+            .map(|(key, totals)| ("model", key.clone(), totals))
+            .chain(
+                self.by_day
+                    .iter()
+                    .map(|(day, totals)| ("day", day.to_string(), totals)),
+            )
+            .chain(
+                self.by_user
+                    .iter()
+                    .map(|(key, totals)| ("user", key.clone(), totals)),
+            )
+            .chain(std::iter::once(("total", String::new(), &self.totals)));
+
+        for (group, key, totals) in groups {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{:.6}\n",
+                group,
+                csv_field(&key),
+                totals.requests,
+                totals.input_tokens,
+                totals.output_tokens,
+                totals.total_tokens,
+                totals.estimated_cost
+            ));
+        }
+        csv
+    }
+}
+
+/// Quote a CSV field if it contains a separator, quote or newline
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
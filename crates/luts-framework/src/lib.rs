@@ -19,13 +19,13 @@ pub use luts_memory::BlockUtils;
 pub use luts_common::{LutsError, Result};
 pub use luts_memory::{MemoryManager, MemoryBlock, BlockType, MemoryContent, BlockId};
 pub use luts_llm::{LLMService, AiTool, ResponseStreamManager};
-pub use luts_tools::{MathTool, DDGSearchTool, WebsiteTool, SemanticSearchTool};
+pub use luts_tools::{MathTool, DDGSearchTool, WebsiteTool, WebsiteToolConfig, SemanticSearchTool, MemoryStatsTool, FileSystemTool};
 pub use luts_agents::{Agent, AgentConfig, PersonalityAgentBuilder, AgentMessage, MessageResponse};
 
 /// Convenience prelude module for common imports
 pub mod prelude {
     // Common types and errors
-    pub use luts_common::{LutsError, Result, BaseConfig, ProviderConfig, TokenPricing};
+    pub use luts_common::{LutsError, Result, BaseConfig, ProviderConfig, TokenPricing, ModelInfo, ModelRegistry};
     
     // Memory management
     pub use luts_memory::{
@@ -36,7 +36,7 @@ pub mod prelude {
     // LLM and streaming
     pub use luts_llm::{
         LLMService, AiService, ResponseStreamManager, StreamConfig, InternalChatMessage,
-        ConversationExporter, ConversationSearchEngine, AutoSaveManager
+        ConversationExporter, ConversationSearchEngine, AutoSaveManager, GenerationParams
     };
     
     // Streaming (from luts-core until migrated)
@@ -51,11 +51,11 @@ pub mod prelude {
     pub use luts_memory::BlockUtils;
     
     // Context and token utils (from luts-core until migrated)
-    pub use luts_core::utils::{TokenManager, TokenBudget, TokenUsage};
+    pub use luts_core::utils::{TokenManager, TokenBudget, TokenUsage, BudgetStatus, UsageReport, TokenCounter};
     
     // Tools
-    pub use luts_tools::{MathTool, DDGSearchTool, WebsiteTool, SemanticSearchTool};
-    pub use luts_llm::AiTool;
+    pub use luts_tools::{MathTool, DDGSearchTool, WebsiteTool, WebsiteToolConfig, SemanticSearchTool, SummarizeUrlTool, MemoryStatsTool, FileSystemTool};
+    pub use luts_llm::{AiTool, ToolResult};
     
     // Agent system
     pub use luts_agents::{
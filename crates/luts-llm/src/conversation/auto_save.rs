@@ -8,7 +8,8 @@ use crate::llm::InternalChatMessage;
 use luts_memory::{MemoryBlock, MemoryManager};
 use luts_core::utils::tokens::TokenUsage;
 use anyhow::Result;
-use chrono::{DateTime, Utc, Duration, Timelike};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc, Timelike};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -17,6 +18,179 @@ use tokio::sync::{RwLock, Mutex};
 use tokio::time::{interval, Interval};
 use tracing::{info, warn, error, debug};
 
+/// Storage for auto-saves
+///
+/// The manager writes through a backend so autosaves can live on disk, in a
+/// database or in object storage. [`FilesystemAutoSaveBackend`] is the default.
+#[async_trait]
+pub trait AutoSaveBackend: Send + Sync {
+    /// Store one auto-save
+    async fn persist(&self, data: &AutoSaveData) -> Result<()>;
+
+    /// The most recent readable auto-save for a session
+    ///
+    /// Saves that fail to deserialize, such as files cut short by a crash,
+    /// are skipped in favor of the next most recent one.
+    async fn load_latest(&self, session_id: &str) -> Result<Option<AutoSaveData>>;
+
+    /// Metadata of a session's auto-saves, newest first
+    async fn list(&self, session_id: &str) -> Result<Vec<AutoSaveMetadata>>;
+
+    /// Delete all but the `keep` newest auto-saves of a session
+    ///
+    /// Returns how many were deleted.
+    async fn prune(&self, session_id: &str, keep: usize) -> Result<usize>;
+
+    /// The most recent readable auto-save of any session
+    ///
+    /// Backends that can't enumerate their sessions find nothing.
+    async fn load_latest_of_any_session(&self) -> Result<Option<AutoSaveData>> {
+        Ok(None)
+    }
+}
+
+/// Stores each auto-save as a JSON file, one directory per session
+pub struct FilesystemAutoSaveBackend {
+    directory: PathBuf,
+}
+
+impl FilesystemAutoSaveBackend {
+    /// Store auto-saves under `directory`
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    fn session_dir(&self, session_id: &str) -> PathBuf {
+        // Session IDs become directory names, so keep them to safe characters
+        let name: String = session_id
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        self.directory.join(name)
+    }
+
+    /// A session's auto-save files with their data, newest first
+    async fn entries(&self, session_id: &str) -> Result<Vec<(PathBuf, AutoSaveData)>> {
+        let session_dir = self.session_dir(session_id);
+        if !session_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut entries = Vec::new();
+        let mut dir = tokio::fs::read_dir(&session_dir).await?;
+        while let Some(entry) = dir.next_entry().await? {
+            let path = entry.path();
+            let is_save = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("autosave_") && name.ends_with(".json"));
+            if !is_save {
+                continue;
+            }
+            let data = match tokio::fs::read_to_string(&path).await {
+                Ok(content) => serde_json::from_str::<AutoSaveData>(&content).map_err(Into::into),
+                Err(e) => Err(anyhow::Error::from(e)),
+            };
+            match data {
+                Ok(data) => entries.push((path, data)),
+                Err(e) => warn!("Skipping unreadable auto-save {:?}: {}", path, e),
+            }
+        }
+
+        entries.sort_by(|a, b| {
+            b.1.metadata
+                .created_at
+                .cmp(&a.1.metadata.created_at)
+                .then(b.1.metadata.sequence.cmp(&a.1.metadata.sequence))
+        });
+        Ok(entries)
+    }
+}
+
+#[async_trait]
+impl AutoSaveBackend for FilesystemAutoSaveBackend {
+    async fn persist(&self, data: &AutoSaveData) -> Result<()> {
+        let session_dir = self.session_dir(&data.metadata.session_id);
+        tokio::fs::create_dir_all(&session_dir).await?;
+
+        let type_suffix = match data.metadata.save_type {
+            AutoSaveType::Periodic => "auto",
+            AutoSaveType::ActivityTriggered => "activity",
+            AutoSaveType::IdleTriggered => "idle",
+            AutoSaveType::ExitSave => "exit",
+            AutoSaveType::ConfigChange => "config",
+            AutoSaveType::Manual => "manual",
+            AutoSaveType::Emergency => "emergency",
+            AutoSaveType::Crash => "crash",
+        };
+        let filename = format!(
+            "autosave_{}_{:04}_{}.json",
+            data.metadata.created_at.format("%Y%m%d_%H%M%S%3f"),
+            data.metadata.sequence,
+            type_suffix
+        );
+
+        let content = serde_json::to_string_pretty(data)?;
+        tokio::fs::write(session_dir.join(filename), content).await?;
+        Ok(())
+    }
+
+    async fn load_latest(&self, session_id: &str) -> Result<Option<AutoSaveData>> {
+        Ok(self.entries(session_id).await?.into_iter().next().map(|(_, data)| data))
+    }
+
+    async fn list(&self, session_id: &str) -> Result<Vec<AutoSaveMetadata>> {
+        Ok(self
+            .entries(session_id)
+            .await?
+            .into_iter()
+            .map(|(_, data)| data.metadata)
+            .collect())
+    }
+
+    async fn prune(&self, session_id: &str, keep: usize) -> Result<usize> {
+        let mut removed = 0;
+        for (path, _) in self.entries(session_id).await?.into_iter().skip(keep) {
+            match tokio::fs::remove_file(&path).await {
+                Ok(()) => {
+                    debug!("Removed old auto-save file: {:?}", path);
+                    removed += 1;
+                }
+                Err(e) => warn!("Failed to remove old auto-save file {:?}: {}", path, e),
+            }
+        }
+        Ok(removed)
+    }
+
+    async fn load_latest_of_any_session(&self) -> Result<Option<AutoSaveData>> {
+        if !self.directory.exists() {
+            return Ok(None);
+        }
+
+        let mut latest: Option<AutoSaveData> = None;
+        let mut dir = tokio::fs::read_dir(&self.directory).await?;
+        while let Some(entry) = dir.next_entry().await? {
+            if !entry.file_type().await?.is_dir() {
+                continue;
+            }
+            // Directory names are already sanitized session IDs
+            let session = entry.file_name().to_string_lossy().to_string();
+            let Some((_, data)) = self.entries(&session).await?.into_iter().next() else {
+                continue;
+            };
+            let is_newer = latest
+                .as_ref()
+                .is_none_or(|latest| data.metadata.created_at > latest.metadata.created_at);
+            if is_newer {
+                latest = Some(data);
+            }
+        }
+        Ok(latest)
+    }
+}
+
 /// Auto-save configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AutoSaveConfig {
@@ -28,13 +202,14 @@ pub struct AutoSaveConfig {
     pub save_on_message_count: Option<usize>,
     /// Save on idle time (seconds since last activity)
     pub save_on_idle_seconds: Option<u64>,
-    /// Maximum number of auto-save files to keep
-    pub max_auto_saves: usize,
+    /// Maximum number of auto-saves to keep per session
+    #[serde(alias = "max_auto_saves")]
+    pub max_versions: usize,
     /// Enable incremental saves (only save changes)
     pub incremental_saves: bool,
     /// Compress auto-save files
     pub compress_saves: bool,
-    /// Auto-save directory
+    /// Auto-save directory used by the default filesystem backend
     pub save_directory: PathBuf,
     /// Save conversation metadata
     pub save_metadata: bool,
@@ -63,7 +238,7 @@ impl Default for AutoSaveConfig {
             interval_seconds: 60,    // Auto-save every minute
             save_on_message_count: Some(5),  // Save after 5 new messages
             save_on_idle_seconds: Some(300), // Save after 5 minutes of idle
-            max_auto_saves: 10,
+            max_versions: 10,
             incremental_saves: true,
             compress_saves: true,
             save_directory: PathBuf::from("./autosaves"),
@@ -101,6 +276,9 @@ pub struct AutoSaveState {
     pub last_save_size: Option<usize>,
     /// Auto-save enabled status
     pub enabled: bool,
+    /// Sequence of the auto-save this session was recovered from, if any
+    #[serde(default)]
+    pub recovered_sequence: Option<usize>,
 }
 
 impl Default for AutoSaveState {
@@ -115,6 +293,7 @@ impl Default for AutoSaveState {
             has_unsaved_changes: false,
             last_save_size: None,
             enabled: true,
+            recovered_sequence: None,
         }
     }
 }
@@ -182,6 +361,8 @@ pub enum AutoSaveType {
     Manual,
     /// Emergency save (on crash/error)
     Emergency,
+    /// Save written from a panic hook
+    Crash,
 }
 
 /// Application state for auto-save
@@ -303,6 +484,14 @@ pub struct AutoSaveManager {
     conflicts: RwLock<Vec<AutoSaveConflict>>,
     /// Activity tracking
     last_activity: RwLock<DateTime<Utc>>,
+    /// Conversations written with each save
+    conversations: RwLock<Vec<ExportableConversation>>,
+    /// Where saves go; `None` writes files under `config.save_directory`
+    backend: Option<Arc<dyn AutoSaveBackend>>,
+    /// User the saves belong to
+    user_id: String,
+    /// Session the saves belong to
+    session_id: String,
 }
 
 impl AutoSaveManager {
@@ -328,9 +517,26 @@ impl AutoSaveManager {
             }),
             conflicts: RwLock::new(Vec::new()),
             last_activity: RwLock::new(Utc::now()),
+            conversations: RwLock::new(Vec::new()),
+            backend: None,
+            user_id: "default_user".to_string(),
+            session_id: "default_session".to_string(),
         }
     }
 
+    /// Store auto-saves in `backend` instead of the save directory
+    pub fn with_backend(mut self, backend: Arc<dyn AutoSaveBackend>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// Save on behalf of this user and session
+    pub fn with_session(mut self, user_id: impl Into<String>, session_id: impl Into<String>) -> Self {
+        self.user_id = user_id.into();
+        self.session_id = session_id.into();
+        self
+    }
+
     /// Create auto-save manager with memory manager
     pub fn new_with_memory_manager(memory_manager: Arc<MemoryManager>) -> Self {
         let mut manager = Self::new();
@@ -372,8 +578,10 @@ impl AutoSaveManager {
         // Stop existing auto-save task
         self.stop_auto_save().await;
 
-        // Create save directory
-        tokio::fs::create_dir_all(&config.save_directory).await?;
+        // Create save directory for the default filesystem backend
+        if self.backend.is_none() {
+            tokio::fs::create_dir_all(&config.save_directory).await?;
+        }
 
         // Start auto-save timer
         let timer = interval(std::time::Duration::from_secs(config.interval_seconds));
@@ -441,6 +649,22 @@ impl AutoSaveManager {
         Ok(())
     }
 
+    /// Save this version of a conversation from now on, replacing any
+    /// earlier version with the same ID
+    pub async fn update_conversation(&self, conversation: ExportableConversation) {
+        let mut conversations = self.conversations.write().await;
+        match conversations
+            .iter_mut()
+            .find(|saved| saved.metadata.id == conversation.metadata.id)
+        {
+            Some(saved) => *saved = conversation,
+            None => conversations.push(conversation),
+        }
+        drop(conversations);
+
+        self.state.write().await.has_unsaved_changes = true;
+    }
+
     /// Manually trigger an auto-save
     pub async fn trigger_save(&self, save_type: AutoSaveType) -> Result<()> {
         let config = self.config.read().await.clone();
@@ -460,22 +684,15 @@ impl AutoSaveManager {
         }
 
         // Prepare save data
-        let save_data = self.prepare_save_data().await?;
-        
-        // Generate save filename
-        let filename = self.generate_save_filename(&save_type).await;
-        let save_path = config.save_directory.join(&filename);
-
-        // Create backup if enabled
-        if config.create_backups && save_path.exists() {
-            let backup_path = save_path.with_extension("backup");
-            if let Err(e) = tokio::fs::copy(&save_path, &backup_path).await {
-                warn!("Failed to create backup: {}", e);
-            }
-        }
+        let mut save_data = self.prepare_save_data().await?;
+        save_data.metadata.save_type = save_type;
 
         // Perform the save
-        let save_result = self.write_save_data(&save_data, &save_path).await;
+        let backend = self.backend().await;
+        let save_result = match serde_json::to_vec(&save_data) {
+            Ok(encoded) => backend.persist(&save_data).await.map(|()| encoded.len()),
+            Err(e) => Err(e.into()),
+        };
         
         let duration = start_time.elapsed();
         let duration_ms = duration.as_millis() as u64;
@@ -495,9 +712,11 @@ impl AutoSaveManager {
                 // Update statistics
                 self.update_save_stats(duration_ms, file_size, true).await;
 
-                // Cleanup old saves
-                if let Err(e) = self.cleanup_old_saves().await {
-                    warn!("Failed to cleanup old saves: {}", e);
+                // Prune old versions
+                match backend.prune(&self.session_id, config.max_versions).await {
+                    Ok(0) => {}
+                    Ok(removed) => debug!("Pruned {} old auto-saves", removed),
+                    Err(e) => warn!("Failed to prune old auto-saves: {}", e),
                 }
 
                 info!("Auto-save completed successfully: {} bytes in {}ms", file_size, duration_ms);
@@ -526,32 +745,44 @@ impl AutoSaveManager {
         self.state.read().await.clone()
     }
 
-    /// List available auto-save files
+    /// List this session's auto-saves, newest first
     pub async fn list_auto_saves(&self) -> Result<Vec<AutoSaveMetadata>> {
-        let config = self.config.read().await;
-        let save_dir = &config.save_directory;
-        
-        if !save_dir.exists() {
-            return Ok(Vec::new());
-        }
+        self.backend().await.list(&self.session_id).await
+    }
 
-        let mut saves = Vec::new();
-        let mut dir = tokio::fs::read_dir(save_dir).await?;
-        
-        while let Some(entry) = dir.next_entry().await? {
-            if let Some(extension) = entry.path().extension() {
-                if extension == "json" || extension == "auto" {
-                    if let Ok(metadata) = self.read_save_metadata(&entry.path()).await {
-                        saves.push(metadata);
-                    }
-                }
-            }
-        }
+    /// Load this session's most recent auto-save
+    pub async fn load_latest(&self) -> Result<Option<AutoSaveData>> {
+        self.backend().await.load_latest(&self.session_id).await
+    }
 
-        // Sort by creation time, newest first
-        saves.sort_by(|a, b| b.created_at.cmp(&a.created_at));
-        
-        Ok(saves)
+    /// Load the most recent auto-save of any session in the backend
+    ///
+    /// Useful to pick up where the last run left off when its session ID
+    /// isn't known.
+    pub async fn load_latest_of_any_session(&self) -> Result<Option<AutoSaveData>> {
+        self.backend().await.load_latest_of_any_session().await
+    }
+
+    /// Recover the most recent intact auto-save of a session after a crash
+    ///
+    /// Corrupt saves are skipped in favor of older ones. Later saves continue
+    /// the recovered save's sequence, so they sort after it.
+    pub async fn recover(&self, session_id: &str) -> Result<Option<AutoSaveData>> {
+        let Some(data) = self.backend().await.load_latest(session_id).await? else {
+            debug!("No auto-save to recover for session {}", session_id);
+            return Ok(None);
+        };
+
+        let mut state = self.state.write().await;
+        state.recovered_sequence = Some(data.metadata.sequence);
+        state.current_sequence = state.current_sequence.max(data.metadata.sequence);
+        drop(state);
+
+        info!(
+            "Recovered auto-save {} of session {} from {}",
+            data.metadata.sequence, session_id, data.metadata.created_at
+        );
+        Ok(Some(data))
     }
 
     /// Restore from an auto-save file
@@ -587,6 +818,15 @@ impl AutoSaveManager {
 
     // Private helper methods
 
+    async fn backend(&self) -> Arc<dyn AutoSaveBackend> {
+        match self.backend {
+            Some(ref backend) => backend.clone(),
+            None => Arc::new(FilesystemAutoSaveBackend::new(
+                self.config.read().await.save_directory.clone(),
+            )),
+        }
+    }
+
     #[allow(dead_code)]
     async fn check_and_save(&self) -> Result<()> {
         let config = self.config.read().await.clone();
@@ -623,8 +863,8 @@ impl AutoSaveManager {
             file_size: None,
             checksum: None,
             sequence: state.current_sequence + 1,
-            user_id: "default_user".to_string(), // Would be dynamic in real implementation
-            session_id: "default_session".to_string(), // Would be dynamic in real implementation
+            user_id: self.user_id.clone(),
+            session_id: self.session_id.clone(),
             app_version: "0.1.0".to_string(),
             is_incremental: config.incremental_saves,
             previous_save: None,
@@ -632,15 +872,14 @@ impl AutoSaveManager {
 
         // Collect data based on configuration
         let conversations = if config.save_metadata {
-            // Would collect actual conversation data
-            Vec::new()
+            self.conversations.read().await.clone()
         } else {
             Vec::new()
         };
 
         let memory_blocks = if config.save_memory_blocks {
             if let Some(ref memory_manager) = self.memory_manager {
-                memory_manager.list("default_user").await.unwrap_or_default()
+                memory_manager.list(&self.user_id).await.unwrap_or_default()
             } else {
                 Vec::new()
             }
@@ -661,99 +900,6 @@ impl AutoSaveManager {
         Ok(save_data)
     }
 
-    async fn write_save_data(&self, data: &AutoSaveData, path: &Path) -> Result<usize> {
-        let content = serde_json::to_string_pretty(data)?;
-        let file_size = content.len();
-        
-        // Ensure parent directory exists
-        if let Some(parent) = path.parent() {
-            tokio::fs::create_dir_all(parent).await?;
-        }
-
-        tokio::fs::write(path, &content).await?;
-        
-        Ok(file_size)
-    }
-
-    async fn generate_save_filename(&self, save_type: &AutoSaveType) -> String {
-        let state = self.state.read().await;
-        let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
-        let type_suffix = match save_type {
-            AutoSaveType::Periodic => "auto",
-            AutoSaveType::ActivityTriggered => "activity",
-            AutoSaveType::IdleTriggered => "idle",
-            AutoSaveType::ExitSave => "exit",
-            AutoSaveType::ConfigChange => "config",
-            AutoSaveType::Manual => "manual",
-            AutoSaveType::Emergency => "emergency",
-        };
-        
-        format!("autosave_{}_{:04}_{}.json", timestamp, state.current_sequence + 1, type_suffix)
-    }
-
-    async fn cleanup_old_saves(&self) -> Result<()> {
-        let config = self.config.read().await;
-        let save_dir = &config.save_directory;
-        
-        if !save_dir.exists() {
-            return Ok(());
-        }
-
-        // Get all auto-save files
-        let mut save_files = Vec::new();
-        let mut dir = tokio::fs::read_dir(save_dir).await?;
-        
-        while let Some(entry) = dir.next_entry().await? {
-            if let Some(name) = entry.file_name().to_str() {
-                if name.starts_with("autosave_") && name.ends_with(".json") {
-                    if let Ok(metadata) = entry.metadata().await {
-                        if let Ok(created) = metadata.created() {
-                            save_files.push((entry.path(), created));
-                        }
-                    }
-                }
-            }
-        }
-
-        // Sort by creation time, newest first
-        save_files.sort_by(|a, b| b.1.cmp(&a.1));
-
-        // Remove excess files
-        if save_files.len() > config.max_auto_saves {
-            let to_remove = &save_files[config.max_auto_saves..];
-            for (path, _) in to_remove {
-                if let Err(e) = tokio::fs::remove_file(path).await {
-                    warn!("Failed to remove old auto-save file {:?}: {}", path, e);
-                } else {
-                    debug!("Removed old auto-save file: {:?}", path);
-                }
-            }
-        }
-
-        // Remove old backups based on retention policy
-        let retention_cutoff = Utc::now() - Duration::days(config.backup_retention_days as i64);
-        let mut backup_dir = tokio::fs::read_dir(save_dir).await?;
-        
-        while let Some(entry) = backup_dir.next_entry().await? {
-            if let Some(name) = entry.file_name().to_str() {
-                if name.ends_with(".backup") {
-                    if let Ok(metadata) = entry.metadata().await {
-                        if let Ok(created) = metadata.created() {
-                            let created_dt = DateTime::<Utc>::from(created);
-                            if created_dt < retention_cutoff {
-                                if let Err(e) = tokio::fs::remove_file(entry.path()).await {
-                                    warn!("Failed to remove old backup file {:?}: {}", entry.path(), e);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        Ok(())
-    }
-
     async fn update_save_stats(&self, duration_ms: u64, file_size: usize, success: bool) {
         let mut stats = self.stats.write().await;
         
@@ -801,14 +947,122 @@ impl AutoSaveManager {
         Ok(())
     }
 
-    async fn read_save_metadata(&self, path: &Path) -> Result<AutoSaveMetadata> {
-        let content = tokio::fs::read_to_string(path).await?;
-        let save_data: AutoSaveData = serde_json::from_str(&content)?;
-        Ok(save_data.metadata)
-    }
-
     fn calculate_checksum(&self, content: &str) -> String {
         // Simplified checksum calculation
         format!("{:x}", content.len())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversation::export::ConversationMetadata;
+    use crate::conversation::test_fixtures;
+
+    #[tokio::test]
+    async fn test_prunes_to_max_versions_on_each_save() {
+        let save_directory =
+            std::env::temp_dir().join(format!("luts_autosave_{}", uuid::Uuid::new_v4()));
+        let manager = AutoSaveManager::new()
+            .with_backend(Arc::new(FilesystemAutoSaveBackend::new(&save_directory)))
+            .with_session("user", "session-1");
+        manager
+            .update_config(AutoSaveConfig {
+                max_versions: 3,
+                save_on_config_change: false,
+                save_directory: save_directory.clone(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        for _ in 0..5 {
+            manager.trigger_save(AutoSaveType::Manual).await.unwrap();
+        }
+
+        let saves = manager.list_auto_saves().await.unwrap();
+        let sequences: Vec<usize> = saves.iter().map(|save| save.sequence).collect();
+        assert_eq!(sequences, vec![5, 4, 3]);
+        let latest = manager.load_latest().await.unwrap().unwrap();
+        assert_eq!(latest.metadata.sequence, 5);
+        assert_eq!(latest.metadata.session_id, "session-1");
+
+        let mut files = tokio::fs::read_dir(save_directory.join("session-1")).await.unwrap();
+        let mut file_count = 0;
+        while files.next_entry().await.unwrap().is_some() {
+            file_count += 1;
+        }
+        assert_eq!(file_count, 3);
+
+        tokio::fs::remove_dir_all(&save_directory).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_recover_skips_truncated_save() {
+        let save_directory =
+            std::env::temp_dir().join(format!("luts_autosave_{}", uuid::Uuid::new_v4()));
+        let backend = Arc::new(FilesystemAutoSaveBackend::new(&save_directory));
+        let writer = AutoSaveManager::new()
+            .with_backend(backend.clone())
+            .with_session("user", "session-1");
+        writer.trigger_save(AutoSaveType::Periodic).await.unwrap();
+        writer.trigger_save(AutoSaveType::Crash).await.unwrap();
+
+        // Cut the newest save short, as if the process died mid-write
+        let mut files = tokio::fs::read_dir(save_directory.join("session-1")).await.unwrap();
+        while let Some(entry) = files.next_entry().await.unwrap() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.ends_with("_0002_crash.json") {
+                let content = tokio::fs::read(entry.path()).await.unwrap();
+                tokio::fs::write(entry.path(), &content[..content.len() / 2])
+                    .await
+                    .unwrap();
+            }
+        }
+
+        let manager = AutoSaveManager::new().with_backend(backend);
+        let recovered = manager.recover("session-1").await.unwrap().unwrap();
+        assert_eq!(recovered.metadata.sequence, 1);
+        assert!(matches!(recovered.metadata.save_type, AutoSaveType::Periodic));
+        let state = manager.get_state().await;
+        assert_eq!(state.recovered_sequence, Some(1));
+        assert_eq!(state.current_sequence, 1);
+
+        assert!(manager.recover("other-session").await.unwrap().is_none());
+
+        tokio::fs::remove_dir_all(&save_directory).await.unwrap();
+    }
+
+    fn conversation(session: &str, title: &str) -> ExportableConversation {
+        ExportableConversation {
+            metadata: ConversationMetadata {
+                session_id: session.to_string(),
+                ..test_fixtures::metadata(session, title, 0)
+            },
+            ..test_fixtures::conversation(session, Vec::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_latest_conversation_is_found_across_sessions() {
+        let save_directory =
+            std::env::temp_dir().join(format!("luts_autosave_{}", uuid::Uuid::new_v4()));
+        let backend = Arc::new(FilesystemAutoSaveBackend::new(&save_directory));
+        for session in ["session-1", "session-2"] {
+            let manager = AutoSaveManager::new()
+                .with_backend(backend.clone())
+                .with_session("user", session);
+            manager.update_conversation(conversation(session, "Draft")).await;
+            manager.update_conversation(conversation(session, "Final")).await;
+            manager.trigger_save(AutoSaveType::Manual).await.unwrap();
+        }
+
+        let manager = AutoSaveManager::new().with_backend(backend);
+        let latest = manager.load_latest_of_any_session().await.unwrap().unwrap();
+        assert_eq!(latest.metadata.session_id, "session-2");
+        assert_eq!(latest.conversations.len(), 1);
+        assert_eq!(latest.conversations[0].metadata.title, "Final");
+
+        tokio::fs::remove_dir_all(&save_directory).await.unwrap();
+        assert!(manager.load_latest_of_any_session().await.unwrap().is_none());
+    }
+}
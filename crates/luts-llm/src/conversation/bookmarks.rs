@@ -3,13 +3,27 @@
 //! This module provides comprehensive bookmark and favorites management for conversations,
 //! including categorization, tagging, notes, and quick access functionality.
 
+use crate::conversation::export::{ExportFormat, ExportableMessage};
 use anyhow::Result;
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use luts_common::LutsError;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::info;
 
+/// Longest message snippet included in a bookmark export, in characters
+const EXPORT_SNIPPET_CHARS: usize = 160;
+
+/// Looks up conversation messages so bookmarks can be shown in context
+#[async_trait]
+pub trait ConversationSource: Send + Sync {
+    /// Messages of a conversation in order, or `None` if it doesn't exist
+    async fn messages(&self, conversation_id: &str) -> Result<Option<Vec<ExportableMessage>>>;
+}
+
 /// A bookmark for a conversation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConversationBookmark {
@@ -17,6 +31,9 @@ pub struct ConversationBookmark {
     pub id: String,
     /// Conversation ID being bookmarked
     pub conversation_id: String,
+    /// Message within the conversation, if a single message is bookmarked
+    #[serde(default)]
+    pub message_id: Option<String>,
     /// User who created the bookmark
     pub user_id: String,
     /// Bookmark title (optional, defaults to conversation title)
@@ -154,9 +171,11 @@ pub struct BookmarkQuery {
     /// Filter by category
     pub category: Option<String>,
     /// Filter by tags (any of these tags)
-    pub tags: Option<Vec<String>>,
+    #[serde(alias = "tags")]
+    pub tags_any: Option<Vec<String>>,
     /// Filter by required tags (all of these tags)
-    pub required_tags: Option<Vec<String>>,
+    #[serde(alias = "required_tags")]
+    pub tags_all: Option<Vec<String>>,
     /// Text search in title and notes
     pub text_search: Option<String>,
     /// Filter by priority
@@ -213,6 +232,8 @@ pub struct BookmarkStats {
     pub by_priority: HashMap<BookmarkPriority, usize>,
     /// Most used tags
     pub popular_tags: Vec<(String, usize)>,
+    /// Number of bookmarks carrying each tag
+    pub tag_histogram: HashMap<String, usize>,
     /// Most accessed bookmarks
     pub most_accessed: Vec<(String, usize)>,
     /// Recent bookmarks
@@ -236,6 +257,9 @@ pub struct QuickAccessBookmark {
     pub color: Option<BookmarkColor>,
     /// Priority
     pub priority: BookmarkPriority,
+    /// Tags
+    #[serde(default)]
+    pub tags: Vec<String>,
     /// Last access time
     pub last_accessed: Option<DateTime<Utc>>,
     /// Access count
@@ -255,6 +279,37 @@ pub struct BookmarkExportData {
     pub collection_memberships: HashMap<String, Vec<String>>, // collection_id -> bookmark_ids
 }
 
+/// One bookmark in a formatted export, with a snippet of the bookmarked message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookmarkExportEntry {
+    /// Bookmark ID
+    pub bookmark_id: String,
+    /// Conversation ID
+    pub conversation_id: String,
+    /// Bookmarked message ID
+    pub message_id: Option<String>,
+    /// Bookmark title
+    pub title: Option<String>,
+    /// User's note
+    pub note: Option<String>,
+    /// Category
+    pub category: Option<String>,
+    /// Tags
+    pub tags: Vec<String>,
+    /// Priority
+    pub priority: BookmarkPriority,
+    /// Whether this is a favorite
+    pub is_favorite: bool,
+    /// When the bookmark was created
+    pub created_at: DateTime<Utc>,
+    /// Author of the bookmarked message
+    pub message_author: Option<String>,
+    /// When the bookmarked message was sent
+    pub message_timestamp: Option<DateTime<Utc>>,
+    /// Start of the bookmarked message, on one line
+    pub snippet: Option<String>,
+}
+
 /// Export metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BookmarkExportMetadata {
@@ -282,6 +337,8 @@ pub struct BookmarkManager {
     config: RwLock<BookmarkConfig>,
     /// Storage path for persistence
     storage_path: std::path::PathBuf,
+    /// Where bookmarked messages are looked up
+    conversation_source: Option<Arc<dyn ConversationSource>>,
 }
 
 /// Bookmark configuration
@@ -330,9 +387,16 @@ impl BookmarkManager {
             bookmark_collections: RwLock::new(HashMap::new()),
             config: RwLock::new(BookmarkConfig::default()),
             storage_path,
+            conversation_source: None,
         }
     }
 
+    /// Look up bookmarked messages in `source`
+    pub fn with_conversation_source(mut self, source: Arc<dyn ConversationSource>) -> Self {
+        self.conversation_source = Some(source);
+        self
+    }
+
     /// Create a bookmark for a conversation
     pub async fn create_bookmark(
         &self,
@@ -360,11 +424,12 @@ impl BookmarkManager {
         let bookmark = ConversationBookmark {
             id: bookmark_id.clone(),
             conversation_id,
+            message_id: None,
             user_id,
             title,
             note,
             category: category.or_else(|| config.default_category.clone()),
-            tags,
+            tags: normalize_tags(tags),
             created_at: Utc::now(),
             last_accessed: None,
             access_count: 0,
@@ -398,11 +463,14 @@ impl BookmarkManager {
             if let Some(note) = updates.note {
                 bookmark.note = Some(note);
             }
+            if let Some(message_id) = updates.message_id {
+                bookmark.message_id = Some(message_id);
+            }
             if let Some(category) = updates.category {
                 bookmark.category = Some(category);
             }
             if let Some(tags) = updates.tags {
-                bookmark.tags = tags;
+                bookmark.tags = normalize_tags(tags);
             }
             if let Some(priority) = updates.priority {
                 bookmark.priority = priority;
@@ -565,6 +633,7 @@ impl BookmarkManager {
                 category: bookmark.category,
                 color: bookmark.color,
                 priority: bookmark.priority,
+                tags: bookmark.tags,
                 last_accessed: bookmark.last_accessed,
                 access_count: bookmark.access_count,
             })
@@ -687,15 +756,18 @@ impl BookmarkManager {
             *by_priority.entry(bookmark.priority.clone()).or_insert(0) += 1;
         }
 
-        // Popular tags
-        let mut tag_counts = HashMap::new();
+        // Tag histogram and popular tags
+        let mut tag_histogram = HashMap::new();
         for bookmark in &user_bookmarks {
             for tag in &bookmark.tags {
-                *tag_counts.entry(tag.clone()).or_insert(0) += 1;
+                *tag_histogram.entry(tag.clone()).or_insert(0) += 1;
             }
         }
-        let mut popular_tags: Vec<_> = tag_counts.into_iter().collect();
-        popular_tags.sort_by(|a, b| b.1.cmp(&a.1));
+        let mut popular_tags: Vec<_> = tag_histogram
+            .iter()
+            .map(|(tag, count)| (tag.clone(), *count))
+            .collect();
+        popular_tags.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
         popular_tags.truncate(10);
 
         // Most accessed
@@ -738,6 +810,7 @@ impl BookmarkManager {
             by_category,
             by_priority,
             popular_tags,
+            tag_histogram,
             most_accessed: bookmark_access,
             recent_bookmarks,
             upcoming_reminders,
@@ -782,6 +855,110 @@ impl BookmarkManager {
         Ok(export_data)
     }
 
+    /// Export all bookmarks as a list with message snippets
+    ///
+    /// Bookmarks whose message can't be found are still listed, without a
+    /// snippet.
+    pub async fn export(&self, format: ExportFormat) -> Result<String> {
+        let mut bookmarks: Vec<ConversationBookmark> =
+            self.bookmarks.read().await.values().cloned().collect();
+        bookmarks.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+
+        let mut conversations: HashMap<String, Option<Vec<ExportableMessage>>> = HashMap::new();
+        let mut entries = Vec::with_capacity(bookmarks.len());
+        for bookmark in bookmarks {
+            let mut message = None;
+            if let (Some(source), Some(message_id)) =
+                (&self.conversation_source, &bookmark.message_id)
+            {
+                if !conversations.contains_key(&bookmark.conversation_id) {
+                    let messages = source.messages(&bookmark.conversation_id).await?;
+                    conversations.insert(bookmark.conversation_id.clone(), messages);
+                }
+                message = conversations[&bookmark.conversation_id]
+                    .iter()
+                    .flatten()
+                    .find(|message| message.id == *message_id);
+            }
+
+            entries.push(BookmarkExportEntry {
+                bookmark_id: bookmark.id,
+                conversation_id: bookmark.conversation_id,
+                message_id: bookmark.message_id,
+                title: bookmark.title,
+                note: bookmark.note,
+                category: bookmark.category,
+                tags: bookmark.tags,
+                priority: bookmark.priority,
+                is_favorite: bookmark.is_favorite,
+                created_at: bookmark.created_at,
+                message_author: message.map(|message| message.author.clone()),
+                message_timestamp: message.map(|message| message.timestamp),
+                snippet: message.map(|message| export_snippet(&message.content)),
+            });
+        }
+
+        match format {
+            ExportFormat::Json => Ok(serde_json::to_string_pretty(&entries)?),
+            ExportFormat::Jsonl => {
+                let mut jsonl = String::new();
+                for entry in &entries {
+                    jsonl.push_str(&serde_json::to_string(entry)?);
+                    jsonl.push('\n');
+                }
+                Ok(jsonl)
+            }
+            ExportFormat::Yaml => Ok(serde_yaml::to_string(&entries)?),
+            ExportFormat::Csv => Ok(bookmarks_to_csv(&entries)),
+            ExportFormat::Markdown | ExportFormat::MarkdownTranscript => {
+                Ok(bookmarks_to_markdown(&entries))
+            }
+            ExportFormat::Txt => Ok(bookmarks_to_text(&entries)),
+            ExportFormat::Html | ExportFormat::Xml => Err(anyhow::anyhow!(
+                "Bookmark export not supported for format: {:?}",
+                format
+            )),
+        }
+    }
+
+    /// The bookmarked message with up to `window` messages on either side
+    ///
+    /// Fails with [`LutsError::NotFound`] when the conversation or the
+    /// message has been deleted.
+    pub async fn resolve_context(
+        &self,
+        bookmark: &ConversationBookmark,
+        window: usize,
+    ) -> Result<Vec<ExportableMessage>> {
+        let source = self
+            .conversation_source
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No conversation source configured for bookmarks"))?;
+        let message_id = bookmark.message_id.as_deref().ok_or_else(|| {
+            anyhow::anyhow!("Bookmark {} doesn't point at a message", bookmark.id)
+        })?;
+
+        let messages = source
+            .messages(&bookmark.conversation_id)
+            .await?
+            .ok_or_else(|| {
+                LutsError::NotFound(format!("conversation {}", bookmark.conversation_id))
+            })?;
+        let position = messages
+            .iter()
+            .position(|message| message.id == message_id)
+            .ok_or_else(|| {
+                LutsError::NotFound(format!(
+                    "message {} in conversation {}",
+                    message_id, bookmark.conversation_id
+                ))
+            })?;
+
+        let start = position.saturating_sub(window);
+        let end = (position + window + 1).min(messages.len());
+        Ok(messages[start..end].to_vec())
+    }
+
     // Private helper methods
 
     fn matches_query(&self, bookmark: &ConversationBookmark, query: &BookmarkQuery) -> bool {
@@ -803,16 +980,20 @@ impl BookmarkManager {
             }
         }
 
-        if let Some(ref tags) = query.tags {
-            if !tags.iter().any(|tag| bookmark.tags.contains(tag)) {
-                return false;
-            }
+        if let Some(ref tags_any) = query.tags_any
+            && !normalize_tags(tags_any.clone())
+                .iter()
+                .any(|tag| bookmark.tags.contains(tag))
+        {
+            return false;
         }
 
-        if let Some(ref required_tags) = query.required_tags {
-            if !required_tags.iter().all(|tag| bookmark.tags.contains(tag)) {
-                return false;
-            }
+        if let Some(ref tags_all) = query.tags_all
+            && !normalize_tags(tags_all.clone())
+                .iter()
+                .all(|tag| bookmark.tags.contains(tag))
+        {
+            return false;
         }
 
         if let Some(ref text_search) = query.text_search {
@@ -911,11 +1092,120 @@ impl BookmarkManager {
     }
 }
 
+/// Message content flattened to one line and cut to [`EXPORT_SNIPPET_CHARS`]
+fn export_snippet(content: &str) -> String {
+    let flattened = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    if flattened.chars().count() <= EXPORT_SNIPPET_CHARS {
+        flattened
+    } else {
+        let cut: String = flattened.chars().take(EXPORT_SNIPPET_CHARS).collect();
+        format!("{}…", cut.trim_end())
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+fn bookmarks_to_csv(entries: &[BookmarkExportEntry]) -> String {
+    let mut csv = String::from(
+        "bookmark_id,conversation_id,message_id,title,category,tags,priority,created_at,message_author,snippet,note\n",
+    );
+    for entry in entries {
+        let fields = [
+            entry.bookmark_id.clone(),
+            entry.conversation_id.clone(),
+            entry.message_id.clone().unwrap_or_default(),
+            entry.title.clone().unwrap_or_default(),
+            entry.category.clone().unwrap_or_default(),
+            entry.tags.join(";"),
+            format!("{:?}", entry.priority),
+            entry.created_at.to_rfc3339(),
+            entry.message_author.clone().unwrap_or_default(),
+            entry.snippet.clone().unwrap_or_default(),
+            entry.note.clone().unwrap_or_default().replace('\n', " "),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn bookmarks_to_markdown(entries: &[BookmarkExportEntry]) -> String {
+    let mut markdown = String::from("# Bookmarks\n\n");
+    for entry in entries {
+        let star = if entry.is_favorite { "⭐ " } else { "" };
+        markdown.push_str(&format!(
+            "## {}{}\n\n",
+            star,
+            entry.title.as_deref().unwrap_or("Untitled")
+        ));
+        markdown.push_str(&format!("**Conversation:** {}\n", entry.conversation_id));
+        if let Some(ref message_id) = entry.message_id {
+            markdown.push_str(&format!("**Message:** {}\n", message_id));
+        }
+        markdown.push_str(&format!(
+            "**Created:** {}\n",
+            entry.created_at.format("%Y-%m-%d %H:%M:%S UTC")
+        ));
+        markdown.push_str(&format!("**Priority:** {:?}\n", entry.priority));
+        if !entry.tags.is_empty() {
+            markdown.push_str(&format!("**Tags:** {}\n", entry.tags.join(", ")));
+        }
+        markdown.push('\n');
+        if let Some(ref snippet) = entry.snippet {
+            let author = entry.message_author.as_deref().unwrap_or("unknown");
+            markdown.push_str(&format!("> **{}:** {}\n\n", author, snippet));
+        }
+        if let Some(ref note) = entry.note {
+            markdown.push_str(&format!("{}\n\n", note));
+        }
+    }
+    markdown
+}
+
+fn bookmarks_to_text(entries: &[BookmarkExportEntry]) -> String {
+    let mut text = String::new();
+    for entry in entries {
+        text.push_str(&format!(
+            "[{}] {} ({})\n",
+            entry.created_at.format("%Y-%m-%d %H:%M"),
+            entry.title.as_deref().unwrap_or("Untitled"),
+            entry.conversation_id
+        ));
+        if !entry.tags.is_empty() {
+            text.push_str(&format!("  tags: {}\n", entry.tags.join(", ")));
+        }
+        if let Some(ref snippet) = entry.snippet {
+            let author = entry.message_author.as_deref().unwrap_or("unknown");
+            text.push_str(&format!("  {}: {}\n", author, snippet));
+        }
+        if let Some(ref note) = entry.note {
+            text.push_str(&format!("  note: {}\n", note));
+        }
+    }
+    text
+}
+
+/// Trim and lowercase tags, dropping empty and duplicate ones
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
 /// Bookmark update parameters
 #[derive(Debug, Default)]
 pub struct BookmarkUpdates {
     pub title: Option<String>,
     pub note: Option<String>,
+    pub message_id: Option<String>,
     pub category: Option<String>,
     pub tags: Option<Vec<String>>,
     pub priority: Option<BookmarkPriority>,
@@ -933,4 +1223,155 @@ struct BookmarkStorageData {
     collection_memberships: HashMap<String, Vec<String>>,
     bookmark_collections: HashMap<String, Vec<String>>,
     config: BookmarkConfig,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversation::export::MessageType;
+    use crate::conversation::test_fixtures;
+
+    fn message(id: &str, author: &str, content: &str) -> ExportableMessage {
+        test_fixtures::message(id, MessageType::User, author, content)
+    }
+
+    struct FixedConversations(HashMap<String, Vec<ExportableMessage>>);
+
+    #[async_trait]
+    impl ConversationSource for FixedConversations {
+        async fn messages(&self, conversation_id: &str) -> Result<Option<Vec<ExportableMessage>>> {
+            Ok(self.0.get(conversation_id).cloned())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tags_all_requires_every_tag() {
+        let storage_dir =
+            std::env::temp_dir().join(format!("luts_bookmarks_{}", uuid::Uuid::new_v4()));
+        let manager = BookmarkManager::new(storage_dir.join("bookmarks.json"));
+
+        let both = manager
+            .create_bookmark(
+                "conv_1".to_string(),
+                "user".to_string(),
+                Some("Crash on startup".to_string()),
+                None,
+                None,
+                vec![" Bug ".to_string(), "TODO".to_string(), "bug".to_string()],
+                Some(BookmarkPriority::High),
+            )
+            .await
+            .unwrap();
+        manager
+            .create_bookmark(
+                "conv_2".to_string(),
+                "user".to_string(),
+                Some("Flaky test".to_string()),
+                None,
+                None,
+                vec!["bug".to_string()],
+                None,
+            )
+            .await
+            .unwrap();
+        manager
+            .create_bookmark(
+                "conv_3".to_string(),
+                "user".to_string(),
+                Some("Nice line".to_string()),
+                None,
+                None,
+                vec!["quote".to_string()],
+                None,
+            )
+            .await
+            .unwrap();
+
+        let results = manager
+            .search_bookmarks(BookmarkQuery {
+                tags_all: Some(vec!["bug".to_string(), "Todo".to_string()]),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, both);
+        assert_eq!(results[0].tags, vec!["bug", "todo"]);
+
+        let results = manager
+            .search_bookmarks(BookmarkQuery {
+                tags_any: Some(vec!["bug".to_string(), "quote".to_string()]),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 3);
+
+        let stats = manager.get_stats(Some("user")).await.unwrap();
+        assert_eq!(stats.tag_histogram.get("bug"), Some(&2));
+        assert_eq!(stats.tag_histogram.get("todo"), Some(&1));
+        assert_eq!(stats.tag_histogram.get("quote"), Some(&1));
+
+        // High priority bookmarks land in quick access, tags included
+        let quick_access = manager.get_quick_access_bookmarks("user").await.unwrap();
+        assert_eq!(quick_access.len(), 1);
+        assert_eq!(quick_access[0].tags, vec!["bug", "todo"]);
+
+        tokio::fs::remove_dir_all(storage_dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_resolve_context_and_export() {
+        let storage_dir =
+            std::env::temp_dir().join(format!("luts_bookmarks_{}", uuid::Uuid::new_v4()));
+        let messages: Vec<ExportableMessage> = (0..6)
+            .map(|i| message(&format!("msg_{}", i), "alice", &format!("Message number {}", i)))
+            .collect();
+        let source = FixedConversations(HashMap::from([("conv_1".to_string(), messages)]));
+        let manager = BookmarkManager::new(storage_dir.join("bookmarks.json"))
+            .with_conversation_source(Arc::new(source));
+
+        let id = manager
+            .create_bookmark(
+                "conv_1".to_string(),
+                "user".to_string(),
+                Some("Key point".to_string()),
+                None,
+                None,
+                vec!["quote".to_string()],
+                None,
+            )
+            .await
+            .unwrap();
+        manager
+            .update_bookmark(
+                &id,
+                BookmarkUpdates {
+                    message_id: Some("msg_1".to_string()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let mut bookmark = manager.access_bookmark(&id).await.unwrap();
+
+        // The window is clamped at the start of the conversation
+        let context = manager.resolve_context(&bookmark, 2).await.unwrap();
+        let ids: Vec<&str> = context.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["msg_0", "msg_1", "msg_2", "msg_3"]);
+
+        let json = manager.export(ExportFormat::Json).await.unwrap();
+        let entries: Vec<BookmarkExportEntry> = serde_json::from_str(&json).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].snippet.as_deref(), Some("Message number 1"));
+        assert_eq!(entries[0].message_author.as_deref(), Some("alice"));
+        let markdown = manager.export(ExportFormat::Markdown).await.unwrap();
+        assert!(markdown.contains("> **alice:** Message number 1"));
+
+        bookmark.message_id = Some("msg_deleted".to_string());
+        let err = manager.resolve_context(&bookmark, 2).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<LutsError>(), Some(LutsError::NotFound(_))));
+
+        tokio::fs::remove_dir_all(storage_dir).await.unwrap();
+    }
+}
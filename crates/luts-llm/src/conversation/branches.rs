@@ -0,0 +1,168 @@
+//! Conversation branching
+//!
+//! To explore an alternative response, a conversation can be forked at one
+//! of its messages: the fork starts with the messages up to that point and
+//! then continues on its own, while the parent is left untouched. Each fork
+//! records its parent, so a conversation's forks can be listed.
+
+use crate::conversation::export::{ExportableConversation, ExportableMessage};
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use tracing::info;
+
+/// Conversations that can be forked and continued independently
+#[derive(Default)]
+pub struct ConversationBranches {
+    conversations: RwLock<HashMap<String, ExportableConversation>>,
+}
+
+impl ConversationBranches {
+    /// Create an empty set of conversations
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a conversation, replacing any with the same ID, and return its ID
+    pub async fn insert(&self, conversation: ExportableConversation) -> String {
+        let id = conversation.metadata.id.clone();
+        self.conversations
+            .write()
+            .await
+            .insert(id.clone(), conversation);
+        id
+    }
+
+    /// Get a conversation by ID
+    pub async fn get(&self, conversation_id: &str) -> Option<ExportableConversation> {
+        self.conversations
+            .read()
+            .await
+            .get(conversation_id)
+            .cloned()
+    }
+
+    /// Append a message to a conversation
+    pub async fn add_message(
+        &self,
+        conversation_id: &str,
+        message: ExportableMessage,
+    ) -> Result<()> {
+        let mut conversations = self.conversations.write().await;
+        let conversation = conversations
+            .get_mut(conversation_id)
+            .ok_or_else(|| anyhow!("Conversation {} not found", conversation_id))?;
+        conversation.metadata.last_message_at = message.timestamp;
+        conversation.messages.push(message);
+        conversation.metadata.message_count = conversation.messages.len();
+        Ok(())
+    }
+
+    /// Fork a conversation after `at_message_id`, returning the fork's ID
+    ///
+    /// See [`ExportableConversation::fork`] for what the fork carries over.
+    pub async fn fork(&self, conversation_id: &str, at_message_id: &str) -> Result<String> {
+        let mut conversations = self.conversations.write().await;
+        let fork = conversations
+            .get(conversation_id)
+            .ok_or_else(|| anyhow!("Conversation {} not found", conversation_id))?
+            .fork(at_message_id)?;
+        let fork_id = fork.metadata.id.clone();
+        conversations.insert(fork_id.clone(), fork);
+
+        info!(
+            "Forked conversation {} at message {} into {}",
+            conversation_id, at_message_id, fork_id
+        );
+        Ok(fork_id)
+    }
+
+    /// Direct forks of a conversation, oldest first
+    pub async fn forks(&self, conversation_id: &str) -> Vec<ExportableConversation> {
+        let mut forks: Vec<ExportableConversation> = self
+            .conversations
+            .read()
+            .await
+            .values()
+            .filter(|conversation| {
+                conversation.metadata.parent_id.as_deref() == Some(conversation_id)
+            })
+            .cloned()
+            .collect();
+        forks.sort_by_key(|fork| fork.metadata.forked_at);
+        forks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversation::export::MessageType;
+    use crate::conversation::test_fixtures::{self, conversation};
+
+    fn message(id: &str, message_type: MessageType, content: &str) -> ExportableMessage {
+        test_fixtures::message(id, message_type, "user", content)
+    }
+
+    fn contents(conversation: &ExportableConversation) -> Vec<&str> {
+        conversation
+            .messages
+            .iter()
+            .map(|message| message.content.as_str())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_fork_shares_prefix_and_diverges_afterward() {
+        let branches = ConversationBranches::new();
+        let parent = branches
+            .insert(conversation(
+                "parent",
+                vec![
+                    message("m1", MessageType::User, "Where should I go in May?"),
+                    message("m2", MessageType::Assistant, "Lisbon is lovely in May."),
+                    message("m3", MessageType::User, "Book me a hotel there."),
+                ],
+            ))
+            .await;
+
+        let fork = branches.fork(&parent, "m2").await.unwrap();
+        branches
+            .add_message(&fork, message("f1", MessageType::User, "What about Porto?"))
+            .await
+            .unwrap();
+        branches
+            .add_message(&parent, message("m4", MessageType::Assistant, "Booked."))
+            .await
+            .unwrap();
+
+        let forked = branches.get(&fork).await.unwrap();
+        assert_eq!(
+            contents(&forked),
+            [
+                "Where should I go in May?",
+                "Lisbon is lovely in May.",
+                "What about Porto?"
+            ]
+        );
+        let ids: Vec<&str> = forked.messages.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["m1", "m2", "f1"]);
+        assert_eq!(forked.metadata.message_count, 3);
+        assert_eq!(forked.metadata.parent_id.as_deref(), Some("parent"));
+        assert_eq!(forked.metadata.forked_from_message.as_deref(), Some("m2"));
+        assert!(forked.metadata.forked_at.is_some());
+
+        let parent_conversation = branches.get(&parent).await.unwrap();
+        assert_eq!(
+            contents(&parent_conversation)[2..],
+            ["Book me a hotel there.", "Booked."]
+        );
+        assert_eq!(parent_conversation.metadata.parent_id, None);
+
+        let forks = branches.forks(&parent).await;
+        assert_eq!(forks.len(), 1);
+        assert_eq!(forks[0].metadata.id, fork);
+        assert!(branches.forks(&fork).await.is_empty());
+        assert!(branches.fork(&parent, "missing").await.is_err());
+    }
+}
@@ -3,19 +3,43 @@
 //! This module provides comprehensive conversation export/import capabilities,
 //! supporting multiple formats with metadata preservation and format conversion.
 
+use crate::conversation::language::{detect_language, dominant_language};
 use crate::conversation::summarization::ConversationSummary;
 use crate::llm::InternalChatMessage;
-use luts_memory::{MemoryBlock, MemoryManager, MemoryQuery};
+use luts_core::context::core_blocks::{CoreBlockManager, CoreBlockType};
+use luts_memory::{BlockId, MemoryBlock, MemoryManager, MemoryQuery};
 use luts_core::utils::tokens::{TokenManager, TokenUsage, UsageFilter};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::info;
 
+/// Custom metadata key naming the core block a system message came from
+const CORE_BLOCK_KEY: &str = "core_block";
+
+/// Counts bytes passed through to the wrapped writer
+struct CountingWriter<'a, W: Write> {
+    inner: &'a mut W,
+    bytes: usize,
+}
+
+impl<W: Write> Write for CountingWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.bytes += written;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
 /// Represents a complete conversation for export/import
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExportableConversation {
@@ -23,6 +47,14 @@ pub struct ExportableConversation {
     pub metadata: ConversationMetadata,
     /// Conversation messages
     pub messages: Vec<ExportableMessage>,
+    /// System messages that framed the conversation, such as the agent's
+    /// system prompt
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub system_messages: Vec<ExportableMessage>,
+    /// Other core-block context pinned into the agent's context window, one
+    /// `# <CoreBlockType>\n\n<content>` section per block
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pinned_context: Vec<String>,
     /// Associated memory blocks
     pub memory_blocks: Vec<MemoryBlock>,
     /// Conversation summaries
@@ -33,6 +65,48 @@ pub struct ExportableConversation {
     pub export_info: ExportInfo,
 }
 
+impl ExportableConversation {
+    /// Start a new conversation from this one's messages up to `at_message_id`
+    ///
+    /// The fork keeps the shared messages, IDs included, and records this
+    /// conversation as its parent. Summaries and token usage describe the
+    /// whole parent, so they aren't carried over.
+    pub fn fork(&self, at_message_id: &str) -> Result<ExportableConversation> {
+        let position = self
+            .messages
+            .iter()
+            .position(|message| message.id == at_message_id)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Message {} not found in conversation {}",
+                    at_message_id,
+                    self.metadata.id
+                )
+            })?;
+        let messages = self.messages[..=position].to_vec();
+
+        let mut metadata = self.metadata.clone();
+        metadata.id = format!("conv_{}", uuid::Uuid::new_v4());
+        metadata.last_message_at = messages[position].timestamp;
+        metadata.message_count = messages.len();
+        metadata.status = ConversationStatus::Active;
+        metadata.parent_id = Some(self.metadata.id.clone());
+        metadata.forked_at = Some(Utc::now());
+        metadata.forked_from_message = Some(at_message_id.to_string());
+
+        Ok(ExportableConversation {
+            metadata,
+            messages,
+            system_messages: self.system_messages.clone(),
+            pinned_context: self.pinned_context.clone(),
+            memory_blocks: self.memory_blocks.clone(),
+            summaries: Vec::new(),
+            token_usage: Vec::new(),
+            export_info: self.export_info.clone(),
+        })
+    }
+}
+
 /// Metadata about the conversation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConversationMetadata {
@@ -62,6 +136,15 @@ pub struct ConversationMetadata {
     pub status: ConversationStatus,
     /// Participants in the conversation
     pub participants: Vec<String>,
+    /// Conversation this one was forked from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    /// When this conversation was forked from its parent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forked_at: Option<DateTime<Utc>>,
+    /// Last parent message the fork shares; later messages are its own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forked_from_message: Option<String>,
 }
 
 /// Status of a conversation
@@ -81,8 +164,23 @@ pub struct ExportableMessage {
     pub id: String,
     /// Message type
     pub message_type: MessageType,
+    /// Chat role (`user`, `assistant`, `system` or `tool`)
+    #[serde(default)]
+    pub role: String,
     /// Message content
     pub content: String,
+    /// Tool calls made by an assistant message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<ExportableToolCall>>,
+    /// Call a tool message answers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    /// Reasoning the model produced before answering
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<String>,
+    /// Detected language of a user message, as an ISO 639-1 code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
     /// When the message was created
     pub timestamp: DateTime<Utc>,
     /// Message author/source
@@ -93,6 +191,25 @@ pub struct ExportableMessage {
     pub references: Vec<String>,
     /// Message attachments
     pub attachments: Vec<MessageAttachment>,
+    /// The response was cut off by a cancellation or error before it finished
+    #[serde(default)]
+    pub incomplete: bool,
+}
+
+/// A tool call in the OpenAI chat format
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExportableToolCall {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub function: ExportableFunctionCall,
+}
+
+/// Function name and JSON-encoded arguments of a tool call
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExportableFunctionCall {
+    pub name: String,
+    pub arguments: String,
 }
 
 /// Type of message
@@ -106,6 +223,18 @@ pub enum MessageType {
     Note,
 }
 
+impl MessageType {
+    /// Chat role for messages of this type
+    pub fn role(&self) -> &'static str {
+        match self {
+            MessageType::User => "user",
+            MessageType::Assistant => "assistant",
+            MessageType::System | MessageType::Error | MessageType::Note => "system",
+            MessageType::Tool => "tool",
+        }
+    }
+}
+
 /// Message metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessageMetadata {
@@ -191,6 +320,23 @@ pub enum ExportFormat {
     Txt,
     Xml,
     Jsonl, // JSON Lines
+    /// Human-readable transcript; can't be imported
+    MarkdownTranscript,
+}
+
+impl ExportFormat {
+    /// Every format the exporter can write
+    pub const ALL: [ExportFormat; 9] = [
+        ExportFormat::Json,
+        ExportFormat::Yaml,
+        ExportFormat::Csv,
+        ExportFormat::Markdown,
+        ExportFormat::Html,
+        ExportFormat::Txt,
+        ExportFormat::Xml,
+        ExportFormat::Jsonl,
+        ExportFormat::MarkdownTranscript,
+    ];
 }
 
 /// Export settings and options
@@ -216,6 +362,15 @@ pub struct ExportSettings {
     pub message_type_filter: Option<Vec<MessageType>>,
     /// Include system messages
     pub include_system_messages: bool,
+    /// Include system notes inserted during the conversation
+    #[serde(default)]
+    pub include_system_notes: bool,
+    /// Include model reasoning alongside assistant messages
+    #[serde(default = "default_include")]
+    pub include_reasoning: bool,
+    /// Include tool calls and tool result messages
+    #[serde(default = "default_include")]
+    pub include_tool_calls: bool,
     /// Pretty print JSON/YAML
     pub pretty_print: bool,
 }
@@ -233,11 +388,18 @@ impl Default for ExportSettings {
             date_range: None,
             message_type_filter: None,
             include_system_messages: true,
+            include_system_notes: false,
+            include_reasoning: true,
+            include_tool_calls: true,
             pretty_print: true,
         }
     }
 }
 
+fn default_include() -> bool {
+    true
+}
+
 /// Import operation information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImportInfo {
@@ -274,6 +436,14 @@ pub struct ImportSettings {
     pub validate_data: bool,
     /// Auto-assign new user/session IDs
     pub auto_assign_ids: bool,
+    /// Write the system messages and pinned context back into the
+    /// exporter's core blocks
+    #[serde(default)]
+    pub restore_core_blocks: bool,
+    /// Store the memory blocks in the exporter's memory manager; with
+    /// assigned ids, importing the same blocks again replaces them
+    #[serde(default)]
+    pub store_memory_blocks: bool,
 }
 
 /// How to merge imported conversations
@@ -312,6 +482,8 @@ impl Default for ImportSettings {
             import_attachments: true,
             validate_data: true,
             auto_assign_ids: true,
+            restore_core_blocks: false,
+            store_memory_blocks: false,
         }
     }
 }
@@ -325,6 +497,8 @@ pub struct ConversationExporter {
     memory_manager: Option<Arc<MemoryManager>>,
     /// Token manager for accessing usage data
     token_manager: Option<Arc<TokenManager>>,
+    /// The agent's core blocks, exported as its framing
+    core_blocks: Option<Arc<RwLock<CoreBlockManager>>>,
     /// Export templates and configurations
     templates: RwLock<HashMap<String, ExportSettings>>,
 }
@@ -336,6 +510,7 @@ impl ConversationExporter {
             storage_dir,
             memory_manager: None,
             token_manager: None,
+            core_blocks: None,
             templates: RwLock::new(HashMap::new()),
         }
     }
@@ -350,10 +525,18 @@ impl ConversationExporter {
             storage_dir,
             memory_manager,
             token_manager,
+            core_blocks: None,
             templates: RwLock::new(HashMap::new()),
         }
     }
 
+    /// Export the system prompt and pinned context of these core blocks, and
+    /// restore them on import when [`ImportSettings::restore_core_blocks`] is set
+    pub fn with_core_blocks(mut self, core_blocks: Arc<RwLock<CoreBlockManager>>) -> Self {
+        self.core_blocks = Some(core_blocks);
+        self
+    }
+
     /// Export a conversation to the specified format
     pub async fn export_conversation(
         &self,
@@ -368,6 +551,11 @@ impl ConversationExporter {
             metadata.id, format
         );
 
+        let mut metadata = metadata;
+        if metadata.language.is_none() {
+            metadata.language = dominant_language(&messages).map(str::to_string);
+        }
+
         // Convert internal messages to exportable format
         let exportable_messages = self
             .convert_messages_to_exportable(messages, &settings)
@@ -395,6 +583,8 @@ impl ConversationExporter {
             Vec::new()
         };
 
+        let (system_messages, pinned_context) = self.collect_framing(&settings).await;
+
         let export_info = ExportInfo {
             exported_at: Utc::now(),
             format: format.clone(),
@@ -408,6 +598,8 @@ impl ConversationExporter {
         let exportable_conversation = ExportableConversation {
             metadata,
             messages: exportable_messages,
+            system_messages,
+            pinned_context,
             memory_blocks,
             summaries,
             token_usage,
@@ -422,6 +614,147 @@ impl ConversationExporter {
         Ok(export_info)
     }
 
+    /// Export a conversation incrementally to a `Write` sink
+    ///
+    /// Markdown and JSON Lines are written one message at a time, so memory
+    /// stays bounded for conversations with thousands of messages. Other
+    /// formats need the whole document and are built in memory first.
+    pub async fn export_to_writer<W: Write>(
+        &self,
+        messages: impl IntoIterator<Item = InternalChatMessage>,
+        metadata: ConversationMetadata,
+        writer: &mut W,
+        format: ExportFormat,
+        settings: ExportSettings,
+    ) -> Result<ExportInfo> {
+        let conversion_settings = settings.clone();
+        let exportable = messages
+            .into_iter()
+            .enumerate()
+            .filter_map(move |(i, message)| Self::to_exportable(i, message, &conversion_settings));
+        self.export_messages_to_writer(exportable, metadata, writer, format, settings)
+            .await
+    }
+
+    /// Export already converted messages to a `Write` sink
+    ///
+    /// Use this when messages carry data [`InternalChatMessage`] has no room
+    /// for, such as reasoning. Reasoning and tool calls are dropped when the
+    /// settings exclude them.
+    pub async fn export_messages_to_writer<W: Write>(
+        &self,
+        messages: impl IntoIterator<Item = ExportableMessage>,
+        metadata: ConversationMetadata,
+        writer: &mut W,
+        format: ExportFormat,
+        settings: ExportSettings,
+    ) -> Result<ExportInfo> {
+        let memory_blocks = if settings.include_memory_blocks {
+            self.collect_memory_blocks(&metadata.user_id, &metadata.session_id)
+                .await?
+        } else {
+            Vec::new()
+        };
+
+        let mut export_info = ExportInfo {
+            exported_at: Utc::now(),
+            format: format.clone(),
+            version: "1.0".to_string(),
+            exporter: "LUTS ConversationExporter".to_string(),
+            settings: settings.clone(),
+            file_size_bytes: None,
+            compression: None,
+        };
+
+        let mut writer = CountingWriter {
+            inner: writer,
+            bytes: 0,
+        };
+        let exportable = messages
+            .into_iter()
+            .filter_map(|message| Self::apply_content_settings(message, &settings));
+
+        match format {
+            ExportFormat::Markdown => {
+                Self::write_markdown_header(&mut writer, &metadata)?;
+                for message in exportable {
+                    Self::write_markdown_message(&mut writer, &message)?;
+                }
+                Self::write_markdown_footer(&mut writer, memory_blocks.len(), 0)?;
+            }
+            ExportFormat::Jsonl => {
+                for message in exportable {
+                    Self::write_jsonl_message(&mut writer, &message)?;
+                }
+            }
+            ExportFormat::MarkdownTranscript => {
+                Self::write_transcript_header(&mut writer, &metadata)?;
+                for message in exportable {
+                    Self::write_transcript_message(&mut writer, &message)?;
+                }
+            }
+            _ => {
+                let token_usage = if settings.include_token_usage {
+                    self.collect_token_usage(&metadata.user_id, &metadata.session_id)
+                        .await?
+                } else {
+                    Vec::new()
+                };
+                let (system_messages, pinned_context) = self.collect_framing(&settings).await;
+                let conversation = ExportableConversation {
+                    metadata,
+                    messages: exportable.collect(),
+                    system_messages,
+                    pinned_context,
+                    memory_blocks,
+                    summaries: Vec::new(),
+                    token_usage,
+                    export_info: export_info.clone(),
+                };
+                match format {
+                    ExportFormat::Json if settings.pretty_print => {
+                        serde_json::to_writer_pretty(&mut writer, &conversation)?
+                    }
+                    ExportFormat::Json => serde_json::to_writer(&mut writer, &conversation)?,
+                    ExportFormat::Yaml => serde_yaml::to_writer(&mut writer, &conversation)?,
+                    ExportFormat::Csv => {
+                        writer.write_all(self.convert_to_csv(&conversation)?.as_bytes())?
+                    }
+                    ExportFormat::Html => {
+                        writer.write_all(self.convert_to_html(&conversation).as_bytes())?
+                    }
+                    ExportFormat::Txt => {
+                        writer.write_all(self.convert_to_text(&conversation).as_bytes())?
+                    }
+                    ExportFormat::Xml => {
+                        writer.write_all(self.convert_to_xml(&conversation)?.as_bytes())?
+                    }
+                    ExportFormat::Markdown
+                    | ExportFormat::Jsonl
+                    | ExportFormat::MarkdownTranscript => unreachable!(),
+                }
+            }
+        }
+
+        writer.flush()?;
+        export_info.file_size_bytes = Some(writer.bytes);
+        Ok(export_info)
+    }
+
+    /// Export a conversation to a string; convenient for small conversations
+    pub async fn export_to_string(
+        &self,
+        messages: Vec<InternalChatMessage>,
+        metadata: ConversationMetadata,
+        format: ExportFormat,
+        settings: ExportSettings,
+    ) -> Result<String> {
+        let mut output = Vec::new();
+        self.export_to_writer(messages, metadata, &mut output, format, settings)
+            .await?;
+        Ok(String::from_utf8(output)?)
+    }
+
     /// Import a conversation from file
     pub async fn import_conversation(
         &self,
@@ -442,15 +775,18 @@ impl ConversationExporter {
         let memory_blocks_imported = conversation.memory_blocks.len();
 
         // Apply import settings
-        if settings.auto_assign_ids {
-            conversation.metadata.id = format!(
-                "imported_{}_{}",
-                Utc::now().timestamp(),
-                uuid::Uuid::new_v4().to_string()[..8].to_string()
+        if settings.auto_assign_ids && !settings.preserve_ids {
+            // Derive ids from the source ids so re-importing the same file
+            // yields the same conversation and block ids
+            let conversation_id = uuid::Uuid::new_v5(
+                &uuid::Uuid::NAMESPACE_OID,
+                conversation.metadata.id.as_bytes(),
             );
+            conversation.metadata.id = format!("imported_{}", conversation_id.simple());
             for (i, message) in conversation.messages.iter_mut().enumerate() {
                 message.id = format!("msg_{}_{}", conversation.metadata.id, i);
             }
+            Self::reassign_block_ids(&mut conversation.memory_blocks, &conversation.metadata.id);
         }
 
         if !settings.preserve_timestamps {
@@ -468,6 +804,34 @@ impl ConversationExporter {
             warnings.extend(validation_warnings);
         }
 
+        if settings.store_memory_blocks {
+            match &self.memory_manager {
+                Some(memory_manager) => {
+                    for block in &conversation.memory_blocks {
+                        memory_manager
+                            .store(block.clone())
+                            .await
+                            .map_err(|e| anyhow::anyhow!(e))?;
+                    }
+                }
+                None => warnings.push(
+                    "No memory manager to store the memory blocks in".to_string(),
+                ),
+            }
+        }
+
+        if settings.restore_core_blocks {
+            match &self.core_blocks {
+                Some(core_blocks) => {
+                    let mut core_blocks = core_blocks.write().await;
+                    warnings.extend(Self::restore_framing(&conversation, &mut core_blocks).await?);
+                }
+                None => warnings.push(
+                    "No core blocks to restore the system messages into".to_string(),
+                ),
+            }
+        }
+
         let import_info = ImportInfo {
             imported_at: Utc::now(),
             source_format: format,
@@ -486,64 +850,251 @@ impl ConversationExporter {
         Ok((conversation, import_info))
     }
 
+    /// Write a conversation's framing back into core blocks, activating them
+    ///
+    /// Untagged system messages restore the system prompt. Returns warnings
+    /// for pinned context that names no core block and for blocks over their
+    /// token budget.
+    async fn restore_framing(
+        conversation: &ExportableConversation,
+        core_blocks: &mut CoreBlockManager,
+    ) -> Result<Vec<String>> {
+        let system_messages = conversation.system_messages.iter().map(|message| {
+            let core_type = match message.metadata.custom.get(CORE_BLOCK_KEY) {
+                Some(name) => core_block_type(name),
+                None => Some(CoreBlockType::SystemPrompt),
+            };
+            (core_type, message.content.as_str())
+        });
+        let pinned = conversation.pinned_context.iter().map(|section| {
+            match section.strip_prefix("# ").and_then(|rest| rest.split_once("\n\n")) {
+                Some((name, content)) => (core_block_type(name), content),
+                None => (None, section.as_str()),
+            }
+        });
+
+        let mut warnings = Vec::new();
+        for (core_type, content) in system_messages.chain(pinned) {
+            let Some(core_type) = core_type else {
+                let preview: String = content.chars().take(40).collect();
+                warnings.push(format!("Skipped context that names no core block: {}", preview));
+                continue;
+            };
+            if let Some(warning) = core_blocks.update_block(core_type, content.to_string()).await? {
+                warnings.push(warning.to_string());
+            }
+            core_blocks.activate_block(core_type)?;
+        }
+        Ok(warnings)
+    }
+
+    /// Give imported blocks deterministic ids and rewrite references to match
+    ///
+    /// Ids derive from the source ids, so a block imported again keeps its id
+    /// even if its content was edited, and blocks that share content stay
+    /// apart. References to blocks outside the import are left untouched.
+    fn reassign_block_ids(blocks: &mut [MemoryBlock], namespace: &str) {
+        let new_ids: HashMap<BlockId, BlockId> = blocks
+            .iter()
+            .map(|block| {
+                let old_id = block.id().clone();
+                let new_id = BlockId::deterministic(namespace, old_id.as_str());
+                (old_id, new_id)
+            })
+            .collect();
+
+        for block in blocks {
+            block.metadata.id = new_ids[&block.metadata.id].clone();
+            for reference in &mut block.metadata.reference_ids {
+                if let Some(new_id) = new_ids.get(reference) {
+                    *reference = new_id.clone();
+                }
+            }
+        }
+    }
+
     /// Convert internal messages to exportable format
     async fn convert_messages_to_exportable(
         &self,
         messages: Vec<InternalChatMessage>,
         settings: &ExportSettings,
     ) -> Result<Vec<ExportableMessage>> {
-        let mut exportable_messages = Vec::new();
+        Ok(messages
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, message)| Self::to_exportable(i, message, settings))
+            .filter_map(|message| Self::apply_content_settings(message, settings))
+            .collect())
+    }
 
-        for (i, message) in messages.into_iter().enumerate() {
-            let (message_type, content, author) = match message {
-                InternalChatMessage::User { content } => {
-                    (MessageType::User, content, "User".to_string())
-                }
-                InternalChatMessage::Assistant { content, .. } => {
-                    (MessageType::Assistant, content, "Assistant".to_string())
+    /// Convert one internal message, or `None` if the settings filter it out
+    fn to_exportable(
+        index: usize,
+        message: InternalChatMessage,
+        settings: &ExportSettings,
+    ) -> Option<ExportableMessage> {
+        let is_system_note = message.is_system_note();
+        let mut tool_calls = None;
+        let mut tool_call_id = None;
+        let mut language = None;
+        let (message_type, content, author) = match message {
+            InternalChatMessage::System { content } if is_system_note => {
+                if !settings.include_system_notes {
+                    return None;
                 }
-                InternalChatMessage::System { content } => {
-                    if !settings.include_system_messages {
-                        continue;
-                    }
-                    (MessageType::System, content, "System".to_string())
+                (MessageType::Note, content, "System".to_string())
+            }
+            InternalChatMessage::User { content } => {
+                language = detect_language(&content).map(str::to_string);
+                (MessageType::User, content, "User".to_string())
+            }
+            InternalChatMessage::Assistant {
+                content,
+                tool_responses,
+            } => {
+                // Assistant tool responses record the calls the model made
+                tool_calls = tool_responses.map(|responses| {
+                    responses
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, response)| ExportableToolCall {
+                            id: response
+                                .call_id
+                                .unwrap_or_else(|| format!("call_{}_{}", index, i)),
+                            kind: "function".to_string(),
+                            function: ExportableFunctionCall {
+                                name: response.tool_name,
+                                arguments: response.content,
+                            },
+                        })
+                        .collect()
+                });
+                (MessageType::Assistant, content, "Assistant".to_string())
+            }
+            InternalChatMessage::System { content } => {
+                if !settings.include_system_messages {
+                    return None;
                 }
-                InternalChatMessage::Tool {
-                    tool_name, content, ..
-                } => (MessageType::Tool, content, format!("Tool({})", tool_name)),
-            };
+                (MessageType::System, content, "System".to_string())
+            }
+            InternalChatMessage::Tool {
+                tool_name,
+                content,
+                call_id,
+            } => {
+                tool_call_id = call_id;
+                (MessageType::Tool, content, format!("Tool({})", tool_name))
+            }
+        };
 
-            // Apply message type filter
-            if let Some(ref filter) = settings.message_type_filter {
-                if !filter.contains(&message_type) {
-                    continue;
-                }
+        // Apply message type filter
+        if let Some(ref filter) = settings.message_type_filter
+            && !filter.contains(&message_type)
+        {
+            return None;
+        }
+
+        Some(ExportableMessage {
+            id: format!("msg_{}", index),
+            role: message_type.role().to_string(),
+            message_type,
+            content,
+            tool_calls,
+            tool_call_id,
+            reasoning: None,
+            language,
+            timestamp: Utc::now(), // Would use actual timestamp in real implementation
+            author,
+            metadata: MessageMetadata {
+                token_count: None, // Would calculate if token manager available
+                processing_time_ms: None,
+                model: None,
+                temperature: None,
+                confidence: None,
+                importance: MessageImportance::default(),
+                is_bookmarked: false,
+                custom: HashMap::new(),
+            },
+            references: Vec::new(),
+            attachments: Vec::new(),
+            incomplete: false,
+        })
+    }
+
+    /// Drop reasoning and tool calls the settings exclude
+    ///
+    /// Tool result messages are dropped entirely without tool calls.
+    fn apply_content_settings(
+        mut message: ExportableMessage,
+        settings: &ExportSettings,
+    ) -> Option<ExportableMessage> {
+        if !settings.include_reasoning {
+            message.reasoning = None;
+        }
+        if !settings.include_tool_calls {
+            if message.message_type == MessageType::Tool {
+                return None;
             }
+            message.tool_calls = None;
+        }
+        Some(message)
+    }
 
-            let exportable_message = ExportableMessage {
-                id: format!("msg_{}", i),
-                message_type,
-                content,
-                timestamp: Utc::now(), // Would use actual timestamp in real implementation
-                author,
+    /// The agent's framing from its active core blocks, by priority
+    ///
+    /// The system prompt becomes a system message and every other block a
+    /// section of pinned context.
+    async fn collect_framing(
+        &self,
+        settings: &ExportSettings,
+    ) -> (Vec<ExportableMessage>, Vec<String>) {
+        let mut system_messages = Vec::new();
+        let mut pinned_context = Vec::new();
+        let Some(core_blocks) = self.core_blocks.as_ref() else {
+            return (system_messages, pinned_context);
+        };
+        if !settings.include_system_messages {
+            return (system_messages, pinned_context);
+        }
+
+        let mut core_blocks = core_blocks.write().await;
+        for block in core_blocks.get_active_blocks() {
+            let Some(content) = block.get_text_content() else {
+                continue;
+            };
+            if block.core_type != CoreBlockType::SystemPrompt {
+                pinned_context.push(format!("# {:?}\n\n{}", block.core_type, content));
+                continue;
+            }
+            let mut custom = HashMap::new();
+            custom.insert(CORE_BLOCK_KEY.to_string(), format!("{:?}", block.core_type));
+            system_messages.push(ExportableMessage {
+                id: format!("system_{}", system_messages.len()),
+                role: MessageType::System.role().to_string(),
+                message_type: MessageType::System,
+                content: content.to_string(),
+                tool_calls: None,
+                tool_call_id: None,
+                reasoning: None,
+                language: None,
+                timestamp: Utc::now(),
+                author: "System".to_string(),
                 metadata: MessageMetadata {
-                    token_count: None, // Would calculate if token manager available
+                    token_count: None,
                     processing_time_ms: None,
                     model: None,
                     temperature: None,
                     confidence: None,
                     importance: MessageImportance::default(),
                     is_bookmarked: false,
-                    custom: HashMap::new(),
+                    custom,
                 },
                 references: Vec::new(),
                 attachments: Vec::new(),
-            };
-
-            exportable_messages.push(exportable_message);
+                incomplete: false,
+            });
         }
-
-        Ok(exportable_messages)
+        (system_messages, pinned_context)
     }
 
     /// Collect memory blocks for the conversation
@@ -632,6 +1183,10 @@ impl ConversationExporter {
                 let jsonl = self.convert_to_jsonl(conversation)?;
                 tokio::fs::write(output_path, jsonl).await?;
             }
+            ExportFormat::MarkdownTranscript => {
+                let transcript = self.convert_to_transcript(conversation);
+                tokio::fs::write(output_path, transcript).await?;
+            }
         }
 
         Ok(())
@@ -647,6 +1202,9 @@ impl ConversationExporter {
             ExportFormat::Json => Ok(serde_json::from_str(content)?),
             ExportFormat::Yaml => Ok(serde_yaml::from_str(content)?),
             ExportFormat::Jsonl => self.parse_jsonl(content),
+            ExportFormat::MarkdownTranscript => Err(anyhow::anyhow!(
+                "Markdown transcripts are for display only and can't be imported; export JSONL instead"
+            )),
             _ => Err(anyhow::anyhow!(
                 "Import not yet supported for format: {:?}",
                 format
@@ -687,73 +1245,147 @@ impl ConversationExporter {
 
     /// Convert conversation to Markdown format
     fn convert_to_markdown(&self, conversation: &ExportableConversation) -> String {
-        let mut markdown = String::new();
+        let mut markdown = Vec::new();
 
-        markdown.push_str(&format!("# {}\n\n", conversation.metadata.title));
-        markdown.push_str(&format!(
-            "**Started:** {}\n",
-            conversation
-                .metadata
-                .started_at
-                .format("%Y-%m-%d %H:%M:%S UTC")
-        ));
-        markdown.push_str(&format!("**User:** {}\n", conversation.metadata.user_id));
-        markdown.push_str(&format!(
-            "**Session:** {}\n",
-            conversation.metadata.session_id
-        ));
-        markdown.push_str(&format!(
-            "**Messages:** {}\n\n",
-            conversation.metadata.message_count
-        ));
+        // Writing to a Vec can't fail
+        Self::write_markdown_header(&mut markdown, &conversation.metadata).unwrap();
+        for message in &conversation.messages {
+            Self::write_markdown_message(&mut markdown, message).unwrap();
+        }
+        Self::write_markdown_footer(
+            &mut markdown,
+            conversation.memory_blocks.len(),
+            conversation.summaries.len(),
+        )
+        .unwrap();
+
+        String::from_utf8(markdown).expect("Markdown export is valid UTF-8")
+    }
 
-        if let Some(ref description) = conversation.metadata.description {
-            markdown.push_str(&format!("**Description:** {}\n\n", description));
+    fn write_markdown_header(writer: &mut impl Write, metadata: &ConversationMetadata) -> Result<()> {
+        write!(writer, "# {}\n\n", metadata.title)?;
+        writeln!(
+            writer,
+            "**Started:** {}",
+            metadata.started_at.format("%Y-%m-%d %H:%M:%S UTC")
+        )?;
+        writeln!(writer, "**User:** {}", metadata.user_id)?;
+        writeln!(writer, "**Session:** {}", metadata.session_id)?;
+        write!(writer, "**Messages:** {}\n\n", metadata.message_count)?;
+
+        if let Some(ref description) = metadata.description {
+            write!(writer, "**Description:** {}\n\n", description)?;
         }
 
-        if !conversation.metadata.tags.is_empty() {
-            markdown.push_str(&format!(
-                "**Tags:** {}\n\n",
-                conversation.metadata.tags.join(", ")
-            ));
+        if !metadata.tags.is_empty() {
+            write!(writer, "**Tags:** {}\n\n", metadata.tags.join(", "))?;
         }
 
-        markdown.push_str("## Conversation\n\n");
+        writer.write_all(b"## Conversation\n\n")?;
+        Ok(())
+    }
 
-        for message in &conversation.messages {
-            let author_emoji = match message.message_type {
-                MessageType::User => "👤",
-                MessageType::Assistant => "🤖",
-                MessageType::System => "⚙️",
-                MessageType::Tool => "🔧",
-                MessageType::Error => "❌",
-                MessageType::Note => "📝",
-            };
+    fn write_markdown_message(writer: &mut impl Write, message: &ExportableMessage) -> Result<()> {
+        let author_emoji = match message.message_type {
+            MessageType::User => "👤",
+            MessageType::Assistant => "🤖",
+            MessageType::System => "⚙️",
+            MessageType::Tool => "🔧",
+            MessageType::Error => "❌",
+            MessageType::Note => "📝",
+        };
 
-            markdown.push_str(&format!(
-                "### {} {} ({})\n\n{}\n\n",
-                author_emoji,
-                message.author,
-                message.timestamp.format("%H:%M:%S"),
-                message.content
-            ));
+        write!(
+            writer,
+            "### {} {} ({})\n\n{}\n\n",
+            author_emoji,
+            message.author,
+            message.timestamp.format("%H:%M:%S"),
+            message.content
+        )?;
+        Ok(())
+    }
+
+    fn write_markdown_footer(
+        writer: &mut impl Write,
+        memory_block_count: usize,
+        summary_count: usize,
+    ) -> Result<()> {
+        if memory_block_count > 0 {
+            write!(writer, "## Memory Blocks ({})\n\n", memory_block_count)?;
         }
 
-        if !conversation.memory_blocks.is_empty() {
-            markdown.push_str(&format!(
-                "## Memory Blocks ({})\n\n",
-                conversation.memory_blocks.len()
-            ));
+        if summary_count > 0 {
+            write!(writer, "## Summaries ({})\n\n", summary_count)?;
         }
 
-        if !conversation.summaries.is_empty() {
-            markdown.push_str(&format!(
-                "## Summaries ({})\n\n",
-                conversation.summaries.len()
-            ));
+        Ok(())
+    }
+
+    /// Convert conversation to a Markdown transcript
+    fn convert_to_transcript(&self, conversation: &ExportableConversation) -> String {
+        let mut transcript = Vec::new();
+
+        // Writing to a Vec can't fail
+        Self::write_transcript_header(&mut transcript, &conversation.metadata).unwrap();
+        for message in &conversation.messages {
+            Self::write_transcript_message(&mut transcript, message).unwrap();
         }
 
-        markdown
+        String::from_utf8(transcript).expect("Markdown transcript is valid UTF-8")
+    }
+
+    fn write_transcript_header(writer: &mut impl Write, metadata: &ConversationMetadata) -> Result<()> {
+        write!(writer, "# {}\n\n", metadata.title)?;
+        write!(
+            writer,
+            "_Started {} · {} messages_\n\n",
+            metadata.started_at.format("%Y-%m-%d %H:%M:%S UTC"),
+            metadata.message_count
+        )?;
+        Ok(())
+    }
+
+    /// Write one message under a role header
+    ///
+    /// Content is written as-is so fenced code blocks survive; a fence left
+    /// open by a cut-off message is closed so it can't swallow the rest of
+    /// the transcript. Tool output and arguments are fenced as code.
+    fn write_transcript_message(writer: &mut impl Write, message: &ExportableMessage) -> Result<()> {
+        let role = match message.message_type {
+            MessageType::User => "User".to_string(),
+            MessageType::Assistant => "Assistant".to_string(),
+            MessageType::System => "System".to_string(),
+            MessageType::Tool => message.author.clone(),
+            MessageType::Error => "Error".to_string(),
+            MessageType::Note => "Note".to_string(),
+        };
+        write!(
+            writer,
+            "---\n\n### {} · {}\n\n",
+            role,
+            message.timestamp.format("%Y-%m-%d %H:%M:%S UTC")
+        )?;
+
+        if let Some(reasoning) = &message.reasoning {
+            writer.write_all(b"**Reasoning**\n\n")?;
+            for line in close_open_fence(reasoning).lines() {
+                writeln!(writer, ">{}{}", if line.is_empty() { "" } else { " " }, line)?;
+            }
+            writer.write_all(b"\n")?;
+        }
+
+        if message.message_type == MessageType::Tool {
+            writeln!(writer, "{}", fenced(&message.content, "text"))?;
+        } else if !message.content.is_empty() {
+            write!(writer, "{}\n\n", close_open_fence(message.content.trim_end()))?;
+        }
+
+        for call in message.tool_calls.iter().flatten() {
+            write!(writer, "**Tool call:** `{}`\n\n", call.function.name)?;
+            writeln!(writer, "{}", fenced(&call.function.arguments, "json"))?;
+        }
+        Ok(())
     }
 
     /// Convert conversation to HTML format
@@ -881,15 +1513,19 @@ impl ConversationExporter {
 
     /// Convert conversation to JSON Lines format
     fn convert_to_jsonl(&self, conversation: &ExportableConversation) -> Result<String> {
-        let mut jsonl = String::new();
+        let mut jsonl = Vec::new();
 
         for message in &conversation.messages {
-            let json_line = serde_json::to_string(message)?;
-            jsonl.push_str(&json_line);
-            jsonl.push('\n');
+            Self::write_jsonl_message(&mut jsonl, message)?;
         }
 
-        Ok(jsonl)
+        Ok(String::from_utf8(jsonl)?)
+    }
+
+    fn write_jsonl_message(writer: &mut impl Write, message: &ExportableMessage) -> Result<()> {
+        serde_json::to_writer(&mut *writer, message)?;
+        writer.write_all(b"\n")?;
+        Ok(())
     }
 
     /// Parse JSON Lines format
@@ -918,6 +1554,9 @@ impl ConversationExporter {
             language: None,
             status: ConversationStatus::Active,
             participants: Vec::new(),
+            parent_id: None,
+            forked_at: None,
+            forked_from_message: None,
         };
 
         let export_info = ExportInfo {
@@ -933,6 +1572,8 @@ impl ConversationExporter {
         Ok(ExportableConversation {
             metadata,
             messages,
+            system_messages: Vec::new(),
+            pinned_context: Vec::new(),
             memory_blocks: Vec::new(),
             summaries: Vec::new(),
             token_usage: Vec::new(),
@@ -996,3 +1637,471 @@ impl ConversationExporter {
         self.templates.read().await.keys().cloned().collect()
     }
 }
+
+/// `text` with a closing fence added if it leaves a code fence open
+fn close_open_fence(text: &str) -> std::borrow::Cow<'_, str> {
+    let mut open_fence: Option<&str> = None;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        let ticks = trimmed.len() - trimmed.trim_start_matches('`').len();
+        if ticks < 3 {
+            continue;
+        }
+        match open_fence {
+            Some(fence) if ticks >= fence.len() && trimmed[ticks..].trim().is_empty() => {
+                open_fence = None
+            }
+            Some(_) => {}
+            None => open_fence = Some(&trimmed[..ticks]),
+        }
+    }
+    match open_fence {
+        Some(fence) => format!("{}\n{}", text, fence).into(),
+        None => text.into(),
+    }
+}
+
+/// Core block type named as in its `Debug` output, e.g. `SystemPrompt`
+fn core_block_type(name: &str) -> Option<CoreBlockType> {
+    CoreBlockType::all_types()
+        .into_iter()
+        .find(|core_type| format!("{:?}", core_type) == name)
+}
+
+/// `text` in a code fence longer than any backtick run inside it
+fn fenced(text: &str, language: &str) -> String {
+    let longest_run = text
+        .split(|c: char| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    format!("{fence}{language}\n{}\n{fence}\n", text.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversation::test_fixtures;
+
+    /// Sink that validates each JSON line as it arrives and keeps only the
+    /// unfinished tail, so the test never holds the whole export
+    struct LineCheckingSink {
+        partial: Vec<u8>,
+        lines: usize,
+        max_buffered: usize,
+    }
+
+    impl Write for LineCheckingSink {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.partial.extend_from_slice(buf);
+            self.max_buffered = self.max_buffered.max(self.partial.len());
+
+            while let Some(end) = self.partial.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = self.partial.drain(..=end).collect();
+                let message: ExportableMessage = serde_json::from_slice(&line[..end])
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+                assert_eq!(message.id, format!("msg_{}", self.lines));
+                assert_eq!(message.content, format!("message number {}", self.lines));
+                self.lines += 1;
+            }
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn metadata(message_count: usize) -> ConversationMetadata {
+        test_fixtures::metadata("conv", "Large conversation", message_count)
+    }
+
+    #[tokio::test]
+    async fn test_streaming_jsonl_export_of_large_conversation() {
+        const COUNT: usize = 5_000;
+        let exporter = ConversationExporter::new(std::env::temp_dir());
+        let messages = (0..COUNT).map(|i| {
+            let content = format!("message number {}", i);
+            if i % 2 == 0 {
+                InternalChatMessage::User { content }
+            } else {
+                InternalChatMessage::Assistant {
+                    content,
+                    tool_responses: None,
+                }
+            }
+        });
+
+        let mut sink = LineCheckingSink {
+            partial: Vec::new(),
+            lines: 0,
+            max_buffered: 0,
+        };
+        let info = exporter
+            .export_to_writer(
+                messages,
+                metadata(COUNT),
+                &mut sink,
+                ExportFormat::Jsonl,
+                ExportSettings::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(sink.lines, COUNT);
+        assert!(sink.partial.is_empty());
+        // Only about one message is ever pending in the sink
+        assert!(sink.max_buffered < 1024, "buffered {} bytes", sink.max_buffered);
+        assert!(info.file_size_bytes.unwrap() > COUNT * 100);
+    }
+
+    #[tokio::test]
+    async fn test_export_to_string_renders_markdown() {
+        let exporter = ConversationExporter::new(std::env::temp_dir());
+        let messages = vec![
+            InternalChatMessage::User {
+                content: "hello".to_string(),
+            },
+            InternalChatMessage::Assistant {
+                content: "hi there".to_string(),
+                tool_responses: None,
+            },
+        ];
+
+        let markdown = exporter
+            .export_to_string(
+                messages,
+                metadata(2),
+                ExportFormat::Markdown,
+                ExportSettings::default(),
+            )
+            .await
+            .unwrap();
+
+        assert!(markdown.starts_with("# Large conversation\n\n"));
+        assert!(markdown.contains("**Messages:** 2\n\n## Conversation\n\n"));
+        assert_eq!(markdown.matches("\n### ").count(), 2);
+        assert!(markdown.contains("### 👤 User ("));
+        assert!(markdown.trim_end().ends_with("hi there"));
+    }
+
+    #[tokio::test]
+    async fn test_reimport_preserves_block_ids_and_references() {
+        use luts_memory::{BlockType, InMemoryMemoryStore, MemoryBlockBuilder, MemoryContent};
+
+        let fact = MemoryBlockBuilder::new()
+            .with_id("fact-1")
+            .with_type(BlockType::Fact)
+            .with_user_id("user")
+            .with_content(MemoryContent::Text("the sky is blue".to_string()))
+            .build()
+            .unwrap();
+        let summary = MemoryBlockBuilder::new()
+            .with_id("summary-1")
+            .with_type(BlockType::Summary)
+            .with_user_id("user")
+            .with_reference_id("fact-1")
+            .with_reference_id("outside-block")
+            .with_content(MemoryContent::Text("colours".to_string()))
+            .build()
+            .unwrap();
+
+        let conversation = ExportableConversation {
+            metadata: metadata(0),
+            memory_blocks: vec![fact, summary],
+            ..test_fixtures::conversation("conv", Vec::new())
+        };
+        let path = std::env::temp_dir().join(format!("luts_import_{}.json", uuid::Uuid::new_v4()));
+        tokio::fs::write(&path, serde_json::to_string(&conversation).unwrap())
+            .await
+            .unwrap();
+
+        let memory = Arc::new(MemoryManager::new(InMemoryMemoryStore::new()));
+        let exporter = ConversationExporter::new_with_components(
+            std::env::temp_dir(),
+            Some(memory.clone()),
+            None,
+        );
+        let settings = ImportSettings {
+            store_memory_blocks: true,
+            ..ImportSettings::default()
+        };
+        let (first, _) = exporter
+            .import_conversation(&path, ExportFormat::Json, settings.clone())
+            .await
+            .unwrap();
+        let (second, _) = exporter
+            .import_conversation(&path, ExportFormat::Json, settings)
+            .await
+            .unwrap();
+        tokio::fs::remove_file(&path).await.unwrap();
+
+        // The second import replaced the stored blocks instead of adding more
+        assert_eq!(memory.list("user").await.unwrap().len(), 2);
+
+        assert_eq!(first.metadata.id, second.metadata.id);
+        let ids: Vec<&BlockId> = first.memory_blocks.iter().map(|b| b.id()).collect();
+        let reimported_ids: Vec<&BlockId> = second.memory_blocks.iter().map(|b| b.id()).collect();
+        assert_eq!(ids, reimported_ids);
+        assert_ne!(ids[0].as_str(), "fact-1");
+
+        // The summary still points at the (renamed) fact; outside references are kept
+        let references = second.memory_blocks[1].reference_ids();
+        assert_eq!(references[0], *ids[0]);
+        assert_eq!(references[1].as_str(), "outside-block");
+    }
+
+    #[test]
+    fn test_reassigned_ids_follow_source_ids_not_content() {
+        use luts_memory::{BlockType, MemoryBlockBuilder, MemoryContent};
+
+        let block = |id: &str, text: &str| {
+            MemoryBlockBuilder::new()
+                .with_id(id)
+                .with_type(BlockType::Fact)
+                .with_user_id("user")
+                .with_content(MemoryContent::Text(text.to_string()))
+                .build()
+                .unwrap()
+        };
+
+        // Blocks sharing content stay separate
+        let mut blocks = vec![block("a", "same"), block("b", "same")];
+        ConversationExporter::reassign_block_ids(&mut blocks, "conversation");
+        assert_ne!(blocks[0].id(), blocks[1].id());
+
+        // An edited block keeps the id it was imported under before
+        let mut edited = vec![block("a", "edited")];
+        ConversationExporter::reassign_block_ids(&mut edited, "conversation");
+        assert_eq!(edited[0].id(), blocks[0].id());
+    }
+
+    fn tool_conversation() -> Vec<InternalChatMessage> {
+        vec![
+            InternalChatMessage::User {
+                content: "What is 2+2?".to_string(),
+            },
+            InternalChatMessage::Assistant {
+                content: String::new(),
+                tool_responses: Some(vec![crate::llm::ToolResponse::with_call_id(
+                    "calculator",
+                    r#"{"expression":"2+2"}"#,
+                    "call_1",
+                )]),
+            },
+            InternalChatMessage::Tool {
+                tool_name: "calculator".to_string(),
+                content: "4".to_string(),
+                call_id: Some("call_1".to_string()),
+            },
+            InternalChatMessage::Assistant {
+                content: "It's 4:\n\n```python\nprint(2 + 2)\n```".to_string(),
+                tool_responses: None,
+            },
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_jsonl_export_round_trips_through_import() {
+        let exporter = ConversationExporter::new(std::env::temp_dir());
+        let jsonl = exporter
+            .export_to_string(
+                tool_conversation(),
+                metadata(4),
+                ExportFormat::Jsonl,
+                ExportSettings::default(),
+            )
+            .await
+            .unwrap();
+
+        let lines: Vec<serde_json::Value> = jsonl
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let roles: Vec<&str> = lines.iter().map(|l| l["role"].as_str().unwrap()).collect();
+        assert_eq!(roles, vec!["user", "assistant", "tool", "assistant"]);
+        assert_eq!(
+            lines[1]["tool_calls"],
+            serde_json::json!([{
+                "id": "call_1",
+                "type": "function",
+                "function": { "name": "calculator", "arguments": "{\"expression\":\"2+2\"}" }
+            }])
+        );
+        assert_eq!(lines[2]["tool_call_id"], "call_1");
+        assert!(lines[0].get("tool_calls").is_none());
+
+        let path = std::env::temp_dir().join(format!("luts_export_{}.jsonl", uuid::Uuid::new_v4()));
+        tokio::fs::write(&path, &jsonl).await.unwrap();
+        let settings = ImportSettings {
+            preserve_ids: true,
+            ..ImportSettings::default()
+        };
+        let (conversation, info) = exporter
+            .import_conversation(&path, ExportFormat::Jsonl, settings)
+            .await
+            .unwrap();
+        tokio::fs::remove_file(&path).await.unwrap();
+
+        assert_eq!(info.messages_imported, 4);
+        let exported: Vec<ExportableMessage> = jsonl
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        for (imported, original) in conversation.messages.iter().zip(&exported) {
+            assert_eq!(imported.id, original.id);
+            assert_eq!(imported.role, original.role);
+            assert_eq!(imported.content, original.content);
+            assert_eq!(imported.tool_calls, original.tool_calls);
+            assert_eq!(imported.tool_call_id, original.tool_call_id);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_markdown_transcript_respects_settings_and_is_display_only() {
+        let exporter = ConversationExporter::new(std::env::temp_dir());
+        let mut messages: Vec<ExportableMessage> = tool_conversation()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, m)| ConversationExporter::to_exportable(i, m, &ExportSettings::default()))
+            .collect();
+        messages[3].reasoning = Some("Add the numbers.".to_string());
+        // A response cut off inside a code block
+        messages[0].content = "Check this:\n```rust\nfn main() {}".to_string();
+
+        let mut output = Vec::new();
+        exporter
+            .export_messages_to_writer(
+                messages.clone(),
+                metadata(4),
+                &mut output,
+                ExportFormat::MarkdownTranscript,
+                ExportSettings::default(),
+            )
+            .await
+            .unwrap();
+        let transcript = String::from_utf8(output).unwrap();
+        assert!(transcript.starts_with("# Large conversation\n\n"));
+        assert_eq!(transcript.matches("\n### ").count(), 4);
+        assert!(transcript.contains("### User · "));
+        assert!(transcript.contains("### Tool(calculator) · "));
+        assert!(transcript.contains("Check this:\n```rust\nfn main() {}\n```\n\n---"));
+        assert!(transcript.contains("```python\nprint(2 + 2)\n```"));
+        assert!(transcript.contains("**Tool call:** `calculator`"));
+        assert!(transcript.contains("> Add the numbers."));
+
+        let settings = ExportSettings {
+            include_reasoning: false,
+            include_tool_calls: false,
+            ..ExportSettings::default()
+        };
+        let mut output = Vec::new();
+        exporter
+            .export_messages_to_writer(
+                messages,
+                metadata(4),
+                &mut output,
+                ExportFormat::MarkdownTranscript,
+                settings,
+            )
+            .await
+            .unwrap();
+        let transcript = String::from_utf8(output).unwrap();
+        assert_eq!(transcript.matches("\n### ").count(), 3);
+        assert!(!transcript.contains("calculator"));
+        assert!(!transcript.contains("Add the numbers."));
+
+        let path = std::env::temp_dir().join(format!("luts_transcript_{}.md", uuid::Uuid::new_v4()));
+        tokio::fs::write(&path, &transcript).await.unwrap();
+        let result = exporter
+            .import_conversation(&path, ExportFormat::MarkdownTranscript, ImportSettings::default())
+            .await;
+        tokio::fs::remove_file(&path).await.unwrap();
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_system_prompt_and_pinned_context_survive_round_trip() {
+        let prompt = "You are a terse ship's navigator.";
+        let mut agent_blocks = CoreBlockManager::new("user", None);
+        agent_blocks.initialize().await.unwrap();
+        agent_blocks
+            .update_block(CoreBlockType::SystemPrompt, prompt.to_string())
+            .await
+            .unwrap();
+        agent_blocks
+            .update_block(CoreBlockType::KeyFacts, "The crew uses metric units.".to_string())
+            .await
+            .unwrap();
+        let exporter = ConversationExporter::new(std::env::temp_dir())
+            .with_core_blocks(Arc::new(RwLock::new(agent_blocks)));
+
+        let path = std::env::temp_dir().join(format!("luts_framing_{}.json", uuid::Uuid::new_v4()));
+        exporter
+            .export_conversation(
+                tool_conversation(),
+                metadata(4),
+                &path,
+                ExportFormat::Json,
+                ExportSettings::default(),
+            )
+            .await
+            .unwrap();
+
+        // A fresh agent picks the framing back up
+        let fresh_blocks = Arc::new(RwLock::new(CoreBlockManager::new("user", None)));
+        let importer =
+            ConversationExporter::new(std::env::temp_dir()).with_core_blocks(fresh_blocks.clone());
+        let settings = ImportSettings {
+            restore_core_blocks: true,
+            ..ImportSettings::default()
+        };
+        let (conversation, _) = importer
+            .import_conversation(&path, ExportFormat::Json, settings)
+            .await
+            .unwrap();
+        tokio::fs::remove_file(&path).await.unwrap();
+
+        assert_eq!(conversation.system_messages.len(), 1);
+        assert_eq!(conversation.system_messages[0].content, prompt);
+        assert!(
+            conversation
+                .pinned_context
+                .contains(&"# KeyFacts\n\nThe crew uses metric units.".to_string())
+        );
+
+        let mut restored = fresh_blocks.write().await;
+        let system_prompt = restored.get_block(CoreBlockType::SystemPrompt).unwrap();
+        assert_eq!(system_prompt.get_text_content(), Some(prompt));
+        let key_facts = restored.get_block(CoreBlockType::KeyFacts).unwrap();
+        assert_eq!(key_facts.get_text_content(), Some("The crew uses metric units."));
+        assert!(key_facts.is_active);
+    }
+
+    #[tokio::test]
+    async fn test_user_message_language_is_recorded() {
+        let exporter = ConversationExporter::new(std::env::temp_dir());
+        let messages = vec![
+            InternalChatMessage::User {
+                content: "Je cherche un restaurant avec une terrasse pour ce soir.".to_string(),
+            },
+            InternalChatMessage::Assistant {
+                content: "Voici quelques idées.".to_string(),
+                tool_responses: None,
+            },
+        ];
+
+        let jsonl = exporter
+            .export_to_string(messages, metadata(2), ExportFormat::Jsonl, ExportSettings::default())
+            .await
+            .unwrap();
+        let lines: Vec<ExportableMessage> = jsonl
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines[0].language.as_deref(), Some("fr"));
+        // Only user messages are detected
+        assert_eq!(lines[1].language, None);
+    }
+}
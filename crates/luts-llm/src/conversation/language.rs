@@ -0,0 +1,222 @@
+//! Lightweight language detection for conversation messages
+//!
+//! Detection runs locally and cheaply: non-Latin scripts are identified from
+//! their Unicode ranges, and Latin-script languages by counting common
+//! function words. Text that's too short or too mixed to call is left
+//! undetected rather than guessed.
+
+use crate::llm::InternalChatMessage;
+use std::collections::HashMap;
+
+/// Minimum function-word hits before a Latin-script language is reported
+const MIN_WORD_HITS: usize = 2;
+/// Share of letters a non-Latin script needs to decide the language
+const MIN_SCRIPT_SHARE: f64 = 0.3;
+
+/// Common function words for the Latin-script languages we detect
+const FUNCTION_WORDS: &[(&str, &[&str])] = &[
+    (
+        "en",
+        &[
+            "the", "and", "is", "are", "of", "to", "in", "that", "it", "you", "with", "for",
+            "this", "was", "what", "how", "have", "not", "can", "my",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "el", "la", "los", "las", "de", "que", "y", "en", "es", "un", "una", "por", "para",
+            "con", "no", "está", "mi", "del", "pero", "cómo",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "le", "la", "les", "de", "des", "et", "est", "un", "une", "que", "pour", "dans",
+            "pas", "je", "vous", "avec", "ce", "mon", "sur", "qui",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "der", "die", "das", "und", "ist", "nicht", "ein", "eine", "ich", "mit", "zu", "den",
+            "von", "auf", "für", "wie", "mein", "sie", "es", "auch",
+        ],
+    ),
+    (
+        "it",
+        &[
+            "il", "la", "di", "che", "e", "è", "un", "una", "per", "non", "con", "sono", "del",
+            "della", "come", "mio", "gli", "lo", "questo", "anche",
+        ],
+    ),
+    (
+        "pt",
+        &[
+            "o", "a", "os", "as", "de", "que", "e", "é", "um", "uma", "para", "com", "não",
+            "do", "da", "em", "meu", "como", "mas", "você",
+        ],
+    ),
+    (
+        "nl",
+        &[
+            "de", "het", "een", "en", "is", "van", "niet", "ik", "dat", "met", "voor", "op",
+            "zijn", "hoe", "mijn", "wat", "je", "ook", "maar", "naar",
+        ],
+    ),
+];
+
+/// Detect the language of `text`, as an ISO 639-1 code
+///
+/// Returns `None` when the text is too short or ambiguous to tell.
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    detect_script(text).or_else(|| detect_latin(text))
+}
+
+/// The language most user messages in `messages` are written in
+pub fn dominant_language(messages: &[InternalChatMessage]) -> Option<&'static str> {
+    let mut counts: HashMap<&'static str, usize> = HashMap::new();
+    for message in messages {
+        if let InternalChatMessage::User { content } = message
+            && let Some(language) = detect_language(content)
+        {
+            *counts.entry(language).or_default() += 1;
+        }
+    }
+    // Ties go to the alphabetically first code, so the result is stable
+    counts
+        .into_iter()
+        .max_by(|(a, a_count), (b, b_count)| a_count.cmp(b_count).then(b.cmp(a)))
+        .map(|(language, _)| language)
+}
+
+/// English name of a language code returned by [`detect_language`]
+pub fn language_name(code: &str) -> Option<&'static str> {
+    Some(match code {
+        "en" => "English",
+        "es" => "Spanish",
+        "fr" => "French",
+        "de" => "German",
+        "it" => "Italian",
+        "pt" => "Portuguese",
+        "nl" => "Dutch",
+        "ru" => "Russian",
+        "el" => "Greek",
+        "ar" => "Arabic",
+        "he" => "Hebrew",
+        "hi" => "Hindi",
+        "th" => "Thai",
+        "ja" => "Japanese",
+        "ko" => "Korean",
+        "zh" => "Chinese",
+        _ => return None,
+    })
+}
+
+/// Language implied by a non-Latin script making up enough of the letters
+fn detect_script(text: &str) -> Option<&'static str> {
+    let mut letters = 0usize;
+    let mut scripts: HashMap<&'static str, usize> = HashMap::new();
+    for c in text.chars().filter(|c| c.is_alphabetic()) {
+        letters += 1;
+        let language = match c as u32 {
+            0x0400..=0x04FF => "ru",
+            0x0370..=0x03FF => "el",
+            0x0600..=0x06FF => "ar",
+            0x0590..=0x05FF => "he",
+            0x0900..=0x097F => "hi",
+            0x0E00..=0x0E7F => "th",
+            0x3040..=0x30FF => "ja",
+            0xAC00..=0xD7AF | 0x1100..=0x11FF => "ko",
+            0x4E00..=0x9FFF => "zh",
+            _ => continue,
+        };
+        *scripts.entry(language).or_default() += 1;
+    }
+    if letters == 0 {
+        return None;
+    }
+
+    // Japanese mixes kana with Han characters
+    if scripts.get("ja").is_some_and(|&kana| kana > 0) {
+        let japanese = scripts.remove("ja").unwrap_or(0) + scripts.remove("zh").unwrap_or(0);
+        scripts.insert("ja", japanese);
+    }
+    scripts
+        .into_iter()
+        .max_by_key(|(_, count)| *count)
+        .filter(|(_, count)| *count as f64 / letters as f64 >= MIN_SCRIPT_SHARE)
+        .map(|(language, _)| language)
+}
+
+/// Latin-script language with clearly the most function-word hits
+fn detect_latin(text: &str) -> Option<&'static str> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+
+    let mut scores: Vec<(&'static str, usize)> = FUNCTION_WORDS
+        .iter()
+        .map(|(language, function_words)| {
+            let hits = words
+                .iter()
+                .filter(|word| function_words.contains(&word.as_str()))
+                .count();
+            (*language, hits)
+        })
+        .collect();
+    scores.sort_by_key(|&(_, hits)| std::cmp::Reverse(hits));
+
+    let (best, best_hits) = scores[0];
+    let runner_up_hits = scores[1].1;
+    (best_hits >= MIN_WORD_HITS && best_hits > runner_up_hits).then_some(best)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_languages_and_abstains_when_unsure() {
+        assert_eq!(
+            detect_language("¿Dónde está la biblioteca? Necesito un libro para mi clase de historia."),
+            Some("es")
+        );
+        assert_eq!(
+            detect_language("Ich habe die Datei nicht gefunden, und der Server ist auch down."),
+            Some("de")
+        );
+        assert_eq!(
+            detect_language("What is the best way to learn Rust and how long does it take?"),
+            Some("en")
+        );
+        assert_eq!(detect_language("Как настроить сервер базы данных?"), Some("ru"));
+        assert_eq!(detect_language("東京の天気はどうですか？"), Some("ja"));
+
+        assert_eq!(detect_language("ok"), None);
+        assert_eq!(detect_language("42 + 17"), None);
+    }
+
+    #[test]
+    fn test_dominant_language_counts_user_messages_only() {
+        let messages = vec![
+            InternalChatMessage::System {
+                content: "You are a helpful assistant and you answer in English.".to_string(),
+            },
+            InternalChatMessage::User {
+                content: "Bonjour, je cherche un restaurant pour ce soir.".to_string(),
+            },
+            InternalChatMessage::Assistant {
+                content: "Sure, what kind of food do you like?".to_string(),
+                tool_responses: None,
+            },
+            InternalChatMessage::User {
+                content: "Je voudrais un restaurant avec une terrasse dans le centre.".to_string(),
+            },
+        ];
+        assert_eq!(dominant_language(&messages), Some("fr"));
+        assert_eq!(dominant_language(&messages[..1]), None);
+    }
+}
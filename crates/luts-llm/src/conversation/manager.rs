@@ -0,0 +1,368 @@
+//! Conversation lifecycle management
+//!
+//! This module keeps track of live conversations and caps their size: when a
+//! conversation reaches the configured message or token limit it is archived
+//! and a linked successor is started, seeded with a summary of the old one.
+
+use crate::conversation::export::ConversationStatus;
+use crate::conversation::summarization::{ConversationSummarizer, estimate_tokens};
+use crate::llm::InternalChatMessage;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+use tracing::info;
+
+/// Size limits that trigger a rollover
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RolloverConfig {
+    /// Roll over once a conversation has this many messages
+    pub max_messages: Option<usize>,
+    /// Roll over once a conversation's messages reach about this many tokens
+    pub max_tokens: Option<usize>,
+}
+
+impl Default for RolloverConfig {
+    fn default() -> Self {
+        Self {
+            max_messages: Some(500),
+            max_tokens: Some(100_000),
+        }
+    }
+}
+
+/// A conversation tracked by the [`ConversationManager`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManagedConversation {
+    /// Unique conversation ID
+    pub id: String,
+    /// User who owns the conversation
+    pub user_id: String,
+    /// Session the conversation belongs to
+    pub session_id: String,
+    /// Messages in order
+    pub messages: Vec<InternalChatMessage>,
+    /// Active, or archived after a rollover
+    pub status: ConversationStatus,
+    /// Conversation this one continues, if it was started by a rollover
+    pub parent_conversation: Option<String>,
+    /// Conversation that continues this one after a rollover
+    pub successor_conversation: Option<String>,
+    /// Summary of the parent conversation this one was seeded with
+    pub carried_summary: Option<String>,
+    /// When the conversation was started
+    pub created_at: DateTime<Utc>,
+    /// When the last message was added
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ManagedConversation {
+    fn new(user_id: &str, session_id: &str) -> Self {
+        let now = Utc::now();
+        Self {
+            id: format!("conv_{}", uuid::Uuid::new_v4()),
+            user_id: user_id.to_string(),
+            session_id: session_id.to_string(),
+            messages: Vec::new(),
+            status: ConversationStatus::Active,
+            parent_conversation: None,
+            successor_conversation: None,
+            carried_summary: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Approximate token count of all messages
+    pub fn token_count(&self) -> usize {
+        self.messages
+            .iter()
+            .map(|message| estimate_tokens(message.content()))
+            .sum()
+    }
+}
+
+/// Tracks conversations and rolls them over when they grow too large
+pub struct ConversationManager {
+    conversations: RwLock<HashMap<String, ManagedConversation>>,
+    config: RwLock<RolloverConfig>,
+}
+
+impl Default for ConversationManager {
+    fn default() -> Self {
+        Self::new(RolloverConfig::default())
+    }
+}
+
+impl ConversationManager {
+    /// Create a manager with the given rollover limits
+    pub fn new(config: RolloverConfig) -> Self {
+        Self {
+            conversations: RwLock::new(HashMap::new()),
+            config: RwLock::new(config),
+        }
+    }
+
+    /// Update the rollover limits
+    pub async fn update_config(&self, config: RolloverConfig) {
+        *self.config.write().await = config;
+    }
+
+    /// Start a new, empty conversation and return its ID
+    pub async fn create_conversation(&self, user_id: &str, session_id: &str) -> String {
+        let conversation = ManagedConversation::new(user_id, session_id);
+        let id = conversation.id.clone();
+        self.conversations
+            .write()
+            .await
+            .insert(id.clone(), conversation);
+        id
+    }
+
+    /// Get a conversation by ID
+    pub async fn get(&self, conversation_id: &str) -> Option<ManagedConversation> {
+        self.conversations
+            .read()
+            .await
+            .get(conversation_id)
+            .cloned()
+    }
+
+    /// Append a message to an active conversation
+    pub async fn add_message(
+        &self,
+        conversation_id: &str,
+        message: InternalChatMessage,
+    ) -> Result<()> {
+        let mut conversations = self.conversations.write().await;
+        let conversation = conversations
+            .get_mut(conversation_id)
+            .ok_or_else(|| anyhow!("Conversation {} not found", conversation_id))?;
+        if !matches!(conversation.status, ConversationStatus::Active) {
+            return Err(anyhow!(
+                "Conversation {} is not active; continue in {}",
+                conversation_id,
+                conversation
+                    .successor_conversation
+                    .as_deref()
+                    .unwrap_or("a new conversation")
+            ));
+        }
+        conversation.messages.push(message);
+        conversation.updated_at = Utc::now();
+        Ok(())
+    }
+
+    /// Append a message, rolling the conversation over if it hits a limit
+    ///
+    /// Returns the ID of the conversation to continue in: the same one, or
+    /// its successor after a rollover.
+    pub async fn add_message_with_rollover(
+        &self,
+        conversation_id: &str,
+        message: InternalChatMessage,
+        summarizer: &ConversationSummarizer,
+    ) -> Result<String> {
+        self.add_message(conversation_id, message).await?;
+        if self.needs_rollover(conversation_id).await {
+            self.rollover(conversation_id, summarizer).await
+        } else {
+            Ok(conversation_id.to_string())
+        }
+    }
+
+    /// Whether a conversation has reached the message or token limit
+    pub async fn needs_rollover(&self, conversation_id: &str) -> bool {
+        let config = self.config.read().await.clone();
+        let conversations = self.conversations.read().await;
+        let Some(conversation) = conversations.get(conversation_id) else {
+            return false;
+        };
+
+        config
+            .max_messages
+            .is_some_and(|max| conversation.messages.len() >= max)
+            || config
+                .max_tokens
+                .is_some_and(|max| conversation.token_count() >= max)
+    }
+
+    /// Archive a conversation and start a linked successor
+    ///
+    /// The successor opens with a system message carrying a summary of the
+    /// old conversation, followed by the recent messages the summary left
+    /// out. Returns the successor's ID.
+    pub async fn rollover(
+        &self,
+        conversation_id: &str,
+        summarizer: &ConversationSummarizer,
+    ) -> Result<String> {
+        let conversation = self
+            .get(conversation_id)
+            .await
+            .ok_or_else(|| anyhow!("Conversation {} not found", conversation_id))?;
+        if !matches!(conversation.status, ConversationStatus::Active) {
+            return Err(anyhow!(
+                "Conversation {} was already archived",
+                conversation_id
+            ));
+        }
+
+        // Summarize outside the lock; the LLM call can take a while
+        let summary = summarizer
+            .summarize_conversation(
+                &conversation.messages,
+                &conversation.user_id,
+                &conversation.session_id,
+            )
+            .await?;
+
+        let mut successor =
+            ManagedConversation::new(&conversation.user_id, &conversation.session_id);
+        successor.parent_conversation = Some(conversation.id.clone());
+        successor.carried_summary = Some(summary.summary_text.clone());
+        successor.messages.push(InternalChatMessage::System {
+            content: format!(
+                "Summary of the previous conversation:\n{}",
+                summary.summary_text
+            ),
+        });
+        successor.messages.extend(
+            conversation
+                .messages
+                .iter()
+                .skip(summary.info.original_message_count)
+                .cloned(),
+        );
+        let successor_id = successor.id.clone();
+
+        let mut conversations = self.conversations.write().await;
+        let archived = conversations
+            .get_mut(conversation_id)
+            .ok_or_else(|| anyhow!("Conversation {} not found", conversation_id))?;
+        if !matches!(archived.status, ConversationStatus::Active) {
+            return Err(anyhow!(
+                "Conversation {} was already archived",
+                conversation_id
+            ));
+        }
+        archived.status = ConversationStatus::Archived;
+        archived.successor_conversation = Some(successor_id.clone());
+        conversations.insert(successor_id.clone(), successor);
+
+        info!(
+            "Rolled conversation {} over into {} after {} messages",
+            conversation_id,
+            successor_id,
+            conversation.messages.len()
+        );
+        Ok(successor_id)
+    }
+
+    /// IDs of a conversation and its ancestors, oldest first
+    pub async fn lineage(&self, conversation_id: &str) -> Vec<String> {
+        let conversations = self.conversations.read().await;
+        let mut lineage = Vec::new();
+        let mut current = Some(conversation_id.to_string());
+        while let Some(id) = current {
+            current = conversations
+                .get(&id)
+                .and_then(|conversation| conversation.parent_conversation.clone());
+            lineage.push(id);
+        }
+        lineage.reverse();
+        lineage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversation::summarization::{SummarizationConfig, SummaryGenerator};
+    use crate::llm::MockAiService;
+    use async_trait::async_trait;
+    use std::sync::Arc;
+
+    struct FixedSummary;
+
+    #[async_trait]
+    impl SummaryGenerator for FixedSummary {
+        async fn summarize(
+            &self,
+            _text: &str,
+            _target_tokens: usize,
+            _language: Option<&str>,
+        ) -> Result<String> {
+            Ok("The user is planning a trip to Lisbon in May.".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_crossing_cap_rolls_over_into_linked_successor() {
+        let storage_path = std::env::temp_dir()
+            .join(format!("luts_rollover_{}", uuid::Uuid::new_v4()))
+            .join("summaries.json");
+        let summarizer =
+            ConversationSummarizer::new(Arc::new(MockAiService::new()), None, storage_path.clone())
+                .with_generator(Arc::new(FixedSummary));
+        summarizer
+            .update_config(SummarizationConfig {
+                preserve_recent_count: 2,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let manager = ConversationManager::new(RolloverConfig {
+            max_messages: Some(6),
+            max_tokens: None,
+        });
+        let first = manager.create_conversation("user", "session").await;
+
+        let mut active = first.clone();
+        for turn in 0..3 {
+            for message in [
+                InternalChatMessage::User {
+                    content: format!("Question {} about Lisbon", turn),
+                },
+                InternalChatMessage::Assistant {
+                    content: format!("Answer {} about Lisbon", turn),
+                    tool_responses: None,
+                },
+            ] {
+                active = manager
+                    .add_message_with_rollover(&active, message, &summarizer)
+                    .await
+                    .unwrap();
+            }
+        }
+        tokio::fs::remove_dir_all(storage_path.parent().unwrap())
+            .await
+            .unwrap();
+
+        assert_ne!(active, first);
+        let archived = manager.get(&first).await.unwrap();
+        assert!(matches!(archived.status, ConversationStatus::Archived));
+        assert_eq!(archived.successor_conversation.as_deref(), Some(active.as_str()));
+
+        let successor = manager.get(&active).await.unwrap();
+        assert_eq!(successor.parent_conversation.as_deref(), Some(first.as_str()));
+        assert_eq!(
+            successor.carried_summary.as_deref(),
+            Some("The user is planning a trip to Lisbon in May.")
+        );
+        // The summary opens the successor, followed by the unsummarized tail
+        assert!(successor.messages[0]
+            .content()
+            .contains("The user is planning a trip to Lisbon in May."));
+        let carried: Vec<&str> = successor.messages[1..].iter().map(|m| m.content()).collect();
+        assert_eq!(carried, vec!["Question 2 about Lisbon", "Answer 2 about Lisbon"]);
+
+        assert!(manager
+            .add_message(&first, InternalChatMessage::User { content: "hi".to_string() })
+            .await
+            .is_err());
+        assert_eq!(manager.lineage(&active).await, vec![first, active]);
+    }
+}
@@ -1,27 +1,38 @@
 //! Conversation management and utilities
 //!
 //! This module contains all conversation-related functionality including
-//! bookmarks, exports, search, segments, auto-save, and summarization.
+//! bookmarks, branching, exports, search, segments, auto-save,
+//! summarization, and conversation rollover.
 
 pub mod auto_save;
 pub mod bookmarks;
+pub mod branches;
 pub mod export;
+pub mod language;
+pub mod manager;
 pub mod search;
 pub mod segments;
 pub mod summarization;
+#[cfg(test)]
+mod test_fixtures;
 
 // Re-export key types for convenience
 pub use auto_save::{
-    AutoSaveConfig, AutoSaveData, AutoSaveManager, AutoSaveState, AutoSaveStats, AutoSaveType,
+    AutoSaveBackend, AutoSaveConfig, AutoSaveData, AutoSaveManager, AutoSaveMetadata,
+    AutoSaveState, AutoSaveStats, AutoSaveType, FilesystemAutoSaveBackend,
 };
 pub use bookmarks::{
-    BookmarkCollection, BookmarkColor, BookmarkManager, BookmarkPriority, BookmarkQuery,
-    BookmarkStats, ConversationBookmark, QuickAccessBookmark,
+    BookmarkCollection, BookmarkColor, BookmarkExportEntry, BookmarkManager, BookmarkPriority,
+    BookmarkQuery, BookmarkStats, BookmarkUpdates, ConversationBookmark, ConversationSource,
+    QuickAccessBookmark,
 };
+pub use branches::ConversationBranches;
 pub use export::{
     ConversationExporter, ConversationMetadata, ExportFormat, ExportSettings,
     ExportableConversation, ExportableMessage, ImportSettings,
 };
+pub use language::{detect_language, dominant_language, language_name};
+pub use manager::{ConversationManager, ManagedConversation, RolloverConfig};
 pub use search::{
     ConversationSearchEngine, ConversationSearchQuery, ConversationSearchResult, SavedSearch,
     SearchAnalytics, SearchFilters,
@@ -31,6 +42,6 @@ pub use segments::{
     SegmentEdit, SegmentType, UndoRedoOperation,
 };
 pub use summarization::{
-    ConversationSummarizer, ConversationSummary, SummarizationAnalytics, SummarizationConfig,
-    SummarizationStrategy,
+    AiSummaryGenerator, ConversationSummarizer, ConversationSummary, ReduceLevelStats,
+    SummarizationAnalytics, SummarizationConfig, SummarizationStrategy, SummaryGenerator,
 };
\ No newline at end of file
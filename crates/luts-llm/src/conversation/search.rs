@@ -4,15 +4,29 @@
 //! supporting full-text search, semantic search, and complex filtering criteria.
 
 use luts_memory::{MemoryManager, BlockType};
-use crate::conversation::export::{ExportableConversation, MessageType, ConversationMetadata};
+use crate::conversation::export::{
+    ConversationMetadata, ExportableConversation, ExportableMessage, MessageType,
+};
 use luts_core::utils::tokens::TokenManager;
 use anyhow::Result;
+use luts_common::LutsError;
+use regex::{Regex, RegexBuilder};
 use chrono::{DateTime, Utc, Duration, Timelike};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
+
+/// BM25 term frequency saturation
+const BM25_K1: f64 = 1.2;
+/// BM25 document length normalization
+const BM25_B: f64 = 0.75;
+/// Longest regex pattern accepted, in bytes
+const MAX_REGEX_PATTERN_LEN: usize = 512;
+/// Compiled program size limit for regex queries
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
 
 /// Advanced search query for conversations
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,6 +49,23 @@ pub struct ConversationSearchQuery {
     pub include_highlights: bool,
     /// Search explanation/debugging
     pub explain: bool,
+    /// How `text_query` is matched against message content
+    #[serde(default)]
+    pub mode: SearchMode,
+}
+
+/// How a text query matches message content
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+pub enum SearchMode {
+    /// Any query term matches; messages are ranked with BM25
+    #[default]
+    Substring,
+    /// The query terms must appear contiguously and in order; surrounding
+    /// quotes are optional
+    Phrase,
+    /// The query is a regular expression; `^` and `$` anchor at line
+    /// boundaries within a message
+    Regex,
 }
 
 impl Default for ConversationSearchQuery {
@@ -49,6 +80,7 @@ impl Default for ConversationSearchQuery {
             offset: None,
             include_highlights: true,
             explain: false,
+            mode: SearchMode::default(),
         }
     }
 }
@@ -70,9 +102,9 @@ pub struct SearchFilters {
     pub token_range: Option<RangeFilter<u32>>,
     /// Filter by tags
     pub tags: Option<TagFilter>,
-    /// Filter by message types
+    /// Only match messages of these types
     pub message_types: Option<Vec<MessageType>>,
-    /// Filter by participants
+    /// Only match messages written by these authors
     pub participants: Option<Vec<String>>,
     /// Filter by language
     pub language: Option<String>,
@@ -286,7 +318,7 @@ pub enum SortDirection {
 pub struct ConversationSearchResult {
     /// Conversation metadata
     pub conversation: ConversationMetadata,
-    /// Search relevance score (0.0 to 1.0)
+    /// Search relevance score (0.0 to 1.0, relative to the best result)
     pub relevance_score: f64,
     /// Highlighted matches in content
     pub highlights: Vec<SearchHighlight>,
@@ -312,9 +344,9 @@ pub struct SearchHighlight {
 /// Highlight position information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HighlightPosition {
-    /// Start character position
+    /// Start byte offset in the original text
     pub start: usize,
-    /// End character position
+    /// End byte offset (exclusive) in the original text
     pub end: usize,
     /// Matched term
     pub term: String,
@@ -344,7 +376,7 @@ pub struct MessageMatch {
     pub timestamp: DateTime<Utc>,
     /// Snippet of matching content
     pub snippet: String,
-    /// Match score for this message
+    /// BM25 score for this message
     pub score: f64,
 }
 
@@ -444,6 +476,9 @@ pub struct SearchAnalytics {
     pub active_users: Vec<(String, usize)>,
     /// Search patterns by time of day
     pub hourly_patterns: Vec<usize>,
+    /// Searches per text search mode
+    #[serde(default)]
+    pub mode_usage: Vec<(SearchMode, usize)>,
 }
 
 /// Conversation search and filtering engine
@@ -495,56 +530,197 @@ impl Default for SearchConfig {
     }
 }
 
-/// Search index for fast text search
+/// Inverted index over message tokens, scored with BM25
+///
+/// Each message is a document. Postings map a term to the messages that
+/// contain it and how often.
 #[derive(Debug, Default)]
 struct SearchIndex {
-    /// Conversation text index
+    /// Indexed conversations by ID
     conversations: HashMap<String, ConversationIndex>,
-    /// Global term frequency
-    term_frequencies: HashMap<String, usize>,
+    /// Term -> (message, term frequency)
+    postings: HashMap<String, HashMap<MessageRef, u32>>,
+    /// Number of indexed messages
+    message_count: usize,
+    /// Sum of indexed message lengths, in tokens
+    total_length: usize,
     /// Last index update time
     last_updated: Option<DateTime<Utc>>,
 }
 
+/// A message in the index: conversation ID and position in it
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct MessageRef {
+    conversation_id: String,
+    message_index: usize,
+}
+
+/// A matched message with its score and highlighted byte ranges
+type MessageHit = (MessageRef, f64, Vec<HighlightPosition>);
+
+/// A matched message's index within its conversation, with score and highlights
+type ConversationHit = (usize, f64, Vec<HighlightPosition>);
+
 /// Individual conversation index
 #[derive(Debug)]
 struct ConversationIndex {
     /// Conversation metadata
     metadata: ConversationMetadata,
-    /// Indexed terms and their positions
-    terms: HashMap<String, Vec<TermPosition>>,
     /// Message content
     messages: Vec<IndexedMessage>,
 }
 
-/// Term position in conversation
-#[derive(Debug)]
-#[allow(dead_code)]
-struct TermPosition {
-    /// Message index
-    message_index: usize,
-    /// Character position in message
-    position: usize,
-    /// Term frequency in this position
-    frequency: usize,
-}
-
 /// Indexed message for search
 #[derive(Debug)]
-#[allow(dead_code)]
 struct IndexedMessage {
     /// Message ID
     id: String,
     /// Message type
     message_type: MessageType,
-    /// Message content (lowercased for search)
-    content: String,
     /// Original content
     original_content: String,
     /// Timestamp
     timestamp: DateTime<Utc>,
     /// Author
     author: String,
+    /// Length in tokens
+    length: usize,
+}
+
+impl SearchIndex {
+    /// Add a message's tokens to the postings and length statistics
+    fn add_message(&mut self, conversation_id: &str, message_index: usize, message: &IndexedMessage) {
+        let message_ref = MessageRef {
+            conversation_id: conversation_id.to_string(),
+            message_index,
+        };
+        for (term, _) in tokenize(&message.original_content) {
+            *self
+                .postings
+                .entry(term)
+                .or_default()
+                .entry(message_ref.clone())
+                .or_insert(0) += 1;
+        }
+        self.message_count += 1;
+        self.total_length += message.length;
+    }
+
+    /// Remove a conversation and its messages' postings
+    fn remove_conversation(&mut self, conversation_id: &str) {
+        let Some(conversation) = self.conversations.remove(conversation_id) else {
+            return;
+        };
+        self.postings.retain(|_, messages| {
+            messages.retain(|message_ref, _| message_ref.conversation_id != conversation_id);
+            !messages.is_empty()
+        });
+        self.message_count -= conversation.messages.len();
+        self.total_length -= conversation
+            .messages
+            .iter()
+            .map(|message| message.length)
+            .sum::<usize>();
+    }
+
+    /// The indexed message a posting refers to
+    fn message(&self, message_ref: &MessageRef) -> &IndexedMessage {
+        &self.conversations[&message_ref.conversation_id].messages[message_ref.message_index]
+    }
+
+    /// BM25 inverse document frequency of a term
+    fn idf(&self, term: &str) -> f64 {
+        let document_frequency = self.postings.get(term).map_or(0, |messages| messages.len()) as f64;
+        let total = self.message_count as f64;
+        (1.0 + (total - document_frequency + 0.5) / (document_frequency + 0.5)).ln()
+    }
+
+    fn average_length(&self) -> f64 {
+        if self.message_count == 0 {
+            0.0
+        } else {
+            self.total_length as f64 / self.message_count as f64
+        }
+    }
+}
+
+impl IndexedMessage {
+    fn new(message: &ExportableMessage) -> Self {
+        Self {
+            id: message.id.clone(),
+            message_type: message.message_type.clone(),
+            original_content: message.content.clone(),
+            timestamp: message.timestamp,
+            author: message.author.clone(),
+            length: tokenize(&message.content).len(),
+        }
+    }
+}
+
+/// Lowercased search terms in `text` with their byte ranges
+fn tokenize(text: &str) -> Vec<(String, Range<usize>)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(token_start)) => {
+                let token = &text[token_start..i];
+                if token.chars().count() > 1 {
+                    tokens.push((token.to_lowercase(), token_start..i));
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+/// Distinct lowercased terms of a query
+fn query_terms(text: &str) -> Vec<String> {
+    let mut terms: Vec<String> = tokenize(text).into_iter().map(|(term, _)| term).collect();
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
+/// Byte ranges where the tokens of `phrase` occur contiguously in `text`
+fn phrase_positions(text: &str, phrase: &[String]) -> Vec<HighlightPosition> {
+    if phrase.is_empty() {
+        return Vec::new();
+    }
+    let tokens = tokenize(text);
+    tokens
+        .windows(phrase.len())
+        .filter(|window| window.iter().zip(phrase).all(|((term, _), p)| term == p))
+        .map(|window| {
+            let range = window[0].1.start..window[window.len() - 1].1.end;
+            HighlightPosition {
+                term: text[range.clone()].to_string(),
+                start: range.start,
+                end: range.end,
+            }
+        })
+        .collect()
+}
+
+/// Compile a regex query with limits that keep hostile patterns cheap
+fn compile_search_regex(pattern: &str) -> Result<Regex> {
+    if pattern.len() > MAX_REGEX_PATTERN_LEN {
+        return Err(LutsError::InvalidQuery(format!(
+            "regex pattern is {} bytes, limit is {}",
+            pattern.len(),
+            MAX_REGEX_PATTERN_LEN
+        ))
+        .into());
+    }
+    RegexBuilder::new(pattern)
+        .multi_line(true)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .dfa_size_limit(REGEX_SIZE_LIMIT)
+        .build()
+        .map_err(|e| LutsError::InvalidQuery(format!("invalid regex {:?}: {}", pattern, e)).into())
 }
 
 impl ConversationSearchEngine {
@@ -562,6 +738,7 @@ impl ConversationSearchEngine {
                 success_rate: 0.0,
                 active_users: Vec::new(),
                 hourly_patterns: vec![0; 24],
+                mode_usage: Vec::new(),
             }),
             search_index: RwLock::new(SearchIndex::default()),
             config: RwLock::new(SearchConfig::default()),
@@ -644,57 +821,81 @@ impl ConversationSearchEngine {
         Ok((results, summary))
     }
 
-    /// Index a conversation for searching
+    /// Index a conversation for searching, replacing any earlier version of it
     pub async fn index_conversation(
         &self,
         conversation: &ExportableConversation,
     ) -> Result<()> {
         let mut search_index = self.search_index.write().await;
-        
-        let mut indexed_messages = Vec::new();
-        let mut terms = HashMap::new();
-
-        for (msg_idx, message) in conversation.messages.iter().enumerate() {
-            let indexed_message = IndexedMessage {
-                id: message.id.clone(),
-                message_type: message.message_type.clone(),
-                content: message.content.to_lowercase(),
-                original_content: message.content.clone(),
-                timestamp: message.timestamp,
-                author: message.author.clone(),
-            };
+        let conversation_id = &conversation.metadata.id;
+        search_index.remove_conversation(conversation_id);
+
+        let messages: Vec<IndexedMessage> = conversation
+            .messages
+            .iter()
+            .map(IndexedMessage::new)
+            .collect();
+        for (message_index, message) in messages.iter().enumerate() {
+            search_index.add_message(conversation_id, message_index, message);
+        }
 
-            // Extract and index terms
-            let words: Vec<&str> = indexed_message.content.split_whitespace().collect();
-            for (pos, word) in words.iter().enumerate() {
-                let term = word.trim_matches(|c: char| !c.is_alphanumeric()).to_string();
-                if !term.is_empty() && term.len() > 2 {
-                    terms.entry(term.clone())
-                        .or_insert_with(Vec::new)
-                        .push(TermPosition {
-                            message_index: msg_idx,
-                            position: pos,
-                            frequency: 1,
-                        });
-                    
-                    // Update global term frequency
-                    *search_index.term_frequencies.entry(term).or_insert(0) += 1;
-                }
-            }
+        search_index.conversations.insert(
+            conversation_id.clone(),
+            ConversationIndex {
+                metadata: conversation.metadata.clone(),
+                messages,
+            },
+        );
+        search_index.last_updated = Some(Utc::now());
 
-            indexed_messages.push(indexed_message);
-        }
+        info!("Indexed conversation: {}", conversation_id);
+        Ok(())
+    }
 
-        let conversation_index = ConversationIndex {
-            metadata: conversation.metadata.clone(),
-            terms,
-            messages: indexed_messages,
-        };
+    /// Add a new message to an indexed conversation
+    pub async fn add_message(&self, conversation_id: &str, message: &ExportableMessage) -> Result<()> {
+        let mut search_index = self.search_index.write().await;
+        let indexed = IndexedMessage::new(message);
+
+        let conversation = search_index
+            .conversations
+            .get_mut(conversation_id)
+            .ok_or_else(|| anyhow::anyhow!("Conversation {} is not indexed", conversation_id))?;
+        let message_index = conversation.messages.len();
+        conversation.metadata.message_count += 1;
+        conversation.metadata.last_message_at =
+            conversation.metadata.last_message_at.max(message.timestamp);
+
+        search_index.add_message(conversation_id, message_index, &indexed);
+        search_index
+            .conversations
+            .get_mut(conversation_id)
+            .expect("conversation was found above")
+            .messages
+            .push(indexed);
+        search_index.last_updated = Some(Utc::now());
+        Ok(())
+    }
 
-        search_index.conversations.insert(conversation.metadata.id.clone(), conversation_index);
+    /// Rebuild the postings and statistics from the indexed messages
+    pub async fn rebuild_index(&self) -> Result<()> {
+        let mut search_index = self.search_index.write().await;
+        let conversations = std::mem::take(&mut search_index.conversations);
+        *search_index = SearchIndex::default();
+
+        for (conversation_id, conversation) in &conversations {
+            for (message_index, message) in conversation.messages.iter().enumerate() {
+                search_index.add_message(conversation_id, message_index, message);
+            }
+        }
+        search_index.conversations = conversations;
         search_index.last_updated = Some(Utc::now());
 
-        info!("Indexed conversation: {}", conversation.metadata.id);
+        info!(
+            "Rebuilt search index: {} messages, {} terms",
+            search_index.message_count,
+            search_index.postings.len()
+        );
         Ok(())
     }
 
@@ -750,65 +951,228 @@ impl ConversationSearchEngine {
 
     // Private helper methods
 
+    /// Match messages against `text_query` in the query's [`SearchMode`]
+    ///
+    /// Message-level filters (date range, sender, message type) are applied
+    /// before scoring. Substring and phrase matches are ranked with BM25,
+    /// regex matches by match count. Conversations are ranked by their best
+    /// message.
     async fn perform_text_search(
         &self,
         text_query: &str,
         query: &ConversationSearchQuery,
         search_index: &SearchIndex,
     ) -> Result<Vec<ConversationSearchResult>> {
-        let mut results = Vec::new();
-        let query_terms: Vec<&str> = text_query.split_whitespace().collect();
-
-        for (_conv_id, conv_index) in &search_index.conversations {
-            let mut relevance_score = 0.0;
-            let highlights = Vec::new();
-            let mut matching_messages = Vec::new();
-
-            // Calculate relevance based on term matches
-            for term in &query_terms {
-                let term_lower = term.to_lowercase();
-                if let Some(positions) = conv_index.terms.get(&term_lower) {
-                    relevance_score += positions.len() as f64 * 0.1;
-                    
-                    // Create highlights and matching messages
-                    for position in positions {
-                        if let Some(message) = conv_index.messages.get(position.message_index) {
-                            matching_messages.push(MessageMatch {
-                                message_id: message.id.clone(),
-                                message_type: message.message_type.clone(),
-                                timestamp: message.timestamp,
-                                snippet: self.create_snippet(&message.original_content, term, 100),
-                                score: 0.5, // Simplified scoring
-                            });
+        let mut term_scores: HashMap<String, f64> = HashMap::new();
+        let (message_matches, query_analysis): (Vec<MessageHit>, String) = match query.mode {
+            SearchMode::Substring => {
+                let terms = query_terms(text_query);
+                let scores = self.bm25_scores(&terms, query, search_index, &mut term_scores);
+                let term_set: HashSet<&str> = terms.iter().map(String::as_str).collect();
+                let matches = scores
+                    .into_iter()
+                    .map(|(message_ref, score)| {
+                        let message = search_index.message(&message_ref);
+                        let positions: Vec<HighlightPosition> = tokenize(&message.original_content)
+                            .into_iter()
+                            .filter(|(term, _)| term_set.contains(term.as_str()))
+                            .map(|(term, range)| HighlightPosition {
+                                start: range.start,
+                                end: range.end,
+                                term,
+                            })
+                            .collect();
+                        (message_ref, score, positions)
+                    })
+                    .collect();
+                (matches, format!("BM25 for terms: {}", terms.join(", ")))
+            }
+            SearchMode::Phrase => {
+                let phrase: Vec<String> = tokenize(text_query.trim().trim_matches('"'))
+                    .into_iter()
+                    .map(|(term, _)| term)
+                    .collect();
+                let mut terms = phrase.clone();
+                terms.sort();
+                terms.dedup();
+                let scores = self.bm25_scores(&terms, query, search_index, &mut term_scores);
+                let matches = scores
+                    .into_iter()
+                    .filter_map(|(message_ref, score)| {
+                        let message = search_index.message(&message_ref);
+                        let positions = phrase_positions(&message.original_content, &phrase);
+                        (!positions.is_empty()).then_some((message_ref, score, positions))
+                    })
+                    .collect();
+                (matches, format!("BM25 for phrase: \"{}\"", phrase.join(" ")))
+            }
+            SearchMode::Regex => {
+                let regex = compile_search_regex(text_query)?;
+                let mut matches = Vec::new();
+                for (conversation_id, conversation) in &search_index.conversations {
+                    for (message_index, message) in conversation.messages.iter().enumerate() {
+                        if !self.message_matches_filters(message, &query.filters) {
+                            continue;
+                        }
+                        let positions: Vec<HighlightPosition> = regex
+                            .find_iter(&message.original_content)
+                            .filter(|m| !m.is_empty())
+                            .map(|m| HighlightPosition {
+                                start: m.start(),
+                                end: m.end(),
+                                term: m.as_str().to_string(),
+                            })
+                            .collect();
+                        if positions.is_empty() {
+                            continue;
                         }
+                        let message_ref = MessageRef {
+                            conversation_id: conversation_id.clone(),
+                            message_index,
+                        };
+                        matches.push((message_ref, positions.len() as f64, positions));
                     }
                 }
+                (matches, format!("Regex: {}", regex.as_str()))
             }
+        };
+
+        let mut by_conversation: HashMap<&str, Vec<ConversationHit>> = HashMap::new();
+        for (message_ref, score, positions) in message_matches {
+            let (conversation_id, _) = search_index
+                .conversations
+                .get_key_value(&message_ref.conversation_id)
+                .expect("matches come from indexed conversations");
+            by_conversation
+                .entry(conversation_id.as_str())
+                .or_default()
+                .push((message_ref.message_index, score, positions));
+        }
 
-            if relevance_score > 0.0 {
-                results.push(ConversationSearchResult {
-                    conversation: conv_index.metadata.clone(),
-                    relevance_score: relevance_score.min(1.0),
-                    highlights,
-                    explanation: if query.explain { 
-                        Some(SearchExplanation {
-                            query_analysis: format!("Matched {} terms", query_terms.len()),
-                            filters_applied: Vec::new(),
-                            score_breakdown: HashMap::new(),
-                            processing_time_ms: 0,
-                        })
-                    } else { 
-                        None 
-                    },
-                    matching_messages,
-                    matching_blocks: Vec::new(),
+        let mut results = Vec::new();
+        for (conversation_id, mut scored) in by_conversation {
+            let conversation = &search_index.conversations[conversation_id];
+            scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+            let best_score = scored[0].1;
+
+            let mut matching_messages = Vec::new();
+            let mut highlights = Vec::new();
+            for (message_index, score, positions) in scored {
+                let message = &conversation.messages[message_index];
+                let first_match = positions.first().map(|p| p.start).unwrap_or(0);
+                matching_messages.push(MessageMatch {
+                    message_id: message.id.clone(),
+                    message_type: message.message_type.clone(),
+                    timestamp: message.timestamp,
+                    snippet: self.create_snippet(&message.original_content, first_match, 100),
+                    score,
                 });
+                if query.include_highlights {
+                    highlights.push(SearchHighlight {
+                        field: format!("messages.{}", message.id),
+                        highlighted_text: highlight_text(&message.original_content, &positions),
+                        positions,
+                    });
+                }
             }
+
+            results.push(ConversationSearchResult {
+                conversation: conversation.metadata.clone(),
+                relevance_score: best_score,
+                highlights,
+                explanation: if query.explain {
+                    Some(SearchExplanation {
+                        query_analysis: format!(
+                            "{} over {} messages",
+                            query_analysis, search_index.message_count
+                        ),
+                        filters_applied: Vec::new(),
+                        score_breakdown: term_scores.clone(),
+                        processing_time_ms: 0,
+                    })
+                } else {
+                    None
+                },
+                matching_messages,
+                matching_blocks: Vec::new(),
+            });
         }
 
+        // Scale scores so the best conversation is 1.0
+        let top_score = results
+            .iter()
+            .map(|result| result.relevance_score)
+            .fold(0.0, f64::max);
+        if top_score > 0.0 {
+            for result in &mut results {
+                result.relevance_score /= top_score;
+            }
+        }
+
+        debug!(
+            "{:?} search for {:?} matched {} conversations",
+            query.mode,
+            text_query,
+            results.len()
+        );
         Ok(results)
     }
 
+    /// BM25 score of each message containing any of `terms`
+    ///
+    /// Messages that fail the filters are skipped; per-term totals are added
+    /// to `term_scores` for explanations.
+    fn bm25_scores(
+        &self,
+        terms: &[String],
+        query: &ConversationSearchQuery,
+        search_index: &SearchIndex,
+        term_scores: &mut HashMap<String, f64>,
+    ) -> HashMap<MessageRef, f64> {
+        let average_length = search_index.average_length();
+        let mut message_scores: HashMap<MessageRef, f64> = HashMap::new();
+        for term in terms {
+            let Some(postings) = search_index.postings.get(term) else {
+                continue;
+            };
+            let idf = search_index.idf(term);
+            for (message_ref, &frequency) in postings {
+                let message = search_index.message(message_ref);
+                if !self.message_matches_filters(message, &query.filters) {
+                    continue;
+                }
+
+                let frequency = frequency as f64;
+                let length_norm =
+                    1.0 - BM25_B + BM25_B * message.length as f64 / average_length.max(1.0);
+                let score = idf * frequency * (BM25_K1 + 1.0) / (frequency + BM25_K1 * length_norm);
+                *message_scores.entry(message_ref.clone()).or_insert(0.0) += score;
+                *term_scores.entry(term.clone()).or_insert(0.0) += score;
+            }
+        }
+        message_scores
+    }
+
+    /// Whether a message passes the message-level filters
+    fn message_matches_filters(&self, message: &IndexedMessage, filters: &SearchFilters) -> bool {
+        if let Some(ref date_range) = filters.date_range
+            && !self.matches_date_range(&message.timestamp, date_range)
+        {
+            return false;
+        }
+        if let Some(ref participants) = filters.participants
+            && !participants.iter().any(|p| p.eq_ignore_ascii_case(&message.author))
+        {
+            return false;
+        }
+        if let Some(ref message_types) = filters.message_types
+            && !message_types.contains(&message.message_type)
+        {
+            return false;
+        }
+        true
+    }
+
     async fn apply_filters(
         &self,
         mut results: Vec<ConversationSearchResult>,
@@ -819,10 +1183,8 @@ impl ConversationSearchEngine {
             results.retain(|r| user_ids.contains(&r.conversation.user_id));
         }
 
-        // Apply date range filter
-        if let Some(ref date_range) = filters.date_range {
-            results.retain(|r| self.matches_date_range(&r.conversation.started_at, date_range));
-        }
+        // Date range, participants and message types are applied per message
+        // before ranking, in `perform_text_search`
 
         // Apply message count filter
         if let Some(ref msg_range) = filters.message_count_range {
@@ -896,6 +1258,11 @@ impl ConversationSearchEngine {
 
         // Extract and track query terms
         if let Some(ref text_query) = query.text_query {
+            match analytics.mode_usage.iter_mut().find(|(mode, _)| *mode == query.mode) {
+                Some((_, count)) => *count += 1,
+                None => analytics.mode_usage.push((query.mode, 1)),
+            }
+
             for term in text_query.split_whitespace() {
                 let entry = analytics.popular_terms.iter_mut()
                     .find(|(t, _)| t == term);
@@ -1012,24 +1379,215 @@ impl ConversationSearchEngine {
         true
     }
 
-    fn create_snippet(&self, content: &str, term: &str, max_length: usize) -> String {
-        let term_lower = term.to_lowercase();
-        let content_lower = content.to_lowercase();
-        
-        if let Some(pos) = content_lower.find(&term_lower) {
-            let start = pos.saturating_sub(max_length / 2);
-            let end = (pos + term.len() + max_length / 2).min(content.len());
-            
-            let mut snippet = content[start..end].to_string();
-            if start > 0 {
-                snippet = format!("...{}", snippet);
-            }
-            if end < content.len() {
-                snippet = format!("{}...", snippet);
-            }
-            snippet
-        } else {
-            content.chars().take(max_length).collect()
+    /// Snippet of roughly `max_length` bytes around the byte offset `position`
+    fn create_snippet(&self, content: &str, position: usize, max_length: usize) -> String {
+        if content.len() <= max_length {
+            return content.to_string();
+        }
+
+        let mut start = position.saturating_sub(max_length / 2);
+        while !content.is_char_boundary(start) {
+            start -= 1;
+        }
+        let mut end = (start + max_length).min(content.len());
+        while !content.is_char_boundary(end) {
+            end += 1;
+        }
+
+        let mut snippet = content[start..end].to_string();
+        if start > 0 {
+            snippet = format!("...{}", snippet);
+        }
+        if end < content.len() {
+            snippet = format!("{}...", snippet);
+        }
+        snippet
+    }
+}
+
+/// `text` with each highlighted byte range wrapped in `**`
+fn highlight_text(text: &str, positions: &[HighlightPosition]) -> String {
+    let mut highlighted = String::with_capacity(text.len() + positions.len() * 4);
+    let mut last = 0;
+    for position in positions {
+        highlighted.push_str(&text[last..position.start]);
+        highlighted.push_str("**");
+        highlighted.push_str(&text[position.start..position.end]);
+        highlighted.push_str("**");
+        last = position.end;
+    }
+    highlighted.push_str(&text[last..]);
+    highlighted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversation::test_fixtures::{conversation, message};
+
+    fn text_query(text: &str) -> ConversationSearchQuery {
+        ConversationSearchQuery {
+            text_query: Some(text.to_string()),
+            include_highlights: true,
+            ..Default::default()
         }
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_bm25_ranks_messages_with_both_terms_first() {
+        let engine = ConversationSearchEngine::new();
+        engine
+            .index_conversation(&conversation(
+                "rust",
+                vec![
+                    message("m0", MessageType::User, "user", "How do I write a tokio runtime?"),
+                    message("m1", MessageType::Assistant, "assistant", "Rust async code runs on an executor."),
+                    message("m2", MessageType::User, "user", "Is Rust hard to learn?"),
+                ],
+            ))
+            .await
+            .unwrap();
+        engine
+            .index_conversation(&conversation(
+                "cooking",
+                vec![message("m0", MessageType::User, "user", "Pasta sauce tips please")],
+            ))
+            .await
+            .unwrap();
+
+        let (results, _) = engine
+            .search_conversations(text_query("rust async"))
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        let matches = &results[0].matching_messages;
+        assert_eq!(matches[0].message_id, "m1");
+        assert_eq!(matches[1].message_id, "m2");
+        assert!(matches[0].score > matches[1].score);
+        assert_eq!(results[0].relevance_score, 1.0);
+
+        // Highlights are byte ranges of the matched terms in the original text
+        let highlight = &results[0].highlights[0];
+        let content = "Rust async code runs on an executor.";
+        let matched: Vec<&str> = highlight
+            .positions
+            .iter()
+            .map(|p| &content[p.start..p.end])
+            .collect();
+        assert_eq!(matched, vec!["Rust", "async"]);
+        assert_eq!(highlight.highlighted_text, "**Rust** **async** code runs on an executor.");
+    }
+
+    #[tokio::test]
+    async fn test_filters_apply_before_ranking_and_index_updates_incrementally() {
+        let engine = ConversationSearchEngine::new();
+        engine
+            .index_conversation(&conversation(
+                "c1",
+                vec![message("m0", MessageType::Assistant, "assistant", "Rust async executors")],
+            ))
+            .await
+            .unwrap();
+
+        let mut query = text_query("rust async");
+        query.filters.message_types = Some(vec![MessageType::User]);
+        let (results, _) = engine.search_conversations(query.clone()).await.unwrap();
+        assert!(results.is_empty());
+
+        engine
+            .add_message("c1", &message("m1", MessageType::User, "user", "Explain rust please"))
+            .await
+            .unwrap();
+        let (results, _) = engine.search_conversations(query.clone()).await.unwrap();
+        assert_eq!(results[0].matching_messages.len(), 1);
+        assert_eq!(results[0].matching_messages[0].message_id, "m1");
+        assert_eq!(results[0].conversation.message_count, 2);
+
+        engine.rebuild_index().await.unwrap();
+        let (rebuilt, _) = engine.search_conversations(query).await.unwrap();
+        assert_eq!(rebuilt[0].matching_messages[0].message_id, "m1");
+        assert!((rebuilt[0].matching_messages[0].score - results[0].matching_messages[0].score).abs() < 1e-9);
+
+        assert!(engine
+            .add_message("missing", &message("m0", MessageType::User, "user", "hi"))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_phrase_requires_contiguous_tokens() {
+        let engine = ConversationSearchEngine::new();
+        engine
+            .index_conversation(&conversation(
+                "tools",
+                vec![
+                    message("m0", MessageType::Assistant, "assistant", "The call to the tool failed."),
+                    message("m1", MessageType::Assistant, "assistant", "Retrying the tool call now."),
+                ],
+            ))
+            .await
+            .unwrap();
+
+        let mut query = text_query("\"tool call\"");
+        query.mode = SearchMode::Phrase;
+        let (results, _) = engine.search_conversations(query).await.unwrap();
+        let matches = &results[0].matching_messages;
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].message_id, "m1");
+        let position = &results[0].highlights[0].positions[0];
+        assert_eq!(position.term, "tool call");
+        assert_eq!(&"Retrying the tool call now."[position.start..position.end], "tool call");
+    }
+
+    #[tokio::test]
+    async fn test_regex_mode_and_invalid_patterns() {
+        let engine = ConversationSearchEngine::new();
+        engine
+            .index_conversation(&conversation(
+                "tools",
+                vec![
+                    message("m0", MessageType::Tool, "tool", "calculator(2 + 2)\nweb_search(rust)"),
+                    message("m1", MessageType::User, "user", "please use the calculator"),
+                ],
+            ))
+            .await
+            .unwrap();
+
+        let mut query = text_query(r"^\w+\(");
+        query.mode = SearchMode::Regex;
+        let (results, _) = engine.search_conversations(query.clone()).await.unwrap();
+        let matches = &results[0].matching_messages;
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].message_id, "m0");
+        assert_eq!(matches[0].score, 2.0);
+
+        query.text_query = Some("(unclosed".to_string());
+        let err = engine.search_conversations(query.clone()).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<LutsError>(), Some(LutsError::InvalidQuery(_))));
+
+        query.text_query = Some("a".repeat(MAX_REGEX_PATTERN_LEN + 1));
+        let err = engine.search_conversations(query).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<LutsError>(), Some(LutsError::InvalidQuery(_))));
+    }
+
+    #[tokio::test]
+    async fn test_saved_search_round_trips_mode() {
+        let engine = ConversationSearchEngine::new();
+        let mut query = text_query("tool call");
+        query.mode = SearchMode::Phrase;
+        engine.search_conversations(query.clone()).await.unwrap();
+        assert_eq!(
+            engine.get_search_analytics().await.mode_usage,
+            vec![(SearchMode::Phrase, 1)]
+        );
+
+        let id = engine
+            .save_search("phrase".to_string(), None, query, Vec::new())
+            .await
+            .unwrap();
+        let saved = engine.load_saved_search(&id).await.unwrap();
+        let restored: SavedSearch =
+            serde_json::from_str(&serde_json::to_string(&saved).unwrap()).unwrap();
+        assert_eq!(restored.query.mode, SearchMode::Phrase);
+    }
+}
@@ -3,7 +3,7 @@
 //! This module provides comprehensive segment-level editing capabilities for conversations,
 //! including message editing, deletion, reordering, and batch operations with undo/redo support.
 
-use crate::llm::InternalChatMessage;
+use crate::llm::{InternalChatMessage, SYSTEM_NOTE_PREFIX};
 use luts_memory::MemoryManager;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
@@ -65,6 +65,16 @@ pub enum SegmentType {
     Custom(String),
 }
 
+/// Custom segment type name used for system notes
+pub const SYSTEM_NOTE_SEGMENT: &str = "system_note";
+
+impl ConversationSegment {
+    /// Whether this segment is a system note
+    pub fn is_system_note(&self) -> bool {
+        matches!(&self.segment_type, SegmentType::Custom(name) if name == SYSTEM_NOTE_SEGMENT)
+    }
+}
+
 /// Segment metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SegmentMetadata {
@@ -88,8 +98,8 @@ pub struct SegmentMetadata {
     pub custom: HashMap<String, serde_json::Value>,
 }
 
-/// Importance levels for segments
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Importance levels for segments, least important first
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum ImportanceLevel {
     Low,
     Normal,
@@ -604,13 +614,75 @@ impl ConversationSegmentEditor {
         Ok(segment_id)
     }
 
-    /// Merge multiple segments into one
+    /// Insert a system note into the conversation
+    ///
+    /// The note is stored as a `system_note` segment and is sent to the model as a
+    /// system message on subsequent turns. Appends to the end when no position is given.
+    pub async fn insert_system_note(&self, note: String, position: Option<usize>) -> Result<String> {
+        let segment_id = self
+            .create_segment(
+                SegmentType::Custom(SYSTEM_NOTE_SEGMENT.to_string()),
+                note,
+                "System".to_string(),
+                position,
+            )
+            .await?;
+
+        if let Some(segment) = self.segments.write().await.iter_mut().find(|s| s.id == segment_id) {
+            segment.tags.push(SYSTEM_NOTE_SEGMENT.to_string());
+        }
+
+        Ok(segment_id)
+    }
+
+    /// Assemble the segments into the messages sent to the model
+    ///
+    /// Annotation-only segments (notes, code blocks, media, other custom types)
+    /// are not part of the prompt and are skipped.
+    pub async fn to_messages(&self) -> Vec<InternalChatMessage> {
+        self.segments
+            .read()
+            .await
+            .iter()
+            .filter_map(|segment| match &segment.segment_type {
+                SegmentType::UserMessage => Some(InternalChatMessage::User {
+                    content: segment.content.clone(),
+                }),
+                SegmentType::AssistantMessage => Some(InternalChatMessage::Assistant {
+                    content: segment.content.clone(),
+                    tool_responses: None,
+                }),
+                SegmentType::SystemMessage => Some(InternalChatMessage::System {
+                    content: segment.content.clone(),
+                }),
+                SegmentType::ToolMessage => Some(InternalChatMessage::Tool {
+                    tool_name: segment
+                        .author
+                        .strip_prefix("Tool(")
+                        .and_then(|name| name.strip_suffix(')'))
+                        .unwrap_or(&segment.author)
+                        .to_string(),
+                    content: segment.content.clone(),
+                    call_id: None,
+                }),
+                _ if segment.is_system_note() => {
+                    Some(InternalChatMessage::system_note(&segment.content))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Merge adjacent segments into one
+    ///
+    /// The segments must be contiguous and of the same type. The merged
+    /// segment keeps the highest importance of its parts.
     pub async fn merge_segments(
         &self,
         segment_ids: Vec<String>,
         separator: Option<String>,
         editor: String,
-    ) -> Result<String> {
+    ) -> Result<ConversationSegment> {
         if segment_ids.len() < 2 {
             return Err(anyhow::anyhow!("Need at least 2 segments to merge"));
         }
@@ -624,20 +696,40 @@ impl ConversationSegmentEditor {
 
         // Collect segments to merge and their indices
         for segment_id in &segment_ids {
-            if let Some(index) = segments.iter().position(|s| s.id == *segment_id) {
+            let index = segments
+                .iter()
+                .position(|s| s.id == *segment_id)
+                .ok_or_else(|| anyhow::anyhow!("Segment not found: {}", segment_id))?;
+            if !indices_to_remove.contains(&index) {
                 segments_to_merge.push(segments[index].clone());
                 indices_to_remove.push(index);
             }
         }
 
-        if segments_to_merge.is_empty() {
-            return Err(anyhow::anyhow!("No valid segments found to merge"));
-        }
-
         // Sort by position to maintain order
         segments_to_merge.sort_by_key(|s| s.position);
         indices_to_remove.sort_by(|a, b| b.cmp(a)); // Sort in reverse for removal
 
+        if indices_to_remove[0] - indices_to_remove[indices_to_remove.len() - 1] + 1
+            != indices_to_remove.len()
+        {
+            return Err(anyhow::anyhow!("Only adjacent segments can be merged"));
+        }
+        let segment_type = &segments_to_merge[0].segment_type;
+        if let Some(other) = segments_to_merge.iter().find(|s| s.segment_type != *segment_type) {
+            return Err(anyhow::anyhow!(
+                "Cannot merge {:?} segment {} into {:?} segments",
+                other.segment_type,
+                other.id,
+                segment_type
+            ));
+        }
+        let importance = segments_to_merge
+            .iter()
+            .map(|s| s.metadata.importance.clone())
+            .max()
+            .unwrap_or_default();
+
         // Create merged content
         let merged_content = segments_to_merge
             .iter()
@@ -662,7 +754,7 @@ impl ConversationSegmentEditor {
                 confidence: None,
                 is_bookmarked: false,
                 is_highlighted: false,
-                importance: ImportanceLevel::default(),
+                importance,
                 custom: HashMap::new(),
             },
             created_at: Utc::now(),
@@ -703,7 +795,7 @@ impl ConversationSegmentEditor {
         }).await;
 
         info!("Merged segments into {} by {}", merged_segment_id, editor);
-        Ok(merged_segment_id)
+        Ok(merged_segment)
     }
 
     /// Split a segment into multiple segments
@@ -717,16 +809,20 @@ impl ConversationSegmentEditor {
 
         let mut segments = self.segments.write().await;
         if let Some(index) = segments.iter().position(|s| s.id == segment_id) {
-            let original_segment = segments.remove(index);
-            let content = &original_segment.content;
-            
-            // Validate split points
+            // Validate split points before taking the segment out
+            let content = &segments[index].content;
             for &point in &split_points {
                 if point >= content.len() {
                     return Err(anyhow::anyhow!("Split point {} is beyond content length {}", point, content.len()));
                 }
+                if !content.is_char_boundary(point) {
+                    return Err(anyhow::anyhow!("Split point {} is inside a character", point));
+                }
             }
 
+            let original_segment = segments.remove(index);
+            let content = &original_segment.content;
+
             // Sort split points
             let mut sorted_points = split_points;
             sorted_points.sort_unstable();
@@ -836,6 +932,51 @@ impl ConversationSegmentEditor {
         }
     }
 
+    /// Split a segment in two at a character offset
+    ///
+    /// Both halves keep the segment's type, importance and tags. Fails if
+    /// either half would be blank.
+    pub async fn split_segment_at(
+        &self,
+        segment_id: &str,
+        at_char: usize,
+        editor: String,
+    ) -> Result<(ConversationSegment, ConversationSegment)> {
+        let segment = self
+            .get_segment(segment_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Segment not found: {}", segment_id))?;
+        let split_at = segment
+            .content
+            .char_indices()
+            .nth(at_char)
+            .map(|(byte, _)| byte)
+            .filter(|&byte| byte > 0)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Split offset {} must fall inside segment {} ({} characters)",
+                    at_char,
+                    segment_id,
+                    segment.content.chars().count()
+                )
+            })?;
+        let (head, tail) = segment.content.split_at(split_at);
+        if head.trim().is_empty() || tail.trim().is_empty() {
+            return Err(anyhow::anyhow!(
+                "Splitting segment {} at {} would leave a blank half",
+                segment_id,
+                at_char
+            ));
+        }
+
+        let new_ids = self.split_segment(segment_id, vec![split_at], editor).await?;
+        let first = self.get_segment(&new_ids[0]).await;
+        let second = self.get_segment(&new_ids[1]).await;
+        first
+            .zip(second)
+            .ok_or_else(|| anyhow::anyhow!("Split halves of segment {} went missing", segment_id))
+    }
+
     /// Undo the last operation
     pub async fn undo(&self) -> Result<Option<UndoRedoOperation>> {
         let mut undo_stack = self.undo_stack.write().await;
@@ -925,7 +1066,10 @@ impl ConversationSegmentEditor {
         let (segment_type, content, author) = match message {
             InternalChatMessage::User { content } => (SegmentType::UserMessage, content, "User".to_string()),
             InternalChatMessage::Assistant { content, .. } => (SegmentType::AssistantMessage, content, "Assistant".to_string()),
-            InternalChatMessage::System { content } => (SegmentType::SystemMessage, content, "System".to_string()),
+            InternalChatMessage::System { content } => match content.strip_prefix(SYSTEM_NOTE_PREFIX) {
+                Some(note) => (SegmentType::Custom(SYSTEM_NOTE_SEGMENT.to_string()), note.to_string(), "System".to_string()),
+                None => (SegmentType::SystemMessage, content, "System".to_string()),
+            },
             InternalChatMessage::Tool { tool_name, content, .. } => (SegmentType::ToolMessage, content, format!("Tool({})", tool_name)),
         };
 
@@ -1047,4 +1191,131 @@ impl ConversationSegmentEditor {
             listener.on_segment_created(segment);
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_system_note_shapes_next_prompt() {
+        let editor = ConversationSegmentEditor::new();
+        editor
+            .load_conversation(vec![
+                InternalChatMessage::User { content: "Hi there".to_string() },
+                InternalChatMessage::Assistant {
+                    content: "Hello! How can I help?".to_string(),
+                    tool_responses: None,
+                },
+            ])
+            .await
+            .unwrap();
+
+        let note_id = editor
+            .insert_system_note("Keep answers under two sentences".to_string(), None)
+            .await
+            .unwrap();
+
+        let segment = editor.get_segment(&note_id).await.unwrap();
+        assert!(segment.is_system_note());
+        assert_eq!(segment.segment_type, SegmentType::Custom(SYSTEM_NOTE_SEGMENT.to_string()));
+
+        let messages = editor.to_messages().await;
+        assert_eq!(messages.len(), 3);
+        let note = messages.last().unwrap();
+        assert!(note.is_system_note());
+        assert!(matches!(
+            note,
+            InternalChatMessage::System { content } if content.ends_with("Keep answers under two sentences")
+        ));
+        assert!(!messages.iter().any(|m| matches!(
+            m,
+            InternalChatMessage::User { content } if content.contains("two sentences")
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_system_note_round_trips_through_messages() {
+        let editor = ConversationSegmentEditor::new();
+        editor
+            .load_conversation(vec![InternalChatMessage::system_note("Be formal")])
+            .await
+            .unwrap();
+
+        let segments = editor.get_segments().await;
+        assert!(segments[0].is_system_note());
+        assert_eq!(segments[0].content, "Be formal");
+    }
+
+    #[tokio::test]
+    async fn test_undo_merge_restores_original_segments() {
+        let editor = ConversationSegmentEditor::new();
+        editor
+            .load_conversation(vec![
+                InternalChatMessage::User { content: "First part".to_string() },
+                InternalChatMessage::User { content: "second part".to_string() },
+                InternalChatMessage::Assistant {
+                    content: "Reply".to_string(),
+                    tool_responses: None,
+                },
+            ])
+            .await
+            .unwrap();
+        let original = editor.get_segments().await;
+        let ids: Vec<String> = original.iter().map(|s| s.id.clone()).collect();
+
+        // Mixed types and gaps are rejected
+        assert!(editor
+            .merge_segments(vec![ids[1].clone(), ids[2].clone()], None, "me".to_string())
+            .await
+            .is_err());
+        assert!(editor
+            .merge_segments(vec![ids[0].clone(), ids[2].clone()], None, "me".to_string())
+            .await
+            .is_err());
+        assert_eq!(editor.get_segments().await.len(), 3);
+
+        editor.segments.write().await[1].metadata.importance = ImportanceLevel::High;
+        let merged = editor
+            .merge_segments(
+                vec![ids[0].clone(), ids[1].clone()],
+                Some(" ".to_string()),
+                "me".to_string(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(merged.content, "First part second part");
+        assert_eq!(merged.metadata.importance, ImportanceLevel::High);
+        assert_eq!(editor.get_segments().await.len(), 2);
+
+        editor.undo().await.unwrap().unwrap();
+        let restored = editor.get_segments().await;
+        let restored_ids: Vec<&str> = restored.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(restored_ids, vec![ids[0].as_str(), ids[1].as_str(), ids[2].as_str()]);
+        assert_eq!(restored[0].content, "First part");
+        assert_eq!(restored[1].content, "second part");
+    }
+
+    #[tokio::test]
+    async fn test_split_at_char_keeps_importance() {
+        let editor = ConversationSegmentEditor::new();
+        editor
+            .load_conversation(vec![InternalChatMessage::User {
+                content: "Café au lait. Then the bill.".to_string(),
+            }])
+            .await
+            .unwrap();
+        let id = editor.get_segments().await[0].id.clone();
+        editor.segments.write().await[0].metadata.importance = ImportanceLevel::Critical;
+
+        assert!(editor.split_segment_at(&id, 0, "me".to_string()).await.is_err());
+        let (first, second) = editor.split_segment_at(&id, 13, "me".to_string()).await.unwrap();
+        assert_eq!(first.content, "Café au lait.");
+        assert_eq!(second.content, " Then the bill.");
+        assert_eq!(first.metadata.importance, ImportanceLevel::Critical);
+        assert_eq!(second.metadata.importance, ImportanceLevel::Critical);
+
+        editor.undo().await.unwrap().unwrap();
+        assert_eq!(editor.get_segments().await[0].content, "Café au lait. Then the bill.");
+    }
+}
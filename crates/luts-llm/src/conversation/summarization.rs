@@ -3,15 +3,22 @@
 //! This module provides intelligent conversation summarization capabilities,
 //! automatically condensing long conversations while preserving key context.
 
-use crate::llm::{AiService, InternalChatMessage};
+use crate::conversation::export::{ExportableMessage, MessageType};
+use crate::conversation::language::{dominant_language, language_name};
+use crate::llm::{AiService, GenerationParams, InternalChatMessage};
 use luts_memory::{MemoryBlock, MemoryBlockBuilder, MemoryContent, BlockType};
 use luts_core::utils::tokens::{TokenManager, TokenUsage};
 use anyhow::Result;
+use async_trait::async_trait;
 use chrono::{DateTime, Timelike, Utc};
 use serde::{Deserialize, Serialize};
+use std::ops::Range;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
+
+/// Reduce levels after which map-reduce accepts the summary as is
+const MAX_REDUCE_LEVELS: usize = 8;
 
 /// Summarization strategy configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +37,13 @@ pub struct SummarizationConfig {
     pub auto_summarize_on_budget_limit: bool,
     /// Keep important messages (marked as important)
     pub preserve_important_messages: bool,
+    /// Largest chunk (approximate tokens) sent in one map-reduce call
+    #[serde(default = "default_max_chunk_tokens")]
+    pub max_chunk_tokens: usize,
+}
+
+fn default_max_chunk_tokens() -> usize {
+    4000
 }
 
 impl Default for SummarizationConfig {
@@ -42,6 +56,7 @@ impl Default for SummarizationConfig {
             preserve_recent_count: 5,        // Always keep last 5 messages
             auto_summarize_on_budget_limit: true,
             preserve_important_messages: true,
+            max_chunk_tokens: default_max_chunk_tokens(),
         }
     }
 }
@@ -57,6 +72,91 @@ pub enum SummarizationStrategy {
     TopicBased,
     /// Hierarchical summarization (multiple levels)
     Hierarchical,
+    /// Summarize chunks of `max_chunk_tokens` separately, then summarize the
+    /// summaries until one fits `target_summary_length`
+    MapReduce,
+}
+
+/// Token counts for one level of a map-reduce summary
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReduceLevelStats {
+    /// 0 for the chunks of messages, then 1, 2, ... for summaries of summaries
+    pub level: usize,
+    /// Summarization calls made at this level
+    pub chunk_count: usize,
+    /// Approximate tokens sent at this level
+    pub input_tokens: usize,
+    /// Approximate tokens produced at this level
+    pub output_tokens: usize,
+}
+
+/// Produces the summary text for a piece of conversation
+///
+/// This is the LLM call behind every strategy; tests can substitute a
+/// deterministic implementation.
+#[async_trait]
+pub trait SummaryGenerator: Send + Sync {
+    /// Summarize `text` in roughly `target_tokens`, in `language` if given
+    async fn summarize(
+        &self,
+        text: &str,
+        target_tokens: usize,
+        language: Option<&str>,
+    ) -> Result<String>;
+}
+
+/// [`SummaryGenerator`] that prompts an [`AiService`]
+pub struct AiSummaryGenerator {
+    ai_service: Arc<dyn AiService>,
+}
+
+impl AiSummaryGenerator {
+    pub fn new(ai_service: Arc<dyn AiService>) -> Self {
+        Self { ai_service }
+    }
+}
+
+#[async_trait]
+impl SummaryGenerator for AiSummaryGenerator {
+    async fn summarize(
+        &self,
+        text: &str,
+        target_tokens: usize,
+        language: Option<&str>,
+    ) -> Result<String> {
+        // Summarize in the language the user writes in
+        let language_instruction = language
+            .and_then(language_name)
+            .map(|name| format!("Write the summary in {}. ", name))
+            .unwrap_or_default();
+        let summary_prompt = format!(
+            "Please provide a comprehensive summary of the following conversation. \
+            Focus on key topics, important decisions, and factual information. \
+            {}Aim for approximately {} tokens in your summary.\n\n\
+            Conversation:\n{}",
+            language_instruction,
+            target_tokens,
+            text
+        );
+
+        let summary_messages = vec![
+            InternalChatMessage::System {
+                content: "You are an expert conversation summarizer. Create concise but comprehensive summaries.".to_string()
+            },
+            InternalChatMessage::User {
+                content: summary_prompt
+            }
+        ];
+
+        match self
+            .ai_service
+            .generate_response(&summary_messages, &GenerationParams::default())
+            .await?
+        {
+            genai::chat::MessageContent::Text(text) => Ok(text),
+            _ => Err(anyhow::anyhow!("Expected text response from summarization")),
+        }
+    }
 }
 
 /// Summary metadata and tracking
@@ -78,6 +178,19 @@ pub struct SummaryInfo {
     pub quality_score: Option<f64>,
     /// Topics detected in the summarized content
     pub detected_topics: Vec<String>,
+    /// Per-level token counts, for map-reduce summaries
+    #[serde(default)]
+    pub reduce_levels: Vec<ReduceLevelStats>,
+    /// Approximate tokens of all messages the summary covers
+    #[serde(default)]
+    pub covered_tokens: usize,
+    /// Times the summary was updated incrementally
+    #[serde(default)]
+    pub update_count: usize,
+    /// Tokens not sent thanks to incremental updates, versus re-summarizing
+    /// everything each time
+    #[serde(default)]
+    pub tokens_saved: usize,
 }
 
 /// Represents a summarized conversation segment
@@ -97,14 +210,21 @@ pub struct ConversationSummary {
     pub time_range: (DateTime<Utc>, DateTime<Utc>),
     /// Original message IDs that were summarized
     pub source_message_ids: Vec<String>,
+    /// Dominant language of the summarized messages, as an ISO 639-1 code
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Number of leading conversation messages the summary covers; messages
+    /// from this index on are not summarized yet
+    #[serde(default)]
+    pub covered_up_to: usize,
 }
 
 /// Intelligent conversation summarizer
 pub struct ConversationSummarizer {
     /// Configuration for summarization behavior
     config: RwLock<SummarizationConfig>,
-    /// AI service for generating summaries
-    ai_service: Arc<dyn AiService>,
+    /// Generates summary text, normally with the AI service
+    generator: Arc<dyn SummaryGenerator>,
     /// Token manager for tracking usage
     token_manager: Option<Arc<TokenManager>>,
     /// Storage for summaries
@@ -122,13 +242,19 @@ impl ConversationSummarizer {
     ) -> Self {
         Self {
             config: RwLock::new(SummarizationConfig::default()),
-            ai_service,
+            generator: Arc::new(AiSummaryGenerator::new(ai_service)),
             token_manager,
             summaries: RwLock::new(Vec::new()),
             storage_path,
         }
     }
 
+    /// Generate summary text with `generator` instead of the AI service
+    pub fn with_generator(mut self, generator: Arc<dyn SummaryGenerator>) -> Self {
+        self.generator = generator;
+        self
+    }
+
     /// Update summarization configuration
     pub async fn update_config(&self, config: SummarizationConfig) -> Result<()> {
         *self.config.write().await = config;
@@ -189,9 +315,108 @@ impl ConversationSummarizer {
             SummarizationStrategy::Hierarchical => {
                 self.hierarchical_summarization(messages, &config, user_id, session_id).await
             }
+            SummarizationStrategy::MapReduce => {
+                self.map_reduce_summarization(messages, &config).await
+            }
         }
     }
 
+    /// Fold new messages into an existing summary
+    ///
+    /// Only the previous summary and `new_messages` are sent to the LLM, so
+    /// keeping a running summary fresh (as the conversation summary core
+    /// block needs) costs the size of the new turns rather than the whole
+    /// conversation. `new_messages` should start at `prev.covered_up_to`.
+    /// The updated summary keeps `prev`'s ID and replaces it in storage.
+    pub async fn update_summary(
+        &self,
+        prev: &ConversationSummary,
+        new_messages: &[ExportableMessage],
+    ) -> Result<ConversationSummary> {
+        if new_messages.is_empty() {
+            return Ok(prev.clone());
+        }
+        let config = self.config.read().await.clone();
+
+        let new_text = format_exportable_messages(new_messages);
+        let user_messages: Vec<InternalChatMessage> = new_messages
+            .iter()
+            .filter(|message| message.message_type == MessageType::User)
+            .map(|message| InternalChatMessage::User {
+                content: message.content.clone(),
+            })
+            .collect();
+        let language = dominant_language(&user_messages).or(prev.language.as_deref());
+
+        let update_text = format!(
+            "Summary of the conversation so far:\n{}\n\n\
+            New messages since that summary:\n{}\n\n\
+            Update the summary so it also covers the new messages.",
+            prev.summary_text, new_text
+        );
+        let summary_text = self
+            .generator
+            .summarize(&update_text, config.target_summary_length, language)
+            .await?;
+
+        // A full re-summarization would send every covered message again
+        let new_tokens = estimate_tokens(&new_text);
+        let covered_tokens = prev.info.covered_tokens + new_tokens;
+        let tokens_saved = covered_tokens.saturating_sub(estimate_tokens(&update_text));
+
+        let topics = self.extract_topics(&summary_text);
+        let mut participants = prev.participants.clone();
+        for message in new_messages {
+            if !participants.contains(&message.author) {
+                participants.push(message.author.clone());
+            }
+        }
+        let mut source_message_ids = prev.source_message_ids.clone();
+        source_message_ids.extend(new_messages.iter().map(|message| message.id.clone()));
+
+        let summary = ConversationSummary {
+            info: SummaryInfo {
+                id: prev.info.id.clone(),
+                created_at: Utc::now(),
+                original_message_count: prev.info.original_message_count + new_messages.len(),
+                compression_ratio: covered_tokens as f64 / estimate_tokens(&summary_text).max(1) as f64,
+                strategy: prev.info.strategy.clone(),
+                token_usage: None,
+                quality_score: None,
+                detected_topics: topics.clone(),
+                reduce_levels: Vec::new(),
+                covered_tokens,
+                update_count: prev.info.update_count + 1,
+                tokens_saved: prev.info.tokens_saved + tokens_saved,
+            },
+            key_facts: self.extract_key_facts(&summary_text),
+            summary_text,
+            topics,
+            participants,
+            time_range: (prev.time_range.0, Utc::now()),
+            source_message_ids,
+            language: language.map(str::to_string),
+            covered_up_to: prev.covered_up_to + new_messages.len(),
+        };
+
+        {
+            let mut summaries = self.summaries.write().await;
+            match summaries.iter_mut().find(|s| s.info.id == summary.info.id) {
+                Some(existing) => *existing = summary.clone(),
+                None => summaries.push(summary.clone()),
+            }
+        }
+        self.save_to_storage().await?;
+
+        info!(
+            "Updated summary {} with {} new messages, saving ~{} tokens",
+            summary.info.id,
+            new_messages.len(),
+            tokens_saved
+        );
+        Ok(summary)
+    }
+
     /// Create memory blocks from conversation summary
     pub async fn create_memory_blocks(
         &self,
@@ -288,6 +513,18 @@ impl ConversationSummarizer {
             .sum();
         
         let topics_frequency = self.calculate_topic_frequency(&summaries);
+
+        let mut tokens_by_level: Vec<ReduceLevelStats> = Vec::new();
+        for stats in summaries.iter().flat_map(|s| &s.info.reduce_levels) {
+            match tokens_by_level.get_mut(stats.level) {
+                Some(total) => {
+                    total.chunk_count += stats.chunk_count;
+                    total.input_tokens += stats.input_tokens;
+                    total.output_tokens += stats.output_tokens;
+                }
+                None => tokens_by_level.push(stats.clone()),
+            }
+        }
         
         SummarizationAnalytics {
             total_summaries,
@@ -296,6 +533,9 @@ impl ConversationSummarizer {
             total_tokens_used,
             topics_frequency,
             most_productive_hour: self.calculate_most_productive_hour(&summaries),
+            tokens_by_level,
+            incremental_updates: summaries.iter().map(|s| s.info.update_count).sum(),
+            tokens_saved: summaries.iter().map(|s| s.info.tokens_saved).sum(),
         }
     }
 
@@ -308,42 +548,130 @@ impl ConversationSummarizer {
         _user_id: &str,
         _session_id: &str,
     ) -> Result<ConversationSummary> {
-        // Preserve recent messages
-        let messages_to_summarize = if config.preserve_recent_count > 0 && messages.len() > config.preserve_recent_count {
+        let messages_to_summarize = Self::messages_to_summarize(messages, config);
+        let conversation_text = self.format_messages_for_summarization(messages_to_summarize);
+        let language = dominant_language(messages_to_summarize);
+
+        let start_time = Utc::now();
+        let summary_text = self
+            .generator
+            .summarize(&conversation_text, config.target_summary_length, language)
+            .await?;
+
+        self.finish_summary(
+            messages_to_summarize,
+            &conversation_text,
+            summary_text,
+            config,
+            language,
+            start_time,
+            Vec::new(),
+        )
+        .await
+    }
+
+    /// Summarize turn-aligned chunks, then the summaries, level by level
+    async fn map_reduce_summarization(
+        &self,
+        messages: &[InternalChatMessage],
+        config: &SummarizationConfig,
+    ) -> Result<ConversationSummary> {
+        let messages_to_summarize = Self::messages_to_summarize(messages, config);
+        let language = dominant_language(messages_to_summarize);
+        let start_time = Utc::now();
+
+        let mut inputs: Vec<String> = chunk_messages(messages_to_summarize, config.max_chunk_tokens)
+            .into_iter()
+            .map(|range| self.format_messages_for_summarization(&messages_to_summarize[range]))
+            .collect();
+        if inputs.is_empty() {
+            return Err(anyhow::anyhow!("No messages to summarize"));
+        }
+        let mut levels = Vec::new();
+
+        let summary_text = loop {
+            let level = levels.len();
+            let mut outputs = Vec::with_capacity(inputs.len());
+            for input in &inputs {
+                outputs.push(
+                    self.generator
+                        .summarize(input, config.target_summary_length, language)
+                        .await?,
+                );
+            }
+            levels.push(ReduceLevelStats {
+                level,
+                chunk_count: inputs.len(),
+                input_tokens: inputs.iter().map(|text| estimate_tokens(text)).sum(),
+                output_tokens: outputs.iter().map(|text| estimate_tokens(text)).sum(),
+            });
+            debug!(
+                "Map-reduce level {}: {} chunks summarized to {} tokens",
+                level, inputs.len(), levels[level].output_tokens
+            );
+
+            if levels.len() >= MAX_REDUCE_LEVELS {
+                warn!("Map-reduce summary still too long after {} levels", MAX_REDUCE_LEVELS);
+                break outputs.join("\n\n");
+            }
+            if outputs.len() == 1 && estimate_tokens(&outputs[0]) <= config.target_summary_length {
+                break outputs.remove(0);
+            }
+
+            // Group at least two summaries per chunk so every level shrinks
+            let sizes: Vec<usize> = outputs.iter().map(|text| estimate_tokens(text)).collect();
+            inputs = pack_chunks(&sizes, config.max_chunk_tokens, 2)
+                .into_iter()
+                .map(|range| {
+                    outputs[range]
+                        .iter()
+                        .enumerate()
+                        .map(|(i, summary)| format!("Part {}: {}", i + 1, summary))
+                        .collect::<Vec<_>>()
+                        .join("\n\n")
+                })
+                .collect();
+        };
+
+        let conversation_text = self.format_messages_for_summarization(messages_to_summarize);
+        self.finish_summary(
+            messages_to_summarize,
+            &conversation_text,
+            summary_text,
+            config,
+            language,
+            start_time,
+            levels,
+        )
+        .await
+    }
+
+    /// Messages to summarize, leaving out the preserved recent ones
+    fn messages_to_summarize<'a>(
+        messages: &'a [InternalChatMessage],
+        config: &SummarizationConfig,
+    ) -> &'a [InternalChatMessage] {
+        if config.preserve_recent_count > 0 && messages.len() > config.preserve_recent_count {
             &messages[..messages.len() - config.preserve_recent_count]
         } else {
             messages
-        };
-        
-        let conversation_text = self.format_messages_for_summarization(messages_to_summarize);
-        
-        let summary_prompt = format!(
-            "Please provide a comprehensive summary of the following conversation. \
-            Focus on key topics, important decisions, and factual information. \
-            Aim for approximately {} tokens in your summary.\n\n\
-            Conversation:\n{}",
-            config.target_summary_length,
-            conversation_text
-        );
-        
-        let summary_messages = vec![
-            InternalChatMessage::System {
-                content: "You are an expert conversation summarizer. Create concise but comprehensive summaries.".to_string()
-            },
-            InternalChatMessage::User {
-                content: summary_prompt
-            }
-        ];
-        
-        let start_time = Utc::now();
-        let response = self.ai_service.generate_response(&summary_messages).await?;
+        }
+    }
+
+    /// Build, store and persist the summary of `messages_to_summarize`
+    #[allow(clippy::too_many_arguments)]
+    async fn finish_summary(
+        &self,
+        messages_to_summarize: &[InternalChatMessage],
+        conversation_text: &str,
+        summary_text: String,
+        config: &SummarizationConfig,
+        language: Option<&str>,
+        start_time: DateTime<Utc>,
+        reduce_levels: Vec<ReduceLevelStats>,
+    ) -> Result<ConversationSummary> {
         let end_time = Utc::now();
-        
-        let summary_text = match response {
-            genai::chat::MessageContent::Text(text) => text,
-            _ => return Err(anyhow::anyhow!("Expected text response from summarization")),
-        };
-        
+
         // Extract topics, facts, and participants (simplified for now)
         let topics = self.extract_topics(&summary_text);
         let key_facts = self.extract_key_facts(&summary_text);
@@ -356,11 +684,15 @@ impl ConversationSummarizer {
                 id: summary_id,
                 created_at: start_time,
                 original_message_count: messages_to_summarize.len(),
-                compression_ratio: self.calculate_compression_ratio(&conversation_text, &summary_text),
+                compression_ratio: self.calculate_compression_ratio(conversation_text, &summary_text),
                 strategy: config.strategy.clone(),
                 token_usage: None, // Will be filled by token manager if available
                 quality_score: None, // Could be implemented later
                 detected_topics: topics.clone(),
+                reduce_levels,
+                covered_tokens: estimate_tokens(conversation_text),
+                update_count: 0,
+                tokens_saved: 0,
             },
             summary_text,
             topics,
@@ -368,6 +700,8 @@ impl ConversationSummarizer {
             participants,
             time_range: (start_time, end_time),
             source_message_ids: self.extract_message_ids(messages_to_summarize),
+            language: language.map(str::to_string),
+            covered_up_to: messages_to_summarize.len(),
         };
         
         // Store the summary
@@ -597,6 +931,98 @@ pub struct SummarizationAnalytics {
     pub topics_frequency: std::collections::HashMap<String, usize>,
     /// Most productive hour (when most summaries are created)
     pub most_productive_hour: Option<u32>,
+    /// Map-reduce token counts per level, summed over all summaries
+    pub tokens_by_level: Vec<ReduceLevelStats>,
+    /// Incremental summary updates made
+    pub incremental_updates: usize,
+    /// Tokens saved by incremental updates versus full re-summarization
+    pub tokens_saved: usize,
+}
+
+/// Rough token estimate, matching the one used for streamed text
+pub(crate) fn estimate_tokens(text: &str) -> usize {
+    (text.split_whitespace().count() as f32 * 1.3) as usize
+}
+
+/// Exported messages as `Role: content` lines for a summarization prompt
+fn format_exportable_messages(messages: &[ExportableMessage]) -> String {
+    messages
+        .iter()
+        .map(|message| {
+            let role = match message.message_type {
+                MessageType::User => "User",
+                MessageType::Assistant => "Assistant",
+                MessageType::System => "System",
+                MessageType::Tool => "Tool",
+                MessageType::Error => "Error",
+                MessageType::Note => "Note",
+            };
+            format!("{}: {}", role, message.content)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Split messages into chunks of at most `max_tokens`, on turn boundaries
+///
+/// A turn starts at each user or system message. Turns larger than
+/// `max_tokens` are split between their messages; a message is never split.
+fn chunk_messages(messages: &[InternalChatMessage], max_tokens: usize) -> Vec<Range<usize>> {
+    let mut turns: Vec<Range<usize>> = Vec::new();
+    for (i, message) in messages.iter().enumerate() {
+        let starts_turn = matches!(
+            message,
+            InternalChatMessage::User { .. } | InternalChatMessage::System { .. }
+        );
+        match turns.last_mut() {
+            Some(turn) if !starts_turn => turn.end = i + 1,
+            _ => turns.push(i..i + 1),
+        }
+    }
+
+    let message_tokens: Vec<usize> = messages
+        .iter()
+        .map(|message| estimate_tokens(message.content()))
+        .collect();
+    let mut units: Vec<Range<usize>> = Vec::new();
+    for turn in turns {
+        if message_tokens[turn.clone()].iter().sum::<usize>() > max_tokens {
+            units.extend(turn.map(|i| i..i + 1));
+        } else {
+            units.push(turn);
+        }
+    }
+
+    let unit_tokens: Vec<usize> = units
+        .iter()
+        .map(|unit| message_tokens[unit.clone()].iter().sum())
+        .collect();
+    pack_chunks(&unit_tokens, max_tokens, 1)
+        .into_iter()
+        .map(|chunk| units[chunk.start].start..units[chunk.end - 1].end)
+        .collect()
+}
+
+/// Greedily group consecutive items into chunks of at most `max_tokens`
+///
+/// A chunk always takes at least `min_items` items (when that many remain),
+/// even if they exceed the limit.
+fn pack_chunks(sizes: &[usize], max_tokens: usize, min_items: usize) -> Vec<Range<usize>> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut tokens = 0;
+    for (i, &size) in sizes.iter().enumerate() {
+        if i - start >= min_items && tokens + size > max_tokens {
+            chunks.push(start..i);
+            start = i;
+            tokens = 0;
+        }
+        tokens += size;
+    }
+    if start < sizes.len() {
+        chunks.push(start..sizes.len());
+    }
+    chunks
 }
 
 /// Storage data structure
@@ -604,4 +1030,220 @@ pub struct SummarizationAnalytics {
 struct SummarizationStorageData {
     summaries: Vec<ConversationSummary>,
     config: SummarizationConfig,
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversation::test_fixtures::message;
+    use crate::llm::{MockAiService, MockResponse};
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn test_summary_uses_dominant_language() {
+        let service = Arc::new(MockAiService::new().with_response(MockResponse::Text(
+            "Resumen de la conversación.".to_string(),
+        )));
+        let storage_path = std::env::temp_dir()
+            .join(format!("luts_summaries_{}", uuid::Uuid::new_v4()))
+            .join("summaries.json");
+        let summarizer = ConversationSummarizer::new(service.clone(), None, storage_path.clone());
+
+        let mut messages = Vec::new();
+        for question in [
+            "¿Cuál es la mejor manera de aprender a programar en Rust?",
+            "¿Y cuánto tiempo se necesita para dominar el lenguaje?",
+            "Necesito un proyecto para practicar con mi equipo de trabajo.",
+            "Gracias, ¿me puedes recomendar un libro de la biblioteca?",
+        ] {
+            messages.push(InternalChatMessage::User {
+                content: question.to_string(),
+            });
+            messages.push(InternalChatMessage::Assistant {
+                content: "Claro, aquí tienes algunas ideas.".to_string(),
+                tool_responses: None,
+            });
+        }
+
+        let summary = summarizer
+            .summarize_conversation(&messages, "user", "session")
+            .await
+            .unwrap();
+        tokio::fs::remove_dir_all(storage_path.parent().unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(summary.language.as_deref(), Some("es"));
+        let prompt = service.requests()[0].last().unwrap().content().to_string();
+        assert!(prompt.contains("Write the summary in Spanish."));
+    }
+
+    /// Generator that answers with a short, predictable summary of each input
+    struct FakeGenerator {
+        inputs: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl SummaryGenerator for FakeGenerator {
+        async fn summarize(
+            &self,
+            text: &str,
+            _target_tokens: usize,
+            _language: Option<&str>,
+        ) -> Result<String> {
+            let mut inputs = self.inputs.lock().unwrap();
+            inputs.push(text.to_string());
+            Ok(format!("summary number {}", inputs.len()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_map_reduce_chunks_on_turn_boundaries() {
+        let generator = Arc::new(FakeGenerator {
+            inputs: Mutex::new(Vec::new()),
+        });
+        let storage_path = std::env::temp_dir()
+            .join(format!("luts_summaries_{}", uuid::Uuid::new_v4()))
+            .join("summaries.json");
+        let summarizer = ConversationSummarizer::new(
+            Arc::new(MockAiService::new()),
+            None,
+            storage_path.clone(),
+        )
+        .with_generator(generator.clone());
+        summarizer
+            .update_config(SummarizationConfig {
+                strategy: SummarizationStrategy::MapReduce,
+                preserve_recent_count: 0,
+                max_chunk_tokens: 30,
+                target_summary_length: 10,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        // 20 turns of about 13 tokens each: two turns fit in a chunk
+        let mut messages = Vec::new();
+        for turn in 0..20 {
+            messages.push(InternalChatMessage::User {
+                content: format!("question {} about the deployment pipeline", turn),
+            });
+            messages.push(InternalChatMessage::Assistant {
+                content: format!("answer {} about the pipeline", turn),
+                tool_responses: None,
+            });
+        }
+
+        let summary = summarizer
+            .summarize_conversation(&messages, "user", "session")
+            .await
+            .unwrap();
+        tokio::fs::remove_dir_all(storage_path.parent().unwrap())
+            .await
+            .unwrap();
+
+        let chunk_counts: Vec<usize> = summary
+            .info
+            .reduce_levels
+            .iter()
+            .map(|level| level.chunk_count)
+            .collect();
+        assert_eq!(chunk_counts, vec![10, 1]);
+        assert_eq!(summary.summary_text, "summary number 11");
+
+        // Every first-level chunk holds whole turns
+        {
+            let inputs = generator.inputs.lock().unwrap();
+            for (chunk, input) in inputs[..10].iter().enumerate() {
+                assert_eq!(
+                    input,
+                    &format!(
+                        "User: question {0} about the deployment pipeline\n\nAssistant: answer {0} about the pipeline\n\n\
+                         User: question {1} about the deployment pipeline\n\nAssistant: answer {1} about the pipeline",
+                        chunk * 2,
+                        chunk * 2 + 1
+                    )
+                );
+            }
+            assert!(inputs[10].starts_with("Part 1: summary number 1\n\nPart 2: summary number 2"));
+        }
+
+        let analytics = summarizer.get_analytics().await;
+        assert_eq!(analytics.tokens_by_level, summary.info.reduce_levels);
+    }
+
+    #[tokio::test]
+    async fn test_update_summary_sends_only_new_turns() {
+        let generator = Arc::new(FakeGenerator {
+            inputs: Mutex::new(Vec::new()),
+        });
+        let storage_path = std::env::temp_dir()
+            .join(format!("luts_summaries_{}", uuid::Uuid::new_v4()))
+            .join("summaries.json");
+        let summarizer = ConversationSummarizer::new(
+            Arc::new(MockAiService::new()),
+            None,
+            storage_path.clone(),
+        )
+        .with_generator(generator.clone());
+        summarizer
+            .update_config(SummarizationConfig {
+                preserve_recent_count: 0,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let mut messages = Vec::new();
+        for turn in 0..10 {
+            messages.push(InternalChatMessage::User {
+                content: format!("question {} about the deployment pipeline", turn),
+            });
+            messages.push(InternalChatMessage::Assistant {
+                content: format!("answer {} about the pipeline", turn),
+                tool_responses: None,
+            });
+        }
+        let first = summarizer
+            .summarize_conversation(&messages, "user", "session")
+            .await
+            .unwrap();
+        assert_eq!(first.covered_up_to, 20);
+
+        let updated = summarizer
+            .update_summary(
+                &first,
+                &[
+                    message("msg_20", MessageType::User, "user", "What about rollbacks?"),
+                    message(
+                        "msg_21",
+                        MessageType::Assistant,
+                        "assistant",
+                        "Rollbacks redeploy the last good build.",
+                    ),
+                ],
+            )
+            .await
+            .unwrap();
+        tokio::fs::remove_dir_all(storage_path.parent().unwrap())
+            .await
+            .unwrap();
+
+        {
+            let inputs = generator.inputs.lock().unwrap();
+            assert!(inputs[1].contains("summary number 1"));
+            assert!(inputs[1].contains("User: What about rollbacks?"));
+            assert!(!inputs[1].contains("question 0"));
+        }
+
+        assert_eq!(updated.info.id, first.info.id);
+        assert_eq!(updated.summary_text, "summary number 2");
+        assert_eq!(updated.covered_up_to, 22);
+        assert_eq!(updated.info.original_message_count, 22);
+        assert_eq!(summarizer.get_summaries().await.len(), 1);
+
+        let analytics = summarizer.get_analytics().await;
+        assert_eq!(analytics.incremental_updates, 1);
+        assert!(analytics.tokens_saved > 0);
+        assert_eq!(analytics.tokens_saved, updated.info.tokens_saved);
+    }
+}
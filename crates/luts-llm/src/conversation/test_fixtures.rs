@@ -0,0 +1,86 @@
+//! Conversations and messages shared by the conversation module's tests
+
+use crate::conversation::export::{
+    ConversationMetadata, ConversationStatus, ExportFormat, ExportInfo, ExportSettings,
+    ExportableConversation, ExportableMessage, MessageImportance, MessageMetadata, MessageType,
+};
+use chrono::Utc;
+use std::collections::HashMap;
+
+/// A plain text message sent now
+pub(crate) fn message(
+    id: &str,
+    message_type: MessageType,
+    author: &str,
+    content: &str,
+) -> ExportableMessage {
+    ExportableMessage {
+        id: id.to_string(),
+        role: message_type.role().to_string(),
+        message_type,
+        content: content.to_string(),
+        tool_calls: None,
+        tool_call_id: None,
+        reasoning: None,
+        language: None,
+        timestamp: Utc::now(),
+        author: author.to_string(),
+        metadata: MessageMetadata {
+            token_count: None,
+            processing_time_ms: None,
+            model: None,
+            temperature: None,
+            confidence: None,
+            importance: MessageImportance::default(),
+            is_bookmarked: false,
+            custom: HashMap::new(),
+        },
+        references: Vec::new(),
+        attachments: Vec::new(),
+        incomplete: false,
+    }
+}
+
+/// Metadata of an active conversation in the user's "session"
+pub(crate) fn metadata(id: &str, title: &str, message_count: usize) -> ConversationMetadata {
+    ConversationMetadata {
+        id: id.to_string(),
+        title: title.to_string(),
+        description: None,
+        user_id: "user".to_string(),
+        session_id: "session".to_string(),
+        started_at: Utc::now(),
+        last_message_at: Utc::now(),
+        message_count,
+        tags: Vec::new(),
+        properties: HashMap::new(),
+        language: None,
+        status: ConversationStatus::Active,
+        participants: Vec::new(),
+        parent_id: None,
+        forked_at: None,
+        forked_from_message: None,
+    }
+}
+
+/// A conversation titled after its id, holding only `messages`
+pub(crate) fn conversation(id: &str, messages: Vec<ExportableMessage>) -> ExportableConversation {
+    ExportableConversation {
+        metadata: metadata(id, id, messages.len()),
+        messages,
+        system_messages: Vec::new(),
+        pinned_context: Vec::new(),
+        memory_blocks: Vec::new(),
+        summaries: Vec::new(),
+        token_usage: Vec::new(),
+        export_info: ExportInfo {
+            exported_at: Utc::now(),
+            format: ExportFormat::Json,
+            version: "1.0".to_string(),
+            exporter: "test".to_string(),
+            settings: ExportSettings::default(),
+            file_size_bytes: None,
+            compression: None,
+        },
+    }
+}
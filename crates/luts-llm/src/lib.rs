@@ -7,24 +7,30 @@ pub mod tools;
 pub mod llm;
 pub mod streaming;
 pub mod conversation;
+pub mod telemetry;
 
 // Re-export key types for convenience
 pub use llm::{
-    AiService, ChatStreamChunk, InternalChatMessage, LLMService, ToolCall, ToolResponse,
+    AiService, ChatStreamChunk, GenerationParams, InternalChatMessage, LLMService,
+    ProviderCapabilities, RetryConfig, RetryableError, ToolCall, ToolResponse, UsageCallback,
 };
+#[cfg(any(test, feature = "test-util"))]
+pub use llm::{MockAiService, MockResponse};
 pub use streaming::{
-    ChunkType, ResponseChunk, ResponseStreamManager, StreamConfig, StreamEvent, StreamableResponse,
-    StreamingResponseBuilder, TypingIndicator, TypingStatus,
+    ChunkType, ProgressEstimate, ResponseChunk, ResponseStreamManager, StreamConfig, StreamEvent,
+    StreamableResponse, StreamingResponseBuilder, TypingIndicator, TypingStatus,
 };
 pub use conversation::{
-    AutoSaveConfig, AutoSaveData, AutoSaveManager, AutoSaveState, AutoSaveStats, AutoSaveType,
+    AutoSaveBackend, AutoSaveConfig, AutoSaveData, AutoSaveManager, AutoSaveState, AutoSaveStats,
+    AutoSaveType, FilesystemAutoSaveBackend,
     BookmarkCollection, BookmarkColor, BookmarkManager, BookmarkPriority, BookmarkQuery,
-    BookmarkStats, ConversationBookmark, ConversationExporter, ConversationMetadata,
-    ConversationSearchEngine, ConversationSearchQuery, ConversationSearchResult,
-    ConversationSegment, ConversationSegmentEditor, ConversationSummarizer,
+    BookmarkStats, ConversationBookmark, ConversationBranches, ConversationExporter,
+    ConversationMetadata,
+    ConversationManager, ConversationSearchEngine, ConversationSearchQuery,
+    ConversationSearchResult, ConversationSegment, ConversationSegmentEditor, ConversationSummarizer,
     ConversationSummary, ExportFormat, ExportSettings, ExportableConversation,
     ExportableMessage, ImportSettings, QuickAccessBookmark, SavedSearch, SearchAnalytics,
     SearchFilters, SegmentEdit, SegmentType, SummarizationAnalytics, SummarizationConfig,
     SummarizationStrategy, UndoRedoOperation,
 };
-pub use tools::AiTool;
\ No newline at end of file
+pub use tools::{AiTool, ToolError, ToolErrorKind, ToolPolicy, ToolResult, ToolSafety};
\ No newline at end of file
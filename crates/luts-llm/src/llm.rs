@@ -3,23 +3,36 @@
 //! This module provides a service for interacting with Large Language Models,
 //! supporting streaming responses, tool calling, and token usage tracking.
 
+use crate::telemetry;
 use crate::tools::AiTool;
-use luts_core::utils::tokens::{TokenManager, TokenUsage};
+use luts_common::{LutsError, ModelInfo, ModelRegistry, PricingConfig};
+use luts_core::utils::tokenizer::TokenCounter;
+use luts_core::utils::tokens::{BudgetStatus, TokenManager, TokenUsage};
 use anyhow::{Error, anyhow};
 use async_trait::async_trait;
 use chrono::{Local, Utc};
-use futures::TryStreamExt;
+use futures::{StreamExt, TryStreamExt};
 use futures_util::Stream;
 use genai::Client as GenaiClient;
 use genai::chat::{
-    ChatMessage as GenaiChatMessage, ChatStreamEvent, MessageContent, Tool,
-    ToolCall as GenaiToolCall, ToolResponse as GenaiToolResponse,
+    ChatMessage as GenaiChatMessage, ChatRequest, ChatStream, ChatStreamEvent, MessageContent,
+    Tool, ToolCall as GenaiToolCall, ToolResponse as GenaiToolResponse,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::pin::Pin;
 use std::sync::Arc;
-use tracing::{debug, info};
+use std::time::Instant;
+use tracing::{debug, info, instrument, warn};
+
+mod generation;
+#[cfg(any(test, feature = "test-util"))]
+mod mock;
+
+pub use generation::GenerationParams;
+#[cfg(any(test, feature = "test-util"))]
+pub use mock::{MockAiService, MockResponse};
+pub use luts_common::retry::{RetryConfig, RetryableError};
 
 /// Response from a tool execution
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -98,7 +111,43 @@ pub enum InternalChatMessage {
     },
 }
 
+/// Prefix marking a system message as a note inserted mid-conversation.
+pub const SYSTEM_NOTE_PREFIX: &str = "[System note] ";
+
+/// Whether `messages` open with a system prompt of their own
+///
+/// A leading system note doesn't count: it steers the conversation but
+/// doesn't replace the service's system prompt.
+fn opens_with_system_prompt(messages: &[InternalChatMessage]) -> bool {
+    matches!(messages.first(), Some(message @ InternalChatMessage::System { .. }) if !message.is_system_note())
+}
+
 impl InternalChatMessage {
+    /// Create a system note that steers subsequent turns.
+    ///
+    /// Notes are sent to the model as system messages but carry a marker so
+    /// they can be rendered distinctly and left out of exports by default.
+    pub fn system_note(note: impl AsRef<str>) -> Self {
+        InternalChatMessage::System {
+            content: format!("{}{}", SYSTEM_NOTE_PREFIX, note.as_ref()),
+        }
+    }
+
+    /// Whether this message is a system note created by [`Self::system_note`]
+    pub fn is_system_note(&self) -> bool {
+        matches!(self, InternalChatMessage::System { content } if content.starts_with(SYSTEM_NOTE_PREFIX))
+    }
+
+    /// Text content of the message, whatever its role
+    pub fn content(&self) -> &str {
+        match self {
+            InternalChatMessage::System { content }
+            | InternalChatMessage::User { content }
+            | InternalChatMessage::Assistant { content, .. }
+            | InternalChatMessage::Tool { content, .. } => content,
+        }
+    }
+
     pub fn to_genai(&self) -> GenaiChatMessage {
         match self {
             InternalChatMessage::System { content } => GenaiChatMessage::system(content),
@@ -153,17 +202,42 @@ pub struct ChatStreamChunk {
 #[async_trait]
 pub trait AiService: Send + Sync {
     /// Generate a response to a conversation
+    ///
+    /// Fields set in `params` override the service's own sampling settings
+    /// for this request only.
     async fn generate_response(
         &self,
         messages: &[InternalChatMessage],
+        params: &GenerationParams,
     ) -> anyhow::Result<MessageContent>;
 
     /// Generate a streaming response to a conversation
+    ///
+    /// Providers may ignore `params.stop`;
+    /// [`stop_at_sequences`](crate::streaming::stop_at_sequences) enforces it
+    /// on the returned stream.
     async fn generate_response_stream<'a>(
         &'a self,
         messages: &'a [InternalChatMessage],
+        params: &GenerationParams,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<ChatStreamEvent, Error>> + Send + 'a>>, Error>;
 
+    /// The tool called `name`, for running the tool calls the model makes
+    ///
+    /// Services without tools can rely on the default, which finds none.
+    fn find_tool(&self, _name: &str) -> Option<&dyn AiTool> {
+        None
+    }
+
+    /// Price and report the token usage of a call once its response is complete
+    ///
+    /// Returns the estimated cost in USD when the service knows its model's
+    /// pricing. Services that neither price nor track usage can rely on the
+    /// default, which does nothing.
+    async fn record_usage(&self, _prompt_tokens: u32, _completion_tokens: u32) -> Option<f64> {
+        None
+    }
+
     /// Downcast to concrete type for tool access
     fn as_any(&self) -> &dyn std::any::Any;
 }
@@ -191,6 +265,24 @@ impl From<GenaiToolCall> for ToolCall {
     }
 }
 
+/// What the configured provider supports, as reported to API clients
+///
+/// Capabilities come from the model registry; they're `None` for models it
+/// doesn't know rather than guessed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderCapabilities {
+    /// Provider/model name requests are sent to
+    pub provider: String,
+    /// Models that can be requested
+    pub models: Vec<String>,
+    /// Whether responses can be streamed
+    pub streaming: Option<bool>,
+    /// Whether the model can call tools
+    pub tool_calling: Option<bool>,
+    /// Maximum context window in tokens
+    pub max_context_tokens: Option<usize>,
+}
+
 /// A service for interacting with LLMs
 pub struct LLMService {
     /// System prompt to use for context
@@ -213,8 +305,26 @@ pub struct LLMService {
     
     /// User ID for token tracking
     user_id: String,
+
+    /// How transient provider errors are retried
+    retry: RetryConfig,
+
+    /// Sampling parameters sent with every request
+    generation: GenerationParams,
+
+    /// Prices used to estimate the cost of each call
+    pricing: PricingConfig,
+
+    /// Context size, capabilities and fallback prices of known models
+    models: ModelRegistry,
+
+    /// Called with the token usage of every completed call
+    usage_callback: Option<UsageCallback>,
 }
 
+/// Receives the token usage of each completed LLM call
+pub type UsageCallback = Arc<dyn Fn(&TokenUsage) + Send + Sync>;
+
 impl LLMService {
     /// Create a new LLM service
     pub fn new(
@@ -253,9 +363,54 @@ impl LLMService {
             token_manager,
             session_id: session_id.to_string(),
             user_id: user_id.to_string(),
+            retry: RetryConfig::default(),
+            generation: GenerationParams::default(),
+            pricing: PricingConfig::default(),
+            models: ModelRegistry::bundled(),
+            usage_callback: None,
         })
     }
 
+    /// Set the prices used for cost estimates
+    pub fn with_pricing(mut self, pricing: PricingConfig) -> Self {
+        self.pricing = pricing;
+        self
+    }
+
+    /// Use `models` instead of the bundled model registry
+    pub fn with_model_registry(mut self, models: ModelRegistry) -> Self {
+        self.models = models;
+        self
+    }
+
+    /// Capabilities and pricing of this service's model, if it is known
+    pub fn model_info(&self) -> Option<ModelInfo> {
+        self.models.lookup(&self.provider)
+    }
+
+    /// Call `callback` with the token usage of every completed call
+    pub fn with_usage_callback(mut self, callback: UsageCallback) -> Self {
+        self.usage_callback = Some(callback);
+        self
+    }
+
+    /// Set how transient provider errors (rate limits, timeouts, 5xx) are retried
+    pub fn with_retry_config(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Set the sampling parameters (temperature, etc.) sent with every request
+    pub fn with_generation_params(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
+
+    /// Sampling parameters sent with every request
+    pub fn generation_params(&self) -> &GenerationParams {
+        &self.generation
+    }
+
     /// Add a tool to the service
     pub fn add_tool(&mut self, tool: Box<dyn AiTool>) {
         self.tools.push(tool);
@@ -271,6 +426,11 @@ impl LLMService {
         }
     }
 
+    /// Provider/model requests are sent to
+    pub fn provider(&self) -> &str {
+        &self.provider
+    }
+
     /// Set the system prompt
     pub fn set_system_prompt(&mut self, prompt: String) {
         self.system_prompt = Some(prompt);
@@ -281,6 +441,19 @@ impl LLMService {
         self.tools.iter().map(|t| t.name().to_string()).collect()
     }
 
+    /// Describe what this service's provider supports, as the model registry lists it
+    pub fn capabilities(&self) -> ProviderCapabilities {
+        let info = self.model_info();
+        ProviderCapabilities {
+            provider: self.provider.clone(),
+            // The provider name doubles as the model name
+            models: vec![self.provider.clone()],
+            streaming: info.as_ref().map(|info| info.supports_streaming),
+            tool_calling: info.as_ref().map(|info| info.supports_tools),
+            max_context_tokens: info.map(|info| info.max_context_tokens),
+        }
+    }
+
     /// Find a tool by name
     pub fn find_tool(&self, tool_name: &str) -> Option<&dyn AiTool> {
         self.tools.iter().find(|t| t.name() == tool_name).map(|b| b.as_ref())
@@ -298,51 +471,34 @@ impl LLMService {
             .collect()
     }
 
-    /// Enhance system prompt with current date and time information
-    fn enhance_system_prompt(&self, base_prompt: &str) -> String {
-        let now_local = Local::now();
-        let now_utc = Utc::now();
-        
-        // Format current date and time
-        let local_datetime = now_local.format("%A, %B %d, %Y at %I:%M:%S %p %Z").to_string();
-        let utc_datetime = now_utc.format("%Y-%m-%d %H:%M:%S UTC").to_string();
-        let weekday = now_local.format("%A").to_string();
-        let date_only = now_local.format("%B %d, %Y").to_string();
-        let time_only = now_local.format("%I:%M:%S %p").to_string();
-        
-        // Create enhanced system prompt with date/time context
-        format!(
-            "{}\n\n## Current Date and Time\n\
-            Current local time: {}\n\
-            Current UTC time: {}\n\
-            Today is: {}\n\
-            Current date: {}\n\
-            Current time: {}\n\
-            \n\
-            Use this current date and time information when:\n\
-            - Answering questions about \"today\", \"now\", \"current time\", etc.\n\
-            - Scheduling or time-related tasks\n\
-            - Providing context-aware responses\n\
-            - Calculating time differences or durations\n\
-            - Any other time or date-sensitive interactions",
-            base_prompt,
-            local_datetime,
-            utc_datetime,
-            weekday,
-            date_only,
-            time_only
-        )
+    /// System prompt to send along with `messages`, if any
+    ///
+    /// The service's prompt is sent unless the conversation opens with a
+    /// system prompt of its own. System notes, even a leading one, never
+    /// replace it.
+    pub fn system_prompt_for(&self, messages: &[InternalChatMessage]) -> Option<String> {
+        let prompt = self.system_prompt.as_deref()?;
+        (!opens_with_system_prompt(messages)).then(|| self.enhance_system_prompt(prompt))
     }
-}
 
-#[async_trait]
-impl AiService for LLMService {
-    async fn generate_response(
+    /// Generate a response and report the tokens it used
+    ///
+    /// The usage carries an estimated cost when pricing is known for the
+    /// model, and is also passed to the usage callback if one is set.
+    #[instrument(
+        name = "llm_request",
+        skip_all,
+        fields(session_id = %self.session_id, user_id = %self.user_id, provider = %self.provider)
+    )]
+    pub async fn generate_response_with_usage(
         &self,
         messages: &[InternalChatMessage],
-    ) -> anyhow::Result<MessageContent> {
+        params: &GenerationParams,
+    ) -> anyhow::Result<(MessageContent, TokenUsage)> {
         debug!("Generating response for {} messages", messages.len());
         debug!("LLM service has {} tools available", self.tools.len());
+        let generation = self.generation.merged_with(params);
+        self.enforce_budget(messages, &generation).await?;
 
         // Build chat request properly with tool calls and responses
         let mut chat_req = genai::chat::ChatRequest::new(Vec::new());
@@ -384,26 +540,25 @@ impl AiService for LLMService {
             debug!("No tools available - LLM will not be able to call tools");
         }
 
-        // Add system prompt if available and no system message exists
-        if let Some(prompt) = &self.system_prompt {
-            let has_system = messages
-                .iter()
-                .any(|msg| matches!(msg, InternalChatMessage::System { .. }));
-            if !has_system {
-                debug!("Adding enhanced system prompt with current date/time to chat request");
-                let enhanced_prompt = self.enhance_system_prompt(prompt);
-                chat_req = chat_req.with_system(enhanced_prompt);
-            }
+        if let Some(prompt) = self.system_prompt_for(messages) {
+            debug!("Adding enhanced system prompt with current date/time to chat request");
+            chat_req = chat_req.with_system(prompt);
         }
 
         debug!("Executing chat request to provider: {}", self.provider);
 
-        // Execute chat request
+        // Execute chat request, retrying transient provider errors
+        let options = generation.chat_options();
+        let started = Instant::now();
         let response = self
-            .client
-            .exec_chat(&self.provider, chat_req, None)
-            .await
-            .map_err(|e| anyhow!("GenAI API error: {}", e))?;
+            .retry
+            .run("Chat request", || {
+                self.client
+                    .exec_chat(&self.provider, chat_req.clone(), Some(&options))
+            })
+            .await;
+        telemetry::record_llm_request(&self.provider, "chat", started.elapsed(), response.is_ok());
+        let response = response.map_err(|e| anyhow!("GenAI API error: {}", e))?;
 
         debug!("Response received with {} content items", response.content.len());
         if let Some(content) = response.content.first() {
@@ -429,36 +584,195 @@ impl AiService for LLMService {
             }
         }
 
+        let mut usage = TokenUsage::from_genai_usage(
+            &response.usage,
+            self.provider.clone(),
+            self.provider.clone(), // For now, use provider as model name
+            "chat".to_string(),
+            self.session_id.clone(),
+            self.user_id.clone(),
+        );
+        usage.estimated_cost = self.estimate_cost(usage.input_tokens, usage.output_tokens);
+        telemetry::record_tokens(usage.input_tokens, usage.output_tokens);
+        debug!(
+            "Chat used {} prompt + {} completion tokens (${:.4})",
+            usage.input_tokens,
+            usage.output_tokens,
+            usage.estimated_cost.unwrap_or(0.0)
+        );
+
+        self.report_usage(&usage).await;
+
+        let content = response
+            .content
+            .first().cloned()
+            .ok_or_else(|| anyhow!("No content in chat response"))?;
+        Ok((content, usage))
+    }
+
+    /// Hand a completed call's usage to the usage callback and token manager
+    async fn report_usage(&self, usage: &TokenUsage) {
+        if let Some(callback) = &self.usage_callback {
+            callback(usage);
+        }
+
         // Record token usage if manager is available
-        if let Some(token_manager) = &self.token_manager {
-            let token_usage = TokenUsage::from_genai_usage(
-                &response.usage,
-                self.provider.clone(),
-                self.provider.clone(), // For now, use provider as model name
-                "chat".to_string(),
-                self.session_id.clone(),
-                self.user_id.clone(),
-            );
-            
-            if let Err(e) = token_manager.record_usage(token_usage).await {
-                debug!("Failed to record token usage: {}", e);
+        if let Some(token_manager) = &self.token_manager
+            && let Err(e) = token_manager.record_usage(usage.clone()).await
+        {
+            debug!("Failed to record token usage: {}", e);
+        }
+    }
+
+    /// Estimated cost in USD of a call to this service's model, if it has pricing
+    ///
+    /// Prices set with [`with_pricing`](Self::with_pricing) win over the model registry's.
+    pub fn estimate_cost(&self, prompt_tokens: u32, completion_tokens: u32) -> Option<f64> {
+        self.pricing
+            .pricing_for_model(&self.provider)
+            .cloned()
+            .or_else(|| self.model_info().map(|info| info.pricing()))
+            .map(|pricing| pricing.calculate_cost(prompt_tokens, completion_tokens))
+    }
+
+    /// Refuse a request the token budgets can't cover before it reaches the provider
+    ///
+    /// The estimate is the prompt plus `max_tokens` when that is set. Without a
+    /// token manager there are no budgets to enforce.
+    async fn enforce_budget(
+        &self,
+        messages: &[InternalChatMessage],
+        generation: &GenerationParams,
+    ) -> anyhow::Result<()> {
+        let Some(token_manager) = &self.token_manager else {
+            return Ok(());
+        };
+
+        let counter = TokenCounter::for_model(&self.provider);
+        let prompt_tokens: usize = messages.iter().map(|m| counter.count(m.content())).sum::<usize>()
+            + self.system_prompt.as_deref().map_or(0, |prompt| counter.count(prompt));
+        let estimated = (prompt_tokens as u32).saturating_add(generation.max_tokens.unwrap_or(0));
+
+        match token_manager
+            .check_budget(&self.user_id, &self.session_id, estimated)
+            .await
+        {
+            BudgetStatus::WithinBudget => Ok(()),
+            BudgetStatus::Warning { scope, used, limit } => {
+                warn!("Token budget warning: {} usage will reach {} of {} tokens", scope, used, limit);
+                Ok(())
+            }
+            BudgetStatus::Exceeded {
+                scope,
+                requested,
+                available,
+            } => {
+                warn!(
+                    "Refusing request of ~{} tokens: {} budget has {} left",
+                    requested, scope, available
+                );
+                Err(LutsError::BudgetExceeded {
+                    requested: requested as usize,
+                    available: available as usize,
+                }
+                .into())
             }
         }
+    }
 
-        response
-            .content
-            .first().cloned()
-            .ok_or_else(|| anyhow!("No content in chat response"))
+    /// Start a streaming request and read up to its first content event
+    ///
+    /// Failures before any content reaches the caller can be retried safely;
+    /// retrying after that would duplicate what was already emitted.
+    async fn open_stream(
+        &self,
+        chat_req: ChatRequest,
+        generation: &GenerationParams,
+    ) -> Result<(Vec<ChatStreamEvent>, ChatStream), genai::Error> {
+        let options = generation.chat_options();
+        let mut stream = self
+            .client
+            .exec_chat_stream(&self.provider, chat_req, Some(&options))
+            .await?
+            .stream;
+
+        let mut buffered = Vec::new();
+        while let Some(event) = stream.next().await {
+            let event = event?;
+            let is_content = !matches!(event, ChatStreamEvent::Start);
+            buffered.push(event);
+            if is_content {
+                break;
+            }
+        }
+        Ok((buffered, stream))
     }
 
+    /// Enhance system prompt with current date and time information
+    fn enhance_system_prompt(&self, base_prompt: &str) -> String {
+        let now_local = Local::now();
+        let now_utc = Utc::now();
+        
+        // Format current date and time
+        let local_datetime = now_local.format("%A, %B %d, %Y at %I:%M:%S %p %Z").to_string();
+        let utc_datetime = now_utc.format("%Y-%m-%d %H:%M:%S UTC").to_string();
+        let weekday = now_local.format("%A").to_string();
+        let date_only = now_local.format("%B %d, %Y").to_string();
+        let time_only = now_local.format("%I:%M:%S %p").to_string();
+        
+        // Create enhanced system prompt with date/time context
+        format!(
+            "{}\n\n## Current Date and Time\n\
+            Current local time: {}\n\
+            Current UTC time: {}\n\
+            Today is: {}\n\
+            Current date: {}\n\
+            Current time: {}\n\
+            \n\
+            Use this current date and time information when:\n\
+            - Answering questions about \"today\", \"now\", \"current time\", etc.\n\
+            - Scheduling or time-related tasks\n\
+            - Providing context-aware responses\n\
+            - Calculating time differences or durations\n\
+            - Any other time or date-sensitive interactions",
+            base_prompt,
+            local_datetime,
+            utc_datetime,
+            weekday,
+            date_only,
+            time_only
+        )
+    }
+}
+
+#[async_trait]
+impl AiService for LLMService {
+    async fn generate_response(
+        &self,
+        messages: &[InternalChatMessage],
+        params: &GenerationParams,
+    ) -> anyhow::Result<MessageContent> {
+        self.generate_response_with_usage(messages, params)
+            .await
+            .map(|(content, _)| content)
+    }
+
+    #[instrument(
+        name = "llm_stream",
+        skip_all,
+        fields(session_id = %self.session_id, user_id = %self.user_id, provider = %self.provider)
+    )]
     async fn generate_response_stream<'a>(
         &'a self,
         messages: &'a [InternalChatMessage],
+        params: &GenerationParams,
     ) -> Result<
         Pin<Box<dyn futures_util::Stream<Item = Result<ChatStreamEvent, Error>> + Send + 'a>>,
         Error,
     > {
         debug!("Streaming response for {} messages", messages.len());
+        let generation = self.generation.merged_with(params);
+        self.enforce_budget(messages, &generation).await?;
 
         // Convert messages to genai format
         let genai_messages: Vec<GenaiChatMessage> =
@@ -472,26 +786,48 @@ impl AiService for LLMService {
             chat_req = chat_req.with_tools(self.get_genai_tools());
         }
 
-        // Add system prompt if available
-        if let Some(prompt) = &self.system_prompt {
-            let has_system = messages
-                .iter()
-                .any(|msg| matches!(msg, InternalChatMessage::System { .. }));
-            if !has_system {
-                debug!("Adding enhanced system prompt with current date/time to streaming chat request");
-                let enhanced_prompt = self.enhance_system_prompt(prompt);
-                chat_req = chat_req.with_system(enhanced_prompt);
-            }
+        if let Some(prompt) = self.system_prompt_for(messages) {
+            debug!("Adding enhanced system prompt with current date/time to streaming chat request");
+            chat_req = chat_req.with_system(prompt);
         }
 
-        // Execute streaming chat request
-        let genai_stream = self
-            .client
-            .exec_chat_stream(&self.provider, chat_req, None)
-            .await
-            .map_err(|e| anyhow!("GenAI API error: {}", e))?;
+        // Execute streaming chat request; only the start of the stream is retried
+        let started = Instant::now();
+        let opened = self
+            .retry
+            .run("Streaming chat request", || {
+                self.open_stream(chat_req.clone(), &generation)
+            })
+            .await;
+        telemetry::record_llm_request(&self.provider, "stream", started.elapsed(), opened.is_ok());
+        let (buffered, stream) = opened.map_err(|e| anyhow!("GenAI API error: {}", e))?;
+
+        Ok(Box::pin(
+            futures::stream::iter(buffered.into_iter().map(Ok))
+                .chain(stream.map_err(|e| anyhow!(e))),
+        ))
+    }
+
+    fn find_tool(&self, name: &str) -> Option<&dyn AiTool> {
+        LLMService::find_tool(self, name)
+    }
 
-        Ok(Box::pin(genai_stream.stream.map_err(|e| anyhow!(e))))
+    async fn record_usage(&self, prompt_tokens: u32, completion_tokens: u32) -> Option<f64> {
+        let estimated_cost = self.estimate_cost(prompt_tokens, completion_tokens);
+        let usage = TokenUsage {
+            input_tokens: prompt_tokens,
+            output_tokens: completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+            estimated_cost,
+            timestamp: chrono::Utc::now(),
+            provider: self.provider.clone(),
+            model: self.provider.clone(),
+            operation_type: "stream".to_string(),
+            session_id: self.session_id.clone(),
+            user_id: self.user_id.clone(),
+        };
+        self.report_usage(&usage).await;
+        estimated_cost
     }
 
     fn as_any(&self) -> &dyn std::any::Any {
@@ -551,4 +887,123 @@ mod tests {
         assert_eq!(service.tools[0].name(), "mock");
         assert!(service.system_prompt.is_some());
     }
+
+    #[test]
+    fn test_estimate_cost_uses_model_pricing() {
+        let mut pricing = PricingConfig::default();
+        pricing.set_pricing(
+            "acme",
+            "rocket-1",
+            luts_common::TokenPricing {
+                input_price_per_1k: 1.0,
+                output_price_per_1k: 4.0,
+            },
+        );
+        let service = LLMService::new(None, Vec::new(), "rocket-1")
+            .unwrap()
+            .with_pricing(pricing);
+        assert_eq!(service.estimate_cost(2000, 500), Some(4.0));
+
+        let unpriced = LLMService::new(None, Vec::new(), "unknown-model").unwrap();
+        assert_eq!(unpriced.estimate_cost(2000, 500), None);
+    }
+
+    #[tokio::test]
+    async fn test_recorded_stream_usage_reaches_the_callback() {
+        let reported = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let service = LLMService::new(None, Vec::new(), "gemini-2.5-pro")
+            .unwrap()
+            .with_usage_callback({
+                let reported = reported.clone();
+                Arc::new(move |usage: &TokenUsage| reported.lock().unwrap().push(usage.clone()))
+            });
+
+        let service: &dyn AiService = &service;
+        let cost = service.record_usage(1000, 1000).await;
+
+        let reported = reported.lock().unwrap();
+        assert_eq!(reported.len(), 1);
+        assert_eq!(reported[0].total_tokens, 2000);
+        assert_eq!(reported[0].operation_type, "stream");
+        assert_eq!(reported[0].estimated_cost, cost);
+        assert!(cost.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_request_over_budget_is_refused_before_sending() {
+        let path = std::env::temp_dir().join(format!("luts-budget-{}.json", uuid::Uuid::new_v4()));
+        let token_manager = Arc::new(TokenManager::new(path.clone()));
+        token_manager.set_session_limit("tight", 5).await.unwrap();
+        let service = LLMService::new_with_token_manager(
+            None,
+            Vec::new(),
+            "test_provider",
+            Some(token_manager),
+            "tight",
+            "test_user",
+        )
+        .unwrap();
+
+        let messages = vec![InternalChatMessage::User {
+            content: "Please write me a very long story about a lighthouse keeper".to_string(),
+        }];
+        let error = service
+            .generate_response(&messages, &GenerationParams::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<LutsError>(),
+            Some(LutsError::BudgetExceeded { available: 5, .. })
+        ));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_capabilities_come_from_model_registry() {
+        let service = LLMService::new(None, Vec::new(), "gemini-2.5-pro").unwrap();
+        let capabilities = service.capabilities();
+        assert_eq!(capabilities.max_context_tokens, Some(1_048_576));
+        assert_eq!(capabilities.tool_calling, Some(true));
+        // 1000 input tokens at $1.25/M and 1000 output at $10/M
+        assert!((service.estimate_cost(1000, 1000).unwrap() - 0.01125).abs() < 1e-9);
+
+        let unknown = LLMService::new(None, Vec::new(), "unknown-model").unwrap();
+        assert!(unknown.model_info().is_none());
+        let capabilities = unknown.capabilities();
+        assert_eq!(capabilities.max_context_tokens, None);
+        assert_eq!(capabilities.streaming, None);
+    }
+
+    #[test]
+    fn test_system_note_is_marked_as_system() {
+        let note = InternalChatMessage::system_note("Answer in French from now on");
+
+        assert!(note.is_system_note());
+        match note {
+            InternalChatMessage::System { content } => {
+                assert_eq!(content, "[System note] Answer in French from now on");
+            }
+            other => panic!("expected a system message, got {:?}", other),
+        }
+
+        let plain = InternalChatMessage::System {
+            content: "You are a helpful assistant".to_string(),
+        };
+        assert!(!plain.is_system_note());
+    }
+
+    #[test]
+    fn test_leading_system_note_does_not_replace_system_prompt() {
+        let note = InternalChatMessage::system_note("Context from notes.md");
+        let user = InternalChatMessage::User {
+            content: "Hi".to_string(),
+        };
+        assert!(!opens_with_system_prompt(&[note.clone(), user.clone()]));
+        assert!(!opens_with_system_prompt(std::slice::from_ref(&user)));
+
+        let prompt = InternalChatMessage::System {
+            content: "You are a helpful assistant".to_string(),
+        };
+        assert!(opens_with_system_prompt(&[prompt, note, user]));
+    }
 }
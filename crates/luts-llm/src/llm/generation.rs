@@ -0,0 +1,118 @@
+//! Sampling parameters sent with each chat request
+
+use genai::chat::ChatOptions;
+use serde::{Deserialize, Serialize};
+
+/// Sampling parameters for generation
+///
+/// Unset fields are left to the provider's defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GenerationParams {
+    /// Sampling temperature; higher values give more varied output
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    /// Nucleus sampling cutoff
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    /// Maximum number of tokens to generate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    /// Sequences that end the response when generated; they aren't included
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stop: Vec<String>,
+    /// Seed for reproducible sampling, on a best-effort basis
+    ///
+    /// The genai version in use has no seed option, so the seed is kept with
+    /// the other params but not yet sent to providers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+impl GenerationParams {
+    /// Set the sampling temperature
+    pub fn with_temperature(mut self, temperature: f64) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Set the nucleus sampling cutoff
+    pub fn with_top_p(mut self, top_p: f64) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    /// Set the maximum number of tokens to generate
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Add a sequence that ends the response
+    pub fn with_stop(mut self, stop: impl Into<String>) -> Self {
+        self.stop.push(stop.into());
+        self
+    }
+
+    /// Set the sampling seed
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// These params with every field set in `overrides` replaced
+    ///
+    /// Stop sequences are replaced as a whole when `overrides` has any.
+    pub fn merged_with(&self, overrides: &GenerationParams) -> Self {
+        Self {
+            temperature: overrides.temperature.or(self.temperature),
+            top_p: overrides.top_p.or(self.top_p),
+            max_tokens: overrides.max_tokens.or(self.max_tokens),
+            stop: if overrides.stop.is_empty() {
+                self.stop.clone()
+            } else {
+                overrides.stop.clone()
+            },
+            seed: overrides.seed.or(self.seed),
+        }
+    }
+
+    /// Per-request chat options carrying these params
+    pub fn chat_options(&self) -> ChatOptions {
+        ChatOptions {
+            temperature: self.temperature,
+            top_p: self.top_p,
+            max_tokens: self.max_tokens,
+            stop_sequences: self.stop.clone(),
+            // No seed: ChatOptions can't carry one yet, see `seed`
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merged_with_prefers_overrides() {
+        let preset = GenerationParams::default()
+            .with_temperature(0.9)
+            .with_max_tokens(2048)
+            .with_stop("\n\n");
+        let merged = preset.merged_with(&GenerationParams::default().with_temperature(0.2));
+
+        assert_eq!(
+            merged,
+            GenerationParams {
+                temperature: Some(0.2),
+                top_p: None,
+                max_tokens: Some(2048),
+                stop: vec!["\n\n".to_string()],
+                seed: None,
+            }
+        );
+        assert_eq!(merged.chat_options().temperature, Some(0.2));
+        assert_eq!(merged.chat_options().max_tokens, Some(2048));
+    }
+}
@@ -0,0 +1,309 @@
+//! A scripted [`AiService`] for tests
+//!
+//! [`MockAiService`] answers with the [`MockResponse`]s queued on it, one per
+//! request and in order, so streaming, tool-execution and agent tests don't
+//! need a provider. Tools given to it are run when a scripted call names
+//! them. Streamed text is split into chunks of a fixed size, or of sizes
+//! drawn from a seeded generator to exercise reassembly while staying
+//! reproducible. Available to other crates with the `test-util` feature.
+
+use super::{AiService, GenerationParams, InternalChatMessage};
+use crate::tools::AiTool;
+use anyhow::{Error, anyhow};
+use async_trait::async_trait;
+use futures_util::Stream;
+use genai::chat::{
+    ChatStreamEvent, MessageContent, StreamChunk, StreamEnd, ToolCall as GenaiToolCall, ToolChunk,
+};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::Mutex;
+
+/// Characters per streamed text chunk unless configured otherwise
+const DEFAULT_CHUNK_CHARS: usize = 8;
+
+/// One scripted answer of a [`MockAiService`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockResponse {
+    /// Answer with text
+    Text(String),
+    /// Ask for a tool to be called
+    ToolCall { name: String, args: Value },
+    /// Fail the request with this message
+    Error(String),
+}
+
+/// AI service that replays scripted responses
+pub struct MockAiService {
+    responses: Mutex<VecDeque<MockResponse>>,
+    requests: Mutex<Vec<Vec<InternalChatMessage>>>,
+    tools: Vec<Box<dyn AiTool>>,
+    chunk_chars: usize,
+    /// When set, chunk sizes are drawn from 1 to `chunk_chars`
+    rng: Mutex<Option<StdRng>>,
+    tool_calls: Mutex<usize>,
+    usage: Mutex<Vec<(u32, u32)>>,
+}
+
+impl MockAiService {
+    /// Create a service with nothing scripted
+    pub fn new() -> Self {
+        Self {
+            responses: Mutex::new(VecDeque::new()),
+            requests: Mutex::new(Vec::new()),
+            tools: Vec::new(),
+            chunk_chars: DEFAULT_CHUNK_CHARS,
+            rng: Mutex::new(None),
+            tool_calls: Mutex::new(0),
+            usage: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Queue a response
+    pub fn with_response(self, response: MockResponse) -> Self {
+        self.push(response);
+        self
+    }
+
+    /// Offer a tool to whoever runs the scripted tool calls
+    pub fn with_tool(mut self, tool: impl AiTool + 'static) -> Self {
+        self.tools.push(Box::new(tool));
+        self
+    }
+
+    /// Stream text in chunks of `chars` characters
+    pub fn with_chunk_size(mut self, chars: usize) -> Self {
+        self.chunk_chars = chars.max(1);
+        self
+    }
+
+    /// Vary chunk sizes, up to the chunk size, in an order fixed by `seed`
+    pub fn with_seed(self, seed: u64) -> Self {
+        *self.rng.lock().unwrap() = Some(StdRng::seed_from_u64(seed));
+        self
+    }
+
+    /// Queue a response on a service that's already shared
+    pub fn push(&self, response: MockResponse) {
+        self.responses.lock().unwrap().push_back(response);
+    }
+
+    /// Number of responses not yet given
+    pub fn remaining(&self) -> usize {
+        self.responses.lock().unwrap().len()
+    }
+
+    /// The messages of each request received so far
+    pub fn requests(&self) -> Vec<Vec<InternalChatMessage>> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// The `(prompt, completion)` tokens of each completed response reported so far
+    pub fn recorded_usage(&self) -> Vec<(u32, u32)> {
+        self.usage.lock().unwrap().clone()
+    }
+
+    fn next_response(&self, messages: &[InternalChatMessage]) -> Result<MockResponse, Error> {
+        self.requests.lock().unwrap().push(messages.to_vec());
+        self.responses
+            .lock()
+            .unwrap()
+            .pop_front()
+            .ok_or_else(|| anyhow!("MockAiService has no scripted responses left"))
+    }
+
+    fn tool_call(&self, name: String, args: Value) -> GenaiToolCall {
+        let mut count = self.tool_calls.lock().unwrap();
+        *count += 1;
+        GenaiToolCall {
+            call_id: format!("call_{}", count),
+            fn_name: name,
+            fn_arguments: args,
+        }
+    }
+
+    fn chunks(&self, text: &str) -> Vec<String> {
+        let mut rng = self.rng.lock().unwrap();
+        let chars: Vec<char> = text.chars().collect();
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        while start < chars.len() {
+            let size = match rng.as_mut() {
+                Some(rng) => rng.gen_range(1..=self.chunk_chars),
+                None => self.chunk_chars,
+            };
+            let end = (start + size).min(chars.len());
+            chunks.push(chars[start..end].iter().collect());
+            start = end;
+        }
+        chunks
+    }
+}
+
+impl Default for MockAiService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl AiService for MockAiService {
+    async fn generate_response(
+        &self,
+        messages: &[InternalChatMessage],
+        _params: &GenerationParams,
+    ) -> anyhow::Result<MessageContent> {
+        match self.next_response(messages)? {
+            MockResponse::Text(text) => Ok(MessageContent::Text(text)),
+            MockResponse::ToolCall { name, args } => {
+                Ok(MessageContent::ToolCalls(vec![self.tool_call(name, args)]))
+            }
+            MockResponse::Error(message) => Err(anyhow!(message)),
+        }
+    }
+
+    async fn generate_response_stream<'a>(
+        &'a self,
+        messages: &'a [InternalChatMessage],
+        _params: &GenerationParams,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<ChatStreamEvent, Error>> + Send + 'a>>, Error>
+    {
+        let mut events = vec![ChatStreamEvent::Start];
+        match self.next_response(messages)? {
+            MockResponse::Text(text) => {
+                events.extend(
+                    self.chunks(&text)
+                        .into_iter()
+                        .map(|content| ChatStreamEvent::Chunk(StreamChunk { content })),
+                );
+            }
+            MockResponse::ToolCall { name, args } => {
+                events.push(ChatStreamEvent::ToolCallChunk(ToolChunk {
+                    tool_call: self.tool_call(name, args),
+                }));
+            }
+            MockResponse::Error(message) => return Err(anyhow!(message)),
+        }
+        events.push(ChatStreamEvent::End(StreamEnd::default()));
+        Ok(Box::pin(futures_util::stream::iter(
+            events.into_iter().map(Ok),
+        )))
+    }
+
+    fn find_tool(&self, name: &str) -> Option<&dyn AiTool> {
+        self.tools
+            .iter()
+            .find(|tool| tool.name() == name)
+            .map(|tool| tool.as_ref())
+    }
+
+    async fn record_usage(&self, prompt_tokens: u32, completion_tokens: u32) -> Option<f64> {
+        self.usage
+            .lock()
+            .unwrap()
+            .push((prompt_tokens, completion_tokens));
+        None
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::streaming::{ChunkType, ResponseChunk, ResponseStreamManager, StreamConfig};
+    use futures_util::StreamExt;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_tool_call_then_answer_streams_in_order() {
+        let service = Arc::new(
+            MockAiService::new()
+                .with_chunk_size(5)
+                .with_response(MockResponse::ToolCall {
+                    name: "calculator".to_string(),
+                    args: json!({ "expression": "6 * 7" }),
+                })
+                .with_response(MockResponse::Text("The answer is 42.".to_string()))
+                .with_response(MockResponse::Error("quota exceeded".to_string())),
+        );
+        let manager = ResponseStreamManager::new();
+        manager
+            .update_config(StreamConfig {
+                coalesce_ms: 0,
+                coalesce_chars: 0,
+                ..StreamConfig::default()
+            })
+            .await
+            .unwrap();
+        let messages = vec![InternalChatMessage::User {
+            content: "What is 6 * 7?".to_string(),
+        }];
+        let stream = manager
+            .stream_genai_response("session_1".to_string(), service.clone(), messages.clone())
+            .await
+            .unwrap();
+        let chunks: Vec<ResponseChunk> = stream.collect().await;
+
+        // No calculator is offered, so the model is told the tool wasn't found
+        let types: Vec<_> = chunks
+            .iter()
+            .map(|chunk| chunk.chunk_type.clone())
+            .collect();
+        assert_eq!(
+            types,
+            [
+                ChunkType::Status,
+                ChunkType::ToolCall,
+                ChunkType::ToolResponse,
+                ChunkType::Status,
+                ChunkType::Text,
+                ChunkType::Text,
+                ChunkType::Text,
+                ChunkType::Text,
+                ChunkType::Complete,
+            ]
+        );
+        let custom = &chunks[1].metadata.custom;
+        assert_eq!(custom["tool_name"], "calculator");
+        assert_eq!(custom["tool_args"], json!({ "expression": "6 * 7" }));
+        assert_eq!(custom["call_id"], "call_1");
+        let text: Vec<&str> = chunks[4..8]
+            .iter()
+            .map(|chunk| chunk.content.as_str())
+            .collect();
+        assert_eq!(text, ["The a", "nswer", " is 4", "2."]);
+        // Usage is reported once, when the answer completes
+        assert_eq!(service.recorded_usage().len(), 1);
+
+        let params = GenerationParams::default();
+        let error = service
+            .generate_response(&messages, &params)
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "quota exceeded");
+        assert_eq!(service.remaining(), 0);
+        assert!(service.generate_response(&messages, &params).await.is_err());
+        assert_eq!(service.requests().len(), 4);
+    }
+
+    #[test]
+    fn test_seeded_chunking_is_reproducible() {
+        let text = "The quick brown fox jumps over the lazy dog";
+        let chunks = |seed| MockAiService::new().with_seed(seed).chunks(text);
+
+        assert_eq!(chunks(7), chunks(7));
+        assert_eq!(chunks(7).concat(), text);
+        assert!(
+            chunks(7)
+                .iter()
+                .all(|chunk| chunk.chars().count() <= DEFAULT_CHUNK_CHARS)
+        );
+    }
+}
@@ -3,7 +3,14 @@
 //! This module provides real-time response streaming capabilities with typing indicators,
 //! progress tracking, and smooth UI updates for both TUI and API interfaces.
 
-use crate::llm::{AiService, InternalChatMessage};
+use crate::llm::{AiService, GenerationParams, InternalChatMessage, LLMService};
+use super::coalesce::TextCoalescer;
+use super::progress::{ProgressEstimate, ProgressEstimator, ResponsePhase};
+use super::reasoning::ThinkTagSplitter;
+use super::stop::stop_at_sequences;
+use crate::telemetry;
+use crate::tools::{ToolErrorKind, ToolPolicy, ToolResult};
+use luts_core::utils::tokenizer::TokenCounter;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use futures_util::{Stream, StreamExt};
@@ -13,9 +20,9 @@ use std::collections::HashMap;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
-use tokio::sync::{RwLock, broadcast, mpsc};
+use tokio::sync::{RwLock, broadcast, mpsc, watch};
 use tokio_stream::wrappers::ReceiverStream;
-use tracing::{debug, info, warn};
+use tracing::{Instrument, debug, info, info_span, warn};
 
 /// Streaming response chunk
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -89,6 +96,14 @@ pub struct TypingIndicator {
     pub progress_percent: Option<u8>,
 }
 
+impl TypingIndicator {
+    /// Whole seconds until the projected completion, if one is known
+    pub fn seconds_remaining(&self) -> Option<i64> {
+        self.estimated_completion
+            .map(|completion| (completion - Utc::now()).num_seconds().max(0))
+    }
+}
+
 /// Typing status states
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TypingStatus {
@@ -119,6 +134,18 @@ pub struct StreamConfig {
     pub max_chunk_delay_ms: u64,
     /// Enable progress estimation
     pub enable_progress_estimation: bool,
+    /// Update the typing indicator's progress every this many content chunks
+    #[serde(default = "default_progress_update_interval")]
+    pub progress_update_interval: usize,
+    /// Hold streamed text back for up to this many ms and send it as one chunk (0 = off)
+    #[serde(default = "default_coalesce_ms")]
+    pub coalesce_ms: u64,
+    /// Send held-back text once it reaches this many characters (0 = no size limit)
+    #[serde(default = "default_coalesce_chars")]
+    pub coalesce_chars: usize,
+    /// Model turns that may end in tool calls before the response is cut off
+    #[serde(default = "default_max_tool_iterations")]
+    pub max_tool_iterations: usize,
     /// Buffer size for streaming
     pub buffer_size: usize,
     /// Timeout for streaming responses
@@ -136,6 +163,10 @@ impl Default for StreamConfig {
             min_chunk_delay_ms: 10,
             max_chunk_delay_ms: 100,
             enable_progress_estimation: true,
+            progress_update_interval: default_progress_update_interval(),
+            coalesce_ms: default_coalesce_ms(),
+            coalesce_chars: default_coalesce_chars(),
+            max_tool_iterations: default_max_tool_iterations(),
             buffer_size: 1000,
             stream_timeout_seconds: 300, // 5 minute timeout
             enable_chunk_compression: false,
@@ -143,6 +174,22 @@ impl Default for StreamConfig {
     }
 }
 
+fn default_progress_update_interval() -> usize {
+    5
+}
+
+fn default_coalesce_ms() -> u64 {
+    30
+}
+
+fn default_coalesce_chars() -> usize {
+    200
+}
+
+fn default_max_tool_iterations() -> usize {
+    10
+}
+
 /// Streaming response stats
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StreamingStats {
@@ -165,13 +212,109 @@ pub struct ResponseStreamManager {
     /// Configuration
     config: RwLock<StreamConfig>,
     /// Active streams
-    active_streams: RwLock<HashMap<String, StreamSession>>,
+    active_streams: Arc<RwLock<HashMap<String, StreamSession>>>,
     /// Typing indicators
-    typing_indicators: RwLock<HashMap<String, TypingIndicator>>,
+    typing_indicators: Arc<RwLock<HashMap<String, TypingIndicator>>>,
     /// Event broadcaster for UI updates
     event_sender: broadcast::Sender<StreamEvent>,
     /// Statistics
-    stats: RwLock<StreamingStats>,
+    stats: Arc<RwLock<StreamingStats>>,
+}
+
+/// What a streaming task sent before it finished
+#[derive(Debug, Clone, Copy, Default)]
+struct StreamTotals {
+    chunks: u64,
+    characters: u64,
+}
+
+/// Handle a spawned streaming task uses to report back to its manager
+struct StreamTracker {
+    session_id: String,
+    started_at: DateTime<Utc>,
+    active_streams: Arc<RwLock<HashMap<String, StreamSession>>>,
+    typing_indicators: Arc<RwLock<HashMap<String, TypingIndicator>>>,
+    event_sender: broadcast::Sender<StreamEvent>,
+    stats: Arc<RwLock<StreamingStats>>,
+    /// Flipped to `true` when the session is cancelled
+    cancelled: watch::Receiver<bool>,
+}
+
+impl StreamTracker {
+    /// Whether the session has been cancelled
+    fn is_cancelled(&self) -> bool {
+        *self.cancelled.borrow()
+    }
+
+    /// Resolves once the session is cancelled
+    ///
+    /// Never resolves if the session is dropped without being cancelled.
+    async fn wait_for_cancel(&self) {
+        let mut cancelled = self.cancelled.clone();
+        if cancelled.wait_for(|cancelled| *cancelled).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+
+    /// Update the session's typing indicator, if it has one
+    async fn update_typing_status(&self, status: TypingStatus, estimate: Option<ProgressEstimate>) {
+        set_typing_status(
+            &self.typing_indicators,
+            &self.event_sender,
+            &self.session_id,
+            status,
+            estimate,
+        )
+        .await;
+    }
+
+    /// Record the task's outcome, then drop its session
+    ///
+    /// Removing the session drops the manager's copy of the chunk sender, so
+    /// the `StreamableResponse` ends once the task's own sender is gone.
+    async fn finish(self, outcome: Result<StreamTotals>) {
+        let totals = match outcome {
+            Ok(totals) => totals,
+            Err(e) => {
+                warn!("Streaming error for session {}: {}", self.session_id, e);
+                let _ = self.event_sender.send(StreamEvent::StreamError {
+                    session_id: self.session_id.clone(),
+                    error: e.to_string(),
+                });
+                StreamTotals::default()
+            }
+        };
+
+        {
+            let duration_ms = (Utc::now() - self.started_at).num_milliseconds().max(0) as u64;
+            let mut stats = self.stats.write().await;
+            stats.total_chunks += totals.chunks;
+            stats.total_characters += totals.characters;
+            stats.total_stream_time_ms += duration_ms;
+            if stats.total_chunks > 0 {
+                stats.avg_chunk_size = stats.total_characters as f64 / stats.total_chunks as f64;
+            }
+            if stats.total_stream_time_ms > 0 {
+                stats.chars_per_second =
+                    stats.total_characters as f64 / stats.total_stream_time_ms as f64 * 1000.0;
+            }
+        }
+
+        if let Some(indicator) = self.typing_indicators.write().await.remove(&self.session_id) {
+            let _ = self.event_sender.send(StreamEvent::TypingStatusChanged {
+                session_id: self.session_id.clone(),
+                indicator: TypingIndicator {
+                    status: TypingStatus::Stopped,
+                    last_activity: Utc::now(),
+                    estimated_completion: None,
+                    ..indicator
+                },
+            });
+        }
+
+        self.active_streams.write().await.remove(&self.session_id);
+        debug!("Cleaned up stream session: {}", self.session_id);
+    }
 }
 
 /// Individual streaming session
@@ -187,10 +330,13 @@ struct StreamSession {
     chunks_sent: u64,
     /// Total characters sent
     characters_sent: u64,
+    /// Set to true by `cancel_stream`
+    cancel: watch::Sender<bool>,
 }
 
 /// Stream events for UI updates
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum StreamEvent {
     /// New chunk received
     ChunkReceived {
@@ -213,15 +359,41 @@ pub enum StreamEvent {
     },
     /// Stream error
     StreamError { session_id: String, error: String },
+    /// Stream cancelled before it completed
+    StreamCancelled { session_id: String },
 }
 
 /// Streamable response wrapper
-#[allow(dead_code)]
 pub struct StreamableResponse {
     receiver: ReceiverStream<ResponseChunk>,
     session_id: String,
 }
 
+impl StreamableResponse {
+    /// Wrap a channel fed by some other producer, e.g. an agent's tool loop
+    pub fn from_receiver(session_id: String, receiver: mpsc::Receiver<ResponseChunk>) -> Self {
+        Self {
+            receiver: ReceiverStream::new(receiver),
+            session_id,
+        }
+    }
+
+    /// Stream that yields `chunks` and then ends
+    pub fn from_chunks(session_id: String, chunks: Vec<ResponseChunk>) -> Self {
+        let (sender, receiver) = mpsc::channel(chunks.len().max(1));
+        for chunk in chunks {
+            // Capacity covers every chunk, so this never fails
+            let _ = sender.try_send(chunk);
+        }
+        Self::from_receiver(session_id, receiver)
+    }
+
+    /// Session this response belongs to
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+}
+
 impl Stream for StreamableResponse {
     type Item = ResponseChunk;
 
@@ -237,17 +409,17 @@ impl ResponseStreamManager {
 
         Self {
             config: RwLock::new(StreamConfig::default()),
-            active_streams: RwLock::new(HashMap::new()),
-            typing_indicators: RwLock::new(HashMap::new()),
+            active_streams: Arc::new(RwLock::new(HashMap::new())),
+            typing_indicators: Arc::new(RwLock::new(HashMap::new())),
             event_sender,
-            stats: RwLock::new(StreamingStats {
+            stats: Arc::new(RwLock::new(StreamingStats {
                 total_chunks: 0,
                 total_characters: 0,
                 avg_chunk_size: 0.0,
                 total_stream_time_ms: 0,
                 chars_per_second: 0.0,
                 active_streams: 0,
-            }),
+            })),
         }
     }
 
@@ -281,12 +453,14 @@ impl ResponseStreamManager {
         }
 
         // Create stream session
+        let (cancel, cancelled) = watch::channel(false);
         let stream_session = StreamSession {
             session_id: session_id.clone(),
             chunk_sender: chunk_sender.clone(),
             started_at: Utc::now(),
             chunks_sent: 0,
             characters_sent: 0,
+            cancel,
         };
 
         self.active_streams
@@ -300,24 +474,24 @@ impl ResponseStreamManager {
         });
 
         // Spawn background task for streaming
-        let session_id_clone = session_id.clone();
         let config_clone = config.clone();
-        let event_sender = self.event_sender.clone();
-
-        tokio::spawn(async move {
-            if let Err(e) = Self::stream_response_task(
-                session_id_clone,
-                ai_service,
-                messages,
-                chunk_sender,
-                config_clone,
-                event_sender,
-            )
-            .await
-            {
-                warn!("Streaming error: {}", e);
+        let tracker = self.tracker(&session_id, cancelled);
+        let span = info_span!("stream_response", session_id = %session_id);
+
+        tokio::spawn(
+            async move {
+                let outcome = Self::stream_response_task(
+                    ai_service,
+                    messages,
+                    chunk_sender,
+                    config_clone,
+                    &tracker,
+                )
+                .await;
+                tracker.finish(outcome).await;
             }
-        });
+            .instrument(span),
+        );
 
         Ok(StreamableResponse {
             receiver: ReceiverStream::new(chunk_receiver),
@@ -349,25 +523,21 @@ impl ResponseStreamManager {
         });
     }
 
-    /// Update typing status
+    /// Update typing status and progress
     pub async fn update_typing_status(
         &self,
         session_id: &str,
         status: TypingStatus,
-        progress: Option<u8>,
+        estimate: Option<ProgressEstimate>,
     ) {
-        let mut indicators = self.typing_indicators.write().await;
-        if let Some(indicator) = indicators.get_mut(session_id) {
-            indicator.status = status;
-            indicator.last_activity = Utc::now();
-            indicator.progress_percent = progress;
-
-            // Broadcast updated typing event
-            let _ = self.event_sender.send(StreamEvent::TypingStatusChanged {
-                session_id: session_id.to_string(),
-                indicator: indicator.clone(),
-            });
-        }
+        set_typing_status(
+            &self.typing_indicators,
+            &self.event_sender,
+            session_id,
+            status,
+            estimate,
+        )
+        .await;
     }
 
     /// Stop typing indicator
@@ -408,25 +578,109 @@ impl ResponseStreamManager {
         stats
     }
 
+    /// Cancel an in-flight stream
+    ///
+    /// The streaming task stops at its next await point. A tool call that is
+    /// still running is abandoned and reported in an aborted tool response
+    /// chunk; the response stream then ends without a completion chunk.
+    /// Returns false if no stream is active for `session_id`.
+    pub async fn cancel_stream(&self, session_id: &str) -> bool {
+        let Some(session) = self.active_streams.write().await.remove(session_id) else {
+            return false;
+        };
+        let _ = session.cancel.send(true);
+        self.stop_typing_indicator(session_id).await;
+
+        let _ = self.event_sender.send(StreamEvent::StreamCancelled {
+            session_id: session_id.to_string(),
+        });
+        info!("Cancelled stream for session: {}", session_id);
+        true
+    }
+
     /// Stream response from an AI service with live genai streaming and tool calling
     pub async fn stream_genai_response(
         &self,
         session_id: String,
         ai_service: Arc<dyn AiService>,
         messages: Vec<InternalChatMessage>,
+    ) -> Result<StreamableResponse> {
+        self.stream_genai_response_with_params(
+            session_id,
+            ai_service,
+            messages,
+            GenerationParams::default(),
+        )
+        .await
+    }
+
+    /// Like [`stream_genai_response`](Self::stream_genai_response), with
+    /// sampling settings for this request
+    ///
+    /// The text is cut at the first of `params.stop`, whether or not the
+    /// provider honours stop sequences itself.
+    pub async fn stream_genai_response_with_params(
+        &self,
+        session_id: String,
+        ai_service: Arc<dyn AiService>,
+        messages: Vec<InternalChatMessage>,
+        params: GenerationParams,
+    ) -> Result<StreamableResponse> {
+        self.stream_genai_response_with_policy(
+            session_id,
+            ai_service,
+            messages,
+            params,
+            ToolPolicy::default(),
+        )
+        .await
+    }
+
+    /// Like [`stream_genai_response_with_params`](Self::stream_genai_response_with_params),
+    /// running only the tools `tool_policy` allows
+    ///
+    /// Tools the policy refuses aren't run; the model is sent a
+    /// [`ToolErrorKind::Refused`] result in their place.
+    pub async fn stream_genai_response_with_policy(
+        &self,
+        session_id: String,
+        ai_service: Arc<dyn AiService>,
+        messages: Vec<InternalChatMessage>,
+        params: GenerationParams,
+        tool_policy: ToolPolicy,
     ) -> Result<StreamableResponse> {
         let (chunk_sender, chunk_receiver) = mpsc::channel(1000);
 
         let config = self.config.read().await.clone();
         let event_sender = self.event_sender.clone();
 
+        // Chunks pass through here on their way out so subscribers see them too
+        let (task_sender, mut task_receiver) = mpsc::channel::<ResponseChunk>(1000);
+        tokio::spawn({
+            let session_id = session_id.clone();
+            let event_sender = event_sender.clone();
+            async move {
+                while let Some(chunk) = task_receiver.recv().await {
+                    let _ = event_sender.send(StreamEvent::ChunkReceived {
+                        session_id: session_id.clone(),
+                        chunk: chunk.clone(),
+                    });
+                    if chunk_sender.send(chunk).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
         // Start streaming session
+        let (cancel, cancelled) = watch::channel(false);
         let session_info = StreamSession {
             session_id: session_id.clone(),
-            chunk_sender: chunk_sender.clone(),
+            chunk_sender: task_sender.clone(),
             started_at: Utc::now(),
             chunks_sent: 0,
             characters_sent: 0,
+            cancel,
         };
 
         self.active_streams
@@ -434,20 +688,36 @@ impl ResponseStreamManager {
             .await
             .insert(session_id.clone(), session_info);
 
+        if config.enable_typing_indicators {
+            self.start_typing_indicator(session_id.clone(), "Assistant".to_string())
+                .await;
+        }
+
         // Send stream started event
         let _ = event_sender.send(StreamEvent::StreamStarted {
             session_id: session_id.clone(),
         });
 
-        // Spawn genai streaming task
-        tokio::spawn(Self::genai_stream_task(
-            session_id.clone(),
-            ai_service,
-            messages,
-            chunk_sender,
-            config.clone(),
-            event_sender.clone(),
-        ));
+        // Spawn genai streaming task; the turn's model calls and tool runs
+        // are traced under its span
+        let tracker = self.tracker(&session_id, cancelled);
+        let span = info_span!("stream_response", session_id = %session_id);
+        tokio::spawn(
+            async move {
+                let outcome = Self::genai_stream_task(
+                    ai_service,
+                    messages,
+                    params,
+                    tool_policy,
+                    task_sender,
+                    config,
+                    &tracker,
+                )
+                .await;
+                tracker.finish(outcome).await;
+            }
+            .instrument(span),
+        );
 
         Ok(StreamableResponse {
             receiver: ReceiverStream::new(chunk_receiver),
@@ -457,19 +727,36 @@ impl ResponseStreamManager {
 
     // Private helper methods
 
+    /// Handle for the task streaming `session_id`
+    fn tracker(&self, session_id: &str, cancelled: watch::Receiver<bool>) -> StreamTracker {
+        StreamTracker {
+            session_id: session_id.to_string(),
+            started_at: Utc::now(),
+            active_streams: self.active_streams.clone(),
+            typing_indicators: self.typing_indicators.clone(),
+            event_sender: self.event_sender.clone(),
+            stats: self.stats.clone(),
+            cancelled,
+        }
+    }
+
     async fn stream_response_task(
-        session_id: String,
         ai_service: Arc<dyn AiService>,
         messages: Vec<InternalChatMessage>,
         chunk_sender: mpsc::Sender<ResponseChunk>,
         config: StreamConfig,
-        event_sender: broadcast::Sender<StreamEvent>,
-    ) -> Result<()> {
+        tracker: &StreamTracker,
+    ) -> Result<StreamTotals> {
+        let session_id = tracker.session_id.clone();
         let start_time = Utc::now();
         let mut sequence = 0u64;
 
         // Generate response (this would ideally be streaming from the AI service)
-        let response = ai_service.generate_response(&messages).await?;
+        let params = GenerationParams::default();
+        let response = tokio::select! {
+            response = ai_service.generate_response(&messages, &params) => response?,
+            _ = tracker.wait_for_cancel() => return Ok(StreamTotals::default()),
+        };
 
         let content = match response {
             genai::chat::MessageContent::Text(text) => text,
@@ -477,10 +764,19 @@ impl ResponseStreamManager {
         };
 
         // Stream the response in chunks
+        let counter = token_counter(ai_service.as_ref());
         let mut total_chars = 0u64;
         let chars: Vec<char> = content.chars().collect();
 
         for chunk_start in (0..chars.len()).step_by(config.chunk_size) {
+            if tracker.is_cancelled() {
+                info!("Stream cancelled for session: {}", session_id);
+                return Ok(StreamTotals {
+                    chunks: sequence,
+                    characters: total_chars,
+                });
+            }
+
             let chunk_end = (chunk_start + config.chunk_size).min(chars.len());
             let chunk_content: String = chars[chunk_start..chunk_end].iter().collect();
             let is_final = chunk_end >= chars.len();
@@ -497,9 +793,7 @@ impl ResponseStreamManager {
                     ChunkType::Text
                 },
                 metadata: ChunkMetadata {
-                    token_count: Some(
-                        (chunk_content.split_whitespace().count() as f32 * 1.3) as u32,
-                    ),
+                    token_count: Some(counter.count(&chunk_content) as u32),
                     processing_time_ms: None,
                     model: Some("streaming_model".to_string()),
                     confidence: None,
@@ -513,7 +807,7 @@ impl ResponseStreamManager {
             }
 
             // Broadcast chunk event
-            let _ = event_sender.send(StreamEvent::ChunkReceived {
+            let _ = tracker.event_sender.send(StreamEvent::ChunkReceived {
                 session_id: session_id.clone(),
                 chunk: chunk.clone(),
             });
@@ -528,23 +822,28 @@ impl ResponseStreamManager {
             );
             tokio::time::sleep(tokio::time::Duration::from_millis(delay)).await;
 
-            // Update progress if enabled (simplified - would need manager reference for full implementation)
-            if config.enable_progress_estimation {
-                let progress = ((chunk_end as f64 / chars.len() as f64) * 100.0) as u8;
-                // Note: In full implementation, would update typing status via manager
-                debug!("Progress: {}%", progress);
+            // The full response is known here, so progress is exact
+            if config.enable_progress_estimation && !is_final {
+                let remaining_chunks = (chars.len() - chunk_end).div_ceil(config.chunk_size);
+                let estimate = ProgressEstimate {
+                    percent: ((chunk_end as f64 / chars.len() as f64) * 100.0) as u8,
+                    estimated_completion: Some(
+                        Utc::now()
+                            + chrono::Duration::milliseconds((remaining_chunks as u64 * delay) as i64),
+                    ),
+                };
+                debug!("Progress: {}%", estimate.percent);
+                tracker
+                    .update_typing_status(TypingStatus::Typing, Some(estimate))
+                    .await;
             }
         }
 
         let duration = Utc::now().signed_duration_since(start_time);
         let duration_ms = duration.num_milliseconds() as u64;
 
-        // Note: In full implementation, would stop typing indicator and update stats via manager
-        // manager.stop_typing_indicator(&session_id).await;
-        // manager.active_streams.write().await.remove(&session_id);
-
         // Broadcast completion event
-        let _ = event_sender.send(StreamEvent::StreamCompleted {
+        let _ = tracker.event_sender.send(StreamEvent::StreamCompleted {
             session_id: session_id.clone(),
             total_chunks: sequence,
             total_characters: total_chars,
@@ -555,328 +854,128 @@ impl ResponseStreamManager {
             "Completed streaming response: {} chars in {}ms",
             total_chars, duration_ms
         );
-        Ok(())
+        Ok(StreamTotals {
+            chunks: sequence,
+            characters: total_chars,
+        })
     }
 
     // Genai streaming task with tool calling support
     async fn genai_stream_task(
-        session_id: String,
         ai_service: Arc<dyn AiService>,
-        messages: Vec<InternalChatMessage>,
+        mut messages: Vec<InternalChatMessage>,
+        params: GenerationParams,
+        tool_policy: ToolPolicy,
         chunk_sender: mpsc::Sender<ResponseChunk>,
-        _config: StreamConfig,
-        event_sender: broadcast::Sender<StreamEvent>,
-    ) -> Result<()> {
+        config: StreamConfig,
+        tracker: &StreamTracker,
+    ) -> Result<StreamTotals> {
+        let session_id = tracker.session_id.clone();
         let start_time = Utc::now();
         let mut sequence = 0u64;
         let mut total_chars = 0u64;
+        let counter = token_counter(ai_service.as_ref());
+        let mut tool_calls: Vec<genai::chat::ToolCall> = Vec::new();
+        // Usage of every model turn of the response
+        let mut prompt_tokens = 0u32;
+        let mut completion_tokens = 0u32;
 
         debug!("Starting genai streaming for session: {}", session_id);
 
-        // Get streaming response from AI service
-        let mut stream = ai_service.generate_response_stream(&messages).await?;
-
-        let mut accumulated_text = String::new();
-        let mut tool_calls: Vec<genai::chat::ToolCall> = Vec::new();
+        // Each model turn that calls tools is followed by one that sees their results
+        for iteration in 0..=config.max_tool_iterations {
+            // Get streaming response from AI service
+            let mut stream = tokio::select! {
+                stream = ai_service.generate_response_stream(&messages, &params) => {
+                    stop_at_sequences(stream?, params.stop.clone())
+                }
+                _ = tracker.wait_for_cancel() => {
+                    info!("Stream cancelled for session: {}", session_id);
+                    break;
+                }
+            };
 
-        // Process stream events
-        while let Some(event_result) = stream.next().await {
-            match event_result {
-                Ok(event) => {
-                    debug!("Received stream event: {:?}", event);
-
-                    match event {
-                        ChatStreamEvent::Start => {
-                            info!("Stream started for session: {}", session_id);
-
-                            // Send typing indicator
-                            let chunk = ResponseChunk {
-                                id: format!("{}_{}", session_id, sequence),
-                                sequence,
-                                content: "".to_string(),
-                                is_final: false,
-                                timestamp: Utc::now(),
-                                chunk_type: ChunkType::Status,
-                                metadata: ChunkMetadata {
-                                    token_count: None,
-                                    processing_time_ms: None,
-                                    model: None,
-                                    confidence: None,
-                                    custom: HashMap::new(),
-                                },
-                            };
-
-                            if chunk_sender.send(chunk).await.is_err() {
-                                warn!("Failed to send status chunk for session: {}", session_id);
-                                break;
-                            }
-                            sequence += 1;
+            let prompt_estimate: u32 = messages
+                .iter()
+                .map(|m| counter.count(m.content()) as u32)
+                .sum();
+            // Answer text of this turn, sent back with the tool results
+            let mut accumulated_text = String::new();
+            let mut tool_messages = Vec::new();
+            // Whether the turn ran to its end rather than being cut short
+            let mut ended = false;
+            let mut exhausted = false;
+            let mut progress = ProgressEstimator::new(prompt_estimate);
+            let mut content_chunks = 0usize;
+            // Pulls inline <think> reasoning out of text chunks
+            let mut think_tags = ThinkTagSplitter::new();
+            let mut coalescer = TextCoalescer::new(config.coalesce_ms, config.coalesce_chars);
+            // Sum of the per-chunk estimates, used when the provider reports no usage
+            let mut streamed_tokens = 0u32;
+
+            // Process stream events until the stream ends or is cancelled
+            'events: loop {
+                let event_result = tokio::select! {
+                    event = stream.next() => match event {
+                        Some(event) => event,
+                        None => {
+                            exhausted = true;
+                            break;
                         }
-
-                        ChatStreamEvent::End(_m) => {
-                            info!("Stream ended for session: {}", session_id);
-
-                            // Send final completion chunk
-                            let duration_ms = (Utc::now() - start_time).num_milliseconds() as u64;
-
-                            let chunk = ResponseChunk {
-                                id: format!("{}_{}", session_id, sequence),
-                                sequence,
-                                content: "".to_string(),
-                                is_final: true,
-                                timestamp: Utc::now(),
-                                chunk_type: ChunkType::Complete,
-                                metadata: ChunkMetadata {
-                                    token_count: None,
-                                    processing_time_ms: Some(duration_ms),
-                                    model: None,
-                                    confidence: None,
-                                    custom: {
-                                        let mut custom = HashMap::new();
-                                        custom.insert(
-                                            "total_chunks".to_string(),
-                                            serde_json::Value::Number(sequence.into()),
-                                        );
-                                        custom.insert(
-                                            "total_characters".to_string(),
-                                            serde_json::Value::Number(total_chars.into()),
-                                        );
-                                        custom.insert(
-                                            "tool_calls_count".to_string(),
-                                            serde_json::Value::Number(tool_calls.len().into()),
-                                        );
-                                        custom
-                                    },
-                                },
-                            };
-
-                            if chunk_sender.send(chunk).await.is_err() {
-                                warn!(
-                                    "Failed to send completion chunk for session: {}",
-                                    session_id
-                                );
-                            }
-
-                            // Send stream completed event
-                            let _ = event_sender.send(StreamEvent::StreamCompleted {
-                                session_id: session_id.clone(),
-                                total_chunks: sequence,
-                                total_characters: total_chars,
-                                duration_ms,
-                            });
-
+                    },
+                    _ = tracker.wait_for_cancel() => {
+                        info!("Stream cancelled for session: {}", session_id);
+                        break;
+                    }
+                    _ = sleep_until_due(coalescer.deadline()) => {
+                        if !flush_coalesced(
+                            &mut coalescer,
+                            &chunk_sender,
+                            &session_id,
+                            &mut sequence,
+                            start_time,
+                        )
+                        .await
+                        {
                             break;
                         }
-
-                        ChatStreamEvent::ToolCallChunk(t) => {
-                            // Handle tool call chunk with proper formatting
-                            debug!("Received tool call chunk: {:?}", t);
-
-                            // Store the tool call for execution
-                            tool_calls.push(t.tool_call.clone());
-
-                            // Create a formatted tool call chunk for UI
-                            let tool_content = format!(
-                                "🔧 Calling {} with args: {}",
-                                t.tool_call.fn_name,
-                                serde_json::to_string(&t.tool_call.fn_arguments)
-                                    .unwrap_or_else(|_| "{}".to_string())
-                            );
-
-                            let chunk = ResponseChunk {
-                                id: format!("{}_{}", session_id, sequence),
-                                sequence,
-                                content: tool_content,
-                                is_final: false,
-                                timestamp: Utc::now(),
-                                chunk_type: ChunkType::ToolCall,
-                                metadata: ChunkMetadata {
-                                    token_count: None,
-                                    processing_time_ms: Some(
-                                        (Utc::now() - start_time).num_milliseconds() as u64,
-                                    ),
-                                    model: None,
-                                    confidence: None,
-                                    custom: {
-                                        let mut custom = HashMap::new();
-                                        custom.insert(
-                                            "tool_name".to_string(),
-                                            serde_json::Value::String(t.tool_call.fn_name.clone()),
-                                        );
-                                        custom.insert(
-                                            "tool_args".to_string(),
-                                            t.tool_call.fn_arguments.clone(),
-                                        );
-                                        custom
-                                    },
-                                },
-                            };
-
-                            if chunk_sender.send(chunk).await.is_err() {
-                                warn!("Failed to send tool call chunk for session: {}", session_id);
-                                break;
-                            }
-                            sequence += 1;
-
-                            // Execute the tool call if we have access to the LLM service
-                            if let Some(llm_service) = ai_service.as_any().downcast_ref::<crate::llm::LLMService>() {
-                                if let Some(tool) = llm_service.find_tool(&t.tool_call.fn_name) {
-                                    debug!("Executing tool: {}", t.tool_call.fn_name);
-                                    
-                                    // Execute the tool
-                                    match tool.execute(t.tool_call.fn_arguments.clone()).await {
-                                        Ok(result) => {
-                                            debug!("Tool {} executed successfully: {:?}", t.tool_call.fn_name, result);
-                                            
-                                            // Send tool result chunk
-                                            let result_content = format!("✅ Tool result: {}", serde_json::to_string(&result).unwrap_or_else(|_| result.to_string()));
-                                            
-                                            let result_chunk = ResponseChunk {
-                                                id: format!("{}_{}", session_id, sequence),
-                                                sequence,
-                                                content: result_content,
-                                                is_final: false,
-                                                timestamp: Utc::now(),
-                                                chunk_type: ChunkType::ToolResponse,
-                                                metadata: ChunkMetadata {
-                                                    token_count: None,
-                                                    processing_time_ms: Some(
-                                                        (Utc::now() - start_time).num_milliseconds() as u64,
-                                                    ),
-                                                    model: None,
-                                                    confidence: None,
-                                                    custom: {
-                                                        let mut custom = HashMap::new();
-                                                        custom.insert(
-                                                            "tool_name".to_string(),
-                                                            serde_json::Value::String(t.tool_call.fn_name.clone()),
-                                                        );
-                                                        custom.insert(
-                                                            "tool_result".to_string(),
-                                                            result.clone(),
-                                                        );
-                                                        custom
-                                                    },
-                                                },
-                                            };
-
-                                            if chunk_sender.send(result_chunk).await.is_err() {
-                                                warn!("Failed to send tool result chunk for session: {}", session_id);
-                                                break;
-                                            }
-                                            sequence += 1;
-                                        }
-                                        Err(e) => {
-                                            warn!("Tool {} execution failed: {}", t.tool_call.fn_name, e);
-                                            
-                                            // Send error chunk  
-                                            let error_content = format!("❌ Tool error: {}", e);
-                                            
-                                            let error_chunk = ResponseChunk {
-                                                id: format!("{}_{}", session_id, sequence),
-                                                sequence,
-                                                content: error_content,
-                                                is_final: false,
-                                                timestamp: Utc::now(),
-                                                chunk_type: ChunkType::ToolResponse,
-                                                metadata: ChunkMetadata {
-                                                    token_count: None,
-                                                    processing_time_ms: Some(
-                                                        (Utc::now() - start_time).num_milliseconds() as u64,
-                                                    ),
-                                                    model: None,
-                                                    confidence: None,
-                                                    custom: {
-                                                        let mut custom = HashMap::new();
-                                                        custom.insert(
-                                                            "tool_name".to_string(),
-                                                            serde_json::Value::String(t.tool_call.fn_name.clone()),
-                                                        );
-                                                        custom.insert(
-                                                            "error".to_string(),
-                                                            serde_json::Value::String(e.to_string()),
-                                                        );
-                                                        custom
-                                                    },
-                                                },
-                                            };
-
-                                            if chunk_sender.send(error_chunk).await.is_err() {
-                                                warn!("Failed to send tool error chunk for session: {}", session_id);
-                                                break;
-                                            }
-                                            sequence += 1;
-                                        }
-                                    }
-                                } else {
-                                    warn!("Tool not found: {}", t.tool_call.fn_name);
-                                    
-                                    // Send tool not found error
-                                    let error_content = format!("❌ Tool error: Tool '{}' not found", t.tool_call.fn_name);
-                                    
-                                    let error_chunk = ResponseChunk {
-                                        id: format!("{}_{}", session_id, sequence),
-                                        sequence,
-                                        content: error_content,
-                                        is_final: false,
-                                        timestamp: Utc::now(),
-                                        chunk_type: ChunkType::ToolResponse,
-                                        metadata: ChunkMetadata {
-                                            token_count: None,
-                                            processing_time_ms: Some(
-                                                (Utc::now() - start_time).num_milliseconds() as u64,
-                                            ),
-                                            model: None,
-                                            confidence: None,
-                                            custom: {
-                                                let mut custom = HashMap::new();
-                                                custom.insert(
-                                                    "tool_name".to_string(),
-                                                    serde_json::Value::String(t.tool_call.fn_name.clone()),
-                                                );
-                                                custom.insert(
-                                                    "error".to_string(),
-                                                    serde_json::Value::String(format!("Tool '{}' not found", t.tool_call.fn_name)),
-                                                );
-                                                custom
-                                            },
-                                        },
-                                    };
-
-                                    if chunk_sender.send(error_chunk).await.is_err() {
-                                        warn!("Failed to send tool not found error chunk for session: {}", session_id);
-                                        break;
-                                    }
-                                    sequence += 1;
-                                }
-                            } else {
-                                warn!("Cannot execute tools: AI service is not an LLMService instance");
-                            }
+                        continue;
+                    }
+                };
+
+                match event_result {
+                    Ok(event) => {
+                        debug!("Received stream event: {:?}", event);
+
+                        // Held-back text goes out before anything that isn't more text
+                        if !matches!(event, ChatStreamEvent::Chunk(_))
+                            && !flush_coalesced(
+                                &mut coalescer,
+                                &chunk_sender,
+                                &session_id,
+                                &mut sequence,
+                                start_time,
+                            )
+                            .await
+                        {
+                            break;
                         }
 
-                        ChatStreamEvent::ReasoningChunk(c) => {
-                            // Handle reasoning chunk
-                            debug!("Received reasoning chunk: {:?}", c);
-                            let content = c.content;
-                            if !content.is_empty() {
-                                accumulated_text.push_str(&content);
-                                total_chars += content.len() as u64;
+                        match event {
+                            ChatStreamEvent::Start => {
+                                info!("Stream started for session: {}", session_id);
 
+                                // Send typing indicator
                                 let chunk = ResponseChunk {
                                     id: format!("{}_{}", session_id, sequence),
                                     sequence,
-                                    content: content.clone(),
+                                    content: "".to_string(),
                                     is_final: false,
                                     timestamp: Utc::now(),
-                                    chunk_type: ChunkType::Reasoning,
+                                    chunk_type: ChunkType::Status,
                                     metadata: ChunkMetadata {
-                                        token_count: Some(
-                                            (content.split_whitespace().count() as f32 * 1.3)
-                                                as u32,
-                                        ),
-                                        processing_time_ms: Some(
-                                            (Utc::now() - start_time).num_milliseconds() as u64,
-                                        ),
+                                        token_count: None,
+                                        processing_time_ms: None,
                                         model: None,
                                         confidence: None,
                                         custom: HashMap::new(),
@@ -884,94 +983,603 @@ impl ResponseStreamManager {
                                 };
 
                                 if chunk_sender.send(chunk).await.is_err() {
-                                    warn!(
-                                        "Failed to send reasoning chunk for session: {}",
-                                        session_id
-                                    );
+                                    warn!("Failed to send status chunk for session: {}", session_id);
                                     break;
                                 }
                                 sequence += 1;
                             }
-                        }
 
-                        ChatStreamEvent::Chunk(c) => {
-                            // Handle regular text chunk
-                            debug!("Received text chunk: {:?}", c);
-                            let content = c.content;
-                            if !content.is_empty() {
-                                accumulated_text.push_str(&content);
-                                total_chars += content.len() as u64;
+                            ChatStreamEvent::End(end) => {
+                                info!("Stream ended for session: {}", session_id);
+
+                                // Release text held back as a possible tag start
+                                if let Some((phase, content)) = think_tags.finish() {
+                                    if phase == ResponsePhase::Text {
+                                        accumulated_text.push_str(&content);
+                                    }
+                                    total_chars += content.len() as u64;
+                                    let token_count = counter.count(&content) as u32;
+                                    streamed_tokens += token_count;
+                                    let chunk = content_chunk(
+                                        &session_id, sequence, start_time, phase, content, token_count,
+                                    );
+                                    if chunk_sender.send(chunk).await.is_ok() {
+                                        sequence += 1;
+                                    }
+                                }
+
+                                // Prefer the provider's reported usage over our estimates
+                                let captured = end.captured_usage.as_ref();
+                                prompt_tokens += captured
+                                    .and_then(|usage| usage.prompt_tokens)
+                                    .map(|tokens| tokens as u32)
+                                    .unwrap_or(prompt_estimate);
+                                completion_tokens += captured
+                                    .and_then(|usage| usage.completion_tokens)
+                                    .map(|tokens| tokens as u32)
+                                    .unwrap_or(streamed_tokens);
+                                ended = true;
+                                break;
+                            }
+
+                            ChatStreamEvent::ToolCallChunk(t) => {
+                                // Handle tool call chunk with proper formatting
+                                debug!("Received tool call chunk: {:?}", t);
+
+                                // Store the tool call for execution
+                                tool_calls.push(t.tool_call.clone());
+
+                                // Create a formatted tool call chunk for UI
+                                let tool_content = format!(
+                                    "🔧 Calling {} with args: {}",
+                                    t.tool_call.fn_name,
+                                    serde_json::to_string(&t.tool_call.fn_arguments)
+                                        .unwrap_or_else(|_| "{}".to_string())
+                                );
 
                                 let chunk = ResponseChunk {
                                     id: format!("{}_{}", session_id, sequence),
                                     sequence,
-                                    content: content.clone(),
+                                    content: tool_content,
                                     is_final: false,
                                     timestamp: Utc::now(),
-                                    chunk_type: ChunkType::Text,
+                                    chunk_type: ChunkType::ToolCall,
                                     metadata: ChunkMetadata {
-                                        token_count: Some(
-                                            (content.split_whitespace().count() as f32 * 1.3)
-                                                as u32,
-                                        ),
+                                        token_count: None,
                                         processing_time_ms: Some(
                                             (Utc::now() - start_time).num_milliseconds() as u64,
                                         ),
                                         model: None,
                                         confidence: None,
-                                        custom: HashMap::new(),
+                                        custom: {
+                                            let mut custom = HashMap::new();
+                                            custom.insert(
+                                                "tool_name".to_string(),
+                                                serde_json::Value::String(t.tool_call.fn_name.clone()),
+                                            );
+                                            custom.insert(
+                                                "tool_args".to_string(),
+                                                t.tool_call.fn_arguments.clone(),
+                                            );
+                                            custom.insert(
+                                                "call_id".to_string(),
+                                                serde_json::Value::String(t.tool_call.call_id.clone()),
+                                            );
+                                            custom
+                                        },
                                     },
                                 };
 
                                 if chunk_sender.send(chunk).await.is_err() {
-                                    warn!("Failed to send text chunk for session: {}", session_id);
+                                    warn!("Failed to send tool call chunk for session: {}", session_id);
+                                    break;
+                                }
+                                sequence += 1;
+
+                                tracker
+                                    .update_typing_status(
+                                        TypingStatus::CallingTools,
+                                        config
+                                            .enable_progress_estimation
+                                            .then(|| progress.estimate(Utc::now())),
+                                    )
+                                    .await;
+
+                                // Execute the tool; its result goes back to the model
+                                let tool_name = &t.tool_call.fn_name;
+                                let tool_result = match ai_service.find_tool(tool_name) {
+                                    Some(tool) => {
+                                        debug!("Executing tool: {}", tool_name);
+
+                                        // Run the tool, abandoning it if the stream is cancelled
+                                        let args = t.tool_call.fn_arguments.clone();
+                                        tokio::select! {
+                                            result = tool_policy.run(tool, args) => result,
+                                            _ = tracker.wait_for_cancel() => {
+                                                info!("Tool {} aborted: stream cancelled for session {}", tool_name, session_id);
+
+                                                let aborted = ToolResult::failure(tool_name, "Aborted: response was cancelled");
+                                                let mut chunk = tool_response_chunk(&session_id, sequence, start_time, &aborted);
+                                                chunk.is_final = true;
+                                                chunk.metadata.custom.insert(
+                                                    "aborted".to_string(),
+                                                    serde_json::Value::Bool(true),
+                                                );
+                                                let _ = chunk_sender.send(chunk).await;
+                                                break;
+                                            }
+                                        }
+                                    }
+                                    None => {
+                                        warn!("Tool not found: {}", tool_name);
+                                        ToolResult::failure_with_kind(
+                                            tool_name,
+                                            ToolErrorKind::NotFound,
+                                            format!("Tool '{}' not found", tool_name),
+                                        )
+                                    }
+                                };
+
+                                if tool_result.is_success() {
+                                    debug!("Tool {} executed successfully: {}", tool_name, tool_result.display_text());
+                                } else {
+                                    warn!("Tool {} execution failed: {}", tool_name, tool_result.display_text());
+                                }
+
+                                // Send the result envelope
+                                let chunk = tool_response_chunk(&session_id, sequence, start_time, &tool_result);
+                                if chunk_sender.send(chunk).await.is_err() {
+                                    warn!("Failed to send tool result chunk for session: {}", session_id);
                                     break;
                                 }
                                 sequence += 1;
+
+                                tool_messages.push(InternalChatMessage::Tool {
+                                    tool_name: tool_name.clone(),
+                                    content: tool_result.display_text(),
+                                    call_id: Some(t.tool_call.call_id.clone()),
+                                });
+                            }
+
+                            ChatStreamEvent::ReasoningChunk(c) => {
+                                // Handle reasoning chunk
+                                debug!("Received reasoning chunk: {:?}", c);
+                                let content = c.content;
+                                if !content.is_empty() {
+                                    total_chars += content.len() as u64;
+                                    let token_count = counter.count(&content) as u32;
+                                    streamed_tokens += token_count;
+
+                                    let chunk = ResponseChunk {
+                                        id: format!("{}_{}", session_id, sequence),
+                                        sequence,
+                                        content: content.clone(),
+                                        is_final: false,
+                                        timestamp: Utc::now(),
+                                        chunk_type: ChunkType::Reasoning,
+                                        metadata: ChunkMetadata {
+                                            token_count: Some(token_count),
+                                            processing_time_ms: Some(
+                                                (Utc::now() - start_time).num_milliseconds() as u64,
+                                            ),
+                                            model: None,
+                                            confidence: None,
+                                            custom: HashMap::new(),
+                                        },
+                                    };
+
+                                    if chunk_sender.send(chunk).await.is_err() {
+                                        warn!(
+                                            "Failed to send reasoning chunk for session: {}",
+                                            session_id
+                                        );
+                                        break;
+                                    }
+                                    sequence += 1;
+
+                                    progress.record(ResponsePhase::Reasoning, content.chars().count(), Utc::now());
+                                    content_chunks += 1;
+                                    if config.enable_progress_estimation
+                                        && content_chunks.is_multiple_of(config.progress_update_interval.max(1))
+                                    {
+                                        tracker
+                                            .update_typing_status(
+                                                TypingStatus::Thinking,
+                                                Some(progress.estimate(Utc::now())),
+                                            )
+                                            .await;
+                                    }
+                                }
+                            }
+
+                            ChatStreamEvent::Chunk(c) => {
+                                // Handle regular text chunk, which may carry inline reasoning
+                                debug!("Received text chunk: {:?}", c);
+                                for (phase, content) in think_tags.push(&c.content) {
+                                    if phase == ResponsePhase::Text {
+                                        accumulated_text.push_str(&content);
+                                    }
+                                    total_chars += content.len() as u64;
+                                    let token_count = counter.count(&content) as u32;
+                                    streamed_tokens += token_count;
+                                    let chars = content.chars().count();
+
+                                    let ready = match phase {
+                                        ResponsePhase::Text => coalescer.push(content, token_count),
+                                        ResponsePhase::Reasoning => {
+                                            if !flush_coalesced(
+                                                &mut coalescer,
+                                                &chunk_sender,
+                                                &session_id,
+                                                &mut sequence,
+                                                start_time,
+                                            )
+                                            .await
+                                            {
+                                                break 'events;
+                                            }
+                                            Some((content, token_count))
+                                        }
+                                    };
+                                    if let Some((content, token_count)) = ready {
+                                        let chunk = content_chunk(
+                                            &session_id,
+                                            sequence,
+                                            start_time,
+                                            phase,
+                                            content,
+                                            token_count,
+                                        );
+                                        if chunk_sender.send(chunk).await.is_err() {
+                                            warn!(
+                                                "Failed to send text chunk for session: {}",
+                                                session_id
+                                            );
+                                            break 'events;
+                                        }
+                                        sequence += 1;
+                                    }
+
+                                    progress.record(phase, chars, Utc::now());
+                                    content_chunks += 1;
+                                    if config.enable_progress_estimation
+                                        && content_chunks.is_multiple_of(config.progress_update_interval.max(1))
+                                    {
+                                        let status = match phase {
+                                            ResponsePhase::Reasoning => TypingStatus::Thinking,
+                                            ResponsePhase::Text => TypingStatus::Typing,
+                                        };
+                                        tracker
+                                            .update_typing_status(
+                                                status,
+                                                Some(progress.estimate(Utc::now())),
+                                            )
+                                            .await;
+                                    }
+                                }
                             }
                         }
                     }
+                    Err(e) => {
+                        warn!("Stream error for session {}: {}", session_id, e);
+                        flush_coalesced(
+                            &mut coalescer,
+                            &chunk_sender,
+                            &session_id,
+                            &mut sequence,
+                            start_time,
+                        )
+                        .await;
+
+                        // Send error chunk
+                        let chunk = ResponseChunk {
+                            id: format!("{}_{}", session_id, sequence),
+                            sequence,
+                            content: format!("Error: {}", e),
+                            is_final: true,
+                            timestamp: Utc::now(),
+                            chunk_type: ChunkType::Error,
+                            metadata: ChunkMetadata {
+                                token_count: None,
+                                processing_time_ms: Some(
+                                    (Utc::now() - start_time).num_milliseconds() as u64
+                                ),
+                                model: None,
+                                confidence: None,
+                                custom: HashMap::new(),
+                            },
+                        };
+
+                        let _ = chunk_sender.send(chunk).await;
+                        let _ = tracker.event_sender.send(StreamEvent::StreamError {
+                            session_id: session_id.clone(),
+                            error: e.to_string(),
+                        });
+
+                        break;
+                    }
                 }
-                Err(e) => {
-                    warn!("Stream error for session {}: {}", session_id, e);
 
-                    // Send error chunk
+                // Respect streaming rate limits
+                // if config.rate_limit_ms > 0 {
+                //     tokio::time::sleep(tokio::time::Duration::from_millis(config.rate_limit_ms)).await;
+                // }
+            }
+
+            // Text still held back when the stream ended without an End event
+            flush_coalesced(
+                &mut coalescer,
+                &chunk_sender,
+                &session_id,
+                &mut sequence,
+                start_time,
+            )
+            .await;
+            drop(stream);
+
+            if !ended && !exhausted {
+                // Cancelled, failed or no longer listened to
+                break;
+            }
+
+            if tool_messages.is_empty() {
+                if ended {
+                    let estimated_cost_usd =
+                        ai_service.record_usage(prompt_tokens, completion_tokens).await;
+                    telemetry::record_tokens(prompt_tokens, completion_tokens);
+
+                    // Send final completion chunk
+                    let duration_ms = (Utc::now() - start_time).num_milliseconds() as u64;
+
                     let chunk = ResponseChunk {
                         id: format!("{}_{}", session_id, sequence),
                         sequence,
-                        content: format!("Error: {}", e),
+                        content: "".to_string(),
                         is_final: true,
                         timestamp: Utc::now(),
-                        chunk_type: ChunkType::Error,
+                        chunk_type: ChunkType::Complete,
                         metadata: ChunkMetadata {
-                            token_count: None,
-                            processing_time_ms: Some(
-                                (Utc::now() - start_time).num_milliseconds() as u64
-                            ),
+                            token_count: Some(completion_tokens),
+                            processing_time_ms: Some(duration_ms),
                             model: None,
                             confidence: None,
-                            custom: HashMap::new(),
+                            custom: {
+                                let mut custom = HashMap::new();
+                                custom.insert(
+                                    "usage".to_string(),
+                                    serde_json::json!({
+                                        "prompt_tokens": prompt_tokens,
+                                        "completion_tokens": completion_tokens,
+                                        "total_tokens": prompt_tokens + completion_tokens,
+                                        "estimated_cost_usd": estimated_cost_usd,
+                                    }),
+                                );
+                                custom.insert(
+                                    "total_chunks".to_string(),
+                                    serde_json::Value::Number(sequence.into()),
+                                );
+                                custom.insert(
+                                    "total_characters".to_string(),
+                                    serde_json::Value::Number(total_chars.into()),
+                                );
+                                custom.insert(
+                                    "tool_calls_count".to_string(),
+                                    serde_json::Value::Number(tool_calls.len().into()),
+                                );
+                                custom.insert(
+                                    "tool_iterations".to_string(),
+                                    serde_json::Value::Number(iteration.into()),
+                                );
+                                custom
+                            },
                         },
                     };
 
-                    let _ = chunk_sender.send(chunk).await;
-                    let _ = event_sender.send(StreamEvent::StreamError {
+                    if chunk_sender.send(chunk).await.is_err() {
+                        warn!(
+                            "Failed to send completion chunk for session: {}",
+                            session_id
+                        );
+                    }
+
+                    // Send stream completed event
+                    let _ = tracker.event_sender.send(StreamEvent::StreamCompleted {
                         session_id: session_id.clone(),
-                        error: e.to_string(),
+                        total_chunks: sequence,
+                        total_characters: total_chars,
+                        duration_ms,
                     });
-
-                    break;
                 }
+                break;
             }
 
-            // Respect streaming rate limits
-            // if config.rate_limit_ms > 0 {
-            //     tokio::time::sleep(tokio::time::Duration::from_millis(config.rate_limit_ms)).await;
-            // }
+            if iteration == config.max_tool_iterations {
+                warn!("Stream {} reached the tool iteration limit", session_id);
+                let error = "Maximum tool execution iterations reached";
+                let chunk = ResponseChunk {
+                    id: format!("{}_{}", session_id, sequence),
+                    sequence,
+                    content: format!("Error: {}", error),
+                    is_final: true,
+                    timestamp: Utc::now(),
+                    chunk_type: ChunkType::Error,
+                    metadata: ChunkMetadata {
+                        token_count: None,
+                        processing_time_ms: Some(
+                            (Utc::now() - start_time).num_milliseconds() as u64
+                        ),
+                        model: None,
+                        confidence: None,
+                        custom: HashMap::new(),
+                    },
+                };
+                let _ = chunk_sender.send(chunk).await;
+                let _ = tracker.event_sender.send(StreamEvent::StreamError {
+                    session_id: session_id.clone(),
+                    error: error.to_string(),
+                });
+                break;
+            }
+
+            // Continue with the turn's tool requests and their results
+            debug!(
+                "Sending {} tool results back to the model for session: {}",
+                tool_messages.len(),
+                session_id
+            );
+            messages.push(InternalChatMessage::Assistant {
+                content: if accumulated_text.is_empty() {
+                    "Tool calls requested".to_string()
+                } else {
+                    accumulated_text
+                },
+                tool_responses: None,
+            });
+            messages.extend(tool_messages);
         }
 
         info!("Genai streaming task completed for session: {}", session_id);
-        Ok(())
+        Ok(StreamTotals {
+            chunks: sequence,
+            characters: total_chars,
+        })
+    }
+}
+
+/// Token counter for `ai_service`'s model
+///
+/// Services other than [`LLMService`] have no known model and estimate.
+fn token_counter(ai_service: &dyn AiService) -> TokenCounter {
+    ai_service
+        .as_any()
+        .downcast_ref::<LLMService>()
+        .map(|llm| TokenCounter::for_model(llm.provider()))
+        .unwrap_or_default()
+}
+
+/// Reasoning or answer text chunk, typed by the phase it belongs to
+fn content_chunk(
+    session_id: &str,
+    sequence: u64,
+    start_time: DateTime<Utc>,
+    phase: ResponsePhase,
+    content: String,
+    token_count: u32,
+) -> ResponseChunk {
+    ResponseChunk {
+        id: format!("{}_{}", session_id, sequence),
+        sequence,
+        content,
+        is_final: false,
+        timestamp: Utc::now(),
+        chunk_type: match phase {
+            ResponsePhase::Reasoning => ChunkType::Reasoning,
+            ResponsePhase::Text => ChunkType::Text,
+        },
+        metadata: ChunkMetadata {
+            token_count: Some(token_count),
+            processing_time_ms: Some((Utc::now() - start_time).num_milliseconds() as u64),
+            model: None,
+            confidence: None,
+            custom: HashMap::new(),
+        },
+    }
+}
+
+/// Send the text `coalescer` is holding back, if any
+///
+/// Returns false once the receiver has gone away.
+async fn flush_coalesced(
+    coalescer: &mut TextCoalescer,
+    chunk_sender: &mpsc::Sender<ResponseChunk>,
+    session_id: &str,
+    sequence: &mut u64,
+    start_time: DateTime<Utc>,
+) -> bool {
+    let Some((content, token_count)) = coalescer.take() else {
+        return true;
+    };
+    let chunk = content_chunk(
+        session_id,
+        *sequence,
+        start_time,
+        ResponsePhase::Text,
+        content,
+        token_count,
+    );
+    if chunk_sender.send(chunk).await.is_err() {
+        return false;
+    }
+    *sequence += 1;
+    true
+}
+
+/// Tool response chunk carrying `result` as its [`ToolResult`] envelope
+///
+/// The chunk content is the envelope JSON; `custom["tool_result"]` holds the
+/// same envelope as a value.
+fn tool_response_chunk(
+    session_id: &str,
+    sequence: u64,
+    start_time: DateTime<Utc>,
+    result: &ToolResult,
+) -> ResponseChunk {
+    let mut custom = HashMap::new();
+    custom.insert(
+        "tool_name".to_string(),
+        serde_json::Value::String(result.tool.clone()),
+    );
+    custom.insert(
+        "tool_result".to_string(),
+        serde_json::to_value(result).unwrap_or_default(),
+    );
+
+    ResponseChunk {
+        id: format!("{}_{}", session_id, sequence),
+        sequence,
+        content: result.to_json(),
+        is_final: false,
+        timestamp: Utc::now(),
+        chunk_type: ChunkType::ToolResponse,
+        metadata: ChunkMetadata {
+            token_count: None,
+            processing_time_ms: Some((Utc::now() - start_time).num_milliseconds() as u64),
+            model: None,
+            confidence: None,
+            custom,
+        },
+    }
+}
+
+/// Update the typing indicator for `session_id` and broadcast the change
+///
+/// Does nothing if the session has no typing indicator.
+async fn set_typing_status(
+    typing_indicators: &RwLock<HashMap<String, TypingIndicator>>,
+    event_sender: &broadcast::Sender<StreamEvent>,
+    session_id: &str,
+    status: TypingStatus,
+    estimate: Option<ProgressEstimate>,
+) {
+    let mut indicators = typing_indicators.write().await;
+    if let Some(indicator) = indicators.get_mut(session_id) {
+        indicator.status = status;
+        indicator.last_activity = Utc::now();
+        indicator.progress_percent = estimate.map(|estimate| estimate.percent);
+        indicator.estimated_completion = estimate.and_then(|estimate| estimate.estimated_completion);
+
+        let _ = event_sender.send(StreamEvent::TypingStatusChanged {
+            session_id: session_id.to_string(),
+            indicator: indicator.clone(),
+        });
+    }
+}
+
+/// Resolves at `deadline`, or never if there is none
+async fn sleep_until_due(deadline: Option<tokio::time::Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending::<()>().await,
     }
 }
 
@@ -1090,3 +1698,525 @@ pub mod streaming_utils {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Error;
+    use crate::llm::{MockAiService, MockResponse};
+    use crate::tools::AiTool;
+    use genai::chat::{MessageContent, StreamChunk};
+    use serde_json::{Value, json};
+
+    /// Service that streams one chunk and then stalls until dropped
+    ///
+    /// MockAiService always finishes its streams, and there would be nothing
+    /// left to cancel.
+    struct StallingService;
+
+    #[async_trait::async_trait]
+    impl AiService for StallingService {
+        async fn generate_response(
+            &self,
+            _messages: &[InternalChatMessage],
+            _params: &GenerationParams,
+        ) -> Result<MessageContent, Error> {
+            Err(anyhow::anyhow!("not used"))
+        }
+
+        async fn generate_response_stream<'a>(
+            &'a self,
+            _messages: &'a [InternalChatMessage],
+            _params: &GenerationParams,
+        ) -> Result<Pin<Box<dyn Stream<Item = Result<ChatStreamEvent, Error>> + Send + 'a>>, Error>
+        {
+            let events = futures_util::stream::iter(vec![
+                Ok(ChatStreamEvent::Start),
+                Ok(ChatStreamEvent::Chunk(StreamChunk {
+                    content: "Once upon a time".to_string(),
+                })),
+            ]);
+            Ok(Box::pin(events.chain(futures_util::stream::pending())))
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    /// Tool that multiplies `a` by `b`
+    struct MultiplyTool;
+
+    #[async_trait::async_trait]
+    impl AiTool for MultiplyTool {
+        fn name(&self) -> &str {
+            "multiply"
+        }
+
+        fn description(&self) -> &str {
+            "Multiplies two numbers"
+        }
+
+        fn schema(&self) -> Value {
+            json!({
+                "type": "object",
+                "properties": {
+                    "a": { "type": "number" },
+                    "b": { "type": "number" }
+                },
+                "required": ["a", "b"]
+            })
+        }
+
+        async fn execute(&self, params: Value) -> Result<Value, Error> {
+            let a = params["a"].as_i64().ok_or_else(|| anyhow::anyhow!("Missing 'a'"))?;
+            let b = params["b"].as_i64().ok_or_else(|| anyhow::anyhow!("Missing 'b'"))?;
+            Ok(json!(a * b))
+        }
+    }
+
+    const PANGRAM: &str = "The quick brown fox jumps over the lazy dog";
+
+    /// Service that reasons for a few chunks, then answers in several more
+    ///
+    /// MockAiService streams no reasoning chunks and doesn't pause between
+    /// chunks, which the completion estimate needs.
+    struct ThoughtfulService;
+
+    #[async_trait::async_trait]
+    impl AiService for ThoughtfulService {
+        async fn generate_response(
+            &self,
+            _messages: &[InternalChatMessage],
+            _params: &GenerationParams,
+        ) -> Result<MessageContent, Error> {
+            Err(anyhow::anyhow!("not used"))
+        }
+
+        async fn generate_response_stream<'a>(
+            &'a self,
+            _messages: &'a [InternalChatMessage],
+            _params: &GenerationParams,
+        ) -> Result<Pin<Box<dyn Stream<Item = Result<ChatStreamEvent, Error>> + Send + 'a>>, Error>
+        {
+            let reasoning = (0..4).map(|_| {
+                Ok(ChatStreamEvent::ReasoningChunk(StreamChunk {
+                    content: "Let me think about this. ".to_string(),
+                }))
+            });
+            let text = (0..8).map(|_| {
+                Ok(ChatStreamEvent::Chunk(StreamChunk {
+                    content: "Here is part of the answer. ".to_string(),
+                }))
+            });
+            let events = std::iter::once(Ok(ChatStreamEvent::Start))
+                .chain(reasoning)
+                .chain(text);
+            Ok(Box::pin(futures_util::stream::iter(events).then(|event| async {
+                tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+                event
+            })))
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    #[tokio::test]
+    async fn test_genai_stream_reports_progress_without_going_backward() {
+        let manager = ResponseStreamManager::new();
+        manager
+            .update_config(StreamConfig {
+                progress_update_interval: 2,
+                ..StreamConfig::default()
+            })
+            .await
+            .unwrap();
+        let mut events = manager.subscribe_to_events();
+
+        let stream = manager
+            .stream_genai_response(
+                "session_1".to_string(),
+                Arc::new(ThoughtfulService),
+                vec![InternalChatMessage::User {
+                    content: "Why is the sky blue?".to_string(),
+                }],
+            )
+            .await
+            .unwrap();
+        let _: Vec<ResponseChunk> = stream.collect().await;
+
+        let mut updates = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            if let StreamEvent::TypingStatusChanged { indicator, .. } = event {
+                updates.push(indicator);
+            }
+        }
+
+        let progress: Vec<(u8, bool)> = updates
+            .iter()
+            .filter(|indicator| !matches!(indicator.status, TypingStatus::Stopped))
+            .filter_map(|indicator| {
+                indicator
+                    .progress_percent
+                    .map(|percent| (percent, matches!(indicator.status, TypingStatus::Thinking)))
+            })
+            .collect();
+        // Two updates while reasoning, four while answering
+        assert_eq!(progress.len(), 6);
+        assert!(progress[..2].iter().all(|(_, thinking)| *thinking));
+        assert!(progress[2..].iter().all(|(_, thinking)| !*thinking));
+        assert!(progress.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+        assert!(updates
+            .iter()
+            .any(|indicator| indicator.estimated_completion.is_some()));
+        assert!(matches!(updates.last().unwrap().status, TypingStatus::Stopped));
+    }
+
+    #[tokio::test]
+    async fn test_single_character_chunks_are_coalesced() {
+        async fn text_chunks(coalesce_ms: u64, coalesce_chars: usize) -> Vec<ResponseChunk> {
+            let manager = ResponseStreamManager::new();
+            manager
+                .update_config(StreamConfig {
+                    coalesce_ms,
+                    coalesce_chars,
+                    ..StreamConfig::default()
+                })
+                .await
+                .unwrap();
+            let stream = manager
+                .stream_genai_response(
+                    "session_1".to_string(),
+                    Arc::new(
+                        MockAiService::new()
+                            .with_chunk_size(1)
+                            .with_response(MockResponse::Text(PANGRAM.to_string())),
+                    ),
+                    vec![InternalChatMessage::User {
+                        content: "Type something".to_string(),
+                    }],
+                )
+                .await
+                .unwrap();
+            let chunks: Vec<ResponseChunk> = stream.collect().await;
+            assert!(chunks.iter().enumerate().all(|(i, chunk)| chunk.sequence == i as u64));
+            chunks
+                .into_iter()
+                .filter(|chunk| chunk.chunk_type == ChunkType::Text)
+                .collect()
+        }
+
+        let uncoalesced = text_chunks(0, 0).await;
+        assert_eq!(uncoalesced.len(), PANGRAM.len());
+
+        // 43 characters in chunks of ten, the last sent when the stream ends
+        let coalesced = text_chunks(60_000, 10).await;
+        assert_eq!(coalesced.len(), 5);
+        assert!(coalesced.iter().all(|chunk| chunk.content.len() <= 10));
+        let text: String = coalesced.iter().map(|chunk| chunk.content.as_str()).collect();
+        assert_eq!(text, PANGRAM);
+    }
+
+    #[tokio::test]
+    async fn test_finished_streams_are_cleaned_up() {
+        let manager = ResponseStreamManager::new();
+        let greeter = MockAiService::new().with_chunk_size(64);
+        for _ in 0..3 {
+            greeter.push(MockResponse::Text("Hello there".to_string()));
+        }
+        let service: Arc<dyn AiService> = Arc::new(greeter);
+        let messages = vec![InternalChatMessage::User {
+            content: "Hi".to_string(),
+        }];
+
+        for i in 0..3 {
+            let stream = if i == 0 {
+                manager
+                    .start_streaming_response(format!("session_{}", i), service.clone(), messages.clone())
+                    .await
+            } else {
+                manager
+                    .stream_genai_response(format!("session_{}", i), service.clone(), messages.clone())
+                    .await
+            }
+            .unwrap();
+
+            // The stream only ends once its session has been dropped
+            let chunks: Vec<ResponseChunk> =
+                tokio::time::timeout(std::time::Duration::from_secs(5), stream.collect())
+                    .await
+                    .expect("stream did not end after the task finished");
+            assert!(chunks.iter().any(|chunk| chunk.content == "Hello there"));
+        }
+
+        let stats = manager.get_stats().await;
+        assert_eq!(stats.active_streams, 0);
+        assert!(manager.get_typing_indicators().await.is_empty());
+        // One text chunk from the simulated stream, a status and a text chunk from each live one
+        assert_eq!(stats.total_chunks, 5);
+        assert_eq!(stats.total_characters, 3 * "Hello there".len() as u64);
+        assert!(stats.avg_chunk_size > 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_stream_ends_response_and_cleans_up() {
+        let manager = ResponseStreamManager::new();
+        let mut events = manager.subscribe_to_events();
+        let mut stream = manager
+            .stream_genai_response(
+                "session_1".to_string(),
+                Arc::new(StallingService),
+                vec![InternalChatMessage::User {
+                    content: "Tell me a long story".to_string(),
+                }],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(stream.next().await.unwrap().chunk_type, ChunkType::Status);
+        assert_eq!(stream.next().await.unwrap().content, "Once upon a time");
+        assert_eq!(manager.get_stats().await.active_streams, 1);
+
+        assert!(manager.cancel_stream("session_1").await);
+        let end = tokio::time::timeout(std::time::Duration::from_secs(5), stream.next())
+            .await
+            .expect("stream did not terminate after cancellation");
+        assert!(end.is_none());
+        assert_eq!(manager.get_stats().await.active_streams, 0);
+
+        let mut cancelled = false;
+        while let Ok(event) = events.try_recv() {
+            cancelled |= matches!(
+                event,
+                StreamEvent::StreamCancelled { ref session_id } if session_id == "session_1"
+            );
+        }
+        assert!(cancelled);
+
+        // A second cancel finds nothing to stop
+        assert!(!manager.cancel_stream("session_1").await);
+    }
+
+    #[tokio::test]
+    async fn test_tool_results_go_back_to_the_model_until_it_answers() {
+        let service = Arc::new(
+            MockAiService::new()
+                .with_tool(MultiplyTool)
+                .with_response(MockResponse::ToolCall {
+                    name: "multiply".to_string(),
+                    args: json!({ "a": 6, "b": 7 }),
+                })
+                .with_response(MockResponse::Text("6 times 7 is 42.".to_string())),
+        );
+        let manager = ResponseStreamManager::new();
+        let stream = manager
+            .stream_genai_response(
+                "session_1".to_string(),
+                service.clone(),
+                vec![InternalChatMessage::User {
+                    content: "What is 6 times 7?".to_string(),
+                }],
+            )
+            .await
+            .unwrap();
+        let chunks: Vec<ResponseChunk> = stream.collect().await;
+
+        assert!(chunks.iter().enumerate().all(|(i, chunk)| chunk.sequence == i as u64));
+        let types: Vec<_> = chunks.iter().map(|chunk| chunk.chunk_type.clone()).collect();
+        assert_eq!(
+            types,
+            [
+                ChunkType::Status,
+                ChunkType::ToolCall,
+                ChunkType::ToolResponse,
+                ChunkType::Status,
+                ChunkType::Text,
+                ChunkType::Complete,
+            ]
+        );
+        let result = ToolResult::from_json(&chunks[2].content).unwrap();
+        assert_eq!(result.result, Some(json!(42)));
+        assert_eq!(chunks[4].content, "6 times 7 is 42.");
+        let complete = &chunks[5].metadata.custom;
+        assert_eq!(complete["tool_calls_count"], 1);
+        assert_eq!(complete["tool_iterations"], 1);
+
+        // The second turn saw the tool request and its result
+        let requests = service.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].len(), 3);
+        assert!(matches!(
+            &requests[1][2],
+            InternalChatMessage::Tool { tool_name, content, call_id }
+                if tool_name == "multiply" && content == "42" && call_id.as_deref() == Some("call_1")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tool_calls_stop_at_the_iteration_limit() {
+        let call = MockResponse::ToolCall {
+            name: "multiply".to_string(),
+            args: json!({ "a": 2, "b": 2 }),
+        };
+        let service = Arc::new(
+            MockAiService::new()
+                .with_tool(MultiplyTool)
+                .with_response(call.clone())
+                .with_response(call.clone())
+                .with_response(call),
+        );
+        let manager = ResponseStreamManager::new();
+        manager
+            .update_config(StreamConfig {
+                max_tool_iterations: 1,
+                ..StreamConfig::default()
+            })
+            .await
+            .unwrap();
+        let stream = manager
+            .stream_genai_response(
+                "session_1".to_string(),
+                service.clone(),
+                vec![InternalChatMessage::User {
+                    content: "Keep multiplying".to_string(),
+                }],
+            )
+            .await
+            .unwrap();
+        let chunks: Vec<ResponseChunk> = stream.collect().await;
+
+        let last = chunks.last().unwrap();
+        assert_eq!(last.chunk_type, ChunkType::Error);
+        assert!(last.content.contains("Maximum tool execution iterations"));
+        assert_eq!(service.requests().len(), 2);
+        assert_eq!(service.remaining(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stream_ends_at_stop_sequence_split_across_chunks() {
+        // Chunks of three split the stop sequence into "\nEN" and "D m"
+        let service = Arc::new(
+            MockAiService::new()
+                .with_chunk_size(3)
+                .with_response(MockResponse::Text("Sure, Paris.\nEND more".to_string())),
+        );
+        let manager = ResponseStreamManager::new();
+        manager
+            .update_config(StreamConfig {
+                coalesce_ms: 0,
+                coalesce_chars: 0,
+                ..StreamConfig::default()
+            })
+            .await
+            .unwrap();
+        let stream = manager
+            .stream_genai_response_with_params(
+                "session_1".to_string(),
+                service,
+                vec![InternalChatMessage::User {
+                    content: "Capital of France?".to_string(),
+                }],
+                GenerationParams::default().with_stop("END"),
+            )
+            .await
+            .unwrap();
+        let chunks: Vec<ResponseChunk> = stream.collect().await;
+
+        let text: String = chunks
+            .iter()
+            .filter(|chunk| chunk.chunk_type == ChunkType::Text)
+            .map(|chunk| chunk.content.as_str())
+            .collect();
+        assert_eq!(text, "Sure, Paris.\n");
+        assert_eq!(chunks.last().unwrap().chunk_type, ChunkType::Complete);
+    }
+
+    /// A span opened while a [`SpanRecorder`] was listening
+    #[derive(Debug, Clone)]
+    struct RecordedSpan {
+        name: &'static str,
+        parent: Option<&'static str>,
+        fields: String,
+    }
+
+    /// Layer that records every span opened, with its parent and fields
+    #[derive(Clone, Default)]
+    struct SpanRecorder(Arc<std::sync::Mutex<Vec<RecordedSpan>>>);
+
+    struct FieldText<'a>(&'a mut String);
+
+    impl tracing::field::Visit for FieldText<'_> {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0.push_str(&format!("{}={:?} ", field.name(), value));
+        }
+    }
+
+    impl<S> tracing_subscriber::Layer<S> for SpanRecorder
+    where
+        S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            id: &tracing::span::Id,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let span = ctx.span(id).unwrap();
+            let mut fields = String::new();
+            attrs.record(&mut FieldText(&mut fields));
+            self.0.lock().unwrap().push(RecordedSpan {
+                name: span.name(),
+                parent: span.parent().map(|parent| parent.name()),
+                fields,
+            });
+        }
+    }
+
+    #[tokio::test]
+    async fn test_turn_is_traced_under_one_stream_span() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        // The test runtime is single-threaded, so the spawned task sees this too
+        let recorder = SpanRecorder::default();
+        let _guard =
+            tracing::subscriber::set_default(tracing_subscriber::registry().with(recorder.clone()));
+
+        let service = Arc::new(
+            MockAiService::new()
+                .with_tool(MultiplyTool)
+                .with_response(MockResponse::ToolCall {
+                    name: "multiply".to_string(),
+                    args: json!({ "a": 6, "b": 7 }),
+                })
+                .with_response(MockResponse::Text("6 times 7 is 42.".to_string())),
+        );
+        let manager = ResponseStreamManager::new();
+        let stream = manager
+            .stream_genai_response(
+                "session_1".to_string(),
+                service,
+                vec![InternalChatMessage::User {
+                    content: "What is 6 times 7?".to_string(),
+                }],
+            )
+            .await
+            .unwrap();
+        let chunks: Vec<ResponseChunk> = stream.collect().await;
+        assert_eq!(chunks.last().unwrap().chunk_type, ChunkType::Complete);
+
+        let spans = recorder.0.lock().unwrap().clone();
+        let turns: Vec<&RecordedSpan> =
+            spans.iter().filter(|span| span.name == "stream_response").collect();
+        assert_eq!(turns.len(), 1);
+        assert_eq!(turns[0].parent, None);
+        assert!(turns[0].fields.contains("session_id=session_1"), "{:?}", turns[0]);
+
+        let tools: Vec<&RecordedSpan> =
+            spans.iter().filter(|span| span.name == "tool_call").collect();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].parent, Some("stream_response"));
+        assert!(tools[0].fields.contains("tool=\"multiply\""), "{:?}", tools[0]);
+    }
+}
@@ -4,9 +4,18 @@
 //! real-time AI responses with tool calling support.
 
 pub mod manager;
+pub mod stop;
+
+// The core stream manager buffers text and splits out reasoning the same way;
+// it can't depend on this crate, so the shared code lives there
+pub use luts_core::streaming::{coalesce, progress, reasoning};
 
 // Re-export key types for convenience
 pub use manager::{
-    ChunkType, ResponseChunk, ResponseStreamManager, StreamConfig, StreamEvent, StreamableResponse,
-    StreamingResponseBuilder, TypingIndicator, TypingStatus,
-};
\ No newline at end of file
+    ChunkMetadata, ChunkType, ResponseChunk, ResponseStreamManager, StreamConfig, StreamEvent,
+    StreamableResponse, StreamingResponseBuilder, TypingIndicator, TypingStatus,
+};
+pub use coalesce::TextCoalescer;
+pub use progress::{ProgressEstimate, ProgressEstimator, ResponsePhase};
+pub use reasoning::ThinkTagSplitter;
+pub use stop::{StopSequenceScanner, stop_at_sequences};
\ No newline at end of file
@@ -0,0 +1,166 @@
+//! Client-side stop sequences
+//!
+//! Stop sequences are sent to the provider, but not every provider honours
+//! them, and a test double never does. [`stop_at_sequences`] watches the text
+//! of a response stream and ends it at the first stop sequence, even when the
+//! sequence is split across chunks.
+
+use anyhow::Error;
+use futures_util::{Stream, StreamExt};
+use genai::chat::{ChatStreamEvent, StreamChunk, StreamEnd};
+use std::collections::VecDeque;
+use std::pin::Pin;
+
+/// A response stream, as returned by
+/// [`AiService::generate_response_stream`](crate::llm::AiService::generate_response_stream)
+pub type EventStream<'a> = Pin<Box<dyn Stream<Item = Result<ChatStreamEvent, Error>> + Send + 'a>>;
+
+/// Finds stop sequences in streamed text
+#[derive(Debug, Default)]
+pub struct StopSequenceScanner {
+    stop: Vec<String>,
+    /// Text held back because it may be the start of a stop sequence
+    pending: String,
+}
+
+impl StopSequenceScanner {
+    /// Scan for `stop`; empty sequences are ignored
+    pub fn new(stop: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            stop: stop.into_iter().filter(|s| !s.is_empty()).collect(),
+            pending: String::new(),
+        }
+    }
+
+    /// Add the next text chunk, returning the text that is safe to emit and
+    /// whether a stop sequence was found
+    ///
+    /// Once a stop sequence is found, the text before it is the last output.
+    pub fn push(&mut self, text: &str) -> (String, bool) {
+        let mut buffer = std::mem::take(&mut self.pending) + text;
+        let first_stop = self
+            .stop
+            .iter()
+            .filter_map(|stop| buffer.find(stop.as_str()))
+            .min();
+        if let Some(pos) = first_stop {
+            buffer.truncate(pos);
+            return (buffer, true);
+        }
+
+        let held = self
+            .stop
+            .iter()
+            .map(|stop| partial_stop_len(&buffer, stop))
+            .max()
+            .unwrap_or(0);
+        self.pending = buffer.split_off(buffer.len() - held);
+        (buffer, false)
+    }
+
+    /// Release any text still held back once the text has ended
+    pub fn finish(&mut self) -> Option<String> {
+        let pending = std::mem::take(&mut self.pending);
+        (!pending.is_empty()).then_some(pending)
+    }
+}
+
+/// Length of the longest end of `text` that is the start of `stop`
+fn partial_stop_len(text: &str, stop: &str) -> usize {
+    (1..stop.len())
+        .rev()
+        .filter(|&len| stop.is_char_boundary(len))
+        .find(|&len| text.ends_with(&stop[..len]))
+        .unwrap_or(0)
+}
+
+struct StopState<'a> {
+    stream: EventStream<'a>,
+    scanner: StopSequenceScanner,
+    ready: VecDeque<Result<ChatStreamEvent, Error>>,
+    done: bool,
+}
+
+impl StopState<'_> {
+    fn emit_text(&mut self, content: String) {
+        if !content.is_empty() {
+            self.ready
+                .push_back(Ok(ChatStreamEvent::Chunk(StreamChunk { content })));
+        }
+    }
+}
+
+/// End `stream` at the first of the `stop` sequences in its text
+///
+/// Text up to the stop sequence is passed on, followed by an `End` event;
+/// the rest of the provider's stream is dropped. Without stop sequences the
+/// stream is returned as is.
+pub fn stop_at_sequences<'a>(stream: EventStream<'a>, stop: Vec<String>) -> EventStream<'a> {
+    let scanner = StopSequenceScanner::new(stop);
+    if scanner.stop.is_empty() {
+        return stream;
+    }
+
+    let state = StopState {
+        stream,
+        scanner,
+        ready: VecDeque::new(),
+        done: false,
+    };
+    Box::pin(futures_util::stream::unfold(state, next_event))
+}
+
+async fn next_event(
+    mut state: StopState<'_>,
+) -> Option<(Result<ChatStreamEvent, Error>, StopState<'_>)> {
+    loop {
+        if let Some(event) = state.ready.pop_front() {
+            return Some((event, state));
+        }
+        if state.done {
+            return None;
+        }
+
+        match state.stream.next().await {
+            Some(Ok(ChatStreamEvent::Chunk(chunk))) => {
+                let (content, stopped) = state.scanner.push(&chunk.content);
+                state.emit_text(content);
+                if stopped {
+                    state
+                        .ready
+                        .push_back(Ok(ChatStreamEvent::End(StreamEnd::default())));
+                    state.done = true;
+                }
+            }
+            // Held-back text goes out before anything that isn't more text
+            Some(event) => {
+                let held = state.scanner.finish().unwrap_or_default();
+                state.emit_text(held);
+                state.ready.push_back(event);
+            }
+            None => {
+                let held = state.scanner.finish().unwrap_or_default();
+                state.emit_text(held);
+                state.done = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_stop_is_held_back_until_resolved() {
+        let mut scanner = StopSequenceScanner::new(["</answer>".to_string()]);
+        assert_eq!(scanner.push("42 </ans"), ("42 ".to_string(), false));
+        assert_eq!(scanner.push("wers>"), ("</answers>".to_string(), false));
+        assert_eq!(scanner.push(" or </an"), (" or ".to_string(), false));
+        assert_eq!(scanner.finish(), Some("</an".to_string()));
+
+        let mut scanner = StopSequenceScanner::new(["</answer>".to_string()]);
+        assert_eq!(scanner.push("42 </ans"), ("42 ".to_string(), false));
+        assert_eq!(scanner.push("wer> ignored"), (String::new(), true));
+    }
+}
@@ -0,0 +1,65 @@
+//! Request metrics
+//!
+//! With the `metrics` feature, LLM requests, token usage and tool calls are
+//! recorded through the [`metrics`](https://docs.rs/metrics) facade for
+//! whichever recorder the application installs; the API server exports them
+//! for Prometheus at `/metrics`. Without the feature these functions do
+//! nothing.
+//!
+//! Spans tying a turn together are emitted with `tracing` regardless:
+//! `stream_response` for a streamed turn, `llm_request` and `llm_stream` for
+//! provider calls, and `tool_call` for each tool run.
+
+use std::time::Duration;
+
+/// LLM requests, labelled by `provider` and `kind` (`chat` or `stream`)
+pub const LLM_REQUESTS: &str = "luts_llm_requests_total";
+/// Failed LLM requests, with the same labels as [`LLM_REQUESTS`]
+pub const LLM_ERRORS: &str = "luts_llm_errors_total";
+/// Seconds until a response, or the start of a streamed one, arrived
+pub const LLM_LATENCY: &str = "luts_llm_request_duration_seconds";
+/// Tokens used, labelled by `kind` (`prompt` or `completion`)
+pub const LLM_TOKENS: &str = "luts_llm_tokens_total";
+/// Tool runs, labelled by `tool` and `outcome` (`success` or `error`)
+pub const TOOL_CALLS: &str = "luts_tool_calls_total";
+
+/// Record one LLM request and how long it took
+pub fn record_llm_request(provider: &str, kind: &'static str, elapsed: Duration, ok: bool) {
+    #[cfg(feature = "metrics")]
+    {
+        let provider = provider.to_string();
+        metrics::counter!(LLM_REQUESTS, "provider" => provider.clone(), "kind" => kind)
+            .increment(1);
+        if !ok {
+            metrics::counter!(LLM_ERRORS, "provider" => provider.clone(), "kind" => kind)
+                .increment(1);
+        }
+        metrics::histogram!(LLM_LATENCY, "provider" => provider, "kind" => kind)
+            .record(elapsed.as_secs_f64());
+    }
+    #[cfg(not(feature = "metrics"))]
+    let _ = (provider, kind, elapsed, ok);
+}
+
+/// Record the tokens of one response
+pub fn record_tokens(prompt_tokens: u32, completion_tokens: u32) {
+    #[cfg(feature = "metrics")]
+    {
+        metrics::counter!(LLM_TOKENS, "kind" => "prompt").increment(prompt_tokens.into());
+        metrics::counter!(LLM_TOKENS, "kind" => "completion").increment(completion_tokens.into());
+    }
+    #[cfg(not(feature = "metrics"))]
+    let _ = (prompt_tokens, completion_tokens);
+}
+
+/// Record one tool run
+pub fn record_tool_call(tool: &str, ok: bool) {
+    #[cfg(feature = "metrics")]
+    {
+        let outcome = if ok { "success" } else { "error" };
+        metrics::counter!(TOOL_CALLS, "tool" => tool.to_string(), "outcome" => outcome)
+            .increment(1);
+    }
+    #[cfg(not(feature = "metrics"))]
+    let _ = (tool, ok);
+}
@@ -3,9 +3,134 @@
 //! This module provides the core tool traits that LLM services can use.
 //! The actual tool implementations are in the luts-tools crate.
 
+use crate::telemetry;
 use anyhow::Error;
 use async_trait::async_trait;
+use luts_common::LutsError;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::BTreeSet;
+use std::time::Duration;
+use tracing::{Instrument, info, info_span};
+
+// The envelope lives in luts-core so tools built on either crate report the same shape
+pub use luts_core::tools::{ToolError, ToolErrorKind, ToolResult, schema_violations};
+
+/// What running a tool can affect, from least to most risky
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolSafety {
+    /// Only reads local state, e.g. a calculator or a memory search
+    ReadOnly,
+    /// Reaches out over the network but changes nothing locally
+    Network,
+    /// Creates or changes stored state
+    Mutating,
+    /// Deletes stored state
+    Destructive,
+}
+
+impl std::fmt::Display for ToolSafety {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ToolSafety::ReadOnly => "read_only",
+            ToolSafety::Network => "network",
+            ToolSafety::Mutating => "mutating",
+            ToolSafety::Destructive => "destructive",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Safety levels a session lets tools run at
+///
+/// Tools outside the allowlist aren't run; the model gets a
+/// [`ToolErrorKind::Refused`] result instead, so it can answer without them.
+/// The default allows every level.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolPolicy {
+    /// Levels that may run
+    pub allowed: BTreeSet<ToolSafety>,
+}
+
+impl Default for ToolPolicy {
+    fn default() -> Self {
+        Self::allow_all()
+    }
+}
+
+impl ToolPolicy {
+    /// Run tools of every safety level
+    pub fn allow_all() -> Self {
+        Self::allowing([
+            ToolSafety::ReadOnly,
+            ToolSafety::Network,
+            ToolSafety::Mutating,
+            ToolSafety::Destructive,
+        ])
+    }
+
+    /// Run only tools that change nothing and stay off the network
+    pub fn read_only() -> Self {
+        Self::allowing([ToolSafety::ReadOnly])
+    }
+
+    /// Run tools of the given levels only
+    pub fn allowing(levels: impl IntoIterator<Item = ToolSafety>) -> Self {
+        Self {
+            allowed: levels.into_iter().collect(),
+        }
+    }
+
+    /// Whether tools of `safety` may run
+    pub fn allows(&self, safety: ToolSafety) -> bool {
+        self.allowed.contains(&safety)
+    }
+
+    /// Policy allowing only the levels both this one and `other` allow
+    pub fn intersect(&self, other: &ToolPolicy) -> Self {
+        Self {
+            allowed: self.allowed.intersection(&other.allowed).copied().collect(),
+        }
+    }
+
+    /// Run `tool` with [`AiTool::run`] if the policy allows it
+    ///
+    /// A disallowed tool isn't run and gets a [`ToolErrorKind::Refused`]
+    /// result naming its safety level.
+    pub async fn run<T: AiTool + ?Sized>(&self, tool: &T, params: Value) -> ToolResult {
+        let safety = tool.safety();
+        if !self.allows(safety) {
+            info!("Refusing {} tool {} under the session's tool policy", safety, tool.name());
+            telemetry::record_tool_call(tool.name(), false);
+            return ToolResult::failure_with_kind(
+                tool.name(),
+                ToolErrorKind::Refused,
+                format!(
+                    "Tool '{}' is not allowed in this session ({} tools are disabled)",
+                    tool.name(),
+                    safety
+                ),
+            );
+        }
+        tool.run(params).await
+    }
+}
+
+/// Validate, execute and time out a tool for [`AiTool::run`]
+async fn run_with_checks<T: AiTool + ?Sized>(tool: &T, params: Value) -> ToolResult {
+    if let Err(e) = tool.validate_params(&params) {
+        let kind = ToolErrorKind::Validation;
+        return ToolResult::failure_with_kind(tool.name(), kind, e.to_string());
+    }
+    let Some(limit) = tool.timeout() else {
+        return ToolResult::from_outcome(tool.name(), tool.execute(params).await);
+    };
+    match tokio::time::timeout(limit, tool.execute(params)).await {
+        Ok(outcome) => ToolResult::from_outcome(tool.name(), outcome),
+        Err(_) => ToolResult::failure_with_kind(tool.name(), ToolErrorKind::Timeout, "timeout"),
+    }
+}
 
 /// A tool that can be used by an AI assistant
 #[async_trait]
@@ -22,11 +147,50 @@ pub trait AiTool: Send + Sync {
     /// Execute the tool with the given parameters
     async fn execute(&self, params: Value) -> Result<Value, Error>;
 
+    /// What running the tool can affect, for [`ToolPolicy`] to gate on
+    ///
+    /// The default, [`ToolSafety::Mutating`], keeps tools that don't say
+    /// out of read-only sessions; read-only, network and destructive tools
+    /// should override it.
+    fn safety(&self) -> ToolSafety {
+        ToolSafety::Mutating
+    }
+
+    /// How long [`AiTool::run`] waits for the tool before giving up
+    ///
+    /// `None`, the default, waits indefinitely.
+    fn timeout(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Execute the tool and report the outcome in a [`ToolResult`] envelope
+    ///
+    /// Callers that show or forward tool output should use this rather than
+    /// [`AiTool::execute`], so every tool's output has the same shape. A run
+    /// that outlasts [`AiTool::timeout`] is abandoned and reported as a
+    /// [`ToolErrorKind::Timeout`] failure with the error `"timeout"`.
+    /// Parameters that fail [`AiTool::validate_params`] are reported as a
+    /// [`ToolErrorKind::Validation`] failure without running the tool.
+    ///
+    /// Each run is traced in a `tool_call` span and counted in the tool
+    /// metrics.
+    async fn run(&self, params: Value) -> ToolResult {
+        let span = info_span!("tool_call", tool = self.name());
+        let result = run_with_checks(self, params).instrument(span).await;
+        telemetry::record_tool_call(self.name(), result.is_success());
+        result
+    }
+
     /// Validate the parameters against the schema
-    fn validate_params(&self, _params: &Value) -> Result<(), Error> {
-        // Default implementation that just passes validation
-        // In a real implementation, this would validate against the schema
-        Ok(())
+    ///
+    /// Fails with [`LutsError::InvalidParams`] listing every missing field and
+    /// mismatched value; see [`schema_violations`] for what is checked.
+    fn validate_params(&self, params: &Value) -> Result<(), Error> {
+        let violations = schema_violations(&self.schema(), params);
+        if violations.is_empty() {
+            return Ok(());
+        }
+        Err(LutsError::InvalidParams(format!("{}: {}", self.name(), violations.join("; "))).into())
     }
     
     /// Convert to a genai Tool
@@ -54,6 +218,10 @@ mod tests {
             "Echoes back the input text"
         }
 
+        fn safety(&self) -> ToolSafety {
+            ToolSafety::ReadOnly
+        }
+
         fn schema(&self) -> Value {
             json!({
                 "type": "object",
@@ -76,6 +244,128 @@ mod tests {
         }
     }
 
+    struct SumTool;
+
+    #[async_trait]
+    impl AiTool for SumTool {
+        fn name(&self) -> &str {
+            "sum"
+        }
+
+        fn description(&self) -> &str {
+            "Adds two numbers"
+        }
+
+        fn schema(&self) -> Value {
+            json!({
+                "type": "object",
+                "properties": {
+                    "a": { "type": "number" },
+                    "b": { "type": "number" }
+                },
+                "required": ["a", "b"]
+            })
+        }
+
+        async fn execute(&self, params: Value) -> Result<Value, Error> {
+            let a = params["a"].as_f64().ok_or_else(|| anyhow::anyhow!("Missing 'a'"))?;
+            let b = params["b"].as_f64().ok_or_else(|| anyhow::anyhow!("Missing 'b'"))?;
+            Ok(json!({ "sum": a + b }))
+        }
+    }
+
+    /// Tool that takes far longer than it allows itself
+    struct SleepyTool;
+
+    #[async_trait]
+    impl AiTool for SleepyTool {
+        fn name(&self) -> &str {
+            "sleepy"
+        }
+
+        fn description(&self) -> &str {
+            "Sleeps for a long time"
+        }
+
+        fn schema(&self) -> Value {
+            json!({ "type": "object", "properties": {} })
+        }
+
+        fn timeout(&self) -> Option<Duration> {
+            Some(Duration::from_millis(50))
+        }
+
+        async fn execute(&self, _params: Value) -> Result<Value, Error> {
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            Ok(json!("finally awake"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_reports_timeout_instead_of_hanging() {
+        let started = std::time::Instant::now();
+        let result = SleepyTool.run(json!({})).await;
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(!result.is_success());
+        assert_eq!(result.tool, "sleepy");
+        assert_eq!(result.display_text(), "timeout");
+        assert_eq!(result.tool_error().unwrap().kind, ToolErrorKind::Timeout);
+
+        // Tools without a timeout are unaffected
+        assert!(EchoTool.timeout().is_none());
+        assert!(EchoTool.run(json!({"text": "hi"})).await.is_success());
+    }
+
+    #[tokio::test]
+    async fn test_text_and_json_results_round_trip_through_envelope() {
+        let text = EchoTool.run(json!({"text": "say \"hi\""})).await;
+        let parsed = ToolResult::from_json(&text.to_json()).unwrap();
+        assert_eq!(parsed, text);
+        assert_eq!(parsed.result, Some(json!("say \"hi\"")));
+        assert_eq!(parsed.display_text(), "say \"hi\"");
+
+        let structured = SumTool.run(json!({"a": 2, "b": 3})).await;
+        let parsed = ToolResult::from_json(&structured.to_json()).unwrap();
+        assert_eq!(parsed, structured);
+        assert_eq!(parsed.result, Some(json!({"sum": 5.0})));
+        assert_eq!(parsed.display_text(), r#"{"sum":5.0}"#);
+
+        let failed = SumTool.run(json!({"a": 2})).await;
+        let parsed = ToolResult::from_json(&failed.to_json()).unwrap();
+        assert!(!parsed.is_success());
+        assert_eq!(parsed.tool, "sum");
+        // The schema check turns the call away before the tool runs
+        assert_eq!(parsed.error_kind, Some(ToolErrorKind::Validation));
+        assert_eq!(
+            parsed.display_text(),
+            "Invalid parameters: sum: missing required field `b`"
+        );
+
+        // Plain tool output isn't mistaken for an envelope
+        assert_eq!(ToolResult::from_json(r#"{"sum": 5}"#), None);
+        assert_eq!(ToolResult::from_json("hello"), None);
+    }
+
+    #[tokio::test]
+    async fn test_read_only_policy_refuses_undeclared_tools() {
+        let policy = ToolPolicy::read_only();
+
+        // Tools that don't declare a level count as mutating
+        assert_eq!(SumTool.safety(), ToolSafety::Mutating);
+        let refused = policy.run(&SumTool, json!({"a": 1, "b": 2})).await;
+        assert!(!refused.is_success());
+        assert_eq!(refused.tool, "sum");
+        assert_eq!(refused.tool_error().unwrap().kind, ToolErrorKind::Refused);
+        assert!(refused.display_text().contains("mutating"));
+        let parsed = ToolResult::from_json(&refused.to_json()).unwrap();
+        assert_eq!(parsed.error_kind, Some(ToolErrorKind::Refused));
+
+        assert!(policy.run(&EchoTool, json!({"text": "hi"})).await.is_success());
+        assert!(ToolPolicy::default().run(&SumTool, json!({"a": 1, "b": 2})).await.is_success());
+        assert_eq!(ToolPolicy::default().intersect(&policy), policy);
+    }
+
     #[tokio::test]
     async fn test_echo_tool() {
         let tool = EchoTool;
@@ -164,6 +164,17 @@ impl MemoryBlock {
         }
     }
 
+    /// Change the block type
+    pub fn set_block_type(&mut self, block_type: BlockType) {
+        if self.metadata.block_type != block_type {
+            self.metadata.block_type = block_type;
+            self.metadata.updated_at = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64;
+        }
+    }
+
     /// Set a property
     pub fn set_property(&mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) {
         self.metadata.properties.insert(key.into(), value.into());
@@ -202,6 +213,8 @@ impl MemoryBlock {
 /// Builder for creating memory blocks
 pub struct MemoryBlockBuilder {
     id: Option<BlockId>,
+    /// Namespace to derive the ID from the content under, if no ID is set
+    content_hash_namespace: Option<String>,
     block_type: Option<BlockType>,
     user_id: Option<String>,
     session_id: Option<String>,
@@ -218,6 +231,7 @@ impl MemoryBlockBuilder {
     pub fn new() -> Self {
         MemoryBlockBuilder {
             id: None,
+            content_hash_namespace: None,
             block_type: None,
             user_id: None,
             session_id: None,
@@ -236,6 +250,13 @@ impl MemoryBlockBuilder {
         self
     }
 
+    /// Derive the ID from the content under `namespace` (see
+    /// [`BlockId::from_content_hash`]) unless an ID is set
+    pub fn with_content_hash_id(mut self, namespace: impl Into<String>) -> Self {
+        self.content_hash_namespace = Some(namespace.into());
+        self
+    }
+
     /// Set the block type
     pub fn with_type(mut self, block_type: BlockType) -> Self {
         self.block_type = Some(block_type);
@@ -315,19 +336,24 @@ impl MemoryBlockBuilder {
 
         let block_type = self
             .block_type
-            .ok_or_else(|| LutsError::Memory("Block type is required".to_string()))?;
+            .ok_or_else(|| LutsError::Validation("Block type is required".to_string()))?;
         let user_id = self
             .user_id
-            .ok_or_else(|| LutsError::Memory("User ID is required".to_string()))?;
+            .ok_or_else(|| LutsError::Validation("User ID is required".to_string()))?;
         let content = self
             .content
-            .ok_or_else(|| LutsError::Memory("Content is required".to_string()))?;
+            .ok_or_else(|| LutsError::Validation("Content is required".to_string()))?;
 
         let created_at = self.created_at.unwrap_or(now);
+        let id = match (self.id, self.content_hash_namespace) {
+            (Some(id), _) => id,
+            (None, Some(namespace)) => BlockId::from_content_hash(&namespace, &content),
+            (None, None) => BlockId::generate(),
+        };
 
         Ok(MemoryBlock {
             metadata: MemoryBlockMetadata {
-                id: self.id.unwrap_or_else(BlockId::generate),
+                id,
                 block_type,
                 user_id,
                 session_id: self.session_id,
@@ -428,4 +454,31 @@ mod tests {
             .build();
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_builder_content_hash_ids() {
+        let build = |content: &str| {
+            MemoryBlockBuilder::new()
+                .with_content_hash_id("import:user123")
+                .with_type(BlockType::Fact)
+                .with_user_id("user123")
+                .with_content(MemoryContent::Text(content.to_string()))
+                .build()
+                .unwrap()
+        };
+
+        assert_eq!(build("the sky is blue").id(), build("the sky is blue").id());
+        assert_ne!(build("the sky is blue").id(), build("grass is green").id());
+
+        // An explicit ID wins
+        let block = MemoryBlockBuilder::new()
+            .with_content_hash_id("import:user123")
+            .with_id("fact-1")
+            .with_type(BlockType::Fact)
+            .with_user_id("user123")
+            .with_content(MemoryContent::Text("the sky is blue".to_string()))
+            .build()
+            .unwrap();
+        assert_eq!(block.id().as_str(), "fact-1");
+    }
 }
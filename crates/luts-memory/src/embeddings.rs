@@ -6,6 +6,14 @@
 use luts_common::{LutsError, Result};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, RwLock};
+
+mod cache;
+mod openai;
+
+pub use cache::{CachingEmbeddingService, EmbeddingCacheStats};
+pub use openai::OpenAIEmbeddingProvider;
 
 /// Configuration for embedding services
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +30,9 @@ pub struct EmbeddingConfig {
     pub max_text_length: usize,
     /// Dimensions of the embedding vectors
     pub dimensions: usize,
+    /// Number of embeddings to keep in an LRU cache (0 disables caching)
+    #[serde(default)]
+    pub cache_capacity: usize,
 }
 
 impl Default for EmbeddingConfig {
@@ -33,6 +44,7 @@ impl Default for EmbeddingConfig {
             base_url: None,
             max_text_length: 8192,
             dimensions: 1536, // OpenAI text-embedding-3-small
+            cache_capacity: 0,
         }
     }
 }
@@ -42,7 +54,8 @@ impl Default for EmbeddingConfig {
 pub enum EmbeddingProvider {
     /// OpenAI embedding API
     OpenAI,
-    /// Local embedding service (e.g., sentence-transformers)
+    /// Self-hosted server with an OpenAI-compatible `/v1/embeddings`
+    /// endpoint (e.g., llama.cpp or text-embeddings-inference)
     Local,
     /// Ollama with embedding models
     Ollama,
@@ -50,6 +63,18 @@ pub enum EmbeddingProvider {
     Mock,
 }
 
+impl EmbeddingProvider {
+    /// Name the provider is registered under in [`EmbeddingServiceFactory`]
+    pub fn registry_name(&self) -> &'static str {
+        match self {
+            EmbeddingProvider::OpenAI => "openai",
+            EmbeddingProvider::Local => "local",
+            EmbeddingProvider::Ollama => "ollama",
+            EmbeddingProvider::Mock => "mock",
+        }
+    }
+}
+
 /// A trait for embedding services that can generate vector representations
 #[async_trait]
 pub trait EmbeddingService: Send + Sync {
@@ -58,6 +83,15 @@ pub trait EmbeddingService: Send + Sync {
     
     /// Generate embeddings for multiple texts (more efficient for batch processing)
     async fn embed_texts(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+
+    /// Generate embeddings for borrowed texts, one vector per input in order
+    async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for text in texts {
+            embeddings.push(self.embed_text(text).await?);
+        }
+        Ok(embeddings)
+    }
     
     /// Get the dimensions of embeddings produced by this service
     fn dimensions(&self) -> usize;
@@ -75,6 +109,9 @@ pub struct VectorSearchConfig {
     pub min_relevance: f32,
     /// Similarity metric to use
     pub metric: SimilarityMetric,
+    /// Dimensions of the query provider's embeddings; stored vectors must match
+    #[serde(default)]
+    pub expected_dimensions: Option<usize>,
 }
 
 impl Default for VectorSearchConfig {
@@ -83,7 +120,33 @@ impl Default for VectorSearchConfig {
             max_results: luts_common::vector_search::DEFAULT_MAX_RESULTS,
             min_relevance: luts_common::vector_search::DEFAULT_MIN_RELEVANCE,
             metric: SimilarityMetric::Cosine,
+            expected_dimensions: None,
+        }
+    }
+}
+
+impl VectorSearchConfig {
+    /// Check that query and stored vectors can be compared
+    ///
+    /// Without `expected_dimensions` the query vector's own length is expected.
+    pub fn check_dimensions(&self, query_dims: usize, stored_dims: usize) -> Result<()> {
+        let expected = self.expected_dimensions.unwrap_or(query_dims);
+        if query_dims != expected {
+            return Err(LutsError::EmbeddingDimensionMismatch {
+                subject: "query vector".to_string(),
+                expected,
+                actual: query_dims,
+            });
+        }
+        if stored_dims != expected {
+            // Blocks embedded by an earlier provider need re-embedding
+            return Err(LutsError::EmbeddingDimensionMismatch {
+                subject: "stored embeddings".to_string(),
+                expected,
+                actual: stored_dims,
+            });
         }
+        Ok(())
     }
 }
 
@@ -98,6 +161,40 @@ pub enum SimilarityMetric {
     DotProduct,
 }
 
+impl SimilarityMetric {
+    /// Score two vectors under this metric, where higher means more similar
+    ///
+    /// Euclidean distance is reported as `1 / (1 + distance)` so every metric
+    /// ranks descending; cosine and dot product scores are returned as-is.
+    pub fn similarity(&self, a: &[f32], b: &[f32]) -> f32 {
+        match self {
+            SimilarityMetric::Cosine => VectorSimilarity::cosine_similarity(a, b),
+            SimilarityMetric::Euclidean => 1.0 / (1.0 + VectorSimilarity::euclidean_distance(a, b)),
+            SimilarityMetric::DotProduct => VectorSimilarity::dot_product(a, b),
+        }
+    }
+
+    /// Human-readable name, as used in error messages
+    pub fn name(&self) -> &'static str {
+        match self {
+            SimilarityMetric::Cosine => "cosine",
+            SimilarityMetric::Euclidean => "euclidean",
+            SimilarityMetric::DotProduct => "dot product",
+        }
+    }
+
+    /// Prepare an embedding for an index searched with this metric
+    ///
+    /// Cosine indexes store unit vectors, so scores don't depend on how the
+    /// provider scales its output; other metrics keep vectors as produced.
+    pub fn prepare_for_index(&self, mut embedding: Vec<f32>) -> Vec<f32> {
+        if *self == SimilarityMetric::Cosine {
+            VectorSimilarity::normalize(&mut embedding);
+        }
+        embedding
+    }
+}
+
 /// Vector similarity operations
 pub struct VectorSimilarity;
 
@@ -119,6 +216,23 @@ impl VectorSimilarity {
         }
     }
     
+    /// Calculate the dot product of two vectors
+    pub fn dot_product(a: &[f32], b: &[f32]) -> f32 {
+        if a.len() != b.len() {
+            return 0.0;
+        }
+
+        a.iter().zip(b.iter()).map(|(x, y)| x * y).sum()
+    }
+
+    /// Scale a vector to unit length in place; zero vectors are left as they are
+    pub fn normalize(vector: &mut [f32]) {
+        let norm: f32 = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|x| *x /= norm);
+        }
+    }
+
     /// Calculate euclidean distance between two vectors
     pub fn euclidean_distance(a: &[f32], b: &[f32]) -> f32 {
         if a.len() != b.len() {
@@ -133,15 +247,77 @@ impl VectorSimilarity {
     }
 }
 
+/// Builds an embedding service from configuration
+pub type EmbeddingServiceBuilder =
+    Box<dyn Fn(EmbeddingConfig) -> Result<Arc<dyn EmbeddingService>> + Send + Sync>;
+
+/// Registered providers by name, seeded with the built-in ones
+static PROVIDERS: LazyLock<RwLock<HashMap<String, EmbeddingServiceBuilder>>> =
+    LazyLock::new(|| {
+        let mut providers: HashMap<String, EmbeddingServiceBuilder> = HashMap::new();
+        providers.insert(
+            "mock".to_string(),
+            Box::new(|config| Ok(Arc::new(MockEmbeddingService::new(config)))),
+        );
+        providers.insert(
+            "openai".to_string(),
+            Box::new(|config| Ok(Arc::new(OpenAIEmbeddingProvider::new(config)?))),
+        );
+        providers.insert(
+            "local".to_string(),
+            Box::new(|config| Ok(Arc::new(OpenAIEmbeddingProvider::local(config)))),
+        );
+        RwLock::new(providers)
+    });
+
 /// Factory for creating embedding services
 pub struct EmbeddingServiceFactory;
 
 impl EmbeddingServiceFactory {
+    /// Register a provider under `name`, replacing any existing one
+    ///
+    /// Use this to plug in e.g. an in-process ONNX model in place of the
+    /// built-in `"local"` provider.
+    pub fn register(name: &str, builder: EmbeddingServiceBuilder) {
+        PROVIDERS
+            .write()
+            .unwrap()
+            .insert(name.to_lowercase(), builder);
+    }
+
+    /// Names of all registered providers
+    pub fn registered() -> Vec<String> {
+        let mut names: Vec<String> = PROVIDERS.read().unwrap().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
     /// Create an embedding service from configuration
-    pub fn create(config: EmbeddingConfig) -> Result<Box<dyn EmbeddingService>> {
-        match config.provider {
-            EmbeddingProvider::Mock => Ok(Box::new(MockEmbeddingService::new(config))),
-            _ => Err(LutsError::Memory("Only mock embedding service is implemented in this phase".to_string())),
+    pub fn create(config: EmbeddingConfig) -> Result<Arc<dyn EmbeddingService>> {
+        let name = config.provider.registry_name();
+        Self::create_by_name(name, config)
+    }
+
+    /// Create an embedding service using the provider registered under `name`
+    ///
+    /// The service is wrapped in a [`CachingEmbeddingService`] when the config
+    /// sets a non-zero `cache_capacity`.
+    pub fn create_by_name(name: &str, config: EmbeddingConfig) -> Result<Arc<dyn EmbeddingService>> {
+        let providers = PROVIDERS.read().unwrap();
+        let builder = providers.get(&name.to_lowercase()).ok_or_else(|| {
+            LutsError::Config(format!(
+                "No embedding provider registered as '{}'",
+                name
+            ))
+        })?;
+
+        let model = config.model.clone();
+        let capacity = config.cache_capacity;
+        let service = builder(config)?;
+        if capacity > 0 {
+            Ok(Arc::new(CachingEmbeddingService::new(service, model, capacity)))
+        } else {
+            Ok(service)
         }
     }
 }
@@ -219,4 +395,50 @@ mod tests {
         assert_eq!(embedding.len(), 384);
         assert!(embedding.iter().all(|&x| x >= -1.0 && x <= 1.0));
     }
+
+    #[tokio::test]
+    async fn test_register_custom_provider() {
+        EmbeddingServiceFactory::register(
+            "test-fixed",
+            Box::new(|config| Ok(Arc::new(MockEmbeddingService::new(config)))),
+        );
+        assert!(EmbeddingServiceFactory::registered().contains(&"test-fixed".to_string()));
+
+        let config = EmbeddingConfig {
+            dimensions: 8,
+            ..Default::default()
+        };
+        let service = EmbeddingServiceFactory::create_by_name("Test-Fixed", config).unwrap();
+        assert_eq!(service.embed_text("hello").await.unwrap().len(), 8);
+
+        assert!(EmbeddingServiceFactory::create_by_name("missing", EmbeddingConfig::default()).is_err());
+    }
+
+    #[test]
+    fn test_local_provider_is_registered() {
+        assert!(EmbeddingServiceFactory::registered().contains(&"local".to_string()));
+
+        // No API key needed for a self-hosted server
+        let config = EmbeddingConfig {
+            provider: EmbeddingProvider::Local,
+            model: "nomic-embed-text".to_string(),
+            dimensions: 768,
+            ..Default::default()
+        };
+        let service = EmbeddingServiceFactory::create(config).unwrap();
+        assert_eq!(service.dimensions(), 768);
+    }
+
+    #[test]
+    fn test_check_dimensions() {
+        let config = VectorSearchConfig {
+            expected_dimensions: Some(384),
+            ..Default::default()
+        };
+        assert!(config.check_dimensions(384, 384).is_ok());
+
+        let err = config.check_dimensions(384, 1536).unwrap_err();
+        assert!(err.to_string().contains("1536"), "unexpected error: {}", err);
+        assert!(config.check_dimensions(1536, 384).is_err());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,206 @@
+//! LRU cache in front of an embedding service
+//!
+//! Re-indexing embeds the same block content over and over; caching by
+//! (model, text) keeps those repeats from reaching the provider.
+
+use super::EmbeddingService;
+use async_trait::async_trait;
+use lru::LruCache;
+use luts_common::{LutsError, Result};
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Hit/miss counters for a [`CachingEmbeddingService`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmbeddingCacheStats {
+    /// Texts served without calling the inner service
+    pub hits: u64,
+    /// Texts sent to the inner service
+    pub misses: u64,
+    /// Embeddings currently cached
+    pub entries: usize,
+    /// Maximum number of cached embeddings
+    pub capacity: usize,
+}
+
+/// The model and the exact text an embedding was made from
+type CacheKey = (String, String);
+
+/// Embedding service decorator that caches vectors by (model, text)
+///
+/// Duplicates within one batch are embedded once; the repeats count as hits.
+pub struct CachingEmbeddingService {
+    inner: Arc<dyn EmbeddingService>,
+    model: String,
+    cache: Mutex<LruCache<CacheKey, Vec<f32>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CachingEmbeddingService {
+    /// Wrap `inner`, keeping up to `capacity` embeddings for `model`
+    pub fn new(inner: Arc<dyn EmbeddingService>, model: impl Into<String>, capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            inner,
+            model: model.into(),
+            cache: Mutex::new(LruCache::new(capacity)),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Current hit/miss counters and cache occupancy
+    pub fn cache_stats(&self) -> EmbeddingCacheStats {
+        let cache = self.cache.lock().unwrap();
+        EmbeddingCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: cache.len(),
+            capacity: cache.cap().get(),
+        }
+    }
+
+    fn key(&self, text: &str) -> CacheKey {
+        (self.model.clone(), text.to_string())
+    }
+}
+
+#[async_trait]
+impl EmbeddingService for CachingEmbeddingService {
+    async fn embed_text(&self, text: &str) -> Result<Vec<f32>> {
+        let mut embeddings = self.embed_batch(&[text]).await?;
+        embeddings
+            .pop()
+            .ok_or_else(|| LutsError::Memory("Embedding service returned no vectors".to_string()))
+    }
+
+    async fn embed_texts(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let texts: Vec<&str> = texts.iter().map(String::as_str).collect();
+        self.embed_batch(&texts).await
+    }
+
+    async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        let keys: Vec<CacheKey> = texts.iter().map(|text| self.key(text)).collect();
+        let mut cached: HashMap<CacheKey, Vec<f32>> = HashMap::new();
+        let mut missing_keys = Vec::new();
+        let mut missing_texts = Vec::new();
+
+        {
+            let mut cache = self.cache.lock().unwrap();
+            for (text, key) in texts.iter().zip(&keys) {
+                if cached.contains_key(key) {
+                    self.hits.fetch_add(1, Ordering::Relaxed);
+                } else if let Some(embedding) = cache.get(key) {
+                    cached.insert(key.clone(), embedding.clone());
+                    self.hits.fetch_add(1, Ordering::Relaxed);
+                } else if missing_keys.contains(key) {
+                    // Repeated within this batch; embedded once below
+                    self.hits.fetch_add(1, Ordering::Relaxed);
+                } else {
+                    missing_keys.push(key.clone());
+                    missing_texts.push(text.to_string());
+                    self.misses.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+
+        if !missing_texts.is_empty() {
+            let fresh = self.inner.embed_texts(&missing_texts).await?;
+            if fresh.len() != missing_texts.len() {
+                return Err(LutsError::Memory(format!(
+                    "Embedding service returned {} vectors for {} inputs",
+                    fresh.len(),
+                    missing_texts.len()
+                )));
+            }
+
+            let mut cache = self.cache.lock().unwrap();
+            for (key, embedding) in missing_keys.into_iter().zip(fresh) {
+                cache.put(key.clone(), embedding.clone());
+                cached.insert(key, embedding);
+            }
+        }
+
+        Ok(keys.iter().map(|key| cached[key].clone()).collect())
+    }
+
+    fn dimensions(&self) -> usize {
+        self.inner.dimensions()
+    }
+
+    fn max_text_length(&self) -> usize {
+        self.inner.max_text_length()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embeddings::{EmbeddingConfig, MockEmbeddingService};
+    use std::sync::atomic::AtomicUsize;
+
+    /// Mock service that counts how many texts reach it
+    struct CountingService {
+        inner: MockEmbeddingService,
+        embedded: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl EmbeddingService for CountingService {
+        async fn embed_text(&self, text: &str) -> Result<Vec<f32>> {
+            self.embedded.fetch_add(1, Ordering::SeqCst);
+            self.inner.embed_text(text).await
+        }
+
+        async fn embed_texts(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            self.embedded.fetch_add(texts.len(), Ordering::SeqCst);
+            self.inner.embed_texts(texts).await
+        }
+
+        fn dimensions(&self) -> usize {
+            self.inner.dimensions()
+        }
+
+        fn max_text_length(&self) -> usize {
+            self.inner.max_text_length()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cache_dedupes_and_evicts() {
+        let counting = Arc::new(CountingService {
+            inner: MockEmbeddingService::new(EmbeddingConfig {
+                dimensions: 16,
+                ..Default::default()
+            }),
+            embedded: AtomicUsize::new(0),
+        });
+        let service = CachingEmbeddingService::new(counting.clone(), "test-model", 2);
+
+        let batch = service.embed_batch(&["a", "b", "a"]).await.unwrap();
+        assert_eq!(batch.len(), 3);
+        assert_eq!(batch[0], batch[2]);
+        assert_eq!(counting.embedded.load(Ordering::SeqCst), 2);
+
+        assert_eq!(service.embed_text("a").await.unwrap(), batch[0]);
+        assert_eq!(counting.embedded.load(Ordering::SeqCst), 2);
+
+        // "b" is least recently used, so "c" pushes it out
+        service.embed_text("c").await.unwrap();
+        service.embed_text("b").await.unwrap();
+        assert_eq!(counting.embedded.load(Ordering::SeqCst), 4);
+
+        assert_eq!(
+            service.cache_stats(),
+            EmbeddingCacheStats {
+                hits: 2,
+                misses: 4,
+                entries: 2,
+                capacity: 2,
+            }
+        );
+    }
+}
@@ -0,0 +1,179 @@
+//! OpenAI-compatible `/v1/embeddings` provider
+
+use super::{EmbeddingConfig, EmbeddingService};
+use async_trait::async_trait;
+use luts_common::{LutsError, Result};
+use serde::Deserialize;
+use serde_json::json;
+
+/// Default endpoint when the config has no `base_url`
+const DEFAULT_BASE_URL: &str = "https://api.openai.com";
+
+/// Default endpoint of a self-hosted server, the port llama.cpp and
+/// text-embeddings-inference listen on
+const DEFAULT_LOCAL_BASE_URL: &str = "http://localhost:8080";
+
+/// Embedding service backed by the OpenAI embeddings API
+///
+/// Works with any server exposing the same `/v1/embeddings` endpoint by
+/// setting `base_url`. The API key comes from the config or `OPENAI_API_KEY`.
+pub struct OpenAIEmbeddingProvider {
+    config: EmbeddingConfig,
+    client: reqwest::Client,
+    api_key: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+impl OpenAIEmbeddingProvider {
+    /// Create a provider from configuration
+    pub fn new(config: EmbeddingConfig) -> Result<Self> {
+        let api_key = config
+            .api_key
+            .clone()
+            .or_else(|| std::env::var("OPENAI_API_KEY").ok());
+
+        if api_key.is_none() && config.base_url.is_none() {
+            return Err(LutsError::Config(
+                "OpenAI embeddings need an api_key or the OPENAI_API_KEY environment variable"
+                    .to_string(),
+            ));
+        }
+
+        Ok(Self {
+            config,
+            client: reqwest::Client::new(),
+            api_key,
+        })
+    }
+
+    /// Create a provider for a self-hosted server with the same API
+    ///
+    /// `base_url` defaults to `http://localhost:8080`. Only an API key set in
+    /// the config is sent; `OPENAI_API_KEY` isn't handed to local servers.
+    pub fn local(mut config: EmbeddingConfig) -> Self {
+        config
+            .base_url
+            .get_or_insert_with(|| DEFAULT_LOCAL_BASE_URL.to_string());
+        Self {
+            api_key: config.api_key.clone(),
+            config,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn endpoint(&self) -> String {
+        let base_url = self.config.base_url.as_deref().unwrap_or(DEFAULT_BASE_URL);
+        format!("{}/v1/embeddings", base_url.trim_end_matches('/'))
+    }
+
+    fn truncate<'a>(&self, text: &'a str) -> &'a str {
+        match text.char_indices().nth(self.config.max_text_length) {
+            Some((end, _)) => &text[..end],
+            None => text,
+        }
+    }
+}
+
+#[async_trait]
+impl EmbeddingService for OpenAIEmbeddingProvider {
+    async fn embed_text(&self, text: &str) -> Result<Vec<f32>> {
+        let mut embeddings = self.embed_texts(&[text.to_string()]).await?;
+        embeddings
+            .pop()
+            .ok_or_else(|| LutsError::Memory("Embedding API returned no vectors".to_string()))
+    }
+
+    async fn embed_texts(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let input: Vec<&str> = texts.iter().map(|text| self.truncate(text)).collect();
+        let mut body = json!({
+            "model": self.config.model,
+            "input": input,
+        });
+        // Only the text-embedding-3 family accepts a requested dimension
+        if self.config.model.starts_with("text-embedding-3") {
+            body["dimensions"] = json!(self.config.dimensions);
+        }
+
+        let mut request = self.client.post(self.endpoint()).json(&body);
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+
+        let response = request.send().await.map_err(|e| {
+            if e.is_timeout() {
+                LutsError::Timeout(format!("embedding request to {}", self.endpoint()))
+            } else {
+                LutsError::Memory(format!("Embedding request failed: {}", e))
+            }
+        })?;
+        let status = response.status();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<u64>().ok())
+                .map(std::time::Duration::from_secs);
+            return Err(LutsError::RateLimited { retry_after });
+        }
+        if !status.is_success() {
+            let detail = response.text().await.unwrap_or_default();
+            return Err(LutsError::Memory(format!(
+                "Embedding API returned {}: {}",
+                status, detail
+            )));
+        }
+
+        let mut parsed: EmbeddingResponse = response
+            .json()
+            .await
+            .map_err(|e| LutsError::Memory(format!("Invalid embedding response: {}", e)))?;
+        parsed.data.sort_by_key(|item| item.index);
+
+        if parsed.data.len() != texts.len() {
+            return Err(LutsError::Memory(format!(
+                "Embedding API returned {} vectors for {} inputs",
+                parsed.data.len(),
+                texts.len()
+            )));
+        }
+
+        parsed
+            .data
+            .into_iter()
+            .map(|item| {
+                if item.embedding.len() == self.config.dimensions {
+                    Ok(item.embedding)
+                } else {
+                    Err(LutsError::EmbeddingDimensionMismatch {
+                        subject: format!("{} embeddings", self.config.model),
+                        expected: self.config.dimensions,
+                        actual: item.embedding.len(),
+                    })
+                }
+            })
+            .collect()
+    }
+
+    fn dimensions(&self) -> usize {
+        self.config.dimensions
+    }
+
+    fn max_text_length(&self) -> usize {
+        self.config.max_text_length
+    }
+}
@@ -12,12 +12,16 @@ pub mod utils;
 // Re-export commonly used types
 pub use block::{MemoryBlock, MemoryBlockBuilder, MemoryBlockMetadata};
 pub use embeddings::{
-    EmbeddingConfig, EmbeddingProvider, EmbeddingService, EmbeddingServiceFactory,
+    CachingEmbeddingService, EmbeddingCacheStats, EmbeddingConfig, EmbeddingProvider,
+    EmbeddingService, EmbeddingServiceBuilder, EmbeddingServiceFactory, OpenAIEmbeddingProvider,
     VectorSearchConfig, VectorSimilarity, SimilarityMetric
 };
 pub use storage::{
-    MemoryStore, MemoryManager, MemoryQuery, MemoryStats, QuerySort, VectorQuery,
-    SurrealMemoryStore, SurrealConfig, AuthConfig, RelationType
+    MemoryStore, MemoryManager, MemoryQuery, MemoryQueryPage, MemoryStats, QuerySort, VectorQuery,
+    SurrealMemoryStore, SurrealConfig, AuthConfig, RelationType, EmbeddingFailurePolicy,
+    InMemoryMemoryStore, Encryptor, AesGcmEncryptor, EncryptionKey, RetryConfig, CircuitBreakerConfig,
+    CircuitBreakerStatus, CircuitState, DedupConfig, DedupPolicy, StoreOutcome, Reranker,
+    IdentityReranker, RerankConfig,
 };
 pub use types::{BlockId, BlockType, MemoryContent, Relevance, TimeRange};
 pub use utils::BlockUtils;
@@ -1,12 +1,13 @@
 //! Storage implementation for memory blocks using SurrealDB
 //!
 //! This module provides the SurrealDB-based storage backend for memory blocks
-//! with automatic embedding generation and vector similarity search.
+//! with automatic embedding generation and vector similarity search, plus an
+//! in-memory backend for tests and ephemeral sessions.
 
 use crate::{
     block::MemoryBlock,
-    embeddings::{EmbeddingService, VectorSearchConfig},
-    types::{BlockId, BlockType, MemoryContent},
+    embeddings::{EmbeddingService, SimilarityMetric, VectorSearchConfig},
+    types::{BlockId, BlockType, MemoryContent, Relevance},
 };
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
@@ -14,18 +15,33 @@ use luts_common::{LutsError, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
 use surrealdb::{
-    Surreal,
+    RecordId, Response, Surreal,
     engine::local::{Db, Mem, SurrealKv},
 };
 use tokio::sync::RwLock;
-use tracing::{debug, info, warn};
+use tracing::{debug, info, instrument, warn};
+
+mod dedup;
+mod encryption;
+mod in_memory;
+mod rerank;
+mod resilience;
+
+pub use dedup::{DedupConfig, DedupPolicy, MERGED_FROM_PROPERTY, StoreOutcome};
+use encryption::Keyring;
+pub use encryption::{AesGcmEncryptor, EncryptionKey, Encryptor};
+pub use in_memory::InMemoryMemoryStore;
+pub use rerank::{IdentityReranker, RerankConfig, Reranker};
+pub use luts_common::retry::RetryConfig;
+pub use resilience::{CircuitBreakerConfig, CircuitBreakerStatus, CircuitState};
+use resilience::Resilience;
 
 /// A trait defining operations for a memory storage system
 #[async_trait]
 pub trait MemoryStore: Send + Sync {
-    /// Store a memory block
+    /// Store a memory block, replacing any stored block with the same ID
     async fn store(&self, block: MemoryBlock) -> Result<BlockId>;
 
     /// Retrieve a memory block by its ID
@@ -37,9 +53,139 @@ pub trait MemoryStore: Send + Sync {
     /// Update an existing memory block
     async fn update(&self, id: &BlockId, block: MemoryBlock) -> Result<MemoryBlock>;
 
+    /// Store several memory blocks at once, replacing stored blocks with the same IDs
+    ///
+    /// The default implementation stores blocks one by one and stops at the first
+    /// failure, leaving earlier blocks stored. Backends that support it should
+    /// override this with a single atomic write.
+    async fn store_many(&self, blocks: Vec<MemoryBlock>) -> Result<Vec<BlockId>> {
+        validate_batch(&blocks)?;
+
+        let mut ids = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            ids.push(self.store(block).await?);
+        }
+        Ok(ids)
+    }
+
+    /// Delete several memory blocks, returning how many were deleted
+    async fn delete_many(&self, ids: &[BlockId]) -> Result<u64> {
+        let mut deleted = 0;
+        for id in ids {
+            if self.delete(id).await? {
+                deleted += 1;
+            }
+        }
+        Ok(deleted)
+    }
+
+    /// Replace several existing blocks, each matched by its ID
+    ///
+    /// Returns how many were updated. The default implementation updates
+    /// blocks one by one and stops at the first failure, leaving earlier
+    /// updates applied. Backends that support it should override this with a
+    /// single atomic write.
+    async fn update_many(&self, blocks: Vec<MemoryBlock>) -> Result<u64> {
+        for block in &blocks {
+            self.update(block.id(), block.clone()).await?;
+        }
+        Ok(blocks.len() as u64)
+    }
+
     /// Search for memory blocks based on criteria
     async fn query(&self, query: MemoryQuery) -> Result<Vec<MemoryBlock>>;
 
+    /// Dimensions of the embeddings this store generates for its blocks
+    ///
+    /// `None` means blocks are stored without embeddings, so vector searches
+    /// can never match anything.
+    fn embedding_dimensions(&self) -> Option<usize> {
+        None
+    }
+
+    /// Fetch one page of blocks, resuming after `query.cursor`
+    ///
+    /// `query.limit` is the page size. The default implementation runs the full
+    /// query and pages in memory; backends should override it with a keyset query.
+    async fn query_paged(&self, query: MemoryQuery) -> Result<MemoryQueryPage> {
+        let page_size = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+        let sort = paged_sort(&query)?;
+        let after = query.cursor.as_deref().map(PageCursor::decode).transpose()?;
+
+        let mut blocks = self
+            .query(MemoryQuery {
+                limit: None,
+                cursor: None,
+                ..query
+            })
+            .await?;
+
+        blocks.sort_by(|a, b| {
+            let ordering = a
+                .created_at()
+                .cmp(&b.created_at())
+                .then_with(|| a.id().as_str().cmp(b.id().as_str()));
+            match sort {
+                QuerySort::OldestFirst => ordering,
+                _ => ordering.reverse(),
+            }
+        });
+
+        let remaining: Vec<MemoryBlock> = match after {
+            Some(cursor) => blocks
+                .into_iter()
+                .filter(|block| cursor.precedes(block, sort))
+                .collect(),
+            None => blocks,
+        };
+
+        Ok(MemoryQueryPage::from_overfetch(remaining, page_size))
+    }
+
+    /// Record a `rel` edge from one block to another
+    ///
+    /// Relating the same pair twice leaves a single edge. Stores without
+    /// relationship support return an error.
+    async fn relate(&self, _from: &BlockId, _to: &BlockId, _rel: RelationType) -> Result<()> {
+        Err(LutsError::Memory(
+            "This store does not support block relationships".to_string(),
+        ))
+    }
+
+    /// Remove a `rel` edge, returning whether there was one
+    async fn unrelate(&self, _from: &BlockId, _to: &BlockId, _rel: RelationType) -> Result<bool> {
+        Err(LutsError::Memory(
+            "This store does not support block relationships".to_string(),
+        ))
+    }
+
+    /// IDs of the blocks `id` has an edge to, of type `rel` or of any type
+    async fn related_ids(&self, _id: &BlockId, _rel: Option<RelationType>) -> Result<Vec<BlockId>> {
+        Ok(Vec::new())
+    }
+
+    /// Up to `limit` of a user's blocks that have no embedding, in ID order
+    /// and starting after `after`
+    ///
+    /// Stores that don't keep embeddings return an error.
+    async fn blocks_without_embedding(
+        &self,
+        _user_id: &str,
+        _after: Option<&BlockId>,
+        _limit: usize,
+    ) -> Result<Vec<MemoryBlock>> {
+        Err(LutsError::Memory(
+            "This store does not keep embeddings".to_string(),
+        ))
+    }
+
+    /// Attach embeddings to stored blocks; blocks that no longer exist are skipped
+    async fn set_embeddings(&self, _embeddings: Vec<(BlockId, Vec<f32>)>) -> Result<()> {
+        Err(LutsError::Memory(
+            "This store does not keep embeddings".to_string(),
+        ))
+    }
+
     /// Clear all data for a specific user
     async fn clear_user_data(&self, user_id: &str) -> Result<u64>;
 
@@ -60,6 +206,10 @@ pub struct MemoryQuery {
     pub block_types: Vec<BlockType>,
 
     /// Text to search for in block content
+    ///
+    /// When [`SurrealMemoryStore`] encrypts content this can't be evaluated by
+    /// the database, so it is matched after decryption and scans every block
+    /// that matches the other filters.
     pub content_contains: Option<String>,
 
     /// Time range filters
@@ -74,6 +224,144 @@ pub struct MemoryQuery {
 
     /// Vector similarity search parameters
     pub vector_search: Option<VectorQuery>,
+
+    /// Opaque cursor from a previous [`MemoryQueryPage`], used by `query_paged`
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+/// Default page size for `query_paged` when the query has no limit
+const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Times an embedding backfill batch is retried after the provider rate limits it
+const BACKFILL_RATE_LIMIT_RETRIES: u32 = 5;
+
+/// Wait before retrying a rate-limited backfill batch when the provider doesn't
+/// say how long; doubled on each retry
+const BACKFILL_RATE_LIMIT_WAIT: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// One page of results from [`MemoryStore::query_paged`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryQueryPage {
+    /// Blocks in this page, in the requested sort order
+    pub blocks: Vec<MemoryBlock>,
+
+    /// Cursor for the next page, or `None` when this is the last page
+    pub next_cursor: Option<String>,
+}
+
+impl MemoryQueryPage {
+    /// Build a page from up to `page_size + 1` sorted blocks; the extra block
+    /// only signals that another page exists
+    fn from_overfetch(mut blocks: Vec<MemoryBlock>, page_size: usize) -> Self {
+        let has_more = blocks.len() > page_size;
+        blocks.truncate(page_size);
+
+        let next_cursor = if has_more {
+            blocks.last().map(|block| PageCursor::from_block(block).encode())
+        } else {
+            None
+        };
+
+        MemoryQueryPage {
+            blocks,
+            next_cursor,
+        }
+    }
+}
+
+/// Sort key of the last block in a page: creation time (ms) plus block ID
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PageCursor {
+    created_at: u64,
+    id: String,
+}
+
+impl PageCursor {
+    fn from_block(block: &MemoryBlock) -> Self {
+        PageCursor {
+            created_at: block.created_at(),
+            id: block.id().as_str().to_string(),
+        }
+    }
+
+    fn encode(&self) -> String {
+        format!("{}:{}", self.created_at, self.id)
+    }
+
+    fn decode(cursor: &str) -> Result<Self> {
+        let (created_at, id) = cursor
+            .split_once(':')
+            .ok_or_else(|| LutsError::InvalidQuery(format!("malformed page cursor {:?}", cursor)))?;
+        let created_at = created_at
+            .parse::<u64>()
+            .map_err(|_| LutsError::InvalidQuery(format!("malformed page cursor {:?}", cursor)))?;
+
+        Ok(PageCursor {
+            created_at,
+            id: id.to_string(),
+        })
+    }
+
+    /// Whether `block` comes after this cursor in the given sort order
+    fn precedes(&self, block: &MemoryBlock, sort: QuerySort) -> bool {
+        let key = (block.created_at(), block.id().as_str());
+        let cursor = (self.created_at, self.id.as_str());
+        match sort {
+            QuerySort::OldestFirst => key > cursor,
+            _ => key < cursor,
+        }
+    }
+
+    /// Creation time in the RFC3339 form used by the SurrealDB store
+    fn created_at_rfc3339(&self) -> String {
+        DateTime::from_timestamp_millis(self.created_at as i64)
+            .unwrap_or_default()
+            .to_rfc3339()
+    }
+}
+
+/// Check a batch before writing it, naming the index of the first bad block
+fn validate_batch(blocks: &[MemoryBlock]) -> Result<()> {
+    let mut seen = std::collections::HashSet::new();
+    for (index, block) in blocks.iter().enumerate() {
+        if block.id().as_str().is_empty() {
+            return Err(LutsError::Validation(format!(
+                "Block at index {} has an empty ID",
+                index
+            )));
+        }
+        if block.user_id().is_empty() {
+            return Err(LutsError::Validation(format!(
+                "Block at index {} has no user ID",
+                index
+            )));
+        }
+        if !seen.insert(block.id().as_str()) {
+            return Err(LutsError::Validation(format!(
+                "Block at index {} duplicates ID {} from earlier in the batch",
+                index,
+                block.id()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Cursor pagination only supports the time-based sort orders
+fn paged_sort(query: &MemoryQuery) -> Result<QuerySort> {
+    if query.vector_search.is_some() {
+        return Err(LutsError::InvalidQuery(
+            "Cursor pagination is not supported for vector search".to_string(),
+        ));
+    }
+
+    match query.sort.unwrap_or_default() {
+        QuerySort::Relevance => Err(LutsError::InvalidQuery(
+            "Cursor pagination requires NewestFirst or OldestFirst sort".to_string(),
+        )),
+        sort => Ok(sort),
+    }
 }
 
 /// Vector similarity search query
@@ -84,6 +372,32 @@ pub struct VectorQuery {
 
     /// Configuration for vector search
     pub search_config: VectorSearchConfig,
+
+    /// Metric for this query only, taking precedence over `search_config.metric`
+    #[serde(default)]
+    pub metric_override: Option<SimilarityMetric>,
+}
+
+impl VectorQuery {
+    /// The similarity metric this query should be scored with
+    pub fn metric(&self) -> SimilarityMetric {
+        self.metric_override.unwrap_or(self.search_config.metric)
+    }
+
+    /// Check that this query can search embeddings indexed for `indexed`
+    ///
+    /// Embeddings stored before metrics were recorded (`None`) match any query.
+    pub fn check_metric(&self, indexed: Option<SimilarityMetric>) -> Result<()> {
+        match indexed {
+            Some(indexed) if indexed != self.metric() => Err(LutsError::Memory(format!(
+                "Stored embeddings were indexed for {} similarity but the query uses {}; \
+                 query with the index metric or re-index the stored blocks",
+                indexed.name(),
+                self.metric().name()
+            ))),
+            _ => Ok(()),
+        }
+    }
 }
 
 /// Sort order for memory queries
@@ -121,6 +435,7 @@ impl Default for MemoryQuery {
             limit: Some(100),
             sort: Some(QuerySort::default()),
             vector_search: None,
+            cursor: None,
         }
     }
 }
@@ -133,9 +448,18 @@ pub enum SurrealConfig {
         path: PathBuf,
         namespace: String,
         database: String,
+        /// Encrypts block content at rest; stored in cleartext when unset
+        #[serde(default)]
+        encryption_key: Option<EncryptionKey>,
     },
     /// Memory-based SurrealDB
-    Memory { namespace: String, database: String },
+    Memory {
+        namespace: String,
+        database: String,
+        /// Encrypts block content at rest; stored in cleartext when unset
+        #[serde(default)]
+        encryption_key: Option<EncryptionKey>,
+    },
 }
 
 impl Default for SurrealConfig {
@@ -144,10 +468,32 @@ impl Default for SurrealConfig {
             path: PathBuf::from("./data/memory.db"),
             namespace: "luts".to_string(),
             database: "memory".to_string(),
+            encryption_key: None,
+        }
+    }
+}
+
+impl SurrealConfig {
+    /// Key block content is encrypted with, if any
+    pub fn encryption_key(&self) -> Option<&EncryptionKey> {
+        match self {
+            SurrealConfig::File { encryption_key, .. }
+            | SurrealConfig::Memory { encryption_key, .. } => encryption_key.as_ref(),
         }
     }
 }
 
+/// What the store should do when the embedding provider fails
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum EmbeddingFailurePolicy {
+    /// Propagate the embedding error to the caller
+    Fail,
+    /// Store blocks without an embedding (flagged `needs_embedding`) and
+    /// fall back to lexical search until the provider recovers
+    #[default]
+    Degrade,
+}
+
 /// Authentication configuration for SurrealDB
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthConfig {
@@ -166,6 +512,23 @@ pub enum RelationType {
     Related,
 }
 
+impl RelationType {
+    pub const ALL: [RelationType; 3] = [
+        RelationType::References,
+        RelationType::DerivedFrom,
+        RelationType::Related,
+    ];
+
+    /// SurrealDB table holding edges of this type
+    fn edge_table(self) -> &'static str {
+        match self {
+            RelationType::References => "block_references",
+            RelationType::DerivedFrom => "block_derived_from",
+            RelationType::Related => "block_related",
+        }
+    }
+}
+
 /// Enhanced memory block with embedding and metadata for SurrealDB
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EnhancedMemoryBlock {
@@ -182,6 +545,10 @@ pub struct EnhancedMemoryBlock {
     pub last_accessed: String,
     pub created_at: String,
     pub updated_at: String,
+    #[serde(default)]
+    pub needs_embedding: bool, // Embedding failed on write, re-embed later
+    #[serde(default)]
+    pub embedding_metric: Option<SimilarityMetric>, // Metric the embedding was indexed for
 }
 
 impl From<MemoryBlock> for EnhancedMemoryBlock {
@@ -207,6 +574,8 @@ impl From<MemoryBlock> for EnhancedMemoryBlock {
             last_accessed: Utc::now().to_rfc3339(),
             created_at,
             updated_at,
+            needs_embedding: false,
+            embedding_metric: None,
         }
     }
 }
@@ -248,19 +617,59 @@ impl From<EnhancedMemoryBlock> for MemoryBlock {
             builder = builder.with_session_id(&session_id);
         }
 
+        // Keep the original creation time; page cursors are built from it
+        if let Ok(created_at) = DateTime::parse_from_rfc3339(&enhanced.created_at) {
+            builder = builder.with_created_at(created_at.timestamp_millis() as u64);
+        }
+
+        // Carry the raw similarity score from vector search back to the caller
+        if let Some(score) = enhanced.relevance_score {
+            builder = builder.with_relevance(Relevance::raw(score));
+        }
+
         builder
             .build()
             .expect("Enhanced block should always be valid")
     }
 }
 
+/// File-backed connections opened by this process, keyed by path
+///
+/// SurrealKV holds an exclusive lock on its files, so stores opened on the same
+/// path (for example by per-request agent instances) share one connection.
+static FILE_CONNECTIONS: LazyLock<tokio::sync::Mutex<HashMap<PathBuf, Surreal<Db>>>> =
+    LazyLock::new(|| tokio::sync::Mutex::new(HashMap::new()));
+
+/// How often stores with an embedding service retry blocks stored without one
+const REEMBED_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Blocks re-embedded per pass of the background task
+const REEMBED_BATCH_SIZE: usize = 32;
+
+/// Stops the background re-embedding task when the last store sharing it is dropped
+struct ReembeddingTask(tokio::task::AbortHandle);
+
+impl Drop for ReembeddingTask {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
 /// SurrealDB memory store implementation with automatic embedding generation
+///
+/// With an embedding service, a background task re-embeds blocks that were
+/// stored without an embedding while the provider was down.
 #[derive(Clone)]
 pub struct SurrealMemoryStore {
     db: Surreal<Db>,
     _config: SurrealConfig,
     initialized: Arc<RwLock<bool>>,
     embedding_service: Option<Arc<dyn EmbeddingService>>,
+    failure_policy: EmbeddingFailurePolicy,
+    index_metric: Option<SimilarityMetric>,
+    keyring: Keyring,
+    resilience: Resilience,
+    reembedding: Option<Arc<ReembeddingTask>>,
 }
 
 impl SurrealMemoryStore {
@@ -276,6 +685,17 @@ impl SurrealMemoryStore {
     ) -> Result<Self> {
         let db = match &config {
             SurrealConfig::File { path, .. } => {
+                // Reuse an already-open connection so several stores can share the file
+                let mut connections = FILE_CONNECTIONS.lock().await;
+                if let Some(db) = connections.get(path) {
+                    debug!("Reusing SurrealDB file connection at: {:?}", path);
+                    return Ok(Self::from_connection(
+                        db.clone(),
+                        config.clone(),
+                        embedding_service,
+                    ));
+                }
+
                 debug!("Initializing SurrealDB in file mode at: {:?}", path);
 
                 let db: Surreal<Db> =
@@ -300,6 +720,7 @@ impl SurrealMemoryStore {
                 })?;
 
                 info!("SurrealDB initialized with file backend at: {:?}", path);
+                connections.insert(path.clone(), db.clone());
                 db
             }
             SurrealConfig::Memory { .. } => {
@@ -327,12 +748,189 @@ impl SurrealMemoryStore {
             }
         };
 
-        Ok(Self {
+        Ok(Self::from_connection(db, config, embedding_service))
+    }
+
+    fn from_connection(
+        db: Surreal<Db>,
+        config: SurrealConfig,
+        embedding_service: Option<Arc<dyn EmbeddingService>>,
+    ) -> Self {
+        let keyring = Keyring::default();
+        if let Some(key) = config.encryption_key() {
+            keyring.push(Arc::new(key.encryptor()));
+        }
+        let store = Self {
             db,
             _config: config,
             initialized: Arc::new(RwLock::new(false)),
             embedding_service,
-        })
+            failure_policy: EmbeddingFailurePolicy::default(),
+            index_metric: None,
+            keyring,
+            resilience: Resilience::default(),
+            reembedding: None,
+        };
+        store.with_reembedding_interval(REEMBED_INTERVAL)
+    }
+
+    /// Set how often blocks stored without an embedding are retried
+    ///
+    /// Replaces the running background task. Stores without an embedding
+    /// service have nothing to re-embed and run no task.
+    pub fn with_reembedding_interval(mut self, interval: std::time::Duration) -> Self {
+        self.reembedding = None;
+        if self.embedding_service.is_some() {
+            // The task's copy doesn't hold the guard, so dropping the last
+            // caller-held store stops it
+            let task = self.spawn_reembedding_task(interval, REEMBED_BATCH_SIZE);
+            self.reembedding = Some(Arc::new(ReembeddingTask(task.abort_handle())));
+        }
+        self
+    }
+
+    /// Set how embedding provider failures are handled
+    pub fn with_failure_policy(mut self, policy: EmbeddingFailurePolicy) -> Self {
+        self.failure_policy = policy;
+        self
+    }
+
+    /// Index embeddings for `metric`
+    ///
+    /// The metric is recorded with each embedding and vector queries using
+    /// another one are rejected. Cosine indexes store normalized vectors.
+    pub fn with_index_metric(mut self, metric: SimilarityMetric) -> Self {
+        self.index_metric = Some(metric);
+        self
+    }
+
+    /// Set how transient database errors are retried
+    pub fn with_retry_config(mut self, retry: RetryConfig) -> Self {
+        self.resilience = self.resilience.with_retry(retry);
+        self
+    }
+
+    /// Set when repeated database failures open the circuit breaker
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreakerConfig) -> Self {
+        self.resilience = self.resilience.with_breaker(breaker);
+        self
+    }
+
+    /// Circuit breaker state, for health checks and monitoring
+    pub fn breaker_status(&self) -> CircuitBreakerStatus {
+        self.resilience.status()
+    }
+
+    /// Set a freshly generated embedding on a block, prepared for the index metric
+    fn set_embedding(&self, block: &mut EnhancedMemoryBlock, embedding: Vec<f32>) {
+        block.embedding = Some(match self.index_metric {
+            Some(metric) => metric.prepare_for_index(embedding),
+            None => embedding,
+        });
+        block.embedding_metric = self.index_metric;
+    }
+
+    /// Encrypt text and binary block content at rest with AES-256-GCM
+    ///
+    /// Embeddings and the metadata used for filtering stay in cleartext. The
+    /// key can also be set through [`SurrealConfig`]'s `encryption_key`.
+    pub fn with_encryption_key(self, key: [u8; 32]) -> Self {
+        self.with_encryptor(Arc::new(AesGcmEncryptor::new(&key)))
+    }
+
+    /// Encrypt text and binary block content at rest with a custom encryptor
+    ///
+    /// Calling this again adds another key: the newest one encrypts, while
+    /// earlier ones are kept for reading blocks that haven't been rotated.
+    pub fn with_encryptor(self, encryptor: Arc<dyn Encryptor>) -> Self {
+        self.keyring.push(encryptor);
+        self
+    }
+
+    /// Re-encrypt all of a user's blocks under `new_key`, which becomes the current key
+    ///
+    /// Blocks stored in cleartext before encryption was enabled are encrypted
+    /// as well. Each block is updated on its own, so an interrupted rotation can
+    /// simply be run again. Returns the number of blocks rewritten.
+    pub async fn rotate_key(&self, user_id: &str, new_key: Arc<dyn Encryptor>) -> Result<u64> {
+        self.initialize_schema().await?;
+        self.keyring.push(new_key);
+
+        let mut response = self
+            .db
+            .query("SELECT record::id(id) AS id, content FROM memory_blocks WHERE user_id = $user_id")
+            .bind(("user_id", user_id.to_string()))
+            .await
+            .map_err(|e| LutsError::Storage(format!("Failed to load blocks for rotation: {}", e)))?;
+
+        let rows: Vec<serde_json::Value> = response
+            .take(0)
+            .map_err(|e| LutsError::Storage(format!("Failed to parse blocks for rotation: {}", e)))?;
+
+        let mut rotated = 0;
+        for row in rows {
+            let (Some(id), Some(stored)) = (
+                row.get("id").and_then(|v| v.as_str()),
+                row.get("content").and_then(|v| v.as_str()),
+            ) else {
+                continue;
+            };
+
+            let content = self.keyring.open(stored)?;
+            if !Keyring::is_sealed(stored) && !Self::encrypts_content(&content) {
+                continue;
+            }
+
+            self.db
+                .query("UPDATE type::thing('memory_blocks', $block_id) SET content = $content")
+                .bind(("block_id", id.to_string()))
+                .bind(("content", self.keyring.seal(&content)?))
+                .await
+                .map_err(|e| LutsError::Storage(format!("Failed to re-encrypt block: {}", e)))?;
+            rotated += 1;
+        }
+
+        info!("🔑 Re-encrypted {} blocks for user {}", rotated, user_id);
+        Ok(rotated)
+    }
+
+    /// Whether serialized content is a kind that gets encrypted at rest
+    fn encrypts_content(content: &str) -> bool {
+        matches!(
+            serde_json::from_str::<MemoryContent>(content),
+            Ok(MemoryContent::Text(_) | MemoryContent::Binary { .. })
+        )
+    }
+
+    /// Whether `content_contains` has to be matched after decryption
+    fn filters_content_locally(&self, query: &MemoryQuery) -> bool {
+        query.content_contains.is_some() && self.keyring.is_enabled()
+    }
+
+    /// Decrypt fetched rows, applying a content filter the database couldn't evaluate
+    fn open_blocks(
+        &self,
+        rows: Vec<EnhancedMemoryBlock>,
+        content_filter: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Vec<MemoryBlock>> {
+        let mut blocks = Vec::with_capacity(rows.len());
+        for mut row in rows {
+            row.content = self.keyring.open(&row.content)?;
+            if content_filter.is_some_and(|needle| !row.content.contains(needle)) {
+                continue;
+            }
+            blocks.push(row.into());
+            if limit.is_some_and(|limit| blocks.len() >= limit) {
+                break;
+            }
+        }
+        Ok(blocks)
+    }
+
+    /// Embedding dimensions of the configured service, if semantic search is available
+    pub fn embedding_dimensions(&self) -> Option<usize> {
+        self.embedding_service.as_ref().map(|service| service.dimensions())
     }
 
     /// Get a clone of the underlying SurrealDB connection
@@ -440,17 +1038,53 @@ impl SurrealMemoryStore {
             )
         };
 
+        // Refuse to compare vectors from different embedding models or metrics
+        let dims_query = format!(
+            "SELECT array::len(embedding) AS dims, embedding_metric AS metric
+             FROM memory_blocks {} GROUP BY dims, metric",
+            where_clause
+        );
+        let mut db_query = self.db.query(&dims_query);
+        for (key, value) in bindings.iter().cloned() {
+            db_query = db_query.bind((key, value));
+        }
+        let mut response = db_query.await.map_err(|e| {
+            LutsError::Storage(format!("Failed to check embedding dimensions: {}", e))
+        })?;
+        let stored_dims: Vec<serde_json::Value> = response.take(0).map_err(|e| {
+            LutsError::Storage(format!("Failed to parse embedding dimensions: {}", e))
+        })?;
+        for row in &stored_dims {
+            if let Some(dims) = row.get("dims").and_then(|v| v.as_u64()) {
+                vector_query
+                    .search_config
+                    .check_dimensions(vector_query.query_vector.len(), dims as usize)?;
+            }
+            let metric = row
+                .get("metric")
+                .and_then(|v| serde_json::from_value::<SimilarityMetric>(v.clone()).ok());
+            vector_query.check_metric(metric)?;
+        }
+
         let max_results = vector_query.search_config.max_results.min(1000); // Cap at 1000 for performance
         let min_relevance = vector_query.search_config.min_relevance;
 
-        // Use SurrealDB's vector similarity functions
+        // Use SurrealDB's vector functions; every score is ranked highest-first
+        // (see `SimilarityMetric::similarity` for how Euclidean is mapped)
+        let score_expr = match vector_query.metric() {
+            SimilarityMetric::Cosine => "vector::similarity::cosine(embedding, $query_vector)",
+            SimilarityMetric::Euclidean => {
+                "1 / (1 + vector::distance::euclidean(embedding, $query_vector))"
+            }
+            SimilarityMetric::DotProduct => "vector::dot(embedding, $query_vector)",
+        };
         let sql_query = format!(
-            "SELECT *, vector::similarity::cosine(embedding, $query_vector) AS similarity_score
+            "SELECT *, record::id(id) AS id, {} AS similarity_score
              FROM memory_blocks
              {}
              ORDER BY similarity_score DESC
              LIMIT {}",
-            where_clause, max_results
+            score_expr, where_clause, max_results
         );
 
         let mut db_query = self.db.query(&sql_query);
@@ -488,8 +1122,9 @@ impl SurrealMemoryStore {
                     LutsError::Storage(format!("Failed to parse enhanced memory block: {}", e))
                 })?;
 
-            // Set the relevance score
+            // Set the relevance score and decrypt the content
             enhanced_block.relevance_score = Some(similarity_score);
+            enhanced_block.content = self.keyring.open(&enhanced_block.content)?;
 
             // Convert to MemoryBlock and add to results
             memory_blocks.push(enhanced_block.into());
@@ -508,17 +1143,32 @@ impl SurrealMemoryStore {
     pub async fn semantic_search(
         &self,
         query_text: &str,
-        config: VectorSearchConfig,
+        mut config: VectorSearchConfig,
         user_id: Option<&str>,
     ) -> Result<Vec<MemoryBlock>> {
         if let Some(embedding_service) = &self.embedding_service {
+            config
+                .expected_dimensions
+                .get_or_insert(embedding_service.dimensions());
+
             // Generate embedding for the query text
-            let query_embedding = embedding_service.embed_text(query_text).await?;
+            let query_embedding = match embedding_service.embed_text(query_text).await {
+                Ok(embedding) => embedding,
+                Err(e) if self.failure_policy == EmbeddingFailurePolicy::Degrade => {
+                    warn!(
+                        "Embedding provider unavailable, falling back to lexical search: {}",
+                        e
+                    );
+                    return self.lexical_search(query_text, &config, user_id).await;
+                }
+                Err(e) => return Err(e),
+            };
 
             // Build the search query
             let vector_query = VectorQuery {
                 query_vector: query_embedding,
                 search_config: config,
+                metric_override: None,
             };
 
             let memory_query = MemoryQuery {
@@ -534,45 +1184,40 @@ impl SurrealMemoryStore {
             ))
         }
     }
-}
 
-#[async_trait]
-impl MemoryStore for SurrealMemoryStore {
-    async fn store(&self, block: MemoryBlock) -> Result<BlockId> {
-        self.initialize_schema().await?;
+    /// Plain substring search used when embeddings can't be generated
+    async fn lexical_search(
+        &self,
+        query_text: &str,
+        config: &VectorSearchConfig,
+        user_id: Option<&str>,
+    ) -> Result<Vec<MemoryBlock>> {
+        let memory_query = MemoryQuery {
+            user_id: user_id.map(|s| s.to_string()),
+            content_contains: Some(query_text.to_string()),
+            limit: Some(config.max_results),
+            ..Default::default()
+        };
 
+        self.query(memory_query).await
+    }
+
+    /// Convert a block for storage, generating its embedding when possible
+    async fn prepare_for_storage(&self, block: MemoryBlock) -> Result<EnhancedMemoryBlock> {
         let mut enhanced_block = EnhancedMemoryBlock::from(block);
+        let encrypt = Self::encrypts_content(&enhanced_block.content);
         let block_id = enhanced_block.id.clone();
 
         // 🚀 AUTOMATIC EMBEDDING GENERATION 🚀
         // Generate embedding if embedding service is available and block doesn't have one
         if enhanced_block.embedding.is_none() {
             if let Some(embedding_service) = &self.embedding_service {
-                // Extract text content from the serialized JSON content
-                let text_content = if let Ok(original_content) =
-                    serde_json::from_str::<MemoryContent>(&enhanced_block.content)
-                {
-                    match original_content {
-                        MemoryContent::Text(text) => text,
-                        MemoryContent::Json(json) => json.to_string(),
-                        MemoryContent::Binary { .. } => {
-                            // Skip embedding for binary content
-                            warn!(
-                                "Skipping embedding generation for binary content in block {}",
-                                block_id.as_str()
-                            );
-                            String::new()
-                        }
-                    }
-                } else {
-                    // Fallback: treat the content string as plain text
-                    enhanced_block.content.clone()
-                };
+                let text_content = Self::embeddable_text(&enhanced_block);
 
                 if !text_content.is_empty() {
                     match embedding_service.embed_text(&text_content).await {
                         Ok(embedding) => {
-                            enhanced_block.embedding = Some(embedding);
+                            self.set_embedding(&mut enhanced_block, embedding);
                             debug!(
                                 "✅ Generated embedding for block {} (content: {}...)",
                                 block_id.as_str(),
@@ -580,12 +1225,16 @@ impl MemoryStore for SurrealMemoryStore {
                             );
                         }
                         Err(e) => {
+                            if self.failure_policy == EmbeddingFailurePolicy::Fail {
+                                return Err(e);
+                            }
                             warn!(
-                                "❌ Failed to generate embedding for block {}: {}",
+                                "❌ Failed to generate embedding for block {}, storing without one: {}",
                                 block_id.as_str(),
                                 e
                             );
-                            // Continue without embedding rather than failing the entire operation
+                            // Continue without embedding; the re-embedding job picks it up later
+                            enhanced_block.needs_embedding = true;
                         }
                     }
                 }
@@ -597,61 +1246,251 @@ impl MemoryStore for SurrealMemoryStore {
             }
         }
 
-        info!(
-            "📦 Stored memory block {} with {} embedding",
-            block_id.as_str(),
-            if enhanced_block.embedding.is_some() {
-                "✅"
-            } else {
-                "❌"
-            }
-        );
-
-        // Store the enhanced block with embedding in SurrealDB
-        let block_id_string = block_id.as_str().to_string();
-        self.db
-            .query(
-                "CREATE type::thing('memory_blocks', $block_id) SET
-                    user_id = $user_id,
-                    session_id = $session_id,
-                    block_type = $block_type,
-                    content = $content,
-                    tags = $tags,
-                    embedding = $embedding,
-                    relevance_score = $relevance_score,
-                    access_count = $access_count,
-                    last_accessed = $last_accessed,
-                    created_at = $created_at,
-                    updated_at = $updated_at",
-            )
-            .bind(("block_id", block_id_string))
-            .bind(("user_id", enhanced_block.user_id))
-            .bind(("session_id", enhanced_block.session_id))
-            .bind(("block_type", enhanced_block.block_type))
-            .bind(("content", enhanced_block.content))
-            .bind(("tags", enhanced_block.tags))
-            .bind(("embedding", enhanced_block.embedding))
-            .bind(("relevance_score", enhanced_block.relevance_score))
-            .bind(("access_count", enhanced_block.access_count))
-            .bind(("last_accessed", enhanced_block.last_accessed))
-            .bind(("created_at", enhanced_block.created_at))
-            .bind(("updated_at", enhanced_block.updated_at))
-            .await
-            .map_err(|e| LutsError::Storage(format!("Failed to store memory block: {}", e)))?;
+        // Encrypt last: embeddings are generated from the plaintext
+        if encrypt {
+            enhanced_block.content = self.keyring.seal(&enhanced_block.content)?;
+        }
 
-        Ok(block_id)
+        Ok(enhanced_block)
     }
 
-    async fn retrieve(&self, id: &BlockId) -> Result<Option<MemoryBlock>> {
-        self.initialize_schema().await?;
-
-        let block_id_string = id.as_str().to_string();
-        let mut response = self
-            .db
-            .query("SELECT * FROM type::thing('memory_blocks', $block_id)")
-            .bind(("block_id", block_id_string))
-            .await
-            .map_err(|e| LutsError::Storage(format!("Failed to retrieve memory block: {}", e)))?;
+    /// Build the WHERE conditions and bindings shared by plain and paged queries
+    ///
+    /// `content_in_db` is false when content is encrypted and `content_contains`
+    /// is matched after decryption instead.
+    fn filter_conditions(
+        query: &MemoryQuery,
+        content_in_db: bool,
+    ) -> (Vec<String>, Vec<(&'static str, String)>) {
+        let mut conditions = Vec::new();
+        let mut bindings = Vec::new();
+
+        if let Some(user_id) = &query.user_id {
+            conditions.push("user_id = $user_id".to_string());
+            bindings.push(("user_id", user_id.clone()));
+        }
+
+        if let Some(session_id) = &query.session_id {
+            conditions.push("session_id = $session_id".to_string());
+            bindings.push(("session_id", session_id.clone()));
+        }
+
+        if !query.block_types.is_empty() {
+            let types: Vec<String> = query.block_types.iter().map(|t| t.to_string()).collect();
+            conditions.push("block_type IN $block_types".to_string());
+            bindings.push(("block_types", serde_json::to_string(&types).unwrap()));
+        }
+
+        if let Some(content) = query.content_contains.as_ref().filter(|_| content_in_db) {
+            conditions.push("content CONTAINS $content".to_string());
+            bindings.push(("content", content.clone()));
+        }
+
+        // Timestamps are stored as RFC 3339 in UTC, which sort as strings
+        if let Some(after) = query.created_after {
+            conditions.push("created_at >= $created_after".to_string());
+            bindings.push(("created_after", after.to_rfc3339()));
+        }
+
+        if let Some(before) = query.created_before {
+            conditions.push("created_at <= $created_before".to_string());
+            bindings.push(("created_before", before.to_rfc3339()));
+        }
+
+        (conditions, bindings)
+    }
+
+    /// Extract the text that should be embedded for a stored block
+    fn embeddable_text(enhanced_block: &EnhancedMemoryBlock) -> String {
+        match serde_json::from_str::<MemoryContent>(&enhanced_block.content) {
+            Ok(MemoryContent::Text(text)) => text,
+            Ok(MemoryContent::Json(json)) => json.to_string(),
+            Ok(MemoryContent::Binary { .. }) => {
+                // Skip embedding for binary content
+                warn!(
+                    "Skipping embedding generation for binary content in block {}",
+                    enhanced_block.id.as_str()
+                );
+                String::new()
+            }
+            // Fallback: treat the content string as plain text
+            Err(_) => enhanced_block.content.clone(),
+        }
+    }
+
+    /// Count blocks that were stored without an embedding and still need one
+    pub async fn pending_embedding_count(&self) -> Result<u64> {
+        self.initialize_schema().await?;
+
+        let mut response = self
+            .db
+            .query("SELECT count() AS count FROM memory_blocks WHERE needs_embedding = true GROUP ALL")
+            .await
+            .map_err(|e| LutsError::Storage(format!("Failed to count pending embeddings: {}", e)))?;
+
+        let rows: Vec<serde_json::Value> = response.take(0).map_err(|e| {
+            LutsError::Storage(format!("Failed to parse pending embedding count: {}", e))
+        })?;
+
+        Ok(rows
+            .first()
+            .and_then(|row| row.get("count"))
+            .and_then(|count| count.as_u64())
+            .unwrap_or(0))
+    }
+
+    /// Re-embed up to `batch_size` blocks flagged `needs_embedding`
+    ///
+    /// Stops at the first provider error so a still-unavailable provider isn't
+    /// hammered; the remaining blocks are retried on the next run. Returns the
+    /// number of blocks that received an embedding.
+    pub async fn reembed_pending(&self, batch_size: usize) -> Result<usize> {
+        let Some(embedding_service) = &self.embedding_service else {
+            return Ok(0);
+        };
+        self.initialize_schema().await?;
+
+        let sql_query = format!(
+            "SELECT *, record::id(id) AS id FROM memory_blocks WHERE needs_embedding = true LIMIT {}",
+            batch_size
+        );
+        let mut response = self
+            .db
+            .query(&sql_query)
+            .await
+            .map_err(|e| LutsError::Storage(format!("Failed to load pending blocks: {}", e)))?;
+
+        let pending: Vec<EnhancedMemoryBlock> = response
+            .take(0)
+            .map_err(|e| LutsError::Storage(format!("Failed to parse pending blocks: {}", e)))?;
+
+        let mut embedded = 0;
+        for mut enhanced_block in pending {
+            enhanced_block.content = self.keyring.open(&enhanced_block.content)?;
+            let text_content = Self::embeddable_text(&enhanced_block);
+            if !text_content.is_empty() {
+                match embedding_service.embed_text(&text_content).await {
+                    Ok(embedding) => self.set_embedding(&mut enhanced_block, embedding),
+                    Err(e) => {
+                        debug!("Embedding provider still unavailable: {}", e);
+                        break;
+                    }
+                }
+            }
+
+            self.db
+                .query(
+                    "UPDATE type::thing('memory_blocks', $block_id) SET
+                        embedding = $embedding,
+                        embedding_metric = $embedding_metric,
+                        needs_embedding = false",
+                )
+                .bind(("block_id", enhanced_block.id.as_str().to_string()))
+                .bind(("embedding", enhanced_block.embedding.clone()))
+                .bind(("embedding_metric", enhanced_block.embedding_metric))
+                .await
+                .map_err(|e| LutsError::Storage(format!("Failed to save embedding: {}", e)))?;
+
+            if enhanced_block.embedding.is_some() {
+                embedded += 1;
+            }
+        }
+
+        if embedded > 0 {
+            info!("Re-embedded {} blocks after provider recovery", embedded);
+        }
+
+        Ok(embedded)
+    }
+
+    /// Spawn a background task that periodically re-embeds pending blocks
+    pub fn spawn_reembedding_task(
+        &self,
+        interval: std::time::Duration,
+        batch_size: usize,
+    ) -> tokio::task::JoinHandle<()> {
+        let mut store = self.clone();
+        store.reembedding = None;
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                if let Err(e) = store.reembed_pending(batch_size).await {
+                    warn!("Background re-embedding failed: {}", e);
+                }
+            }
+        })
+    }
+}
+
+impl SurrealMemoryStore {
+    /// Store a block in a single attempt
+    async fn store_once(&self, block: MemoryBlock) -> Result<BlockId> {
+        self.initialize_schema().await?;
+
+        let enhanced_block = self.prepare_for_storage(block).await?;
+        let block_id = enhanced_block.id.clone();
+
+        info!(
+            "📦 Stored memory block {} with {} embedding",
+            block_id.as_str(),
+            if enhanced_block.embedding.is_some() {
+                "✅"
+            } else {
+                "❌"
+            }
+        );
+
+        // Store the enhanced block with embedding in SurrealDB, replacing
+        // any block with the same ID so re-imports don't fail
+        let block_id_string = block_id.as_str().to_string();
+        self.db
+            .query(
+                "UPSERT type::thing('memory_blocks', $block_id) SET
+                    user_id = $user_id,
+                    session_id = $session_id,
+                    block_type = $block_type,
+                    content = $content,
+                    tags = $tags,
+                    embedding = $embedding,
+                    relevance_score = $relevance_score,
+                    access_count = $access_count,
+                    last_accessed = $last_accessed,
+                    created_at = $created_at,
+                    updated_at = $updated_at,
+                    needs_embedding = $needs_embedding,
+                    embedding_metric = $embedding_metric",
+            )
+            .bind(("block_id", block_id_string))
+            .bind(("user_id", enhanced_block.user_id))
+            .bind(("session_id", enhanced_block.session_id))
+            .bind(("block_type", enhanced_block.block_type))
+            .bind(("content", enhanced_block.content))
+            .bind(("tags", enhanced_block.tags))
+            .bind(("embedding", enhanced_block.embedding))
+            .bind(("relevance_score", enhanced_block.relevance_score))
+            .bind(("access_count", enhanced_block.access_count))
+            .bind(("last_accessed", enhanced_block.last_accessed))
+            .bind(("created_at", enhanced_block.created_at))
+            .bind(("updated_at", enhanced_block.updated_at))
+            .bind(("needs_embedding", enhanced_block.needs_embedding))
+            .bind(("embedding_metric", enhanced_block.embedding_metric))
+            .await
+            .map_err(|e| LutsError::Storage(format!("Failed to store memory block: {}", e)))?;
+
+        Ok(block_id)
+    }
+
+    /// Retrieve a block in a single attempt
+    async fn retrieve_once(&self, id: &BlockId) -> Result<Option<MemoryBlock>> {
+        self.initialize_schema().await?;
+
+        let block_id_string = id.as_str().to_string();
+        let mut response = self
+            .db
+            .query("SELECT *, record::id(id) AS id FROM type::thing('memory_blocks', $block_id)")
+            .bind(("block_id", block_id_string))
+            .await
+            .map_err(|e| LutsError::Storage(format!("Failed to retrieve memory block: {}", e)))?;
 
         let enhanced_blocks: Vec<EnhancedMemoryBlock> = response
             .take(0)
@@ -661,49 +1500,323 @@ impl MemoryStore for SurrealMemoryStore {
             Some(enhanced_block) => {
                 // Update access tracking
                 let _ = self.update_access_count(id).await;
-                Ok(Some(enhanced_block.into()))
+                Ok(self.open_blocks(vec![enhanced_block], None, None)?.pop())
             }
             None => Ok(None),
         }
     }
 
-    async fn delete(&self, _id: &BlockId) -> Result<bool> {
-        // In real implementation, this would delete the block from SurrealDB
-        Ok(false)
+    /// Count a user's blocks by type in a single attempt
+    async fn get_stats_once(&self, user_id: &str) -> Result<MemoryStats> {
+        self.initialize_schema().await?;
+
+        #[derive(Deserialize)]
+        struct TypeStats {
+            block_type: String,
+            count: u64,
+            size: Option<u64>,
+        }
+
+        let mut response = self
+            .db
+            .query(
+                "SELECT block_type, count() AS count, math::sum(string::len(content)) AS size \
+                 FROM memory_blocks WHERE user_id = $user_id GROUP BY block_type",
+            )
+            .bind(("user_id", user_id.to_string()))
+            .await
+            .map_err(|e| LutsError::Storage(format!("Failed to count memory blocks: {}", e)))?;
+        let rows: Vec<TypeStats> = response
+            .take(0)
+            .map_err(|e| LutsError::Storage(format!("Failed to parse memory stats: {}", e)))?;
+
+        Ok(MemoryStats {
+            total_blocks: rows.iter().map(|row| row.count).sum(),
+            total_size_bytes: rows.iter().filter_map(|row| row.size).sum(),
+            blocks_by_type: rows.into_iter().map(|row| (row.block_type, row.count)).collect(),
+            last_updated: Utc::now(),
+        })
     }
 
-    async fn update(&self, _id: &BlockId, block: MemoryBlock) -> Result<MemoryBlock> {
-        // In real implementation, this would update the block in SurrealDB
-        Ok(block)
+    /// Store a batch in a single attempt
+    async fn store_many_once(&self, blocks: Vec<MemoryBlock>) -> Result<Vec<BlockId>> {
+        validate_batch(&blocks)?;
+        if blocks.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.initialize_schema().await?;
+
+        let ids: Vec<BlockId> = blocks.iter().map(|block| block.id().clone()).collect();
+        let mut records = Vec::with_capacity(blocks.len());
+        for (index, block) in blocks.into_iter().enumerate() {
+            let record = self.prepare_for_storage(block).await.map_err(|e| {
+                LutsError::Memory(format!("Block at index {} could not be stored: {}", index, e))
+            })?;
+            records.push(record);
+        }
+
+        // Upsert like `store`, inside a transaction: if any record is rejected,
+        // none are written
+        self.db
+            .query(
+                "BEGIN TRANSACTION; \
+                 FOR $block IN $blocks { \
+                     UPSERT type::thing('memory_blocks', $block.id) CONTENT $block; \
+                 }; \
+                 COMMIT TRANSACTION;",
+            )
+            .bind(("blocks", records))
+            .await
+            .and_then(Response::check)
+            .map_err(|e| LutsError::Storage(format!("Failed to store memory block batch: {}", e)))?;
+
+        info!("📦 Stored batch of {} memory blocks", ids.len());
+        Ok(ids)
     }
 
-    async fn query(&self, query: MemoryQuery) -> Result<Vec<MemoryBlock>> {
+    /// Replace a batch of blocks in a single attempt
+    async fn update_many_once(&self, blocks: Vec<MemoryBlock>) -> Result<u64> {
+        if blocks.is_empty() {
+            return Ok(0);
+        }
         self.initialize_schema().await?;
 
-        let mut conditions = Vec::new();
-        let mut bindings = Vec::new();
+        // Every block must already exist, so an update never creates one
+        let ids: Vec<String> = blocks
+            .iter()
+            .map(|block| block.id().as_str().to_string())
+            .collect();
+        let mut response = self
+            .db
+            .query("SELECT VALUE record::id(id) FROM memory_blocks WHERE record::id(id) IN $ids")
+            .bind(("ids", ids.clone()))
+            .await
+            .map_err(|e| LutsError::Storage(format!("Failed to check existing blocks: {}", e)))?;
+        let existing: Vec<String> = response
+            .take(0)
+            .map_err(|e| LutsError::Storage(format!("Failed to parse existing blocks: {}", e)))?;
+        if let Some(missing) = ids.iter().find(|id| !existing.contains(id)) {
+            return Err(LutsError::BlockNotFound(missing.clone()));
+        }
 
-        // Build WHERE conditions
-        if let Some(user_id) = &query.user_id {
-            conditions.push("user_id = $user_id".to_string());
-            bindings.push(("user_id", user_id.clone()));
+        let mut records = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            records.push(self.prepare_for_storage(block).await?);
         }
+        let things: Vec<RecordId> = ids
+            .iter()
+            .map(|id| RecordId::from(("memory_blocks", id.as_str())))
+            .collect();
 
-        if let Some(session_id) = &query.session_id {
-            conditions.push("session_id = $session_id".to_string());
-            bindings.push(("session_id", session_id.clone()));
+        // Replace old records with new ones together, or not at all
+        self.db
+            .query(
+                "BEGIN TRANSACTION; DELETE $things; INSERT INTO memory_blocks $blocks; \
+                 COMMIT TRANSACTION;",
+            )
+            .bind(("things", things))
+            .bind(("blocks", records))
+            .await
+            .and_then(Response::check)
+            .map_err(|e| LutsError::Storage(format!("Failed to update memory blocks: {}", e)))?;
+
+        info!("📦 Updated batch of {} memory blocks", ids.len());
+        Ok(ids.len() as u64)
+    }
+
+    /// Fetch blocks missing an embedding in a single attempt
+    async fn blocks_without_embedding_once(
+        &self,
+        user_id: &str,
+        after: Option<&BlockId>,
+        limit: usize,
+    ) -> Result<Vec<MemoryBlock>> {
+        self.initialize_schema().await?;
+
+        let after_clause = if after.is_some() {
+            " AND record::id(id) > $after"
+        } else {
+            ""
+        };
+        let sql_query = format!(
+            "SELECT *, record::id(id) AS id FROM memory_blocks WHERE user_id = $user_id \
+             AND (embedding IS NONE OR embedding IS NULL){} ORDER BY id ASC LIMIT {}",
+            after_clause, limit
+        );
+        let mut response = self
+            .db
+            .query(&sql_query)
+            .bind(("user_id", user_id.to_string()))
+            .bind(("after", after.map(|id| id.as_str().to_string())))
+            .await
+            .map_err(|e| LutsError::Storage(format!("Failed to load unembedded blocks: {}", e)))?;
+
+        let rows: Vec<EnhancedMemoryBlock> = response
+            .take(0)
+            .map_err(|e| LutsError::Storage(format!("Failed to parse unembedded blocks: {}", e)))?;
+        self.open_blocks(rows, None, None)
+    }
+
+    /// Save a batch of embeddings in a single attempt
+    async fn set_embeddings_once(&self, embeddings: Vec<(BlockId, Vec<f32>)>) -> Result<()> {
+        if embeddings.is_empty() {
+            return Ok(());
         }
+        self.initialize_schema().await?;
 
-        if !query.block_types.is_empty() {
-            let types: Vec<String> = query.block_types.iter().map(|t| t.to_string()).collect();
-            conditions.push("block_type IN $block_types".to_string());
-            bindings.push(("block_types", serde_json::to_string(&types).unwrap()));
+        // One UPDATE per block, applied together
+        let mut sql_query = String::from("BEGIN TRANSACTION;");
+        for index in 0..embeddings.len() {
+            sql_query.push_str(&format!(
+                " UPDATE type::thing('memory_blocks', $id_{index}) SET \
+                 embedding = $embedding_{index}, embedding_metric = $embedding_metric, \
+                 needs_embedding = false;"
+            ));
         }
+        sql_query.push_str(" COMMIT TRANSACTION;");
 
-        if let Some(content) = &query.content_contains {
-            conditions.push("content CONTAINS $content".to_string());
-            bindings.push(("content", content.clone()));
+        let mut db_query = self
+            .db
+            .query(&sql_query)
+            .bind(("embedding_metric", self.index_metric));
+        for (index, (id, embedding)) in embeddings.into_iter().enumerate() {
+            let embedding = match self.index_metric {
+                Some(metric) => metric.prepare_for_index(embedding),
+                None => embedding,
+            };
+            db_query = db_query
+                .bind((format!("id_{}", index), id.as_str().to_string()))
+                .bind((format!("embedding_{}", index), embedding));
+        }
+        db_query
+            .await
+            .and_then(Response::check)
+            .map_err(|e| LutsError::Storage(format!("Failed to save embeddings: {}", e)))?;
+        Ok(())
+    }
+
+    /// Record a relationship edge in a single attempt
+    async fn relate_once(&self, from: &BlockId, to: &BlockId, rel: RelationType) -> Result<()> {
+        self.initialize_schema().await?;
+        let records = [from, to].map(|id| RecordId::from(("memory_blocks", id.as_str())));
+
+        let mut response = self
+            .db
+            .query("SELECT VALUE record::id(id) FROM memory_blocks WHERE id IN $records")
+            .bind(("records", records.to_vec()))
+            .await
+            .map_err(|e| LutsError::Storage(format!("Failed to look up related blocks: {}", e)))?;
+        let existing: Vec<String> = response
+            .take(0)
+            .map_err(|e| LutsError::Storage(format!("Failed to parse related blocks: {}", e)))?;
+        if let Some(missing) = [from, to]
+            .into_iter()
+            .find(|id| !existing.iter().any(|e| e == id.as_str()))
+        {
+            return Err(LutsError::BlockNotFound(missing.to_string()));
+        }
+
+        if self.related_ids_once(from, Some(rel)).await?.contains(to) {
+            return Ok(());
+        }
+
+        let [from_record, to_record] = records;
+        self.db
+            .query(format!("RELATE $from->{}->$to", rel.edge_table()))
+            .bind(("from", from_record))
+            .bind(("to", to_record))
+            .await
+            .and_then(Response::check)
+            .map_err(|e| LutsError::Storage(format!("Failed to relate blocks: {}", e)))?;
+
+        debug!("🔗 Related {} -{:?}-> {}", from, rel, to);
+        Ok(())
+    }
+
+    /// Remove a relationship edge in a single attempt
+    async fn unrelate_once(&self, from: &BlockId, to: &BlockId, rel: RelationType) -> Result<bool> {
+        self.initialize_schema().await?;
+
+        let mut response = self
+            .db
+            .query(format!(
+                "DELETE {} WHERE in = $from AND out = $to RETURN BEFORE",
+                rel.edge_table()
+            ))
+            .bind(("from", RecordId::from(("memory_blocks", from.as_str()))))
+            .bind(("to", RecordId::from(("memory_blocks", to.as_str()))))
+            .await
+            .map_err(|e| LutsError::Storage(format!("Failed to unrelate blocks: {}", e)))?;
+        let removed: Vec<serde::de::IgnoredAny> = response
+            .take(0)
+            .map_err(|e| LutsError::Storage(format!("Failed to count removed relations: {}", e)))?;
+        Ok(!removed.is_empty())
+    }
+
+    /// Follow one hop of relationship edges in a single attempt
+    async fn related_ids_once(
+        &self,
+        id: &BlockId,
+        rel: Option<RelationType>,
+    ) -> Result<Vec<BlockId>> {
+        self.initialize_schema().await?;
+        let relations = match rel {
+            Some(rel) => vec![rel],
+            None => RelationType::ALL.to_vec(),
+        };
+
+        let mut related = Vec::new();
+        for rel in relations {
+            let mut response = self
+                .db
+                .query(format!(
+                    "SELECT VALUE record::id(out) FROM {} WHERE in = $from",
+                    rel.edge_table()
+                ))
+                .bind(("from", RecordId::from(("memory_blocks", id.as_str()))))
+                .await
+                .map_err(|e| LutsError::Storage(format!("Failed to follow relations: {}", e)))?;
+            let ids: Vec<String> = response
+                .take(0)
+                .map_err(|e| LutsError::Storage(format!("Failed to parse relations: {}", e)))?;
+            related.extend(ids.into_iter().map(BlockId::from));
+        }
+        Ok(related)
+    }
+
+    /// Delete blocks in a single attempt
+    async fn delete_many_once(&self, ids: &[BlockId]) -> Result<u64> {
+        if ids.is_empty() {
+            return Ok(0);
         }
+        self.initialize_schema().await?;
+
+        let records: Vec<RecordId> = ids
+            .iter()
+            .map(|id| RecordId::from(("memory_blocks", id.as_str())))
+            .collect();
+
+        let mut response = self
+            .db
+            .query("DELETE $records RETURN BEFORE")
+            .bind(("records", records))
+            .await
+            .map_err(|e| LutsError::Storage(format!("Failed to delete memory blocks: {}", e)))?;
+
+        let deleted: Vec<serde::de::IgnoredAny> = response
+            .take(0)
+            .map_err(|e| LutsError::Storage(format!("Failed to count deleted blocks: {}", e)))?;
+
+        debug!("Deleted {} of {} requested memory blocks", deleted.len(), ids.len());
+        Ok(deleted.len() as u64)
+    }
+
+    /// Run a query in a single attempt
+    async fn query_once(&self, query: MemoryQuery) -> Result<Vec<MemoryBlock>> {
+        self.initialize_schema().await?;
+
+        let filter_locally = self.filters_content_locally(&query);
+        let (conditions, bindings) = Self::filter_conditions(&query, !filter_locally);
 
         // Handle vector similarity search
         if let Some(vector_query) = &query.vector_search {
@@ -723,13 +1836,15 @@ impl MemoryStore for SurrealMemoryStore {
             QuerySort::Relevance => " ORDER BY relevance_score DESC",
         };
 
+        // The limit has to wait until after decryption when content is filtered locally
         let limit_clause = query
             .limit
+            .filter(|_| !filter_locally)
             .map(|l| format!(" LIMIT {}", l))
             .unwrap_or_default();
 
         let sql_query = format!(
-            "SELECT * FROM memory_blocks{}{}{}",
+            "SELECT *, record::id(id) AS id FROM memory_blocks{}{}{}",
             where_clause, order_clause, limit_clause
         );
 
@@ -746,28 +1861,198 @@ impl MemoryStore for SurrealMemoryStore {
             .take(0)
             .map_err(|e| LutsError::Storage(format!("Failed to parse memory blocks: {}", e)))?;
 
-        Ok(enhanced_blocks.into_iter().map(|eb| eb.into()).collect())
+        let content_filter = query.content_contains.as_deref().filter(|_| filter_locally);
+        self.open_blocks(enhanced_blocks, content_filter, query.limit)
     }
 
-    async fn clear_user_data(&self, _user_id: &str) -> Result<u64> {
-        // In real implementation, this would delete all blocks for the user
-        Ok(0)
+    /// Fetch a query page in a single attempt
+    async fn query_paged_once(&self, query: MemoryQuery) -> Result<MemoryQueryPage> {
+        self.initialize_schema().await?;
+
+        let page_size = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+        let sort = paged_sort(&query)?;
+        let filter_locally = self.filters_content_locally(&query);
+        let (mut conditions, mut bindings) = Self::filter_conditions(&query, !filter_locally);
+
+        // Keyset pagination: resume strictly after the cursor's (created_at, id)
+        if let Some(cursor) = query.cursor.as_deref() {
+            let cursor = PageCursor::decode(cursor)?;
+            let op = match sort {
+                QuerySort::OldestFirst => ">",
+                _ => "<",
+            };
+            conditions.push(format!(
+                "(created_at {op} $cursor_created_at OR (created_at = $cursor_created_at AND record::id(id) {op} $cursor_id))"
+            ));
+            bindings.push(("cursor_created_at", cursor.created_at_rfc3339()));
+            bindings.push(("cursor_id", cursor.id));
+        }
+
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", conditions.join(" AND "))
+        };
+
+        let order_clause = match sort {
+            QuerySort::OldestFirst => " ORDER BY created_at ASC, id ASC",
+            _ => " ORDER BY created_at DESC, id DESC",
+        };
+
+        // Fetch one extra row to find out whether there is another page
+        let limit_clause = if filter_locally {
+            String::new()
+        } else {
+            format!(" LIMIT {}", page_size + 1)
+        };
+        let sql_query = format!(
+            "SELECT *, record::id(id) AS id FROM memory_blocks{}{}{}",
+            where_clause, order_clause, limit_clause
+        );
+
+        let mut db_query = self.db.query(&sql_query);
+        for (key, value) in bindings {
+            db_query = db_query.bind((key, value));
+        }
+
+        let mut response = db_query
+            .await
+            .map_err(|e| LutsError::Storage(format!("Failed to query memory page: {}", e)))?;
+
+        let enhanced_blocks: Vec<EnhancedMemoryBlock> = response
+            .take(0)
+            .map_err(|e| LutsError::Storage(format!("Failed to parse memory page: {}", e)))?;
+
+        let content_filter = query.content_contains.as_deref().filter(|_| filter_locally);
+        let blocks = self.open_blocks(enhanced_blocks, content_filter, Some(page_size + 1))?;
+        Ok(MemoryQueryPage::from_overfetch(blocks, page_size))
     }
+}
 
-    async fn get_stats(&self, _user_id: &str) -> Result<MemoryStats> {
-        // In real implementation, this would calculate statistics from SurrealDB
-        Ok(MemoryStats {
-            total_blocks: 0,
-            blocks_by_type: HashMap::new(),
-            total_size_bytes: 0,
-            last_updated: Utc::now(),
-        })
+#[async_trait]
+impl MemoryStore for SurrealMemoryStore {
+    async fn store(&self, block: MemoryBlock) -> Result<BlockId> {
+        self.resilience
+            .run("store", || self.store_once(block.clone()))
+            .await
+    }
+
+    fn embedding_dimensions(&self) -> Option<usize> {
+        SurrealMemoryStore::embedding_dimensions(self)
+    }
+
+    async fn retrieve(&self, id: &BlockId) -> Result<Option<MemoryBlock>> {
+        self.resilience.run("retrieve", || self.retrieve_once(id)).await
+    }
+
+    async fn delete(&self, id: &BlockId) -> Result<bool> {
+        Ok(self.delete_many(std::slice::from_ref(id)).await? > 0)
+    }
+
+    async fn update(&self, id: &BlockId, mut block: MemoryBlock) -> Result<MemoryBlock> {
+        if self.retrieve(id).await?.is_none() {
+            return Err(LutsError::BlockNotFound(id.to_string()));
+        }
+
+        // Upsert over the record, keeping the ID it is known by, so a
+        // failed write leaves the old block in place
+        block.metadata.id = id.clone();
+        self.store(block.clone()).await?;
+        Ok(block)
+    }
+
+    async fn store_many(&self, blocks: Vec<MemoryBlock>) -> Result<Vec<BlockId>> {
+        self.resilience
+            .run("store_many", || self.store_many_once(blocks.clone()))
+            .await
+    }
+
+    async fn delete_many(&self, ids: &[BlockId]) -> Result<u64> {
+        self.resilience
+            .run("delete_many", || self.delete_many_once(ids))
+            .await
+    }
+
+    async fn update_many(&self, blocks: Vec<MemoryBlock>) -> Result<u64> {
+        self.resilience
+            .run("update_many", || self.update_many_once(blocks.clone()))
+            .await
+    }
+
+    async fn query(&self, query: MemoryQuery) -> Result<Vec<MemoryBlock>> {
+        self.resilience
+            .run("query", || self.query_once(query.clone()))
+            .await
+    }
+
+    async fn query_paged(&self, query: MemoryQuery) -> Result<MemoryQueryPage> {
+        self.resilience
+            .run("query_paged", || self.query_paged_once(query.clone()))
+            .await
+    }
+
+    async fn relate(&self, from: &BlockId, to: &BlockId, rel: RelationType) -> Result<()> {
+        self.resilience
+            .run("relate", || self.relate_once(from, to, rel))
+            .await
+    }
+
+    async fn unrelate(&self, from: &BlockId, to: &BlockId, rel: RelationType) -> Result<bool> {
+        self.resilience
+            .run("unrelate", || self.unrelate_once(from, to, rel))
+            .await
+    }
+
+    async fn related_ids(&self, id: &BlockId, rel: Option<RelationType>) -> Result<Vec<BlockId>> {
+        self.resilience
+            .run("related_ids", || self.related_ids_once(id, rel))
+            .await
+    }
+
+    async fn blocks_without_embedding(
+        &self,
+        user_id: &str,
+        after: Option<&BlockId>,
+        limit: usize,
+    ) -> Result<Vec<MemoryBlock>> {
+        self.resilience
+            .run("blocks_without_embedding", || {
+                self.blocks_without_embedding_once(user_id, after, limit)
+            })
+            .await
+    }
+
+    async fn set_embeddings(&self, embeddings: Vec<(BlockId, Vec<f32>)>) -> Result<()> {
+        self.resilience
+            .run("set_embeddings", || {
+                self.set_embeddings_once(embeddings.clone())
+            })
+            .await
+    }
+
+    async fn clear_user_data(&self, _user_id: &str) -> Result<u64> {
+        // In real implementation, this would delete all blocks for the user
+        Ok(0)
+    }
+
+    async fn get_stats(&self, user_id: &str) -> Result<MemoryStats> {
+        self.resilience
+            .run("get_stats", || self.get_stats_once(user_id))
+            .await
     }
 }
 
 /// A memory manager that interfaces with a storage backend
 pub struct MemoryManager {
     store: Box<dyn MemoryStore>,
+    /// Proposed blocks waiting for user approval, kept apart so queries don't see them
+    review_queue: Option<Box<dyn MemoryStore>>,
+    dedup_config: DedupConfig,
+    /// Embeds incoming blocks to look for duplicates; dedup is off without one
+    dedup_embedder: Option<Arc<dyn EmbeddingService>>,
+    rerank_config: RerankConfig,
+    /// Reorders semantic search results; they keep the vector order without one
+    reranker: Option<Arc<dyn Reranker>>,
 }
 
 impl MemoryManager {
@@ -775,12 +2060,157 @@ impl MemoryManager {
     pub fn new(store: impl MemoryStore + 'static) -> Self {
         MemoryManager {
             store: Box::new(store),
+            review_queue: None,
+            dedup_config: DedupConfig::default(),
+            dedup_embedder: None,
+            rerank_config: RerankConfig::default(),
+            reranker: None,
         }
     }
 
+    /// Hold agent-proposed blocks in `queue` until they are approved
+    pub fn with_review_queue(mut self, queue: impl MemoryStore + 'static) -> Self {
+        self.review_queue = Some(Box::new(queue));
+        self
+    }
+
+    /// Check new blocks against similar stored ones before storing them
+    ///
+    /// `embedding_service` should be the one the store indexes with, so
+    /// incoming and stored embeddings are comparable.
+    pub fn with_dedup(
+        mut self,
+        config: DedupConfig,
+        embedding_service: Arc<dyn EmbeddingService>,
+    ) -> Self {
+        self.dedup_config = config;
+        self.dedup_embedder = Some(embedding_service);
+        self
+    }
+
+    /// Rerank semantic search results with `reranker`
+    pub fn with_reranker(mut self, config: RerankConfig, reranker: Arc<dyn Reranker>) -> Self {
+        self.rerank_config = config;
+        self.reranker = Some(reranker);
+        self
+    }
+
+    /// Whether new blocks from agents should be proposed rather than stored
+    pub fn has_review_queue(&self) -> bool {
+        self.review_queue.is_some()
+    }
+
+    fn review_queue(&self) -> Result<&dyn MemoryStore> {
+        self.review_queue
+            .as_deref()
+            .ok_or_else(|| LutsError::Memory("No review queue configured".to_string()))
+    }
+
+    /// Queue a block for review instead of storing it
+    pub async fn propose(&self, block: MemoryBlock) -> Result<BlockId> {
+        self.review_queue()?.store(block).await
+    }
+
+    /// List pending proposals, oldest first; `None` lists every user's proposals
+    pub async fn review_proposals(&self, user_id: Option<&str>) -> Result<Vec<MemoryBlock>> {
+        let query = MemoryQuery {
+            user_id: user_id.map(|s| s.to_string()),
+            sort: Some(QuerySort::OldestFirst),
+            ..Default::default()
+        };
+        self.review_queue()?.query(query).await
+    }
+
+    /// Commit a proposal to memory and remove it from the queue
+    pub async fn approve(&self, id: &BlockId) -> Result<BlockId> {
+        let queue = self.review_queue()?;
+        let block = queue
+            .retrieve(id)
+            .await?
+            .ok_or_else(|| LutsError::BlockNotFound(id.to_string()))?;
+
+        let stored_id = self.store.store(block).await?;
+        queue.delete(id).await?;
+        Ok(stored_id)
+    }
+
+    /// Discard a proposal, returning whether it was pending
+    pub async fn reject(&self, id: &BlockId) -> Result<bool> {
+        self.review_queue()?.delete(id).await
+    }
+
     /// Store a memory block
+    ///
+    /// With dedup enabled the returned ID may be that of an existing block;
+    /// use [`store_with_outcome`](Self::store_with_outcome) to tell.
     pub async fn store(&self, block: MemoryBlock) -> Result<BlockId> {
-        self.store.store(block).await
+        self.store_with_outcome(block)
+            .await
+            .map(StoreOutcome::into_id)
+    }
+
+    /// Store a memory block, skipping or merging it if it duplicates one already stored
+    ///
+    /// A duplicate is a block of the same user and type whose embedding is at
+    /// least `threshold` cosine-similar. If dedup is off, or the similarity
+    /// search fails, the block is simply inserted.
+    pub async fn store_with_outcome(&self, block: MemoryBlock) -> Result<StoreOutcome> {
+        let Some(duplicate) = self.find_duplicate(&block).await? else {
+            return self.store.store(block).await.map(StoreOutcome::Inserted);
+        };
+
+        let id = duplicate.id().clone();
+        let merged = match self.dedup_config.policy {
+            DedupPolicy::Merge => dedup::merge(duplicate, &block),
+            DedupPolicy::Skip => None,
+        };
+        if let Some(merged) = merged {
+            self.store.update(&id, merged).await?;
+            debug!("Merged block {} into {}", block.id(), id);
+            return Ok(StoreOutcome::Merged(id));
+        }
+        debug!("Skipped block {} as a duplicate of {}", block.id(), id);
+        Ok(StoreOutcome::Deduped(id))
+    }
+
+    /// The stored block `block` duplicates, if dedup is on and there is one
+    async fn find_duplicate(&self, block: &MemoryBlock) -> Result<Option<MemoryBlock>> {
+        let Some(embedder) = self.dedup_embedder.as_ref().filter(|_| self.dedup_config.enabled)
+        else {
+            return Ok(None);
+        };
+        let Some(text) = dedup::comparable_text(block) else {
+            return Ok(None);
+        };
+
+        let query = MemoryQuery {
+            user_id: Some(block.user_id().to_string()),
+            block_types: vec![block.block_type()],
+            vector_search: Some(VectorQuery {
+                query_vector: embedder.embed_text(&text).await?,
+                search_config: VectorSearchConfig {
+                    max_results: 1,
+                    min_relevance: self.dedup_config.threshold,
+                    metric: SimilarityMetric::Cosine,
+                    expected_dimensions: Some(embedder.dimensions()),
+                },
+                metric_override: Some(SimilarityMetric::Cosine),
+            }),
+            ..Default::default()
+        };
+        let matches = match self.store.query(query).await {
+            Ok(matches) => matches,
+            Err(e) => {
+                warn!("Duplicate check failed, storing block {} as new: {}", block.id(), e);
+                return Ok(None);
+            }
+        };
+
+        // Re-read the match so its search score isn't written back on merge
+        match matches.into_iter().next() {
+            Some(found) => self.store.retrieve(found.id()).await,
+            None => Ok(None),
+        }
     }
 
     /// Retrieve a memory block by its ID
@@ -788,22 +2218,237 @@ impl MemoryManager {
         self.store.retrieve(id).await
     }
 
+    /// Record that `from` relates to `to`, e.g. a summary `DerivedFrom` a message
+    pub async fn relate(&self, from: &BlockId, to: &BlockId, rel: RelationType) -> Result<()> {
+        self.store.relate(from, to, rel).await
+    }
+
+    /// Remove a relationship, returning whether it existed
+    pub async fn unrelate(&self, from: &BlockId, to: &BlockId, rel: RelationType) -> Result<bool> {
+        self.store.unrelate(from, to, rel).await
+    }
+
+    /// Blocks reachable from `id` within `depth` edges, nearest first
+    ///
+    /// Only `rel` edges are followed, or every kind when it is `None`. Each
+    /// block is returned once and `id` itself never is, so cycles end the walk.
+    pub async fn related(
+        &self,
+        id: &BlockId,
+        rel: Option<RelationType>,
+        depth: usize,
+    ) -> Result<Vec<MemoryBlock>> {
+        let mut visited = std::collections::HashSet::from([id.clone()]);
+        let mut frontier = vec![id.clone()];
+        let mut related = Vec::new();
+
+        for _ in 0..depth {
+            let mut next = Vec::new();
+            for current in &frontier {
+                for neighbour in self.store.related_ids(current, rel).await? {
+                    if visited.insert(neighbour.clone()) {
+                        next.push(neighbour);
+                    }
+                }
+            }
+            if next.is_empty() {
+                break;
+            }
+
+            // Edges can outlive their blocks; skip the ones that are gone
+            for neighbour in &next {
+                if let Some(block) = self.store.retrieve(neighbour).await? {
+                    related.push(block);
+                }
+            }
+            frontier = next;
+        }
+        Ok(related)
+    }
+
     /// Delete a memory block
     pub async fn delete(&self, id: &BlockId) -> Result<bool> {
         self.store.delete(id).await
     }
 
+    /// Store a batch of memory blocks, e.g. when importing a conversation
+    pub async fn store_many(&self, blocks: Vec<MemoryBlock>) -> Result<Vec<BlockId>> {
+        self.store.store_many(blocks).await
+    }
+
+    /// Delete a batch of memory blocks, returning how many were deleted
+    pub async fn delete_many(&self, ids: &[BlockId]) -> Result<u64> {
+        self.store.delete_many(ids).await
+    }
+
     /// Update an existing memory block
     pub async fn update(&self, id: &BlockId, block: MemoryBlock) -> Result<MemoryBlock> {
         self.store.update(id, block).await
     }
 
+    /// Replace a batch of existing memory blocks, returning how many were updated
+    pub async fn update_many(&self, blocks: Vec<MemoryBlock>) -> Result<u64> {
+        self.store.update_many(blocks).await
+    }
+
     /// Search for memory blocks based on criteria
+    #[instrument(
+        name = "memory_query",
+        skip_all,
+        fields(user_id = query.user_id.as_deref(), session_id = query.session_id.as_deref())
+    )]
     pub async fn search(&self, query: &MemoryQuery) -> Result<Vec<MemoryBlock>> {
         self.store.query(query.clone()).await
     }
 
+    /// Run a vector search for `query_text`, reranking the matches if a reranker is set
+    ///
+    /// `query.vector_search` must hold the embedding of `query_text`. With a
+    /// reranker, the store is asked for [`RerankConfig::candidates`] matches
+    /// and the reranker's top `max_results` of them are returned, best first.
+    #[instrument(
+        name = "memory_semantic_search",
+        skip_all,
+        fields(user_id = query.user_id.as_deref(), session_id = query.session_id.as_deref())
+    )]
+    pub async fn semantic_search(
+        &self,
+        query_text: &str,
+        query: &MemoryQuery,
+    ) -> Result<Vec<MemoryBlock>> {
+        let Some(vector_search) = &query.vector_search else {
+            return Err(LutsError::Validation(
+                "Semantic search needs a vector query".to_string(),
+            ));
+        };
+        let Some(reranker) = &self.reranker else {
+            return self.search(query).await;
+        };
+
+        let max_results = vector_search.search_config.max_results;
+        let mut candidate_query = query.clone();
+        if let Some(vector_search) = candidate_query.vector_search.as_mut() {
+            vector_search.search_config.max_results =
+                max_results.max(self.rerank_config.candidates);
+        }
+        let candidates = self.store.query(candidate_query).await?;
+        let candidate_count = candidates.len();
+
+        // Stable sort, so equally scored blocks keep their vector order
+        let mut scored = reranker.rerank(query_text, candidates).await?;
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        debug!(
+            "Reranked {} semantic search candidates for {:?}",
+            candidate_count, query_text
+        );
+        Ok(scored
+            .into_iter()
+            .take(max_results)
+            .map(|(block, _)| block)
+            .collect())
+    }
+
+    /// Embed a user's blocks that were stored without an embedding
+    ///
+    /// Blocks are read `batch_size` at a time and each batch is embedded with
+    /// one provider call and saved before the next is read, so a backfill that
+    /// stops part way can simply be run again. A rate-limited batch is retried
+    /// after the wait the provider asks for. Binary and empty blocks are
+    /// skipped. `embedding_service` must match the store's own embeddings.
+    /// Returns how many blocks were backfilled.
+    pub async fn backfill_embeddings(
+        &self,
+        embedding_service: &dyn EmbeddingService,
+        user_id: &str,
+        batch_size: usize,
+    ) -> Result<usize> {
+        if let Some(dimensions) = self.store.embedding_dimensions()
+            && dimensions != embedding_service.dimensions()
+        {
+            return Err(LutsError::EmbeddingDimensionMismatch {
+                subject: "backfill embeddings".to_string(),
+                expected: dimensions,
+                actual: embedding_service.dimensions(),
+            });
+        }
+
+        let batch_size = batch_size.max(1);
+        let mut after: Option<BlockId> = None;
+        let mut backfilled = 0;
+        loop {
+            let batch = self
+                .store
+                .blocks_without_embedding(user_id, after.as_ref(), batch_size)
+                .await?;
+            let Some(last) = batch.last() else {
+                break;
+            };
+            after = Some(last.id().clone());
+
+            let (ids, texts): (Vec<BlockId>, Vec<String>) = batch
+                .iter()
+                .filter_map(|block| {
+                    dedup::comparable_text(block).map(|text| (block.id().clone(), text))
+                })
+                .unzip();
+            if !texts.is_empty() {
+                let embeddings = Self::embed_backfill_batch(embedding_service, &texts).await?;
+                backfilled += ids.len();
+                self.store
+                    .set_embeddings(ids.into_iter().zip(embeddings).collect())
+                    .await?;
+            }
+
+            if batch.len() < batch_size {
+                break;
+            }
+        }
+
+        if backfilled > 0 {
+            info!(
+                "Backfilled embeddings for {} blocks of {}",
+                backfilled, user_id
+            );
+        }
+        Ok(backfilled)
+    }
+
+    /// Embed one backfill batch, waiting out rate limits
+    async fn embed_backfill_batch(
+        embedding_service: &dyn EmbeddingService,
+        texts: &[String],
+    ) -> Result<Vec<Vec<f32>>> {
+        let mut retries = 0;
+        loop {
+            match embedding_service.embed_texts(texts).await {
+                Ok(embeddings) if embeddings.len() == texts.len() => return Ok(embeddings),
+                Ok(embeddings) => {
+                    return Err(LutsError::Memory(format!(
+                        "Embedding service returned {} embeddings for {} texts",
+                        embeddings.len(),
+                        texts.len()
+                    )));
+                }
+                Err(LutsError::RateLimited { retry_after })
+                    if retries < BACKFILL_RATE_LIMIT_RETRIES =>
+                {
+                    let wait = retry_after.unwrap_or(BACKFILL_RATE_LIMIT_WAIT * 2u32.pow(retries));
+                    warn!("Embedding backfill rate limited, retrying in {:?}", wait);
+                    tokio::time::sleep(wait).await;
+                    retries += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Embedding dimensions of the store, or `None` if it can't do vector search
+    pub fn embedding_dimensions(&self) -> Option<usize> {
+        self.store.embedding_dimensions()
+    }
+
     /// List all memory blocks for a user
+    #[instrument(name = "memory_query", skip(self))]
     pub async fn list(&self, user_id: &str) -> Result<Vec<MemoryBlock>> {
         let query = MemoryQuery {
             user_id: Some(user_id.to_string()),
@@ -812,6 +2457,34 @@ impl MemoryManager {
         self.store.query(query).await
     }
 
+    /// Fetch one page of search results; pass the previous page's
+    /// `next_cursor` as `query.cursor` to continue
+    #[instrument(
+        name = "memory_query",
+        skip_all,
+        fields(user_id = query.user_id.as_deref(), session_id = query.session_id.as_deref())
+    )]
+    pub async fn search_paged(&self, query: &MemoryQuery) -> Result<MemoryQueryPage> {
+        self.store.query_paged(query.clone()).await
+    }
+
+    /// List a user's memory blocks one page at a time, newest first
+    #[instrument(name = "memory_query", skip(self, cursor))]
+    pub async fn list_page(
+        &self,
+        user_id: &str,
+        page_size: usize,
+        cursor: Option<String>,
+    ) -> Result<MemoryQueryPage> {
+        let query = MemoryQuery {
+            user_id: Some(user_id.to_string()),
+            limit: Some(page_size),
+            cursor,
+            ..Default::default()
+        };
+        self.store.query_paged(query).await
+    }
+
     /// Clear all data for a user
     pub async fn clear_user_data(&self, user_id: &str) -> Result<u64> {
         self.store.clear_user_data(user_id).await
@@ -827,11 +2500,40 @@ impl MemoryManager {
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_proposals_hidden_until_approved() {
+        let manager = MemoryManager::new(InMemoryMemoryStore::new())
+            .with_review_queue(InMemoryMemoryStore::new());
+
+        let approved = manager
+            .propose(text_block("reviewer", "prefers tea over coffee"))
+            .await
+            .unwrap();
+        let rejected = manager
+            .propose(text_block("reviewer", "lives on the moon"))
+            .await
+            .unwrap();
+
+        assert!(manager.list("reviewer").await.unwrap().is_empty());
+        assert!(manager.get(&approved).await.unwrap().is_none());
+        assert_eq!(manager.review_proposals(Some("reviewer")).await.unwrap().len(), 2);
+
+        manager.approve(&approved).await.unwrap();
+        assert!(manager.reject(&rejected).await.unwrap());
+
+        let committed = manager.list("reviewer").await.unwrap();
+        assert_eq!(committed.len(), 1);
+        assert_eq!(committed[0].id(), &approved);
+        assert!(manager.review_proposals(None).await.unwrap().is_empty());
+        assert!(manager.approve(&rejected).await.is_err());
+    }
+
     #[tokio::test]
     async fn test_surreal_memory_store_creation() {
         let config = SurrealConfig::Memory {
             namespace: "test".to_string(),
             database: "memory".to_string(),
+            encryption_key: None,
         };
 
         let store = SurrealMemoryStore::new(config).await.unwrap();
@@ -846,6 +2548,7 @@ mod tests {
         let config = SurrealConfig::Memory {
             namespace: "test".to_string(),
             database: "memory".to_string(),
+            encryption_key: None,
         };
 
         // Create embedding service for testing
@@ -876,4 +2579,886 @@ mod tests {
         assert!(retrieved.is_some());
         assert_eq!(retrieved.unwrap().id(), &block_id);
     }
+
+    /// Embedding service that can be switched off to simulate an outage
+    struct FlakyEmbeddingService {
+        inner: crate::embeddings::MockEmbeddingService,
+        available: std::sync::atomic::AtomicBool,
+    }
+
+    #[async_trait]
+    impl EmbeddingService for FlakyEmbeddingService {
+        async fn embed_text(&self, text: &str) -> Result<Vec<f32>> {
+            if self.available.load(std::sync::atomic::Ordering::SeqCst) {
+                self.inner.embed_text(text).await
+            } else {
+                Err(LutsError::Memory("embedding provider unavailable".to_string()))
+            }
+        }
+
+        async fn embed_texts(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            let mut embeddings = Vec::new();
+            for text in texts {
+                embeddings.push(self.embed_text(text).await?);
+            }
+            Ok(embeddings)
+        }
+
+        fn dimensions(&self) -> usize {
+            self.inner.dimensions()
+        }
+
+        fn max_text_length(&self) -> usize {
+            self.inner.max_text_length()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_background_task_embeds_blocks_after_recovery() {
+        use crate::embeddings::{EmbeddingConfig, EmbeddingProvider, MockEmbeddingService};
+        use std::sync::atomic::Ordering;
+
+        let config = SurrealConfig::Memory {
+            namespace: "test".to_string(),
+            database: "background_reembed".to_string(),
+            encryption_key: None,
+        };
+        let embedding_service = Arc::new(FlakyEmbeddingService {
+            inner: MockEmbeddingService::new(EmbeddingConfig {
+                provider: EmbeddingProvider::Mock,
+                dimensions: 384,
+                ..Default::default()
+            }),
+            available: std::sync::atomic::AtomicBool::new(false),
+        });
+        let store = SurrealMemoryStore::with_embedding_service(
+            config,
+            Some(embedding_service.clone() as Arc<dyn EmbeddingService>),
+        )
+        .await
+        .unwrap()
+        .with_reembedding_interval(std::time::Duration::from_millis(20));
+        store.initialize_schema_with_dimensions(384).await.unwrap();
+
+        let block = MemoryBlock::new(
+            BlockType::Fact,
+            "test_user",
+            MemoryContent::Text("rust borrow checker".to_string()),
+        );
+        store.store(block).await.unwrap();
+        assert_eq!(store.pending_embedding_count().await.unwrap(), 1);
+
+        // Nobody calls reembed_pending; the store's own task does
+        embedding_service.available.store(true, Ordering::SeqCst);
+        for _ in 0..100 {
+            if store.pending_embedding_count().await.unwrap() == 0 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert_eq!(store.pending_embedding_count().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_embedding_outage_degrades_and_recovers() {
+        use crate::embeddings::{EmbeddingConfig, EmbeddingProvider, MockEmbeddingService};
+        use std::sync::atomic::Ordering;
+
+        let config = SurrealConfig::Memory {
+            namespace: "test".to_string(),
+            database: "degrade".to_string(),
+            encryption_key: None,
+        };
+        let embedding_service = Arc::new(FlakyEmbeddingService {
+            inner: MockEmbeddingService::new(EmbeddingConfig {
+                provider: EmbeddingProvider::Mock,
+                dimensions: 384,
+                ..Default::default()
+            }),
+            available: std::sync::atomic::AtomicBool::new(false),
+        });
+
+        let store = SurrealMemoryStore::with_embedding_service(
+            config,
+            Some(embedding_service.clone() as Arc<dyn EmbeddingService>),
+        )
+        .await
+        .unwrap();
+        store.initialize_schema_with_dimensions(384).await.unwrap();
+
+        // Provider is down: the write still succeeds, flagged for later
+        let block = MemoryBlock::new(
+            BlockType::Fact,
+            "test_user",
+            MemoryContent::Text("rust borrow checker".to_string()),
+        );
+        store.store(block).await.unwrap();
+        assert_eq!(store.pending_embedding_count().await.unwrap(), 1);
+
+        // Semantic search falls back to lexical matching
+        let results = store
+            .semantic_search("borrow checker", VectorSearchConfig::default(), Some("test_user"))
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+
+        // Nothing can be embedded while the provider is still down
+        assert_eq!(store.reembed_pending(10).await.unwrap(), 0);
+        assert_eq!(store.pending_embedding_count().await.unwrap(), 1);
+
+        // Provider recovers: the background pass embeds the block
+        embedding_service.available.store(true, Ordering::SeqCst);
+        assert_eq!(store.reembed_pending(10).await.unwrap(), 1);
+        assert_eq!(store.pending_embedding_count().await.unwrap(), 0);
+
+        let results = store
+            .semantic_search(
+                "rust borrow checker",
+                VectorSearchConfig {
+                    min_relevance: 0.0,
+                    ..Default::default()
+                },
+                Some("test_user"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].relevance().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_embedding_outage_fail_policy() {
+        use crate::embeddings::{EmbeddingConfig, EmbeddingProvider, MockEmbeddingService};
+
+        let config = SurrealConfig::Memory {
+            namespace: "test".to_string(),
+            database: "fail_policy".to_string(),
+            encryption_key: None,
+        };
+        let embedding_service = Arc::new(FlakyEmbeddingService {
+            inner: MockEmbeddingService::new(EmbeddingConfig {
+                provider: EmbeddingProvider::Mock,
+                dimensions: 384,
+                ..Default::default()
+            }),
+            available: std::sync::atomic::AtomicBool::new(false),
+        });
+
+        let store = SurrealMemoryStore::with_embedding_service(
+            config,
+            Some(embedding_service as Arc<dyn EmbeddingService>),
+        )
+        .await
+        .unwrap()
+        .with_failure_policy(EmbeddingFailurePolicy::Fail);
+        store.initialize_schema_with_dimensions(384).await.unwrap();
+
+        let block = MemoryBlock::new(
+            BlockType::Fact,
+            "test_user",
+            MemoryContent::Text("rust borrow checker".to_string()),
+        );
+        assert!(store.store(block).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_failed_update_keeps_the_original_block() {
+        use crate::embeddings::{EmbeddingConfig, EmbeddingProvider, MockEmbeddingService};
+        use std::sync::atomic::Ordering;
+
+        let config = SurrealConfig::Memory {
+            namespace: "test".to_string(),
+            database: "failed_update".to_string(),
+            encryption_key: None,
+        };
+        let embedding_service = Arc::new(FlakyEmbeddingService {
+            inner: MockEmbeddingService::new(EmbeddingConfig {
+                provider: EmbeddingProvider::Mock,
+                dimensions: 384,
+                ..Default::default()
+            }),
+            available: std::sync::atomic::AtomicBool::new(true),
+        });
+        let store = SurrealMemoryStore::with_embedding_service(
+            config,
+            Some(embedding_service.clone() as Arc<dyn EmbeddingService>),
+        )
+        .await
+        .unwrap()
+        .with_failure_policy(EmbeddingFailurePolicy::Fail);
+        store.initialize_schema_with_dimensions(384).await.unwrap();
+
+        let original = text_block("test_user", "first draft");
+        let id = store.store(original).await.unwrap();
+
+        // The replacement can't be embedded, so its write fails
+        embedding_service.available.store(false, Ordering::SeqCst);
+        assert!(
+            store
+                .update(&id, text_block("test_user", "second draft"))
+                .await
+                .is_err()
+        );
+
+        let kept = store.retrieve(&id).await.unwrap().unwrap();
+        assert_eq!(kept.content().as_text(), Some("first draft"));
+    }
+
+    async fn page_through(store: &SurrealMemoryStore, sort: QuerySort) -> Vec<MemoryBlock> {
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = store
+                .query_paged(MemoryQuery {
+                    user_id: Some("pager".to_string()),
+                    limit: Some(50),
+                    sort: Some(sort),
+                    cursor: cursor.take(),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+
+            assert!(page.blocks.len() <= 50);
+            seen.extend(page.blocks);
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        seen
+    }
+
+    #[tokio::test]
+    async fn test_query_paged_walks_all_blocks_without_gaps() {
+        use crate::block::MemoryBlockBuilder;
+        use std::collections::HashSet;
+
+        let config = SurrealConfig::Memory {
+            namespace: "test".to_string(),
+            database: "paging".to_string(),
+            encryption_key: None,
+        };
+        let store = SurrealMemoryStore::new(config).await.unwrap();
+
+        // Several blocks share a timestamp so the ID tie-breaker is exercised
+        let base = 1_700_000_000_000u64;
+        let mut ids = HashSet::new();
+        for i in 0..250u64 {
+            let block = MemoryBlockBuilder::new()
+                .with_user_id("pager")
+                .with_type(BlockType::Fact)
+                .with_content(MemoryContent::Text(format!("fact {}", i)))
+                .with_created_at(base + (i / 3) * 1000)
+                .build()
+                .unwrap();
+            ids.insert(store.store(block).await.unwrap());
+        }
+
+        for sort in [QuerySort::NewestFirst, QuerySort::OldestFirst] {
+            let seen = page_through(&store, sort).await;
+            let unique: HashSet<BlockId> = seen.iter().map(|b| b.id().clone()).collect();
+
+            assert_eq!(seen.len(), 250, "{:?} returned duplicates or gaps", sort);
+            assert_eq!(unique, ids);
+
+            let ordered = seen.windows(2).all(|pair| {
+                let a = (pair[0].created_at(), pair[0].id().as_str());
+                let b = (pair[1].created_at(), pair[1].id().as_str());
+                match sort {
+                    QuerySort::OldestFirst => a < b,
+                    _ => a > b,
+                }
+            });
+            assert!(ordered, "{:?} pages are out of order", sort);
+        }
+    }
+
+    #[test]
+    fn test_page_cursor_round_trip() {
+        let cursor = PageCursor {
+            created_at: 1_700_000_000_123,
+            id: "block_1:with_colon".to_string(),
+        };
+        assert_eq!(PageCursor::decode(&cursor.encode()).unwrap(), cursor);
+        assert!(PageCursor::decode("not-a-cursor").is_err());
+    }
+
+    fn text_block(user_id: &str, text: &str) -> MemoryBlock {
+        MemoryBlock::new(
+            BlockType::Fact,
+            user_id,
+            MemoryContent::Text(text.to_string()),
+        )
+    }
+
+    async fn batch_test_store(database: &str) -> SurrealMemoryStore {
+        let config = SurrealConfig::Memory {
+            namespace: "test".to_string(),
+            database: database.to_string(),
+            encryption_key: None,
+        };
+        SurrealMemoryStore::new(config).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_store_many_and_delete_many() {
+        let store = batch_test_store("batch").await;
+
+        let blocks: Vec<MemoryBlock> = (0..5)
+            .map(|i| text_block("batch_user", &format!("fact {}", i)))
+            .collect();
+        let ids = store.store_many(blocks).await.unwrap();
+        assert_eq!(ids.len(), 5);
+        for id in &ids {
+            assert!(store.retrieve(id).await.unwrap().is_some());
+        }
+
+        let deleted = store.delete_many(&ids[..3]).await.unwrap();
+        assert_eq!(deleted, 3);
+        assert!(store.retrieve(&ids[0]).await.unwrap().is_none());
+        assert!(store.retrieve(&ids[4]).await.unwrap().is_some());
+
+        // Already-deleted IDs aren't counted twice
+        assert_eq!(store.delete_many(&ids[..3]).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_get_stats_counts_blocks_by_type() {
+        let store = batch_test_store("stats").await;
+        store.store(text_block("stats_user", "fact one")).await.unwrap();
+        store.store(text_block("stats_user", "fact two")).await.unwrap();
+        store
+            .store(MemoryBlock::new(
+                BlockType::Goal,
+                "stats_user",
+                MemoryContent::Text("ship it".to_string()),
+            ))
+            .await
+            .unwrap();
+        store.store(text_block("someone_else", "not counted")).await.unwrap();
+
+        let stats = store.get_stats("stats_user").await.unwrap();
+        assert_eq!(stats.total_blocks, 3);
+        assert_eq!(stats.blocks_by_type.get("fact"), Some(&2));
+        assert_eq!(stats.blocks_by_type.get("goal"), Some(&1));
+        assert!(stats.total_size_bytes > 0);
+
+        assert_eq!(store.get_stats("nobody").await.unwrap().total_blocks, 0);
+    }
+
+    #[tokio::test]
+    async fn test_storing_an_existing_id_replaces_the_block() {
+        let store = batch_test_store("upsert").await;
+        let first = text_block("upsert_user", "first draft");
+        let id = store.store(first.clone()).await.unwrap();
+
+        let mut second = first;
+        second.set_content(MemoryContent::Text("second draft".to_string()));
+        assert_eq!(store.store(second).await.unwrap(), id);
+
+        assert_eq!(store.get_stats("upsert_user").await.unwrap().total_blocks, 1);
+        let stored = store.retrieve(&id).await.unwrap().unwrap();
+        assert_eq!(stored.content().as_text(), Some("second draft"));
+    }
+
+    #[tokio::test]
+    async fn test_query_filters_by_creation_date() {
+        let store = batch_test_store("date_range").await;
+        for (created_at, text) in [(1_000_000, "early"), (2_000_000, "middle"), (3_000_000, "late")] {
+            let mut block = text_block("dated_user", text);
+            block.metadata.created_at = created_at;
+            store.store(block).await.unwrap();
+        }
+
+        let blocks = store
+            .query(MemoryQuery {
+                user_id: Some("dated_user".to_string()),
+                created_after: DateTime::from_timestamp_millis(1_500_000),
+                created_before: DateTime::from_timestamp_millis(2_500_000),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let texts: Vec<&str> = blocks.iter().filter_map(|b| b.content().as_text()).collect();
+        assert_eq!(texts, vec!["middle"]);
+    }
+
+    #[tokio::test]
+    async fn test_store_many_rolls_back_invalid_batch() {
+        let store = batch_test_store("batch_rollback").await;
+
+        let invalid = vec![
+            text_block("batch_user", "fine"),
+            text_block("", "missing user"),
+        ];
+        let first_id = invalid[0].id().clone();
+        let err = store.store_many(invalid).await.unwrap_err();
+        assert!(err.to_string().contains("index 1"), "unexpected error: {}", err);
+        assert!(store.retrieve(&first_id).await.unwrap().is_none());
+
+    }
+
+    #[tokio::test]
+    async fn test_store_many_replaces_existing_blocks_like_store() {
+        let store = batch_test_store("batch_upsert").await;
+        let mut existing = text_block("batch_user", "first draft");
+        store.store(existing.clone()).await.unwrap();
+
+        existing.set_content(MemoryContent::Text("second draft".to_string()));
+        let fresh = text_block("batch_user", "new");
+        let ids = store
+            .store_many(vec![fresh.clone(), existing.clone()])
+            .await
+            .unwrap();
+        assert_eq!(ids, vec![fresh.id().clone(), existing.id().clone()]);
+
+        assert_eq!(store.get_stats("batch_user").await.unwrap().total_blocks, 2);
+        let stored = store.retrieve(existing.id()).await.unwrap().unwrap();
+        assert_eq!(stored.content().as_text(), Some("second draft"));
+    }
+
+    /// Raw `content` values as SurrealDB holds them, bypassing decryption
+    async fn raw_contents(store: &SurrealMemoryStore) -> Vec<String> {
+        let mut response = store
+            .db()
+            .query("SELECT VALUE content FROM memory_blocks")
+            .await
+            .unwrap();
+        response.take(0).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_content_round_trip() {
+        let store = batch_test_store("encrypted")
+            .await
+            .with_encryption_key([42; 32]);
+
+        let block = text_block("crypto_user", "my passport number is X1234567");
+        let id = store.store(block).await.unwrap();
+
+        let retrieved = store.retrieve(&id).await.unwrap().unwrap();
+        assert_eq!(
+            retrieved.content().as_text(),
+            Some("my passport number is X1234567")
+        );
+
+        let raw = raw_contents(&store).await;
+        assert_eq!(raw.len(), 1);
+        assert!(!raw[0].contains("passport"), "plaintext on disk: {}", raw[0]);
+
+        // content_contains still works, matched after decryption
+        let found = store
+            .query(MemoryQuery {
+                user_id: Some("crypto_user".to_string()),
+                content_contains: Some("passport".to_string()),
+                limit: Some(1),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(found.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_config_key_encrypts_stored_content() {
+        let config: SurrealConfig = serde_json::from_value(serde_json::json!({
+            "Memory": {
+                "namespace": "test",
+                "database": "config_key",
+                "encryption_key": EncryptionKey::from([7; 32]).to_base64(),
+            }
+        }))
+        .unwrap();
+        let store = SurrealMemoryStore::new(config).await.unwrap();
+
+        let plaintext = "my passport number is X1234567";
+        let id = store.store(text_block("crypto_user", plaintext)).await.unwrap();
+
+        let raw = raw_contents(&store).await;
+        assert_eq!(raw.len(), 1);
+        assert_ne!(raw[0], plaintext);
+        assert!(!raw[0].contains("X1234567"), "plaintext on disk: {}", raw[0]);
+
+        let retrieved = store.retrieve(&id).await.unwrap().unwrap();
+        assert_eq!(retrieved.content().as_text(), Some(plaintext));
+    }
+
+    #[tokio::test]
+    async fn test_rotate_key_reencrypts_user_blocks() {
+        let store = batch_test_store("rotation")
+            .await
+            .with_encryption_key([1; 32]);
+
+        let id = store
+            .store(text_block("rotating_user", "favourite colour is teal"))
+            .await
+            .unwrap();
+        let before = raw_contents(&store).await;
+
+        let new_key = Arc::new(AesGcmEncryptor::new(&[2; 32]));
+        assert_eq!(store.rotate_key("rotating_user", new_key).await.unwrap(), 1);
+
+        let after = raw_contents(&store).await;
+        assert_ne!(before, after);
+        assert!(!after[0].contains("teal"));
+
+        // A store holding only the new key can read the rotated block
+        let rotated_only = SurrealMemoryStore::from_connection(
+            store.db(),
+            store._config.clone(),
+            None,
+        )
+        .with_encryption_key([2; 32]);
+        let retrieved = rotated_only.retrieve(&id).await.unwrap().unwrap();
+        assert_eq!(retrieved.content().as_text(), Some("favourite colour is teal"));
+    }
+
+    /// Embedding service returning hand-picked vectors, so rankings are predictable
+    struct FixedEmbeddingService(HashMap<&'static str, Vec<f32>>);
+
+    #[async_trait]
+    impl EmbeddingService for FixedEmbeddingService {
+        async fn embed_text(&self, text: &str) -> Result<Vec<f32>> {
+            self.0
+                .get(text)
+                .cloned()
+                .ok_or_else(|| LutsError::Memory(format!("no fixed embedding for {}", text)))
+        }
+
+        async fn embed_texts(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            let mut embeddings = Vec::new();
+            for text in texts {
+                embeddings.push(self.embed_text(text).await?);
+            }
+            Ok(embeddings)
+        }
+
+        fn dimensions(&self) -> usize {
+            2
+        }
+
+        fn max_text_length(&self) -> usize {
+            1000
+        }
+    }
+
+    #[tokio::test]
+    async fn test_metric_override_changes_ranking() {
+        // "far" points the same way as the query but is much longer;
+        // "near" is slightly off-angle but close in space
+        let embeddings = FixedEmbeddingService(HashMap::from([
+            ("far", vec![10.0, 0.0]),
+            ("near", vec![0.9, 0.1]),
+        ]));
+        let config = SurrealConfig::Memory {
+            namespace: "test".to_string(),
+            database: "metrics".to_string(),
+            encryption_key: None,
+        };
+        let store = SurrealMemoryStore::with_embedding_service(config, Some(Arc::new(embeddings)))
+            .await
+            .unwrap();
+        store.initialize_schema_with_dimensions(2).await.unwrap();
+        let far_id = store.store(text_block("metric_user", "far")).await.unwrap();
+        let near_id = store.store(text_block("metric_user", "near")).await.unwrap();
+
+        let search = |metric| {
+            let store = store.clone();
+            async move {
+                store
+                    .query(MemoryQuery {
+                        user_id: Some("metric_user".to_string()),
+                        vector_search: Some(VectorQuery {
+                            query_vector: vec![1.0, 0.0],
+                            search_config: VectorSearchConfig {
+                                min_relevance: 0.0,
+                                ..Default::default()
+                            },
+                            metric_override: Some(metric),
+                        }),
+                        ..Default::default()
+                    })
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let cosine = search(SimilarityMetric::Cosine).await;
+        let ids: Vec<&BlockId> = cosine.iter().map(|block| block.id()).collect();
+        assert_eq!(ids, vec![&far_id, &near_id]);
+
+        let euclidean = search(SimilarityMetric::Euclidean).await;
+        let ids: Vec<&BlockId> = euclidean.iter().map(|block| block.id()).collect();
+        assert_eq!(ids, vec![&near_id, &far_id]);
+
+        // Raw scores are surfaced unclamped, e.g. a dot product of 10
+        let dot = search(SimilarityMetric::DotProduct).await;
+        let score = dot[0].relevance().unwrap().score();
+        assert!((score - 10.0).abs() < 1e-4, "expected raw dot product, got {}", score);
+    }
+
+    #[tokio::test]
+    async fn test_cosine_index_rejects_dot_product_query() {
+        let embeddings = FixedEmbeddingService(HashMap::from([("cat", vec![1.0, 0.0])]));
+        let config = SurrealConfig::Memory {
+            namespace: "test".to_string(),
+            database: "index_metric".to_string(),
+            encryption_key: None,
+        };
+        let store = SurrealMemoryStore::with_embedding_service(config, Some(Arc::new(embeddings)))
+            .await
+            .unwrap()
+            .with_index_metric(SimilarityMetric::Cosine);
+        store.initialize_schema_with_dimensions(2).await.unwrap();
+        store.store(text_block("index_user", "cat")).await.unwrap();
+
+        let query = |metric| MemoryQuery {
+            user_id: Some("index_user".to_string()),
+            vector_search: Some(VectorQuery {
+                query_vector: vec![1.0, 0.0],
+                search_config: VectorSearchConfig {
+                    min_relevance: 0.0,
+                    ..Default::default()
+                },
+                metric_override: Some(metric),
+            }),
+            ..Default::default()
+        };
+        let err = store
+            .query(query(SimilarityMetric::DotProduct))
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("indexed for cosine similarity but the query uses dot product"),
+            "unexpected error: {}",
+            err
+        );
+        assert_eq!(store.query(query(SimilarityMetric::Cosine)).await.unwrap().len(), 1);
+    }
+
+    /// Reranker recording what it was given that prefers the weakest vector matches
+    struct ReversingReranker(std::sync::Mutex<Vec<(String, usize)>>);
+
+    #[async_trait]
+    impl Reranker for ReversingReranker {
+        async fn rerank(
+            &self,
+            query: &str,
+            candidates: Vec<MemoryBlock>,
+        ) -> Result<Vec<(MemoryBlock, f32)>> {
+            self.0
+                .lock()
+                .unwrap()
+                .push((query.to_string(), candidates.len()));
+            let scored = IdentityReranker.rerank(query, candidates).await?;
+            Ok(scored
+                .into_iter()
+                .map(|(block, score)| (block, -score))
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_semantic_search_passes_candidates_through_reranker() {
+        let search = |reranker: Option<Arc<dyn Reranker>>| async move {
+            let embeddings = FixedEmbeddingService(HashMap::from([
+                ("closest", vec![1.0, 0.0]),
+                ("close", vec![0.8, 0.2]),
+                ("distant", vec![0.5, 0.5]),
+            ]));
+            let mut manager = MemoryManager::new(InMemoryMemoryStore::with_embedding_service(
+                Arc::new(embeddings),
+            ));
+            if let Some(reranker) = reranker {
+                manager = manager.with_reranker(RerankConfig { candidates: 3 }, reranker);
+            }
+            for text in ["closest", "close", "distant"] {
+                manager.store(text_block("rerank_user", text)).await.unwrap();
+            }
+            let query = MemoryQuery {
+                user_id: Some("rerank_user".to_string()),
+                vector_search: Some(VectorQuery {
+                    query_vector: vec![1.0, 0.0],
+                    search_config: VectorSearchConfig {
+                        max_results: 2,
+                        min_relevance: 0.0,
+                        ..Default::default()
+                    },
+                    metric_override: None,
+                }),
+                ..Default::default()
+            };
+            let results = manager.semantic_search("query", &query).await.unwrap();
+            results
+                .iter()
+                .map(|block| block.content().as_text().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(search(None).await, ["closest", "close"]);
+        assert_eq!(
+            search(Some(Arc::new(IdentityReranker))).await,
+            ["closest", "close"]
+        );
+
+        // All three candidates reach the reranker, which picks the top two
+        let reranker = Arc::new(ReversingReranker(Default::default()));
+        assert_eq!(search(Some(reranker.clone())).await, ["distant", "close"]);
+        assert_eq!(*reranker.0.lock().unwrap(), [("query".to_string(), 3)]);
+
+        let manager = MemoryManager::new(InMemoryMemoryStore::new());
+        assert!(
+            manager
+                .semantic_search("query", &MemoryQuery::default())
+                .await
+                .is_err()
+        );
+    }
+
+    /// Embedding service that rate limits its first batch calls
+    struct RateLimitedEmbeddingService {
+        inner: crate::embeddings::MockEmbeddingService,
+        rate_limits_left: std::sync::atomic::AtomicUsize,
+        batches: std::sync::atomic::AtomicUsize,
+    }
+
+    #[async_trait]
+    impl EmbeddingService for RateLimitedEmbeddingService {
+        async fn embed_text(&self, text: &str) -> Result<Vec<f32>> {
+            self.inner.embed_text(text).await
+        }
+
+        async fn embed_texts(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            use std::sync::atomic::Ordering;
+            let limited = self
+                .rate_limits_left
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |left| left.checked_sub(1))
+                .is_ok();
+            if limited {
+                return Err(LutsError::RateLimited {
+                    retry_after: Some(std::time::Duration::from_millis(1)),
+                });
+            }
+            self.batches.fetch_add(1, Ordering::SeqCst);
+            self.inner.embed_texts(texts).await
+        }
+
+        fn dimensions(&self) -> usize {
+            self.inner.dimensions()
+        }
+
+        fn max_text_length(&self) -> usize {
+            self.inner.max_text_length()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backfilled_blocks_are_found_by_semantic_search() {
+        use crate::embeddings::{EmbeddingConfig, EmbeddingProvider, MockEmbeddingService};
+        use std::sync::atomic::Ordering;
+
+        // Stored without an embedding service, so no block has a vector
+        let manager = MemoryManager::new(InMemoryMemoryStore::new());
+        for text in ["rust borrow checker", "tea over coffee", "lisbon in may"] {
+            manager.store(text_block("backfill_user", text)).await.unwrap();
+        }
+        manager.store(text_block("other_user", "rust borrow checker")).await.unwrap();
+
+        let embedding_service = RateLimitedEmbeddingService {
+            inner: MockEmbeddingService::new(EmbeddingConfig {
+                provider: EmbeddingProvider::Mock,
+                dimensions: 384,
+                ..Default::default()
+            }),
+            rate_limits_left: std::sync::atomic::AtomicUsize::new(1),
+            batches: std::sync::atomic::AtomicUsize::new(0),
+        };
+        let query = MemoryQuery {
+            user_id: Some("backfill_user".to_string()),
+            vector_search: Some(VectorQuery {
+                query_vector: embedding_service.embed_text("rust borrow checker").await.unwrap(),
+                search_config: VectorSearchConfig {
+                    max_results: 1,
+                    min_relevance: 0.99,
+                    ..Default::default()
+                },
+                metric_override: None,
+            }),
+            ..Default::default()
+        };
+        assert!(manager.semantic_search("rust borrow checker", &query).await.unwrap().is_empty());
+
+        // Two batches, the first retried after being rate limited
+        let backfilled = manager
+            .backfill_embeddings(&embedding_service, "backfill_user", 2)
+            .await
+            .unwrap();
+        assert_eq!(backfilled, 3);
+        assert_eq!(embedding_service.batches.load(Ordering::SeqCst), 2);
+
+        // Already embedded blocks are skipped when the backfill runs again
+        let backfilled = manager
+            .backfill_embeddings(&embedding_service, "backfill_user", 2)
+            .await
+            .unwrap();
+        assert_eq!(backfilled, 0);
+
+        let found = manager.semantic_search("rust borrow checker", &query).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].user_id(), "backfill_user");
+        assert_eq!(found[0].content().as_text(), Some("rust borrow checker"));
+
+        // Other users' blocks are left for their own backfill
+        let backfilled = manager
+            .backfill_embeddings(&embedding_service, "other_user", 2)
+            .await
+            .unwrap();
+        assert_eq!(backfilled, 1);
+    }
+
+    #[tokio::test]
+    async fn test_related_follows_edges_to_depth() {
+        let config = SurrealConfig::Memory {
+            namespace: "test".to_string(),
+            database: "relations".to_string(),
+            encryption_key: None,
+        };
+        let manager = MemoryManager::new(SurrealMemoryStore::new(config).await.unwrap());
+        let a = manager.store(text_block("graph_user", "summary")).await.unwrap();
+        let b = manager.store(text_block("graph_user", "message")).await.unwrap();
+        let c = manager.store(text_block("graph_user", "source")).await.unwrap();
+
+        manager.relate(&a, &b, RelationType::DerivedFrom).await.unwrap();
+        manager.relate(&b, &c, RelationType::DerivedFrom).await.unwrap();
+        manager.relate(&a, &b, RelationType::DerivedFrom).await.unwrap();
+        // Closes a cycle, which the walk must not go round
+        manager.relate(&c, &a, RelationType::Related).await.unwrap();
+
+        let ids = |blocks: Vec<MemoryBlock>| -> Vec<BlockId> {
+            blocks.into_iter().map(|block| block.id().clone()).collect()
+        };
+        let depth_2 = manager
+            .related(&a, Some(RelationType::DerivedFrom), 2)
+            .await
+            .unwrap();
+        assert_eq!(ids(depth_2), vec![b.clone(), c.clone()]);
+        let depth_1 = manager
+            .related(&a, Some(RelationType::DerivedFrom), 1)
+            .await
+            .unwrap();
+        assert_eq!(ids(depth_1), vec![b.clone()]);
+        let any = manager.related(&a, None, 10).await.unwrap();
+        assert_eq!(ids(any), vec![b.clone(), c.clone()]);
+
+        assert!(manager.unrelate(&a, &b, RelationType::DerivedFrom).await.unwrap());
+        assert!(!manager.unrelate(&a, &b, RelationType::DerivedFrom).await.unwrap());
+        assert!(manager.related(&a, None, 2).await.unwrap().is_empty());
+
+        let missing = BlockId::from("no-such-block");
+        assert!(matches!(
+            manager.relate(&a, &missing, RelationType::References).await,
+            Err(LutsError::BlockNotFound(_))
+        ));
+    }
 }
@@ -0,0 +1,233 @@
+//! Near-duplicate detection for [`MemoryManager::store`](super::MemoryManager::store)
+//!
+//! Agents tend to store slightly reworded copies of facts they already
+//! know. With deduplication on, an incoming block is embedded and compared
+//! with the same user's blocks of the same type; if one is similar enough,
+//! the new block is either dropped or appended to it.
+
+use crate::block::MemoryBlock;
+use crate::types::{BlockId, MemoryContent};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+/// Property of a merged block listing the blocks merged into it
+pub const MERGED_FROM_PROPERTY: &str = "merged_from";
+
+/// What to do with a block that duplicates a stored one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum DedupPolicy {
+    /// Don't store it; the stored block stands in for it
+    #[default]
+    Skip,
+    /// Append its text to the stored block and record where it came from
+    Merge,
+}
+
+/// Settings for deduplicating blocks on store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DedupConfig {
+    /// Whether to look for duplicates at all
+    pub enabled: bool,
+    /// Cosine similarity at or above which two blocks are duplicates
+    pub threshold: f32,
+    pub policy: DedupPolicy,
+}
+
+impl Default for DedupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: 0.92,
+            policy: DedupPolicy::default(),
+        }
+    }
+}
+
+/// What storing a block did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoreOutcome {
+    /// Stored as a new block
+    Inserted(BlockId),
+    /// Not stored; this existing block is a near-duplicate
+    Deduped(BlockId),
+    /// Appended to this existing block
+    Merged(BlockId),
+}
+
+impl StoreOutcome {
+    /// ID of the block that now holds the content
+    pub fn id(&self) -> &BlockId {
+        match self {
+            StoreOutcome::Inserted(id) | StoreOutcome::Deduped(id) | StoreOutcome::Merged(id) => id,
+        }
+    }
+
+    pub fn into_id(self) -> BlockId {
+        match self {
+            StoreOutcome::Inserted(id) | StoreOutcome::Deduped(id) | StoreOutcome::Merged(id) => id,
+        }
+    }
+}
+
+/// Text to compare a block by, or `None` for binary and empty content
+pub(crate) fn comparable_text(block: &MemoryBlock) -> Option<String> {
+    let text = match block.content() {
+        MemoryContent::Text(text) => text.clone(),
+        MemoryContent::Json(json) => json.to_string(),
+        MemoryContent::Binary { .. } => return None,
+    };
+    (!text.is_empty()).then_some(text)
+}
+
+/// `existing` with `incoming`'s text appended, or `None` if either isn't text
+///
+/// The incoming block's ID, session and creation time are added to the
+/// [`MERGED_FROM_PROPERTY`] list and its tags are carried over.
+pub(crate) fn merge(mut existing: MemoryBlock, incoming: &MemoryBlock) -> Option<MemoryBlock> {
+    let (MemoryContent::Text(base), MemoryContent::Text(addition)) =
+        (existing.content(), incoming.content())
+    else {
+        return None;
+    };
+    let merged_text = format!("{}\n{}", base, addition);
+
+    let mut provenance = existing
+        .get_property(MERGED_FROM_PROPERTY)
+        .and_then(|value| value.as_array().cloned())
+        .unwrap_or_default();
+    provenance.push(json!({
+        "id": incoming.id().as_str(),
+        "session_id": incoming.session_id(),
+        "created_at": incoming.created_at(),
+    }));
+
+    existing.set_content(MemoryContent::Text(merged_text));
+    existing.set_property(MERGED_FROM_PROPERTY, provenance);
+    for tag in incoming.tags() {
+        existing.add_tag(tag.clone());
+    }
+    Some(existing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embeddings::EmbeddingService;
+    use crate::storage::{InMemoryMemoryStore, MemoryManager};
+    use crate::types::BlockType;
+    use async_trait::async_trait;
+    use luts_common::Result;
+    use std::sync::Arc;
+
+    /// Bag-of-words embedding, so rewordings of a sentence stay close
+    struct WordEmbedder;
+
+    #[async_trait]
+    impl EmbeddingService for WordEmbedder {
+        async fn embed_text(&self, text: &str) -> Result<Vec<f32>> {
+            let mut embedding = vec![0.0; 256];
+            for word in text.to_lowercase().split_whitespace() {
+                embedding[luts_common::string_hash(word) as usize % 256] += 1.0;
+            }
+            Ok(embedding)
+        }
+
+        async fn embed_texts(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            let mut embeddings = Vec::new();
+            for text in texts {
+                embeddings.push(self.embed_text(text).await?);
+            }
+            Ok(embeddings)
+        }
+
+        fn dimensions(&self) -> usize {
+            256
+        }
+
+        fn max_text_length(&self) -> usize {
+            8192
+        }
+    }
+
+    fn deduplicating_manager(policy: DedupPolicy) -> MemoryManager {
+        let embedder = Arc::new(WordEmbedder);
+        MemoryManager::new(InMemoryMemoryStore::with_embedding_service(
+            embedder.clone(),
+        ))
+        .with_dedup(
+            DedupConfig {
+                enabled: true,
+                threshold: 0.8,
+                policy,
+            },
+            embedder,
+        )
+    }
+
+    fn fact(user_id: &str, text: &str) -> MemoryBlock {
+        MemoryBlock::new(
+            BlockType::Fact,
+            user_id,
+            MemoryContent::Text(text.to_string()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_skip_returns_existing_block() {
+        let manager = deduplicating_manager(DedupPolicy::Skip);
+
+        let first = manager
+            .store_with_outcome(fact("alice", "Alice likes green tea in the morning"))
+            .await
+            .unwrap();
+        let StoreOutcome::Inserted(id) = first else {
+            panic!("expected an insert, got {:?}", first);
+        };
+
+        let reworded = manager
+            .store_with_outcome(fact("alice", "Alice likes green tea in the morning a lot"))
+            .await
+            .unwrap();
+        assert_eq!(reworded, StoreOutcome::Deduped(id.clone()));
+
+        // Other users and unrelated facts are stored as usual
+        let other_user = manager
+            .store_with_outcome(fact("bob", "Alice likes green tea in the morning"))
+            .await
+            .unwrap();
+        assert!(matches!(other_user, StoreOutcome::Inserted(_)));
+        let unrelated = manager
+            .store_with_outcome(fact("alice", "Alice moved to Lisbon last spring"))
+            .await
+            .unwrap();
+        assert!(matches!(unrelated, StoreOutcome::Inserted(_)));
+        assert_eq!(manager.list("alice").await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_merge_appends_with_provenance() {
+        let manager = deduplicating_manager(DedupPolicy::Merge);
+
+        let id = manager
+            .store(fact("alice", "Alice likes green tea in the morning"))
+            .await
+            .unwrap();
+        let incoming = fact("alice", "Alice likes green tea in the morning a lot");
+        let incoming_id = incoming.id().clone();
+
+        let outcome = manager.store_with_outcome(incoming).await.unwrap();
+        assert_eq!(outcome, StoreOutcome::Merged(id.clone()));
+
+        let merged = manager.get(&id).await.unwrap().unwrap();
+        assert_eq!(
+            merged.content().as_text(),
+            Some(
+                "Alice likes green tea in the morning\nAlice likes green tea in the morning a lot"
+            )
+        );
+        let provenance = merged.get_property(MERGED_FROM_PROPERTY).unwrap();
+        assert_eq!(provenance[0]["id"], incoming_id.as_str());
+        assert_eq!(manager.list("alice").await.unwrap().len(), 1);
+    }
+}
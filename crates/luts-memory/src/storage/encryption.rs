@@ -0,0 +1,218 @@
+//! Encryption at rest for memory block content
+//!
+//! Stored content is sealed as `enc:v1:<base64(nonce || ciphertext)>`. Rows
+//! without the prefix are treated as plaintext, so stores that enable
+//! encryption can still read blocks written before it was turned on.
+
+use aes_gcm::{
+    Aes256Gcm, Key, Nonce,
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use luts_common::{LutsError, Result};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+/// Prefix marking content that was sealed by a [`Keyring`]
+const SEALED_PREFIX: &str = "enc:v1:";
+
+/// AES-GCM nonce length in bytes
+const NONCE_LEN: usize = 12;
+
+/// Symmetric encryption used to protect block content on disk
+pub trait Encryptor: Send + Sync {
+    /// Encrypt `plaintext`, returning bytes that [`Encryptor::decrypt`] accepts
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>>;
+
+    /// Decrypt bytes produced by [`Encryptor::encrypt`] with the same key
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// AES-256-GCM with a random nonce per message
+pub struct AesGcmEncryptor {
+    cipher: Aes256Gcm,
+}
+
+impl AesGcmEncryptor {
+    /// Create an encryptor from a 32-byte key
+    pub fn new(key: &[u8; 32]) -> Self {
+        Self {
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)),
+        }
+    }
+}
+
+impl Encryptor for AesGcmEncryptor {
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| LutsError::Storage("Failed to encrypt block content".to_string()))?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        if ciphertext.len() < NONCE_LEN {
+            return Err(LutsError::Storage(
+                "Encrypted block content is truncated".to_string(),
+            ));
+        }
+        let (nonce, ciphertext) = ciphertext.split_at(NONCE_LEN);
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| LutsError::Storage("Failed to decrypt block content".to_string()))
+    }
+}
+
+/// A 256-bit AES key, written in config files as base64
+///
+/// `Debug` output leaves the key out.
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncryptionKey([u8; 32]);
+
+impl EncryptionKey {
+    /// Parse a base64-encoded 32-byte key
+    pub fn from_base64(encoded: &str) -> Result<Self> {
+        let bytes = STANDARD
+            .decode(encoded.trim())
+            .map_err(|e| LutsError::Config(format!("Encryption key is not valid base64: {}", e)))?;
+        let key: [u8; 32] = bytes.try_into().map_err(|bytes: Vec<u8>| {
+            LutsError::Config(format!(
+                "Encryption key must be 32 bytes, got {}",
+                bytes.len()
+            ))
+        })?;
+        Ok(Self(key))
+    }
+
+    /// The key as base64
+    pub fn to_base64(&self) -> String {
+        STANDARD.encode(self.0)
+    }
+
+    /// AES-256-GCM encryptor for this key
+    pub fn encryptor(&self) -> AesGcmEncryptor {
+        AesGcmEncryptor::new(&self.0)
+    }
+}
+
+impl From<[u8; 32]> for EncryptionKey {
+    fn from(key: [u8; 32]) -> Self {
+        Self(key)
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EncryptionKey(..)")
+    }
+}
+
+impl Serialize for EncryptionKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_base64())
+    }
+}
+
+impl<'de> Deserialize<'de> for EncryptionKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        Self::from_base64(&encoded).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Keys known to a store, newest last
+///
+/// The newest key encrypts; older keys stay available so blocks that haven't
+/// been rotated yet can still be read. Clones share the same keys.
+#[derive(Clone, Default)]
+pub(crate) struct Keyring {
+    keys: Arc<RwLock<Vec<Arc<dyn Encryptor>>>>,
+}
+
+impl Keyring {
+    /// Whether content is encrypted before it is written
+    pub(crate) fn is_enabled(&self) -> bool {
+        !self.keys.read().unwrap().is_empty()
+    }
+
+    /// Make `key` the current encryption key
+    pub(crate) fn push(&self, key: Arc<dyn Encryptor>) {
+        self.keys.write().unwrap().push(key);
+    }
+
+    /// Encrypt stored content with the current key, or return it unchanged if encryption is off
+    pub(crate) fn seal(&self, content: &str) -> Result<String> {
+        let keys = self.keys.read().unwrap();
+        match keys.last() {
+            Some(key) => {
+                let sealed = key.encrypt(content.as_bytes())?;
+                Ok(format!("{}{}", SEALED_PREFIX, STANDARD.encode(sealed)))
+            }
+            None => Ok(content.to_string()),
+        }
+    }
+
+    /// Decrypt stored content, trying the newest key first
+    pub(crate) fn open(&self, stored: &str) -> Result<String> {
+        let Some(encoded) = stored.strip_prefix(SEALED_PREFIX) else {
+            return Ok(stored.to_string());
+        };
+        let sealed = STANDARD.decode(encoded).map_err(|e| {
+            LutsError::Storage(format!("Encrypted block content is not valid base64: {}", e))
+        })?;
+
+        let keys = self.keys.read().unwrap();
+        let plaintext = keys
+            .iter()
+            .rev()
+            .find_map(|key| key.decrypt(&sealed).ok())
+            .ok_or_else(|| {
+                LutsError::Storage(
+                    "Block content is encrypted and no configured key can decrypt it".to_string(),
+                )
+            })?;
+
+        String::from_utf8(plaintext).map_err(|e| {
+            LutsError::Storage(format!("Decrypted block content is not valid UTF-8: {}", e))
+        })
+    }
+
+    /// Whether stored content was sealed by a keyring
+    pub(crate) fn is_sealed(stored: &str) -> bool {
+        stored.starts_with(SEALED_PREFIX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_and_open_round_trip() {
+        let keyring = Keyring::default();
+        assert_eq!(keyring.seal("plain").unwrap(), "plain");
+
+        keyring.push(Arc::new(AesGcmEncryptor::new(&[7; 32])));
+        let sealed = keyring.seal("secret text").unwrap();
+        assert!(Keyring::is_sealed(&sealed));
+        assert!(!sealed.contains("secret text"));
+        assert_eq!(keyring.open(&sealed).unwrap(), "secret text");
+
+        // Unsealed rows written before encryption was enabled still read back
+        assert_eq!(keyring.open("legacy").unwrap(), "legacy");
+
+        // Older keys keep decrypting after a newer one is added
+        keyring.push(Arc::new(AesGcmEncryptor::new(&[9; 32])));
+        assert_eq!(keyring.open(&sealed).unwrap(), "secret text");
+
+        let stranger = Keyring::default();
+        stranger.push(Arc::new(AesGcmEncryptor::new(&[1; 32])));
+        assert!(stranger.open(&sealed).is_err());
+    }
+}
@@ -0,0 +1,645 @@
+//! In-memory storage backend for memory blocks
+//!
+//! Keeps blocks in a `HashMap` behind an async lock. Nothing touches the
+//! filesystem, which makes it a good fit for tests and ephemeral sessions.
+
+use super::{MemoryQuery, MemoryStats, MemoryStore, QuerySort, VectorQuery, validate_batch};
+use crate::{
+    block::MemoryBlock,
+    embeddings::{EmbeddingService, SimilarityMetric},
+    types::{BlockId, MemoryContent, Relevance},
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use luts_common::{LutsError, Result};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+/// A stored block together with its embedding, if one was generated
+#[derive(Debug, Clone)]
+struct StoredBlock {
+    block: MemoryBlock,
+    embedding: Option<Vec<f32>>,
+    /// Metric the embedding was indexed for, if the store had one
+    metric: Option<SimilarityMetric>,
+}
+
+/// Memory store that keeps all blocks in process memory
+#[derive(Default)]
+pub struct InMemoryMemoryStore {
+    blocks: RwLock<HashMap<BlockId, StoredBlock>>,
+    embedding_service: Option<Arc<dyn EmbeddingService>>,
+    index_metric: Option<SimilarityMetric>,
+}
+
+impl InMemoryMemoryStore {
+    /// Create an empty store without embeddings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty store that embeds text blocks on write, enabling vector search
+    pub fn with_embedding_service(embedding_service: Arc<dyn EmbeddingService>) -> Self {
+        InMemoryMemoryStore {
+            blocks: RwLock::new(HashMap::new()),
+            embedding_service: Some(embedding_service),
+            index_metric: None,
+        }
+    }
+
+    /// Index embeddings for `metric`
+    ///
+    /// The metric is recorded with each embedding and queries using another
+    /// one are rejected. Cosine indexes store normalized vectors.
+    pub fn with_index_metric(mut self, metric: SimilarityMetric) -> Self {
+        self.index_metric = Some(metric);
+        self
+    }
+
+    /// Number of blocks currently stored
+    pub async fn len(&self) -> usize {
+        self.blocks.read().await.len()
+    }
+
+    /// Whether the store holds no blocks
+    pub async fn is_empty(&self) -> bool {
+        self.blocks.read().await.is_empty()
+    }
+
+    /// Generate an embedding for a block's text, if an embedding service is configured
+    async fn embed(&self, block: &MemoryBlock) -> Result<Option<Vec<f32>>> {
+        let Some(embedding_service) = &self.embedding_service else {
+            return Ok(None);
+        };
+
+        let text = match block.content() {
+            MemoryContent::Text(text) => text.clone(),
+            MemoryContent::Json(json) => json.to_string(),
+            MemoryContent::Binary { .. } => return Ok(None),
+        };
+        if text.is_empty() {
+            return Ok(None);
+        }
+
+        embedding_service.embed_text(&text).await.map(Some)
+    }
+
+    /// Entry for `block`, with its embedding prepared for the index metric
+    fn entry(&self, block: MemoryBlock, embedding: Option<Vec<f32>>) -> StoredBlock {
+        let (embedding, metric) = match (embedding, self.index_metric) {
+            (Some(embedding), Some(metric)) => {
+                (Some(metric.prepare_for_index(embedding)), Some(metric))
+            }
+            (embedding, _) => (embedding, None),
+        };
+        StoredBlock {
+            block,
+            embedding,
+            metric,
+        }
+    }
+
+    /// Whether a block passes the non-vector filters of a query
+    fn matches(block: &MemoryBlock, query: &MemoryQuery) -> bool {
+        if let Some(user_id) = &query.user_id
+            && block.user_id() != user_id
+        {
+            return false;
+        }
+
+        if let Some(session_id) = &query.session_id
+            && block.session_id() != Some(session_id.as_str())
+        {
+            return false;
+        }
+
+        if !query.block_types.is_empty() && !query.block_types.contains(&block.block_type()) {
+            return false;
+        }
+
+        if let Some(needle) = &query.content_contains {
+            let found = match block.content() {
+                MemoryContent::Text(text) => text.contains(needle.as_str()),
+                MemoryContent::Json(json) => json.to_string().contains(needle.as_str()),
+                MemoryContent::Binary { .. } => false,
+            };
+            if !found {
+                return false;
+            }
+        }
+
+        let created_at = DateTime::<Utc>::from_timestamp_millis(block.created_at() as i64);
+        if let (Some(after), Some(created_at)) = (query.created_after, created_at)
+            && created_at < after
+        {
+            return false;
+        }
+        if let (Some(before), Some(created_at)) = (query.created_before, created_at)
+            && created_at > before
+        {
+            return false;
+        }
+
+        true
+    }
+
+    /// Brute-force similarity over every stored embedding using the query's metric
+    fn vector_search(
+        stored: &HashMap<BlockId, StoredBlock>,
+        vector_query: &VectorQuery,
+        query: &MemoryQuery,
+    ) -> Result<Vec<MemoryBlock>> {
+        let config = &vector_query.search_config;
+        let metric = vector_query.metric();
+
+        let mut scored: Vec<(f32, MemoryBlock)> = Vec::new();
+        for entry in stored.values().filter(|entry| Self::matches(&entry.block, query)) {
+            let Some(embedding) = entry.embedding.as_ref() else {
+                continue;
+            };
+            config.check_dimensions(vector_query.query_vector.len(), embedding.len())?;
+            vector_query.check_metric(entry.metric)?;
+
+            let score = metric.similarity(&vector_query.query_vector, embedding);
+            if score >= config.min_relevance {
+                scored.push((score, entry.block.clone()));
+            }
+        }
+
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        scored.truncate(config.max_results);
+
+        Ok(scored
+            .into_iter()
+            .map(|(score, mut block)| {
+                block.set_relevance(Relevance::raw(score));
+                block
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl MemoryStore for InMemoryMemoryStore {
+    async fn store(&self, block: MemoryBlock) -> Result<BlockId> {
+        let embedding = match self.embed(&block).await {
+            Ok(embedding) => embedding,
+            Err(e) => {
+                warn!("Failed to embed block {}, storing without one: {}", block.id(), e);
+                None
+            }
+        };
+
+        let id = block.id().clone();
+        self.blocks
+            .write()
+            .await
+            .insert(id.clone(), self.entry(block, embedding));
+
+        debug!("Stored memory block {} in memory", id);
+        Ok(id)
+    }
+
+    fn embedding_dimensions(&self) -> Option<usize> {
+        self.embedding_service.as_ref().map(|service| service.dimensions())
+    }
+
+    async fn retrieve(&self, id: &BlockId) -> Result<Option<MemoryBlock>> {
+        Ok(self
+            .blocks
+            .read()
+            .await
+            .get(id)
+            .map(|entry| entry.block.clone()))
+    }
+
+    async fn delete(&self, id: &BlockId) -> Result<bool> {
+        Ok(self.blocks.write().await.remove(id).is_some())
+    }
+
+    async fn update(&self, id: &BlockId, block: MemoryBlock) -> Result<MemoryBlock> {
+        if !self.blocks.read().await.contains_key(id) {
+            return Err(LutsError::BlockNotFound(id.to_string()));
+        }
+
+        let embedding = self.embed(&block).await.unwrap_or_else(|e| {
+            warn!("Failed to re-embed block {}: {}", id, e);
+            None
+        });
+
+        self.blocks
+            .write()
+            .await
+            .insert(id.clone(), self.entry(block.clone(), embedding));
+        Ok(block)
+    }
+
+    async fn store_many(&self, blocks: Vec<MemoryBlock>) -> Result<Vec<BlockId>> {
+        validate_batch(&blocks)?;
+
+        let mut entries = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            let embedding = self.embed(&block).await.unwrap_or_else(|e| {
+                warn!("Failed to embed block {}, storing without one: {}", block.id(), e);
+                None
+            });
+            entries.push(self.entry(block, embedding));
+        }
+
+        // Insert under a single lock so the batch appears all at once
+        let mut stored = self.blocks.write().await;
+        let ids = entries
+            .into_iter()
+            .map(|entry| {
+                let id = entry.block.id().clone();
+                stored.insert(id.clone(), entry);
+                id
+            })
+            .collect();
+        Ok(ids)
+    }
+
+    async fn update_many(&self, blocks: Vec<MemoryBlock>) -> Result<u64> {
+        let mut entries = Vec::with_capacity(blocks.len());
+        for block in blocks {
+            let embedding = self.embed(&block).await.unwrap_or_else(|e| {
+                warn!("Failed to re-embed block {}: {}", block.id(), e);
+                None
+            });
+            entries.push(self.entry(block, embedding));
+        }
+
+        // Check and replace under a single lock so the batch applies all at once
+        let mut stored = self.blocks.write().await;
+        if let Some(missing) = entries
+            .iter()
+            .find(|entry| !stored.contains_key(entry.block.id()))
+        {
+            return Err(LutsError::BlockNotFound(missing.block.id().to_string()));
+        }
+        let updated = entries.len() as u64;
+        for entry in entries {
+            stored.insert(entry.block.id().clone(), entry);
+        }
+        Ok(updated)
+    }
+
+    async fn delete_many(&self, ids: &[BlockId]) -> Result<u64> {
+        let mut stored = self.blocks.write().await;
+        Ok(ids.iter().filter(|id| stored.remove(*id).is_some()).count() as u64)
+    }
+
+    async fn query(&self, query: MemoryQuery) -> Result<Vec<MemoryBlock>> {
+        let stored = self.blocks.read().await;
+
+        if let Some(vector_query) = &query.vector_search {
+            return Self::vector_search(&stored, vector_query, &query);
+        }
+
+        let mut blocks: Vec<MemoryBlock> = stored
+            .values()
+            .filter(|entry| Self::matches(&entry.block, &query))
+            .map(|entry| entry.block.clone())
+            .collect();
+        drop(stored);
+
+        match query.sort.unwrap_or_default() {
+            QuerySort::NewestFirst => {
+                blocks.sort_by_key(|block| std::cmp::Reverse(block.created_at()))
+            }
+            QuerySort::OldestFirst => blocks.sort_by_key(|block| block.created_at()),
+            QuerySort::Relevance => blocks.sort_by(|a, b| {
+                let score = |block: &MemoryBlock| block.relevance().map(|r| r.score()).unwrap_or(0.0);
+                score(b).total_cmp(&score(a))
+            }),
+        }
+
+        if let Some(limit) = query.limit {
+            blocks.truncate(limit);
+        }
+
+        Ok(blocks)
+    }
+
+    async fn blocks_without_embedding(
+        &self,
+        user_id: &str,
+        after: Option<&BlockId>,
+        limit: usize,
+    ) -> Result<Vec<MemoryBlock>> {
+        let stored = self.blocks.read().await;
+        let mut blocks: Vec<MemoryBlock> = stored
+            .values()
+            .filter(|entry| entry.embedding.is_none() && entry.block.user_id() == user_id)
+            .filter(|entry| after.is_none_or(|after| entry.block.id().as_str() > after.as_str()))
+            .map(|entry| entry.block.clone())
+            .collect();
+        blocks.sort_by(|a, b| a.id().as_str().cmp(b.id().as_str()));
+        blocks.truncate(limit);
+        Ok(blocks)
+    }
+
+    async fn set_embeddings(&self, embeddings: Vec<(BlockId, Vec<f32>)>) -> Result<()> {
+        let mut stored = self.blocks.write().await;
+        for (id, embedding) in embeddings {
+            if let Some(entry) = stored.get(&id) {
+                let entry = self.entry(entry.block.clone(), Some(embedding));
+                stored.insert(id, entry);
+            }
+        }
+        Ok(())
+    }
+
+    async fn clear_user_data(&self, user_id: &str) -> Result<u64> {
+        let mut stored = self.blocks.write().await;
+        let before = stored.len();
+        stored.retain(|_, entry| entry.block.user_id() != user_id);
+        Ok((before - stored.len()) as u64)
+    }
+
+    async fn get_stats(&self, user_id: &str) -> Result<MemoryStats> {
+        let stored = self.blocks.read().await;
+
+        let mut total_blocks = 0;
+        let mut blocks_by_type = HashMap::new();
+        let mut total_size_bytes = 0;
+        let mut last_updated = None;
+
+        for entry in stored.values().filter(|entry| entry.block.user_id() == user_id) {
+            total_blocks += 1;
+            *blocks_by_type
+                .entry(entry.block.block_type().to_string())
+                .or_insert(0) += 1;
+            total_size_bytes += serde_json::to_vec(entry.block.content())
+                .map(|bytes| bytes.len() as u64)
+                .unwrap_or(0);
+            last_updated = last_updated.max(Some(entry.block.updated_at()));
+        }
+
+        Ok(MemoryStats {
+            total_blocks,
+            blocks_by_type,
+            total_size_bytes,
+            last_updated: last_updated
+                .and_then(|millis| DateTime::from_timestamp_millis(millis as i64))
+                .unwrap_or_else(Utc::now),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::MemoryBlockBuilder;
+    use crate::embeddings::{EmbeddingConfig, MockEmbeddingService, VectorSearchConfig};
+    use crate::storage::MemoryManager;
+    use crate::types::BlockType;
+
+    fn fact(user_id: &str, text: &str, created_at: u64) -> MemoryBlock {
+        MemoryBlockBuilder::new()
+            .with_user_id(user_id)
+            .with_type(BlockType::Fact)
+            .with_content(MemoryContent::Text(text.to_string()))
+            .with_created_at(created_at)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_filters_and_sorting() {
+        let manager = MemoryManager::new(InMemoryMemoryStore::new());
+
+        manager.store(fact("alice", "likes tea", 1_000)).await.unwrap();
+        manager.store(fact("alice", "likes coffee", 2_000)).await.unwrap();
+        manager.store(fact("bob", "likes tea", 3_000)).await.unwrap();
+        let goal = MemoryBlock::new(
+            BlockType::Goal,
+            "alice",
+            MemoryContent::Text("learn rust".to_string()),
+        );
+        manager.store(goal).await.unwrap();
+
+        let tea = manager
+            .search(&MemoryQuery {
+                user_id: Some("alice".to_string()),
+                block_types: vec![BlockType::Fact],
+                content_contains: Some("likes".to_string()),
+                sort: Some(QuerySort::OldestFirst),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let texts: Vec<&str> = tea.iter().filter_map(|b| b.content().as_text()).collect();
+        assert_eq!(texts, vec!["likes tea", "likes coffee"]);
+
+        let recent = manager
+            .search(&MemoryQuery {
+                created_after: DateTime::from_timestamp_millis(1_500),
+                created_before: DateTime::from_timestamp_millis(2_500),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].content().as_text(), Some("likes coffee"));
+
+        assert_eq!(manager.clear_user_data("alice").await.unwrap(), 3);
+        assert_eq!(manager.get_stats("bob").await.unwrap().total_blocks, 1);
+    }
+
+    #[tokio::test]
+    async fn test_vector_search_ranks_by_cosine_similarity() {
+        let embedding_service = Arc::new(MockEmbeddingService::new(EmbeddingConfig {
+            dimensions: 64,
+            ..Default::default()
+        }));
+        let store = InMemoryMemoryStore::with_embedding_service(embedding_service.clone());
+
+        let target = fact("alice", "the cat sat on the mat", 1_000);
+        let target_id = store.store(target).await.unwrap();
+        store.store(fact("alice", "quarterly tax filing", 2_000)).await.unwrap();
+
+        let query_vector = embedding_service
+            .embed_text("the cat sat on the mat")
+            .await
+            .unwrap();
+        let results = store
+            .query(MemoryQuery {
+                user_id: Some("alice".to_string()),
+                vector_search: Some(VectorQuery {
+                    query_vector,
+                    search_config: VectorSearchConfig {
+                        max_results: 1,
+                        min_relevance: 0.0,
+                        ..Default::default()
+                    },
+                    metric_override: None,
+                }),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id(), &target_id);
+        let score = results[0].relevance().unwrap().score();
+        assert!((score - 1.0).abs() < 1e-4, "identical text should score ~1.0, got {}", score);
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_typed() {
+        let store = InMemoryMemoryStore::new();
+
+        let missing = BlockId::from("missing");
+        let err = store.update(&missing, fact("alice", "x", 1_000)).await.unwrap_err();
+        assert!(matches!(err, LutsError::BlockNotFound(id) if id == "missing"));
+
+        let mut nameless = fact("alice", "x", 1_000);
+        nameless.metadata.user_id.clear();
+        let err = store.store_many(vec![nameless]).await.unwrap_err();
+        assert!(matches!(err, LutsError::Validation(_)), "unexpected error: {}", err);
+    }
+
+    #[tokio::test]
+    async fn test_vector_search_rejects_dimension_mismatch() {
+        let store = InMemoryMemoryStore::with_embedding_service(Arc::new(MockEmbeddingService::new(
+            EmbeddingConfig {
+                dimensions: 64,
+                ..Default::default()
+            },
+        )));
+        store.store(fact("alice", "stored with 64 dims", 1_000)).await.unwrap();
+
+        let err = store
+            .query(MemoryQuery {
+                vector_search: Some(VectorQuery {
+                    query_vector: vec![0.5; 32],
+                    search_config: VectorSearchConfig {
+                        expected_dimensions: Some(32),
+                        ..Default::default()
+                    },
+                    metric_override: None,
+                }),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                LutsError::EmbeddingDimensionMismatch { expected: 32, actual: 64, .. }
+            ),
+            "unexpected error: {}",
+            err
+        );
+    }
+
+    fn vector_query(query_vector: Vec<f32>, metric: SimilarityMetric) -> MemoryQuery {
+        MemoryQuery {
+            user_id: Some("alice".to_string()),
+            vector_search: Some(VectorQuery {
+                query_vector,
+                search_config: VectorSearchConfig {
+                    min_relevance: -1.0,
+                    ..Default::default()
+                },
+                metric_override: Some(metric),
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cosine_index_rejects_dot_product_query() {
+        let embedding_service = Arc::new(MockEmbeddingService::new(EmbeddingConfig {
+            dimensions: 64,
+            ..Default::default()
+        }));
+        let store = InMemoryMemoryStore::with_embedding_service(embedding_service.clone())
+            .with_index_metric(SimilarityMetric::Cosine);
+        store.store(fact("alice", "the cat sat on the mat", 1_000)).await.unwrap();
+        let query_vector = embedding_service.embed_text("the cat sat").await.unwrap();
+
+        let err = store
+            .query(vector_query(query_vector.clone(), SimilarityMetric::DotProduct))
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("indexed for cosine similarity but the query uses dot product"),
+            "unexpected error: {}",
+            err
+        );
+
+        let results = store
+            .query(vector_query(query_vector, SimilarityMetric::Cosine))
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cosine_index_normalizes_embeddings() {
+        let embedding_service = Arc::new(MockEmbeddingService::new(EmbeddingConfig {
+            dimensions: 64,
+            ..Default::default()
+        }));
+        let indexed = InMemoryMemoryStore::with_embedding_service(embedding_service.clone())
+            .with_index_metric(SimilarityMetric::Cosine);
+        let plain = InMemoryMemoryStore::with_embedding_service(embedding_service.clone());
+        for store in [&indexed, &plain] {
+            for (i, text) in ["the cat sat on the mat", "quarterly tax filing"].iter().enumerate() {
+                let block = MemoryBlockBuilder::new()
+                    .with_id(BlockId::new(format!("block_{}", i)))
+                    .with_user_id("alice")
+                    .with_type(BlockType::Fact)
+                    .with_content(MemoryContent::Text(text.to_string()))
+                    .build()
+                    .unwrap();
+                store.store(block).await.unwrap();
+            }
+        }
+
+        for entry in indexed.blocks.read().await.values() {
+            let embedding = entry.embedding.as_ref().unwrap();
+            let norm = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+            assert!((norm - 1.0).abs() < 1e-4, "stored vector has norm {}", norm);
+            assert_eq!(entry.metric, Some(SimilarityMetric::Cosine));
+        }
+
+        // Cosine scores don't change with the stored vectors' scale
+        let query_vector: Vec<f32> = embedding_service
+            .embed_text("a cat on a mat")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|x| x * 3.0)
+            .collect();
+        let scores = |blocks: Vec<MemoryBlock>| -> Vec<(BlockId, f32)> {
+            blocks
+                .into_iter()
+                .map(|block| (block.id().clone(), block.relevance().unwrap().score()))
+                .collect()
+        };
+        let indexed_scores = scores(
+            indexed
+                .query(vector_query(query_vector.clone(), SimilarityMetric::Cosine))
+                .await
+                .unwrap(),
+        );
+        let plain_scores = scores(
+            plain
+                .query(vector_query(query_vector, SimilarityMetric::Cosine))
+                .await
+                .unwrap(),
+        );
+        assert_eq!(indexed_scores.len(), 2);
+        for ((indexed_id, indexed_score), (plain_id, plain_score)) in
+            indexed_scores.iter().zip(&plain_scores)
+        {
+            assert_eq!(indexed_id, plain_id);
+            assert!((indexed_score - plain_score).abs() < 1e-4);
+        }
+    }
+}
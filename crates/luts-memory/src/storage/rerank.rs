@@ -0,0 +1,56 @@
+//! Reranking for [`MemoryManager::semantic_search`](super::MemoryManager::semantic_search)
+//!
+//! Vector similarity finds blocks about the same topic as the query, not
+//! necessarily the ones that help answer it. A reranker, such as a
+//! cross-encoder or an LLM asked to score each block, looks at the query and
+//! a wider set of vector matches together and picks the best of them.
+
+use crate::block::MemoryBlock;
+use async_trait::async_trait;
+use luts_common::Result;
+use serde::{Deserialize, Serialize};
+
+/// Scores semantic search candidates against the query text
+#[async_trait]
+pub trait Reranker: Send + Sync {
+    /// Score each candidate; higher scores are more relevant
+    async fn rerank(
+        &self,
+        query: &str,
+        candidates: Vec<MemoryBlock>,
+    ) -> Result<Vec<(MemoryBlock, f32)>>;
+}
+
+/// Reranker that keeps the vector search order
+pub struct IdentityReranker;
+
+#[async_trait]
+impl Reranker for IdentityReranker {
+    async fn rerank(
+        &self,
+        _query: &str,
+        candidates: Vec<MemoryBlock>,
+    ) -> Result<Vec<(MemoryBlock, f32)>> {
+        let count = candidates.len();
+        Ok(candidates
+            .into_iter()
+            .enumerate()
+            .map(|(index, block)| (block, (count - index) as f32))
+            .collect())
+    }
+}
+
+/// Settings for reranking semantic search results
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RerankConfig {
+    /// How many vector matches to hand the reranker; at least the number of
+    /// results asked for is always fetched
+    pub candidates: usize,
+}
+
+impl Default for RerankConfig {
+    fn default() -> Self {
+        Self { candidates: 50 }
+    }
+}
@@ -0,0 +1,416 @@
+//! Retries and a circuit breaker for storage backend operations
+//!
+//! Transient backend errors (timeouts, dropped connections, transaction
+//! conflicts) are retried with exponential backoff. When operations keep
+//! failing, the breaker opens and calls fail fast with
+//! [`LutsError::Backend`] instead of waiting on a dead database. After a
+//! cool-down one call is let through as a probe; if it succeeds the breaker
+//! closes again.
+
+use chrono::{DateTime, Utc};
+use luts_common::retry::{RetryConfig, RetryableError};
+use luts_common::{LutsError, Result};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// When the circuit breaker opens and how often it probes for recovery
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failed operations that open the breaker
+    pub failure_threshold: u32,
+    /// How long the breaker stays open before letting a probe through
+    pub probe_interval: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            probe_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Circuit breaker state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CircuitState {
+    /// Operations run normally
+    Closed,
+    /// Operations fail fast until the next probe
+    Open,
+    /// A probe operation is in flight
+    HalfOpen,
+}
+
+/// Breaker state snapshot for monitoring
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitBreakerStatus {
+    pub state: CircuitState,
+    /// Failed operations since the last success
+    pub consecutive_failures: u32,
+    /// Retries performed since the store was created
+    pub total_retries: u64,
+    /// Calls rejected while the breaker was open
+    pub rejected_calls: u64,
+    /// When the breaker last opened
+    pub opened_at: Option<DateTime<Utc>>,
+    /// The most recent backend error
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug)]
+struct BreakerInner {
+    state: CircuitState,
+    consecutive_failures: u32,
+    total_retries: u64,
+    rejected_calls: u64,
+    opened_at: Option<DateTime<Utc>>,
+    probe_after: Option<Instant>,
+    last_error: Option<String>,
+}
+
+/// Retry policy plus circuit breaker, shared by clones of a store
+#[derive(Debug, Clone)]
+pub struct Resilience {
+    retry: RetryConfig,
+    breaker: CircuitBreakerConfig,
+    inner: Arc<Mutex<BreakerInner>>,
+}
+
+impl Default for Resilience {
+    fn default() -> Self {
+        Self::new(RetryConfig::default(), CircuitBreakerConfig::default())
+    }
+}
+
+impl Resilience {
+    pub fn new(retry: RetryConfig, breaker: CircuitBreakerConfig) -> Self {
+        Self {
+            retry,
+            breaker,
+            inner: Arc::new(Mutex::new(BreakerInner {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                total_retries: 0,
+                rejected_calls: 0,
+                opened_at: None,
+                probe_after: None,
+                last_error: None,
+            })),
+        }
+    }
+
+    /// Replace the retry policy, keeping breaker state
+    pub fn with_retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Replace the breaker thresholds, keeping breaker state
+    pub fn with_breaker(mut self, breaker: CircuitBreakerConfig) -> Self {
+        self.breaker = breaker;
+        self
+    }
+
+    /// Current breaker state
+    pub fn status(&self) -> CircuitBreakerStatus {
+        let inner = self.inner.lock().expect("circuit breaker lock poisoned");
+        CircuitBreakerStatus {
+            state: inner.state,
+            consecutive_failures: inner.consecutive_failures,
+            total_retries: inner.total_retries,
+            rejected_calls: inner.rejected_calls,
+            opened_at: inner.opened_at,
+            last_error: inner.last_error.clone(),
+        }
+    }
+
+    /// Run `operation`, retrying transient errors, unless the breaker is open
+    ///
+    /// Only transient errors count against the breaker; parse and validation
+    /// errors mean the backend answered.
+    pub async fn run<T, F, Fut>(&self, name: &str, mut operation: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let probing = self.admit(name)?;
+        // Hands the probe slot back if this future is dropped before it settles
+        let _probe = probing.then(|| ProbeGuard { inner: &self.inner });
+        // A probe gets a single attempt so a dead backend is detected quickly
+        let max_retries = if probing { 0 } else { self.retry.max_retries };
+
+        let mut retry = 0;
+        loop {
+            match operation().await {
+                Ok(value) => {
+                    self.record_success();
+                    return Ok(value);
+                }
+                Err(e) if self.is_transient(&e) && retry < max_retries => {
+                    let delay = self.retry.delay(retry);
+                    warn!(
+                        "{} failed with a transient error, retrying in {:?} ({}/{}): {}",
+                        name,
+                        delay,
+                        retry + 1,
+                        max_retries,
+                        e
+                    );
+                    self.inner
+                        .lock()
+                        .expect("circuit breaker lock poisoned")
+                        .total_retries += 1;
+                    tokio::time::sleep(delay).await;
+                    retry += 1;
+                }
+                Err(e) => {
+                    if self.is_transient(&e) {
+                        self.record_failure(&e);
+                    } else {
+                        self.record_success();
+                    }
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    /// Decide whether a call may run; returns whether it is the recovery probe
+    fn admit(&self, name: &str) -> Result<bool> {
+        let mut inner = self.inner.lock().expect("circuit breaker lock poisoned");
+        match inner.state {
+            CircuitState::Closed => Ok(false),
+            CircuitState::Open if inner.probe_after.is_some_and(|at| Instant::now() >= at) => {
+                info!("Circuit breaker probing backend recovery with {}", name);
+                inner.state = CircuitState::HalfOpen;
+                Ok(true)
+            }
+            CircuitState::Open | CircuitState::HalfOpen => {
+                inner.rejected_calls += 1;
+                Err(LutsError::Backend(format!(
+                    "circuit breaker open after {} consecutive failures, {} rejected (last error: {})",
+                    inner.consecutive_failures,
+                    name,
+                    inner.last_error.as_deref().unwrap_or("unknown")
+                )))
+            }
+        }
+    }
+
+    /// Whether an error is a timeout or dropped connection that may succeed if retried
+    fn is_transient(&self, error: &LutsError) -> bool {
+        match error {
+            LutsError::Storage(message) => matches!(
+                self.retry.retryable(message),
+                Some(RetryableError::Timeout | RetryableError::Connection)
+            ),
+            LutsError::Timeout(_) => true,
+            _ => false,
+        }
+    }
+
+    fn record_success(&self) {
+        let mut inner = self.inner.lock().expect("circuit breaker lock poisoned");
+        if inner.state != CircuitState::Closed {
+            info!("Circuit breaker closed, backend recovered");
+        }
+        inner.state = CircuitState::Closed;
+        inner.consecutive_failures = 0;
+        inner.probe_after = None;
+    }
+
+    fn record_failure(&self, error: &LutsError) {
+        let mut inner = self.inner.lock().expect("circuit breaker lock poisoned");
+        inner.consecutive_failures += 1;
+        inner.last_error = Some(error.to_string());
+
+        let reopen = inner.state == CircuitState::HalfOpen;
+        if reopen || inner.consecutive_failures >= self.breaker.failure_threshold {
+            if inner.state == CircuitState::Closed {
+                warn!(
+                    "Circuit breaker opened after {} consecutive failures: {}",
+                    inner.consecutive_failures, error
+                );
+                inner.opened_at = Some(Utc::now());
+            }
+            inner.state = CircuitState::Open;
+            inner.probe_after = Some(Instant::now() + self.breaker.probe_interval);
+        }
+    }
+}
+
+/// Reopens the breaker if a probe is dropped, e.g. cancelled or timed out,
+/// before it recorded a success or failure
+struct ProbeGuard<'a> {
+    inner: &'a Mutex<BreakerInner>,
+}
+
+impl Drop for ProbeGuard<'_> {
+    fn drop(&mut self) {
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+        // Only the probe runs while half-open, so this one never settled
+        if inner.state == CircuitState::HalfOpen {
+            info!("Circuit breaker probe abandoned, the next call probes again");
+            inner.state = CircuitState::Open;
+            inner.probe_after = Some(Instant::now());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn fast_resilience() -> Resilience {
+        Resilience::new(
+            RetryConfig {
+                max_retries: 3,
+                base_delay_ms: 1,
+                max_delay_ms: 5,
+                ..Default::default()
+            },
+            CircuitBreakerConfig {
+                failure_threshold: 2,
+                probe_interval: Duration::from_millis(50),
+            },
+        )
+    }
+
+    #[tokio::test]
+    async fn test_transient_failures_succeed_on_retry() {
+        let resilience = fast_resilience();
+        let attempts = AtomicU32::new(0);
+
+        let result = resilience
+            .run("store", || async {
+                if attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+                    Err(LutsError::Storage("connection reset by peer".to_string()))
+                } else {
+                    Ok(42)
+                }
+            })
+            .await;
+
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        let status = resilience.status();
+        assert_eq!(status.state, CircuitState::Closed);
+        assert_eq!(status.total_retries, 2);
+        assert_eq!(status.consecutive_failures, 0);
+
+        // Non-transient errors are returned at once
+        let attempts = AtomicU32::new(0);
+        let result: Result<()> = resilience
+            .run("store", || async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(LutsError::Memory("block already exists".to_string()))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_parse_errors_do_not_open_breaker() {
+        let resilience = fast_resilience();
+        let attempts = AtomicU32::new(0);
+
+        for _ in 0..3 {
+            let result: Result<()> = resilience
+                .run("query", || async {
+                    attempts.fetch_add(1, Ordering::SeqCst);
+                    Err(LutsError::Storage(
+                        "Parse error: unexpected token at line 500".to_string(),
+                    ))
+                })
+                .await;
+            assert!(matches!(result, Err(LutsError::Storage(_))));
+        }
+
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        let status = resilience.status();
+        assert_eq!(status.state, CircuitState::Closed);
+        assert_eq!(status.consecutive_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_persistent_failures_open_breaker_until_probe_succeeds() {
+        let resilience = fast_resilience();
+        let attempts = AtomicU32::new(0);
+        let failing = || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err::<(), _>(LutsError::Storage("connection refused".to_string()))
+        };
+
+        for _ in 0..2 {
+            assert!(matches!(
+                resilience.run("query", failing).await,
+                Err(LutsError::Storage(_))
+            ));
+        }
+        assert_eq!(attempts.load(Ordering::SeqCst), 8);
+        assert_eq!(resilience.status().state, CircuitState::Open);
+
+        // Open: fail fast without touching the backend
+        assert!(matches!(
+            resilience.run("query", failing).await,
+            Err(LutsError::Backend(_))
+        ));
+        assert_eq!(attempts.load(Ordering::SeqCst), 8);
+        assert_eq!(resilience.status().rejected_calls, 1);
+
+        // A failed probe reopens the breaker after a single attempt
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(resilience.run("query", failing).await.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 9);
+        assert_eq!(resilience.status().state, CircuitState::Open);
+
+        // A successful probe closes it
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert_eq!(
+            resilience.run("query", || async { Ok(7) }).await.unwrap(),
+            7
+        );
+        let status = resilience.status();
+        assert_eq!(status.state, CircuitState::Closed);
+        assert!(status.opened_at.is_some());
+        assert_eq!(status.consecutive_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_probe_lets_the_next_call_probe() {
+        let resilience = fast_resilience();
+        for _ in 0..2 {
+            let result: Result<()> = resilience
+                .run("query", || async {
+                    Err(LutsError::Storage("connection refused".to_string()))
+                })
+                .await;
+            assert!(result.is_err());
+        }
+        assert_eq!(resilience.status().state, CircuitState::Open);
+
+        // The probe never finishes and is dropped by the timeout
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        let stalled = resilience.run("query", std::future::pending::<Result<()>>);
+        assert!(
+            tokio::time::timeout(Duration::from_millis(10), stalled)
+                .await
+                .is_err()
+        );
+        assert_eq!(resilience.status().state, CircuitState::Open);
+
+        assert_eq!(
+            resilience.run("query", || async { Ok(7) }).await.unwrap(),
+            7
+        );
+        assert_eq!(resilience.status().state, CircuitState::Closed);
+    }
+}
@@ -5,6 +5,7 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::time::{SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
 
 /// A unique identifier for a memory block
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
@@ -26,6 +27,31 @@ impl BlockId {
         BlockId(format!("block_{:x}_{:x}", timestamp, random))
     }
 
+    /// Derive a stable block ID from a namespace and key
+    ///
+    /// The same inputs always give the same ID (a UUIDv5 under the namespace),
+    /// so importing the same data twice keeps ids and references intact.
+    pub fn deterministic(namespace: &str, key: &str) -> Self {
+        let namespace = Uuid::new_v5(&Uuid::NAMESPACE_OID, namespace.as_bytes());
+        BlockId(format!("block_{}", Uuid::new_v5(&namespace, key.as_bytes()).simple()))
+    }
+
+    /// Derive a stable block ID from a namespace and the block's content
+    ///
+    /// Blocks with the same content under the same namespace get the same
+    /// ID, so storing them again replaces rather than duplicates. The content
+    /// kind is part of the hash: text `"1"` and JSON `1` differ.
+    pub fn from_content_hash(namespace: &str, content: &MemoryContent) -> Self {
+        let key = match content {
+            MemoryContent::Text(text) => format!("text:{}", text),
+            MemoryContent::Json(json) => format!("json:{}", json),
+            MemoryContent::Binary { mime_type, data } => {
+                format!("binary:{}:{}", mime_type, data)
+            }
+        };
+        Self::deterministic(namespace, &key)
+    }
+
     /// Get the inner string value
     pub fn as_str(&self) -> &str {
         &self.0
@@ -201,6 +227,14 @@ impl Relevance {
         Relevance(clamped)
     }
 
+    /// Wrap a raw similarity score without clamping it
+    ///
+    /// Vector search reports the metric's own score, which can fall outside
+    /// 0.0 to 1.0 (dot products, negative cosine similarity).
+    pub fn raw(score: f32) -> Self {
+        Relevance(score)
+    }
+
     /// Get the relevance score
     pub fn score(&self) -> f32 {
         self.0
@@ -245,6 +279,30 @@ mod tests {
         assert!(id2.as_str().starts_with("block_"));
     }
 
+    #[test]
+    fn test_deterministic_block_id() {
+        let id = BlockId::deterministic("import", "fact-1");
+        assert_eq!(id, BlockId::deterministic("import", "fact-1"));
+        assert!(id.as_str().starts_with("block_"));
+
+        assert_ne!(id, BlockId::deterministic("import", "fact-2"));
+        assert_ne!(id, BlockId::deterministic("other", "fact-1"));
+    }
+
+    #[test]
+    fn test_content_hash_block_id() {
+        let sky = MemoryContent::Text("the sky is blue".to_string());
+        let id = BlockId::from_content_hash("user123", &sky);
+        assert_eq!(id, BlockId::from_content_hash("user123", &sky.clone()));
+        assert!(id.as_str().starts_with("block_"));
+
+        assert_ne!(id, BlockId::from_content_hash("user456", &sky));
+        assert_ne!(
+            BlockId::from_content_hash("user123", &MemoryContent::Text("1".to_string())),
+            BlockId::from_content_hash("user123", &MemoryContent::Json(serde_json::json!(1)))
+        );
+    }
+
     #[test]
     fn test_time_range() {
         let range = TimeRange::last_days(1);
@@ -3,9 +3,9 @@
 //! This module provides high-level utility functions for common memory block operations.
 
 use crate::{
-    storage::{MemoryManager, MemoryQuery},
+    storage::{MemoryManager, MemoryQuery, MemoryQueryPage},
     block::MemoryBlock,
-    types::BlockId,
+    types::{BlockId, BlockType},
 };
 use anyhow::Result;
 use std::sync::Arc;
@@ -32,10 +32,9 @@ impl BlockUtils {
         Ok(self.memory_manager.get(id).await?)
     }
 
-    /// Delete a memory block by its ID
-    pub async fn delete_block(&self, id: &BlockId) -> Result<()> {
-        self.memory_manager.delete(id).await?;
-        Ok(())
+    /// Delete a memory block by its ID, returning whether it existed
+    pub async fn delete_block(&self, id: &BlockId) -> Result<bool> {
+        Ok(self.memory_manager.delete(id).await?)
     }
 
     /// Update a memory block by deleting the old one and storing the new one
@@ -49,6 +48,11 @@ impl BlockUtils {
         Ok(self.memory_manager.search(query).await?)
     }
 
+    /// Fetch one page of blocks matching a MemoryQuery, continuing from its cursor
+    pub async fn search_blocks_page(&self, query: &MemoryQuery) -> Result<MemoryQueryPage> {
+        Ok(self.memory_manager.search_paged(query).await?)
+    }
+
     /// List all memory blocks for a user
     pub async fn list_blocks(&self, user_id: &str) -> Result<Vec<MemoryBlock>> {
         Ok(self.memory_manager.list(user_id).await?)
@@ -63,6 +67,55 @@ impl BlockUtils {
     pub async fn get_stats(&self, user_id: &str) -> Result<crate::storage::MemoryStats> {
         Ok(self.memory_manager.get_stats(user_id).await?)
     }
+
+    /// Add `tag` to every block matching a MemoryQuery, returning how many changed
+    ///
+    /// Blocks that already have the tag are left alone. Like every batch
+    /// helper here, this pages through all matching blocks, so the query's
+    /// limit only sets the page size.
+    pub async fn add_tag_to_matching(&self, query: &MemoryQuery, tag: &str) -> Result<u64> {
+        self.update_matching(query, |block| block.add_tag(tag))
+            .await
+    }
+
+    /// Remove `tag` from every block matching a MemoryQuery, returning how many changed
+    pub async fn remove_tag_from_matching(&self, query: &MemoryQuery, tag: &str) -> Result<u64> {
+        self.update_matching(query, |block| block.remove_tag(tag)).await
+    }
+
+    /// Change the type of every block matching a MemoryQuery, returning how many changed
+    pub async fn reclassify(&self, query: &MemoryQuery, new_type: BlockType) -> Result<u64> {
+        self.update_matching(query, |block| block.set_block_type(new_type)).await
+    }
+
+    /// Apply `change` to every matching block and write the changed ones in one batch
+    ///
+    /// All pages are read before anything is written, and the batch is
+    /// atomic when the store supports it.
+    async fn update_matching(
+        &self,
+        query: &MemoryQuery,
+        mut change: impl FnMut(&mut MemoryBlock),
+    ) -> Result<u64> {
+        let mut query = query.clone();
+        let mut changed = Vec::new();
+        loop {
+            let page = self.memory_manager.search_paged(&query).await?;
+            changed.extend(page.blocks.into_iter().filter_map(|mut block| {
+                let before = (block.tags().to_vec(), block.block_type());
+                change(&mut block);
+                (before != (block.tags().to_vec(), block.block_type())).then_some(block)
+            }));
+            match page.next_cursor {
+                Some(cursor) => query.cursor = Some(cursor),
+                None => break,
+            }
+        }
+        if changed.is_empty() {
+            return Ok(0);
+        }
+        Ok(self.memory_manager.update_many(changed).await?)
+    }
 }
 
 #[cfg(test)]
@@ -80,6 +133,7 @@ mod tests {
         let config = SurrealConfig::Memory {
             namespace: "test".to_string(),
             database: "memory".to_string(),
+            encryption_key: None,
         };
         
         let store = SurrealMemoryStore::new(config).await.unwrap();
@@ -105,4 +159,95 @@ mod tests {
         let blocks = utils.list_blocks("test_user").await.unwrap();
         assert_eq!(blocks.len(), 1);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_tagging_by_content_touches_only_matching_blocks() {
+        let config = SurrealConfig::Memory {
+            namespace: "test".to_string(),
+            database: "bulk_tags".to_string(),
+            encryption_key: None,
+        };
+        let store = SurrealMemoryStore::new(config).await.unwrap();
+        let utils = BlockUtils::new(Arc::new(MemoryManager::new(store)));
+
+        let mut ids = Vec::new();
+        for text in ["Rust is fast", "Python is readable", "Rust has no GC"] {
+            let block = MemoryBlockBuilder::new()
+                .with_user_id("test_user")
+                .with_type(BlockType::Fact)
+                .with_content(MemoryContent::Text(text.to_string()))
+                .build()
+                .unwrap();
+            ids.push(utils.create_block(block).await.unwrap());
+        }
+        let rust = MemoryQuery {
+            user_id: Some("test_user".to_string()),
+            content_contains: Some("Rust".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(utils.add_tag_to_matching(&rust, "rust").await.unwrap(), 2);
+        // Blocks that already have the tag aren't counted again
+        assert_eq!(utils.add_tag_to_matching(&rust, "rust").await.unwrap(), 0);
+        let mut tagged = Vec::new();
+        for id in &ids {
+            let block = utils.get_block(id).await.unwrap().unwrap();
+            tagged.push(block.tags().contains(&"rust".to_string()));
+        }
+        assert_eq!(tagged, [true, false, true]);
+
+        assert_eq!(
+            utils
+                .reclassify(&rust, BlockType::Preference)
+                .await
+                .unwrap(),
+            2
+        );
+        let python = utils.get_block(&ids[1]).await.unwrap().unwrap();
+        assert_eq!(python.block_type(), BlockType::Fact);
+        let rust_block = utils.get_block(&ids[2]).await.unwrap().unwrap();
+        assert_eq!(rust_block.block_type(), BlockType::Preference);
+        assert_eq!(rust_block.content().as_text(), Some("Rust has no GC"));
+
+        assert_eq!(
+            utils.remove_tag_from_matching(&rust, "rust").await.unwrap(),
+            2
+        );
+        let block = utils.get_block(&ids[0]).await.unwrap().unwrap();
+        assert!(block.tags().is_empty());
+        assert_eq!(utils.list_blocks("test_user").await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_tagging_reaches_matches_past_the_query_limit() {
+        let config = SurrealConfig::Memory {
+            namespace: "test".to_string(),
+            database: "bulk_tags_paged".to_string(),
+            encryption_key: None,
+        };
+        let store = SurrealMemoryStore::new(config).await.unwrap();
+        let utils = BlockUtils::new(Arc::new(MemoryManager::new(store)));
+
+        for i in 0..130 {
+            let block = MemoryBlockBuilder::new()
+                .with_user_id("test_user")
+                .with_type(BlockType::Fact)
+                .with_content(MemoryContent::Text(format!("Note {}", i)))
+                .build()
+                .unwrap();
+            utils.create_block(block).await.unwrap();
+        }
+        // The default limit of 100 only sets the page size
+        let everything = MemoryQuery {
+            user_id: Some("test_user".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(everything.limit, Some(100));
+
+        assert_eq!(utils.add_tag_to_matching(&everything, "note").await.unwrap(), 130);
+        assert_eq!(utils.reclassify(&everything, BlockType::Task).await.unwrap(), 130);
+        // Nothing is left unchanged on a second pass
+        assert_eq!(utils.add_tag_to_matching(&everything, "note").await.unwrap(), 0);
+        assert_eq!(utils.reclassify(&everything, BlockType::Task).await.unwrap(), 0);
+    }
+}
@@ -1,6 +1,6 @@
 //! Base tool functionality
 //!
-//! Re-exports the AiTool trait from luts-llm for use by tools.
+//! Re-exports the AiTool trait and its safety levels from luts-llm for use by tools.
 
 // Re-export the AiTool trait from luts-llm
-pub use luts_llm::tools::AiTool;
\ No newline at end of file
+pub use luts_llm::tools::{AiTool, ToolSafety};
\ No newline at end of file
@@ -1,14 +1,148 @@
 //! Calculator tool for AI assistants
 //!
-//! This module provides a simple calculator tool that can evaluate mathematical expressions.
+//! This module provides a simple calculator tool that can evaluate mathematical expressions,
+//! convert between units and refer back to its previous result as `ans`.
 
-use crate::base::AiTool;
+use crate::base::{AiTool, ToolSafety};
 use anyhow::{Error, anyhow};
 use async_trait::async_trait;
 use serde_json::Value;
+use std::fmt;
+use std::sync::Mutex;
+
+/// Results kept for `ans`; only the latest is addressable, the rest are for display
+const MAX_HISTORY: usize = 20;
+
+/// Calculation failures callers may want to tell apart
+#[derive(Debug, Clone, PartialEq)]
+pub enum CalcError {
+    /// Division by zero
+    DivisionByZero,
+    /// A result too large to represent
+    Overflow,
+    /// `ans` was used before anything was calculated
+    NoPreviousResult,
+    /// A unit the calculator doesn't know
+    UnknownUnit(String),
+    /// Units that measure different things, e.g. miles and kilograms
+    IncompatibleUnits(String, String),
+}
+
+impl fmt::Display for CalcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CalcError::DivisionByZero => write!(f, "Division by zero"),
+            CalcError::Overflow => write!(f, "Result is too large to represent"),
+            CalcError::NoPreviousResult => write!(f, "`ans` used before any result was calculated"),
+            CalcError::UnknownUnit(unit) => write!(f, "Unknown unit: {}", unit),
+            CalcError::IncompatibleUnits(from, to) => {
+                write!(f, "Cannot convert {} to {}: they measure different things", from, to)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CalcError {}
+
+/// What a unit measures
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Dimension {
+    Length,
+    Mass,
+    Temperature,
+    Time,
+}
+
+/// A unit as `base = value * scale + offset`, in metres, kilograms, kelvin or seconds
+struct Unit {
+    dimension: Dimension,
+    scale: f64,
+    offset: f64,
+}
+
+impl Unit {
+    fn parse(name: &str) -> Result<Self, CalcError> {
+        let (dimension, scale, offset) = match name.trim().to_lowercase().as_str() {
+            "mm" | "millimeter" | "millimeters" | "millimetre" | "millimetres" => (Dimension::Length, 0.001, 0.0),
+            "cm" | "centimeter" | "centimeters" | "centimetre" | "centimetres" => (Dimension::Length, 0.01, 0.0),
+            "m" | "meter" | "meters" | "metre" | "metres" => (Dimension::Length, 1.0, 0.0),
+            "km" | "kilometer" | "kilometers" | "kilometre" | "kilometres" => (Dimension::Length, 1000.0, 0.0),
+            "in" | "inch" | "inches" => (Dimension::Length, 0.0254, 0.0),
+            "ft" | "foot" | "feet" => (Dimension::Length, 0.3048, 0.0),
+            "yd" | "yard" | "yards" => (Dimension::Length, 0.9144, 0.0),
+            "mi" | "mile" | "miles" => (Dimension::Length, 1609.344, 0.0),
+            "mg" | "milligram" | "milligrams" => (Dimension::Mass, 1e-6, 0.0),
+            "g" | "gram" | "grams" => (Dimension::Mass, 0.001, 0.0),
+            "kg" | "kilogram" | "kilograms" => (Dimension::Mass, 1.0, 0.0),
+            "t" | "tonne" | "tonnes" => (Dimension::Mass, 1000.0, 0.0),
+            "oz" | "ounce" | "ounces" => (Dimension::Mass, 0.028349523125, 0.0),
+            "lb" | "lbs" | "pound" | "pounds" => (Dimension::Mass, 0.45359237, 0.0),
+            "k" | "kelvin" => (Dimension::Temperature, 1.0, 0.0),
+            "c" | "celsius" => (Dimension::Temperature, 1.0, 273.15),
+            "f" | "fahrenheit" => (Dimension::Temperature, 5.0 / 9.0, 459.67 * 5.0 / 9.0),
+            "ms" | "millisecond" | "milliseconds" => (Dimension::Time, 0.001, 0.0),
+            "s" | "sec" | "second" | "seconds" => (Dimension::Time, 1.0, 0.0),
+            "min" | "minute" | "minutes" => (Dimension::Time, 60.0, 0.0),
+            "h" | "hr" | "hour" | "hours" => (Dimension::Time, 3600.0, 0.0),
+            "d" | "day" | "days" => (Dimension::Time, 86_400.0, 0.0),
+            "wk" | "week" | "weeks" => (Dimension::Time, 604_800.0, 0.0),
+            _ => return Err(CalcError::UnknownUnit(name.to_string())),
+        };
+        Ok(Self {
+            dimension,
+            scale,
+            offset,
+        })
+    }
+}
+
+/// Convert `value` from one unit to another of the same dimension
+fn convert(value: f64, from: &str, to: &str) -> Result<f64, CalcError> {
+    let (from_unit, to_unit) = (Unit::parse(from)?, Unit::parse(to)?);
+    if from_unit.dimension != to_unit.dimension {
+        return Err(CalcError::IncompatibleUnits(from.to_string(), to.to_string()));
+    }
+    let base = value * from_unit.scale + from_unit.offset;
+    finite((base - to_unit.offset) / to_unit.scale)
+}
+
+/// `value`, or [`CalcError::Overflow`] if it is infinite or NaN
+fn finite(value: f64) -> Result<f64, CalcError> {
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(CalcError::Overflow)
+    }
+}
 
 /// A simple calculator tool for evaluating mathematical expressions
-pub struct MathTool;
+///
+/// Each instance keeps its own result history, so `ans` refers to the last
+/// result calculated by the same agent session.
+#[derive(Default)]
+pub struct MathTool {
+    history: Mutex<Vec<f64>>,
+}
+
+impl MathTool {
+    /// Results calculated so far, oldest first
+    pub fn history(&self) -> Vec<f64> {
+        self.history.lock().unwrap().clone()
+    }
+
+    /// Forget previous results, so `ans` is unset again
+    pub fn clear_history(&self) {
+        self.history.lock().unwrap().clear();
+    }
+
+    fn record(&self, value: f64) {
+        let mut history = self.history.lock().unwrap();
+        history.push(value);
+        if history.len() > MAX_HISTORY {
+            history.remove(0);
+        }
+    }
+}
 
 #[async_trait]
 impl AiTool for MathTool {
@@ -17,7 +151,13 @@ impl AiTool for MathTool {
     }
 
     fn description(&self) -> &str {
-        "Evaluates mathematical expressions"
+        "Evaluates mathematical expressions with +, -, * and /. Use `ans` for the previous result. \
+         Give `from_unit` and `to_unit` to convert the result between units of length (mm, cm, m, km, in, ft, yd, mi), \
+         mass (mg, g, kg, t, oz, lb), temperature (C, F, K) or time (ms, s, min, h, day, week)."
+    }
+
+    fn safety(&self) -> ToolSafety {
+        ToolSafety::ReadOnly
     }
 
     fn schema(&self) -> Value {
@@ -26,7 +166,15 @@ impl AiTool for MathTool {
             "properties": {
                 "expression": {
                     "type": "string",
-                    "description": "The mathematical expression to evaluate"
+                    "description": "The mathematical expression to evaluate, e.g. \"5 * 3\" or \"ans / 2\""
+                },
+                "from_unit": {
+                    "type": "string",
+                    "description": "Unit the expression's value is in, e.g. \"miles\" (requires to_unit)"
+                },
+                "to_unit": {
+                    "type": "string",
+                    "description": "Unit to convert the value to, e.g. \"km\" (requires from_unit)"
                 }
             },
             "required": ["expression"]
@@ -40,25 +188,42 @@ impl AiTool for MathTool {
             .as_str()
             .ok_or_else(|| anyhow!("Missing 'expression' parameter"))?;
 
+        // Substitute the previous result for `ans` before parsing
+        let expression = if expression.contains("ans") {
+            let previous = self
+                .history
+                .lock()
+                .unwrap()
+                .last()
+                .copied()
+                .ok_or(CalcError::NoPreviousResult)?;
+            expression.replace("ans", &previous.to_string())
+        } else {
+            expression.to_string()
+        };
+
         // Use a simple evaluation approach for basic arithmetic
         // This is a very simplistic implementation that only handles basic operations
-        let result = evaluate_expression(expression)?;
+        let result = evaluate_expression(&expression)?;
 
-        Ok(Value::Number(
-            serde_json::Number::from_f64(result).expect("f64 is valid serde_json::Number"),
-        ))
-    }
-
-    fn validate_params(&self, params: &Value) -> Result<(), Error> {
-        if !params.is_object() {
-            return Err(anyhow!("Parameters must be an object"));
-        }
-
-        if !params.get("expression").is_some_and(|v| v.is_string()) {
-            return Err(anyhow!("Missing or invalid 'expression' parameter"));
-        }
-
-        Ok(())
+        let units = (
+            params["from_unit"].as_str(),
+            params["to_unit"].as_str(),
+        );
+        let (result, unit) = match units {
+            (None, None) => (result, None),
+            (Some(from), Some(to)) => (convert(result, from, to)?, Some(to)),
+            _ => return Err(anyhow!("Unit conversion needs both 'from_unit' and 'to_unit'")),
+        };
+        self.record(result);
+
+        let number = Value::Number(
+            serde_json::Number::from_f64(result).expect("finite f64 is valid serde_json::Number"),
+        );
+        Ok(match unit {
+            Some(unit) => serde_json::json!({ "value": number, "unit": unit }),
+            None => number,
+        })
     }
 }
 
@@ -90,7 +255,7 @@ fn evaluate_expression(expr: &str) -> Result<f64, Error> {
 
         // If we've reached the end or the next character is + or -, add the current term to the result
         if i >= expr.len() || expr.chars().nth(i) == Some('+') || expr.chars().nth(i) == Some('-') {
-            result += current_term;
+            result = finite(result + current_term)?;
             current_term = 0.0;
         }
 
@@ -126,12 +291,13 @@ fn evaluate_term(expr: &str) -> Result<(usize, f64), Error> {
                 '*' => current_factor *= factor,
                 '/' => {
                     if factor == 0.0 {
-                        return Err(anyhow!("Division by zero"));
+                        return Err(CalcError::DivisionByZero.into());
                     }
                     current_factor /= factor;
                 }
                 _ => return Err(anyhow!("Invalid operator: {}", current_op)),
             }
+            current_factor = finite(current_factor)?;
         }
 
         // If we've reached the end or the next character is not * or /, return the result
@@ -180,7 +346,7 @@ fn evaluate_factor(expr: &str) -> Result<(usize, f64), Error> {
         .parse::<f64>()
         .map_err(|_| anyhow!("Invalid number: {}", num_str))?;
 
-    Ok((i, num))
+    Ok((i, finite(num)?))
 }
 
 #[cfg(test)]
@@ -207,7 +373,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_math_tool() {
-        let tool = MathTool;
+        let tool = MathTool::default();
 
         // Test basic addition
         let params = json!({"expression": "2 + 3"});
@@ -230,7 +396,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_basic_arithmetic() {
-        let tool = MathTool;
+        let tool = MathTool::default();
         
         // Addition
         let result = tool.execute(json!({"expression": "2 + 3"})).await.unwrap();
@@ -251,7 +417,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_complex_expressions() {
-        let tool = MathTool;
+        let tool = MathTool::default();
         
         // Order of operations
         let result = tool.execute(json!({"expression": "2 + 3 * 4"})).await.unwrap();
@@ -264,7 +430,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_invalid_expressions() {
-        let tool = MathTool;
+        let tool = MathTool::default();
         
         // Division by zero
         let result = tool.execute(json!({"expression": "5 / 0"})).await;
@@ -281,7 +447,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_parameter_validation() {
-        let tool = MathTool;
+        let tool = MathTool::default();
         
         // Missing expression parameter
         let result = tool.execute(json!({})).await;
@@ -301,7 +467,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_edge_cases() {
-        let tool = MathTool;
+        let tool = MathTool::default();
         
         // Negative numbers
         let result = tool.execute(json!({"expression": "-5 + 3"})).await.unwrap();
@@ -314,7 +480,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_whitespace_handling() {
-        let tool = MathTool;
+        let tool = MathTool::default();
         
         // Extra whitespace
         let result = tool.execute(json!({"expression": "  2   +   3  "})).await.unwrap();
@@ -327,7 +493,7 @@ mod tests {
 
     #[test]
     fn test_tool_metadata() {
-        let tool = MathTool;
+        let tool = MathTool::default();
         
         assert_eq!(tool.name(), "calculator");
         assert!(!tool.description().is_empty());
@@ -337,4 +503,70 @@ mod tests {
         assert!(schema["properties"]["expression"].is_object());
         assert!(schema["required"].as_array().unwrap().contains(&json!("expression")));
     }
+
+    #[tokio::test]
+    async fn test_invalid_params_report_schema_violations() {
+        let tool = MathTool::default();
+
+        let missing = tool.run(json!({})).await;
+        assert!(!missing.is_success());
+        assert!(missing.display_text().contains("missing required field `expression`"));
+
+        let mistyped = tool.run(json!({"expression": 42})).await;
+        assert!(mistyped.display_text().contains("`expression` should be string, got integer"));
+    }
+
+    #[tokio::test]
+    async fn test_unit_conversion() {
+        let tool = MathTool::default();
+
+        let result = tool
+            .execute(json!({"expression": "5", "from_unit": "miles", "to_unit": "km"}))
+            .await
+            .unwrap();
+        assert_eq!(result["unit"], "km");
+        assert!((result["value"].as_f64().unwrap() - 8.04672).abs() < 1e-9);
+
+        let result = tool
+            .execute(json!({"expression": "100", "from_unit": "C", "to_unit": "F"}))
+            .await
+            .unwrap();
+        assert!((result["value"].as_f64().unwrap() - 212.0).abs() < 1e-9);
+
+        let error = tool
+            .execute(json!({"expression": "5", "from_unit": "miles", "to_unit": "kg"}))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<CalcError>(),
+            Some(&CalcError::IncompatibleUnits("miles".to_string(), "kg".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ans_refers_to_previous_result() {
+        let tool = MathTool::default();
+
+        let error = tool.execute(json!({"expression": "ans + 1"})).await.unwrap_err();
+        assert_eq!(error.downcast_ref::<CalcError>(), Some(&CalcError::NoPreviousResult));
+
+        tool.execute(json!({"expression": "6 * 7"})).await.unwrap();
+        let result = tool.execute(json!({"expression": "ans / 2"})).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), 21.0);
+        let result = tool.execute(json!({"expression": "10 - ans"})).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), -11.0);
+        let result = tool.execute(json!({"expression": "2 * ans"})).await.unwrap();
+        assert_eq!(result.as_f64().unwrap(), -22.0);
+        assert_eq!(tool.history(), vec![42.0, 21.0, -11.0, -22.0]);
+    }
+
+    #[test]
+    fn test_overflow_and_division_by_zero_are_typed() {
+        let huge = format!("1{} * 1{}", "0".repeat(300), "0".repeat(300));
+        let error = evaluate_expression(&huge).unwrap_err();
+        assert_eq!(error.downcast_ref::<CalcError>(), Some(&CalcError::Overflow));
+
+        let error = evaluate_expression("1 / 0").unwrap_err();
+        assert_eq!(error.downcast_ref::<CalcError>(), Some(&CalcError::DivisionByZero));
+    }
 }
@@ -0,0 +1,348 @@
+use anyhow::{Error, anyhow};
+use serde_json::{Value, json};
+use std::path::{Component, Path, PathBuf};
+use tracing::debug;
+
+use crate::base::{AiTool, ToolSafety};
+
+/// Files larger than this are cut off when read
+const MAX_READ_BYTES: usize = 256 * 1024;
+
+/// Tool that reads, lists and optionally writes files under allowed roots.
+///
+/// Every path is relative to one of the configured roots. Absolute paths,
+/// `..` that climbs out of a root and symlinks that lead outside one are all
+/// rejected, so the model only ever sees what the roots contain.
+pub struct FileSystemTool {
+    roots: Vec<PathBuf>,
+    allow_write: bool,
+}
+
+impl FileSystemTool {
+    /// Read-only access to `roots`, which must be existing directories
+    pub fn new<P: Into<PathBuf>>(roots: impl IntoIterator<Item = P>) -> Result<Self, Error> {
+        let roots = roots
+            .into_iter()
+            .map(|root| {
+                let root = root.into();
+                let canonical = root
+                    .canonicalize()
+                    .map_err(|e| anyhow!("Invalid root {}: {}", root.display(), e))?;
+                if !canonical.is_dir() {
+                    return Err(anyhow!("Root {} is not a directory", root.display()));
+                }
+                Ok(canonical)
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+        if roots.is_empty() {
+            return Err(anyhow!("FileSystemTool needs at least one root directory"));
+        }
+
+        Ok(Self {
+            roots,
+            allow_write: false,
+        })
+    }
+
+    /// Also allow `write_file`
+    pub fn with_write_access(mut self) -> Self {
+        self.allow_write = true;
+        self
+    }
+
+    /// The allowed roots, canonicalized
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    fn operations(&self) -> Vec<&'static str> {
+        let mut operations = vec!["read_file", "list_dir"];
+        if self.allow_write {
+            operations.push("write_file");
+        }
+        operations
+    }
+
+    /// Resolve `relative` inside root `root_idx`, refusing anything outside it
+    ///
+    /// The target itself need not exist when `must_exist` is false, but its
+    /// parent directory must.
+    fn resolve(&self, root_idx: usize, relative: &str, must_exist: bool) -> Result<PathBuf, Error> {
+        let root = self
+            .roots
+            .get(root_idx)
+            .ok_or_else(|| anyhow!("No root {}; there are {} roots", root_idx, self.roots.len()))?;
+
+        // Lexical check first, so escapes are refused before touching the disk
+        let mut normalized = PathBuf::new();
+        for component in Path::new(relative).components() {
+            match component {
+                Component::Normal(part) => normalized.push(part),
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    if !normalized.pop() {
+                        return Err(anyhow!("Path {} escapes its root", relative));
+                    }
+                }
+                Component::RootDir | Component::Prefix(_) => {
+                    return Err(anyhow!(
+                        "Absolute path {} is not allowed; give a path relative to a root",
+                        relative
+                    ));
+                }
+            }
+        }
+        let joined = root.join(&normalized);
+
+        // Canonicalizing follows symlinks, which may point anywhere; a
+        // dangling one fails here rather than being written through
+        let resolved = if must_exist || joined.symlink_metadata().is_ok() {
+            joined
+                .canonicalize()
+                .map_err(|e| anyhow!("Cannot access {}: {}", relative, e))?
+        } else {
+            let name = joined
+                .file_name()
+                .ok_or_else(|| anyhow!("Path {} does not name a file", relative))?;
+            let parent = joined
+                .parent()
+                .unwrap_or(root)
+                .canonicalize()
+                .map_err(|e| anyhow!("Cannot access the directory of {}: {}", relative, e))?;
+            parent.join(name)
+        };
+        if !resolved.starts_with(root) {
+            return Err(anyhow!("Path {} leads outside its root", relative));
+        }
+        Ok(resolved)
+    }
+
+    async fn read_file(&self, path: &Path, relative: &str) -> Result<Value, Error> {
+        let bytes = tokio::fs::read(path)
+            .await
+            .map_err(|e| anyhow!("Cannot read {}: {}", relative, e))?;
+        let truncated = bytes.len() > MAX_READ_BYTES;
+        let kept = &bytes[..bytes.len().min(MAX_READ_BYTES)];
+        let content = String::from_utf8_lossy(kept).into_owned();
+
+        Ok(json!({
+            "path": relative,
+            "content": content,
+            "size": bytes.len(),
+            "truncated": truncated,
+        }))
+    }
+
+    async fn list_dir(&self, path: &Path, relative: &str) -> Result<Value, Error> {
+        let mut reader = tokio::fs::read_dir(path)
+            .await
+            .map_err(|e| anyhow!("Cannot list {}: {}", relative, e))?;
+
+        let mut entries = Vec::new();
+        while let Some(entry) = reader.next_entry().await? {
+            let file_type = entry.file_type().await?;
+            let kind = if file_type.is_symlink() {
+                "symlink"
+            } else if file_type.is_dir() {
+                "dir"
+            } else {
+                "file"
+            };
+            let size = entry.metadata().await.map(|metadata| metadata.len()).ok();
+            entries.push(json!({
+                "name": entry.file_name().to_string_lossy(),
+                "kind": kind,
+                "size": size,
+            }));
+        }
+        entries.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+
+        Ok(json!({
+            "path": relative,
+            "entries": entries,
+        }))
+    }
+
+    async fn write_file(&self, path: &Path, relative: &str, content: &str) -> Result<Value, Error> {
+        tokio::fs::write(path, content)
+            .await
+            .map_err(|e| anyhow!("Cannot write {}: {}", relative, e))?;
+
+        Ok(json!({
+            "path": relative,
+            "bytes_written": content.len(),
+        }))
+    }
+}
+
+#[async_trait::async_trait]
+impl AiTool for FileSystemTool {
+    fn name(&self) -> &str {
+        "filesystem"
+    }
+
+    fn description(&self) -> &str {
+        r#"Reads and lists local project files.
+Parameters:
+- `operation`: "read_file", "list_dir" or, when enabled, "write_file".
+- `path`: Path relative to the root, e.g. "src/main.rs" or "." for the root itself.
+- `root`: Index of the root directory to use (default 0).
+- `content`: Text to write, for "write_file" only.
+
+Absolute paths and paths leading outside the root are rejected.
+"#
+    }
+
+    fn safety(&self) -> ToolSafety {
+        if self.allow_write {
+            ToolSafety::Mutating
+        } else {
+            ToolSafety::ReadOnly
+        }
+    }
+
+    fn schema(&self) -> Value {
+        let roots: Vec<String> = self
+            .roots
+            .iter()
+            .enumerate()
+            .map(|(idx, root)| format!("{}: {}", idx, root.display()))
+            .collect();
+
+        json!({
+            "type": "object",
+            "properties": {
+                "operation": {
+                    "type": "string",
+                    "enum": self.operations(),
+                    "description": "What to do with the path"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Path relative to the root"
+                },
+                "root": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": self.roots.len() - 1,
+                    "description": format!(
+                        "Root directory to use (default 0). Roots: {}",
+                        roots.join(", ")
+                    )
+                },
+                "content": {
+                    "type": "string",
+                    "description": "Text to write, for write_file"
+                }
+            },
+            "required": ["operation", "path"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<Value, Error> {
+        self.validate_params(&params)?;
+
+        let operation = params["operation"].as_str().unwrap_or_default();
+        let relative = params["path"].as_str().unwrap_or_default();
+        let root_idx = params["root"].as_u64().unwrap_or(0) as usize;
+        debug!("filesystem {} {} (root {})", operation, relative, root_idx);
+
+        match operation {
+            "read_file" => {
+                let path = self.resolve(root_idx, relative, true)?;
+                self.read_file(&path, relative).await
+            }
+            "list_dir" => {
+                let path = self.resolve(root_idx, relative, true)?;
+                self.list_dir(&path, relative).await
+            }
+            "write_file" => {
+                let content = params["content"]
+                    .as_str()
+                    .ok_or_else(|| anyhow!("write_file needs a 'content' parameter"))?;
+                let path = self.resolve(root_idx, relative, false)?;
+                self.write_file(&path, relative, content).await
+            }
+            other => Err(anyhow!("Unknown operation: {}", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A root holding `notes.txt`, next to a `secret.txt` outside it
+    fn sandbox() -> (TempDir, FileSystemTool) {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("project");
+        std::fs::create_dir(&root).unwrap();
+        std::fs::write(root.join("notes.txt"), "remember the milk").unwrap();
+        std::fs::write(dir.path().join("secret.txt"), "hunter2").unwrap();
+        let tool = FileSystemTool::new([root]).unwrap();
+        (dir, tool)
+    }
+
+    #[tokio::test]
+    async fn test_permitted_read_and_list_succeed() {
+        let (_dir, tool) = sandbox();
+
+        let result = tool
+            .execute(json!({"operation": "read_file", "path": "notes.txt"}))
+            .await
+            .unwrap();
+        assert_eq!(result["content"], "remember the milk");
+        assert_eq!(result["truncated"], false);
+
+        let result = tool
+            .execute(json!({"operation": "list_dir", "path": "."}))
+            .await
+            .unwrap();
+        assert_eq!(result["entries"][0]["name"], "notes.txt");
+        assert_eq!(result["entries"][0]["kind"], "file");
+    }
+
+    #[tokio::test]
+    async fn test_escapes_from_the_root_are_denied() {
+        let (dir, tool) = sandbox();
+
+        let error = tool
+            .execute(json!({"operation": "read_file", "path": "../secret.txt"}))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("escapes its root"), "{}", error);
+
+        let absolute = dir.path().join("secret.txt");
+        let error = tool
+            .execute(json!({"operation": "read_file", "path": absolute.to_str().unwrap()}))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("Absolute path"), "{}", error);
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&absolute, tool.roots()[0].join("link.txt")).unwrap();
+            let error = tool
+                .execute(json!({"operation": "read_file", "path": "link.txt"}))
+                .await
+                .unwrap_err();
+            assert!(error.to_string().contains("leads outside its root"), "{}", error);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_writes_need_write_access() {
+        let (_dir, tool) = sandbox();
+        let params = json!({"operation": "write_file", "path": "todo.txt", "content": "buy milk"});
+
+        let result = tool.run(params.clone()).await;
+        assert!(!result.is_success());
+        assert!(result.display_text().contains("`operation` must be one of"));
+
+        let tool = tool.with_write_access();
+        tool.execute(params).await.unwrap();
+        let written = std::fs::read_to_string(tool.roots()[0].join("todo.txt")).unwrap();
+        assert_eq!(written, "buy milk");
+    }
+}
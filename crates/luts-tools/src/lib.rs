@@ -1,17 +1,23 @@
 //! LUTS Tools - AI tools collection
 //!
 //! This crate provides agent-independent AI tools including
-//! calculator, web search, website scraping, and semantic search.
+//! calculator, web search, sandboxed file access, website scraping and summarization, semantic search, and memory statistics.
 
 pub mod base;
 pub mod calc;
+pub mod filesystem;
+pub mod memory_stats;
 pub mod search;
 pub mod website;
 pub mod semantic_search;
+pub mod summarize_url;
 
 // Re-export key tools for convenience
-pub use calc::MathTool;
-pub use search::DDGSearchTool;
-pub use website::WebsiteTool;
+pub use calc::{CalcError, MathTool};
+pub use filesystem::FileSystemTool;
+pub use memory_stats::MemoryStatsTool;
+pub use search::{DDGSearchTool, SearchBackend, SearchResult, StaticSearchBackend};
+pub use website::{WebsiteTool, WebsiteToolConfig};
 pub use semantic_search::SemanticSearchTool;
-pub use base::AiTool;
\ No newline at end of file
+pub use summarize_url::{SummarizeUrlTool, UrlSummary};
+pub use base::{AiTool, ToolSafety};
\ No newline at end of file
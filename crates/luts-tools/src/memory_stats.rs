@@ -0,0 +1,308 @@
+//! Memory statistics tool for AI agents
+//!
+//! This tool answers counting questions about stored memory ("how many facts
+//! did I save last week?") with small tables instead of block listings.
+
+use crate::base::{AiTool, ToolSafety};
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use luts_memory::{BlockType, MemoryBlock, MemoryManager, MemoryQuery};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tracing::debug;
+
+/// Fields blocks can be grouped by
+const GROUP_BY_FIELDS: &[&str] = &["type", "tag", "session", "day"];
+
+/// Tool that counts memory blocks, optionally grouped, and returns tables
+pub struct MemoryStatsTool {
+    pub memory_manager: Arc<MemoryManager>,
+}
+
+impl MemoryStatsTool {
+    /// Create a new memory stats tool
+    pub fn new(memory_manager: Arc<MemoryManager>) -> Self {
+        Self { memory_manager }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct MemoryStatsParams {
+    /// `count` (default) or `stats`
+    operation: Option<String>,
+    /// User ID to count blocks for (defaults to current user)
+    user_id: Option<String>,
+    /// Only count blocks from this session
+    session_id: Option<String>,
+    /// Only count blocks of these types
+    block_types: Option<Vec<String>>,
+    /// Only count blocks created in the last N days
+    since_days: Option<u32>,
+    /// Field to group counts by
+    group_by: Option<String>,
+}
+
+/// Tabular result returned to the model
+#[derive(Debug, Serialize)]
+struct StatsTable {
+    columns: Vec<String>,
+    rows: Vec<Vec<Value>>,
+    total: u64,
+}
+
+#[async_trait]
+impl AiTool for MemoryStatsTool {
+    fn name(&self) -> &str {
+        "memory_stats"
+    }
+
+    fn description(&self) -> &str {
+        "Count memory blocks and return the result as a table. Filter by block type, session \
+         or recent days, and group counts by type, tag, session or day. Use this for questions \
+         like \"how many facts did I save last week?\" instead of listing blocks."
+    }
+
+    fn safety(&self) -> ToolSafety {
+        ToolSafety::ReadOnly
+    }
+
+    fn schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "operation": {
+                    "type": "string",
+                    "enum": ["count", "stats"],
+                    "default": "count",
+                    "description": "`count` counts matching blocks; `stats` returns overall usage with counts by type"
+                },
+                "user_id": {
+                    "type": "string",
+                    "description": "User ID to count blocks for (optional - defaults to current user)",
+                    "default": "current_user"
+                },
+                "session_id": {
+                    "type": "string",
+                    "description": "Only count blocks from this session (optional)"
+                },
+                "block_types": {
+                    "type": "array",
+                    "items": {
+                        "type": "string",
+                        "enum": ["Message", "Summary", "Fact", "Preference", "PersonalInfo", "Goal", "Task"]
+                    },
+                    "description": "Only count blocks of these types (optional)"
+                },
+                "since_days": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Only count blocks created in the last N days (optional)"
+                },
+                "group_by": {
+                    "type": "string",
+                    "enum": GROUP_BY_FIELDS,
+                    "description": "Group counts by block type, tag, session or creation day (optional)"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<Value> {
+        let params: MemoryStatsParams = serde_json::from_value(params)
+            .map_err(|e| anyhow!("Invalid parameters for memory stats: {}", e))?;
+
+        if let Some(group_by) = &params.group_by
+            && !GROUP_BY_FIELDS.contains(&group_by.as_str())
+        {
+            return Err(anyhow!(
+                "Cannot group by '{}'; expected one of: {}",
+                group_by,
+                GROUP_BY_FIELDS.join(", ")
+            ));
+        }
+
+        let user_id = params
+            .user_id
+            .clone()
+            .unwrap_or_else(|| "current_user".to_string());
+
+        match params.operation.as_deref().unwrap_or("count") {
+            "count" => self.count(&user_id, &params).await,
+            "stats" => self.stats(&user_id).await,
+            other => Err(anyhow!(
+                "Unknown operation '{}'; expected 'count' or 'stats'",
+                other
+            )),
+        }
+    }
+}
+
+impl MemoryStatsTool {
+    /// Count matching blocks, grouped when requested
+    async fn count(&self, user_id: &str, params: &MemoryStatsParams) -> Result<Value> {
+        let block_types = params
+            .block_types
+            .iter()
+            .flatten()
+            .map(|name| parse_block_type(name))
+            .collect::<Result<Vec<_>>>()?;
+
+        let query = MemoryQuery {
+            user_id: Some(user_id.to_string()),
+            session_id: params.session_id.clone(),
+            block_types,
+            created_after: params
+                .since_days
+                .map(|days| Utc::now() - Duration::days(days as i64)),
+            limit: None,
+            ..Default::default()
+        };
+        let blocks = self.memory_manager.search(&query).await?;
+        debug!("Counting {} memory blocks for {}", blocks.len(), user_id);
+
+        let total = blocks.len() as u64;
+        let table = match params.group_by.as_deref() {
+            None => StatsTable {
+                columns: vec!["count".to_string()],
+                rows: vec![vec![json!(total)]],
+                total,
+            },
+            Some(field) => {
+                let mut counts: BTreeMap<String, u64> = BTreeMap::new();
+                for block in &blocks {
+                    for key in group_keys(block, field) {
+                        *counts.entry(key).or_default() += 1;
+                    }
+                }
+                let mut rows: Vec<(String, u64)> = counts.into_iter().collect();
+                // Days read best in order; other groups largest first
+                if field != "day" {
+                    rows.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                }
+                StatsTable {
+                    columns: vec![field.to_string(), "count".to_string()],
+                    rows: rows
+                        .into_iter()
+                        .map(|(key, count)| vec![json!(key), json!(count)])
+                        .collect(),
+                    total,
+                }
+            }
+        };
+
+        Ok(serde_json::to_value(table)?)
+    }
+
+    /// Overall usage statistics for a user
+    async fn stats(&self, user_id: &str) -> Result<Value> {
+        let stats = self.memory_manager.get_stats(user_id).await?;
+
+        let mut rows: Vec<(String, u64)> = stats.blocks_by_type.into_iter().collect();
+        rows.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        Ok(json!({
+            "columns": ["type", "count"],
+            "rows": rows
+                .into_iter()
+                .map(|(block_type, count)| vec![json!(block_type), json!(count)])
+                .collect::<Vec<_>>(),
+            "total": stats.total_blocks,
+            "total_size_bytes": stats.total_size_bytes,
+            "last_updated": stats.last_updated.to_rfc3339(),
+        }))
+    }
+}
+
+/// Keys a block is counted under when grouping by `field`
+///
+/// Blocks with several tags count once per tag.
+fn group_keys(block: &MemoryBlock, field: &str) -> Vec<String> {
+    match field {
+        "type" => vec![block.block_type().to_string()],
+        "tag" if block.tags().is_empty() => vec!["(untagged)".to_string()],
+        "tag" => block.tags().to_vec(),
+        "session" => vec![block.session_id().unwrap_or("(none)").to_string()],
+        "day" => vec![
+            DateTime::from_timestamp_millis(block.created_at() as i64)
+                .map(|created| created.format("%Y-%m-%d").to_string())
+                .unwrap_or_else(|| "(unknown)".to_string()),
+        ],
+        _ => unreachable!("group-by field is validated against GROUP_BY_FIELDS"),
+    }
+}
+
+fn parse_block_type(name: &str) -> Result<BlockType> {
+    match name {
+        "Message" => Ok(BlockType::Message),
+        "Summary" => Ok(BlockType::Summary),
+        "Fact" => Ok(BlockType::Fact),
+        "Preference" => Ok(BlockType::Preference),
+        "PersonalInfo" => Ok(BlockType::PersonalInfo),
+        "Goal" => Ok(BlockType::Goal),
+        "Task" => Ok(BlockType::Task),
+        _ => Err(anyhow!("Unknown block type: {}", name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use luts_memory::{InMemoryMemoryStore, MemoryBlockBuilder, MemoryContent};
+
+    async fn seeded_tool() -> MemoryStatsTool {
+        let memory_manager = Arc::new(MemoryManager::new(InMemoryMemoryStore::new()));
+        for (block_type, text) in [
+            (BlockType::Fact, "The capital of France is Paris"),
+            (BlockType::Fact, "Water boils at 100 degrees"),
+            (BlockType::Fact, "Rust 1.0 shipped in 2015"),
+            (BlockType::Preference, "Prefers tea over coffee"),
+            (BlockType::Goal, "Learn Spanish this year"),
+        ] {
+            let block = MemoryBlockBuilder::new()
+                .with_user_id("test_user")
+                .with_type(block_type)
+                .with_content(MemoryContent::Text(text.to_string()))
+                .build()
+                .unwrap();
+            memory_manager.store(block).await.unwrap();
+        }
+        MemoryStatsTool::new(memory_manager)
+    }
+
+    #[tokio::test]
+    async fn test_count_grouped_by_type() {
+        let tool = seeded_tool().await;
+
+        let table = tool
+            .execute(json!({ "user_id": "test_user", "group_by": "type", "since_days": 7 }))
+            .await
+            .unwrap();
+        assert_eq!(
+            table,
+            json!({
+                "columns": ["type", "count"],
+                "rows": [["fact", 3], ["goal", 1], ["preference", 1]],
+                "total": 5
+            })
+        );
+
+        let facts = tool
+            .execute(json!({ "user_id": "test_user", "block_types": ["Fact"] }))
+            .await
+            .unwrap();
+        assert_eq!(facts["rows"], json!([[3]]));
+    }
+
+    #[tokio::test]
+    async fn test_rejects_unknown_group_by_field() {
+        let tool = seeded_tool().await;
+        let err = tool
+            .execute(json!({ "user_id": "test_user", "group_by": "content" }))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Cannot group by 'content'"));
+    }
+}
@@ -1,85 +1,62 @@
 //! Search tool for AI assistants
 //!
-//! This module provides a real DuckDuckGo search tool.
+//! This module provides a web search tool. Results come from a
+//! [`SearchBackend`], DuckDuckGo by default, so another provider (or canned
+//! results in tests) can be swapped in.
 
-use crate::base::AiTool;
+use crate::base::{AiTool, ToolSafety};
 use anyhow::{Error, anyhow};
 use async_trait::async_trait;
 use reqwest;
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::time::Duration;
 use tracing::{debug, trace};
 
-/// Parameters for the DuckDuckGo search tool.
+/// Parameters for the search tool.
 #[derive(Deserialize)]
 struct SearchParams {
-    /// The search query to send to DuckDuckGo.
+    /// The search query to send to the backend.
     query: String,
     /// Number of results to return (default: 3, max: 10)
     num_results: Option<usize>,
 }
 
-/// Represents a single search result.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct SearchResult {
-    title: String,
-    link: String,
-    snippet: String,
+/// A single search result.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchResult {
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
 }
 
-/// Tool for searching DuckDuckGo.
-pub struct DDGSearchTool;
-
+/// A search provider the search tool can query
 #[async_trait]
-impl AiTool for DDGSearchTool {
-    fn name(&self) -> &str {
-        "search"
-    }
+pub trait SearchBackend: Send + Sync {
+    /// Short name of the provider, for logs
+    fn name(&self) -> &str;
 
-    fn description(&self) -> &str {
-        r#"Searches the web using DuckDuckGo. Use this tool liberally to find information you aren't certain about.
-Important search operators:
-cats dogs	results about cats or dogs
-"cats and dogs"	exact term (avoid unless necessary)
-~"cats and dogs"	semantically similar terms
-cats -dogs	reduce results about dogs
-cats +dogs	increase results about dogs
-cats filetype:pdf	search pdfs about cats (supports doc(x), xls(x), ppt(x), html)
-dogs site:example.com	search dogs on example.com
-cats -site:example.com	exclude example.com from results
-intitle:dogs	title contains "dogs"
-inurl:cats	URL contains "cats""#
-    }
+    /// Up to `max_results` results for `query`, best first
+    async fn search(&self, query: &str, max_results: usize) -> Result<Vec<SearchResult>, Error>;
+}
 
-    fn schema(&self) -> Value {
-        serde_json::json!({
-            "type": "object",
-            "properties": {
-                "query": {
-                    "type": "string",
-                    "description": "The search query"
-                },
-                "num_results": {
-                    "type": "integer",
-                    "description": "Number of results to return (default: 3, max: 10)"
-                }
-            },
-            "required": ["query"]
-        })
-    }
+/// Backend that scrapes DuckDuckGo's HTML results page
+pub struct DuckDuckGoBackend;
 
-    async fn execute(&self, args: Value) -> Result<Value, Error> {
-        let params: SearchParams = serde_json::from_value(args.clone())
-            .map_err(|_| anyhow!("Missing or invalid 'query' parameter"))?;
-        let num_results = params.num_results.unwrap_or(3).clamp(1, 10);
+#[async_trait]
+impl SearchBackend for DuckDuckGoBackend {
+    fn name(&self) -> &str {
+        "duckduckgo"
+    }
 
+    async fn search(&self, query: &str, max_results: usize) -> Result<Vec<SearchResult>, Error> {
         debug!("=== DDG SEARCH DEBUG ===");
-        debug!("Query: '{}'", params.query);
-        debug!("Num results: {}", num_results);
+        debug!("Query: '{}'", query);
+        debug!("Num results: {}", max_results);
 
         let client = reqwest::Client::new();
-        let url = format!("https://html.duckduckgo.com/html/?q={}", params.query);
+        let url = format!("https://html.duckduckgo.com/html/?q={}", query);
         debug!("Request URL: {}", url);
 
         let resp = client
@@ -129,7 +106,7 @@ inurl:cats	URL contains "cats""#
 
         let document = Html::parse_document(&body);
 
-        trace!("Parsed HTML document for query: {}", params.query);
+        trace!("Parsed HTML document for query: {}", query);
         trace!("{:?}", body);
 
         let result_selector = Selector::parse(".web-result").unwrap();
@@ -145,7 +122,7 @@ inurl:cats	URL contains "cats""#
                     .next()
                     .map(|n| n.text().collect::<Vec<_>>().join(""))
                     .unwrap_or_default();
-                let link = result
+                let url = result
                     .select(&result_url_selector)
                     .next()
                     .map(|n| n.text().collect::<Vec<_>>().join("").trim().to_string())
@@ -156,30 +133,132 @@ inurl:cats	URL contains "cats""#
                     .map(|n| n.text().collect::<Vec<_>>().join(""))
                     .unwrap_or_default();
 
-                if !title.is_empty() && !link.is_empty() {
+                if !title.is_empty() && !url.is_empty() {
                     Some(SearchResult {
                         title,
-                        link,
+                        url,
                         snippet,
                     })
                 } else {
                     None
                 }
             })
-            .take(num_results)
+            .take(max_results)
             .collect::<Vec<_>>();
 
         debug!("Parsed {} search results", results.len());
         for (i, result) in results.iter().enumerate() {
             debug!(
-                "Result #{}: title='{}', link='{}'",
+                "Result #{}: title='{}', url='{}'",
                 i + 1,
                 result.title,
-                result.link
+                result.url
             );
         }
         debug!("=== END DDG SEARCH DEBUG ===");
 
+        Ok(results)
+    }
+}
+
+/// Backend that returns the same canned results for every query
+///
+/// Lets tests and CI exercise the search tool without the network.
+pub struct StaticSearchBackend {
+    results: Vec<SearchResult>,
+}
+
+impl StaticSearchBackend {
+    pub fn new(results: Vec<SearchResult>) -> Self {
+        Self { results }
+    }
+}
+
+#[async_trait]
+impl SearchBackend for StaticSearchBackend {
+    fn name(&self) -> &str {
+        "static"
+    }
+
+    async fn search(&self, _query: &str, max_results: usize) -> Result<Vec<SearchResult>, Error> {
+        Ok(self.results.iter().take(max_results).cloned().collect())
+    }
+}
+
+/// Tool for searching the web, through DuckDuckGo unless told otherwise.
+pub struct DDGSearchTool {
+    backend: Box<dyn SearchBackend>,
+}
+
+impl Default for DDGSearchTool {
+    fn default() -> Self {
+        Self::new(Box::new(DuckDuckGoBackend))
+    }
+}
+
+impl DDGSearchTool {
+    /// Search through `backend` instead of DuckDuckGo
+    pub fn new(backend: Box<dyn SearchBackend>) -> Self {
+        Self { backend }
+    }
+}
+
+#[async_trait]
+impl AiTool for DDGSearchTool {
+    fn name(&self) -> &str {
+        "search"
+    }
+
+    fn description(&self) -> &str {
+        r#"Searches the web using DuckDuckGo. Use this tool liberally to find information you aren't certain about.
+Important search operators:
+cats dogs	results about cats or dogs
+"cats and dogs"	exact term (avoid unless necessary)
+~"cats and dogs"	semantically similar terms
+cats -dogs	reduce results about dogs
+cats +dogs	increase results about dogs
+cats filetype:pdf	search pdfs about cats (supports doc(x), xls(x), ppt(x), html)
+dogs site:example.com	search dogs on example.com
+cats -site:example.com	exclude example.com from results
+intitle:dogs	title contains "dogs"
+inurl:cats	URL contains "cats""#
+    }
+
+    fn safety(&self) -> ToolSafety {
+        ToolSafety::Network
+    }
+
+    fn schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "The search query"
+                },
+                "num_results": {
+                    "type": "integer",
+                    "description": "Number of results to return (default: 3, max: 10)"
+                }
+            },
+            "required": ["query"]
+        })
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        // A slow search shouldn't stall the whole turn
+        Some(Duration::from_secs(15))
+    }
+
+    async fn execute(&self, args: Value) -> Result<Value, Error> {
+        self.validate_params(&args)?;
+        let params: SearchParams = serde_json::from_value(args.clone())
+            .map_err(|_| anyhow!("Missing or invalid 'query' parameter"))?;
+        let num_results = params.num_results.unwrap_or(3).clamp(1, 10);
+
+        debug!("Searching {} for '{}'", self.backend.name(), params.query);
+        let results = self.backend.search(&params.query, num_results).await?;
+
         Ok(serde_json::json!({ "results": results }))
     }
 }
@@ -191,7 +270,7 @@ mod tests {
 
     #[test]
     fn test_tool_metadata() {
-        let tool = DDGSearchTool;
+        let tool = DDGSearchTool::default();
 
         assert_eq!(tool.name(), "search");
         assert!(!tool.description().is_empty());
@@ -210,7 +289,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_parameter_validation() {
-        let tool = DDGSearchTool;
+        let tool = DDGSearchTool::default();
 
         // Missing query parameter
         let result = tool.execute(json!({})).await;
@@ -224,7 +303,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_valid_query_structure() {
-        let tool = DDGSearchTool;
+        let tool = DDGSearchTool::default();
 
         // Test with a simple valid query
         let result = tool.execute(json!({"query": "test"})).await;
@@ -243,7 +322,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_extra_parameters() {
-        let tool = DDGSearchTool;
+        let tool = DDGSearchTool::default();
 
         // Extra parameters in the right structure should work
         let result = tool
@@ -267,4 +346,39 @@ mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_invalid_params_report_schema_violations() {
+        let tool = DDGSearchTool::default();
+
+        let error = tool.validate_params(&json!({"query": 123})).unwrap_err().to_string();
+        assert!(error.contains("`query` should be string, got integer"), "{}", error);
+
+        let result = tool.run(json!({"num_results": "three"})).await;
+        assert!(!result.is_success());
+        let error = result.display_text();
+        assert!(error.contains("missing required field `query`"), "{}", error);
+        assert!(error.contains("`num_results` should be integer, got string"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_static_backend_results_are_returned_typed() {
+        let canned: Vec<SearchResult> = (1..=4)
+            .map(|i| SearchResult {
+                title: format!("Result {}", i),
+                url: format!("https://example.com/{}", i),
+                snippet: format!("Snippet {}", i),
+            })
+            .collect();
+        let tool = DDGSearchTool::new(Box::new(StaticSearchBackend::new(canned.clone())));
+
+        let response = tool
+            .execute(json!({"query": "anything", "num_results": 2}))
+            .await
+            .unwrap();
+        let results: Vec<SearchResult> =
+            serde_json::from_value(response["results"].clone()).unwrap();
+        assert_eq!(results, canned[..2]);
+        assert_eq!(response["results"][0]["url"], "https://example.com/1");
+    }
 }
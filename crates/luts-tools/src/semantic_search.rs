@@ -2,13 +2,19 @@
 //!
 //! This tool provides semantic search capabilities using vector embeddings
 //! to find relevant memory blocks based on meaning rather than just keywords.
+//!
+//! Results carry their similarity scores, and the model can raise `min_score`
+//! to drop weak matches. Searching a memory store that has no embedding
+//! service fails with [`LutsError::Config`] instead of returning nothing,
+//! since no stored block could ever match.
 
 use luts_memory::{
     MemoryManager, VectorSearchConfig, EmbeddingService, EmbeddingServiceFactory, 
     EmbeddingConfig, EmbeddingProvider, BlockType, MemoryContent, MemoryQuery, VectorQuery,
 };
-use crate::base::AiTool;
+use crate::base::{AiTool, ToolSafety};
 use anyhow::{Result, anyhow};
+use luts_common::LutsError;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
@@ -18,7 +24,7 @@ use tracing::{debug, warn};
 /// Semantic search tool that uses vector embeddings for similarity search
 pub struct SemanticSearchTool {
     pub memory_manager: Arc<MemoryManager>,
-    pub embedding_service: Box<dyn EmbeddingService>,
+    pub embedding_service: Arc<dyn EmbeddingService>,
 }
 
 impl SemanticSearchTool {
@@ -42,7 +48,7 @@ impl SemanticSearchTool {
     /// Create a semantic search tool with a specific embedding service
     pub fn with_embedding_service(
         memory_manager: Arc<MemoryManager>,
-        embedding_service: Box<dyn EmbeddingService>,
+        embedding_service: Arc<dyn EmbeddingService>,
     ) -> Self {
         Self {
             memory_manager,
@@ -62,8 +68,12 @@ struct SemanticSearchParams {
     /// Block types to search (optional - searches all if not specified)
     block_types: Option<Vec<String>>,
     /// Maximum number of results (defaults to 5)
+    limit: Option<usize>,
+    /// Minimum similarity score (0.0 to 1.0, defaults to 0.7)
+    min_score: Option<f32>,
+    /// Older name for `limit`
     max_results: Option<usize>,
-    /// Similarity threshold (0.0 to 1.0, defaults to 0.7)
+    /// Older name for `min_score`
     similarity_threshold: Option<f32>,
 }
 
@@ -101,10 +111,10 @@ struct SearchMetadata {
     query: String,
     /// Embedding dimensions used
     embedding_dimensions: usize,
-    /// Search configuration used
-    similarity_threshold: f32,
+    /// Minimum similarity score applied
+    min_score: f32,
     /// Maximum results requested
-    max_results: usize,
+    limit: usize,
 }
 
 #[async_trait]
@@ -116,7 +126,12 @@ impl AiTool for SemanticSearchTool {
     fn description(&self) -> &str {
         "Search memory blocks using semantic similarity based on meaning rather than keywords. \
          This tool uses vector embeddings to find content that is conceptually similar to your query, \
-         even if it doesn't contain the exact same words."
+         even if it doesn't contain the exact same words. Each result has a similarity_score from 0.0 to 1.0; \
+         raise min_score to keep only close matches and use block_types and limit to narrow the results."
+    }
+
+    fn safety(&self) -> ToolSafety {
+        ToolSafety::ReadOnly
     }
 
     fn schema(&self) -> Value {
@@ -144,19 +159,31 @@ impl AiTool for SemanticSearchTool {
                     },
                     "description": "Types of memory blocks to search (optional - searches all types if not specified)"
                 },
-                "max_results": {
+                "limit": {
                     "type": "integer",
                     "minimum": 1,
                     "maximum": 20,
                     "default": 5,
                     "description": "Maximum number of results to return (1-20, defaults to 5)"
                 },
-                "similarity_threshold": {
+                "min_score": {
                     "type": "number",
                     "minimum": 0.0,
                     "maximum": 1.0,
                     "default": 0.7,
                     "description": "Minimum similarity score for results (0.0-1.0, defaults to 0.7)"
+                },
+                "max_results": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 20,
+                    "description": "Deprecated name for limit"
+                },
+                "similarity_threshold": {
+                    "type": "number",
+                    "minimum": 0.0,
+                    "maximum": 1.0,
+                    "description": "Deprecated name for min_score"
                 }
             },
             "required": ["query"]
@@ -164,11 +191,21 @@ impl AiTool for SemanticSearchTool {
     }
 
     async fn execute(&self, params: Value) -> Result<Value> {
+        self.validate_params(&params)?;
         let params: SemanticSearchParams = serde_json::from_value(params)
             .map_err(|e| anyhow!("Invalid parameters for semantic search: {}", e))?;
 
         debug!("Performing semantic search for query: '{}'", params.query);
 
+        // Without embeddings on the stored blocks every search would come back empty
+        if self.memory_manager.embedding_dimensions().is_none() {
+            return Err(LutsError::Config(
+                "Semantic search is unavailable: the memory store has no embedding service configured"
+                    .to_string(),
+            )
+            .into());
+        }
+
         // Default user_id if not provided
         let user_id = params.user_id.unwrap_or_else(|| "current_user".to_string());
 
@@ -197,8 +234,9 @@ impl AiTool for SemanticSearchTool {
 
         // Configure search parameters
         let search_config = VectorSearchConfig {
-            min_relevance: params.similarity_threshold.unwrap_or(0.7),
-            max_results: params.max_results.unwrap_or(5),
+            min_relevance: params.min_score.or(params.similarity_threshold).unwrap_or(0.7),
+            max_results: params.limit.or(params.max_results).unwrap_or(5),
+            expected_dimensions: Some(self.embedding_service.dimensions()),
             ..Default::default()
         };
 
@@ -209,6 +247,7 @@ impl AiTool for SemanticSearchTool {
         let vector_query = VectorQuery {
             query_vector: query_embedding,
             search_config: search_config.clone(),
+            metric_override: None,
         };
         
         // Create memory query with vector search
@@ -220,8 +259,11 @@ impl AiTool for SemanticSearchTool {
             ..Default::default()
         };
         
-        // Perform the search
-        let results = self.memory_manager.search(&query).await?;
+        // Perform the search, reranked if the memory manager has a reranker
+        let results = self
+            .memory_manager
+            .semantic_search(&params.query, &query)
+            .await?;
 
         debug!("Semantic search found {} results", results.len());
 
@@ -270,8 +312,8 @@ impl AiTool for SemanticSearchTool {
             search_info: SearchMetadata {
                 query: params.query,
                 embedding_dimensions: self.embedding_service.dimensions(),
-                similarity_threshold: search_config.min_relevance,
-                max_results: search_config.max_results,
+                min_score: search_config.min_relevance,
+                limit: search_config.max_results,
             },
         };
 
@@ -284,7 +326,7 @@ mod tests {
     use super::*;
     use luts_memory::{
         MemoryBlockBuilder, MemoryContent, BlockType, 
-        SurrealMemoryStore, SurrealConfig, 
+        SurrealMemoryStore, SurrealConfig, InMemoryMemoryStore,
         EmbeddingServiceFactory, EmbeddingConfig, EmbeddingProvider,
     };
     use tempfile::TempDir;
@@ -299,6 +341,7 @@ mod tests {
             path: db_path,
             namespace: "test".to_string(),
             database: "memory".to_string(),
+            encryption_key: None,
         };
 
         // Create embedding service
@@ -309,8 +352,8 @@ mod tests {
         };
         let embedding_service = EmbeddingServiceFactory::create(embedding_config).unwrap();
 
-        // Create store
-        let store = SurrealMemoryStore::new(config)
+        // Create store; it embeds blocks with the same service the tool queries with
+        let store = SurrealMemoryStore::with_embedding_service(config, Some(embedding_service.clone()))
             .await
             .unwrap();
         
@@ -357,4 +400,110 @@ mod tests {
 
         println!("Semantic search result: {}", serde_json::to_string_pretty(&result).unwrap());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_invalid_params_report_schema_violations() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = SurrealMemoryStore::new(SurrealConfig::File {
+            path: temp_dir.path().join("test.db"),
+            namespace: "test".to_string(),
+            database: "memory".to_string(),
+            encryption_key: None,
+        })
+        .await
+        .unwrap();
+        let tool = SemanticSearchTool::new(Arc::new(MemoryManager::new(store))).unwrap();
+
+        let result = tool
+            .run(json!({
+                "max_results": 50,
+                "block_types": ["Fact", "Rumour"],
+                "similarity_threshold": "high"
+            }))
+            .await;
+        assert!(!result.is_success());
+        let error = result.display_text();
+        assert!(error.contains("missing required field `query`"), "{}", error);
+        assert!(error.contains("`max_results` must be at most 20"), "{}", error);
+        assert!(error.contains("`block_types[1]` must be one of"), "{}", error);
+        assert!(error.contains("`similarity_threshold` should be number, got string"), "{}", error);
+    }
+
+    /// A tool over an in-memory store holding four facts, embedded with mock embeddings
+    async fn tool_with_facts() -> SemanticSearchTool {
+        let embedding_service = EmbeddingServiceFactory::create(EmbeddingConfig {
+            provider: EmbeddingProvider::Mock,
+            dimensions: 384,
+            ..Default::default()
+        })
+        .unwrap();
+        let memory_manager = Arc::new(MemoryManager::new(
+            InMemoryMemoryStore::with_embedding_service(embedding_service.clone()),
+        ));
+        for text in [
+            "The capital of France is Paris",
+            "Python is a programming language",
+            "Rust has a borrow checker",
+            "quarterly tax filing",
+        ] {
+            let block = MemoryBlockBuilder::new()
+                .with_user_id("test_user")
+                .with_type(BlockType::Fact)
+                .with_content(MemoryContent::Text(text.to_string()))
+                .build()
+                .unwrap();
+            memory_manager.store(block).await.unwrap();
+        }
+        SemanticSearchTool::with_embedding_service(memory_manager, embedding_service)
+    }
+
+    #[tokio::test]
+    async fn test_min_score_filters_low_similarity_blocks() {
+        let tool = tool_with_facts().await;
+        let search = |min_score: f32| {
+            tool.execute(json!({
+                "query": "The capital of France is Paris",
+                "user_id": "test_user",
+                "min_score": min_score,
+                "limit": 10
+            }))
+        };
+
+        let loose = search(0.0).await.unwrap();
+        let strict = search(0.95).await.unwrap();
+        assert!(loose["results_found"].as_u64().unwrap() > 1);
+        assert_eq!(strict["results_found"], 1);
+        assert_eq!(strict["search_info"]["min_score"].as_f64().unwrap() as f32, 0.95);
+
+        let best = &strict["results"][0];
+        assert_eq!(best["content_preview"], "The capital of France is Paris");
+        assert!(best["similarity_score"].as_f64().unwrap() > 0.99);
+        for result in loose["results"].as_array().unwrap() {
+            assert!(result["similarity_score"].as_f64().unwrap() >= 0.0);
+        }
+
+        // The older parameter name still works
+        let legacy = tool
+            .execute(json!({
+                "query": "The capital of France is Paris",
+                "user_id": "test_user",
+                "similarity_threshold": 0.95
+            }))
+            .await
+            .unwrap();
+        assert_eq!(legacy["results_found"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_store_without_embeddings_is_a_config_error() {
+        let tool = SemanticSearchTool::new(Arc::new(MemoryManager::new(InMemoryMemoryStore::new())))
+            .unwrap();
+
+        let error = tool.execute(json!({"query": "anything"})).await.unwrap_err();
+        assert!(
+            matches!(error.downcast_ref::<LutsError>(), Some(LutsError::Config(_))),
+            "{}",
+            error
+        );
+    }
+}
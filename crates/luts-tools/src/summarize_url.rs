@@ -0,0 +1,230 @@
+use anyhow::{Error, anyhow};
+use luts_llm::{AiService, GenerationParams, InternalChatMessage};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tracing::debug;
+
+use crate::base::{AiTool, ToolSafety};
+use crate::website::{WebsiteTool, WebsiteToolConfig, page_title, strip_hidden_elements};
+
+/// Page text sent to the summarizer is cut to this many characters
+const MAX_PAGE_CHARS: usize = 20_000;
+
+/// Structured summary of a web page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UrlSummary {
+    pub title: String,
+    pub url: String,
+    pub summary: String,
+    pub key_points: Vec<String>,
+}
+
+/// What the summarizer is asked to return
+#[derive(Deserialize)]
+struct SummarizerReply {
+    summary: String,
+    #[serde(default)]
+    key_points: Vec<String>,
+}
+
+/// Tool that fetches a page and returns an LLM-written summary of it.
+///
+/// Pages are fetched through a [`WebsiteTool`], with the same rate limit,
+/// robots.txt checks and size cap, and summaries are cached by URL so repeat requests cost no tokens.
+pub struct SummarizeUrlTool {
+    summarizer: Arc<dyn AiService>,
+    cache: Mutex<HashMap<String, UrlSummary>>,
+    fetcher: WebsiteTool,
+}
+
+impl SummarizeUrlTool {
+    pub fn new(summarizer: Arc<dyn AiService>) -> Self {
+        Self {
+            summarizer,
+            cache: Mutex::new(HashMap::new()),
+            fetcher: WebsiteTool::default(),
+        }
+    }
+
+    /// Fetch pages with `config` instead of the default one
+    pub fn with_website_config(mut self, config: WebsiteToolConfig) -> Self {
+        self.fetcher = WebsiteTool::new(config);
+        self
+    }
+
+    /// Fetch and summarize `url`, or return the cached summary
+    pub async fn summarize(&self, url: &str) -> Result<UrlSummary, Error> {
+        if let Some(cached) = self.cache.lock().unwrap().get(url) {
+            debug!("Using cached summary for {}", url);
+            return Ok(cached.clone());
+        }
+
+        let html = strip_hidden_elements(&self.fetcher.fetch(url).await?.html);
+        let title = page_title(&html).unwrap_or_else(|| url.to_string());
+        let markdown = html2md::rewrite_html(&html, false);
+        let text: String = markdown.chars().take(MAX_PAGE_CHARS).collect();
+        debug!("Summarizing {} ({} of {} chars)", url, text.len(), markdown.len());
+
+        let messages = vec![
+            InternalChatMessage::System {
+                content: "You summarize web pages. Reply with only a JSON object of the form \
+                          {\"summary\": \"<2-4 sentences>\", \"key_points\": [\"<point>\", ...]}."
+                    .to_string(),
+            },
+            InternalChatMessage::User {
+                content: format!("Title: {}\nURL: {}\n\n{}", title, url, text),
+            },
+        ];
+        let reply = self
+            .summarizer
+            .generate_response(&messages, &GenerationParams::default())
+            .await?
+            .into_text()
+            .ok_or_else(|| anyhow!("Summarizer returned no text"))?;
+        let reply = parse_reply(&reply);
+
+        let summary = UrlSummary {
+            title,
+            url: url.to_string(),
+            summary: reply.summary,
+            key_points: reply.key_points,
+        };
+        self.cache
+            .lock()
+            .unwrap()
+            .insert(url.to_string(), summary.clone());
+        Ok(summary)
+    }
+}
+
+/// Read the summarizer's JSON reply, tolerating surrounding prose
+///
+/// A reply that isn't JSON is used as the summary, with any `-` bullet lines
+/// taken as key points.
+fn parse_reply(reply: &str) -> SummarizerReply {
+    let json = match (reply.find('{'), reply.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &reply[start..=end],
+        _ => reply,
+    };
+    serde_json::from_str(json).unwrap_or_else(|_| SummarizerReply {
+        summary: reply.trim().to_string(),
+        key_points: reply
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("- "))
+            .map(str::to_string)
+            .collect(),
+    })
+}
+
+#[async_trait::async_trait]
+impl AiTool for SummarizeUrlTool {
+    fn name(&self) -> &str {
+        "summarize_url"
+    }
+
+    fn description(&self) -> &str {
+        r#"Fetches a web page and summarizes it.
+Parameters:
+- `url`: The URL of the page to summarize.
+
+Returns the page title, a short summary and its key points. Prefer this over `website` when you only need the gist of a page.
+"#
+    }
+
+    fn safety(&self) -> ToolSafety {
+        ToolSafety::Network
+    }
+
+    fn schema(&self) -> Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "url": {
+                    "type": "string",
+                    "description": "The URL of the page to summarize"
+                }
+            },
+            "required": ["url"]
+        })
+    }
+
+    async fn execute(&self, params: Value) -> Result<Value, Error> {
+        self.validate_params(&params)?;
+        let url = params["url"].as_str().unwrap_or_default();
+
+        let summary = self.summarize(url).await?;
+        Ok(serde_json::to_value(summary)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use luts_llm::{MockAiService, MockResponse};
+    use serde_json::json;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Serve `html` to every request on a local port
+    async fn serve_page(html: &'static str) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = [0u8; 1024];
+                let _ = socket.read(&mut request).await;
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    html.len(),
+                    html
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}/release-notes", addr)
+    }
+
+    #[tokio::test]
+    async fn test_summarizes_page_into_structured_output() {
+        let url = serve_page(
+            "<html><head><title>Rust 9.0 Release Notes</title></head>\
+             <body><h1>Rust 9.0</h1><p>Borrows can now fly.</p></body></html>",
+        )
+        .await;
+        let summarizer = Arc::new(MockAiService::new().with_response(MockResponse::Text(
+            "Here you go:\n{\"summary\": \"Rust 9.0 adds flying borrows.\", \
+             \"key_points\": [\"Borrows can fly\", \"No breaking changes\"]}"
+                .to_string(),
+        )));
+        let tool = SummarizeUrlTool::new(summarizer.clone());
+
+        let result = tool.execute(json!({ "url": url })).await.unwrap();
+        assert_eq!(
+            result,
+            json!({
+                "title": "Rust 9.0 Release Notes",
+                "url": url,
+                "summary": "Rust 9.0 adds flying borrows.",
+                "key_points": ["Borrows can fly", "No breaking changes"],
+            })
+        );
+        let requests = summarizer.requests();
+        let Some(InternalChatMessage::User { content: prompt }) = requests[0].last() else {
+            panic!("the page should be sent as a user message");
+        };
+        assert!(prompt.contains("Borrows can now fly."));
+
+        // The second request is served from the cache
+        let again = tool.execute(json!({ "url": url })).await.unwrap();
+        assert_eq!(again, result);
+        assert_eq!(summarizer.requests().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_reply_falls_back_to_plain_text() {
+        let reply = parse_reply("A short page.\n- first point\n- second point");
+        assert_eq!(reply.summary, "A short page.\n- first point\n- second point");
+        assert_eq!(reply.key_points, vec!["first point", "second point"]);
+    }
+}
@@ -1,11 +1,222 @@
 use anyhow::{Error, anyhow};
+use reqwest::Url;
+use scraper::{Html, Selector};
 use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tracing::debug;
 
-use crate::base::AiTool;
+use crate::base::{AiTool, ToolSafety};
 
-/// Tool that fetches a website and renders its content as HTML or Markdown.
-pub struct WebsiteTool;
+/// Elements whose contents are never readable page text
+const HIDDEN_ELEMENTS: [&str; 3] = ["script", "style", "noscript"];
+
+/// Elements that start a new line when a page is rendered as text
+const BLOCK_ELEMENTS: [&str; 20] = [
+    "p", "div", "br", "li", "ul", "ol", "h1", "h2", "h3", "h4", "h5", "h6", "tr", "table",
+    "section", "article", "header", "footer", "pre", "blockquote",
+];
+
+/// How politely [`WebsiteTool`] fetches pages
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebsiteToolConfig {
+    /// User-Agent header sent with every request, also matched against robots.txt
+    pub user_agent: String,
+    /// Response bodies are cut off after this many bytes
+    pub max_bytes: usize,
+    /// Refuse paths the site's robots.txt disallows
+    pub respect_robots: bool,
+    /// Requests allowed per host in any one minute; 0 means unlimited
+    pub per_host_rpm: u32,
+}
+
+impl Default for WebsiteToolConfig {
+    fn default() -> Self {
+        Self {
+            user_agent: concat!("luts/", env!("CARGO_PKG_VERSION")).to_string(),
+            max_bytes: 1024 * 1024,
+            respect_robots: true,
+            per_host_rpm: 30,
+        }
+    }
+}
+
+/// A fetched page, possibly cut off at the configured size
+pub(crate) struct FetchedPage {
+    pub url: String,
+    pub html: String,
+    pub truncated: bool,
+}
+
+/// Tool that fetches a website and renders its content as text, Markdown or HTML.
+///
+/// Scripts and styles are always stripped. Requests are rate limited per host,
+/// robots.txt is honoured and bodies are capped, as set by [`WebsiteToolConfig`].
+pub struct WebsiteTool {
+    config: WebsiteToolConfig,
+    client: reqwest::Client,
+    /// Start times of recent requests, per host
+    requests: Mutex<HashMap<String, VecDeque<Instant>>>,
+    /// Parsed robots.txt rules, per origin
+    robots: Mutex<HashMap<String, Arc<RobotsRules>>>,
+}
+
+impl Default for WebsiteTool {
+    fn default() -> Self {
+        Self::new(WebsiteToolConfig::default())
+    }
+}
+
+impl WebsiteTool {
+    pub fn new(config: WebsiteToolConfig) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::new(),
+            requests: Mutex::new(HashMap::new()),
+            robots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &WebsiteToolConfig {
+        &self.config
+    }
+
+    /// Fetch `website`, subject to the rate limit, robots.txt and size cap
+    ///
+    /// Shared with other tools that read web pages so they all behave the same.
+    pub(crate) async fn fetch(&self, website: &str) -> Result<FetchedPage, Error> {
+        let website = if website.starts_with("http://") || website.starts_with("https://") {
+            website.to_string()
+        } else {
+            debug!("Prepending 'https://' to website URL");
+            format!("https://{}", website)
+        };
+        let url = Url::parse(&website).map_err(|e| anyhow!("Invalid URL {}: {}", website, e))?;
+        let host = match (url.host_str(), url.port_or_known_default()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            _ => return Err(anyhow!("URL {} has no host", website)),
+        };
+
+        if self.config.respect_robots {
+            let mut path = url.path().to_string();
+            if let Some(query) = url.query() {
+                path.push('?');
+                path.push_str(query);
+            }
+            if !self.robots_rules(&url).await.allows(&path) {
+                return Err(anyhow!(
+                    "Not fetching {}: the site's robots.txt asks crawlers like {} to stay out of {}",
+                    website,
+                    self.config.user_agent,
+                    path
+                ));
+            }
+        }
+        self.take_request_slot(&host)?;
+
+        let mut resp = self
+            .client
+            .get(url.clone())
+            .header("User-Agent", &self.config.user_agent)
+            .send()
+            .await
+            .map_err(|e| anyhow!("Request error: {}", e))?;
+        debug!("Response status: {}", resp.status());
+
+        let (body, truncated) = self.read_capped(&mut resp).await?;
+        debug!("Response body length: {} (truncated: {})", body.len(), truncated);
+        Ok(FetchedPage {
+            url: url.to_string(),
+            html: String::from_utf8_lossy(&body).into_owned(),
+            truncated,
+        })
+    }
+
+    /// Count a request against `host`, or refuse if its minute is used up
+    fn take_request_slot(&self, host: &str) -> Result<(), Error> {
+        if self.config.per_host_rpm == 0 {
+            return Ok(());
+        }
+        let window = Duration::from_secs(60);
+        let now = Instant::now();
+        let mut requests = self.requests.lock().unwrap();
+        let recent = requests.entry(host.to_string()).or_default();
+        while recent.front().is_some_and(|start| now.duration_since(*start) >= window) {
+            recent.pop_front();
+        }
+        if recent.len() >= self.config.per_host_rpm as usize {
+            let wait = recent
+                .front()
+                .map(|start| window.saturating_sub(now.duration_since(*start)))
+                .unwrap_or(window);
+            return Err(anyhow!(
+                "Rate limit reached for {}: at most {} requests per minute; try again in {}s",
+                host,
+                self.config.per_host_rpm,
+                wait.as_secs().max(1)
+            ));
+        }
+        recent.push_back(now);
+        Ok(())
+    }
+
+    /// Read the body up to `max_bytes`, reporting whether any was left unread
+    async fn read_capped(&self, resp: &mut reqwest::Response) -> Result<(Vec<u8>, bool), Error> {
+        let max_bytes = self.config.max_bytes;
+        let mut body = Vec::new();
+        while let Some(chunk) = resp
+            .chunk()
+            .await
+            .map_err(|e| anyhow!("Body error: {}", e))?
+        {
+            let room = max_bytes - body.len();
+            if chunk.len() > room {
+                body.extend_from_slice(&chunk[..room]);
+                return Ok((body, true));
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok((body, false))
+    }
+
+    /// robots.txt rules for `url`'s origin, fetched once and cached
+    ///
+    /// A missing or unreachable robots.txt allows everything.
+    async fn robots_rules(&self, url: &Url) -> Arc<RobotsRules> {
+        let origin = url.origin().ascii_serialization();
+        if let Some(rules) = self.robots.lock().unwrap().get(&origin) {
+            return rules.clone();
+        }
+
+        let robots_url = format!("{}/robots.txt", origin);
+        let text = match self
+            .client
+            .get(&robots_url)
+            .header("User-Agent", &self.config.user_agent)
+            .send()
+            .await
+        {
+            Ok(mut resp) if resp.status().is_success() => match self.read_capped(&mut resp).await {
+                Ok((body, _)) => String::from_utf8_lossy(&body).into_owned(),
+                Err(_) => String::new(),
+            },
+            Ok(resp) => {
+                debug!("No robots.txt at {} ({})", robots_url, resp.status());
+                String::new()
+            }
+            Err(e) => {
+                debug!("Could not fetch {}: {}", robots_url, e);
+                String::new()
+            }
+        };
+
+        let rules = Arc::new(RobotsRules::parse(&text, &self.config.user_agent));
+        self.robots.lock().unwrap().insert(origin, rules.clone());
+        rules
+    }
+}
 
 #[async_trait::async_trait]
 impl AiTool for WebsiteTool {
@@ -17,12 +228,19 @@ impl AiTool for WebsiteTool {
         r#"Fetches a website.
 Parameters:
 - `website`: The URL of the website to fetch.
-- `render`: Which format to render the content in. Options are "html" or "md" (default is "md").
+- `render`: Which format to render the content in. Options are "text", "md" or "html" (default is "md"). Scripts and styles are always removed.
+- `include_title`: Whether to return the page title alongside the content (default is true).
 
 Note: The website must start with http:// or https://. If not, https:// will be prepended automatically.
+Sites are fetched politely: pages their robots.txt disallows are refused, each site may only be
+fetched a limited number of times per minute, and very large pages are cut off (`truncated` is then true).
 "#
     }
 
+    fn safety(&self) -> ToolSafety {
+        ToolSafety::Network
+    }
+
     fn schema(&self) -> Value {
         serde_json::json!({
             "type": "object",
@@ -33,32 +251,26 @@ Note: The website must start with http:// or https://. If not, https:// will be
                 },
                 "render": {
                     "type": "string",
-                    "description": "Format to render the content: 'html' or 'md' (default: 'md')"
+                    "enum": ["text", "md", "html"],
+                    "description": "Format to render the content: 'text', 'md' or 'html' (default: 'md')"
+                },
+                "include_title": {
+                    "type": "boolean",
+                    "description": "Also return the page title (default: true)"
                 }
             },
             "required": ["website"]
         })
     }
 
-    fn validate_params(&self, params: &Value) -> Result<(), Error> {
-        if !params.is_object() {
-            return Err(anyhow!("Parameters must be an object"));
-        }
-        if !params.get("website").is_some_and(|v| v.is_string()) {
-            return Err(anyhow!("Missing or invalid 'website' parameter"));
-        }
-        if let Some(render) = params.get("render") {
-            if !render.is_string() {
-                return Err(anyhow!("'render' must be a string"));
-            }
-        }
-        Ok(())
+    fn timeout(&self) -> Option<Duration> {
+        // A hanging site shouldn't stall the whole turn
+        Some(Duration::from_secs(15))
     }
 
     async fn execute(&self, params: Value) -> Result<Value, Error> {
         self.validate_params(&params)?;
 
-        let client = reqwest::Client::new();
         let website = params
             .get("website")
             .and_then(|v| v.as_str())
@@ -67,40 +279,199 @@ Note: The website must start with http:// or https://. If not, https:// will be
             .get("render")
             .and_then(|v| v.as_str())
             .unwrap_or("md");
+        let include_title = params
+            .get("include_title")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
 
-        if !website.starts_with("http://") && !website.starts_with("https://") {
-            debug!("Prepending 'https://' to website URL");
-            let website = format!("https://{}", website);
-            debug!("Final website URL: {}", website);
-        }
+        let page = self.fetch(website).await?;
+        let html = strip_hidden_elements(&page.html);
 
-        let resp = client
-            .get(website)
-            .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/114.0.0.0 Safari/537.36 Edg/114.0.1823.67a")
-            .send()
-            .await
-            .map_err(|e| anyhow!("Request error: {}", e))?;
+        let content = match render {
+            "html" => html.clone(),
+            "md" => {
+                let markdown = html2md::rewrite_html(&html, false);
+                debug!("Converted HTML to Markdown, length: {}", markdown.len());
+                markdown
+            }
+            "text" => readable_text(&html),
+            _ => {
+                return Err(anyhow!(
+                    "Invalid 'render' parameter, must be 'text', 'md' or 'html'"
+                ));
+            }
+        };
 
-        debug!("Response status: {}", resp.status());
+        let mut result = serde_json::json!({
+            "url": page.url,
+            "content": content,
+            "truncated": page.truncated,
+        });
+        if let Some(title) = page_title(&html).filter(|_| include_title) {
+            result["title"] = Value::String(title);
+        }
+        Ok(result)
+    }
+}
 
-        let body = resp
+/// Text of the page's `<title>`, falling back to its first `<h1>`
+pub(crate) fn page_title(html: &str) -> Option<String> {
+    let document = Html::parse_document(html);
+    ["title", "h1"].iter().find_map(|tag| {
+        let selector = Selector::parse(tag).ok()?;
+        let text = document
+            .select(&selector)
+            .next()?
             .text()
-            .await
-            .map_err(|e| anyhow!("Body error: {}", e))?;
+            .collect::<String>();
+        let text = text.trim();
+        (!text.is_empty()).then(|| text.to_string())
+    })
+}
 
-        debug!("Response body length: {}", body.len());
+/// Remove `<script>`, `<style>` and `<noscript>` elements, contents and all
+///
+/// An element cut off by truncation is removed up to the end of the page.
+pub(crate) fn strip_hidden_elements(html: &str) -> String {
+    // ASCII lowercasing keeps byte offsets lined up with `html`
+    let lower = html.to_ascii_lowercase();
+    let mut out = String::with_capacity(html.len());
+    let mut pos = 0;
 
-        match render {
-            "html" => Ok(serde_json::json!({ "content": body })),
-            "md" => {
-                let markdown = html2md::rewrite_html(&body, false);
-                debug!("Converted HTML to Markdown, length: {}", markdown.len());
-                Ok(serde_json::json!({ "content": markdown }))
+    while let Some((start, tag)) = HIDDEN_ELEMENTS
+        .iter()
+        .filter_map(|tag| find_open_tag(&lower, pos, tag).map(|start| (start, *tag)))
+        .min_by_key(|(start, _)| *start)
+    {
+        out.push_str(&html[pos..start]);
+        let close = format!("</{}", tag);
+        pos = match lower[start..].find(&close) {
+            Some(offset) => {
+                let close_start = start + offset;
+                lower[close_start..]
+                    .find('>')
+                    .map_or(html.len(), |end| close_start + end + 1)
+            }
+            None => html.len(),
+        };
+    }
+    out.push_str(&html[pos..]);
+    out
+}
+
+/// Position of the next `<tag` at or after `from` that opens that exact element
+fn find_open_tag(lower: &str, mut from: usize, tag: &str) -> Option<usize> {
+    let open = format!("<{}", tag);
+    while let Some(offset) = lower[from..].find(&open) {
+        let start = from + offset;
+        let next = lower.as_bytes().get(start + open.len()).copied();
+        if next.is_none_or(|c| c == b'>' || c == b'/' || c.is_ascii_whitespace()) {
+            return Some(start);
+        }
+        from = start + open.len();
+    }
+    None
+}
+
+/// The page body's text, one line per block element
+pub(crate) fn readable_text(html: &str) -> String {
+    let document = Html::parse_document(html);
+    let body = Selector::parse("body").ok().and_then(|selector| document.select(&selector).next());
+    let root = body.unwrap_or_else(|| document.root_element());
+
+    let mut text = String::new();
+    for node in root.descendants() {
+        if let Some(element) = node.value().as_element() {
+            if BLOCK_ELEMENTS.contains(&element.name()) {
+                text.push('\n');
+            }
+        } else if let Some(fragment) = node.value().as_text() {
+            text.push_str(fragment);
+        }
+    }
+
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// An Allow (`true`) or Disallow rule and the path prefix it covers
+type RobotsRule = (bool, String);
+
+/// The Allow and Disallow rules of a robots.txt that apply to one user agent
+///
+/// Rules are plain path prefixes; the longest matching rule wins, and Allow
+/// wins a tie.
+#[derive(Debug, Default)]
+struct RobotsRules {
+    /// `(allow, path prefix)` pairs
+    rules: Vec<RobotsRule>,
+}
+
+impl RobotsRules {
+    /// Rules from the groups naming `user_agent`, or from the `*` group if none do
+    fn parse(text: &str, user_agent: &str) -> Self {
+        let product = user_agent
+            .split(['/', ' '])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        // (agents, rules) for each group
+        let mut groups: Vec<(Vec<String>, Vec<RobotsRule>)> = Vec::new();
+        let mut in_agent_lines = false;
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    if !in_agent_lines {
+                        groups.push((Vec::new(), Vec::new()));
+                    }
+                    in_agent_lines = true;
+                    if let Some((agents, _)) = groups.last_mut() {
+                        agents.push(value.to_ascii_lowercase());
+                    }
+                }
+                key @ ("allow" | "disallow") => {
+                    in_agent_lines = false;
+                    // An empty Disallow allows everything
+                    if value.is_empty() {
+                        continue;
+                    }
+                    if let Some((_, rules)) = groups.last_mut() {
+                        rules.push((key == "allow", value.to_string()));
+                    }
+                }
+                _ => {}
             }
-            _ => Err(anyhow!(
-                "Invalid 'render' parameter, must be 'html' or 'md'"
-            )),
         }
+
+        let matching = |wanted: &dyn Fn(&str) -> bool| -> Vec<RobotsRule> {
+            groups
+                .iter()
+                .filter(|(agents, _)| agents.iter().any(|agent| wanted(agent)))
+                .flat_map(|(_, rules)| rules.iter().cloned())
+                .collect()
+        };
+        let mut rules = matching(&|agent| agent != "*" && agent == product);
+        if rules.is_empty() {
+            rules = matching(&|agent| agent == "*");
+        }
+        Self { rules }
+    }
+
+    fn allows(&self, path: &str) -> bool {
+        self.rules
+            .iter()
+            .filter(|(_, prefix)| path.starts_with(prefix.as_str()))
+            .max_by_key(|(allow, prefix)| (prefix.len(), *allow))
+            .is_none_or(|(allow, _)| *allow)
     }
 }
 
@@ -108,10 +479,37 @@ Note: The website must start with http:// or https://. If not, https:// will be
 mod tests {
     use super::*;
     use serde_json::json;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Serve each `(path, body)` route on a local port; other paths are 404s
+    async fn serve(routes: &'static [(&'static str, &'static str)]) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = [0u8; 1024];
+                let read = socket.read(&mut request).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&request[..read]);
+                let path = request.split_whitespace().nth(1).unwrap_or("/");
+                let (status, body) = match routes.iter().find(|(route, _)| *route == path) {
+                    Some((_, body)) => ("200 OK", *body),
+                    None => ("404 Not Found", ""),
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}", addr)
+    }
 
     #[test]
     fn test_tool_metadata() {
-        let tool = WebsiteTool;
+        let tool = WebsiteTool::default();
         
         assert_eq!(tool.name(), "website");
         assert!(!tool.description().is_empty());
@@ -125,7 +523,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_parameter_validation() {
-        let tool = WebsiteTool;
+        let tool = WebsiteTool::default();
         
         // Missing URL parameter
         let result = tool.execute(json!({})).await;
@@ -139,7 +537,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_url_validation() {
-        let tool = WebsiteTool;
+        let tool = WebsiteTool::default();
         
         // Invalid URLs should be rejected during parameter validation
         let invalid_urls = vec![
@@ -156,7 +554,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_valid_url_formats() {
-        let tool = WebsiteTool;
+        let tool = WebsiteTool::default();
         
         // Valid URLs (though they might not exist)
         let valid_urls = vec![
@@ -184,4 +582,125 @@ mod tests {
             }
         }
     }
+
+    #[tokio::test]
+    async fn test_invalid_params_report_schema_violations() {
+        let tool = WebsiteTool::default();
+
+        let result = tool.run(json!({"render": 1})).await;
+        assert!(!result.is_success());
+        let error = result.display_text();
+        assert!(error.contains("missing required field `website`"), "{}", error);
+        assert!(error.contains("`render` should be string, got integer"), "{}", error);
+
+        // Validation fails before any request is made
+        let error = tool.execute(json!("https://example.com")).await.unwrap_err().to_string();
+        assert!(error.contains("parameters should be object, got string"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_byte_cap_truncates_large_pages() {
+        let base = serve(&[(
+            "/big",
+            "<html><body><p>aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa\
+             aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa</p></body></html>",
+        )])
+        .await;
+        let tool = WebsiteTool::new(WebsiteToolConfig {
+            max_bytes: 40,
+            ..Default::default()
+        });
+
+        let result = tool
+            .execute(json!({"website": format!("{}/big", base), "render": "html"}))
+            .await
+            .unwrap();
+        assert_eq!(result["truncated"], true);
+        assert_eq!(result["content"].as_str().unwrap().len(), 40);
+
+        let tool = WebsiteTool::default();
+        let result = tool
+            .execute(json!({"website": format!("{}/big", base), "render": "html"}))
+            .await
+            .unwrap();
+        assert_eq!(result["truncated"], false);
+    }
+
+    #[tokio::test]
+    async fn test_robots_disallowed_path_is_politely_refused() {
+        let base = serve(&[
+            ("/robots.txt", "User-agent: *\nDisallow: /private\n"),
+            ("/private/page", "<html><body>secret</body></html>"),
+            ("/public", "<html><body>hello</body></html>"),
+        ])
+        .await;
+        let tool = WebsiteTool::default();
+
+        let error = tool
+            .execute(json!({"website": format!("{}/private/page", base)}))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("robots.txt asks crawlers"), "{}", error);
+
+        let result = tool
+            .execute(json!({"website": format!("{}/public", base), "render": "text"}))
+            .await
+            .unwrap();
+        assert_eq!(result["content"], "hello");
+
+        // Without robots.txt checks the same path is fetched
+        let tool = WebsiteTool::new(WebsiteToolConfig {
+            respect_robots: false,
+            ..Default::default()
+        });
+        let result = tool
+            .execute(json!({"website": format!("{}/private/page", base), "render": "text"}))
+            .await
+            .unwrap();
+        assert_eq!(result["content"], "secret");
+    }
+
+    #[tokio::test]
+    async fn test_requests_per_host_are_rate_limited() {
+        let base = serve(&[("/", "<html><body>hi</body></html>")]).await;
+        let tool = WebsiteTool::new(WebsiteToolConfig {
+            per_host_rpm: 2,
+            ..Default::default()
+        });
+
+        for _ in 0..2 {
+            tool.execute(json!({"website": format!("{}/", base)})).await.unwrap();
+        }
+        let error = tool
+            .execute(json!({"website": format!("{}/", base)}))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("at most 2 requests per minute"), "{}", error);
+    }
+
+    #[test]
+    fn test_text_rendering_drops_scripts_and_styles() {
+        let html = "<html><head><title> Docs </title><style>p { color: red }</style></head>\
+                    <body><SCRIPT type=\"text/javascript\">alert('hi')</SCRIPT>\
+                    <h1>Intro</h1><p>Read   the <a href=\"/x\">manual</a>.</p><noscript>Enable JS</noscript></body></html>";
+        let stripped = strip_hidden_elements(html);
+
+        assert_eq!(readable_text(&stripped), "Intro\nRead the manual.");
+        assert_eq!(page_title(&stripped).as_deref(), Some("Docs"));
+    }
+
+    #[test]
+    fn test_robots_rules_prefer_specific_agent_and_longest_match() {
+        let robots = "User-agent: *\nDisallow: /\n\nUser-agent: luts\nDisallow: /drafts\nAllow: /drafts/public\n";
+
+        let rules = RobotsRules::parse(robots, "luts/0.1.0");
+        assert!(rules.allows("/docs"));
+        assert!(!rules.allows("/drafts/secret"));
+        assert!(rules.allows("/drafts/public/post"));
+
+        let rules = RobotsRules::parse(robots, "OtherBot/1.0");
+        assert!(!rules.allows("/docs"));
+    }
 }
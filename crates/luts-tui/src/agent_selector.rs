@@ -48,6 +48,13 @@ impl AgentSelector {
         }
     }
 
+    /// Move the selection to the agent with this id, if it is listed
+    pub fn highlight(&mut self, agent_id: &str) {
+        if let Some(index) = self.agent_details.iter().position(|(id, _, _)| id == agent_id) {
+            self.agent_list.state.select(Some(index));
+        }
+    }
+
     pub fn handle_mouse_event(&mut self, mouse: MouseEvent) -> Result<()> {
         match mouse.kind {
             MouseEventKind::Down(_) => {
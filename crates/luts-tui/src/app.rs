@@ -11,7 +11,9 @@ use crate::{
     tool_activity::ToolActivityPanel,
 };
 use anyhow::Result;
-use luts_framework::agents::PersonalityAgentBuilder;
+use luts_framework::agents::{
+    Agent, ConversationPersistencePolicy, PersonalityAgentBuilder, PersonalityMatch,
+};
 use luts_core::llm::LLMService;
 use ratatui::{Terminal, backend::Backend};
 use std::sync::Arc;
@@ -45,6 +47,7 @@ pub struct App {
     data_dir: String,
     provider: String,
     initial_agent: Option<String>,
+    persistence: ConversationPersistencePolicy,
     needs_redraw: bool, // Track if we need to redraw
     _log_buffer: LogBuffer, // Keep reference to log buffer
 }
@@ -74,6 +77,8 @@ impl App {
 
         let mut conversation = Conversation::new(event_sender.clone());
         conversation.set_llm_service(llm_service.clone());
+        conversation.set_export_dir(std::path::Path::new(data_dir).join("exports"));
+        conversation.set_autosave_dir(std::path::Path::new(data_dir).join("autosaves"));
 
         Self {
             state: if initial_agent.is_some() {
@@ -93,24 +98,57 @@ impl App {
             data_dir: data_dir.to_string(),
             provider: provider.to_string(),
             initial_agent,
+            persistence: ConversationPersistencePolicy::default(),
             needs_redraw: true, // Initial draw needed
             _log_buffer: log_buffer,
         }
     }
 
+    /// Choose whether the partial text of interrupted streamed responses is kept
+    pub fn with_persist_partial_responses(mut self, enabled: bool) -> Self {
+        self.conversation.set_persist_partial_responses(enabled);
+        self
+    }
+
+    /// Always start a new conversation instead of offering to resume the last one
+    pub fn with_new_session(mut self, new_session: bool) -> Self {
+        self.conversation.set_new_session(new_session);
+        self
+    }
+
+    /// Choose what agents keep in their memory once a turn completes
+    pub fn with_persistence_policy(mut self, persistence: ConversationPersistencePolicy) -> Self {
+        self.persistence = persistence;
+        self
+    }
+
+    /// Create the agent with the given personality, storing turns by the app's policy
+    fn create_agent(&self, agent_id: &str) -> Result<Box<dyn Agent>> {
+        let mut agent =
+            PersonalityAgentBuilder::create_by_type(agent_id, &self.data_dir, &self.provider)?;
+        agent.set_persistence_policy(self.persistence)?;
+        Ok(agent)
+    }
+
     pub async fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
         info!("Starting LUTS TUI application");
 
         // If we have an initial agent, load it immediately
         if let Some(agent_id) = &self.initial_agent.clone() {
-            match PersonalityAgentBuilder::create_by_type(agent_id, &self.data_dir, &self.provider)
-            {
+            match self.create_agent(agent_id) {
                 Ok(agent) => {
+                    self.block_mode.use_agent_memory(&self.data_dir, agent.agent_id());
                     self.conversation.set_agent(agent);
                     self.state = AppState::Conversation;
                 }
                 Err(e) => {
                     error!("Failed to create initial agent {}: {}", agent_id, e);
+                    // Ask the user to pick between close matches instead of guessing
+                    if let PersonalityMatch::Ambiguous(candidates) =
+                        PersonalityAgentBuilder::resolve_personality(agent_id)
+                    {
+                        self.agent_selector.highlight(candidates[0]);
+                    }
                     self.state = AppState::AgentSelection;
                 }
             }
@@ -121,8 +159,24 @@ impl App {
             match self.event_handler.next_event().await? {
                 AppEvent::Key(key) => {
                     self.needs_redraw = true; // Key events usually need redraw
-                    // Check for global quit commands first
-                    if let Some(global_event) = handle_key_event(key) {
+                    // Esc or Ctrl+C stops the agent's response before anything else
+                    let is_cancel_key = matches!(key.code, crossterm::event::KeyCode::Esc)
+                        || matches!(key.code, crossterm::event::KeyCode::Char('c'))
+                            && key
+                                .modifiers
+                                .contains(crossterm::event::KeyModifiers::CONTROL);
+                    if self.state == AppState::Conversation
+                        && is_cancel_key
+                        && self.conversation.cancel_streaming()
+                    {
+                        debug!("Cancelled agent response");
+                    } else if self.state == AppState::Conversation
+                        && matches!(key.code, crossterm::event::KeyCode::Esc)
+                        && self.conversation.dismiss_overlay()
+                    {
+                        // Esc closes a popup or search before leaving the conversation
+                    } else if let Some(global_event) = handle_key_event(key) {
+                        // Global quit commands come next
                         if let AppEvent::Quit = global_event {
                             self.state = AppState::Quitting;
                             break;
@@ -208,13 +262,14 @@ impl App {
                                                     // Continue anyway with default setup
                                                 }
                                                 
-                                                // Pass agent and LLM service to context viewer
-                                                if let Some(agent) = self.conversation.agent() {
-                                                    viewer.set_agent(agent);
-                                                }
+                                                // Pass LLM service and agent to context viewer; the
+                                                // service comes first so the window fits its model
                                                 if let Some(llm_service) = self.conversation.llm_service() {
                                                     viewer.set_llm_service(llm_service);
                                                 }
+                                                if let Some(agent) = self.conversation.agent() {
+                                                    viewer.set_agent(agent);
+                                                }
                                                 // Pass current conversation history
                                                 let conversation_messages = self.conversation.get_message_history();
                                                 viewer.update_conversation_history(conversation_messages);
@@ -234,12 +289,12 @@ impl App {
                                                 error!("Failed to re-initialize context viewer with data dir: {}", e);
                                             }
                                             
-                                            if let Some(agent) = self.conversation.agent() {
-                                                viewer.set_agent(agent);
-                                            }
                                             if let Some(llm_service) = self.conversation.llm_service() {
                                                 viewer.set_llm_service(llm_service);
                                             }
+                                            if let Some(agent) = self.conversation.agent() {
+                                                viewer.set_agent(agent);
+                                            }
                                             let conversation_messages = self.conversation.get_message_history();
                                             viewer.update_conversation_history(conversation_messages);
                                         }
@@ -461,12 +516,9 @@ impl App {
                 AppEvent::AgentSelected(agent_id) => {
                     self.needs_redraw = true;
                     info!("Agent selected: {}", agent_id);
-                    match PersonalityAgentBuilder::create_by_type(
-                        &agent_id,
-                        &self.data_dir,
-                        &self.provider,
-                    ) {
+                    match self.create_agent(&agent_id) {
                         Ok(agent) => {
+                            self.block_mode.use_agent_memory(&self.data_dir, agent.agent_id());
                             self.conversation.set_agent(agent);
                             self.state = AppState::Conversation;
                         }
@@ -489,31 +541,11 @@ impl App {
                     }
                 }
                 
-                AppEvent::AgentResponseReceived(response) => {
-                    self.needs_redraw = true;
-                    debug!("Agent response received with {} tool calls", response.tool_calls.len());
-                    if let Err(e) = self.conversation.handle_agent_response(response).await {
-                        error!("Failed to handle agent response: {}", e);
-                    }
-                }
-                
-                AppEvent::AgentResponseError(error) => {
-                    self.needs_redraw = true;
-                    debug!("Agent response error: {}", error);
-                    self.conversation.handle_agent_error(error);
-                }
-                
                 AppEvent::AgentProcessingStarted => {
                     self.needs_redraw = true;
                     debug!("Agent processing started");
                     self.conversation.set_processing(true);
                 }
-                
-                AppEvent::AgentProcessingFinished => {
-                    self.needs_redraw = true;
-                    debug!("Agent processing finished");
-                    self.conversation.set_processing(false);
-                }
 
                 AppEvent::StreamingChunk(chunk) => {
                     self.needs_redraw = true;
@@ -642,6 +674,7 @@ impl App {
             }
         }
 
+        self.conversation.save_session().await;
         info!("LUTS TUI application exiting");
         Ok(())
     }
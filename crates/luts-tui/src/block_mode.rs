@@ -20,7 +20,7 @@ use ratatui::{
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::mpsc;
-use tracing::info;
+use tracing::{error, info};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum FocusedPanel {
@@ -30,8 +30,12 @@ enum FocusedPanel {
 }
 
 pub struct BlockMode {
-    _memory_manager: Arc<MemoryManager>,
+    memory_manager: Arc<MemoryManager>,
     memory_blocks: Vec<MemoryBlock>,
+    /// Whether the list shows pending proposals instead of committed blocks
+    reviewing_proposals: bool,
+    /// Committed blocks set aside while proposals are being reviewed
+    stashed_blocks: Vec<MemoryBlock>,
     focused_panel: FocusedPanel,
     block_list_state: ListState,
     scroll_state: ScrollbarState,
@@ -57,6 +61,7 @@ impl BlockMode {
             path: data_dir.join("memory.db"),
             namespace: "luts".to_string(),
             database: "memory".to_string(),
+            encryption_key: None,
         };
         let surreal_store = tokio::task::block_in_place(|| {
             tokio::runtime::Handle::current().block_on(async {
@@ -79,8 +84,10 @@ impl BlockMode {
         }
 
         Self {
-            _memory_manager: memory_manager,
+            memory_manager,
             memory_blocks,
+            reviewing_proposals: false,
+            stashed_blocks: Vec::new(),
             focused_panel: FocusedPanel::List,
             block_list_state,
             scroll_state: ScrollbarState::default(),
@@ -99,6 +106,112 @@ impl BlockMode {
         }
     }
 
+    /// Point the view at an agent's memory and review queue
+    ///
+    /// Uses the same files as the personality agents' memory tools, so
+    /// proposals made by the agent show up for review here.
+    pub fn use_agent_memory(&mut self, data_dir: &str, agent_id: &str) {
+        let agent_data_dir = PathBuf::from(data_dir).join("agents").join(agent_id);
+        let open = |file: &str, database: &str| {
+            let config = SurrealConfig::File {
+                path: agent_data_dir.join(file),
+                namespace: "luts".to_string(),
+                database: database.to_string(),
+                encryption_key: None,
+            };
+            tokio::task::block_in_place(|| {
+                tokio::runtime::Handle::current()
+                    .block_on(SurrealMemoryStore::new(config))
+            })
+        };
+
+        if let Err(e) = std::fs::create_dir_all(&agent_data_dir) {
+            error!("Failed to create agent data directory: {}", e);
+            return;
+        }
+        match (open("memory.db", "memory"), open("proposals.db", "proposals")) {
+            (Ok(store), Ok(queue)) => {
+                self.memory_manager =
+                    Arc::new(MemoryManager::new(store).with_review_queue(queue));
+                self.reviewing_proposals = false;
+                self.stashed_blocks.clear();
+                info!("Memory blocks view now using agent {}", agent_id);
+            }
+            (Err(e), _) | (_, Err(e)) => {
+                error!("Failed to open memory for agent {}: {}", agent_id, e);
+            }
+        }
+    }
+
+    /// Switch between committed blocks and the pending proposal queue
+    fn toggle_proposal_review(&mut self) {
+        if self.reviewing_proposals {
+            self.memory_blocks = std::mem::take(&mut self.stashed_blocks);
+            self.reviewing_proposals = false;
+        } else {
+            let manager = self.memory_manager.clone();
+            let proposals = tokio::task::block_in_place(|| {
+                tokio::runtime::Handle::current()
+                    .block_on(async move { manager.review_proposals(None).await })
+            });
+            match proposals {
+                Ok(proposals) => {
+                    self.stashed_blocks = std::mem::replace(&mut self.memory_blocks, proposals);
+                    self.reviewing_proposals = true;
+                    info!("Reviewing {} pending proposals", self.memory_blocks.len());
+                }
+                Err(e) => {
+                    error!("Failed to load proposals: {}", e);
+                    return;
+                }
+            }
+        }
+
+        self.block_list_state
+            .select(if self.memory_blocks.is_empty() { None } else { Some(0) });
+    }
+
+    /// Approve or reject the selected proposal and drop it from the list
+    fn resolve_selected_proposal(&mut self, approve: bool) {
+        let Some(selected) = self.block_list_state.selected() else {
+            return;
+        };
+        let Some(block) = self.memory_blocks.get(selected) else {
+            return;
+        };
+
+        let id = block.id().clone();
+        let manager = self.memory_manager.clone();
+        let result = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(async move {
+                if approve {
+                    manager.approve(&id).await.map(|_| ())
+                } else {
+                    manager.reject(&id).await.map(|_| ())
+                }
+            })
+        });
+
+        match result {
+            Ok(()) => {
+                let block = self.memory_blocks.remove(selected);
+                if approve {
+                    info!("Approved proposal {}", block.id());
+                    self.stashed_blocks.push(block);
+                } else {
+                    info!("Rejected proposal {}", block.id());
+                }
+
+                if self.memory_blocks.is_empty() {
+                    self.block_list_state.select(None);
+                } else if selected >= self.memory_blocks.len() {
+                    self.block_list_state.select(Some(self.memory_blocks.len() - 1));
+                }
+            }
+            Err(e) => error!("Failed to resolve proposal: {}", e),
+        }
+    }
+
     pub fn handle_mouse_event(&mut self, mouse: MouseEvent) -> Result<()> {
         match mouse.kind {
             MouseEventKind::Down(_) => {
@@ -169,6 +282,23 @@ impl BlockMode {
                 self.create_dialog_input.clear();
                 self.create_dialog_type = BlockType::Message;
             }
+            KeyCode::Char('p')
+                if key
+                    .modifiers
+                    .contains(crossterm::event::KeyModifiers::CONTROL) =>
+            {
+                self.toggle_proposal_review();
+            }
+            KeyCode::Char('a')
+                if self.reviewing_proposals && self.focused_panel == FocusedPanel::List =>
+            {
+                self.resolve_selected_proposal(true);
+            }
+            KeyCode::Char('x')
+                if self.reviewing_proposals && self.focused_panel == FocusedPanel::List =>
+            {
+                self.resolve_selected_proposal(false);
+            }
             KeyCode::Char('r')
                 if key
                     .modifiers
@@ -624,6 +754,8 @@ impl BlockMode {
                  Ctrl+N     - Create new memory block\n\
                  Ctrl+S     - Save all blocks to storage\n\
                  Ctrl+R     - Refresh blocks from storage\n\
+                 Ctrl+P     - Review blocks proposed by the agent\n\
+                 a / x      - Approve / reject selected proposal\n\
                  F2         - Change block type (in create dialog)\n\
                  \n\
                  Memory Block Types:\n\
@@ -719,11 +851,17 @@ impl BlockMode {
             Style::default().fg(Color::Gray)
         };
 
+        let title = if self.reviewing_proposals {
+            "Pending Proposals (a: approve, x: reject)"
+        } else {
+            "Memory Blocks"
+        };
+
         let list = List::new(items)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .title("Memory Blocks")
+                    .title(title)
                     .border_style(style),
             )
             .style(Style::default().fg(Color::White))
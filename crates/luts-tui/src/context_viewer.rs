@@ -17,7 +17,9 @@ use luts_core::{
         },
     },
     llm::LLMService,
-    memory::{SurrealMemoryStore, SurrealConfig, MemoryManager},
+    memory::{
+        EmbeddingConfig, EmbeddingServiceFactory, MemoryManager, SurrealConfig, SurrealMemoryStore,
+    },
     utils::tokens::TokenManager,
 };
 use ratatui::{
@@ -30,7 +32,7 @@ use ratatui::{
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::{RwLock, mpsc};
-use tracing::info;
+use tracing::{info, warn};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum FocusedPanel {
@@ -189,18 +191,14 @@ impl ContextViewer {
     fn initialize_context_manager(&mut self) {
         if self.agent.is_some() {
             let data_dir = PathBuf::from(&self.data_dir);
-            let token_manager = Arc::new(RwLock::new(TokenManager::new(data_dir)));
-
-            let context_config = ContextWindowConfig {
-                max_total_tokens: 8000,
-                core_block_tokens: 3000,
-                conversation_tokens: 3000,
-                dynamic_memory_tokens: 2000,
-                max_dynamic_blocks: 10,
-                min_relevance_score: 0.3,
-                auto_manage: true,
-                update_interval: 30,
-            };
+            // The window is sized and counted for the model when we know which one it is
+            let provider = self
+                .llm_service
+                .as_ref()
+                .map(|llm_service| llm_service.provider().to_string())
+                .unwrap_or_default();
+            let token_manager = Arc::new(RwLock::new(TokenManager::new(data_dir).with_model(&provider)));
+
 
             let core_config = CoreBlockConfig {
                 total_token_budget: 3000,
@@ -208,22 +206,42 @@ impl ContextViewer {
                 auto_update_enabled: true,
                 min_active_blocks: 3,
                 max_active_blocks: 8,
+                ..Default::default()
             };
 
-            let context_manager = ContextWindowManager::new(
-                &self.user_id,
-                &self.session_id,
-                self.memory_manager.clone(),
-                token_manager,
-                Some(context_config),
-                Some(core_config.clone()),
-            );
-
-            // Initialize core block manager
-            let mut core_block_manager = CoreBlockManager::new(&self.user_id, Some(core_config));
-            core_block_manager
-                .initialize()
-                .expect("Failed to initialize core blocks");
+            // Both managers load core blocks from, and save them to, the memory store
+            let mut core_block_manager = CoreBlockManager::new(&self.user_id, Some(core_config.clone()))
+                .with_memory_manager(self.memory_manager.clone());
+            let mut context_manager = tokio::task::block_in_place(|| {
+                tokio::runtime::Handle::current().block_on(async {
+                    core_block_manager
+                        .initialize()
+                        .await
+                        .expect("Failed to initialize core blocks");
+                    ContextWindowManager::new(
+                        &self.user_id,
+                        &self.session_id,
+                        &provider,
+                        self.memory_manager.clone(),
+                        token_manager,
+                        None,
+                        Some(core_config),
+                    )
+                    .await
+                })
+            });
+            // Diversified selection compares blocks by embedding; without a
+            // service it picks by relevance
+            match std::env::var("OPENAI_API_KEY") {
+                Ok(api_key) => match EmbeddingServiceFactory::create(EmbeddingConfig {
+                    api_key: Some(api_key),
+                    ..Default::default()
+                }) {
+                    Ok(embedding_service) => context_manager.set_embedding_service(embedding_service),
+                    Err(e) => warn!("Failed to create the embedding service: {}", e),
+                },
+                Err(_) => info!("OPENAI_API_KEY not set, diversified selection falls back to relevance"),
+            }
 
             self.context_manager = Some(context_manager);
             self.core_block_manager = Some(core_block_manager);
@@ -413,8 +431,16 @@ impl ContextViewer {
     fn save_current_edit(&mut self) {
         if let EditMode::EditingCoreBlock(core_type) = self.edit_mode {
             if let Some(manager) = &mut self.core_block_manager {
-                match manager.update_block(core_type, self.edit_content.clone()) {
-                    Ok(()) => {
+                let content = self.edit_content.clone();
+                let result = tokio::task::block_in_place(|| {
+                    tokio::runtime::Handle::current()
+                        .block_on(manager.update_block(core_type, content))
+                });
+                match result {
+                    Ok(warning) => {
+                        if let Some(warning) = warning {
+                            info!("{}", warning);
+                        }
                         info!("Saved changes to {:?} core block", core_type);
                         self.needs_refresh = true;
                         self.exit_edit_mode();
@@ -655,6 +681,11 @@ impl ContextViewer {
     }
 
     fn render_header(&mut self, frame: &mut Frame<'_>, area: Rect) {
+        let window_size = self
+            .context_manager
+            .as_ref()
+            .map(|manager| manager.config().max_total_tokens)
+            .unwrap_or(ContextWindowConfig::default().max_total_tokens);
         let (total_tokens, max_tokens, utilization, active_blocks, dynamic_count, agent_status) =
             if let Some(stats) = &self.cached_stats {
                 (
@@ -667,9 +698,9 @@ impl ContextViewer {
                 )
             } else if self.agent.is_some() {
                 // Agent is loaded but context not yet generated
-                (0, 8000, 0.0, 0, 0, "Agent Ready (F5 to refresh)")
+                (0, window_size, 0.0, 0, 0, "Agent Ready (F5 to refresh)")
             } else {
-                (0, 8000, 0.0, 0, 0, "No Agent Selected")
+                (0, window_size, 0.0, 0, 0, "No Agent Selected")
             };
 
         let content = vec![Line::from(vec![
@@ -1225,6 +1256,31 @@ impl ContextViewer {
         }
     }
 
+    /// One line per core block that is over its per-block budget
+    fn core_block_budget_summary(&self) -> String {
+        let over_budget: Vec<String> = self
+            .core_block_manager
+            .as_ref()
+            .map(|manager| manager.budget_report())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|budget| budget.is_over_budget())
+            .map(|budget| {
+                format!(
+                    "• {:?}: {} / {} tokens (over budget)",
+                    budget.core_type,
+                    budget.used_tokens,
+                    budget.allotted_tokens.unwrap_or_default()
+                )
+            })
+            .collect();
+        if over_budget.is_empty() {
+            "• All core blocks are within their budgets".to_string()
+        } else {
+            over_budget.join("\n")
+        }
+    }
+
     fn render_token_analysis_detail(&mut self, frame: &mut Frame<'_>, area: Rect) {
         let content = if let Some(stats) = &self.cached_stats {
             format!(
@@ -1243,6 +1299,8 @@ impl ContextViewer {
                 • Active Core Blocks: {}\n\
                 • Dynamic Blocks: {}\n\
                 • Efficiency: {:.1}% utilization\n\n\
+                Core Block Budgets:\n\
+                {}\n\n\
                 Recommendations:\n\
                 {}",
                 stats.max_tokens,
@@ -1260,6 +1318,7 @@ impl ContextViewer {
                 stats.core_block_stats.active_blocks,
                 stats.dynamic_blocks_count,
                 stats.utilization,
+                self.core_block_budget_summary(),
                 if stats.utilization > 90.0 {
                     "• Consider reducing conversation history\n• Deactivate non-essential core blocks\n• Increase relevance threshold for dynamic blocks"
                 } else if stats.utilization < 50.0 {
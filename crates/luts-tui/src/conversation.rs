@@ -1,12 +1,21 @@
 //! Conversation TUI component for chatting with agents
 
 use crate::{components::show_popup, events::AppEvent, markdown::SimpleMarkdownRenderer};
-use anyhow::Result;
+use crate::conversation_session::{ConversationSession, save_message_count};
+use crate::transcript_export::{self, EXPORT_FORMATS};
+use crate::transcript_search::{self, TranscriptSearch};
+use anyhow::{Result, anyhow};
 use crossterm::event::{KeyCode, KeyEvent, MouseEvent, MouseEventKind};
 use futures_util::StreamExt;
 use luts_framework::agents::{Agent, AgentMessage};
+use luts_framework::llm::conversation::ExportSettings;
+use luts_framework::llm::conversation::auto_save::AutoSaveData;
+use luts_framework::llm::InternalChatMessage as AgentChatMessage;
+use luts_framework::llm::streaming::{ChunkType as AgentChunkType, ResponseChunk as AgentChunk};
 use luts_core::llm::{InternalChatMessage, LLMService};
-use luts_core::streaming::{ChunkType, ResponseStreamManager};
+use luts_core::{EditType, SegmentEdit};
+use luts_core::streaming::{ChunkType, ResponseStreamManager, StreamEvent, TypingIndicator, TypingStatus};
+use luts_core::tools::ToolResult;
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
@@ -17,12 +26,44 @@ use ratatui::{
         ScrollbarState, Wrap,
     },
 };
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tokio::sync::mpsc;
-use tracing::{debug, info};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, info, warn};
 use tui_textarea::TextArea;
 
+/// Convert a chunk streamed by an agent into the chunk type the UI renders
+///
+/// Agents stream `luts_llm` chunks while the UI events still carry the
+/// `luts_core` ones; the two have the same fields.
+fn to_ui_chunk(chunk: AgentChunk) -> luts_framework::streaming::ResponseChunk {
+    let chunk_type = match chunk.chunk_type {
+        AgentChunkType::Text => ChunkType::Text,
+        AgentChunkType::ToolCall => ChunkType::ToolCall,
+        AgentChunkType::ToolResponse => ChunkType::ToolResponse,
+        AgentChunkType::Reasoning => ChunkType::Reasoning,
+        AgentChunkType::Error => ChunkType::Error,
+        AgentChunkType::Status => ChunkType::Status,
+        AgentChunkType::Complete => ChunkType::Complete,
+    };
+    luts_framework::streaming::ResponseChunk {
+        id: chunk.id,
+        sequence: chunk.sequence,
+        content: chunk.content,
+        is_final: chunk.is_final,
+        timestamp: chunk.timestamp,
+        chunk_type,
+        metadata: luts_core::streaming::manager::ChunkMetadata {
+            token_count: chunk.metadata.token_count,
+            processing_time_ms: chunk.metadata.processing_time_ms,
+            model: chunk.metadata.model,
+            confidence: chunk.metadata.confidence,
+            custom: chunk.metadata.custom,
+        },
+    }
+}
+
 /// Wrap text to fit within a specified width, breaking at word boundaries when possible
 fn wrap_text(text: &str, width: usize) -> Vec<String> {
     if width == 0 {
@@ -70,6 +111,26 @@ fn spans_to_text(spans: &[Span]) -> String {
     spans.iter().map(|span| span.content.as_ref()).collect()
 }
 
+/// Sender name of messages typed by the user
+const USER_SENDER: &str = "You";
+
+/// Drop the last `user_turns` user messages from `history`, with everything after them
+fn drop_last_user_turns<M>(history: &mut Vec<M>, user_turns: usize, is_user: fn(&M) -> bool) {
+    let Some(nth_from_end) = user_turns.checked_sub(1) else {
+        return;
+    };
+    let cut = history
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(_, entry)| is_user(entry))
+        .nth(nth_from_end)
+        .map(|(idx, _)| idx);
+    if let Some(idx) = cut {
+        history.truncate(idx);
+    }
+}
+
 #[derive(Clone)]
 pub struct ChatMessage {
     pub sender: String,
@@ -84,6 +145,12 @@ pub struct ChatMessage {
     // Streaming state
     pub is_streaming: bool,
     pub streaming_complete: bool,
+    /// The response was cut off by a cancellation or error
+    pub incomplete: bool,
+    /// The user cancelled the response; it is also `incomplete`
+    pub cancelled: bool,
+    /// Answer text arrived since the last reasoning chunk
+    reasoning_interrupted: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -115,6 +182,9 @@ impl ChatMessage {
             cached_width: None,
             is_streaming: false,
             streaming_complete: false,
+            incomplete: false,
+            cancelled: false,
+            reasoning_interrupted: false,
         }
     }
 
@@ -132,6 +202,9 @@ impl ChatMessage {
             cached_width: None,
             is_streaming: true,
             streaming_complete: false,
+            incomplete: false,
+            cancelled: false,
+            reasoning_interrupted: false,
         }
     }
 
@@ -139,10 +212,28 @@ impl ChatMessage {
     pub fn append_chunk(&mut self, chunk_content: &str, chunk_type: &ChunkType) {
         match chunk_type {
             ChunkType::Text => {
-                self.content.push_str(chunk_content);
+                // Drop the blank lines models leave between their reasoning and the answer
+                let text = if self.content.is_empty() && self.reasoning.is_some() {
+                    chunk_content.trim_start()
+                } else {
+                    chunk_content
+                };
+                self.content.push_str(text);
+                self.reasoning_interrupted = self.reasoning.is_some();
                 self.cached_lines = None; // Invalidate cache
                 self.cached_width = None; // Invalidate width cache
             }
+            ChunkType::Reasoning => {
+                let reasoning = self.reasoning.get_or_insert_with(String::new);
+                // Reasoning that resumes after some of the answer starts a new paragraph
+                if self.reasoning_interrupted && !reasoning.is_empty() {
+                    reasoning.push_str("\n\n");
+                }
+                reasoning.push_str(chunk_content);
+                self.reasoning_interrupted = false;
+                self.cached_lines = None;
+                self.cached_width = None;
+            }
             ChunkType::ToolCall => {
                 // Parse tool call information from chunk_content
                 if let Some(tool_call) = self.parse_tool_call_chunk(chunk_content) {
@@ -234,33 +325,15 @@ impl ChatMessage {
 
     /// Parse tool result from chunk content and return both result and status
     fn parse_tool_result_chunk(&self, chunk_content: &str) -> Option<(String, ToolStatus)> {
-        // Handle formatted tool results
-        if chunk_content.starts_with("✅ Tool result: ") {
-            let result = chunk_content.replace("✅ Tool result: ", "");
-            return Some((result, ToolStatus::Completed));
-        }
-
-        // Handle error results
-        if chunk_content.starts_with("❌ Tool error: ") {
-            let error = chunk_content.replace("❌ Tool error: ", "");
-            return Some((error.clone(), ToolStatus::Failed(error)));
-        }
-
-        // Handle JSON-structured tool results
-        if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(chunk_content) {
-            if let Some(result_obj) = parsed.as_object() {
-                if let Some(result) = result_obj.get("tool_result") {
-                    let result_str =
-                        serde_json::to_string(result).unwrap_or_else(|_| chunk_content.to_string());
-
-                    // Check if there's an error field
-                    if let Some(error) = result_obj.get("error").and_then(|v| v.as_str()) {
-                        return Some((result_str, ToolStatus::Failed(error.to_string())));
-                    } else {
-                        return Some((result_str, ToolStatus::Completed));
-                    }
-                }
-            }
+        // Tool response chunks carry a ToolResult envelope
+        if let Some(envelope) = ToolResult::from_json(chunk_content) {
+            let text = envelope.display_text();
+            let status = if envelope.is_success() {
+                ToolStatus::Completed
+            } else {
+                ToolStatus::Failed(text.clone())
+            };
+            return Some((text, status));
         }
 
         // Return raw content as fallback (assume success)
@@ -280,6 +353,9 @@ impl ChatMessage {
             cached_width: None,
             is_streaming: false,
             streaming_complete: false,
+            incomplete: false,
+            cancelled: false,
+            reasoning_interrupted: false,
         }
     }
 
@@ -299,6 +375,11 @@ impl ChatMessage {
         self.cached_width = None; // Also invalidate width cache
     }
 
+    /// Whether the user typed this message
+    pub fn is_from_user(&self) -> bool {
+        self.sender == USER_SENDER
+    }
+
     pub fn get_or_render_lines_with_width(
         &mut self,
         markdown_renderer: &SimpleMarkdownRenderer,
@@ -309,7 +390,7 @@ impl ChatMessage {
             let mut lines = vec![];
 
             // Header line
-            let sender_style = if self.sender == "You" {
+            let sender_style = if self.sender == USER_SENDER {
                 Style::default().fg(Color::Cyan)
             } else if self.sender == "System" {
                 Style::default().fg(Color::Red)
@@ -317,14 +398,27 @@ impl ChatMessage {
                 Style::default().fg(Color::Green)
             };
 
-            let header = Line::from(vec![
+            let mut header = vec![
                 Span::styled(
                     format!("[{}] ", self.timestamp),
                     Style::default().fg(Color::Gray),
                 ),
                 Span::styled(format!("{}: ", self.sender), sender_style),
-            ]);
-            lines.push(header);
+            ];
+            if self.incomplete {
+                let label = if self.cancelled {
+                    "(cancelled)"
+                } else {
+                    "(incomplete)"
+                };
+                header.push(Span::styled(
+                    label.to_string(),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::ITALIC),
+                ));
+            }
+            lines.push(Line::from(header));
 
             // Show reasoning if present and toggled on
             if let Some(reasoning) = &self.reasoning {
@@ -399,11 +493,11 @@ impl ChatMessage {
 
             // Main content with width-aware wrapping
             if self.is_markdown {
-                let markdown_text = markdown_renderer.render(&self.content);
+                let markdown_text = markdown_renderer.render_with_width(&self.content, width);
                 // Process each markdown line and wrap if necessary
                 for line in markdown_text.lines {
                     let line_text = spans_to_text(&line.spans);
-                    if line_text.len() > width {
+                    if line_text.chars().count() > width {
                         // Line is too long, need to wrap
                         let wrapped_lines = wrap_text(&line_text, width);
                         for wrapped_line in wrapped_lines {
@@ -462,12 +556,44 @@ pub struct Conversation {
     // Streaming with ResponseStreamManager
     stream_manager: Arc<ResponseStreamManager>,
     current_streaming_message_idx: Option<usize>,
+    /// Messages exchanged with the LLM service, sent as context with each request
+    history: Vec<InternalChatMessage>,
+    /// Index in `history` of the in-flight response, once it has text
+    current_history_idx: Option<usize>,
+    /// Save streamed text to `history` as it arrives, so an interrupted
+    /// response keeps what was received
+    persist_partial_responses: bool,
+    /// Stream manager session of the in-flight response, for cancelling it
+    current_stream_session: Option<String>,
+    /// Task running the in-flight response, aborted to cancel it
+    agent_stream_task: Option<tokio::task::AbortHandle>,
+    /// Stream manager events, read for progress updates
+    stream_events: broadcast::Receiver<StreamEvent>,
+    /// Latest typing indicator of the in-flight response
+    typing_indicator: Option<TypingIndicator>,
+    /// User message being edited in the input box, resent on Enter
+    editing_message_idx: Option<usize>,
+    /// Edits made to sent messages, oldest first
+    edit_history: Vec<SegmentEdit>,
     /// Streaming state
     is_streaming: bool,
     /// Spinner for tool execution
     spinner_frame: usize,
     /// Spinner frames
     spinner_frames: [char; 7],
+    /// Search within the chat history
+    search: TranscriptSearch,
+    /// Directory transcripts are exported to
+    export_dir: PathBuf,
+    export_popup: Option<ExportPopup>,
+    /// Where conversations are auto-saved; `None` turns saving off
+    autosave_dir: Option<PathBuf>,
+    /// Start every conversation fresh instead of offering to resume one
+    new_session: bool,
+    /// Auto-saved session of the current agent
+    session: Option<ConversationSession>,
+    /// Last saved session of the agent, offered for resuming, with its agent ID
+    resume_offer: Option<(String, AutoSaveData)>,
     chat_area: Option<Rect>, // Store chat area for mouse handling
 }
 
@@ -477,6 +603,15 @@ enum FocusedComponent {
     History,
 }
 
+/// Popup of a transcript export
+#[derive(Debug, Clone, PartialEq)]
+enum ExportPopup {
+    /// Asking which format to export to
+    ChooseFormat,
+    /// Outcome of the export, shown until a key is pressed
+    Outcome { title: &'static str, text: String },
+}
+
 impl Conversation {
     pub fn new(event_sender: mpsc::UnboundedSender<AppEvent>) -> Self {
         let mut textarea = TextArea::default();
@@ -488,6 +623,8 @@ impl Conversation {
         );
 
         let rat_skin = SimpleMarkdownRenderer::default();
+        let stream_manager = Arc::new(ResponseStreamManager::new());
+        let stream_events = stream_manager.subscribe_to_events();
 
         Self {
             agent: None,
@@ -502,17 +639,36 @@ impl Conversation {
             scroll_state: ScrollbarState::default(),
             scroll_offset: 0,
             // Initialize streaming components
-            stream_manager: Arc::new(ResponseStreamManager::new()),
+            stream_manager,
             current_streaming_message_idx: None,
+            history: Vec::new(),
+            current_history_idx: None,
+            persist_partial_responses: true,
+            current_stream_session: None,
+            agent_stream_task: None,
+            stream_events,
+            typing_indicator: None,
+            editing_message_idx: None,
+            edit_history: Vec::new(),
             is_streaming: false,
             spinner_frame: 0,
             spinner_frames: ['✴', '✦', '✶', '✺', '✶', '✦', '✴'],
+            search: TranscriptSearch::default(),
+            export_dir: PathBuf::from("./data/exports"),
+            export_popup: None,
+            autosave_dir: None,
+            new_session: false,
+            session: None,
+            resume_offer: None,
             chat_area: None,
         }
     }
 
     pub fn set_agent(&mut self, agent: Box<dyn Agent>) {
         info!("Setting agent: {} ({})", agent.name(), agent.agent_id());
+        if let Some(session) = self.session.take() {
+            session.save_in_background(&self.messages);
+        }
 
         // Add welcome message
         let welcome_msg = ChatMessage::new(
@@ -530,7 +686,180 @@ impl Conversation {
             self.scroll_to_bottom();
         }
 
+        let agent_id = agent.agent_id().to_string();
         self.agent = Some(Arc::new(RwLock::new(agent)));
+        self.open_session(&agent_id);
+    }
+
+    /// Auto-save conversations under `autosave_dir`, one directory per agent
+    pub fn set_autosave_dir(&mut self, autosave_dir: PathBuf) {
+        self.autosave_dir = Some(autosave_dir);
+    }
+
+    /// Choose whether selecting an agent offers to resume its last conversation
+    /// (the default) or always starts a new one
+    pub fn set_new_session(&mut self, new_session: bool) {
+        self.new_session = new_session;
+    }
+
+    /// Open a session with the agent, offering to resume its last one
+    fn open_session(&mut self, agent_id: &str) {
+        let Some(autosave_dir) = self.autosave_dir.clone() else {
+            return;
+        };
+
+        let latest = if self.new_session {
+            Ok(None)
+        } else {
+            tokio::task::block_in_place(|| {
+                tokio::runtime::Handle::current()
+                    .block_on(ConversationSession::latest(&autosave_dir, agent_id))
+            })
+        };
+        match latest {
+            Ok(Some(save)) => self.resume_offer = Some((agent_id.to_string(), save)),
+            Ok(None) => self.start_session(&autosave_dir, agent_id),
+            Err(e) => {
+                warn!("Failed to look for a saved conversation: {}", e);
+                self.start_session(&autosave_dir, agent_id);
+            }
+        }
+    }
+
+    fn start_session(&mut self, autosave_dir: &std::path::Path, agent_id: &str) {
+        let session = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current()
+                .block_on(ConversationSession::start(autosave_dir, agent_id))
+        });
+        match session {
+            Ok(session) => self.session = Some(session),
+            Err(e) => warn!("Auto-save is off for this conversation: {}", e),
+        }
+    }
+
+    /// Keys pressed while offering to resume the last conversation
+    fn handle_resume_key(&mut self, key: KeyEvent) -> Result<()> {
+        if !matches!(key.code, KeyCode::Char('y') | KeyCode::Enter) {
+            self.decline_resume();
+            return Ok(());
+        }
+        let (Some((agent_id, save)), Some(autosave_dir)) =
+            (self.resume_offer.take(), self.autosave_dir.clone())
+        else {
+            return Ok(());
+        };
+        let resumed = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current()
+                .block_on(ConversationSession::resume(&autosave_dir, &agent_id, &save))
+        });
+        match resumed {
+            Ok((session, messages)) => {
+                self.session = Some(session);
+                self.restore_messages(messages);
+            }
+            Err(e) => {
+                warn!("Failed to resume the last conversation: {}", e);
+                self.start_session(&autosave_dir, &agent_id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Start a new session instead of the one offered; false if none was
+    fn decline_resume(&mut self) -> bool {
+        let Some((agent_id, _)) = self.resume_offer.take() else {
+            return false;
+        };
+        if let Some(autosave_dir) = self.autosave_dir.clone() {
+            self.start_session(&autosave_dir, &agent_id);
+        }
+        true
+    }
+
+    /// Show a resumed conversation and send it as context again
+    ///
+    /// Context starts at the first user message, leaving out the greeting.
+    /// The agent gets the same history in place of its own.
+    fn restore_messages(&mut self, messages: Vec<ChatMessage>) {
+        let first_user_message = messages
+            .iter()
+            .position(ChatMessage::is_from_user)
+            .unwrap_or(messages.len());
+        self.history = messages[first_user_message..]
+            .iter()
+            .filter(|msg| msg.sender != "System")
+            .map(|msg| {
+                if msg.is_from_user() {
+                    InternalChatMessage::User {
+                        content: msg.content.clone(),
+                    }
+                } else {
+                    InternalChatMessage::Assistant {
+                        content: msg.content.clone(),
+                        tool_responses: None,
+                    }
+                }
+            })
+            .collect();
+        self.current_history_idx = None;
+        let agent_history = self
+            .history
+            .iter()
+            .map(|entry| match entry {
+                InternalChatMessage::User { content } => AgentChatMessage::User {
+                    content: content.clone(),
+                },
+                _ => AgentChatMessage::Assistant {
+                    content: entry.content().to_string(),
+                    tool_responses: None,
+                },
+            })
+            .collect();
+        if let Err(e) = self.set_agent_history(agent_history) {
+            warn!("Failed to restore the agent's history: {}", e);
+        }
+        self.messages = messages;
+        self.search.close();
+        self.scroll_to_bottom();
+    }
+
+    /// Replace the agent's conversation history, if there is an agent
+    fn set_agent_history(&self, history: Vec<AgentChatMessage>) -> Result<()> {
+        let Some(agent) = &self.agent else {
+            return Ok(());
+        };
+        agent
+            .try_write()
+            .map_err(|_| anyhow!("The agent is still handling a message"))?
+            .set_history(history)
+    }
+
+    /// Auto-save the conversation, if saving is on
+    fn autosave(&self) {
+        if let Some(session) = &self.session {
+            session.save_in_background(&self.messages);
+        }
+    }
+
+    /// Save the conversation before the application exits
+    pub async fn save_session(&self) {
+        let Some(session) = &self.session else {
+            return;
+        };
+        if let Err(e) = session.save_on_exit(&self.messages).await {
+            warn!("Failed to save the conversation on exit: {}", e);
+        }
+    }
+
+    /// Choose whether streamed responses are saved to the history as they
+    /// arrive (the default) or only once they complete
+    pub fn set_persist_partial_responses(&mut self, enabled: bool) {
+        self.persist_partial_responses = enabled;
+    }
+
+    /// Choose the directory Ctrl+E exports the transcript to
+    pub fn set_export_dir(&mut self, export_dir: PathBuf) {
+        self.export_dir = export_dir;
     }
 
     /// Set the LLM service for direct streaming (bypassing agent)
@@ -580,7 +909,30 @@ impl Conversation {
     }
 
     pub fn handle_key_event(&mut self, key: KeyEvent) -> Result<()> {
+        if self.resume_offer.is_some() {
+            return self.handle_resume_key(key);
+        }
+        if self.export_popup.is_some() {
+            return self.handle_export_key(key);
+        }
+        if self.search.editing {
+            return self.handle_search_key(key);
+        }
         match key.code {
+            KeyCode::Char('e')
+                if key
+                    .modifiers
+                    .contains(crossterm::event::KeyModifiers::CONTROL) =>
+            {
+                self.export_popup = Some(ExportPopup::ChooseFormat);
+            }
+            KeyCode::Char('f')
+                if key
+                    .modifiers
+                    .contains(crossterm::event::KeyModifiers::CONTROL) =>
+            {
+                self.search.open();
+            }
             KeyCode::Tab => {
                 self.focused_component = match self.focused_component {
                     FocusedComponent::Input => FocusedComponent::History,
@@ -599,6 +951,103 @@ impl Conversation {
         Ok(())
     }
 
+    /// Keys typed while the search prompt is open
+    fn handle_search_key(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Enter if !self.search.query.trim().is_empty() => {
+                self.run_search();
+                // n/N step through the matches from the history
+                self.focused_component = FocusedComponent::History;
+                self.update_focus_styling();
+            }
+            KeyCode::Esc => {
+                self.search.close();
+            }
+            KeyCode::Backspace => {
+                self.search.query.pop();
+            }
+            KeyCode::Char(c) => {
+                self.search.query.push(c);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Search the chat history for the query in the search prompt
+    fn run_search(&mut self) {
+        let query = transcript_search::build_query(&self.search.query);
+        let messages = &self.messages;
+        let result = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current()
+                .block_on(transcript_search::search_messages(messages, query))
+        });
+        self.search.set_results(result);
+    }
+
+    /// Close the search prompt or clear the last search's highlights
+    ///
+    /// Returns false if no search was open.
+    fn close_search(&mut self) -> bool {
+        self.search.close()
+    }
+
+    /// Keys pressed while the export popup is shown
+    ///
+    /// A format key exports while choosing; any other key closes the popup.
+    fn handle_export_key(&mut self, key: KeyEvent) -> Result<()> {
+        let choosing = self.export_popup == Some(ExportPopup::ChooseFormat);
+        let choice = match key.code {
+            KeyCode::Char(c) if choosing => EXPORT_FORMATS
+                .into_iter()
+                .find(|(format_key, ..)| *format_key == c.to_ascii_lowercase()),
+            _ => None,
+        };
+        self.export_popup = choice.map(|(_, _, format, extension)| {
+            let result = tokio::task::block_in_place(|| {
+                tokio::runtime::Handle::current().block_on(async {
+                    let core_blocks = match &self.agent {
+                        Some(agent) => agent.read().await.core_blocks(),
+                        None => None,
+                    };
+                    transcript_export::export_transcript(
+                        &self.messages,
+                        core_blocks,
+                        &self.export_dir,
+                        format,
+                        extension,
+                        ExportSettings::default(),
+                    )
+                    .await
+                })
+            });
+            match result {
+                Ok(path) => ExportPopup::Outcome {
+                    title: "Conversation Exported",
+                    text: format!("Saved to {}", path.display()),
+                },
+                Err(e) => ExportPopup::Outcome {
+                    title: "Export Failed",
+                    text: format!("{:#}", e),
+                },
+            }
+        });
+        Ok(())
+    }
+
+    /// Close the export popup; returns false if it wasn't shown
+    fn close_export_popup(&mut self) -> bool {
+        self.export_popup.take().is_some()
+    }
+
+    /// Close whichever popup or search is open, as Esc does
+    ///
+    /// Declining to resume the last conversation starts a new one. Returns
+    /// false if nothing was open.
+    pub fn dismiss_overlay(&mut self) -> bool {
+        self.decline_resume() || self.close_export_popup() || self.close_search()
+    }
+
     fn update_focus_styling(&mut self) {
         let (title, style) = match self.focused_component {
             FocusedComponent::Input => (
@@ -623,10 +1072,19 @@ impl Conversation {
         match key.code {
             KeyCode::Enter => {
                 let text = self.textarea.lines().join("\n");
-                if !text.trim().is_empty() && !self.processing {
+                if let Some(idx) = self.editing_message_idx {
+                    if !text.trim().is_empty() && !self.processing {
+                        self.editing_message_idx = None;
+                        self.textarea = TextArea::default();
+                        self.textarea.set_placeholder_text("Type your message...");
+                        self.update_focus_styling();
+                        self.edit_and_resend(idx, text)?;
+                    }
+                } else if !text.trim().is_empty() && !self.processing {
                     // Add user message to history
-                    let user_msg = ChatMessage::new_plain("You".to_string(), text.clone());
+                    let user_msg = ChatMessage::new_plain(USER_SENDER.to_string(), text.clone());
                     self.messages.push(user_msg);
+                    self.autosave();
 
                     // Clear input
                     self.textarea = TextArea::default();
@@ -652,6 +1110,15 @@ impl Conversation {
 
     fn handle_history_key(&mut self, key: KeyEvent) -> Result<()> {
         match key.code {
+            KeyCode::Char('/') => {
+                self.search.open();
+            }
+            KeyCode::Char('n') if self.search.has_matches() => {
+                self.search.step(true);
+            }
+            KeyCode::Char('N') if self.search.has_matches() => {
+                self.search.step(false);
+            }
             KeyCode::Up | KeyCode::Char('k') => {
                 if self.scroll_offset > 0 {
                     self.scroll_offset -= 1;
@@ -683,6 +1150,21 @@ impl Conversation {
                     message.toggle_reasoning();
                 }
             }
+            KeyCode::Char('e') if !self.processing => {
+                // Load the last user message into the input to edit and resend it
+                if let Some(idx) = self.messages.iter().rposition(|m| m.sender == USER_SENDER) {
+                    self.textarea = TextArea::from(self.messages[idx].content.lines());
+                    self.editing_message_idx = Some(idx);
+                    self.focused_component = FocusedComponent::Input;
+                    self.update_focus_styling();
+                    self.textarea.set_block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .title("Edit message (Enter to resend and regenerate)")
+                            .border_style(Style::default().fg(Color::Yellow)),
+                    );
+                }
+            }
             KeyCode::Home => {
                 self.scroll_offset = 0;
             }
@@ -710,48 +1192,73 @@ impl Conversation {
     pub async fn send_message_to_agent(&mut self, message: String) -> Result<()> {
         // Always prefer the agent's own processing over direct LLM service
         if let Some(agent) = &self.agent {
-            debug!("Sending message to agent: {}", message);
+            debug!("Streaming message through agent: {}", message);
 
             // Start processing indicator
             self.event_sender.send(AppEvent::AgentProcessingStarted)?;
             self.processing = true;
+            self.is_streaming = true;
+
+            // Create streaming message
+            let agent_name = agent.read().await.name().to_string();
+            self.messages.push(ChatMessage::new_streaming(agent_name));
+            self.current_streaming_message_idx = Some(self.messages.len() - 1);
+
+            self.history.push(InternalChatMessage::User {
+                content: message.clone(),
+            });
+            self.current_history_idx = None;
+
+            let agent_id = agent.read().await.agent_id().to_string();
+            let agent_message = AgentMessage::new_chat("user".to_string(), agent_id, message);
+            self.current_stream_session = Some(agent_message.message_id.clone());
 
             let agent_clone = agent.clone();
             let event_sender_clone = self.event_sender.clone();
 
-            // Spawn agent processing on a separate task
-            tokio::spawn(async move {
-                let agent_id = agent_clone.read().await.agent_id().to_string();
-                let agent_message = AgentMessage::new_chat("user".to_string(), agent_id, message);
-
-                match agent_clone
+            // The agent runs its tool loop in the background; dropping the
+            // stream (by aborting this task) cancels it
+            let task = tokio::spawn(async move {
+                let stream = agent_clone
                     .write()
                     .await
-                    .process_message(agent_message)
-                    .await
-                {
-                    Ok(response) => {
-                        if response.success {
-                            let _ = event_sender_clone
-                                .send(AppEvent::AgentResponseReceived(response));
-                        } else {
-                            let error_msg = response
-                                .error
-                                .unwrap_or_else(|| "Unknown error".to_string());
-                            let _ =
-                                event_sender_clone.send(AppEvent::AgentResponseError(error_msg));
+                    .process_message_streaming(agent_message)
+                    .await;
+
+                match stream {
+                    Ok(mut stream) => {
+                        while let Some(chunk) = stream.next().await {
+                            match chunk.chunk_type {
+                                AgentChunkType::Error => {
+                                    let _ = event_sender_clone
+                                        .send(AppEvent::StreamingError(chunk.content));
+                                    return;
+                                }
+                                AgentChunkType::Complete => {
+                                    // Agents that don't stream put the whole reply here
+                                    if !chunk.content.is_empty() {
+                                        let mut text = to_ui_chunk(chunk);
+                                        text.chunk_type = ChunkType::Text;
+                                        let _ = event_sender_clone.send(AppEvent::StreamingChunk(text));
+                                    }
+                                    break;
+                                }
+                                _ => {
+                                    let _ = event_sender_clone
+                                        .send(AppEvent::StreamingChunk(to_ui_chunk(chunk)));
+                                }
+                            }
                         }
+                        let _ = event_sender_clone.send(AppEvent::StreamingComplete);
                     }
                     Err(e) => {
                         let _ = event_sender_clone
-                            .send(AppEvent::AgentResponseError(format!("Agent error: {}", e)));
+                            .send(AppEvent::StreamingError(format!("Agent error: {}", e)));
                     }
                 }
-
-                // Notify processing finished
-                let _ = event_sender_clone.send(AppEvent::AgentProcessingFinished);
             });
-            
+            self.agent_stream_task = Some(task.abort_handle());
+
             // Auto-scroll to bottom
             self.scroll_to_bottom();
         } else if let Some(_llm_service) = &self.llm_service {
@@ -784,16 +1291,18 @@ impl Conversation {
             self.current_streaming_message_idx = Some(self.messages.len() - 1);
 
             // Prepare messages for LLM
-            let mut conversation_messages = Vec::new();
-            conversation_messages.push(InternalChatMessage::User { content: message });
+            self.history.push(InternalChatMessage::User { content: message });
+            self.current_history_idx = None;
+            let conversation_messages = self.history.clone();
 
             // Start streaming
             let llm_service_clone = llm_service.clone();
             let stream_manager_clone = self.stream_manager.clone();
             let event_sender_clone = self.event_sender.clone();
             let session_id = format!("session_{}", chrono::Utc::now().timestamp_millis());
+            self.current_stream_session = Some(session_id.clone());
 
-            tokio::spawn(async move {
+            let task = tokio::spawn(async move {
                 match stream_manager_clone
                     .stream_genai_response(session_id, llm_service_clone, conversation_messages)
                     .await
@@ -815,6 +1324,7 @@ impl Conversation {
                     }
                 }
             });
+            self.agent_stream_task = Some(task.abort_handle());
             
             // Auto-scroll to bottom after setting up streaming
             self.scroll_to_bottom();
@@ -823,6 +1333,77 @@ impl Conversation {
         Ok(())
     }
 
+    /// Replace a sent user message and regenerate the reply to it
+    ///
+    /// Everything after the edited message, including the old reply, is
+    /// dropped from the chat and the history. The edit is recorded as a
+    /// [`SegmentEdit`], and the new text is sent for a fresh generation.
+    /// Edits can't be undone, since the dropped replies aren't kept.
+    pub fn edit_and_resend(&mut self, message_idx: usize, new_text: String) -> Result<()> {
+        if self.processing || self.is_streaming {
+            return Err(anyhow!("Cannot edit a message while a response is in progress"));
+        }
+        let message = self
+            .messages
+            .get(message_idx)
+            .ok_or_else(|| anyhow!("No message at index {}", message_idx))?;
+        if message.sender != USER_SENDER {
+            return Err(anyhow!(
+                "Only your own messages can be edited, message {} is from {}",
+                message_idx,
+                message.sender
+            ));
+        }
+
+        // Counted from the end, since the agent may have trimmed its oldest turns
+        let user_turns = self.messages[message_idx..]
+            .iter()
+            .filter(|m| m.sender == USER_SENDER)
+            .count();
+        // The resend adds the edited message back to the history
+        if let Some(agent) = &self.agent {
+            let mut agent = agent
+                .try_write()
+                .map_err(|_| anyhow!("Cannot edit a message while the agent is busy"))?;
+            let mut history = agent.get_history();
+            drop_last_user_turns(&mut history, user_turns, |entry| {
+                matches!(entry, AgentChatMessage::User { .. })
+            });
+            agent.set_history(history)?;
+        }
+        drop_last_user_turns(&mut self.history, user_turns, |entry| {
+            matches!(entry, InternalChatMessage::User { .. })
+        });
+
+        let before_content = std::mem::replace(
+            &mut self.messages[message_idx].content,
+            new_text.clone(),
+        );
+        self.messages[message_idx].cached_lines = None;
+        self.messages[message_idx].cached_width = None;
+        let dropped = self.messages.len() - message_idx - 1;
+        self.messages.truncate(message_idx + 1);
+
+        self.edit_history.push(SegmentEdit {
+            id: format!("edit_{}", chrono::Utc::now().timestamp_millis()),
+            edit_type: EditType::ContentEdit,
+            before_content,
+            after_content: new_text.clone(),
+            editor: "user".to_string(),
+            timestamp: chrono::Utc::now(),
+            reason: Some(format!(
+                "Edited message {} and regenerated, dropping {} later messages",
+                message_idx, dropped
+            )),
+            can_undo: false,
+        });
+        info!("Edited message {} and resending", message_idx);
+
+        self.scroll_to_bottom();
+        self.event_sender.send(AppEvent::MessageSent(new_text))?;
+        Ok(())
+    }
+
     /// Handle streaming chunk events
     pub fn handle_streaming_chunk(
         &mut self,
@@ -831,6 +1412,9 @@ impl Conversation {
         if let Some(idx) = self.current_streaming_message_idx {
             if let Some(message) = self.messages.get_mut(idx) {
                 message.append_chunk(&chunk.content, &chunk.chunk_type);
+                if self.persist_partial_responses && chunk.chunk_type == ChunkType::Text {
+                    self.persist_streaming_message();
+                }
 
                 // Auto-scroll to follow streaming
                 if !self.messages.is_empty() {
@@ -846,88 +1430,115 @@ impl Conversation {
         Ok(())
     }
 
-    /// Handle streaming completion
-    pub fn handle_streaming_complete(&mut self) -> Result<()> {
-        if let Some(idx) = self.current_streaming_message_idx {
-            if let Some(message) = self.messages.get_mut(idx) {
-                message.is_streaming = false;
-                message.streaming_complete = true;
+    /// Write the in-flight response's text so far to the history
+    fn persist_streaming_message(&mut self) {
+        let Some(content) = self
+            .current_streaming_message_idx
+            .and_then(|idx| self.messages.get(idx))
+            .map(|message| message.content.clone())
+        else {
+            return;
+        };
+        if content.is_empty() {
+            return;
+        }
+
+        let entry = InternalChatMessage::Assistant {
+            content,
+            tool_responses: None,
+        };
+        match self.current_history_idx {
+            Some(idx) => self.history[idx] = entry,
+            None => {
+                self.history.push(entry);
+                self.current_history_idx = Some(self.history.len() - 1);
             }
         }
+    }
 
-        self.current_streaming_message_idx = None;
+    /// Finish the in-flight response, marking it incomplete if it was cut off
+    fn end_streaming_message(&mut self, incomplete: bool) {
+        if !incomplete || self.persist_partial_responses {
+            self.persist_streaming_message();
+        }
+        if let Some(idx) = self.current_streaming_message_idx.take()
+            && let Some(message) = self.messages.get_mut(idx)
+        {
+            message.is_streaming = false;
+            message.streaming_complete = true;
+            message.incomplete = incomplete;
+            message.cached_lines = None;
+            message.cached_width = None;
+        }
+        self.current_history_idx = None;
+        self.current_stream_session = None;
+        self.agent_stream_task = None;
+        self.typing_indicator = None;
         self.is_streaming = false;
         self.processing = false;
-
-        info!("Streaming completed");
-        Ok(())
+        self.autosave();
     }
 
-    /// Handle streaming error
-    pub fn handle_streaming_error(&mut self, error: String) -> Result<()> {
-        if let Some(idx) = self.current_streaming_message_idx {
-            if let Some(message) = self.messages.get_mut(idx) {
-                message.content.push_str(&format!("\n❌ Error: {}", error));
-                message.is_streaming = false;
-                message.streaming_complete = true;
-                message.cached_lines = None;
-                message.cached_width = None;
-            }
+    /// Stop the in-flight response, keeping what was received so far
+    ///
+    /// Aborts the agent's turn even while it is stuck in a tool loop. The
+    /// partial response stays in the chat and the history, marked cancelled,
+    /// and tools that were still running are recorded as aborted. Returns
+    /// false if nothing was in flight.
+    pub fn cancel_streaming(&mut self) -> bool {
+        if !self.processing && !self.is_streaming {
+            return false;
         }
 
-        self.current_streaming_message_idx = None;
-        self.is_streaming = false;
-        self.processing = false;
+        if let Some(task) = self.agent_stream_task.take() {
+            task.abort();
+        }
+        if let Some(session_id) = self.current_stream_session.take() {
+            let stream_manager = self.stream_manager.clone();
+            tokio::spawn(async move {
+                stream_manager.cancel_stream(&session_id).await;
+            });
+        }
 
-        info!("Streaming error: {}", error);
-        Ok(())
-    }
-    pub async fn handle_agent_response(&mut self, response: luts_framework::agents::MessageResponse) -> Result<()> {
-        if let Some(agent) = &self.agent {
-            let agent_name = agent.read().await.name().to_string();
-            
-            // Create a message with the response content
-            let mut agent_msg = ChatMessage::new(agent_name, response.content);
-            
-            // Add tool calls to the message if any were executed
-            for tool_call_info in response.tool_calls {
-                let tool_status = if tool_call_info.success {
-                    ToolStatus::Completed
-                } else {
-                    ToolStatus::Failed("Tool execution failed".to_string())
-                };
-                
-                let tool_call = ToolCall {
-                    name: tool_call_info.tool_name,
-                    arguments: serde_json::to_string(&tool_call_info.tool_args)
-                        .unwrap_or_else(|_| "{}".to_string()),
-                    result: Some(tool_call_info.tool_result),
-                    status: tool_status,
-                };
-                
-                agent_msg.add_tool_call(tool_call);
+        if let Some(message) = self
+            .current_streaming_message_idx
+            .and_then(|idx| self.messages.get_mut(idx))
+        {
+            // A tool that was still running is abandoned with the stream
+            for tool_call in &mut message.tool_calls {
+                if matches!(tool_call.status, ToolStatus::Running) {
+                    let aborted = "Aborted: response was cancelled".to_string();
+                    tool_call.result = Some(aborted.clone());
+                    tool_call.status = ToolStatus::Failed(aborted);
+                }
             }
-            
-            self.messages.push(agent_msg);
+            message.cancelled = true;
         }
+        self.end_streaming_message(true);
 
-        // Auto-scroll to bottom
-        if !self.messages.is_empty() {
-            self.scroll_to_bottom();
-        }
+        info!("Streaming cancelled by user");
+        true
+    }
 
+    /// Handle streaming completion
+    pub fn handle_streaming_complete(&mut self) -> Result<()> {
+        self.end_streaming_message(false);
+
+        info!("Streaming completed");
         Ok(())
     }
 
-    /// Handle agent error events from background thread
-    pub fn handle_agent_error(&mut self, error: String) {
-        let error_msg = ChatMessage::new_plain("System".to_string(), format!("Error: {}", error));
-        self.messages.push(error_msg);
-
-        // Auto-scroll to bottom
-        if !self.messages.is_empty() {
-            self.scroll_to_bottom();
+    /// Handle streaming error
+    pub fn handle_streaming_error(&mut self, error: String) -> Result<()> {
+        // Save the partial text before the error note is added to it
+        let idx = self.current_streaming_message_idx;
+        self.end_streaming_message(true);
+        if let Some(message) = idx.and_then(|idx| self.messages.get_mut(idx)) {
+            message.content.push_str(&format!("\n❌ Error: {}", error));
         }
+
+        info!("Streaming error: {}", error);
+        Ok(())
     }
 
     /// Handle processing state changes
@@ -940,6 +1551,36 @@ impl Conversation {
         if self.is_streaming || self.processing {
             self.spinner_frame = (self.spinner_frame + 1) % self.spinner_frames.len();
         }
+        self.poll_stream_events();
+    }
+
+    /// Pick up typing indicator updates for the in-flight response
+    fn poll_stream_events(&mut self) {
+        loop {
+            match self.stream_events.try_recv() {
+                Ok(StreamEvent::TypingStatusChanged {
+                    session_id,
+                    indicator,
+                }) if self.current_stream_session.as_deref() == Some(session_id.as_str()) => {
+                    self.typing_indicator = match indicator.status {
+                        TypingStatus::Stopped => None,
+                        _ => Some(indicator),
+                    };
+                }
+                Ok(_) | Err(broadcast::error::TryRecvError::Lagged(_)) => {}
+                Err(_) => break,
+            }
+        }
+    }
+
+    /// Progress and ETA of the in-flight response, e.g. "42% · ~8s left"
+    fn streaming_progress_text(&self) -> Option<String> {
+        let indicator = self.typing_indicator.as_ref()?;
+        let percent = indicator.progress_percent?;
+        Some(match indicator.seconds_remaining() {
+            Some(seconds) => format!("{}% · ~{}s left", percent, seconds),
+            None => format!("{}%", percent),
+        })
     }
 
     /// Get current spinner character
@@ -961,6 +1602,7 @@ impl Conversation {
     pub fn is_processing(&self) -> bool {
         self.processing
     }
+
     
     /// Get agent reference for context viewer integration
     pub fn agent(&self) -> Option<Arc<RwLock<Box<dyn Agent>>>> {
@@ -1008,6 +1650,48 @@ impl Conversation {
         // Render status bar
         self.render_status(frame, main_chunks[3]);
 
+        match &self.export_popup {
+            Some(ExportPopup::ChooseFormat) => {
+                let options: Vec<String> = EXPORT_FORMATS
+                    .iter()
+                    .map(|(key, label, ..)| format!("  {}  - {}", key, label))
+                    .collect();
+                show_popup(
+                    frame,
+                    "Export Conversation",
+                    &format!(
+                        "Export format:\n{}\n\nAny other key cancels",
+                        options.join("\n")
+                    ),
+                    (40, 30),
+                );
+            }
+            Some(ExportPopup::Outcome { title, text }) => {
+                show_popup(frame, title, &format!("{}\n\nPress any key", text), (60, 25));
+            }
+            None => {}
+        }
+
+        if let Some((agent_id, save)) = &self.resume_offer {
+            show_popup(
+                frame,
+                "Resume Conversation",
+                &format!(
+                    "Resume your last conversation with {}?\n\n\
+                     {} messages, last saved {}\n\n\
+                     y/Enter - Resume\n\
+                     Any other key - Start a new conversation",
+                    agent_id,
+                    save_message_count(save),
+                    save.metadata
+                        .created_at
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M")
+                ),
+                (50, 30),
+            );
+        }
+
         // Show help if requested
         if self.show_help {
             show_popup(
@@ -1024,6 +1708,11 @@ impl Conversation {
                  \n\
                  Message Features:\n\
                  Ctrl+R      - Toggle reasoning for selected message\n\
+                 / or Ctrl+F - Search the conversation (/ when history focused)\n\
+                 n/N         - Next/previous search match (history focused)\n\
+                 Ctrl+E      - Export conversation (Markdown/JSON/JSONL)\n\
+                 e           - Edit and resend last message (history focused)\n\
+                 Esc/Ctrl+C  - Stop the agent's response\n\
                  \n\
                  Mode Switching:\n\
                  Ctrl+B      - Memory Blocks (view/edit AI memory)\n\
@@ -1064,13 +1753,28 @@ impl Conversation {
         // Create all lines from all messages
         let mut all_lines: Vec<Line<'static>> = Vec::new();
 
-        for msg in &mut self.messages {
-            let msg_lines = msg.get_or_render_lines_with_width(&self.rat_skin, available_width);
-            all_lines.extend(msg_lines.clone());
+        let mut current_match_line = None;
+        for (idx, msg) in self.messages.iter_mut().enumerate() {
+            let mut msg_lines = msg
+                .get_or_render_lines_with_width(&self.rat_skin, available_width)
+                .clone();
+            if let Some(line) = self.search.highlight(idx, &msg.content, &mut msg_lines) {
+                current_match_line = Some(all_lines.len() + line);
+            }
+            all_lines.extend(msg_lines);
             // Add an empty line between messages for better readability
             all_lines.push(Line::from(""));
         }
 
+        // Bring the selected search match into view, a third of the way down
+        if self.search.scroll_pending {
+            self.search.scroll_pending = false;
+            if let Some(line) = current_match_line {
+                let max_scroll = total_lines.saturating_sub(visible_height);
+                self.scroll_offset = line.saturating_sub(visible_height / 3).min(max_scroll) as u16;
+            }
+        }
+
         // Remove the last empty line if we added one
         if !all_lines.is_empty() && all_lines.last().unwrap().spans.is_empty() {
             all_lines.pop();
@@ -1174,26 +1878,37 @@ impl Conversation {
     }
 
     fn render_status(&self, frame: &mut Frame, area: Rect) {
-        let status_text = if self.is_streaming {
+        let search_status = self.search.status_text();
+        let status_text = if let Some(search_status) = &search_status {
+            search_status.clone()
+        } else if self.is_streaming {
             // Show streaming indicator
             let spinner_char = self.get_spinner_char();
-            format!("{} Streaming response...", spinner_char)
+            match self.streaming_progress_text() {
+                Some(progress) => format!(
+                    "{} Streaming response... {} (Esc to stop)",
+                    spinner_char, progress
+                ),
+                None => format!("{} Streaming response... (Esc to stop)", spinner_char),
+            }
         } else if self.processing {
             // Show spinner when processing
             let spinner_char = self.get_spinner_char();
-            format!("{} Processing...", spinner_char)
+            format!("{} Processing... (Esc to stop)", spinner_char)
         } else {
             match self.focused_component {
                 FocusedComponent::Input => {
-            "Type your message | Tab: Switch to history | Ctrl+B: Blocks | Ctrl+W: Context | Ctrl+T: Tools | Ctrl+L: Logs | F1: Help | Esc: Agent selection".to_string()
+            "Type your message | Tab: Switch to history | Ctrl+F: Search | Ctrl+B: Blocks | Ctrl+W: Context | Ctrl+T: Tools | Ctrl+L: Logs | F1: Help | Esc: Agent selection".to_string()
                 }
                 FocusedComponent::History => {
-            "Navigate history | Tab: Switch to input | Ctrl+B: Blocks | Ctrl+W: Context | Ctrl+T: Tools | Ctrl+L: Logs | F1: Help | Esc: Agent selection".to_string()
+            "Navigate history | Tab: Switch to input | /: Search | Ctrl+B: Blocks | Ctrl+W: Context | Ctrl+T: Tools | Ctrl+L: Logs | F1: Help | Esc: Agent selection".to_string()
                 }
             }
         };
 
-        let style = if self.is_streaming {
+        let style = if search_status.is_some() {
+            Style::default().fg(Color::Yellow)
+        } else if self.is_streaming {
             Style::default().fg(Color::Cyan)
         } else if self.processing {
             Style::default().fg(Color::Yellow)
@@ -1254,3 +1969,367 @@ impl Conversation {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use luts_core::streaming::ResponseChunk;
+    use luts_core::streaming::manager::ChunkMetadata;
+    use std::collections::HashMap;
+
+    fn text_chunk(sequence: u64, content: &str) -> ResponseChunk {
+        chunk(sequence, content, ChunkType::Text)
+    }
+
+    fn chunk(sequence: u64, content: &str, chunk_type: ChunkType) -> ResponseChunk {
+        ResponseChunk {
+            id: format!("chunk_{}", sequence),
+            sequence,
+            content: content.to_string(),
+            is_final: false,
+            timestamp: chrono::Utc::now(),
+            chunk_type,
+            metadata: ChunkMetadata {
+                token_count: None,
+                processing_time_ms: None,
+                model: None,
+                confidence: None,
+                custom: HashMap::new(),
+            },
+        }
+    }
+
+    /// A conversation partway through streaming a reply to `prompt`
+    fn streaming_conversation(prompt: &str) -> Conversation {
+        let (event_sender, _events) = mpsc::unbounded_channel();
+        let mut conversation = Conversation::new(event_sender);
+        conversation.history.push(InternalChatMessage::User {
+            content: prompt.to_string(),
+        });
+        conversation
+            .messages
+            .push(ChatMessage::new_streaming("AI".to_string()));
+        conversation.current_streaming_message_idx = Some(conversation.messages.len() - 1);
+        conversation.current_stream_session = Some("session_test".to_string());
+        conversation.is_streaming = true;
+        conversation.processing = true;
+        conversation
+    }
+
+    /// Agent that only keeps the history it is given
+    #[derive(Default)]
+    struct HistoryAgent {
+        history: Vec<AgentChatMessage>,
+    }
+
+    #[async_trait::async_trait]
+    impl Agent for HistoryAgent {
+        fn agent_id(&self) -> &str {
+            "historian"
+        }
+
+        fn name(&self) -> &str {
+            "Historian"
+        }
+
+        fn role(&self) -> &str {
+            "test"
+        }
+
+        async fn process_message(
+            &mut self,
+            _message: AgentMessage,
+        ) -> Result<luts_framework::agents::MessageResponse> {
+            Err(anyhow!("not used"))
+        }
+
+        async fn send_message(&self, _message: AgentMessage) -> Result<()> {
+            Ok(())
+        }
+
+        fn get_history(&self) -> Vec<AgentChatMessage> {
+            self.history.clone()
+        }
+
+        fn set_history(&mut self, history: Vec<AgentChatMessage>) -> Result<()> {
+            self.history = history;
+            Ok(())
+        }
+
+        fn get_available_tools(&self) -> Vec<String> {
+            Vec::new()
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    async fn agent_history(conversation: &Conversation) -> Vec<(bool, String)> {
+        let agent = conversation.agent.as_ref().unwrap().read().await;
+        agent
+            .get_history()
+            .iter()
+            .map(|entry| {
+                (
+                    matches!(entry, AgentChatMessage::User { .. }),
+                    entry.content().to_string(),
+                )
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_restored_messages_become_the_agent_history() {
+        let (event_sender, _events) = mpsc::unbounded_channel();
+        let mut conversation = Conversation::new(event_sender);
+        conversation.agent = Some(Arc::new(RwLock::new(Box::new(HistoryAgent::default()))));
+
+        conversation.restore_messages(vec![
+            ChatMessage::new_plain("Historian".to_string(), "Hello! How can I help?".to_string()),
+            ChatMessage::new_plain(USER_SENDER.to_string(), "Name a river".to_string()),
+            ChatMessage::new_plain("Historian".to_string(), "The Tagus.".to_string()),
+        ]);
+
+        // The greeting isn't part of the context
+        assert_eq!(
+            agent_history(&conversation).await,
+            [
+                (true, "Name a river".to_string()),
+                (false, "The Tagus.".to_string()),
+            ]
+        );
+        assert_eq!(conversation.messages.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_cancelling_mid_stream_keeps_incomplete_message() {
+        let mut conversation = streaming_conversation("Tell me a story");
+        conversation.handle_streaming_chunk(text_chunk(0, "Once upon ")).unwrap();
+        conversation.handle_streaming_chunk(text_chunk(1, "a time")).unwrap();
+
+        // The partial reply is in the history before anything interrupts it
+        assert!(matches!(
+            conversation.history.last(),
+            Some(InternalChatMessage::Assistant { content, .. }) if content == "Once upon a time"
+        ));
+
+        assert!(conversation.cancel_streaming());
+        assert!(!conversation.is_streaming);
+        assert!(!conversation.is_processing());
+
+        let message = conversation.messages.last().unwrap();
+        assert!(message.incomplete);
+        assert_eq!(message.content, "Once upon a time");
+        assert_eq!(conversation.history.len(), 2);
+        assert!(matches!(
+            &conversation.history[1],
+            InternalChatMessage::Assistant { content, .. } if content == "Once upon a time"
+        ));
+
+        // Chunks still in flight after the cancel are dropped
+        conversation.handle_streaming_chunk(text_chunk(2, ", there")).unwrap();
+        assert_eq!(conversation.messages.last().unwrap().content, "Once upon a time");
+    }
+
+    #[tokio::test]
+    async fn test_cancelling_aborts_a_turn_stuck_in_a_tool_loop() {
+        let mut conversation = streaming_conversation("Search everything");
+        let idx = conversation.current_streaming_message_idx.unwrap();
+        conversation.messages[idx].add_tool_call(ToolCall {
+            name: "search".to_string(),
+            arguments: "{}".to_string(),
+            result: None,
+            status: ToolStatus::Running,
+        });
+        let turn = tokio::spawn(std::future::pending::<()>());
+        conversation.agent_stream_task = Some(turn.abort_handle());
+
+        assert!(conversation.cancel_streaming());
+        assert!(!conversation.is_processing());
+        assert!(!conversation.is_streaming);
+        assert!(turn.await.unwrap_err().is_cancelled());
+
+        let message = &conversation.messages[idx];
+        assert!(message.cancelled && message.incomplete);
+        assert!(matches!(message.tool_calls[0].status, ToolStatus::Failed(_)));
+
+        // Nothing is left to cancel
+        assert!(!conversation.cancel_streaming());
+    }
+
+    #[tokio::test]
+    async fn test_reasoning_chunks_stay_out_of_the_answer() {
+        let mut conversation = streaming_conversation("What is 2 + 2?");
+        let stream = [
+            ("The user wants a sum.", ChunkType::Reasoning),
+            (" 2 + 2 is 4.", ChunkType::Reasoning),
+            ("\n\nThe answer", ChunkType::Text),
+            ("Double-checking: yes.", ChunkType::Reasoning),
+            (" is 4.", ChunkType::Text),
+            ("", ChunkType::Complete),
+        ];
+        for (sequence, (content, chunk_type)) in stream.into_iter().enumerate() {
+            conversation
+                .handle_streaming_chunk(chunk(sequence as u64, content, chunk_type))
+                .unwrap();
+        }
+
+        let message = conversation.messages.last().unwrap();
+        assert_eq!(message.content, "The answer is 4.");
+        assert_eq!(
+            message.reasoning.as_deref(),
+            Some("The user wants a sum. 2 + 2 is 4.\n\nDouble-checking: yes.")
+        );
+        assert!(message.streaming_complete);
+        assert!(matches!(
+            conversation.history.last(),
+            Some(InternalChatMessage::Assistant { content, .. }) if content == "The answer is 4."
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_partial_responses_are_dropped_when_persistence_is_off() {
+        let mut conversation = streaming_conversation("Tell me a story");
+        conversation.set_persist_partial_responses(false);
+        conversation.handle_streaming_chunk(text_chunk(0, "Once upon ")).unwrap();
+        assert_eq!(conversation.history.len(), 1);
+
+        conversation.handle_streaming_error("connection reset".to_string()).unwrap();
+        let message = conversation.messages.last().unwrap();
+        assert!(message.incomplete);
+        assert!(message.content.starts_with("Once upon "));
+        assert_eq!(conversation.history.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_edit_and_resend_replaces_message_and_regenerates_reply() {
+        let (event_sender, mut events) = mpsc::unbounded_channel();
+        let mut conversation = Conversation::new(event_sender);
+        let agent = HistoryAgent {
+            history: vec![
+                AgentChatMessage::User {
+                    content: "Waht is Rust?".to_string(),
+                },
+                AgentChatMessage::Assistant {
+                    content: "Did you mean Rust?".to_string(),
+                    tool_responses: None,
+                },
+            ],
+        };
+        conversation.agent = Some(Arc::new(RwLock::new(Box::new(agent))));
+        conversation.history = vec![
+            InternalChatMessage::User {
+                content: "Waht is Rust?".to_string(),
+            },
+            InternalChatMessage::Assistant {
+                content: "Did you mean Rust?".to_string(),
+                tool_responses: None,
+            },
+        ];
+        conversation.messages = vec![
+            ChatMessage::new_plain(USER_SENDER.to_string(), "Waht is Rust?".to_string()),
+            ChatMessage::new_plain("AI".to_string(), "Did you mean Rust?".to_string()),
+        ];
+
+        // Only user messages can be edited
+        assert!(conversation.edit_and_resend(1, "Hi".to_string()).is_err());
+
+        conversation
+            .edit_and_resend(0, "What is Rust?".to_string())
+            .unwrap();
+        assert_eq!(conversation.messages.len(), 1);
+        assert_eq!(conversation.messages[0].content, "What is Rust?");
+        assert!(conversation.history.is_empty());
+        assert!(agent_history(&conversation).await.is_empty());
+        assert!(matches!(
+            events.try_recv(),
+            Ok(AppEvent::MessageSent(text)) if text == "What is Rust?"
+        ));
+        let edit = &conversation.edit_history[0];
+        assert_eq!(edit.before_content, "Waht is Rust?");
+        assert_eq!(edit.after_content, "What is Rust?");
+        assert!(!edit.can_undo);
+
+        // The resent message streams a new reply in place of the old one
+        conversation.history.push(InternalChatMessage::User {
+            content: "What is Rust?".to_string(),
+        });
+        conversation
+            .messages
+            .push(ChatMessage::new_streaming("AI".to_string()));
+        conversation.current_streaming_message_idx = Some(1);
+        conversation.current_stream_session = Some("session_test".to_string());
+        conversation.is_streaming = true;
+        conversation
+            .handle_streaming_chunk(text_chunk(0, "A systems language."))
+            .unwrap();
+        conversation.handle_streaming_complete().unwrap();
+
+        assert_eq!(conversation.messages.len(), 2);
+        assert_eq!(conversation.messages[1].content, "A systems language.");
+        assert!(matches!(
+            conversation.history.as_slice(),
+            [
+                InternalChatMessage::User { content: question },
+                InternalChatMessage::Assistant { content: answer, .. },
+            ] if question == "What is Rust?" && answer == "A systems language."
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_search_cycles_through_matches_and_reports_no_results() {
+        let (event_sender, _events) = mpsc::unbounded_channel();
+        let mut conversation = Conversation::new(event_sender);
+        conversation.messages = vec![
+            ChatMessage::new_plain(
+                USER_SENDER.to_string(),
+                "Is tea better than coffee?".to_string(),
+            ),
+            ChatMessage::new_plain("AI".to_string(), "Green tea has less caffeine.".to_string()),
+        ];
+        let key = |code: KeyCode| KeyEvent::from(code);
+        let search = |conversation: &mut Conversation, query: &str| {
+            conversation.handle_key_event(key(KeyCode::Char('/'))).unwrap();
+            for c in query.chars() {
+                conversation.handle_key_event(key(KeyCode::Char(c))).unwrap();
+            }
+            conversation.handle_key_event(key(KeyCode::Enter)).unwrap();
+        };
+
+        // `/` is typed into the input; Ctrl+F opens the search from anywhere
+        conversation
+            .handle_key_event(KeyEvent::new(
+                KeyCode::Char('f'),
+                crossterm::event::KeyModifiers::CONTROL,
+            ))
+            .unwrap();
+        assert!(conversation.search.editing);
+        conversation.handle_key_event(key(KeyCode::Esc)).unwrap();
+        conversation.focused_component = FocusedComponent::History;
+
+        search(&mut conversation, "TEA");
+        assert_eq!(conversation.focused_component, FocusedComponent::History);
+        let selected = |conversation: &Conversation| {
+            let found = conversation.search.current_match().unwrap();
+            (found.message_idx, found.range.clone())
+        };
+        assert_eq!(conversation.search.matches.len(), 2);
+        assert_eq!(selected(&conversation), (0, 3..6));
+        conversation.handle_key_event(key(KeyCode::Char('n'))).unwrap();
+        assert_eq!(selected(&conversation), (1, 6..9));
+        conversation.handle_key_event(key(KeyCode::Char('n'))).unwrap();
+        assert_eq!(selected(&conversation), (0, 3..6));
+        conversation.handle_key_event(key(KeyCode::Char('N'))).unwrap();
+        assert_eq!(selected(&conversation), (1, 6..9));
+
+        search(&mut conversation, "matcha");
+        assert!(!conversation.search.has_matches());
+        assert_eq!(
+            conversation.search.status_text().as_deref(),
+            Some("No matches for \"matcha\"")
+        );
+        assert!(conversation.close_search());
+        assert!(!conversation.close_search());
+    }
+}
@@ -0,0 +1,244 @@
+//! Saving conversations so they can be resumed after a restart
+//!
+//! Each agent's conversations are auto-saved under `<autosave dir>/<agent id>`
+//! by an [`AutoSaveManager`], one session directory per conversation. When an
+//! agent is selected, its most recently saved session can be picked up again.
+
+use crate::conversation::ChatMessage;
+use crate::transcript_export::{
+    from_exportable_messages, to_exportable_messages, transcript_metadata,
+};
+use anyhow::Result;
+use chrono::{Local, Utc};
+use luts_framework::llm::conversation::auto_save::{
+    AutoSaveConfig, AutoSaveData, AutoSaveManager, AutoSaveType, FilesystemAutoSaveBackend,
+};
+use luts_framework::llm::conversation::export::ExportInfo;
+use luts_framework::llm::conversation::{ExportFormat, ExportSettings, ExportableConversation};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::warn;
+
+/// Saved versions kept per session
+const MAX_VERSIONS: usize = 5;
+
+/// A conversation with one agent, saved as it grows
+pub struct ConversationSession {
+    manager: Arc<AutoSaveManager>,
+    agent_id: String,
+    session_id: String,
+}
+
+impl ConversationSession {
+    /// Start a new session with the agent
+    ///
+    /// The id carries a random suffix so sessions started in the same second
+    /// don't share a save file.
+    pub async fn start(autosave_dir: &Path, agent_id: &str) -> Result<Self> {
+        let session_id = format!(
+            "tui-{}-{:08x}",
+            Local::now().format("%Y%m%d-%H%M%S"),
+            rand::random::<u32>()
+        );
+        Self::open(autosave_dir, agent_id, session_id).await
+    }
+
+    /// Continue the session a save belongs to, returning its messages
+    pub async fn resume(
+        autosave_dir: &Path,
+        agent_id: &str,
+        save: &AutoSaveData,
+    ) -> Result<(Self, Vec<ChatMessage>)> {
+        let session = Self::open(autosave_dir, agent_id, save.metadata.session_id.clone()).await?;
+        // Later saves continue the resumed one's sequence
+        session.manager.recover(&session.session_id).await?;
+        let messages = save
+            .conversations
+            .iter()
+            .find(|conversation| conversation.metadata.id == session.session_id)
+            .map(|conversation| from_exportable_messages(&conversation.messages))
+            .unwrap_or_default();
+        Ok((session, messages))
+    }
+
+    /// The agent's most recently saved session, if it has one with messages
+    pub async fn latest(autosave_dir: &Path, agent_id: &str) -> Result<Option<AutoSaveData>> {
+        let manager = AutoSaveManager::new().with_backend(Arc::new(
+            FilesystemAutoSaveBackend::new(agent_dir(autosave_dir, agent_id)),
+        ));
+        Ok(manager
+            .load_latest_of_any_session()
+            .await?
+            .filter(|save| save_message_count(save) > 0))
+    }
+
+    /// Save the messages without waiting for the write
+    pub fn save_in_background(&self, messages: &[ChatMessage]) {
+        let manager = self.manager.clone();
+        let conversation = self.snapshot(messages);
+        tokio::spawn(async move {
+            manager.update_conversation(conversation).await;
+            if let Err(e) = manager.trigger_save(AutoSaveType::ActivityTriggered).await {
+                warn!("Failed to auto-save conversation: {}", e);
+            }
+        });
+    }
+
+    /// Save the messages before the application exits
+    pub async fn save_on_exit(&self, messages: &[ChatMessage]) -> Result<()> {
+        self.manager
+            .update_conversation(self.snapshot(messages))
+            .await;
+        self.manager.save_on_exit().await
+    }
+
+    async fn open(autosave_dir: &Path, agent_id: &str, session_id: String) -> Result<Self> {
+        let directory = agent_dir(autosave_dir, agent_id);
+        let manager = AutoSaveManager::new()
+            .with_backend(Arc::new(FilesystemAutoSaveBackend::new(&directory)))
+            .with_session(agent_id, session_id.clone());
+        manager
+            .update_config(AutoSaveConfig {
+                max_versions: MAX_VERSIONS,
+                save_directory: directory,
+                save_memory_blocks: false,
+                save_on_config_change: false,
+                ..Default::default()
+            })
+            .await?;
+        Ok(Self {
+            manager: Arc::new(manager),
+            agent_id: agent_id.to_string(),
+            session_id,
+        })
+    }
+
+    fn snapshot(&self, messages: &[ChatMessage]) -> ExportableConversation {
+        let settings = ExportSettings::default();
+        let mut metadata = transcript_metadata(messages.len());
+        metadata.id = self.session_id.clone();
+        metadata.session_id = self.session_id.clone();
+        metadata.user_id = self.agent_id.clone();
+        ExportableConversation {
+            metadata,
+            messages: to_exportable_messages(messages, &settings),
+            system_messages: Vec::new(),
+            pinned_context: Vec::new(),
+            memory_blocks: Vec::new(),
+            summaries: Vec::new(),
+            token_usage: Vec::new(),
+            export_info: ExportInfo {
+                exported_at: Utc::now(),
+                format: ExportFormat::Json,
+                version: "1.0".to_string(),
+                exporter: "luts-tui".to_string(),
+                settings,
+                file_size_bytes: None,
+                compression: None,
+            },
+        }
+    }
+}
+
+/// Number of messages in the conversation of a save
+pub fn save_message_count(save: &AutoSaveData) -> usize {
+    save.conversations
+        .iter()
+        .map(|conversation| conversation.metadata.message_count)
+        .sum()
+}
+
+fn agent_dir(autosave_dir: &Path, agent_id: &str) -> PathBuf {
+    // Agent IDs become directory names, so keep them to safe characters
+    let name: String = agent_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    autosave_dir.join(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_saved_session_is_resumed_with_its_messages() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let autosave_dir = temp_dir.path();
+        assert!(
+            ConversationSession::latest(autosave_dir, "researcher")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let messages = vec![
+            ChatMessage::new_plain("You".to_string(), "Remember: the code is 42.".to_string()),
+            ChatMessage::new("Researcher".to_string(), "Noted, **42**.".to_string())
+                .with_reasoning("Store the code.".to_string()),
+        ];
+        let session = ConversationSession::start(autosave_dir, "researcher")
+            .await
+            .unwrap();
+        session.save_on_exit(&messages).await.unwrap();
+
+        let save = ConversationSession::latest(autosave_dir, "researcher")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(save_message_count(&save), 2);
+        assert!(
+            ConversationSession::latest(autosave_dir, "coder")
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let (resumed, restored) = ConversationSession::resume(autosave_dir, "researcher", &save)
+            .await
+            .unwrap();
+        assert_eq!(resumed.session_id, session.session_id);
+        assert_eq!(restored.len(), 2);
+        assert_eq!(restored[0].content, "Remember: the code is 42.");
+        assert_eq!(restored[1].sender, "Researcher");
+        assert_eq!(restored[1].reasoning.as_deref(), Some("Store the code."));
+
+        // A second session started right away gets its own id
+        let other = ConversationSession::start(autosave_dir, "researcher")
+            .await
+            .unwrap();
+        assert_ne!(other.session_id, session.session_id);
+    }
+
+    #[tokio::test]
+    async fn test_cut_off_response_is_still_incomplete_after_resume() {
+        let autosave_dir = tempfile::TempDir::new().unwrap();
+        let mut partial = ChatMessage::new("Researcher".to_string(), "The answer is".to_string());
+        partial.incomplete = true;
+        let messages = vec![
+            ChatMessage::new_plain("You".to_string(), "What's the answer?".to_string()),
+            partial,
+        ];
+        let session = ConversationSession::start(autosave_dir.path(), "researcher")
+            .await
+            .unwrap();
+        session.save_on_exit(&messages).await.unwrap();
+
+        let save = ConversationSession::latest(autosave_dir.path(), "researcher")
+            .await
+            .unwrap()
+            .unwrap();
+        let (_, restored) = ConversationSession::resume(autosave_dir.path(), "researcher", &save)
+            .await
+            .unwrap();
+        assert!(!restored[0].incomplete);
+        assert!(restored[1].incomplete);
+        assert_eq!(restored[1].content, "The answer is");
+    }
+}
@@ -2,7 +2,6 @@
 
 use anyhow::Result;
 use crossterm::event::{self, Event, KeyCode, KeyEvent, MouseEvent};
-use luts_framework::agents::MessageResponse;
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
@@ -15,10 +14,7 @@ pub enum AppEvent {
     Quit,
     AgentSelected(String),
     MessageSent(String),
-    AgentResponseReceived(MessageResponse), // Now includes full response with tool calls
-    AgentResponseError(String),
     AgentProcessingStarted,
-    AgentProcessingFinished,
     // Streaming events with ResponseChunk
     StreamingChunk(luts_framework::streaming::ResponseChunk),
     StreamingComplete,
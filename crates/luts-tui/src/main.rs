@@ -4,7 +4,7 @@
 
 use anyhow::Result;
 use clap::Parser;
-use luts_framework::agents::PersonalityAgentBuilder;
+use luts_framework::agents::{ConversationPersistencePolicy, PersonalityAgentBuilder};
 use std::path::PathBuf;
 use tracing::info;
 
@@ -16,11 +16,14 @@ mod config;
 mod config_manager;
 mod context_viewer;
 mod conversation;
+mod conversation_session;
 mod events;
 mod log_viewer;
 mod markdown;
 mod streaming_test;
 mod tool_activity;
+mod transcript_export;
+mod transcript_search;
 
 use app::App;
 
@@ -62,6 +65,20 @@ pub struct Args {
     /// List available test scenarios
     #[clap(long)]
     list_test_scenarios: bool,
+
+    /// Only keep streamed responses that finish, dropping the partial text
+    /// of cancelled or failed ones
+    #[clap(long)]
+    discard_partial_responses: bool,
+
+    /// Start a new conversation instead of offering to resume the last one
+    #[clap(long)]
+    new_session: bool,
+
+    /// Store finished turns in the agent's memory: none, messages, or
+    /// messages-and-summaries
+    #[clap(long, value_name = "POLICY", default_value = "none")]
+    persistence: ConversationPersistencePolicy,
 }
 
 /// Initialize the terminal for TUI mode
@@ -94,9 +111,21 @@ pub fn restore_terminal<B: ratatui::backend::Backend + std::io::Write>(
 }
 
 /// Run the TUI application
-pub async fn run_tui(data_dir: &str, provider: &str, agent: Option<String>) -> Result<()> {
+pub async fn run_tui(
+    data_dir: &str,
+    provider: &str,
+    agent: Option<String>,
+    persist_partial_responses: bool,
+    new_session: bool,
+    persistence: ConversationPersistencePolicy,
+) -> Result<()> {
     let mut terminal = init_terminal()?;
-    let app_result = App::new(data_dir, provider, agent).run(&mut terminal).await;
+    let app_result = App::new(data_dir, provider, agent)
+        .with_persist_partial_responses(persist_partial_responses)
+        .with_new_session(new_session)
+        .with_persistence_policy(persistence)
+        .run(&mut terminal)
+        .await;
     restore_terminal(&mut terminal)?;
     app_result
 }
@@ -137,5 +166,13 @@ async fn main() -> Result<()> {
     info!("Data directory: {}", data_dir);
     info!("Provider: {}", args.provider);
 
-    run_tui(&data_dir, &args.provider, args.agent).await
+    run_tui(
+        &data_dir,
+        &args.provider,
+        args.agent,
+        !args.discard_partial_responses,
+        args.new_session,
+        args.persistence,
+    )
+    .await
 }
@@ -1,7 +1,8 @@
 //! Simple markdown renderer for ratatui TUI
 //! 
 //! This module provides basic markdown rendering functionality without external dependencies
-//! that might cause version conflicts.
+//! that might cause version conflicts. Fenced code blocks are coloured by a small built-in
+//! tokenizer for common languages, and GitHub-flavored tables are drawn as aligned grids.
 
 use ratatui::{
     style::{Color, Modifier, Style},
@@ -14,6 +15,11 @@ pub struct SimpleMarkdownRenderer {
     italic_style: Style,
     code_style: Style,
     heading_styles: [Style; 6],
+    fence_style: Style,
+    /// Code block styles, indexed by [`TokenKind`]
+    token_styles: [Style; 6],
+    table_border_style: Style,
+    table_header_style: Style,
 }
 
 impl Default for SimpleMarkdownRenderer {
@@ -30,16 +36,131 @@ impl Default for SimpleMarkdownRenderer {
                 Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),    // H5
                 Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD), // H6
             ],
+            fence_style: Style::default().fg(Color::DarkGray),
+            token_styles: [
+                Style::default().fg(Color::White),                                 // Plain
+                Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD), // Keyword
+                Style::default().fg(Color::Cyan),                                  // Type
+                Style::default().fg(Color::Green),                                 // String
+                Style::default().fg(Color::Yellow),                                // Number
+                Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC), // Comment
+            ],
+            table_border_style: Style::default().fg(Color::DarkGray),
+            table_header_style: Style::default().add_modifier(Modifier::BOLD),
         }
     }
 }
 
 impl SimpleMarkdownRenderer {
     pub fn render(&self, content: &str) -> Text<'static> {
-        let lines: Vec<Line> = content.lines().map(|line| self.render_line(line)).collect();
+        self.render_with_width(content, usize::MAX)
+    }
+
+    /// Render `content`, narrowing and wrapping tables to fit in `width` columns
+    pub fn render_with_width(&self, content: &str, width: usize) -> Text<'static> {
+        let source: Vec<&str> = content.lines().collect();
+        let mut lines = Vec::new();
+        let mut i = 0;
+        while i < source.len() {
+            if let Some(language) = fence_language(source[i]) {
+                i += self.render_code_block(&source[i..], language, &mut lines);
+            } else if let Some((table, consumed)) = Table::parse(&source[i..]) {
+                lines.extend(self.render_table(&table, width));
+                i += consumed;
+            } else {
+                lines.push(self.render_line(source[i]));
+                i += 1;
+            }
+        }
         Text::from(lines)
     }
 
+    /// Render the code block opened by `source[0]`; returns the number of lines it spans
+    ///
+    /// A block that is never closed (e.g. one still streaming in) runs to the end.
+    fn render_code_block(
+        &self,
+        source: &[&str],
+        language: &str,
+        lines: &mut Vec<Line<'static>>,
+    ) -> usize {
+        lines.push(Line::from(Span::styled(source[0].to_string(), self.fence_style)));
+        let syntax = Syntax::for_language(language);
+        let mut in_block_comment = false;
+        for (offset, line) in source.iter().enumerate().skip(1) {
+            if is_closing_fence(line) {
+                lines.push(Line::from(Span::styled(line.to_string(), self.fence_style)));
+                return offset + 1;
+            }
+            let spans = match syntax {
+                Some(syntax) => tokenize(line, syntax, &mut in_block_comment)
+                    .into_iter()
+                    .map(|(kind, text)| Span::styled(text.to_string(), self.token_style(kind)))
+                    .collect(),
+                None => vec![Span::styled(line.to_string(), self.token_style(TokenKind::Plain))],
+            };
+            lines.push(Line::from(spans));
+        }
+        source.len()
+    }
+
+    fn token_style(&self, kind: TokenKind) -> Style {
+        self.token_styles[kind as usize]
+    }
+
+    fn render_table(&self, table: &Table, width: usize) -> Vec<Line<'static>> {
+        let widths = table.column_widths(width);
+        let border = |left: &str, middle: &str, right: &str| {
+            let segments: Vec<String> = widths.iter().map(|w| "─".repeat(w + 2)).collect();
+            Line::from(Span::styled(
+                format!("{}{}{}", left, segments.join(middle), right),
+                self.table_border_style,
+            ))
+        };
+
+        let mut lines = vec![border("┌", "┬", "┐")];
+        lines.extend(self.render_table_row(
+            &table.header,
+            &widths,
+            &table.alignments,
+            self.table_header_style,
+        ));
+        lines.push(border("├", "┼", "┤"));
+        for row in &table.rows {
+            lines.extend(self.render_table_row(row, &widths, &table.alignments, Style::default()));
+        }
+        lines.push(border("└", "┴", "┘"));
+        lines
+    }
+
+    /// Lines of one table row, as many as its tallest wrapped cell needs
+    fn render_table_row(
+        &self,
+        cells: &[String],
+        widths: &[usize],
+        alignments: &[Alignment],
+        style: Style,
+    ) -> Vec<Line<'static>> {
+        let wrapped: Vec<Vec<String>> = cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, &width)| wrap_cell(cell, width))
+            .collect();
+        let height = wrapped.iter().map(Vec::len).max().unwrap_or(1);
+
+        (0..height)
+            .map(|row| {
+                let mut spans = vec![Span::styled("│", self.table_border_style)];
+                for ((cell, &width), &alignment) in wrapped.iter().zip(widths).zip(alignments) {
+                    let text = cell.get(row).map(String::as_str).unwrap_or("");
+                    spans.push(Span::styled(format!(" {} ", align(text, width, alignment)), style));
+                    spans.push(Span::styled("│", self.table_border_style));
+                }
+                Line::from(spans)
+            })
+            .collect()
+    }
+
     fn render_line(&self, line: &str) -> Line<'static> {
         // Handle headings
         if let Some(heading_level) = self.parse_heading(line) {
@@ -168,4 +289,470 @@ enum MatchType {
     Bold,
     Italic,
     Code,
-}
\ No newline at end of file
+}
+
+/// Language of the code fence `line` opens, or None if it isn't a fence
+fn fence_language(line: &str) -> Option<&str> {
+    line.trim_start().strip_prefix("```").map(str::trim)
+}
+
+fn is_closing_fence(line: &str) -> bool {
+    fence_language(line).is_some_and(str::is_empty)
+}
+
+/// What a piece of code is, for colouring; the value indexes `token_styles`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Plain,
+    Keyword,
+    Type,
+    String,
+    Number,
+    Comment,
+}
+
+/// Just enough of a language's lexical rules to colour it
+struct Syntax {
+    keywords: &'static [&'static str],
+    /// Starts a comment running to the end of the line ("" for none)
+    line_comment: &'static str,
+    block_comment: Option<(&'static str, &'static str)>,
+    /// Single quotes delimit strings (in Rust they are chars and lifetimes)
+    single_quote_strings: bool,
+    /// Capitalized identifiers are types
+    capitalized_types: bool,
+}
+
+const RUST: Syntax = Syntax {
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "self", "static", "struct", "super", "trait", "true",
+        "type", "unsafe", "use", "where", "while",
+    ],
+    line_comment: "//",
+    block_comment: Some(("/*", "*/")),
+    single_quote_strings: false,
+    capitalized_types: true,
+};
+
+const PYTHON: Syntax = Syntax {
+    keywords: &[
+        "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+        "elif", "else", "except", "False", "finally", "for", "from", "global", "if", "import",
+        "in", "is", "lambda", "None", "nonlocal", "not", "or", "pass", "raise", "return", "True",
+        "try", "while", "with", "yield",
+    ],
+    line_comment: "#",
+    block_comment: None,
+    single_quote_strings: true,
+    capitalized_types: true,
+};
+
+const JAVASCRIPT: Syntax = Syntax {
+    keywords: &[
+        "async", "await", "break", "case", "catch", "class", "const", "continue", "default",
+        "delete", "do", "else", "export", "extends", "false", "finally", "for", "from",
+        "function", "if", "import", "in", "instanceof", "interface", "let", "new", "null",
+        "return", "switch", "this", "throw", "true", "try", "type", "typeof", "undefined", "var",
+        "while", "yield",
+    ],
+    line_comment: "//",
+    block_comment: Some(("/*", "*/")),
+    single_quote_strings: true,
+    capitalized_types: true,
+};
+
+const GO: Syntax = Syntax {
+    keywords: &[
+        "break", "case", "chan", "const", "continue", "default", "defer", "else", "false",
+        "for", "func", "go", "if", "import", "interface", "map", "nil", "package", "range",
+        "return", "select", "struct", "switch", "true", "type", "var",
+    ],
+    line_comment: "//",
+    block_comment: Some(("/*", "*/")),
+    single_quote_strings: false,
+    capitalized_types: true,
+};
+
+const SHELL: Syntax = Syntax {
+    keywords: &[
+        "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if",
+        "in", "local", "return", "then", "until", "while",
+    ],
+    line_comment: "#",
+    block_comment: None,
+    single_quote_strings: true,
+    capitalized_types: false,
+};
+
+const JSON: Syntax = Syntax {
+    keywords: &["false", "null", "true"],
+    line_comment: "",
+    block_comment: None,
+    single_quote_strings: false,
+    capitalized_types: false,
+};
+
+const TOML: Syntax = Syntax {
+    keywords: &["false", "true"],
+    line_comment: "#",
+    block_comment: None,
+    single_quote_strings: true,
+    capitalized_types: false,
+};
+
+impl Syntax {
+    /// Syntax for a code fence's language hint, if it's one we know
+    fn for_language(language: &str) -> Option<&'static Syntax> {
+        match language.to_lowercase().as_str() {
+            "rust" | "rs" => Some(&RUST),
+            "python" | "py" => Some(&PYTHON),
+            "javascript" | "js" | "jsx" | "typescript" | "ts" | "tsx" => Some(&JAVASCRIPT),
+            "go" | "golang" => Some(&GO),
+            "sh" | "bash" | "shell" | "zsh" => Some(&SHELL),
+            "json" => Some(&JSON),
+            "toml" => Some(&TOML),
+            _ => None,
+        }
+    }
+}
+
+/// Split a line of code into coloured tokens, merging neighbours of the same kind
+///
+/// `in_block_comment` carries an unterminated block comment over to the next line.
+fn tokenize<'a>(
+    line: &'a str,
+    syntax: &Syntax,
+    in_block_comment: &mut bool,
+) -> Vec<(TokenKind, &'a str)> {
+    let mut tokens: Vec<(TokenKind, &str)> = Vec::new();
+    let mut start = 0;
+    while start < line.len() {
+        let (kind, len) = next_token(&line[start..], syntax, in_block_comment);
+        let end = start + len;
+        match tokens.last_mut() {
+            Some((last_kind, text)) if *last_kind == kind => {
+                *text = &line[start - text.len()..end];
+            }
+            _ => tokens.push((kind, &line[start..end])),
+        }
+        start = end;
+    }
+    tokens
+}
+
+/// Kind and byte length of the token at the start of `rest`
+fn next_token(rest: &str, syntax: &Syntax, in_block_comment: &mut bool) -> (TokenKind, usize) {
+    match syntax.block_comment {
+        Some((open, close)) if *in_block_comment || rest.starts_with(open) => {
+            let skip = if *in_block_comment { 0 } else { open.len() };
+            return match rest[skip..].find(close) {
+                Some(end) => {
+                    *in_block_comment = false;
+                    (TokenKind::Comment, skip + end + close.len())
+                }
+                None => {
+                    *in_block_comment = true;
+                    (TokenKind::Comment, rest.len())
+                }
+            };
+        }
+        _ => {}
+    }
+    if !syntax.line_comment.is_empty() && rest.starts_with(syntax.line_comment) {
+        return (TokenKind::Comment, rest.len());
+    }
+
+    let Some(first) = rest.chars().next() else {
+        return (TokenKind::Plain, 0);
+    };
+    if first == '"' || (first == '\'' && syntax.single_quote_strings) {
+        return (TokenKind::String, string_len(rest, first));
+    }
+    let word_len = |is_part: fn(char) -> bool| {
+        rest.find(|c: char| !is_part(c)).unwrap_or(rest.len())
+    };
+    if first.is_ascii_digit() {
+        return (
+            TokenKind::Number,
+            word_len(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.'),
+        );
+    }
+    if first.is_alphabetic() || first == '_' {
+        let len = word_len(|c| c.is_alphanumeric() || c == '_');
+        let kind = if syntax.keywords.contains(&&rest[..len]) {
+            TokenKind::Keyword
+        } else if syntax.capitalized_types && first.is_uppercase() {
+            TokenKind::Type
+        } else {
+            TokenKind::Plain
+        };
+        return (kind, len);
+    }
+    (TokenKind::Plain, first.len_utf8())
+}
+
+/// Length of the string literal opening `rest`, up to its closing quote or the line's end
+fn string_len(rest: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (i, c) in rest.char_indices().skip(1) {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == quote {
+            return i + c.len_utf8();
+        }
+    }
+    rest.len()
+}
+
+/// Narrowest a table column is squeezed to
+const MIN_COLUMN_WIDTH: usize = 3;
+
+/// Column alignment, from a table's delimiter row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Alignment {
+    Left,
+    Center,
+    Right,
+}
+
+/// A GitHub-flavored markdown table
+struct Table {
+    header: Vec<String>,
+    alignments: Vec<Alignment>,
+    /// Body rows, padded or cut to the header's column count
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Parse a table starting at `lines[0]`; returns it and the number of lines it spans
+    fn parse(lines: &[&str]) -> Option<(Table, usize)> {
+        let header = table_cells(lines.first()?)?;
+        let alignments = table_cells(lines.get(1)?)?
+            .iter()
+            .map(|cell| parse_alignment(cell))
+            .collect::<Option<Vec<_>>>()?;
+        if alignments.len() != header.len() {
+            return None;
+        }
+
+        let rows: Vec<Vec<String>> = lines[2..]
+            .iter()
+            .map_while(|line| table_cells(line))
+            .map(|mut cells| {
+                cells.resize(header.len(), String::new());
+                cells
+            })
+            .collect();
+        let consumed = 2 + rows.len();
+        Some((
+            Table {
+                header,
+                alignments,
+                rows,
+            },
+            consumed,
+        ))
+    }
+
+    /// Column widths that fit the table in `width`, narrowing the widest columns first
+    ///
+    /// A table that can't fit even at the narrowest columns is left wider than `width`.
+    fn column_widths(&self, width: usize) -> Vec<usize> {
+        let mut widths: Vec<usize> = (0..self.header.len())
+            .map(|column| {
+                std::iter::once(&self.header)
+                    .chain(&self.rows)
+                    .map(|row| row[column].chars().count())
+                    .max()
+                    .unwrap_or(0)
+                    .max(MIN_COLUMN_WIDTH)
+            })
+            .collect();
+
+        // Every column has a space either side and a border after it, plus the first border
+        let available = width.saturating_sub(3 * widths.len() + 1);
+        while widths.iter().sum::<usize>() > available {
+            let Some(widest) = widths
+                .iter_mut()
+                .filter(|width| **width > MIN_COLUMN_WIDTH)
+                .max_by_key(|width| **width)
+            else {
+                break;
+            };
+            *widest -= 1;
+        }
+        widths
+    }
+}
+
+/// Cells of a table row, or None if the line isn't one
+fn table_cells(line: &str) -> Option<Vec<String>> {
+    let trimmed = line.trim();
+    if !trimmed.contains('|') {
+        return None;
+    }
+    let inner = trimmed.strip_prefix('|').unwrap_or(trimmed);
+    let inner = inner.strip_suffix('|').unwrap_or(inner);
+    Some(inner.split('|').map(|cell| cell.trim().to_string()).collect())
+}
+
+/// Alignment of a delimiter row cell like `:---:`, or None if it isn't one
+fn parse_alignment(cell: &str) -> Option<Alignment> {
+    let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+    if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+        return None;
+    }
+    Some(match (cell.starts_with(':'), cell.ends_with(':')) {
+        (true, true) => Alignment::Center,
+        (false, true) => Alignment::Right,
+        _ => Alignment::Left,
+    })
+}
+
+/// Word-wrap a cell to `width` characters, breaking words longer than that
+fn wrap_cell(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        let mut chars: Vec<char> = word.chars().collect();
+        while chars.len() > width {
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+            lines.push(chars.drain(..width).collect());
+        }
+        if chars.is_empty() {
+            continue;
+        }
+
+        let current_len = current.chars().count();
+        if current_len > 0 && current_len + 1 + chars.len() > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.extend(chars);
+    }
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// Pad `text` to `width` characters according to `alignment`
+fn align(text: &str, width: usize, alignment: Alignment) -> String {
+    let gap = width.saturating_sub(text.chars().count());
+    let left = match alignment {
+        Alignment::Left => 0,
+        Alignment::Center => gap / 2,
+        Alignment::Right => gap,
+    };
+    format!("{}{}{}", " ".repeat(left), text, " ".repeat(gap - left))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLE: &str = "\
+| Name | Qty | Note |
+|:-----|----:|:----:|
+| tea | 2 | hot |
+| coffee | 10 | iced |";
+
+    fn plain_lines(text: &Text) -> Vec<String> {
+        text.lines.iter().map(|line| spans_text(&line.spans)).collect()
+    }
+
+    fn spans_text(spans: &[Span]) -> String {
+        spans.iter().map(|span| span.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn test_table_renders_as_aligned_grid() {
+        let renderer = SimpleMarkdownRenderer::default();
+
+        let text = renderer.render(&format!("Prices:\n{}\nThat's all.", TABLE));
+        assert_eq!(
+            plain_lines(&text).join("\n"),
+            "\
+Prices:
+┌────────┬─────┬──────┐
+│ Name   │ Qty │ Note │
+├────────┼─────┼──────┤
+│ tea    │   2 │ hot  │
+│ coffee │  10 │ iced │
+└────────┴─────┴──────┘
+That's all."
+        );
+
+        // Too wide for the pane: columns narrow and their cells wrap
+        let narrow = plain_lines(&renderer.render_with_width(TABLE, 20));
+        assert_eq!(
+            narrow.join("\n"),
+            "\
+┌──────┬─────┬─────┐
+│ Name │ Qty │ Not │
+│      │     │  e  │
+├──────┼─────┼─────┤
+│ tea  │   2 │ hot │
+│ coff │  10 │ ice │
+│ ee   │     │  d  │
+└──────┴─────┴─────┘"
+        );
+        assert!(narrow.iter().all(|line| line.chars().count() <= 20));
+    }
+
+    #[test]
+    fn test_rust_code_fence_is_highlighted() {
+        let renderer = SimpleMarkdownRenderer::default();
+        let source = "\
+```rust
+fn main() {
+    let greeting = \"hi\"; // say hi
+    /* struct */ let n = Some(42);
+}
+```";
+
+        let text = renderer.render(source);
+        assert_eq!(plain_lines(&text).join("\n"), source);
+
+        let spans: Vec<(String, Option<Color>)> = text.lines[2]
+            .spans
+            .iter()
+            .map(|span| (span.content.to_string(), span.style.fg))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![
+                ("    ".to_string(), Some(Color::White)),
+                ("let".to_string(), Some(Color::Magenta)),
+                (" greeting = ".to_string(), Some(Color::White)),
+                ("\"hi\"".to_string(), Some(Color::Green)),
+                ("; ".to_string(), Some(Color::White)),
+                ("// say hi".to_string(), Some(Color::DarkGray)),
+            ]
+        );
+
+        let styled = |line: usize, content: &str| {
+            text.lines[line]
+                .spans
+                .iter()
+                .find(|span| span.content == content)
+                .and_then(|span| span.style.fg)
+        };
+        assert_eq!(styled(1, "fn"), Some(Color::Magenta));
+        assert_eq!(styled(3, "/* struct */"), Some(Color::DarkGray));
+        assert_eq!(styled(3, "Some"), Some(Color::Cyan));
+        assert_eq!(styled(3, "42"), Some(Color::Yellow));
+
+        // Unknown languages are shown as plain code
+        let text = renderer.render("```brainfuck\n++[>+<-]\n```");
+        assert_eq!(text.lines[1].spans.len(), 1);
+    }
+}
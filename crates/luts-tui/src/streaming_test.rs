@@ -9,7 +9,7 @@ use futures_util::StreamExt;
 use luts_core::{
     llm::{InternalChatMessage, LLMService},
     streaming::{ChunkType, ResponseStreamManager},
-    tools::{calc::MathTool, search::DDGSearchTool, website::WebsiteTool},
+    tools::{ToolResult, calc::MathTool, search::DDGSearchTool, website::WebsiteTool},
 };
 use std::{
     io::{self, Write},
@@ -393,9 +393,13 @@ async fn print_streaming_response(
                 println!();
                 println!("  {}", chunk.content.cyan().bold());
             }
-            ChunkType::ToolResponse => {
-                println!("  {}", chunk.content.green());
-            }
+            ChunkType::ToolResponse => match ToolResult::from_json(&chunk.content) {
+                Some(result) if result.is_success() => {
+                    println!("  ✅ {}", result.display_text().green());
+                }
+                Some(result) => println!("  ❌ {}", result.display_text().red()),
+                None => println!("  {}", chunk.content.green()),
+            },
             ChunkType::Reasoning => {
                 if !chunk.content.is_empty() {
                     println!();
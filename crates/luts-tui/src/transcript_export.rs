@@ -0,0 +1,379 @@
+//! Exporting the conversation transcript
+//!
+//! Chat messages are converted to the library's [`ExportableMessage`]s,
+//! keeping reasoning and tool calls, and written with
+//! [`ConversationExporter`] so the files match exports made elsewhere. The
+//! conversion also goes the other way, to restore a saved conversation.
+
+use crate::conversation::{ChatMessage, ToolCall, ToolStatus};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveTime, Utc};
+use luts_core::context::core_blocks::CoreBlockManager;
+use luts_framework::llm::conversation::export::{
+    ConversationStatus, ExportableFunctionCall, ExportableToolCall, MessageImportance,
+    MessageMetadata, MessageType,
+};
+use luts_framework::llm::conversation::{
+    ConversationExporter, ConversationMetadata, ExportFormat, ExportSettings, ExportableMessage,
+};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Custom metadata key recording whether a tool result is a failure
+const TOOL_STATUS_KEY: &str = "tool_status";
+
+/// Formats offered by the export prompt: key, label, format, file extension
+pub const EXPORT_FORMATS: [(char, &str, ExportFormat, &str); 3] = [
+    ('m', "Markdown", ExportFormat::Markdown, "md"),
+    ('j', "JSON", ExportFormat::Json, "json"),
+    ('l', "JSON Lines", ExportFormat::Jsonl, "jsonl"),
+];
+
+/// Metadata of the conversation being shown
+pub fn transcript_metadata(message_count: usize) -> ConversationMetadata {
+    let now = Utc::now();
+    ConversationMetadata {
+        id: "transcript".to_string(),
+        title: "Current conversation".to_string(),
+        description: None,
+        user_id: "user".to_string(),
+        session_id: "tui".to_string(),
+        started_at: now,
+        last_message_at: now,
+        message_count,
+        tags: Vec::new(),
+        properties: HashMap::new(),
+        language: None,
+        status: ConversationStatus::Active,
+        participants: Vec::new(),
+        parent_id: None,
+        forked_at: None,
+        forked_from_message: None,
+    }
+}
+
+/// Convert a chat message, with its reasoning and tool calls
+///
+/// Returns `None` when the settings leave its type out. Tool results are
+/// separate messages; see [`to_exportable_messages`].
+pub fn to_exportable_message(
+    index: usize,
+    message: &ChatMessage,
+    settings: &ExportSettings,
+) -> Option<ExportableMessage> {
+    let message_type = if message.is_from_user() {
+        MessageType::User
+    } else if message.sender == "System" {
+        MessageType::System
+    } else {
+        MessageType::Assistant
+    };
+    if message_type == MessageType::System && !settings.include_system_messages {
+        return None;
+    }
+    if settings
+        .message_type_filter
+        .as_ref()
+        .is_some_and(|filter| !filter.contains(&message_type))
+    {
+        return None;
+    }
+
+    let tool_calls = (settings.include_tool_calls && !message.tool_calls.is_empty()).then(|| {
+        message
+            .tool_calls
+            .iter()
+            .enumerate()
+            .map(|(i, call)| ExportableToolCall {
+                id: tool_call_id(index, i),
+                kind: "function".to_string(),
+                function: ExportableFunctionCall {
+                    name: call.name.clone(),
+                    arguments: call.arguments.clone(),
+                },
+            })
+            .collect()
+    });
+
+    Some(ExportableMessage {
+        id: format!("msg_{}", index),
+        role: message_type.role().to_string(),
+        content: message.content.clone(),
+        tool_calls,
+        tool_call_id: None,
+        reasoning: message
+            .reasoning
+            .clone()
+            .filter(|_| settings.include_reasoning),
+        language: None,
+        timestamp: timestamp(&message.timestamp),
+        author: message.sender.clone(),
+        metadata: message_metadata(),
+        references: Vec::new(),
+        attachments: Vec::new(),
+        incomplete: message.incomplete,
+        message_type,
+    })
+}
+
+/// Convert the chat history, following each message with its tool results
+pub fn to_exportable_messages(
+    messages: &[ChatMessage],
+    settings: &ExportSettings,
+) -> Vec<ExportableMessage> {
+    let mut exported = Vec::new();
+    for (index, message) in messages.iter().enumerate() {
+        let Some(converted) = to_exportable_message(index, message, settings) else {
+            continue;
+        };
+        let timestamp = converted.timestamp;
+        exported.push(converted);
+        if !settings.include_tool_calls {
+            continue;
+        }
+
+        for (i, call) in message.tool_calls.iter().enumerate() {
+            let content = match (&call.status, &call.result) {
+                (ToolStatus::Running, _) => continue,
+                (_, Some(result)) => result.clone(),
+                (ToolStatus::Failed(error), None) => format!("Error: {}", error),
+                (ToolStatus::Completed, None) => String::new(),
+            };
+            let mut metadata = message_metadata();
+            let status = match call.status {
+                ToolStatus::Failed(_) => "failed",
+                _ => "completed",
+            };
+            metadata
+                .custom
+                .insert(TOOL_STATUS_KEY.to_string(), status.to_string());
+            exported.push(ExportableMessage {
+                id: format!("msg_{}_tool_{}", index, i),
+                role: MessageType::Tool.role().to_string(),
+                message_type: MessageType::Tool,
+                content,
+                tool_calls: None,
+                tool_call_id: Some(tool_call_id(index, i)),
+                reasoning: None,
+                language: None,
+                timestamp,
+                author: format!("Tool({})", call.name),
+                metadata,
+                references: Vec::new(),
+                attachments: Vec::new(),
+                incomplete: false,
+            });
+        }
+    }
+    exported
+}
+
+/// Rebuild chat messages from converted ones
+///
+/// Tool results are folded back into the calls they answer; a call without
+/// one was cut off and is marked failed. Times keep only the local time of
+/// day, as the TUI shows them.
+pub fn from_exportable_messages(messages: &[ExportableMessage]) -> Vec<ChatMessage> {
+    let results: HashMap<&str, &ExportableMessage> = messages
+        .iter()
+        .filter(|message| message.message_type == MessageType::Tool)
+        .filter_map(|message| Some((message.tool_call_id.as_deref()?, message)))
+        .collect();
+
+    messages
+        .iter()
+        .filter(|message| message.message_type != MessageType::Tool)
+        .map(|message| {
+            let mut chat = match message.message_type {
+                MessageType::Assistant => {
+                    ChatMessage::new(message.author.clone(), message.content.clone())
+                }
+                _ => ChatMessage::new_plain(message.author.clone(), message.content.clone()),
+            };
+            chat.timestamp = message
+                .timestamp
+                .with_timezone(&Local)
+                .format("%H:%M:%S")
+                .to_string();
+            chat.reasoning = message.reasoning.clone();
+            chat.incomplete = message.incomplete;
+
+            for call in message.tool_calls.iter().flatten() {
+                let (status, result) = match results.get(call.id.as_str()) {
+                    Some(result)
+                        if result
+                            .metadata
+                            .custom
+                            .get(TOOL_STATUS_KEY)
+                            .is_some_and(|status| status == "failed") =>
+                    {
+                        (
+                            ToolStatus::Failed(result.content.clone()),
+                            Some(result.content.clone()),
+                        )
+                    }
+                    Some(result) => (ToolStatus::Completed, Some(result.content.clone())),
+                    None => (ToolStatus::Failed("Interrupted".to_string()), None),
+                };
+                chat.add_tool_call(ToolCall {
+                    name: call.function.name.clone(),
+                    arguments: call.function.arguments.clone(),
+                    result,
+                    status,
+                });
+            }
+            chat
+        })
+        .collect()
+}
+
+/// Write the chat history to a timestamped file in `dir`, returning its path
+///
+/// The agent's `core_blocks`, if given, are exported with it as the
+/// conversation's system prompt and pinned context.
+pub async fn export_transcript(
+    messages: &[ChatMessage],
+    core_blocks: Option<Arc<RwLock<CoreBlockManager>>>,
+    dir: &Path,
+    format: ExportFormat,
+    extension: &str,
+    settings: ExportSettings,
+) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let path = dir.join(format!(
+        "conversation-{}.{}",
+        Local::now().format("%Y%m%d-%H%M%S"),
+        extension
+    ));
+    let file =
+        File::create(&path).with_context(|| format!("Failed to create {}", path.display()))?;
+
+    let exported = to_exportable_messages(messages, &settings);
+    let mut exporter = ConversationExporter::new(dir.to_path_buf());
+    if let Some(core_blocks) = core_blocks {
+        exporter = exporter.with_core_blocks(core_blocks);
+    }
+    exporter
+        .export_messages_to_writer(
+            exported,
+            transcript_metadata(messages.len()),
+            &mut BufWriter::new(file),
+            format,
+            settings,
+        )
+        .await
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+fn tool_call_id(message_index: usize, call_index: usize) -> String {
+    format!("call_{}_{}", message_index, call_index)
+}
+
+/// When a message was sent; the TUI only keeps the local time of day
+fn timestamp(time_of_day: &str) -> DateTime<Utc> {
+    NaiveTime::parse_from_str(time_of_day, "%H:%M:%S")
+        .ok()
+        .and_then(|time| {
+            Local::now()
+                .date_naive()
+                .and_time(time)
+                .and_local_timezone(Local)
+                .single()
+        })
+        .map_or_else(Utc::now, |time| time.with_timezone(&Utc))
+}
+
+fn message_metadata() -> MessageMetadata {
+    MessageMetadata {
+        token_count: None,
+        processing_time_ms: None,
+        model: None,
+        temperature: None,
+        confidence: None,
+        importance: MessageImportance::default(),
+        is_bookmarked: false,
+        custom: HashMap::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_calls_and_results_are_preserved() {
+        let mut message = ChatMessage::new_plain("AI".to_string(), "It's 18°C.".to_string())
+            .with_reasoning("The user wants the weather.".to_string());
+        message.add_tool_call(ToolCall {
+            name: "weather".to_string(),
+            arguments: r#"{"city":"Lisbon"}"#.to_string(),
+            result: Some("18°C, sunny".to_string()),
+            status: ToolStatus::Completed,
+        });
+        message.add_tool_call(ToolCall {
+            name: "calendar".to_string(),
+            arguments: "{}".to_string(),
+            result: None,
+            status: ToolStatus::Failed("timeout".to_string()),
+        });
+        let messages = vec![
+            ChatMessage::new_plain("You".to_string(), "Weather?".to_string()),
+            message,
+        ];
+
+        let exported = to_exportable_messages(&messages, &ExportSettings::default());
+        assert_eq!(exported.len(), 4);
+        assert_eq!(exported[0].message_type, MessageType::User);
+        let answer = &exported[1];
+        assert_eq!(answer.message_type, MessageType::Assistant);
+        assert_eq!(
+            answer.reasoning.as_deref(),
+            Some("The user wants the weather.")
+        );
+        let calls = answer.tool_calls.as_ref().unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].function.name, "weather");
+        assert_eq!(calls[0].function.arguments, r#"{"city":"Lisbon"}"#);
+        assert_eq!(
+            exported[2].tool_call_id.as_deref(),
+            Some(calls[0].id.as_str())
+        );
+        assert_eq!(exported[2].content, "18°C, sunny");
+        assert_eq!(
+            exported[3].tool_call_id.as_deref(),
+            Some(calls[1].id.as_str())
+        );
+        assert_eq!(exported[3].content, "Error: timeout");
+
+        let restored = from_exportable_messages(&exported);
+        assert_eq!(restored.len(), 2);
+        assert!(restored[0].is_from_user());
+        assert_eq!(restored[1].content, "It's 18°C.");
+        assert_eq!(restored[1].timestamp, messages[1].timestamp);
+        assert_eq!(
+            restored[1].reasoning.as_deref(),
+            Some("The user wants the weather.")
+        );
+        let calls = &restored[1].tool_calls;
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].arguments, r#"{"city":"Lisbon"}"#);
+        assert_eq!(calls[0].result.as_deref(), Some("18°C, sunny"));
+        assert!(matches!(calls[0].status, ToolStatus::Completed));
+        assert!(matches!(calls[1].status, ToolStatus::Failed(_)));
+
+        let settings = ExportSettings {
+            include_reasoning: false,
+            include_tool_calls: false,
+            ..Default::default()
+        };
+        let exported = to_exportable_messages(&messages, &settings);
+        assert_eq!(exported.len(), 2);
+        assert_eq!(exported[1].tool_calls, None);
+        assert_eq!(exported[1].reasoning, None);
+    }
+}
@@ -0,0 +1,443 @@
+//! Searching the conversation transcript
+//!
+//! The chat history is indexed as a single conversation in a throwaway
+//! [`ConversationSearchEngine`], so terms are matched and phrases parsed the
+//! same way as in the library search. Matches come back in transcript order,
+//! to be stepped through with n/N.
+
+use crate::conversation::ChatMessage;
+use crate::transcript_export::{to_exportable_message, transcript_metadata};
+use anyhow::Result;
+use chrono::Utc;
+use luts_framework::llm::conversation::export::ExportInfo;
+use luts_framework::llm::conversation::search::SearchMode;
+use luts_framework::llm::conversation::{
+    ConversationSearchEngine, ConversationSearchQuery, ExportFormat, ExportSettings,
+    ExportableConversation, ExportableMessage, SearchFilters,
+};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use std::ops::Range;
+
+/// Prefix of the IDs given to indexed messages, followed by their index
+const MESSAGE_ID_PREFIX: &str = "msg_";
+
+/// A match: a byte range in the content of one message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscriptMatch {
+    pub message_idx: usize,
+    pub range: Range<usize>,
+}
+
+/// Search state of the conversation view
+#[derive(Debug)]
+pub struct TranscriptSearch {
+    /// The search prompt is open and keys edit the query
+    pub editing: bool,
+    pub query: String,
+    /// Matches of the last search, in transcript order
+    pub matches: Vec<TranscriptMatch>,
+    /// Index in `matches` of the selected match
+    pub current: usize,
+    /// The history should scroll to the selected match on the next render
+    pub scroll_pending: bool,
+    /// Outcome of the last search when it found nothing
+    pub message: Option<String>,
+    match_style: Style,
+    current_style: Style,
+}
+
+impl Default for TranscriptSearch {
+    fn default() -> Self {
+        Self {
+            editing: false,
+            query: String::new(),
+            matches: Vec::new(),
+            current: 0,
+            scroll_pending: false,
+            message: None,
+            match_style: Style::default().add_modifier(Modifier::REVERSED),
+            current_style: Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        }
+    }
+}
+
+impl TranscriptSearch {
+    /// Open the prompt for a new query
+    pub fn open(&mut self) {
+        self.editing = true;
+        self.query.clear();
+        self.message = None;
+    }
+
+    /// Close the prompt and forget the last search; false if nothing was open
+    pub fn close(&mut self) -> bool {
+        let was_active = self.editing || !self.matches.is_empty() || self.message.is_some();
+        self.editing = false;
+        self.matches.clear();
+        self.current = 0;
+        self.scroll_pending = false;
+        self.message = None;
+        was_active
+    }
+
+    pub fn has_matches(&self) -> bool {
+        !self.matches.is_empty()
+    }
+
+    pub fn current_match(&self) -> Option<&TranscriptMatch> {
+        self.matches.get(self.current)
+    }
+
+    /// Record the outcome of a search and select its first match
+    pub fn set_results(&mut self, result: Result<Vec<TranscriptMatch>>) {
+        self.editing = false;
+        self.current = 0;
+        match result {
+            Ok(matches) => {
+                self.message = matches
+                    .is_empty()
+                    .then(|| format!("No matches for \"{}\"", self.query.trim()));
+                self.scroll_pending = !matches.is_empty();
+                self.matches = matches;
+            }
+            Err(e) => {
+                self.matches.clear();
+                self.scroll_pending = false;
+                self.message = Some(format!("Search failed: {}", e));
+            }
+        }
+    }
+
+    /// Select the next match, or the previous one, wrapping around
+    pub fn step(&mut self, forward: bool) {
+        let count = self.matches.len();
+        if count == 0 {
+            return;
+        }
+        self.current = if forward {
+            (self.current + 1) % count
+        } else {
+            (self.current + count - 1) % count
+        };
+        self.scroll_pending = true;
+    }
+
+    /// Status bar text while a search is open
+    pub fn status_text(&self) -> Option<String> {
+        if self.editing {
+            return Some(format!(
+                "Search: {}▏ | Enter: search | Esc: cancel",
+                self.query
+            ));
+        }
+        if self.has_matches() {
+            return Some(format!(
+                "Match {}/{} for \"{}\" | n/N: next/previous | Esc: close search",
+                self.current + 1,
+                self.matches.len(),
+                self.query.trim()
+            ));
+        }
+        self.message.clone()
+    }
+
+    /// Highlight the matches of message `message_idx` in its rendered lines
+    ///
+    /// Rendering strips markdown and wraps text, so byte offsets into the
+    /// content don't carry over to the lines. The matched words are found
+    /// again by text instead, and the occurrence at the selected match's
+    /// position among the message's matches is marked as current. Returns the
+    /// line holding it, if this message has the selected match.
+    pub fn highlight(
+        &self,
+        message_idx: usize,
+        content: &str,
+        lines: &mut [Line<'static>],
+    ) -> Option<usize> {
+        let matches: Vec<&TranscriptMatch> = self
+            .matches
+            .iter()
+            .filter(|m| m.message_idx == message_idx)
+            .collect();
+        let mut needles: Vec<&str> = matches
+            .iter()
+            .filter_map(|m| content.get(m.range.clone()))
+            .filter(|text| !text.is_empty())
+            .collect();
+        if needles.is_empty() {
+            return None;
+        }
+        // Longest first, so a longer match wins over a word inside it
+        needles.sort_by_key(|text| std::cmp::Reverse(text.len()));
+        needles.dedup();
+
+        let current = self.current_match();
+        let current_occurrence = matches.iter().position(|m| Some(*m) == current);
+        let mut occurrence = 0;
+        let mut current_line = None;
+        for (line_idx, line) in lines.iter_mut().enumerate() {
+            let text: String = line
+                .spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect();
+            let found = find_all(&text, &needles);
+            if found.is_empty() {
+                continue;
+            }
+            let styled: Vec<(Range<usize>, Style)> = found
+                .into_iter()
+                .map(|range| {
+                    let style = if Some(occurrence) == current_occurrence {
+                        current_line = Some(line_idx);
+                        self.current_style
+                    } else {
+                        self.match_style
+                    };
+                    occurrence += 1;
+                    (range, style)
+                })
+                .collect();
+            line.spans = restyle_spans(&line.spans, &styled);
+        }
+        current_line
+    }
+}
+
+/// Turn what the user typed into a library query; quoted text is a phrase
+pub fn build_query(text: &str) -> ConversationSearchQuery {
+    let text = text.trim();
+    let quoted = text.len() > 1 && text.starts_with('"') && text.ends_with('"');
+    ConversationSearchQuery {
+        text_query: Some(text.to_string()),
+        // The whole transcript is searched
+        filters: SearchFilters::default(),
+        include_highlights: true,
+        mode: if quoted {
+            SearchMode::Phrase
+        } else {
+            SearchMode::Substring
+        },
+        ..Default::default()
+    }
+}
+
+/// Search the messages, returning every match in transcript order
+pub async fn search_messages(
+    messages: &[ChatMessage],
+    query: ConversationSearchQuery,
+) -> Result<Vec<TranscriptMatch>> {
+    let engine = ConversationSearchEngine::new();
+    engine.index_conversation(&transcript(messages)).await?;
+    let (results, _) = engine.search_conversations(query).await?;
+
+    let mut matches: Vec<TranscriptMatch> = results
+        .into_iter()
+        .flat_map(|result| result.highlights)
+        .filter_map(|highlight| {
+            let message_idx = highlight
+                .field
+                .strip_prefix("messages.")?
+                .strip_prefix(MESSAGE_ID_PREFIX)?
+                .parse()
+                .ok()?;
+            Some(
+                highlight
+                    .positions
+                    .into_iter()
+                    .map(move |position| TranscriptMatch {
+                        message_idx,
+                        range: position.start..position.end,
+                    }),
+            )
+        })
+        .flatten()
+        .collect();
+    matches.sort_by_key(|m| (m.message_idx, m.range.start));
+    matches.dedup();
+    Ok(matches)
+}
+
+/// The chat history as a conversation the search engine can index
+///
+/// Default settings keep every message, so each ID is `msg_{index}` of its
+/// position in the history.
+fn transcript(messages: &[ChatMessage]) -> ExportableConversation {
+    let settings = ExportSettings::default();
+    let indexed: Vec<ExportableMessage> = messages
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, msg)| to_exportable_message(idx, msg, &settings))
+        .collect();
+
+    ExportableConversation {
+        metadata: transcript_metadata(messages.len()),
+        messages: indexed,
+        system_messages: Vec::new(),
+        pinned_context: Vec::new(),
+        memory_blocks: Vec::new(),
+        summaries: Vec::new(),
+        token_usage: Vec::new(),
+        export_info: ExportInfo {
+            exported_at: Utc::now(),
+            format: ExportFormat::Json,
+            version: "1.0".to_string(),
+            exporter: "luts-tui".to_string(),
+            settings,
+            file_size_bytes: None,
+            compression: None,
+        },
+    }
+}
+
+/// Non-overlapping occurrences of any of `needles`, left to right
+///
+/// At each position the first needle that matches wins.
+fn find_all(text: &str, needles: &[&str]) -> Vec<Range<usize>> {
+    let mut found = Vec::new();
+    let mut pos = 0;
+    while pos < text.len() {
+        let hit = needles
+            .iter()
+            .find(|needle| text[pos..].starts_with(*needle));
+        match hit {
+            Some(needle) => {
+                found.push(pos..pos + needle.len());
+                pos += needle.len();
+            }
+            None => {
+                pos += text[pos..].chars().next().map_or(1, char::len_utf8);
+            }
+        }
+    }
+    found
+}
+
+/// Split spans at the edges of `ranges` (byte offsets into the line's text)
+/// and patch the given style onto the parts inside them
+fn restyle_spans(spans: &[Span<'static>], ranges: &[(Range<usize>, Style)]) -> Vec<Span<'static>> {
+    let mut result = Vec::new();
+    let mut offset = 0;
+    for span in spans {
+        let content = span.content.as_ref();
+        let span_range = offset..offset + content.len();
+        let mut cut = span_range.start;
+        for (range, style) in ranges {
+            let start = range.start.max(span_range.start);
+            let end = range.end.min(span_range.end);
+            if start >= end {
+                continue;
+            }
+            if start > cut {
+                result.push(Span::styled(
+                    content[cut - offset..start - offset].to_string(),
+                    span.style,
+                ));
+            }
+            result.push(Span::styled(
+                content[start - offset..end - offset].to_string(),
+                span.style.patch(*style),
+            ));
+            cut = end;
+        }
+        if cut < span_range.end {
+            result.push(Span::styled(
+                content[cut - offset..].to_string(),
+                span.style,
+            ));
+        }
+        offset = span_range.end;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line_texts(lines: &[Line<'static>], style: Style) -> Vec<String> {
+        lines
+            .iter()
+            .flat_map(|line| &line.spans)
+            .filter(|span| span.style == style)
+            .map(|span| span.content.to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_matches_come_back_in_transcript_order() {
+        let messages = vec![
+            ChatMessage::new_plain(
+                "You".to_string(),
+                "How do I start a tokio runtime?".to_string(),
+            ),
+            ChatMessage::new_plain(
+                "AI".to_string(),
+                "Use #[tokio::main], or build a Runtime.".to_string(),
+            ),
+            ChatMessage::new_plain("You".to_string(), "Thanks!".to_string()),
+        ];
+
+        let matches = search_messages(&messages, build_query("Runtime"))
+            .await
+            .unwrap();
+        let found: Vec<(usize, &str)> = matches
+            .iter()
+            .map(|m| {
+                (
+                    m.message_idx,
+                    &messages[m.message_idx].content[m.range.clone()],
+                )
+            })
+            .collect();
+        assert_eq!(found, vec![(0, "runtime"), (1, "Runtime")]);
+
+        let phrase = search_messages(&messages, build_query("\"build a runtime\""))
+            .await
+            .unwrap();
+        assert_eq!(phrase.len(), 1);
+        assert_eq!(phrase[0].message_idx, 1);
+
+        assert!(
+            search_messages(&messages, build_query("async"))
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_current_match_is_highlighted_apart_from_the_others() {
+        let content = "A **cat** and a dog, and another cat";
+        let mut search = TranscriptSearch::default();
+        search.set_results(Ok(vec![
+            TranscriptMatch {
+                message_idx: 0,
+                range: 4..7,
+            },
+            TranscriptMatch {
+                message_idx: 0,
+                range: 33..36,
+            },
+        ]));
+        search.step(true);
+
+        let mut lines = vec![
+            Line::from(vec![
+                Span::raw("A "),
+                Span::raw("cat"),
+                Span::raw(" and a dog,"),
+            ]),
+            Line::from("and another cat"),
+        ];
+        assert_eq!(search.highlight(0, content, &mut lines), Some(1));
+        assert_eq!(line_texts(&lines, search.match_style), vec!["cat"]);
+        assert_eq!(line_texts(&lines, search.current_style), vec!["cat"]);
+        assert_eq!(lines[1].spans[0].content, "and another ");
+        assert_eq!(search.highlight(1, content, &mut lines), None);
+    }
+}